@@ -0,0 +1,259 @@
+//! Autoscaling daemon (`runner-mgr autoscale`): polls each configured repository for
+//! queued self-hosted jobs, starts the scope's runner when work arrives, adds single-use
+//! just-in-time runners up to `max_runners`, and stops the runner once it has been idle
+
+use anyhow::{Context, Result};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use crate::config::{AutoscaleConfig, Config};
+use crate::github::{self, GitHubClient, RunnerScope, WorkflowJob, WorkflowRun};
+use crate::runner::{self, RunnerStatus};
+
+/// Default seconds between polls
+pub const DEFAULT_INTERVAL_SECS: u64 = 30;
+
+/// Recent runs checked for queued jobs on each poll
+const RUNS_PER_POLL: u32 = 20;
+
+/// What a poll found for one scope
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Observation {
+    /// Jobs waiting for a self-hosted runner
+    pub queued: u32,
+    /// The instance's service is running
+    pub running: bool,
+    /// The instance's runner is executing a job
+    pub busy: bool,
+    /// Just-in-time runners launched by the daemon that haven't exited
+    pub jit_running: u32,
+    /// Of those, how many have picked up their job
+    pub jit_busy: u32,
+}
+
+impl Observation {
+    /// Running with no job and nothing waiting
+    pub fn is_idle(&self) -> bool {
+        self.running && !self.busy && self.queued == 0
+    }
+}
+
+/// What to do for one scope
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    pub start: bool,
+    pub stop: bool,
+    /// Just-in-time runners to launch
+    pub launch_jit: u32,
+}
+
+/// Decide how to scale a scope. `idle_secs` is how long the instance has been idle.
+pub fn plan(policy: &AutoscaleConfig, obs: &Observation, idle_secs: Option<i64>) -> Plan {
+    let start = !obs.running && (obs.queued > 0 || policy.min_runners > 0);
+    let stop = policy.min_runners == 0
+        && obs.is_idle()
+        && idle_secs.is_some_and(|s| s >= i64::from(policy.idle_minutes) * 60);
+
+    // The instance counts as one runner once it runs (or is being started)
+    let instance = u32::from((obs.running || start) && !stop);
+    let instance_free = u32::from((obs.running && !obs.busy) || start);
+    let free = instance_free + obs.jit_running.saturating_sub(obs.jit_busy);
+    let total = instance + obs.jit_running;
+
+    let for_queue = obs.queued.saturating_sub(free);
+    let for_min = policy.min_runners.saturating_sub(total);
+    let launch_jit = for_queue
+        .max(for_min)
+        .min(policy.max_runners.saturating_sub(total));
+
+    Plan {
+        start,
+        stop,
+        launch_jit,
+    }
+}
+
+/// Runs worth checking for queued jobs: waiting to start, or running (later jobs of
+/// a running workflow can still be queued)
+pub fn may_have_queued_jobs(run: &WorkflowRun) -> bool {
+    run.is_queued() || run.status == "in_progress"
+}
+
+/// Jobs waiting for a self-hosted runner
+pub fn count_queued_jobs(jobs: &[WorkflowJob]) -> u32 {
+    let queued = jobs
+        .iter()
+        .filter(|job| job.status == "queued" && job.is_self_hosted(&[]))
+        .count();
+    u32::try_from(queued).unwrap_or(u32::MAX)
+}
+
+/// Whether `name` is a just-in-time runner started on `hostname` (`<hostname>-jit-<ts>`,
+/// with the hostname possibly truncated)
+pub fn is_local_jit_runner(name: &str, hostname: &str) -> bool {
+    name.rsplit_once("-jit-")
+        .is_some_and(|(host, ts)| hostname.starts_with(host) && ts.parse::<i64>().is_ok())
+}
+
+/// Parse the `[autoscale]` table, rejecting invalid limits and organization targets
+pub fn scopes(config: &Config) -> Result<Vec<(RunnerScope, AutoscaleConfig)>> {
+    if config.autoscale.is_empty() {
+        anyhow::bail!(
+            "No scopes to autoscale. Add an [autoscale.\"owner/repo\"] table to {}",
+            Config::config_file().display()
+        );
+    }
+    config
+        .autoscale
+        .iter()
+        .map(|(target, policy)| {
+            let scope = RunnerScope::parse(target)?;
+            if !scope.supports_workflow_runs() {
+                anyhow::bail!(
+                    "Cannot autoscale {scope}: GitHub only lists queued jobs per repository"
+                );
+            }
+            policy
+                .validate()
+                .with_context(|| format!("Invalid [autoscale] settings for {target}"))?;
+            Ok((scope, policy.clone()))
+        })
+        .collect()
+}
+
+async fn queued_jobs(client: &GitHubClient, scope: &RunnerScope) -> Result<u32> {
+    let RunnerScope::Repository { owner, repo } = scope else {
+        return Ok(0);
+    };
+    let runs = client
+        .list_workflow_runs(owner, repo, RUNS_PER_POLL)
+        .await?;
+    let mut queued = 0;
+    for run in runs
+        .workflow_runs
+        .iter()
+        .filter(|r| may_have_queued_jobs(r))
+    {
+        let jobs = client.list_jobs_for_run(owner, repo, run.id).await?;
+        queued += count_queued_jobs(&jobs.jobs);
+    }
+    Ok(queued)
+}
+
+/// Start a just-in-time runner as a child `runner-mgr jit` process
+fn launch_jit(scope: &RunnerScope, labels: &[String]) -> Result<Child> {
+    let exe = std::env::current_exe().context("Failed to locate the runner-mgr binary")?;
+    let mut command = Command::new(exe);
+    if let Some(account) = crate::config::account() {
+        command.args(["--account", &account]);
+    }
+    command
+        .args(["jit", &scope.to_display(), "--labels", &labels.join(",")])
+        .stdin(Stdio::null())
+        .spawn()
+        .context("Failed to start a just-in-time runner")
+}
+
+/// Per-scope state kept between polls
+#[derive(Default)]
+struct ScopeState {
+    idle_since: Option<i64>,
+    jit: Vec<Child>,
+}
+
+impl ScopeState {
+    /// Forget just-in-time runners that have exited
+    fn reap(&mut self) {
+        self.jit
+            .retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+    }
+}
+
+async fn poll_scope(
+    config: &Config,
+    client: &GitHubClient,
+    hostname: &str,
+    scope: &RunnerScope,
+    policy: &AutoscaleConfig,
+    state: &mut ScopeState,
+) -> Result<()> {
+    let instance = runner::list_instances(config)
+        .into_iter()
+        .find(|i| &i.scope == scope)
+        .with_context(|| format!("No runner configured for {scope}"))?;
+    state.reap();
+
+    let queued = queued_jobs(client, scope).await?;
+    let runners = client.list_runners(scope).await?.runners;
+    let busy =
+        github::find_runner(&runners, instance.runner_name.as_deref()).is_some_and(|r| r.busy);
+    let jit_busy = runners
+        .iter()
+        .filter(|r| r.busy && is_local_jit_runner(&r.name, hostname))
+        .count();
+    let jit_running = u32::try_from(state.jit.len()).unwrap_or(u32::MAX);
+    let obs = Observation {
+        queued,
+        running: instance.status == RunnerStatus::Running,
+        busy,
+        jit_running,
+        jit_busy: u32::try_from(jit_busy).unwrap_or(u32::MAX).min(jit_running),
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    if obs.is_idle() {
+        state.idle_since.get_or_insert(now);
+    } else {
+        state.idle_since = None;
+    }
+    let plan = plan(policy, &obs, state.idle_since.map(|since| now - since));
+
+    if plan.start {
+        println!("{scope}: {queued} job(s) queued, starting the runner");
+        runner::start_runner(config, &instance.id())?;
+    }
+    if plan.stop {
+        println!(
+            "{scope}: idle for {} minutes, stopping the runner",
+            policy.idle_minutes
+        );
+        runner::stop_runner(config, &instance.id())?;
+        state.idle_since = None;
+    }
+    if plan.launch_jit > 0 {
+        println!(
+            "{scope}: {queued} job(s) queued, launching {} just-in-time runner(s)",
+            plan.launch_jit
+        );
+        let labels = runner::read_labels(&instance.dir);
+        for _ in 0..plan.launch_jit {
+            state.jit.push(launch_jit(scope, &labels)?);
+        }
+    }
+    Ok(())
+}
+
+/// Poll every `interval` until the process is stopped
+pub async fn run(config: &Config, interval: Duration) -> Result<()> {
+    let scopes = scopes(config)?;
+    let client = GitHubClient::for_config(config);
+    let hostname = hostname::get().map_or_else(
+        |_| "runner".to_string(),
+        |h| h.to_string_lossy().to_string(),
+    );
+    let mut states: Vec<ScopeState> = scopes.iter().map(|_| ScopeState::default()).collect();
+
+    println!(
+        "Autoscaling {} scope(s), polling every {}s",
+        scopes.len(),
+        interval.as_secs()
+    );
+    loop {
+        for ((scope, policy), state) in scopes.iter().zip(&mut states) {
+            if let Err(e) = poll_scope(config, &client, &hostname, scope, policy, state).await {
+                eprintln!("{scope}: {e:#}");
+            }
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
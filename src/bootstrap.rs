@@ -0,0 +1,92 @@
+//! Standalone self-registration scripts for new machines (cloud-init user-data,
+//! Terraform, autoscaling groups)
+
+use std::fmt::Write as _;
+
+use crate::github::RunnerScope;
+
+/// Everything baked into a bootstrap script
+#[derive(Debug, Clone)]
+pub struct BootstrapOptions {
+    pub scope: RunnerScope,
+    /// Web UI root of the GitHub instance the runner registers with
+    pub web_url: String,
+    /// Registration token (short-lived: GitHub expires it after an hour)
+    pub token: String,
+    pub expires_at: Option<String>,
+    /// Comma-separated labels
+    pub labels: String,
+    /// Runner package architecture (`x64` or `arm64`)
+    pub arch: String,
+    pub version: String,
+    /// Account the runner service runs as on the new machine (created if missing)
+    pub runner_user: String,
+    /// Where the runner is installed on the new machine
+    pub dir: String,
+    /// Register an ephemeral runner that takes a single job
+    pub ephemeral: bool,
+}
+
+/// Quote `value` for POSIX sh
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Render a POSIX sh script that installs the runner on a Linux machine, registers it
+/// under the machine's hostname, and installs it as a service. The script must run as
+/// root, which is how cloud-init runs user-data.
+pub fn render(opts: &BootstrapOptions) -> String {
+    let package = format!("actions-runner-linux-{}-{}.tar.gz", opts.arch, opts.version);
+    let mut script = String::new();
+    let _ = writeln!(script, "#!/bin/sh");
+    let _ = writeln!(script, "# Generated by runner-mgr for {}", opts.scope);
+    if let Some(expires) = &opts.expires_at {
+        let _ = writeln!(
+            script,
+            "# The registration token expires at {expires}; run this script before then."
+        );
+    }
+    let _ = writeln!(script, "set -eu");
+    let _ = writeln!(script);
+    for (name, value) in [
+        ("RUNNER_URL", opts.scope.web_url(&opts.web_url)),
+        ("RUNNER_TOKEN", opts.token.clone()),
+        ("RUNNER_LABELS", opts.labels.clone()),
+        ("RUNNER_VERSION", opts.version.clone()),
+        ("RUNNER_PACKAGE", package),
+        ("RUNNER_USER", opts.runner_user.clone()),
+        ("RUNNER_DIR", opts.dir.clone()),
+    ] {
+        let _ = writeln!(script, "{name}={}", shell_quote(&value));
+    }
+    let ephemeral = if opts.ephemeral { " --ephemeral" } else { "" };
+    let _ = write!(
+        script,
+        r#"
+if [ "$(id -u)" -ne 0 ]; then
+    echo "This script must run as root" >&2
+    exit 1
+fi
+
+if ! id "$RUNNER_USER" >/dev/null 2>&1; then
+    useradd --create-home --shell /bin/bash "$RUNNER_USER"
+fi
+
+mkdir -p "$RUNNER_DIR"
+cd "$RUNNER_DIR"
+curl -fsSL -o runner.tar.gz \
+    "https://github.com/actions/runner/releases/download/v$RUNNER_VERSION/$RUNNER_PACKAGE"
+tar xzf runner.tar.gz
+rm runner.tar.gz
+./bin/installdependencies.sh || echo "Warning: installdependencies.sh failed" >&2
+chown -R "$RUNNER_USER" "$RUNNER_DIR"
+
+su "$RUNNER_USER" -c "./config.sh --unattended --replace --url '$RUNNER_URL' \
+    --token '$RUNNER_TOKEN' --labels '$RUNNER_LABELS' --name \"\$(hostname)\"{ephemeral}"
+
+./svc.sh install "$RUNNER_USER"
+./svc.sh start
+"#
+    );
+    script
+}
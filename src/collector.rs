@@ -0,0 +1,273 @@
+//! Headless collector for `runner-mgr serve`: polls GitHub for the scopes of the local
+//! instances, records metrics, sends notifications, writes the status file and runs the
+//! container janitor, like an open dashboard does
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::config::{Config, NotifyConfig};
+use crate::docker;
+use crate::dormant::{self, DormantScopes};
+use crate::github::{GitHubClient, Runner, RunnerScope, WorkflowRun};
+use crate::health::{self, CollectorHealth};
+use crate::metrics::MetricsDb;
+use crate::notify::{self, Event, FailedRun, RunTracker, RunnerTracker, ServiceTracker};
+use crate::runner::{self, RunnerInstance};
+use crate::status_file::{self, FleetStatus, ScopeStatus};
+use crate::systemd;
+
+/// Scopes of `instances`, each once
+pub fn scopes(instances: &[RunnerInstance]) -> Vec<RunnerScope> {
+    let mut scopes: Vec<RunnerScope> = Vec::new();
+    for instance in instances {
+        if !scopes.contains(&instance.scope) {
+            scopes.push(instance.scope.clone());
+        }
+    }
+    scopes
+}
+
+/// What one poll found
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    pub instances: Vec<RunnerInstance>,
+    /// Runners of each scope, `None` where they couldn't be fetched
+    pub runners: Vec<(RunnerScope, Option<Vec<Runner>>)>,
+    pub workflow_runs: Vec<(RunnerScope, Vec<WorkflowRun>)>,
+}
+
+impl Snapshot {
+    pub fn status(&self) -> FleetStatus {
+        FleetStatus {
+            scopes: self
+                .runners
+                .iter()
+                .map(|(scope, runners)| ScopeStatus::new(scope, runners.as_deref()))
+                .collect(),
+        }
+    }
+}
+
+/// Fetch the runners and, for a repository, the recent workflow runs of every scope
+/// and record them in `db`. The runs of repositories found dormant aren't fetched.
+pub async fn poll(
+    config: &Config,
+    client: &GitHubClient,
+    db: &MetricsDb,
+    dormant: &mut DormantScopes,
+) -> Snapshot {
+    let mut snapshot = Snapshot {
+        instances: runner::list_instances(config),
+        ..Snapshot::default()
+    };
+    for scope in scopes(&snapshot.instances) {
+        let runners = match client.list_runners(&scope).await {
+            Ok(list) => {
+                let _ = db.record_runner_snapshots(&scope, &list.runners);
+                Some(list.runners)
+            }
+            Err(e) => {
+                eprintln!("{scope}: {e:#}");
+                None
+            }
+        };
+        if dormant.due(&scope, Instant::now()) {
+            let check = dormant::check(client, &scope).await;
+            if let Some(reason) = dormant.record(&scope, check, Instant::now()) {
+                println!("{}", dormant::suggestion(&scope, reason));
+            }
+        }
+        // Dormant repositories have no new runs
+        if dormant.reason(&scope).is_none() {
+            if let RunnerScope::Repository { owner, repo } = &scope {
+                match client.list_workflow_runs(owner, repo, 5).await {
+                    Ok(list) => {
+                        let _ = db.record_workflow_runs(&scope, &list.workflow_runs);
+                        snapshot
+                            .workflow_runs
+                            .push((scope.clone(), list.workflow_runs));
+                    }
+                    Err(e) => eprintln!("{scope}: {e:#}"),
+                }
+                match client.list_queued_workflow_runs(owner, repo).await {
+                    Ok(list) => {
+                        let _ = db.record_queue_depth(&scope, list.total_count);
+                    }
+                    Err(e) => eprintln!("{scope}: {e:#}"),
+                }
+            }
+        }
+        snapshot.runners.push((scope, runners));
+    }
+    snapshot
+}
+
+/// Failed runs, offline runners and crashed services seen since the last poll
+#[derive(Debug, Default)]
+pub struct Trackers {
+    runs: RunTracker,
+    runners: RunnerTracker,
+    services: ServiceTracker,
+}
+
+impl Trackers {
+    /// Send the notifications for what changed in `snapshot`
+    pub async fn notify(
+        &mut self,
+        config: &NotifyConfig,
+        client: &GitHubClient,
+        snapshot: &Snapshot,
+    ) -> Vec<String> {
+        let mut notifications = Vec::new();
+        for (scope, runners) in &snapshot.runners {
+            for runner in self
+                .runners
+                .went_offline(scope, runners.as_deref().unwrap_or_default())
+            {
+                notifications.push(notify::offline_notification(scope, &runner));
+            }
+        }
+        for instance in self.services.crashed(&snapshot.instances) {
+            notifications.push(notify::crash_notification(&instance));
+        }
+        let mut failures = Vec::new();
+        for (scope, runs) in &snapshot.workflow_runs {
+            let runner_names: Vec<String> = snapshot
+                .runners
+                .iter()
+                .filter(|(s, _)| s == scope)
+                .flat_map(|(_, runners)| runners.iter().flatten().map(|r| r.name.clone()))
+                .collect();
+            for run in self.runs.new_failures(scope, runs) {
+                failures.push(FailedRun {
+                    scope: scope.clone(),
+                    run,
+                    runner_names: runner_names.clone(),
+                });
+            }
+        }
+
+        let mut log = notify::send_all(config.clone(), notifications).await;
+        if notify::wants(config, Event::RunFailed) && !failures.is_empty() {
+            log.extend(notify::notify_failures(client.clone(), config.clone(), failures).await);
+        }
+        log
+    }
+}
+
+/// Whether GitHub answered: some scope's runners were fetched, or else the
+/// authenticated user can be
+async fn github_reachable(client: &GitHubClient, snapshot: &Snapshot) -> Result<()> {
+    if snapshot
+        .runners
+        .iter()
+        .any(|(_, runners)| runners.is_some())
+    {
+        return Ok(());
+    }
+    client.get_user().await.map(|_| ())
+}
+
+/// Run the container janitor if it is enabled and due, reporting what it reclaimed
+async fn run_janitor(config: &Config) {
+    if !config.janitor.as_ref().is_some_and(|j| j.enabled) {
+        return;
+    }
+    let config = config.clone();
+    match tokio::task::spawn_blocking(move || docker::run_janitor_if_due(&config)).await {
+        Ok(Ok(Some(report))) => println!("[janitor] {report}"),
+        Ok(Ok(None)) => {}
+        Ok(Err(e)) => eprintln!("[janitor] prune failed: {e:#}"),
+        Err(e) => eprintln!("[janitor] task failed: {e}"),
+    }
+}
+
+/// Poll every `interval` until the process is stopped, recording each poll in `health`
+async fn collect(
+    config: &Config,
+    interval: Duration,
+    health: &Mutex<CollectorHealth>,
+) -> Result<()> {
+    let client = GitHubClient::for_config(config);
+    let db = MetricsDb::open()?;
+    let notifications = config.notifications.clone().unwrap_or_default();
+    let mut trackers = Trackers::default();
+    let mut dormant = DormantScopes::default();
+    tell_systemd("READY=1");
+    loop {
+        let snapshot = poll(config, &client, &db, &mut dormant).await;
+        for line in trackers.notify(&notifications, &client, &snapshot).await {
+            println!("{line}");
+        }
+        let status = snapshot.status();
+        if let Some(settings) = &config.status_file {
+            if let Err(e) = status_file::write(settings, &status) {
+                eprintln!("Status file: {e:#}");
+            }
+        }
+        tell_systemd(&format!(
+            "STATUS={}/{} runner(s) online",
+            status.online(),
+            status.total()
+        ));
+        let github = github_reachable(&client, &snapshot).await;
+        health
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .record_poll(Instant::now(), &db.check(), &github);
+        run_janitor(config).await;
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Send `state` to systemd when running as a `Type=notify` unit
+fn tell_systemd(state: &str) {
+    if let Err(e) = systemd::notify(state) {
+        eprintln!("{e:#}");
+    }
+}
+
+/// Ping systemd's watchdog, if the unit has one, for as long as the collector is live,
+/// so a stuck loop gets restarted
+async fn feed_watchdog(health: &Mutex<CollectorHealth>) -> Result<()> {
+    let Some(every) = systemd::watchdog_interval() else {
+        return std::future::pending().await;
+    };
+    loop {
+        let live = health
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .is_live(Instant::now());
+        if live {
+            tell_systemd("WATCHDOG=1");
+        }
+        tokio::time::sleep(every).await;
+    }
+}
+
+/// Poll every `interval` until the process is stopped, answering health checks on
+/// `listen` if given
+pub async fn run(config: &Config, interval: Duration, listen: Option<&str>) -> Result<()> {
+    println!(
+        "Collecting {} scope(s) every {}s",
+        scopes(&runner::list_instances(config)).len(),
+        interval.as_secs()
+    );
+    if let Some(settings) = &config.status_file {
+        println!("Writing status to {}", settings.path);
+    }
+    let health = Arc::new(Mutex::new(CollectorHealth::new(interval)));
+    let server = async {
+        match listen {
+            Some(listen) => health::serve(listen, Arc::clone(&health)).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        result = collect(config, interval, &health) => result,
+        result = server => result,
+        result = feed_watchdog(&health) => result,
+    }
+}
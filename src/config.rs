@@ -1,10 +1,17 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 
-use crate::github::RunnerScope;
+use crate::credentials::{self, CredentialBackend};
+use crate::failure::Failure;
+use crate::github::{self, RunnerScope};
+use crate::privilege::Escalation;
+use crate::runner::InstanceId;
+use crate::scheduler::RequestLimits;
 
 /// Configuration for the scan command - specifies additional paths to search for runners
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -60,18 +67,638 @@ impl ScanConfig {
     }
 }
 
+/// Commands probed for the toolchain inventory when `toolchain_commands` is not set
+pub const DEFAULT_TOOLCHAIN_COMMANDS: &[&str] = &[
+    "git --version",
+    "node --version",
+    "python3 --version",
+    "docker --version",
+    "go version",
+    "java -version",
+];
+
+/// Opt-in cleanup of dangling container resources left behind by jobs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JanitorConfig {
+    /// Enable automatic pruning from the dashboard
+    #[serde(default)]
+    pub enabled: bool,
+    /// Minimum hours between scheduled prunes
+    #[serde(default = "default_janitor_interval")]
+    pub interval_hours: u64,
+    /// Prune early when the container data disk is at least this full (percent)
+    #[serde(default)]
+    pub disk_threshold_percent: Option<u8>,
+    /// Also prune unused volumes (may remove job caches)
+    #[serde(default)]
+    pub prune_volumes: bool,
+}
+
+fn default_janitor_interval() -> u64 {
+    24
+}
+
+impl Default for JanitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: default_janitor_interval(),
+            disk_threshold_percent: None,
+            prune_volumes: false,
+        }
+    }
+}
+
+/// Where to send notifications about failed workflow runs, offline runners and crashed
+/// runner services
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    /// Slack incoming webhook URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slack_webhook: Option<String>,
+    /// Discord webhook URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discord_webhook: Option<String>,
+    /// Show a desktop notification (`notify-send` on Linux, `osascript` on macOS)
+    #[serde(default)]
+    pub desktop: bool,
+    /// Command run for each failed run, e.g. `/usr/local/bin/page-me {scope} {url}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_run_failed: Option<String>,
+    /// Command run when a runner goes offline, e.g. `/usr/local/bin/page-me {scope} {runner}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_runner_offline: Option<String>,
+    /// Command run when a runner service stops on its own, e.g.
+    /// `/usr/local/bin/page-me {scope} {service}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_service_crashed: Option<String>,
+    /// Command run when the watchdog's restarts don't bring a runner back online
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_restart_failed: Option<String>,
+}
+
+impl NotifyConfig {
+    /// Whether any notification is sent anywhere
+    pub fn is_enabled(&self) -> bool {
+        self.slack_webhook.is_some()
+            || self.discord_webhook.is_some()
+            || self.desktop
+            || self.on_run_failed.is_some()
+            || self.on_runner_offline.is_some()
+            || self.on_service_crashed.is_some()
+            || self.on_restart_failed.is_some()
+    }
+}
+
+fn default_log_lines() -> usize {
+    100
+}
+
+/// Verbose log panel of the dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerboseLogConfig {
+    /// Lines kept in the panel
+    #[serde(default = "default_log_lines")]
+    pub max_lines: usize,
+    /// Also append every line to this file (`~/` is expanded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl Default for VerboseLogConfig {
+    fn default() -> Self {
+        Self {
+            max_lines: default_log_lines(),
+            file: None,
+        }
+    }
+}
+
+fn default_refresh_seconds() -> u64 {
+    30
+}
+
+/// Dashboard settings, reloaded while the dashboard runs
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardConfig {
+    /// Seconds between automatic refreshes
+    #[serde(default = "default_refresh_seconds")]
+    pub refresh_seconds: u64,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self {
+            refresh_seconds: default_refresh_seconds(),
+        }
+    }
+}
+
+fn default_max_concurrent_requests() -> usize {
+    RequestLimits::default().max_concurrent
+}
+
+fn default_requests_per_minute() -> u32 {
+    RequestLimits::default().per_minute
+}
+
+/// Pacing of GitHub API requests, shared by everything one process does
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    /// Requests in flight at once
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+    /// Requests started per minute
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: default_max_concurrent_requests(),
+            requests_per_minute: default_requests_per_minute(),
+        }
+    }
+}
+
+fn default_retention_days() -> u32 {
+    180
+}
+
+/// Metrics database settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// Days of history kept; older rows are pruned when the database is opened.
+    /// 0 keeps everything.
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u32,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: default_retention_days(),
+        }
+    }
+}
+
+fn default_grace_seconds() -> u64 {
+    120
+}
+
+fn default_backoff_seconds() -> u64 {
+    60
+}
+
+fn default_max_backoff_seconds() -> u64 {
+    3600
+}
+
+fn default_notify_after() -> u32 {
+    3
+}
+
+/// Automatic restarts of runners whose service runs but GitHub reports offline
+/// (`runner-mgr watch` and the dashboard's `W` toggle)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// Seconds a runner may be offline before its first restart
+    #[serde(default = "default_grace_seconds")]
+    pub grace_seconds: u64,
+    /// Seconds before the second restart, doubling after each further one
+    #[serde(default = "default_backoff_seconds")]
+    pub backoff_seconds: u64,
+    /// Longest wait between restarts
+    #[serde(default = "default_max_backoff_seconds")]
+    pub max_backoff_seconds: u64,
+    /// Restarts after which a runner that is still offline is reported
+    #[serde(default = "default_notify_after")]
+    pub notify_after: u32,
+    /// Register runners again when GitHub lost their registration, instead of only
+    /// reporting them (`runner-mgr watch`)
+    #[serde(default)]
+    pub reregister: bool,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            grace_seconds: default_grace_seconds(),
+            backoff_seconds: default_backoff_seconds(),
+            max_backoff_seconds: default_max_backoff_seconds(),
+            notify_after: default_notify_after(),
+            reregister: false,
+        }
+    }
+}
+
+/// Status file for external status pages, written after every refresh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusFileConfig {
+    /// Path of the JSON status file (fleet and per-scope health)
+    pub path: String,
+    /// Path of a shields.io endpoint badge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub badge: Option<String>,
+}
+
+fn default_webhook_listen() -> String {
+    "127.0.0.1:8787".to_string()
+}
+
+/// `webhook-server` settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Secret configured on the GitHub webhook, used to verify signatures
+    /// (`RUNNER_MGR_WEBHOOK_SECRET` overrides it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Address to listen on
+    #[serde(default = "default_webhook_listen")]
+    pub listen: String,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            listen: default_webhook_listen(),
+        }
+    }
+}
+
+fn default_max_runners() -> u32 {
+    1
+}
+
+fn default_idle_minutes() -> u32 {
+    15
+}
+
+/// Per-scope limits for `runner-mgr autoscale`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoscaleConfig {
+    /// Runners kept running even with nothing queued (the instance, then
+    /// just-in-time runners)
+    #[serde(default)]
+    pub min_runners: u32,
+    /// Most runners at once: the instance plus single-use just-in-time runners
+    #[serde(default = "default_max_runners")]
+    pub max_runners: u32,
+    /// Stop the instance after it has been idle this long
+    #[serde(default = "default_idle_minutes")]
+    pub idle_minutes: u32,
+}
+
+impl Default for AutoscaleConfig {
+    fn default() -> Self {
+        Self {
+            min_runners: 0,
+            max_runners: default_max_runners(),
+            idle_minutes: default_idle_minutes(),
+        }
+    }
+}
+
+impl AutoscaleConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_runners == 0 {
+            anyhow::bail!("max_runners must be at least 1");
+        }
+        if self.min_runners > self.max_runners {
+            anyhow::bail!(
+                "min_runners ({}) is greater than max_runners ({})",
+                self.min_runners,
+                self.max_runners
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Service level objectives for one scope, measured from the metrics database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloConfig {
+    /// Target runner availability in percent (share of runner snapshots online)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability: Option<f64>,
+    /// Target workflow run success rate in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success_rate: Option<f64>,
+    /// Rolling window the objectives are measured over
+    #[serde(default = "default_slo_window_days")]
+    pub window_days: u32,
+}
+
+fn default_slo_window_days() -> u32 {
+    30
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self {
+            availability: None,
+            success_rate: None,
+            window_days: default_slo_window_days(),
+        }
+    }
+}
+
+/// Proxy and work folder settings written to every runner's `.env`, so jobs inherit them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunnerEnvConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_proxy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub https_proxy: Option<String>,
+    /// Comma-separated hosts that bypass the proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// Folder jobs run in, relative to the instance directory unless absolute.
+    /// `{instance}` is replaced with the instance directory name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub work_folder: Option<String>,
+}
+
+impl RunnerEnvConfig {
+    /// The work folder of the instance in `dir`, as passed to `config.sh --work`
+    pub fn work_folder(&self, dir: &Path) -> Option<String> {
+        let instance = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.work_folder
+            .as_ref()
+            .map(|folder| folder.replace("{instance}", &instance))
+    }
+
+    /// Variables written to the `.env` of the instance in `dir`
+    pub fn env_vars(&self, dir: &Path) -> BTreeMap<String, String> {
+        let mut vars = BTreeMap::new();
+        let proxies = [
+            ("http_proxy", &self.http_proxy),
+            ("https_proxy", &self.https_proxy),
+            ("no_proxy", &self.no_proxy),
+        ];
+        for (key, value) in proxies {
+            if let Some(v) = value {
+                vars.insert(key.to_string(), v.clone());
+            }
+        }
+        if let Some(folder) = self.work_folder(dir) {
+            vars.insert(
+                "RUNNER_WORKSPACE".to_string(),
+                dir.join(folder).to_string_lossy().to_string(),
+            );
+        }
+        vars
+    }
+}
+
+/// Named settings applied by `add --profile <name>`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunnerProfile {
+    /// Labels added to the ones given on the command line
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Environment variables written to the runner's `.env`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// systemd `MemoryMax` for the runner service (e.g. "16G")
+    #[serde(default)]
+    pub memory_max: Option<String>,
+    /// systemd `CPUQuota` for the runner service (e.g. "400%")
+    #[serde(default)]
+    pub cpu_quota: Option<String>,
+    /// Script run before each job (`ACTIONS_RUNNER_HOOK_JOB_STARTED`)
+    #[serde(default)]
+    pub job_started_hook: Option<String>,
+    /// Script run after each job (`ACTIONS_RUNNER_HOOK_JOB_COMPLETED`)
+    #[serde(default)]
+    pub job_completed_hook: Option<String>,
+    /// Container hooks script enabling container mode (`ACTIONS_RUNNER_CONTAINER_HOOKS`)
+    #[serde(default)]
+    pub container_hooks: Option<String>,
+}
+
+impl RunnerProfile {
+    /// All variables this profile writes to `.env`, including hook settings
+    pub fn env_vars(&self) -> BTreeMap<String, String> {
+        let mut vars = self.env.clone();
+        let hooks = [
+            ("ACTIONS_RUNNER_HOOK_JOB_STARTED", &self.job_started_hook),
+            (
+                "ACTIONS_RUNNER_HOOK_JOB_COMPLETED",
+                &self.job_completed_hook,
+            ),
+            ("ACTIONS_RUNNER_CONTAINER_HOOKS", &self.container_hooks),
+        ];
+        for (key, value) in hooks {
+            if let Some(v) = value {
+                vars.insert(key.to_string(), v.clone());
+            }
+        }
+        vars
+    }
+}
+
+/// Commands run before an instance's runner starts, e.g. to mount a volume or start
+/// docker
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreStartConfig {
+    /// Shell commands, run in order; the runner doesn't start if one fails
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Run them as root instead of the runner user
+    #[serde(default)]
+    pub as_root: bool,
+}
+
+/// Environment variables `runner-mgr init` reads when the matching flag isn't given
+pub const PAT_ENV: &str = "RUNNER_MGR_PAT";
+pub const RUNNER_USER_ENV: &str = "RUNNER_MGR_RUNNER_USER";
+pub const INSTANCES_BASE_ENV: &str = "RUNNER_MGR_INSTANCES_BASE";
+
+/// Environment variable selecting the account when `--account` isn't given
+pub const ACCOUNT_ENV: &str = "RUNNER_MGR_ACCOUNT";
+
+/// Alias of `ACCOUNT_ENV`. Accounts are selected with `--account` rather than
+/// `--profile`, which `add` already uses for runner profiles.
+pub const PROFILE_ENV: &str = "RUNNER_MGR_PROFILE";
+
+static ACCOUNT: Mutex<Option<String>> = Mutex::new(None);
+
+/// Select the named account whose config (and state) is used, or the default one
+pub fn set_account(name: Option<String>) -> Result<()> {
+    if let Some(name) = &name {
+        if !valid_account_name(name) {
+            anyhow::bail!(Failure::InvalidAccount(format!(
+                "Invalid account name '{name}': use letters, digits, '-' and '_'"
+            )));
+        }
+    }
+    *ACCOUNT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = name;
+    Ok(())
+}
+
+/// The account selected with `--account`, if any
+pub fn account() -> Option<String> {
+    ACCOUNT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+}
+
+pub fn valid_account_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Named accounts with a config file, in `accounts/` of the base config directory
+pub fn list_accounts() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(Config::base_config_dir().join("accounts")) else {
+        return Vec::new();
+    };
+    let mut accounts: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().join("config.toml").is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| valid_account_name(name))
+        .collect();
+    accounts.sort();
+    accounts
+}
+
+/// The config file of account `name` (None: the default one) as written, without
+/// reading the PAT from the keychain
+pub fn read_account(name: Option<&str>) -> Option<Config> {
+    let mut dir = Config::base_config_dir();
+    if let Some(name) = name {
+        dir = dir.join("accounts").join(name);
+    }
+    let content = fs::read_to_string(dir.join("config.toml")).ok()?;
+    toml::from_str(&content).ok()
+}
+
+/// Check that a token looks like a GitHub PAT (classic `ghp_` or fine-grained `github_pat_`)
+pub fn validate_pat_format(pat: &str) -> Result<()> {
+    if pat.is_empty() {
+        anyhow::bail!("PAT cannot be empty");
+    }
+    if pat.chars().any(char::is_whitespace) {
+        anyhow::bail!("PAT must not contain whitespace");
+    }
+    let body = pat
+        .strip_prefix("ghp_")
+        .or_else(|| pat.strip_prefix("github_pat_"))
+        .context("PAT should start with 'ghp_' (classic) or 'github_pat_' (fine-grained)")?;
+    if body.len() < 20 || !body.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        anyhow::bail!("PAT looks truncated or malformed");
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
+    /// Empty in the file when `credential_backend` is `keyring`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub github_pat: String,
     pub github_user: String,
     pub runner_user: String,
     pub runner_os: String,
     pub runner_arch: String,
     pub instances_base: String,
+    /// GitHub Enterprise Server API root, e.g. `https://ghe.example.com/api/v3`
+    /// (github.com when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_api_url: Option<String>,
+    /// Where the PAT is stored: `file` (config.toml) or `keyring` (the OS keychain)
+    #[serde(default, skip_serializing_if = "CredentialBackend::is_default")]
+    pub credential_backend: CredentialBackend,
+    /// Tool used for steps that need root or the runner user (sudo, doas, run0, none)
+    #[serde(default, skip_serializing_if = "Escalation::is_default")]
+    pub escalation: Escalation,
+    /// `false` runs everything as the current user: no escalation, runner services
+    /// installed as systemd user units (Linux) or launch agents (macOS)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_sudo: Option<bool>,
+    /// Commands run as the runner user to build the toolchain inventory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub toolchain_commands: Vec<String>,
+    /// Label patterns appended by `add`, e.g. `["{os}", "{arch}", "{hostname}"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auto_labels: Vec<String>,
+    /// Container resource cleanup settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub janitor: Option<JanitorConfig>,
+    /// Notifications for failed runs on self-hosted runners (dashboard only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<NotifyConfig>,
+    /// Proxy and work folder settings for every runner's `.env`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runner_env: Option<RunnerEnvConfig>,
+    /// Dashboard verbose log buffer and persistence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verbose_log: Option<VerboseLogConfig>,
+    /// Dashboard refresh settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dashboard: Option<DashboardConfig>,
+    /// Receiver for `workflow_job` webhooks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookConfig>,
+    /// Metrics database retention
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MetricsConfig>,
+    /// GitHub API request pacing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api: Option<ApiConfig>,
+    /// Status file for external status pages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_file: Option<StatusFileConfig>,
+    /// Automatic restarts of offline runners
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watchdog: Option<WatchdogConfig>,
+    /// Autoscaling limits keyed by target (`owner/repo`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub autoscale: BTreeMap<String, AutoscaleConfig>,
+    /// Service level objectives keyed by target (`owner/repo` or `org:name`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub slo: BTreeMap<String, SloConfig>,
+    /// SSH destinations (`user@host` or an alias from `~/.ssh/config`) searched by
+    /// `scan --remote`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remote_hosts: Vec<String>,
+    /// Named runner profiles for `add --profile`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, RunnerProfile>,
+    /// Pre-start hooks keyed by instance (`owner/repo`, `org:name`, or either with
+    /// `@name`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pre_start: BTreeMap<String, PreStartConfig>,
+    /// Proxy for GitHub API requests, runner downloads and runner registration
+    /// (default: `HTTPS_PROXY`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
 }
 
 impl Config {
+    /// Config directory of the selected account: `accounts/<name>` of the base
+    /// directory for a named one
     pub fn config_dir() -> PathBuf {
+        let base = Self::base_config_dir();
+        match account() {
+            Some(name) => base.join("accounts").join(name),
+            None => base,
+        }
+    }
+
+    /// Config directory of the default account
+    pub fn base_config_dir() -> PathBuf {
         if let Ok(dir) = std::env::var("RUNNER_MGR_CONFIG_DIR") {
             PathBuf::from(dir)
         } else {
@@ -88,50 +715,279 @@ impl Config {
     pub fn load() -> Result<Self> {
         let path = Self::config_file();
         let content = fs::read_to_string(&path).with_context(|| {
-            format!(
-                "Not initialized. Run: runner-mgr init\n  (expected config at {})",
+            let account = account()
+                .map(|a| format!("--account {a} "))
+                .unwrap_or_default();
+            Failure::ConfigMissing(format!(
+                "Not initialized. Run: runner-mgr {account}init\n  (expected config at {})",
                 path.display()
-            )
+            ))
         })?;
-        let config: Config =
+        let mut config: Config =
             toml::from_str(&content).with_context(|| "Failed to parse config file")?;
+        if config.rootless() {
+            config.escalation = Escalation::None;
+        }
+        if let Some(url) = &config.proxy_url {
+            reqwest::Proxy::all(url)
+                .with_context(|| format!("Invalid proxy_url '{url}' in {}", path.display()))?;
+        }
+        config.load_pat()?;
         Ok(config)
     }
 
+    /// Read the PAT from the keychain. After `credential_backend` changed, the PAT is
+    /// moved to the new place on the first load.
+    fn load_pat(&mut self) -> Result<()> {
+        match self.credential_backend {
+            // A PAT left in the file: move it to the keychain
+            CredentialBackend::Keyring if !self.github_pat.is_empty() => self.save(),
+            CredentialBackend::Keyring => {
+                self.github_pat = credentials::load()?.with_context(|| {
+                    format!(
+                        "No PAT in the OS keychain (credential_backend = \"keyring\" in {}). \
+                         Run: runner-mgr init",
+                        Self::config_file().display()
+                    )
+                })?;
+                Ok(())
+            }
+            // Switched back to the file: move the PAT out of the keychain
+            CredentialBackend::File if self.github_pat.is_empty() => {
+                if let Some(pat) = credentials::load().ok().flatten() {
+                    self.github_pat = pat;
+                    self.save()?;
+                    credentials::delete()?;
+                }
+                Ok(())
+            }
+            CredentialBackend::File => Ok(()),
+        }
+    }
+
+    /// Time between automatic dashboard refreshes (at least one second)
+    pub fn refresh_interval(&self) -> Duration {
+        let secs = self
+            .dashboard
+            .as_ref()
+            .map_or_else(default_refresh_seconds, |d| d.refresh_seconds);
+        Duration::from_secs(secs.max(1))
+    }
+
+    /// Limits for the process-wide GitHub API scheduler
+    pub fn request_limits(&self) -> RequestLimits {
+        let api = self.api.clone().unwrap_or_default();
+        RequestLimits {
+            max_concurrent: api.max_concurrent_requests,
+            per_minute: api.requests_per_minute,
+        }
+    }
+
+    /// Days of metrics history to keep (0 keeps everything)
+    pub fn metrics_retention_days(&self) -> u32 {
+        self.metrics
+            .as_ref()
+            .map_or_else(default_retention_days, |m| m.retention_days)
+    }
+
+    /// `new` with the credentials and API endpoint of `self`. The dashboard applies
+    /// edits to the config file this way: its GitHub client keeps the token it was
+    /// started with.
+    #[must_use]
+    pub fn reloaded(&self, new: Config) -> Config {
+        Config {
+            github_pat: self.github_pat.clone(),
+            github_user: self.github_user.clone(),
+            github_api_url: self.github_api_url.clone(),
+            ..new
+        }
+    }
+
     pub fn save(&self) -> Result<()> {
         let dir = Self::config_dir();
         fs::create_dir_all(&dir)?;
-        // Restrict config dir permissions
-        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
-
         let path = Self::config_file();
-        let content = toml::to_string_pretty(self)?;
+        let content = if self.credential_backend == CredentialBackend::Keyring {
+            if !self.github_pat.is_empty() {
+                credentials::store(&self.github_pat)?;
+            }
+            toml::to_string_pretty(&Config {
+                github_pat: String::new(),
+                ..self.clone()
+            })?
+        } else {
+            toml::to_string_pretty(self)?
+        };
         fs::write(&path, &content)?;
-        // Restrict config file permissions (contains PAT)
-        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        // Restrict permissions: the file may contain the PAT. On Windows the per-user
+        // config directory is already private to the user.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        }
         Ok(())
     }
 
+    /// REST API root of the configured GitHub instance
+    pub fn api_url(&self) -> String {
+        self.github_api_url
+            .as_deref()
+            .unwrap_or(github::DEFAULT_API_URL)
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    /// Proxy variables for runners: those set in `[runner_env]`, else `proxy_url`
+    pub fn runner_proxy_vars(&self) -> BTreeMap<String, String> {
+        let mut vars = BTreeMap::new();
+        if let Some(env) = &self.runner_env {
+            let proxies = [
+                ("http_proxy", &env.http_proxy),
+                ("https_proxy", &env.https_proxy),
+                ("no_proxy", &env.no_proxy),
+            ];
+            for (key, value) in proxies {
+                if let Some(v) = value {
+                    vars.insert(key.to_string(), v.clone());
+                }
+            }
+        }
+        if let Some(url) = &self.proxy_url {
+            for key in ["http_proxy", "https_proxy"] {
+                vars.entry(key.to_string()).or_insert_with(|| url.clone());
+            }
+        }
+        vars
+    }
+
+    /// Web UI root of the configured GitHub instance
+    pub fn web_url(&self) -> String {
+        github::web_url_for_api(&self.api_url())
+    }
+
     pub fn instances_dir(&self) -> PathBuf {
         PathBuf::from(&self.instances_base).join("instances")
     }
 
+    /// Working directories of single-use just-in-time runners
+    pub fn jit_dir(&self) -> PathBuf {
+        PathBuf::from(&self.instances_base).join("jit")
+    }
+
     pub fn template_dir(&self) -> PathBuf {
         PathBuf::from(&self.instances_base).join("template")
     }
 
     pub fn instance_dir(&self, scope: &RunnerScope) -> PathBuf {
-        self.instances_dir().join(scope.to_dir_name())
+        self.instance_path(&scope.to_dir_name())
+    }
+
+    /// Path of the instance directory `dir_name`. Scope names are case-insensitive, so an
+    /// existing directory whose name differs only in case is the same instance.
+    pub fn instance_path(&self, dir_name: &str) -> PathBuf {
+        let instances_dir = self.instances_dir();
+        let exact = instances_dir.join(dir_name);
+        if exact.exists() {
+            return exact;
+        }
+        std::fs::read_dir(&instances_dir)
+            .ok()
+            .and_then(|entries| {
+                entries.flatten().map(|entry| entry.path()).find(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.eq_ignore_ascii_case(dir_name))
+                })
+            })
+            .unwrap_or(exact)
+    }
+
+    /// Trigger files for on-demand runners
+    pub fn queue_dir(&self) -> PathBuf {
+        PathBuf::from(&self.instances_base).join("queue")
+    }
+
+    /// Toolchain probe commands, falling back to `DEFAULT_TOOLCHAIN_COMMANDS`
+    pub fn toolchain_commands(&self) -> Vec<String> {
+        if self.toolchain_commands.is_empty() {
+            DEFAULT_TOOLCHAIN_COMMANDS
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        } else {
+            self.toolchain_commands.clone()
+        }
+    }
+
+    /// Look up a named profile, listing the available names if it doesn't exist
+    pub fn profile(&self, name: &str) -> Result<&RunnerProfile> {
+        self.profiles.get(name).ok_or_else(|| {
+            let available: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if available.is_empty() {
+                anyhow::anyhow!("Unknown profile '{name}' (no profiles defined in config)")
+            } else {
+                anyhow::anyhow!(
+                    "Unknown profile '{name}'. Available: {}",
+                    available.join(", ")
+                )
+            }
+        })
     }
 
     pub fn detect_os() -> String {
         if cfg!(target_os = "macos") {
             "darwin".to_string()
+        } else if cfg!(windows) {
+            "windows".to_string()
         } else {
             "linux".to_string()
         }
     }
 
+    /// Where `init` puts runner instances on this host
+    pub fn default_instances_base() -> &'static str {
+        if cfg!(windows) {
+            r"C:\github-runners"
+        } else {
+            "/opt/github-runners"
+        }
+    }
+
+    /// Where `init --rootless` puts runner instances: a directory the current user owns
+    pub fn rootless_instances_base() -> PathBuf {
+        let base = dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("runner-mgr");
+        match account() {
+            Some(name) => base.join("accounts").join(name).join("runners"),
+            None => base.join("runners"),
+        }
+    }
+
+    /// Service level objectives configured for `scope`, if any
+    pub fn slo_for(&self, scope: &RunnerScope) -> Option<&SloConfig> {
+        self.slo
+            .iter()
+            .find(|(target, _)| RunnerScope::parse(target).is_ok_and(|t| t == *scope))
+            .map(|(_, slo)| slo)
+    }
+
+    /// Pre-start hooks configured for the instance `id`, if any
+    pub fn pre_start_for(&self, id: &InstanceId) -> Option<&PreStartConfig> {
+        self.pre_start
+            .iter()
+            .find(|(target, _)| InstanceId::parse(target).is_ok_and(|t| t == *id))
+            .map(|(_, hooks)| hooks)
+            .filter(|hooks| !hooks.commands.is_empty())
+    }
+
+    /// Whether runner-mgr operates entirely as the current user (`use_sudo = false`)
+    pub fn rootless(&self) -> bool {
+        self.use_sudo == Some(false)
+    }
+
     pub fn detect_arch() -> String {
         if cfg!(target_arch = "aarch64") {
             "arm64".to_string()
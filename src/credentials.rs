@@ -0,0 +1,60 @@
+//! The PAT in the OS keychain (`credential_backend = "keyring"`): macOS Keychain, Windows
+//! Credential Manager, or the Secret Service (GNOME Keyring, `KWallet`) on Linux
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Service name of the keychain entry
+pub const SERVICE: &str = "runner-mgr";
+
+/// Where the PAT is stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialBackend {
+    /// `github_pat` in config.toml, readable only by its owner
+    #[default]
+    File,
+    /// The OS keychain; config.toml holds no secret
+    Keyring,
+}
+
+impl CredentialBackend {
+    // Takes `&self` because serde's `skip_serializing_if` passes a reference
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Keychain account of the PAT: the config file, so separate configs
+/// (`RUNNER_MGR_CONFIG_DIR`) keep separate tokens
+fn entry() -> Result<keyring::Entry> {
+    let account = Config::config_file().display().to_string();
+    keyring::Entry::new(SERVICE, &account).context("Failed to open the OS keychain")
+}
+
+/// Store `pat` in the keychain, replacing an earlier one
+pub fn store(pat: &str) -> Result<()> {
+    entry()?
+        .set_password(pat)
+        .context("Failed to store the PAT in the OS keychain")
+}
+
+/// The PAT in the keychain; None when there is none
+pub fn load() -> Result<Option<String>> {
+    match entry()?.get_password() {
+        Ok(pat) => Ok(Some(pat)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e).context("Failed to read the PAT from the OS keychain"),
+    }
+}
+
+/// Remove the PAT from the keychain, if it's there
+pub fn delete() -> Result<()> {
+    match entry()?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e).context("Failed to remove the PAT from the OS keychain"),
+    }
+}
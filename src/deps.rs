@@ -0,0 +1,192 @@
+//! Linux prerequisites for the runner (.NET needs ICU, OpenSSL, Kerberos and zlib)
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+
+use crate::config::Config;
+use crate::runner;
+
+/// Linux distribution family, from `/etc/os-release`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Distro {
+    Debian,
+    Fedora,
+    Suse,
+    Alpine,
+    Arch,
+    Unknown(String),
+}
+
+impl Distro {
+    /// Read `/etc/os-release` on this host
+    pub fn detect() -> Self {
+        fs::read_to_string("/etc/os-release").map_or_else(
+            |_| Self::Unknown(String::new()),
+            |c| Self::from_os_release(&c),
+        )
+    }
+
+    /// Classify by `ID`, falling back to each entry of `ID_LIKE`
+    pub fn from_os_release(content: &str) -> Self {
+        let value = |key: &str| {
+            content
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+                .map(|v| v.trim().trim_matches('"').to_lowercase())
+                .unwrap_or_default()
+        };
+        let id = value("ID");
+        let like = value("ID_LIKE");
+        std::iter::once(id.as_str())
+            .chain(like.split_whitespace())
+            .find_map(|name| match name {
+                "debian" | "ubuntu" => Some(Self::Debian),
+                "fedora" | "rhel" | "centos" | "rocky" | "almalinux" | "amzn" => Some(Self::Fedora),
+                "suse" | "opensuse" | "sles" | "opensuse-leap" | "opensuse-tumbleweed" => {
+                    Some(Self::Suse)
+                }
+                "alpine" => Some(Self::Alpine),
+                "arch" | "manjaro" => Some(Self::Arch),
+                _ => None,
+            })
+            .unwrap_or(Self::Unknown(id))
+    }
+
+    /// Whether the runner's own `installdependencies.sh` knows this distro
+    pub fn has_runner_script(&self) -> bool {
+        matches!(self, Self::Debian | Self::Fedora | Self::Suse)
+    }
+
+    /// Package manager commands (run as root) that install the runner's prerequisites
+    pub fn package_commands(&self) -> Vec<Vec<&'static str>> {
+        match self {
+            Self::Debian => vec![
+                vec!["apt-get", "update"],
+                vec![
+                    "apt-get",
+                    "install",
+                    "-y",
+                    "libicu-dev",
+                    "libkrb5-3",
+                    "libssl-dev",
+                    "zlib1g",
+                ],
+            ],
+            Self::Fedora => vec![vec![
+                "dnf",
+                "install",
+                "-y",
+                "libicu",
+                "krb5-libs",
+                "openssl-libs",
+                "zlib",
+                "lttng-ust",
+            ]],
+            Self::Suse => vec![vec![
+                "zypper", "-n", "install", "libicu", "krb5", "openssl", "zlib",
+            ]],
+            Self::Alpine => vec![vec![
+                "apk",
+                "add",
+                "icu-libs",
+                "krb5-libs",
+                "libgcc",
+                "libintl",
+                "libssl3",
+                "libstdc++",
+                "zlib",
+            ]],
+            Self::Arch => vec![vec![
+                "pacman",
+                "-S",
+                "--noconfirm",
+                "--needed",
+                "icu",
+                "krb5",
+                "openssl",
+                "zlib",
+            ]],
+            Self::Unknown(_) => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Distro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Debian => write!(f, "Debian/Ubuntu"),
+            Self::Fedora => write!(f, "Fedora/RHEL"),
+            Self::Suse => write!(f, "SUSE"),
+            Self::Alpine => write!(f, "Alpine"),
+            Self::Arch => write!(f, "Arch"),
+            Self::Unknown(id) if id.is_empty() => write!(f, "unknown distribution"),
+            Self::Unknown(id) => write!(f, "unsupported distribution ({id})"),
+        }
+    }
+}
+
+/// Install the runner's Linux prerequisites. Uses the template's
+/// `bin/installdependencies.sh` where it supports the distro, otherwise (or if the
+/// script fails) the distro's package manager. Then re-checks the template binary.
+pub fn install(config: &Config) -> Result<()> {
+    if config.runner_os == "darwin" {
+        println!("macOS needs no extra runner dependencies.");
+        return Ok(());
+    }
+
+    let distro = Distro::detect();
+    println!("Detected {distro}");
+
+    let script = config
+        .template_dir()
+        .join("bin")
+        .join("installdependencies.sh");
+    let mut installed = false;
+    if distro.has_runner_script() && script.exists() {
+        println!("Running {}...", script.display());
+        let script = script.to_string_lossy();
+        let status = config
+            .escalation
+            .command(&["bash", &script])
+            .status()
+            .context("Failed to run installdependencies.sh")?;
+        if status.success() {
+            installed = true;
+        } else {
+            println!("installdependencies.sh failed; falling back to the package manager.");
+        }
+    }
+
+    if !installed {
+        let commands = distro.package_commands();
+        if commands.is_empty() {
+            anyhow::bail!(
+                "Don't know how to install dependencies on {distro}. Install ICU, OpenSSL, \
+                 Kerberos (libkrb5) and zlib with your package manager."
+            );
+        }
+        for args in commands {
+            println!("Running: {}", args.join(" "));
+            let status = config
+                .escalation
+                .command(&args)
+                .status()
+                .with_context(|| format!("Failed to run {}", args[0]))?;
+            if !status.success() {
+                anyhow::bail!("{} failed ({status})", args.join(" "));
+            }
+        }
+    }
+
+    println!("Dependencies installed.");
+    if config
+        .template_dir()
+        .join("bin")
+        .join("Runner.Listener")
+        .exists()
+    {
+        runner::validate_template(config)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,243 @@
+//! Container engine health checks for runner hosts (docker or podman)
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::config::{Config, JanitorConfig};
+use crate::runner;
+use crate::toolchain::first_line;
+
+/// Container engine used by container jobs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    Docker,
+    Podman,
+}
+
+impl Engine {
+    pub fn command(self) -> &'static str {
+        match self {
+            Engine::Docker => "docker",
+            Engine::Podman => "podman",
+        }
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.command())
+    }
+}
+
+/// Whether the container engine can be used by the runner user
+#[derive(Debug, Clone)]
+pub enum DockerHealth {
+    Reachable { engine: Engine, version: String },
+    Unreachable { engine: Engine, error: String },
+    NotInstalled,
+}
+
+impl DockerHealth {
+    /// The installed engine, if any
+    pub fn engine(&self) -> Option<Engine> {
+        match self {
+            DockerHealth::Reachable { engine, .. } | DockerHealth::Unreachable { engine, .. } => {
+                Some(*engine)
+            }
+            DockerHealth::NotInstalled => None,
+        }
+    }
+}
+
+impl fmt::Display for DockerHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DockerHealth::Reachable { engine, version } => {
+                write!(f, "{engine} {version} (reachable)")
+            }
+            DockerHealth::Unreachable { engine, error } => {
+                write!(f, "{engine} daemon not reachable: {error}")
+            }
+            DockerHealth::NotInstalled => write!(f, "no container engine installed"),
+        }
+    }
+}
+
+/// Run a shell command as the runner user without prompting for a password
+fn as_runner_user(config: &Config, command: &str) -> Option<std::process::Output> {
+    config
+        .escalation
+        .command(&["-n", "-u", &config.runner_user, "sh", "-lc", command])
+        .output()
+        .ok()
+}
+
+/// Check that docker (or podman, if docker isn't installed) answers as the runner user
+pub fn check(config: &Config) -> DockerHealth {
+    for engine in [Engine::Docker, Engine::Podman] {
+        let installed = as_runner_user(config, &format!("command -v {engine}"))
+            .is_some_and(|o| o.status.success());
+        if !installed {
+            continue;
+        }
+
+        let output = as_runner_user(
+            config,
+            &format!("{engine} info --format '{{{{.ServerVersion}}}}'"),
+        );
+        return match output {
+            Some(o) if o.status.success() => DockerHealth::Reachable {
+                engine,
+                version: first_line(&o.stdout).unwrap_or_else(|| "unknown".to_string()),
+            },
+            Some(o) => DockerHealth::Unreachable {
+                engine,
+                error: first_line(&o.stderr).unwrap_or_else(|| "unknown error".to_string()),
+            },
+            None => DockerHealth::Unreachable {
+                engine,
+                error: "failed to run as runner user".to_string(),
+            },
+        };
+    }
+    DockerHealth::NotInstalled
+}
+
+/// Restart the container engine daemon (Linux/systemd only)
+pub fn restart_daemon(config: &Config, engine: Engine) -> Result<()> {
+    if config.runner_os == "darwin" {
+        anyhow::bail!(
+            "Restarting {engine} is not supported on macOS; restart Docker Desktop instead"
+        );
+    }
+
+    let unit = match engine {
+        Engine::Docker => "docker.service",
+        Engine::Podman => "podman.socket",
+    };
+    runner::run_elevated(config, &["systemctl", "restart", unit])
+}
+
+/// Space reclaimed by one prune pass, per resource kind
+#[derive(Debug, Clone, Default)]
+pub struct PruneReport {
+    pub reclaimed: Vec<(&'static str, String)>,
+}
+
+impl fmt::Display for PruneReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .reclaimed
+            .iter()
+            .map(|(kind, amount)| format!("{kind} {amount}"))
+            .collect();
+        write!(f, "reclaimed {}", parts.join(", "))
+    }
+}
+
+/// Parse the "Total reclaimed space: 1.2GB" line printed by `docker ... prune`
+pub fn parse_reclaimed(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("Total reclaimed space:"))
+        .map(|s| s.trim().to_string())
+}
+
+/// Remove stopped containers, dangling images and (optionally) unused volumes
+pub fn prune(config: &Config, engine: Engine, volumes: bool) -> Result<PruneReport> {
+    let mut kinds = vec![("containers", "container"), ("images", "image")];
+    if volumes {
+        kinds.push(("volumes", "volume"));
+    }
+
+    let mut report = PruneReport::default();
+    for (label, kind) in kinds {
+        let output = as_runner_user(config, &format!("{engine} {kind} prune -f"))
+            .with_context(|| format!("Failed to run {engine} {kind} prune"))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "{engine} {kind} prune failed: {}",
+                first_line(&output.stderr).unwrap_or_default()
+            );
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let amount = parse_reclaimed(&stdout).unwrap_or_else(|| "0B".to_string());
+        report.reclaimed.push((label, amount));
+    }
+    Ok(report)
+}
+
+/// Usage percentage of the filesystem holding the engine's data directory
+fn data_disk_usage(config: &Config, engine: Engine) -> Option<u8> {
+    let root = as_runner_user(
+        config,
+        &format!("{engine} info --format '{{{{.DockerRootDir}}}}'"),
+    )
+    .filter(|o| o.status.success())
+    .and_then(|o| first_line(&o.stdout))
+    .unwrap_or_else(|| "/".to_string());
+
+    let output = Command::new("df").args(["-P", &root]).output().ok()?;
+    parse_df_usage(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse the capacity column ("83%") from `df -P` output
+pub fn parse_df_usage(output: &str) -> Option<u8> {
+    output
+        .lines()
+        .nth(1)?
+        .split_whitespace()
+        .nth(4)?
+        .trim_end_matches('%')
+        .parse()
+        .ok()
+}
+
+fn janitor_stamp_file() -> PathBuf {
+    Config::config_dir().join("janitor.last")
+}
+
+fn last_janitor_run() -> Option<i64> {
+    fs::read_to_string(janitor_stamp_file())
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Whether a scheduled prune is due, or the data disk has crossed the threshold
+pub fn janitor_due(config: &Config, janitor: &JanitorConfig, engine: Engine) -> bool {
+    let now = chrono::Utc::now().timestamp();
+    let interval = i64::try_from(janitor.interval_hours.saturating_mul(3600)).unwrap_or(i64::MAX);
+    let scheduled = last_janitor_run().is_none_or(|last| now - last >= interval);
+
+    scheduled
+        || janitor
+            .disk_threshold_percent
+            .zip(data_disk_usage(config, engine))
+            .is_some_and(|(threshold, usage)| usage >= threshold)
+}
+
+/// Run the janitor if it is enabled and due. Returns `None` when nothing ran.
+pub fn run_janitor_if_due(config: &Config) -> Result<Option<PruneReport>> {
+    let Some(janitor) = config.janitor.as_ref().filter(|j| j.enabled) else {
+        return Ok(None);
+    };
+    let Some(engine) = check(config).engine() else {
+        return Ok(None);
+    };
+    if !janitor_due(config, janitor, engine) {
+        return Ok(None);
+    }
+
+    let report = prune(config, engine, janitor.prune_volumes)?;
+    fs::write(
+        janitor_stamp_file(),
+        chrono::Utc::now().timestamp().to_string(),
+    )
+    .context("Failed to record janitor run")?;
+    Ok(Some(report))
+}
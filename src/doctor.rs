@@ -0,0 +1,503 @@
+//! `runner-mgr doctor`: checks of the config, the PAT, the runner template and every
+//! instance, each problem with the command or edit that fixes it
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::collector;
+use crate::config::{self, Config};
+use crate::docker::{self, DockerHealth};
+use crate::github::{self, GitHubClient, Runner, RunnerScope, TokenInfo};
+use crate::labels;
+use crate::runner::{self, InstanceId, RunnerInstance, UnrecognizedDir};
+use crate::updates;
+
+/// Tokens expiring within this many days are flagged
+pub const EXPIRY_WARNING_DAYS: i64 = 14;
+
+/// Disk usage of a work directory that is flagged, and that fails
+pub const DISK_WARN_PERCENT: u8 = 80;
+pub const DISK_FAIL_PERCENT: u8 = 95;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl Severity {
+    pub fn mark(self) -> &'static str {
+        match self {
+            Severity::Ok => "✓",
+            Severity::Warn => "!",
+            Severity::Fail => "✗",
+        }
+    }
+}
+
+/// The outcome of one check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub detail: String,
+    /// What to do about a warning or failure
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    fn ok(detail: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Findings under one heading of the report
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub title: String,
+    pub findings: Vec<Finding>,
+}
+
+/// Settings that parse but can't work
+pub fn check_config(config: &Config) -> Vec<Finding> {
+    let file = Config::config_file();
+    let mut findings = vec![Finding::ok(format!("{} loaded", file.display()))];
+    findings.push(match config::validate_pat_format(&config.github_pat) {
+        Ok(()) => Finding::ok("PAT format looks valid"),
+        Err(e) => Finding::fail(
+            format!("PAT: {e:#}"),
+            "set a new token with `runner-mgr init` (see docs/configuration.md#updating-the-pat)",
+        ),
+    });
+    let instances = config.instances_dir();
+    if !instances.is_dir() {
+        findings.push(Finding::fail(
+            format!("instances directory {} is missing", instances.display()),
+            "run `runner-mgr init` to set up the host",
+        ));
+    }
+    for (target, autoscale) in &config.autoscale {
+        if let Err(e) = autoscale.validate() {
+            findings.push(Finding::fail(
+                format!("[autoscale.\"{target}\"]: {e:#}"),
+                format!("edit {}", file.display()),
+            ));
+        }
+    }
+    findings
+}
+
+/// Parses the `github-authentication-token-expiration` header GitHub sends with
+/// fine-grained and expiring tokens, e.g. `2026-11-01 12:00:00 UTC` or
+/// `2026-11-01 12:00:00 -0700`.
+pub fn parse_expiration(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z") {
+        return Some(time.with_timezone(&Utc));
+    }
+    let naive = value.strip_suffix("UTC")?.trim();
+    NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// GitHub reachability and the token's scopes and expiry. `org_scopes` is whether any
+/// instance is registered for an organization, which needs `admin:org`; `web_url` is
+/// the web UI of the GitHub instance, where tokens are managed.
+pub fn check_token(
+    info: &Result<TokenInfo>,
+    org_scopes: bool,
+    web_url: &str,
+    now: DateTime<Utc>,
+) -> Vec<Finding> {
+    let info = match info {
+        Ok(info) => info,
+        Err(e) => {
+            return vec![Finding::fail(
+                format!("GitHub API: {e:#}"),
+                "check the network, proxy and `github_api_url`; on 401, replace the PAT with \
+                 `runner-mgr init`",
+            )]
+        }
+    };
+    let mut findings = vec![Finding::ok(format!(
+        "GitHub API reachable, authenticated as {}",
+        info.login
+    ))];
+
+    match &info.scopes {
+        None => findings.push(Finding::ok(
+            "fine-grained token (GitHub doesn't report its permissions)",
+        )),
+        Some(scopes) => {
+            // Each required scope, with the narrower scopes that also grant it
+            let mut required = vec![("repo", &[][..])];
+            if org_scopes {
+                required.push(("admin:org", &["manage_runners:org"][..]));
+            }
+            let missing: Vec<&str> = required
+                .into_iter()
+                .filter(|(needed, narrower)| {
+                    !scopes
+                        .iter()
+                        .any(|s| s == needed || narrower.contains(&s.as_str()))
+                })
+                .map(|(needed, _)| needed)
+                .collect();
+            if missing.is_empty() {
+                findings.push(Finding::ok(format!("scopes: {}", scopes.join(", "))));
+            } else {
+                findings.push(Finding::fail(
+                    format!("token lacks the {} scope(s)", missing.join(", ")),
+                    format!(
+                        "add them at {web_url}/settings/tokens, or create a new token and run \
+                         `runner-mgr init`"
+                    ),
+                ));
+            }
+        }
+    }
+
+    match info.expires.as_deref() {
+        None => findings.push(Finding::ok("token doesn't expire")),
+        Some(value) => match parse_expiration(value) {
+            None => findings.push(Finding::ok(format!("token expires {value}"))),
+            Some(expires) if expires <= now => findings.push(Finding::fail(
+                format!("token expired {}", expires.format("%Y-%m-%d")),
+                "create a new token and run `runner-mgr init`",
+            )),
+            Some(expires) if expires - now < chrono::Duration::days(EXPIRY_WARNING_DAYS) => {
+                findings.push(Finding::warn(
+                    format!(
+                        "token expires in {} day(s), on {}",
+                        (expires - now).num_days(),
+                        expires.format("%Y-%m-%d")
+                    ),
+                    "create a new token and run `runner-mgr init` before then",
+                ));
+            }
+            Some(expires) => findings.push(Finding::ok(format!(
+                "token expires {}",
+                expires.format("%Y-%m-%d")
+            ))),
+        },
+    }
+    findings
+}
+
+/// The runner template new instances are copied from
+pub fn check_template(
+    template: &Path,
+    installed: Option<&str>,
+    latest: Option<&str>,
+) -> Vec<Finding> {
+    let script = if cfg!(windows) {
+        "config.cmd"
+    } else {
+        "config.sh"
+    };
+    if !template.join(script).exists() {
+        return vec![Finding::fail(
+            format!("no runner template in {}", template.display()),
+            "download it with `runner-mgr update`",
+        )];
+    }
+    let Some(installed) = installed else {
+        return vec![Finding::warn(
+            format!(
+                "template in {} has no working Runner.Listener",
+                template.display()
+            ),
+            "download it again with `runner-mgr update`",
+        )];
+    };
+    match latest {
+        Some(latest) if updates::is_outdated(installed, latest) => vec![Finding::warn(
+            format!("template is {installed}, latest is {latest}"),
+            "run `runner-mgr update`",
+        )],
+        _ => vec![Finding::ok(format!("template {installed}"))],
+    }
+}
+
+/// Usage of the filesystem holding `dir`, from `df -P`
+fn disk_usage(dir: &Path) -> Option<u8> {
+    let output = Command::new("df").arg("-P").arg(dir).output().ok()?;
+    docker::parse_df_usage(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Free space for the jobs of an instance working in `work`
+pub fn disk_finding(work: &Path, usage: Option<u8>) -> Finding {
+    match usage {
+        None => Finding::ok(format!("disk usage of {} unknown", work.display())),
+        Some(percent) if percent >= DISK_FAIL_PERCENT => Finding::fail(
+            format!("{} is on a disk {percent}% full", work.display()),
+            "free space, e.g. with `runner-mgr docker prune` or by clearing old _work checkouts",
+        ),
+        Some(percent) if percent >= DISK_WARN_PERCENT => Finding::warn(
+            format!("{} is on a disk {percent}% full", work.display()),
+            "free space before jobs start failing, e.g. with `runner-mgr docker prune`",
+        ),
+        Some(percent) => Finding::ok(format!("disk {percent}% full")),
+    }
+}
+
+/// Whether the unit or plist of `service` exists, where that can be told from its name
+fn service_unit_exists(config: &Config, service: &str) -> Option<bool> {
+    match config.runner_os.as_str() {
+        "linux" => Some(
+            runner::systemd_unit_dir(config)
+                .join(format!("{service}.service"))
+                .exists(),
+        ),
+        "darwin" if service.starts_with('/') => Some(Path::new(service).exists()),
+        _ => None,
+    }
+}
+
+/// An instance's link target, registration, credentials, service and disk
+pub fn check_instance(config: &Config, instance: &RunnerInstance) -> Vec<Finding> {
+    let id = instance.id();
+    let dir = &instance.dir;
+    let mut findings = Vec::new();
+
+    if fs::symlink_metadata(dir).is_ok_and(|m| m.file_type().is_symlink()) {
+        if let Ok(target) = fs::read_link(dir) {
+            findings.push(Finding::ok(format!("linked to {}", target.display())));
+        }
+    }
+
+    match &instance.runner_name {
+        Some(name) => findings.push(Finding::ok(format!("registered as {name}"))),
+        None => findings.push(Finding::fail(
+            format!(
+                "{} has no .runner file; it was never registered",
+                dir.display()
+            ),
+            format!("remove it with `runner-mgr remove {id}`, then `runner-mgr add {id}`"),
+        )),
+    }
+    if let Some(problem) = runner::credential_problem(dir) {
+        findings.push(Finding::fail(problem, runner::reregister_hint(&id)));
+    }
+
+    match &instance.service_name {
+        None => findings.push(Finding::warn(
+            "no .service file: no service is installed",
+            format!("run `sudo ./svc.sh install` in {}", dir.display()),
+        )),
+        Some(service) => match service_unit_exists(config, service) {
+            Some(false) => findings.push(Finding::fail(
+                format!(".service names {service}, but its unit is missing"),
+                format!(
+                    "reinstall it with `sudo ./svc.sh install` in {}, or re-add the runner",
+                    dir.display()
+                ),
+            )),
+            _ => findings.push(Finding::ok(format!(
+                "service {service} {}",
+                instance.status
+            ))),
+        },
+    }
+
+    let work = config
+        .runner_env
+        .as_ref()
+        .and_then(|env| env.work_folder(dir))
+        .map_or_else(|| dir.join("_work"), |folder| dir.join(folder));
+    let measured = if work.exists() {
+        work.clone()
+    } else {
+        dir.clone()
+    };
+    findings.push(disk_finding(&work, disk_usage(&measured)));
+    findings
+}
+
+/// Whether container jobs can reach docker or podman as the runner user
+pub fn check_container_engine(health: &DockerHealth) -> Finding {
+    match health {
+        DockerHealth::Reachable { .. } => Finding::ok(health.to_string()),
+        DockerHealth::Unreachable { .. } => Finding::warn(
+            format!("{health}; container jobs will fail"),
+            "restart the daemon with `runner-mgr docker restart`, and check that the runner \
+             user may use it (e.g. is in the `docker` group)",
+        ),
+        DockerHealth::NotInstalled => {
+            Finding::ok("no container engine installed (only container jobs need one)")
+        }
+    }
+}
+
+/// Labels of an instance that name another OS or architecture than this host, or that
+/// disagree with GitHub's record of the runner (`record`, when it could be fetched).
+/// Mislabeled runners get jobs they can't run.
+pub fn check_labels(
+    id: &InstanceId,
+    registered: &[String],
+    record: Option<&Runner>,
+    host_os: &str,
+    host_arch: &str,
+) -> Vec<Finding> {
+    let warnings = labels::runner_mismatches(registered, record, host_os, host_arch);
+    if warnings.is_empty() {
+        return vec![Finding::ok("labels match this host")];
+    }
+    warnings
+        .into_iter()
+        .map(|w| {
+            Finding::warn(
+                w,
+                format!("correct the labels with `runner-mgr labels set {id} <labels>`"),
+            )
+        })
+        .collect()
+}
+
+/// Entries in the instances directory that are links to directories that no longer
+/// exist (`list_instances` skips them)
+pub fn broken_links(config: &Config) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(config.instances_dir()) else {
+        return Vec::new();
+    };
+    let mut links: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()) && !path.exists()
+        })
+        .collect();
+    links.sort();
+    links
+}
+
+/// A directory in the instances directory that isn't named like an instance, so no
+/// command manages it
+pub fn check_unrecognized(dir: &UnrecognizedDir) -> Finding {
+    let path = dir.path.display();
+    match &dir.scope {
+        Some(scope) => Finding::warn(
+            format!("not named like an instance, so it isn't managed (registered for {scope})"),
+            format!(
+                "link it with `runner-mgr import {path}`, or rename it with \
+                 `runner-mgr import {path} --rename`"
+            ),
+        ),
+        None => Finding::warn(
+            "not named like an instance and has no .runner file, so it isn't managed",
+            format!("import it with `runner-mgr import {path} --target <owner/repo> [--rename]`"),
+        ),
+    }
+}
+
+/// Run every check
+pub async fn run(config: &Config) -> Vec<Section> {
+    let client = GitHubClient::for_config(config);
+    let instances = runner::list_instances(config);
+    let mut sections = vec![Section {
+        title: "Config".to_string(),
+        findings: check_config(config),
+    }];
+
+    let org_scopes = instances
+        .iter()
+        .any(|i| matches!(i.scope, RunnerScope::Organization { .. }));
+    let token = client.token_info().await;
+    let reachable = token.is_ok();
+    sections.push(Section {
+        title: "GitHub".to_string(),
+        findings: check_token(&token, org_scopes, &config.web_url(), Utc::now()),
+    });
+
+    let template = config.template_dir();
+    let latest = if reachable {
+        updates::latest_version(&client).await.ok()
+    } else {
+        None
+    };
+    sections.push(Section {
+        title: "Template".to_string(),
+        findings: check_template(
+            &template,
+            runner::installed_version(&template).as_deref(),
+            latest.as_deref(),
+        ),
+    });
+
+    sections.push(Section {
+        title: "Container engine".to_string(),
+        findings: vec![check_container_engine(&docker::check(config))],
+    });
+
+    for link in broken_links(config) {
+        sections.push(Section {
+            title: link.display().to_string(),
+            findings: vec![Finding::fail(
+                "link to a runner directory that no longer exists",
+                format!("remove the link with `rm {}`", link.display()),
+            )],
+        });
+    }
+
+    let mut records: HashMap<RunnerScope, Vec<Runner>> = HashMap::new();
+    if reachable {
+        for scope in collector::scopes(&instances) {
+            if let Ok(list) = client.list_runners(&scope).await {
+                records.insert(scope, list.runners);
+            }
+        }
+    }
+    let host_os = Config::detect_os();
+    let host_arch = Config::detect_arch();
+    for dir in runner::list_unrecognized(config) {
+        sections.push(Section {
+            title: dir.path.display().to_string(),
+            findings: vec![check_unrecognized(&dir)],
+        });
+    }
+    for instance in &instances {
+        let id = instance.id();
+        let mut findings = check_instance(config, instance);
+        let record = records
+            .get(&instance.scope)
+            .and_then(|runners| github::find_runner(runners, instance.runner_name.as_deref()));
+        findings.extend(check_labels(
+            &id,
+            &runner::read_labels(&instance.dir),
+            record,
+            &host_os,
+            &host_arch,
+        ));
+        sections.push(Section {
+            title: id.to_string(),
+            findings,
+        });
+    }
+    sections
+}
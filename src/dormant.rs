@@ -0,0 +1,127 @@
+//! Repositories that can't run workflows anymore (archived, disabled, or with Actions
+//! turned off). Refreshes stop polling their runs and suggest removing their runners.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::github::{ActionsPermissions, GitHubClient, Repository, RunnerScope};
+
+/// How often a repository is checked again, in case it was unarchived or re-enabled
+pub const RECHECK_INTERVAL: Duration = Duration::from_hours(6);
+
+/// Why a repository can't run workflows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Archived,
+    Disabled,
+    ActionsDisabled,
+}
+
+impl Reason {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Reason::Archived => "archived",
+            Reason::Disabled => "disabled",
+            Reason::ActionsDisabled => "has Actions disabled",
+        }
+    }
+
+    /// Short label for the dashboard
+    pub fn label(self) -> &'static str {
+        match self {
+            Reason::Archived => "archived",
+            Reason::Disabled => "disabled",
+            Reason::ActionsDisabled => "actions off",
+        }
+    }
+}
+
+/// What a check of a repository found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    Active,
+    Dormant(Reason),
+    /// The check failed; the previous result stands
+    Unknown,
+}
+
+/// Why `repo` can't run workflows, if it can't. `actions` is None when its Actions
+/// settings couldn't be read (they need admin access).
+pub fn reason(repo: &Repository, actions: Option<&ActionsPermissions>) -> Option<Reason> {
+    if repo.archived {
+        Some(Reason::Archived)
+    } else if repo.disabled {
+        Some(Reason::Disabled)
+    } else if actions.is_some_and(|a| !a.enabled) {
+        Some(Reason::ActionsDisabled)
+    } else {
+        None
+    }
+}
+
+/// Check `scope` on GitHub. Organizations are always active.
+pub async fn check(client: &GitHubClient, scope: &RunnerScope) -> Check {
+    let RunnerScope::Repository { owner, repo } = scope else {
+        return Check::Active;
+    };
+    let Ok(repository) = client.get_repo(owner, repo).await else {
+        return Check::Unknown;
+    };
+    let actions = client.get_actions_permissions(owner, repo).await.ok();
+    match reason(&repository, actions.as_ref()) {
+        Some(reason) => Check::Dormant(reason),
+        None => Check::Active,
+    }
+}
+
+/// What to tell the user about a dormant repository
+pub fn suggestion(scope: &RunnerScope, reason: Reason) -> String {
+    format!(
+        "{scope} is {}: its workflow runs are no longer polled. Remove its runner with \
+         `runner-mgr remove {}`",
+        reason.describe(),
+        scope.to_display()
+    )
+}
+
+/// The last check of each repository
+#[derive(Debug, Default)]
+pub struct DormantScopes {
+    checks: HashMap<RunnerScope, (Option<Reason>, Instant)>,
+}
+
+impl DormantScopes {
+    /// Whether `scope` is a repository that wasn't checked within `RECHECK_INTERVAL`
+    pub fn due(&self, scope: &RunnerScope, now: Instant) -> bool {
+        matches!(scope, RunnerScope::Repository { .. })
+            && self
+                .checks
+                .get(scope)
+                .is_none_or(|(_, at)| now.saturating_duration_since(*at) >= RECHECK_INTERVAL)
+    }
+
+    /// Record a check of `scope`. Returns the reason when the scope just became dormant.
+    pub fn record(&mut self, scope: &RunnerScope, check: Check, now: Instant) -> Option<Reason> {
+        let previous = self.reason(scope);
+        let found = match check {
+            Check::Active => None,
+            Check::Dormant(reason) => Some(reason),
+            Check::Unknown => previous,
+        };
+        self.checks.insert(scope.clone(), (found, now));
+        found.filter(|_| previous.is_none())
+    }
+
+    /// Why `scope`'s runs aren't polled, if they aren't
+    pub fn reason(&self, scope: &RunnerScope) -> Option<Reason> {
+        self.checks.get(scope).and_then(|(reason, _)| *reason)
+    }
+
+    /// Dormant scopes and why
+    pub fn dormant(&self) -> Vec<(&RunnerScope, Reason)> {
+        self.checks
+            .iter()
+            .filter_map(|(scope, (reason, _))| Some((scope, (*reason)?)))
+            .collect()
+    }
+}
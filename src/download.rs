@@ -0,0 +1,163 @@
+//! File downloads over HTTP: progress bar, resuming interrupted downloads and SHA-256
+//! verification
+
+use anyhow::{Context, Result};
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::{Client, StatusCode};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Width of the progress bar, in characters
+const BAR_WIDTH: usize = 30;
+
+/// Hex-encoded SHA-256 of a file
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{b:02x}");
+        hex
+    }))
+}
+
+/// Check `path` against an expected hex SHA-256 (case-insensitive)
+pub fn verify_sha256(path: &Path, expected: &str) -> Result<()> {
+    let actual = sha256_file(path)?;
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        anyhow::bail!(
+            "Checksum mismatch for {}: expected {expected}, got {actual}",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// First byte of a `Content-Range: bytes <start>-<end>/<total>` header
+pub fn content_range_start(header: &str) -> Option<u64> {
+    header
+        .trim()
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// One line of download progress, e.g. `[#######-------]  48%  12.0/25.0 MB`. Without
+/// a known size only the amount downloaded is shown.
+#[allow(clippy::cast_precision_loss)]
+pub fn progress_line(done: u64, total: Option<u64>) -> String {
+    let mb = |bytes: u64| bytes as f64 / 1_000_000.0;
+    match total.filter(|&t| t > 0) {
+        Some(total) => {
+            let done = done.min(total);
+            let filled = usize::try_from(done * BAR_WIDTH as u64 / total).unwrap_or(BAR_WIDTH);
+            format!(
+                "[{}{}] {:>3}%  {:.1}/{:.1} MB",
+                "#".repeat(filled),
+                "-".repeat(BAR_WIDTH - filled),
+                done * 100 / total,
+                mb(done),
+                mb(total)
+            )
+        }
+        None => format!("{:.1} MB", mb(done)),
+    }
+}
+
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Download `url` to `dest`, verifying it against `sha256` when given.
+///
+/// Data is written to `<dest>.part` and renamed once complete and verified, so an
+/// interrupted download is never mistaken for a finished one. A leftover `.part` file
+/// is resumed with a `Range` request; servers that ignore the range send the whole
+/// file again. A download that fails verification is deleted.
+pub async fn download(client: &Client, url: &str, dest: &Path, sha256: Option<&str>) -> Result<()> {
+    let partial = partial_path(dest);
+    let offset = fs::metadata(&partial).map_or(0, |m| m.len());
+
+    let mut request = client.get(url).header("User-Agent", "runner-mgr");
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={offset}-"));
+    }
+    let mut resp = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to download {url}"))?;
+
+    let resumed = resp.status() == StatusCode::PARTIAL_CONTENT
+        && resp
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(content_range_start)
+            == Some(offset);
+    if resp.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file is already complete (or longer than the file): start over
+        let _ = fs::remove_file(&partial);
+        return Box::pin(download(client, url, dest, sha256)).await;
+    }
+    if !resp.status().is_success() {
+        anyhow::bail!("Download failed: {} returned {}", url, resp.status());
+    }
+
+    let mut file = if resumed {
+        println!("Resuming download at {}", progress_line(offset, None));
+        OpenOptions::new().append(true).open(&partial)
+    } else {
+        File::create(&partial)
+    }
+    .with_context(|| format!("Failed to write {}", partial.display()))?;
+    let mut done = if resumed { offset } else { 0 };
+    let total = resp.content_length().map(|len| len + done);
+
+    let show_progress = io::stderr().is_terminal();
+    let mut last_percent = None;
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .with_context(|| format!("Download of {url} was interrupted"))?
+    {
+        file.write_all(&chunk)?;
+        done += chunk.len() as u64;
+        // Redraw only when the percentage changes
+        let percent = total.map(|t| done * 100 / t.max(1));
+        if show_progress && (percent.is_none() || percent != last_percent) {
+            eprint!("\r{}", progress_line(done, total));
+            last_percent = percent;
+        }
+    }
+    file.flush()?;
+    drop(file);
+    if show_progress {
+        eprintln!();
+    }
+
+    if let Some(expected) = sha256 {
+        if let Err(e) = verify_sha256(&partial, expected) {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+        println!("Checksum verified (SHA-256 {expected})");
+    }
+    fs::rename(&partial, dest)
+        .with_context(|| format!("Failed to move download to {}", dest.display()))
+}
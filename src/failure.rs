@@ -0,0 +1,84 @@
+//! Failure categories with their own process exit codes, so wrapper scripts can tell
+//! "that runner doesn't exist" from "GitHub is down" instead of always getting 1
+
+use std::fmt;
+
+use crate::github::ApiError;
+
+/// Any other error
+pub const EXIT_FAILURE: i32 = 1;
+/// No config file: runner-mgr (or the selected account) isn't initialized
+pub const EXIT_CONFIG_MISSING: i32 = 3;
+/// GitHub rejected the token: invalid, expired, or missing a permission
+pub const EXIT_AUTH: i32 = 4;
+/// GitHub couldn't be reached or returned an error
+pub const EXIT_API: i32 = 5;
+/// systemctl, launchctl or the Windows service manager failed
+pub const EXIT_SERVICE_CONTROL: i32 = 6;
+/// The target names no configured runner
+pub const EXIT_RUNNER_NOT_FOUND: i32 = 7;
+/// The account selected with `--account` (or its variable) has an invalid name
+pub const EXIT_INVALID_ACCOUNT: i32 = 8;
+
+/// A failure `exit_code` can tell apart. Raise it with `bail!` or attach it as the
+/// context of a lower-level error; either way it's found anywhere in the chain.
+/// GitHub errors (`ApiError`, connection failures) are categorized without one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    ConfigMissing(String),
+    Auth(String),
+    Api(String),
+    ServiceControl(String),
+    RunnerNotFound(String),
+    InvalidAccount(String),
+}
+
+impl Failure {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::ConfigMissing(_) => EXIT_CONFIG_MISSING,
+            Self::Auth(_) => EXIT_AUTH,
+            Self::Api(_) => EXIT_API,
+            Self::ServiceControl(_) => EXIT_SERVICE_CONTROL,
+            Self::RunnerNotFound(_) => EXIT_RUNNER_NOT_FOUND,
+            Self::InvalidAccount(_) => EXIT_INVALID_ACCOUNT,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConfigMissing(message)
+            | Self::Auth(message)
+            | Self::Api(message)
+            | Self::ServiceControl(message)
+            | Self::RunnerNotFound(message)
+            | Self::InvalidAccount(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// The process exit code for `e`: that of the outermost `Failure` in its chain, else
+/// `EXIT_AUTH` or `EXIT_API` for errors from GitHub, else `EXIT_FAILURE`
+pub fn exit_code(e: &anyhow::Error) -> i32 {
+    if let Some(failure) = find::<Failure>(e) {
+        return failure.exit_code();
+    }
+    if let Some(api) = find::<ApiError>(e) {
+        let rejected = api.status == 401 || (api.status == 403 && !api.is_rate_limited());
+        return if rejected { EXIT_AUTH } else { EXIT_API };
+    }
+    if find::<reqwest::Error>(e).is_some() {
+        return EXIT_API;
+    }
+    EXIT_FAILURE
+}
+
+/// `T` as the error or a context of `e`, or as the source of one of them
+fn find<T: std::error::Error + Send + Sync + 'static>(e: &anyhow::Error) -> Option<&T> {
+    e.downcast_ref::<T>()
+        .or_else(|| e.chain().find_map(|cause| cause.downcast_ref::<T>()))
+}
@@ -0,0 +1,192 @@
+//! Following runner logs as they're written: `journalctl -f` or the newest `_diag` log
+
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the newest `_diag` log is checked for new lines
+const DIAG_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a followed command gets to exit after `SIGTERM` before it is killed
+const TERMINATE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// The newest `_diag/Runner_*.log` in `dir`
+pub fn newest_diag_log(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir.join("_diag"))
+        .ok()?
+        .flatten()
+        .filter(|e| {
+            let path = e.path();
+            e.file_name().to_string_lossy().starts_with("Runner_")
+                && path.extension().is_some_and(|ext| ext == "log")
+        })
+        .max_by_key(|e| e.metadata().ok().and_then(|m| m.modified().ok()))
+        .map(|e| e.path())
+}
+
+/// New lines of a runner's newest `_diag` log, across the runner starting a new log
+#[derive(Debug)]
+pub struct DiagTail {
+    dir: PathBuf,
+    /// Lines of an existing log returned by the first poll
+    backlog: usize,
+    file: Option<PathBuf>,
+    /// Bytes of `file` already read
+    offset: u64,
+    /// Text after the last newline, completed by a later poll
+    partial: String,
+}
+
+impl DiagTail {
+    /// Follow the logs of the runner in `dir`, starting with the last `backlog` lines
+    /// of its newest log
+    pub fn new(dir: impl Into<PathBuf>, backlog: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            backlog,
+            file: None,
+            offset: 0,
+            partial: String::new(),
+        }
+    }
+
+    /// Complete lines written since the last poll. A new log (the runner restarted) is
+    /// read from its start; a log truncated in place is read again from its start.
+    pub fn poll(&mut self) -> Result<Vec<String>> {
+        let Some(newest) = newest_diag_log(&self.dir) else {
+            return Ok(Vec::new());
+        };
+        let first = self.file.is_none();
+        if self.file.as_ref() != Some(&newest) {
+            self.file = Some(newest.clone());
+            self.offset = 0;
+            self.partial.clear();
+        }
+
+        let mut file =
+            File::open(&newest).with_context(|| format!("Failed to open {}", newest.display()))?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut bytes)?;
+        self.offset += bytes.len() as u64;
+
+        self.partial.push_str(&String::from_utf8_lossy(&bytes));
+        let Some(end) = self.partial.rfind('\n') else {
+            return Ok(Vec::new());
+        };
+        let complete: String = self.partial.drain(..=end).collect();
+        let mut lines: Vec<String> = complete
+            .lines()
+            .map(|l| l.trim_end_matches('\r').to_string())
+            .collect();
+        if first {
+            lines.drain(..lines.len().saturating_sub(self.backlog));
+        }
+        Ok(lines)
+    }
+}
+
+/// Lines of a log followed in the background, until dropped
+pub struct LogFollow {
+    receiver: Receiver<String>,
+    child: Option<Child>,
+    stop: Arc<AtomicBool>,
+}
+
+impl LogFollow {
+    /// Follow the output of a command that keeps running, e.g. `journalctl -f`
+    pub fn command(mut command: Command) -> Result<Self> {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to start log command")?;
+        let stdout = child.stdout.take().context("Log command has no output")?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            receiver,
+            child: Some(child),
+            stop: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Follow a runner's `_diag` logs (see `DiagTail`)
+    pub fn diag(dir: &Path, backlog: usize) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut tail = DiagTail::new(dir, backlog);
+        let stopped = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                if !send_all(&sender, tail.poll()) {
+                    break;
+                }
+                thread::sleep(DIAG_POLL_INTERVAL);
+            }
+        });
+        Self {
+            receiver,
+            child: None,
+            stop,
+        }
+    }
+
+    /// Lines that arrived since the last call
+    pub fn lines(&self) -> Vec<String> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+/// Send polled lines (or the error, as a line); false once nobody is listening
+fn send_all(sender: &Sender<String>, lines: Result<Vec<String>>) -> bool {
+    match lines {
+        Ok(lines) => lines.into_iter().all(|line| sender.send(line).is_ok()),
+        Err(e) => sender.send(format!("Error reading log: {e:#}")).is_ok(),
+    }
+}
+
+impl Drop for LogFollow {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        // SIGTERM first: sudo passes it on to journalctl, which SIGKILL wouldn't
+        let _ = Command::new("kill")
+            .arg(child.id().to_string())
+            .stderr(Stdio::null())
+            .status();
+        let deadline = Instant::now() + TERMINATE_TIMEOUT;
+        while Instant::now() < deadline {
+            if !matches!(child.try_wait(), Ok(None)) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
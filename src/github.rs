@@ -1,27 +1,117 @@
 use anyhow::{Context, Result};
-use reqwest::Client;
+use reqwest::{Client, Method, NoProxy, Proxy, RequestBuilder, Response};
 use serde::Deserialize;
 use std::fmt;
+use std::fmt::Write as _;
 use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-/// Represents either a repository or organization scope for runner management
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::config::Config;
+use crate::recording;
+use crate::runner;
+use crate::scheduler;
+
+/// REST API of github.com
+pub const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// Web UI of github.com
+pub const DEFAULT_WEB_URL: &str = "https://github.com";
+
+/// Web UI root for an API root: GitHub Enterprise Server serves the API under `/api/v3`
+pub fn web_url_for_api(api_url: &str) -> String {
+    let api_url = api_url.trim_end_matches('/');
+    if api_url == DEFAULT_API_URL {
+        return DEFAULT_WEB_URL.to_string();
+    }
+    api_url
+        .strip_suffix("/api/v3")
+        .unwrap_or(api_url)
+        .to_string()
+}
+
+static DEBUG_HTTP: AtomicBool = AtomicBool::new(false);
+
+/// Log every API call (`--debug-http`)
+pub fn set_debug_http(enabled: bool) {
+    DEBUG_HTTP.store(enabled, Ordering::SeqCst);
+}
+
+/// Rate-limit response headers included in `--debug-http` lines
+const RATE_LIMIT_HEADERS: &[&str] = &[
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-used",
+    "x-ratelimit-reset",
+    "x-ratelimit-resource",
+];
+
+/// One `--debug-http` line. `status` is the response status or the transport error;
+/// any occurrence of `token` is redacted.
+pub fn http_log_line(
+    method: &str,
+    url: &str,
+    status: &str,
+    rate_limit: &[(&str, String)],
+    elapsed: Duration,
+    token: &str,
+) -> String {
+    let mut line = format!(
+        "[http] {method} {url} -> {status} in {}ms",
+        elapsed.as_millis()
+    );
+    if !rate_limit.is_empty() {
+        let headers: Vec<String> = rate_limit
+            .iter()
+            .map(|(name, value)| format!("{}={value}", name.trim_start_matches("x-ratelimit-")))
+            .collect();
+        line = format!("{line} [ratelimit {}]", headers.join(" "));
+    }
+    if token.is_empty() {
+        line
+    } else {
+        line.replace(token, "[REDACTED]")
+    }
+}
+
+/// Represents either a repository or organization scope for runner management.
+/// Scopes compare and hash case-insensitively, like GitHub's owner and repo names; the
+/// case they were written in is kept for display.
+#[derive(Debug, Clone, Eq)]
 pub enum RunnerScope {
     Repository { owner: String, repo: String },
     Organization { org: String },
 }
 
+impl PartialEq for RunnerScope {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                RunnerScope::Repository { owner, repo },
+                RunnerScope::Repository {
+                    owner: other_owner,
+                    repo: other_repo,
+                },
+            ) => owner.eq_ignore_ascii_case(other_owner) && repo.eq_ignore_ascii_case(other_repo),
+            (RunnerScope::Organization { org }, RunnerScope::Organization { org: other_org }) => {
+                org.eq_ignore_ascii_case(other_org)
+            }
+            _ => false,
+        }
+    }
+}
+
 impl Hash for RunnerScope {
     fn hash<H: Hasher>(&self, state: &mut H) {
         match self {
             RunnerScope::Repository { owner, repo } => {
                 "repo".hash(state);
-                owner.hash(state);
-                repo.hash(state);
+                owner.to_ascii_lowercase().hash(state);
+                repo.to_ascii_lowercase().hash(state);
             }
             RunnerScope::Organization { org } => {
                 "org".hash(state);
-                org.hash(state);
+                org.to_ascii_lowercase().hash(state);
             }
         }
     }
@@ -57,11 +147,24 @@ impl RunnerScope {
         }
     }
 
-    /// Convert to a safe directory name (no slashes or colons)
+    /// Convert to a safe directory name: `owner__repo` or `org__name`. Characters other
+    /// than letters, digits, `.`, `-` and `_` are percent-encoded, as are underscores in
+    /// the owner (so the first `__` always separates owner and repo) and the first letter
+    /// of an owner named `org` in any case (so it can't be mistaken for an organization,
+    /// as directory names are matched case-insensitively).
     pub fn to_dir_name(&self) -> String {
         match self {
-            RunnerScope::Repository { owner, repo } => format!("{owner}__{repo}"),
-            RunnerScope::Organization { org } => format!("org__{org}"),
+            RunnerScope::Repository { owner, repo } => {
+                let owner = if owner.eq_ignore_ascii_case("org") {
+                    format!("%{:02X}{}", owner.as_bytes()[0], &owner[1..])
+                } else {
+                    escape_dir_component(owner, true)
+                };
+                format!("{owner}__{}", escape_dir_component(repo, false))
+            }
+            RunnerScope::Organization { org } => {
+                format!("org__{}", escape_dir_component(org, false))
+            }
         }
     }
 
@@ -75,19 +178,48 @@ impl RunnerScope {
 
     /// Get the GitHub URL for this scope
     pub fn github_url(&self) -> String {
+        self.web_url(DEFAULT_WEB_URL)
+    }
+
+    /// URL of this scope on the GitHub instance at `web_url` (github.com or a GHES host)
+    pub fn web_url(&self, web_url: &str) -> String {
+        match self {
+            RunnerScope::Repository { owner, repo } => format!("{web_url}/{owner}/{repo}"),
+            RunnerScope::Organization { org } => format!("{web_url}/{org}"),
+        }
+    }
+
+    /// Settings page listing the self-hosted runners for this scope
+    pub fn runners_settings_url(&self) -> String {
+        self.settings_url(DEFAULT_WEB_URL)
+    }
+
+    /// Runner settings page on the GitHub instance at `web_url`
+    pub fn settings_url(&self, web_url: &str) -> String {
         match self {
             RunnerScope::Repository { owner, repo } => {
-                format!("https://github.com/{owner}/{repo}")
+                format!("{web_url}/{owner}/{repo}/settings/actions/runners")
+            }
+            RunnerScope::Organization { org } => {
+                format!("{web_url}/organizations/{org}/settings/actions/runners")
             }
-            RunnerScope::Organization { org } => format!("https://github.com/{org}"),
         }
     }
 
     /// Parse a `RunnerScope` from a GitHub URL
     pub fn from_github_url(url: &str) -> Result<Self> {
+        Self::from_web_url(url, DEFAULT_WEB_URL)
+    }
+
+    /// Parse a `RunnerScope` from a URL on the GitHub instance at `web_url`
+    pub fn from_web_url(url: &str, web_url: &str) -> Result<Self> {
+        let host = web_url
+            .trim_end_matches('/')
+            .trim_start_matches("https://")
+            .trim_start_matches("http://");
         let path = url
-            .strip_prefix("https://github.com/")
-            .or_else(|| url.strip_prefix("http://github.com/"))
+            .strip_prefix(&format!("https://{host}/"))
+            .or_else(|| url.strip_prefix(&format!("http://{host}/")))
             .ok_or_else(|| anyhow::anyhow!("Unexpected GitHub URL format: {url}"))?;
 
         let path = path.trim_end_matches('/');
@@ -111,29 +243,22 @@ impl RunnerScope {
         }
     }
 
-    /// Parse a directory name back into a `RunnerScope`
+    /// Parse a directory name back into a `RunnerScope`. Only names `to_dir_name`
+    /// produces are accepted; older names that don't round-trip (e.g. an owner with
+    /// `_` in it) are left for `runner-mgr migrate` to relink.
     pub fn from_dir_name(dir_name: &str) -> Option<Self> {
-        if let Some(org_name) = dir_name.strip_prefix("org__") {
-            if !org_name.is_empty() {
-                return Some(RunnerScope::Organization {
-                    org: org_name.to_string(),
-                });
+        let scope = if let Some(org_name) = dir_name.strip_prefix("org__") {
+            RunnerScope::Organization {
+                org: unescape_dir_component(org_name)?,
             }
-        }
-
-        // Try to parse as owner__repo
-        if let Some(idx) = dir_name.find("__") {
-            let owner = &dir_name[..idx];
-            let repo = &dir_name[idx + 2..];
-            if !owner.is_empty() && !repo.is_empty() && owner != "org" {
-                return Some(RunnerScope::Repository {
-                    owner: owner.to_string(),
-                    repo: repo.to_string(),
-                });
+        } else {
+            let (owner, repo) = dir_name.split_once("__")?;
+            RunnerScope::Repository {
+                owner: unescape_dir_component(owner)?,
+                repo: unescape_dir_component(repo)?,
             }
-        }
-
-        None
+        };
+        (scope.to_dir_name() == dir_name).then_some(scope)
     }
 
     /// Check if this scope supports workflow runs (repos only)
@@ -150,16 +275,307 @@ impl RunnerScope {
     }
 }
 
+/// Percent-encode a scope component for a directory name
+fn escape_dir_component(component: &str, escape_underscore: bool) -> String {
+    let mut escaped = String::with_capacity(component.len());
+    for byte in component.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || matches!(byte, b'.' | b'-')
+            || (byte == b'_' && !escape_underscore)
+        {
+            escaped.push(char::from(byte));
+        } else {
+            let _ = write!(escaped, "%{byte:02X}");
+        }
+    }
+    escaped
+}
+
+/// Decode a percent-encoded directory name component; None if it is empty or malformed
+fn unescape_dir_component(component: &str) -> Option<String> {
+    if component.is_empty() {
+        return None;
+    }
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = component.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
 impl fmt::Display for RunnerScope {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.to_display())
     }
 }
 
+/// A non-success response from the GitHub API, with the details needed to explain it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    /// What was being attempted, e.g. "list runners"
+    pub action: String,
+    pub status: u16,
+    /// `x-ratelimit-remaining` header
+    pub rate_limit_remaining: Option<u64>,
+    /// `x-ratelimit-reset` header (Unix timestamp)
+    pub rate_limit_reset: Option<i64>,
+    /// `retry-after` header (seconds), sent with secondary rate limits
+    pub retry_after: Option<u64>,
+    /// `message` of the JSON body, when it was read
+    pub message: Option<String>,
+    /// `x-accepted-github-permissions` header, e.g. `administration=write`, or the
+    /// permission the endpoint is documented to need
+    pub accepted_permissions: Option<String>,
+    /// `x-accepted-oauth-scopes` header: classic PAT scopes the endpoint accepts
+    pub accepted_scopes: Option<String>,
+    /// `x-oauth-scopes` header: the scopes of a classic PAT (absent for fine-grained ones)
+    pub token_scopes: Option<String>,
+    /// The repository or organization the request was about
+    pub scope: Option<RunnerScope>,
+}
+
+/// Wait after a 429 that says nothing about when to retry (GitHub asks for a minute)
+const DEFAULT_RATE_LIMIT_PAUSE: Duration = Duration::from_mins(1);
+
+impl ApiError {
+    fn from_response(action: &str, resp: &reqwest::Response) -> Self {
+        let header = |name: &str| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            action: action.to_string(),
+            status: resp.status().as_u16(),
+            rate_limit_remaining: header("x-ratelimit-remaining").and_then(|v| v.parse().ok()),
+            rate_limit_reset: header("x-ratelimit-reset").and_then(|v| v.parse().ok()),
+            retry_after: header("retry-after").and_then(|v| v.parse().ok()),
+            message: None,
+            accepted_permissions: header("x-accepted-github-permissions"),
+            accepted_scopes: header("x-accepted-oauth-scopes"),
+            token_scopes: header("x-oauth-scopes"),
+            scope: None,
+        }
+    }
+
+    /// Like `from_response`, for runner administration endpoints of `scope`: also reads
+    /// the body, and when GitHub doesn't say which permission was missing assumes the
+    /// runner permission at `level` ("read" or "write")
+    async fn from_runner_response(
+        action: &str,
+        resp: reqwest::Response,
+        scope: &RunnerScope,
+        level: &str,
+    ) -> Self {
+        let mut error = Self::from_response(action, &resp);
+        error.message = resp
+            .json::<serde_json::Value>()
+            .await
+            .ok()
+            .and_then(|body| body["message"].as_str().map(str::to_string));
+        if error.accepted_permissions.is_none() {
+            error.accepted_permissions = Some(runner_permission(scope, level));
+        }
+        error.scope = Some(scope.clone());
+        error
+    }
+
+    /// Which permission a 403 on a repository or organization was missing, e.g.
+    /// "fine-grained token needs 'Administration: write' on repo owner/repo"
+    pub fn permission_hint(&self) -> Option<String> {
+        if self.status != 403 || self.is_rate_limited() {
+            return None;
+        }
+        let scope = self.scope.as_ref()?;
+        let target = match scope {
+            RunnerScope::Repository { owner, repo } => format!("repo {owner}/{repo}"),
+            RunnerScope::Organization { org } => format!("organization {org}"),
+        };
+        let fine_grained = self.token_scopes.is_none()
+            || self
+                .message
+                .as_deref()
+                .is_some_and(|m| m.contains("personal access token"));
+        if !fine_grained {
+            let needed = self
+                .accepted_scopes
+                .as_deref()
+                .filter(|s| !s.trim().is_empty())
+                .map_or_else(
+                    || match scope {
+                        RunnerScope::Repository { .. } => "'repo'".to_string(),
+                        RunnerScope::Organization { .. } => "'admin:org'".to_string(),
+                    },
+                    |s| {
+                        s.split(',')
+                            .map(|s| format!("'{}'", s.trim()))
+                            .collect::<Vec<_>>()
+                            .join(" or ")
+                    },
+                );
+            let has = self
+                .token_scopes
+                .as_deref()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or("none");
+            return Some(format!(
+                "classic token needs the {needed} scope for {target} (it has: {has})"
+            ));
+        }
+        let needed = describe_permissions(self.accepted_permissions.as_deref()?);
+        Some(format!("fine-grained token needs {needed} on {target}"))
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == 429
+            || (self.status == 403 && self.rate_limit_remaining == Some(0))
+            || self.is_secondary_rate_limit()
+    }
+
+    /// GitHub's secondary (abuse) rate limit: 403 or 429 with `retry-after`
+    pub fn is_secondary_rate_limit(&self) -> bool {
+        matches!(self.status, 403 | 429) && self.retry_after.is_some()
+    }
+
+    /// How long every API call should pause, for rate limits: `retry-after` if given,
+    /// else until the primary limit resets (`now` is a Unix timestamp)
+    pub fn pause(&self, now: i64) -> Option<Duration> {
+        if !self.is_rate_limited() {
+            return None;
+        }
+        if let Some(secs) = self.retry_after {
+            return Some(Duration::from_secs(secs));
+        }
+        match self.rate_limit_reset {
+            Some(reset) if self.rate_limit_remaining == Some(0) => {
+                Some(Duration::from_secs(u64::try_from(reset - now).unwrap_or(0)))
+            }
+            _ => Some(DEFAULT_RATE_LIMIT_PAUSE),
+        }
+    }
+
+    /// What the user can do about this error, on the GitHub instance at `web_url`
+    pub fn suggestion(&self, web_url: &str) -> String {
+        if let Some(hint) = self.permission_hint() {
+            return format!("The {hint}. Edit the token at {web_url}/settings/tokens.");
+        }
+        match self.status {
+            _ if self.is_secondary_rate_limit() => format!(
+                "GitHub's secondary rate limit was hit (too many requests in a short time). \
+                 API calls pause for {}s; manage fewer scopes with this token if it keeps \
+                 happening.",
+                self.retry_after.unwrap_or_default()
+            ),
+            _ if self.is_rate_limited() => {
+                let reset = self
+                    .rate_limit_reset
+                    .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                    .map(|t| {
+                        format!(
+                            " (resets at {})",
+                            t.with_timezone(&chrono::Local).format("%H:%M:%S")
+                        )
+                    })
+                    .unwrap_or_default();
+                format!(
+                    "API rate limit exhausted{reset}. Wait for the reset or manage fewer scopes \
+                     with this token."
+                )
+            }
+            401 => "The token is invalid or expired. Create a new PAT and update github_pat \
+                    in config.toml (or run `runner-mgr init` again)."
+                .to_string(),
+            403 => "The token lacks permission. Classic PATs need the `repo` scope \
+                    (`admin:org` for organization runners); fine-grained PATs need \
+                    Administration read access."
+                .to_string(),
+            404 => "Not found: check the repository or organization name, and that the token \
+                    can see it."
+                .to_string(),
+            500..=599 => "GitHub is having problems (see https://www.githubstatus.com). \
+                          The dashboard keeps retrying."
+                .to_string(),
+            _ => "Unexpected response from GitHub; run with --verbose for details.".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = reqwest::StatusCode::from_u16(self.status)
+            .ok()
+            .and_then(|s| s.canonical_reason())
+            .unwrap_or("");
+        write!(f, "Failed to {}: {} {reason}", self.action, self.status)?;
+        if let Some(hint) = self.permission_hint() {
+            write!(f, " ({hint})")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// The fine-grained permission that manages `scope`'s runners, at `level`
+fn runner_permission(scope: &RunnerScope, level: &str) -> String {
+    match scope {
+        RunnerScope::Repository { .. } => format!("administration={level}"),
+        RunnerScope::Organization { .. } => format!("organization_self_hosted_runners={level}"),
+    }
+}
+
+/// Describe an `x-accepted-github-permissions` value as GitHub's token settings name the
+/// permissions: `administration=write` is 'Administration: write'. Sets separated by `;`
+/// are alternatives; permissions separated by `,` are all needed.
+pub fn describe_permissions(accepted: &str) -> String {
+    accepted
+        .split(';')
+        .filter(|set| !set.trim().is_empty())
+        .map(|set| {
+            set.split(',')
+                .filter_map(|permission| {
+                    let (name, level) = permission.trim().split_once('=')?;
+                    Some(format!("'{}: {}'", permission_name(name), level.trim()))
+                })
+                .collect::<Vec<_>>()
+                .join(" and ")
+        })
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
+
+/// GitHub's display name for a permission key, e.g. `Self-hosted runners` for
+/// `organization_self_hosted_runners`
+fn permission_name(name: &str) -> String {
+    let name = name.trim();
+    let name = name.strip_prefix("organization_").unwrap_or(name);
+    if name == "self_hosted_runners" {
+        return "Self-hosted runners".to_string();
+    }
+    let words = name.replace('_', " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GitHubClient {
     client: Client,
     token: String,
+    /// REST API root, without a trailing slash
+    api_url: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -167,16 +583,77 @@ pub struct User {
     pub login: String,
 }
 
+/// The authenticated user and what GitHub reports about the token
+#[derive(Debug, Clone)]
+pub struct TokenInfo {
+    pub login: String,
+    /// Scopes of a classic PAT (`x-oauth-scopes`); None for fine-grained tokens, which
+    /// don't report them
+    pub scopes: Option<Vec<String>>,
+    /// When the token expires (`github-authentication-token-expiration`), if it does
+    pub expires: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Repository {
     pub full_name: String,
     pub private: bool,
     pub archived: bool,
+    /// Disabled by GitHub or an organization admin
+    #[serde(default)]
+    pub disabled: bool,
+    /// The authenticated user's permissions (present on authenticated requests)
+    #[serde(default)]
+    pub permissions: Option<RepoPermissions>,
+}
+
+/// A repository's Actions settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActionsPermissions {
+    /// Whether Actions is enabled for the repository
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepoSearchResults {
+    pub total_count: u64,
+    pub items: Vec<Repository>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Org {
+    login: String,
+}
+
+/// Build a repository search query matching `query` in repo names, restricted to
+/// repos owned by one of `owners` (the user and their organizations)
+pub fn repo_search_query(query: &str, owners: &[String]) -> String {
+    let mut terms = vec![format!("{} in:name", query.trim())];
+    for (i, owner) in owners.iter().enumerate() {
+        let qualifier = if i == 0 { "user" } else { "org" };
+        terms.push(format!("{qualifier}:{owner}"));
+    }
+    terms.join(" ")
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RepoPermissions {
+    #[serde(default)]
+    pub admin: bool,
+}
+
+impl Repository {
+    /// Whether the authenticated user can manage runners for this repository
+    pub fn is_admin(&self) -> bool {
+        self.permissions.as_ref().is_some_and(|p| p.admin)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct RegistrationToken {
     pub token: String,
+    #[serde(default)]
+    pub expires_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -193,6 +670,70 @@ pub struct Runner {
 #[derive(Debug, Clone, Deserialize)]
 pub struct RunnerLabel {
     pub name: String,
+    /// `read-only` for the labels GitHub assigns (self-hosted, OS, architecture),
+    /// `custom` for the rest
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+}
+
+/// A runner's labels, as returned after changing them
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunnerLabelList {
+    pub total_count: u64,
+    pub labels: Vec<RunnerLabel>,
+}
+
+impl RunnerLabel {
+    pub fn is_custom(&self) -> bool {
+        self.kind.as_deref() != Some("read-only")
+    }
+}
+
+/// A release of `actions/runner` with its package checksums
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunnerRelease {
+    pub tag_name: String,
+    /// Release notes, which list each package's SHA-256
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    /// `sha256:<hex>`, published for assets uploaded since mid-2025
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+impl RunnerRelease {
+    /// Expected SHA-256 of `package`: the asset digest, or else the hash the release
+    /// notes list between `<!-- BEGIN SHA <platform> -->` and `<!-- END SHA <platform> -->`
+    pub fn package_sha256(&self, package: &str) -> Option<String> {
+        let from_asset = self
+            .assets
+            .iter()
+            .find(|a| a.name == package)
+            .and_then(|a| a.digest.as_deref())
+            .and_then(|d| d.strip_prefix("sha256:"));
+        if let Some(hex) = from_asset {
+            return Some(hex.to_string());
+        }
+        // actions-runner-<platform>-<version>.<ext>, e.g. platform `linux-x64`
+        let version = self.tag_name.trim_start_matches('v');
+        let platform = package
+            .strip_prefix("actions-runner-")?
+            .split_once(&format!("-{version}."))?
+            .0;
+        let body = self.body.as_deref()?;
+        let start = format!("<!-- BEGIN SHA {platform} -->");
+        let end = format!("<!-- END SHA {platform} -->");
+        let after = &body[body.find(&start)? + start.len()..];
+        let hex = after[..after.find(&end)?].trim();
+        (hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| hex.to_string())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -201,6 +742,16 @@ pub struct RunnerList {
     pub runners: Vec<Runner>,
 }
 
+/// The runner record registered as `name`. Without a name, a scope's only runner is
+/// assumed to be ours; with several runners there is no way to tell.
+pub fn find_runner<'a>(runners: &'a [Runner], name: Option<&str>) -> Option<&'a Runner> {
+    match name {
+        Some(name) => runners.iter().find(|r| r.name == name),
+        None if runners.len() == 1 => runners.first(),
+        None => None,
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct WorkflowRun {
     pub id: u64,
@@ -213,69 +764,426 @@ pub struct WorkflowRun {
     pub html_url: String,
 }
 
+impl WorkflowRun {
+    /// Whether the run is waiting for a runner (or for approval) rather than running
+    pub fn is_queued(&self) -> bool {
+        matches!(
+            self.status.as_str(),
+            "queued" | "waiting" | "pending" | "requested"
+        )
+    }
+
+    /// Whether the run can still be cancelled (it hasn't completed yet)
+    pub fn can_cancel(&self) -> bool {
+        self.status != "completed"
+    }
+
+    /// Whether the run can be re-run: GitHub only re-runs completed runs
+    pub fn can_rerun(&self) -> bool {
+        self.status == "completed"
+    }
+}
+
+/// Single-use runner registration from the generate-jitconfig API
+#[derive(Debug, Clone, Deserialize)]
+pub struct JitConfig {
+    pub runner: Runner,
+    /// Passed to `run.sh --jitconfig`; it embeds the runner's credentials
+    pub encoded_jit_config: String,
+}
+
+/// The runner group every repository and organization has
+pub const DEFAULT_RUNNER_GROUP_ID: u64 = 1;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct WorkflowRunList {
     pub total_count: u64,
     pub workflow_runs: Vec<WorkflowRun>,
 }
 
+/// A job of a workflow run, with the runner it was assigned to
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkflowJob {
+    pub id: u64,
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    /// Labels the job requested in `runs-on`
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Runner that picked up the job (None while queued)
+    pub runner_name: Option<String>,
+    pub html_url: Option<String>,
+    #[serde(default)]
+    pub run_id: u64,
+    #[serde(default)]
+    pub workflow_name: Option<String>,
+    /// When the job was queued
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub started_at: Option<String>,
+    #[serde(default)]
+    pub completed_at: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<JobStep>,
+}
+
+/// A step of a workflow job
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobStep {
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    #[serde(default)]
+    pub number: u32,
+}
+
+impl WorkflowJob {
+    /// Whether the job ran (or asked to run) on a self-hosted runner. `runner_names`
+    /// are the runners registered for the job's scope.
+    pub fn is_self_hosted(&self, runner_names: &[String]) -> bool {
+        self.labels
+            .iter()
+            .any(|l| l.eq_ignore_ascii_case("self-hosted"))
+            || self
+                .runner_name
+                .as_ref()
+                .is_some_and(|name| runner_names.contains(name))
+    }
+
+    /// Seconds the job waited for a runner: from being queued to starting
+    pub fn queue_wait_seconds(&self) -> Option<i64> {
+        let parse = |t: Option<&str>| chrono::DateTime::parse_from_rfc3339(t?).ok();
+        let wait = parse(self.started_at.as_deref())? - parse(self.created_at.as_deref())?;
+        Some(wait.num_seconds().max(0))
+    }
+
+    /// Whether the job failed (a cancelled job didn't)
+    pub fn failed(&self) -> bool {
+        matches!(
+            self.conclusion.as_deref(),
+            Some("failure" | "timed_out" | "startup_failure")
+        )
+    }
+}
+
+/// An annotation of a check run: an error or warning a job reported, e.g. through a
+/// problem matcher. A job's check run has the job's ID.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckAnnotation {
+    #[serde(default)]
+    pub path: String,
+    pub start_line: Option<u64>,
+    /// `failure`, `warning` or `notice`
+    pub annotation_level: Option<String>,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl CheckAnnotation {
+    /// The runner's closing annotation for a failed step, which says nothing about why
+    fn is_exit_code(&self) -> bool {
+        self.message.starts_with("Process completed with exit code")
+    }
+}
+
+/// The first error line of a failed `job`, for triage without opening the browser: its
+/// first failure annotation (with the file and line it points at), else the generic exit
+/// code annotation, else the name of the step that failed
+pub fn first_error(job: &WorkflowJob, annotations: &[CheckAnnotation]) -> Option<String> {
+    let failures: Vec<&CheckAnnotation> = annotations
+        .iter()
+        .filter(|a| a.annotation_level.as_deref() == Some("failure"))
+        .collect();
+    let annotation = failures
+        .iter()
+        .find(|a| !a.is_exit_code())
+        .or_else(|| failures.first());
+    annotation
+        .and_then(|annotation| {
+            let line = annotation
+                .message
+                .lines()
+                .map(str::trim)
+                .find(|l| !l.is_empty())?;
+            // Annotations without a file point at the workflow
+            Some(match annotation.start_line {
+                Some(n) if !annotation.path.is_empty() && annotation.path != ".github" => {
+                    format!("{}:{n}: {line}", annotation.path)
+                }
+                _ => line.to_string(),
+            })
+        })
+        .or_else(|| {
+            job.steps
+                .iter()
+                .find(|step| step.conclusion.as_deref() == Some("failure"))
+                .map(|step| format!("Step '{}' failed", step.name))
+        })
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkflowJobList {
+    pub total_count: u64,
+    pub jobs: Vec<WorkflowJob>,
+}
+
+/// HTTP client for API requests and downloads. Without `proxy_url` reqwest uses the
+/// proxy from `HTTPS_PROXY`/`HTTP_PROXY`; with it, every request goes through
+/// `proxy_url` except for the hosts in `NO_PROXY`.
+pub fn http_client(proxy_url: Option<&str>) -> Result<Client> {
+    let Some(url) = proxy_url else {
+        return Ok(Client::new());
+    };
+    let proxy = Proxy::all(url)
+        .with_context(|| format!("Invalid proxy_url '{url}'"))?
+        .no_proxy(NoProxy::from_env());
+    Client::builder()
+        .proxy(proxy)
+        .build()
+        .context("Failed to create the HTTP client")
+}
+
+/// Pass the quota in `resp`'s rate-limit headers on to the scheduler
+fn record_rate_limit(resp: &Response) {
+    let header = |name: &str| resp.headers().get(name).and_then(|v| v.to_str().ok());
+    scheduler::record_rate_limit(
+        header("x-ratelimit-resource"),
+        header("x-ratelimit-limit"),
+        header("x-ratelimit-remaining"),
+        header("x-ratelimit-reset"),
+    );
+}
+
 impl GitHubClient {
     pub fn new(token: &str) -> Self {
+        Self::with_api_url(token, DEFAULT_API_URL)
+    }
+
+    /// Client for the API at `api_url`, e.g. `https://ghe.example.com/api/v3` for GitHub
+    /// Enterprise Server
+    pub fn with_api_url(token: &str, api_url: &str) -> Self {
+        Self::with_http(Client::new(), token, api_url)
+    }
+
+    /// Client for the API at `api_url` sending its requests through `http` (see
+    /// `http_client`)
+    pub fn with_http(http: Client, token: &str, api_url: &str) -> Self {
         Self {
-            client: Client::new(),
+            client: http,
             token: token.to_string(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Client for the GitHub instance in the config
+    pub fn for_config(config: &Config) -> Self {
+        scheduler::configure(config.request_limits());
+        // `proxy_url` was checked when the config was loaded
+        let http = http_client(config.proxy_url.as_deref()).unwrap_or_default();
+        Self::with_http(http, &config.github_pat, &config.api_url())
+    }
+
+    /// The underlying HTTP client, for downloads outside the API
+    pub fn http(&self) -> &Client {
+        &self.client
+    }
+
+    /// Send a request, or answer it from the recording being replayed; GET responses
+    /// are saved to the recording being made (see `recording`)
+    async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let method = request.method().to_string();
+        let path = self.recording_path(request.url());
+        if let Some(resp) = recording::replay(&method, &path) {
+            record_rate_limit(&resp);
+            return Ok(resp);
+        }
+        let resp = self
+            .send_with_retries(RequestBuilder::from_parts(client, request))
+            .await?;
+        recording::record(&method, &path, resp).await
+    }
+
+    /// A request URL without the API root, so a recording replays against any `api_url`
+    fn recording_path(&self, url: &reqwest::Url) -> String {
+        let url = url.as_str();
+        url.strip_prefix(self.api_url.as_str())
+            .or_else(|| url.strip_prefix(DEFAULT_API_URL))
+            .unwrap_or(url)
+            .to_string()
+    }
+
+    /// Send a request through the process-wide scheduler. A rate-limited response holds
+    /// back every request until GitHub's `retry-after` (or quota reset) time, and this
+    /// one is sent again; server errors are retried after a backoff. At most
+    /// `MAX_RETRIES` retries, then the last response is returned.
+    async fn send_with_retries(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        // Only methods that are safe to repeat are retried after a server error
+        let idempotent = request
+            .try_clone()
+            .and_then(|r| r.build().ok())
+            .is_some_and(|r| matches!(*r.method(), Method::GET | Method::PUT | Method::DELETE));
+        let mut request = request;
+        let mut attempt = 0;
+        loop {
+            let retry = request.try_clone();
+            let resp = self.send_once(request).await?;
+            let status = resp.status().as_u16();
+            let header = |name: &str| resp.headers().get(name).and_then(|v| v.to_str().ok());
+            let wait = scheduler::retry_delay(
+                status,
+                idempotent,
+                header("retry-after"),
+                header("x-ratelimit-remaining"),
+                header("x-ratelimit-reset"),
+                chrono::Utc::now().timestamp(),
+                attempt,
+            );
+            let (Some(wait), Some(retry)) = (wait, retry) else {
+                return Ok(resp);
+            };
+            if attempt >= scheduler::MAX_RETRIES {
+                return Ok(resp);
+            }
+            if status == 403 || status == 429 {
+                scheduler::global().pause(wait);
+            } else {
+                tokio::time::sleep(wait).await;
+            }
+            request = retry;
+            attempt += 1;
         }
     }
 
+    /// Send a request once, logging it when `--debug-http` is on
+    async fn send_once(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let _permit = scheduler::global().acquire().await;
+        if !DEBUG_HTTP.load(Ordering::SeqCst) {
+            let resp = request.send().await?;
+            record_rate_limit(&resp);
+            return Ok(resp);
+        }
+        let request = request.build()?;
+        let method = request.method().to_string();
+        let url = request.url().to_string();
+        let start = Instant::now();
+        let result = self.client.execute(request).await;
+        if let Ok(resp) = &result {
+            record_rate_limit(resp);
+        }
+        let (status, rate_limit) = match &result {
+            Ok(resp) => (
+                resp.status().to_string(),
+                RATE_LIMIT_HEADERS
+                    .iter()
+                    .filter_map(|&name| {
+                        let value = resp.headers().get(name)?.to_str().ok()?;
+                        Some((name, value.to_string()))
+                    })
+                    .collect(),
+            ),
+            Err(e) => (format!("error: {e}"), Vec::new()),
+        };
+        runner::log_line(&http_log_line(
+            &method,
+            &url,
+            &status,
+            &rate_limit,
+            start.elapsed(),
+            &self.token,
+        ));
+        result
+    }
+
     pub async fn get_user(&self) -> Result<User> {
         let resp = self
-            .client
-            .get("https://api.github.com/user")
-            .header("Authorization", format!("token {}", self.token))
-            .header("Accept", "application/vnd.github+json")
-            .header("User-Agent", "runner-mgr")
-            .send()
+            .send(
+                self.client
+                    .get(format!("{}/user", self.api_url))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
             .await
             .context("Failed to connect to GitHub API")?;
 
         if !resp.status().is_success() {
-            anyhow::bail!(
-                "GitHub API error: {} {}",
-                resp.status(),
-                resp.text().await.unwrap_or_default()
-            );
+            return Err(ApiError::from_response("get the authenticated user", &resp).into());
         }
 
         resp.json().await.context("Failed to parse user response")
     }
 
+    /// The authenticated user, with the scopes and expiry of the token
+    pub async fn token_info(&self) -> Result<TokenInfo> {
+        let resp = self
+            .send(
+                self.client
+                    .get(format!("{}/user", self.api_url))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await
+            .context("Failed to connect to GitHub API")?;
+        if !resp.status().is_success() {
+            return Err(ApiError::from_response("get the authenticated user", &resp).into());
+        }
+        let header = |name: &str| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let scopes = header("x-oauth-scopes").map(|s| {
+            s.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        });
+        let expires = header("github-authentication-token-expiration");
+        let user: User = resp.json().await.context("Failed to parse user response")?;
+        Ok(TokenInfo {
+            login: user.login,
+            scopes,
+            expires,
+        })
+    }
+
+    /// List repositories the user can register runners for: their own, plus repos
+    /// they collaborate on or reach through an organization where they have admin rights
     pub async fn list_repos(&self) -> Result<Vec<Repository>> {
         let mut all_repos = Vec::new();
         let mut page = 1u32;
 
         loop {
             let resp = self
-                .client
-                .get("https://api.github.com/user/repos")
-                .query(&[
-                    ("per_page", "100"),
-                    ("page", &page.to_string()),
-                    ("affiliation", "owner"),
-                    ("sort", "updated"),
-                ])
-                .header("Authorization", format!("token {}", self.token))
-                .header("Accept", "application/vnd.github+json")
-                .header("User-Agent", "runner-mgr")
-                .send()
+                .send(
+                    self.client
+                        .get(format!("{}/user/repos", self.api_url))
+                        .query(&[
+                            ("per_page", "100"),
+                            ("page", &page.to_string()),
+                            ("affiliation", "owner,collaborator,organization_member"),
+                            ("sort", "updated"),
+                        ])
+                        .header("Authorization", format!("token {}", self.token))
+                        .header("Accept", "application/vnd.github+json")
+                        .header("User-Agent", "runner-mgr"),
+                )
                 .await?;
 
             if !resp.status().is_success() {
-                anyhow::bail!("GitHub API error: {}", resp.status());
+                return Err(ApiError::from_response("list repositories", &resp).into());
             }
 
             let repos: Vec<Repository> = resp.json().await?;
             let count = repos.len();
-            all_repos.extend(repos);
+            all_repos.extend(repos.into_iter().filter(Repository::is_admin));
 
             if count < 100 {
                 break;
@@ -286,32 +1194,198 @@ impl GitHubClient {
         Ok(all_repos)
     }
 
+    /// Logins of the organizations the user belongs to
+    pub async fn list_orgs(&self) -> Result<Vec<String>> {
+        let resp = self
+            .send(
+                self.client
+                    .get(format!("{}/user/orgs", self.api_url))
+                    .query(&[("per_page", "100")])
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(ApiError::from_response("list organizations", &resp).into());
+        }
+
+        let orgs: Vec<Org> = resp.json().await.context("Failed to parse organizations")?;
+        Ok(orgs.into_iter().map(|o| o.login).collect())
+    }
+
+    /// Search repository names across the user's own repos and their organizations'
+    /// repos (first 100 matches)
+    pub async fn search_repos(&self, user: &str, query: &str) -> Result<RepoSearchResults> {
+        let mut owners = vec![user.to_string()];
+        owners.extend(self.list_orgs().await?);
+        let q = repo_search_query(query, &owners);
+
+        let resp = self
+            .send(
+                self.client
+                    .get(format!("{}/search/repositories", self.api_url))
+                    .query(&[("q", q.as_str()), ("per_page", "100")])
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(ApiError::from_response("search repositories", &resp).into());
+        }
+
+        resp.json().await.context("Failed to parse search results")
+    }
+
+    pub async fn get_repo(&self, owner: &str, repo: &str) -> Result<Repository> {
+        let resp = self
+            .send(
+                self.client
+                    .get(format!("{}/repos/{owner}/{repo}", self.api_url))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await?;
+
+        if !resp.status().is_success() {
+            let action = format!("get repository {owner}/{repo}");
+            return Err(ApiError::from_response(&action, &resp).into());
+        }
+
+        resp.json().await.context("Failed to parse repository")
+    }
+
+    /// Add custom labels to a runner, returning all of its labels
+    pub async fn add_runner_labels(
+        &self,
+        scope: &RunnerScope,
+        runner_id: u64,
+        labels: &[String],
+    ) -> Result<RunnerLabelList> {
+        let resp = self
+            .send(
+                self.client
+                    .post(format!(
+                        "{}/{}/actions/runners/{runner_id}/labels",
+                        self.api_url,
+                        scope.api_path()
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr")
+                    .json(&serde_json::json!({ "labels": labels })),
+            )
+            .await
+            .context("Failed to add runner labels")?;
+
+        if !resp.status().is_success() {
+            return Err(
+                ApiError::from_runner_response("add runner labels", resp, scope, "write")
+                    .await
+                    .into(),
+            );
+        }
+        resp.json().await.context("Failed to parse runner labels")
+    }
+
+    /// Remove a custom label from a runner, returning its remaining labels
+    pub async fn remove_runner_label(
+        &self,
+        scope: &RunnerScope,
+        runner_id: u64,
+        label: &str,
+    ) -> Result<RunnerLabelList> {
+        let mut url = reqwest::Url::parse(&format!(
+            "{}/{}/actions/runners/{runner_id}/labels",
+            self.api_url,
+            scope.api_path()
+        ))
+        .context("Invalid GitHub API URL")?;
+        url.path_segments_mut()
+            .map_err(|()| anyhow::anyhow!("Invalid GitHub API URL"))?
+            .push(label);
+        let resp = self
+            .send(
+                self.client
+                    .delete(url)
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await
+            .context("Failed to remove runner label")?;
+
+        if !resp.status().is_success() {
+            return Err(ApiError::from_runner_response(
+                "remove a runner label",
+                resp,
+                scope,
+                "write",
+            )
+            .await
+            .into());
+        }
+        resp.json().await.context("Failed to parse runner labels")
+    }
+
+    /// Whether Actions is enabled for a repository (needs admin access)
+    pub async fn get_actions_permissions(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<ActionsPermissions> {
+        let resp = self
+            .send(
+                self.client
+                    .get(format!(
+                        "{}/repos/{owner}/{repo}/actions/permissions",
+                        self.api_url
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(ApiError::from_response("get the Actions permissions", &resp).into());
+        }
+
+        resp.json()
+            .await
+            .context("Failed to parse Actions permissions")
+    }
+
     pub async fn get_registration_token(&self, scope: &RunnerScope) -> Result<RegistrationToken> {
         let api_path = scope.api_path();
-        let scope_type = match scope {
-            RunnerScope::Repository { .. } => "repo",
-            RunnerScope::Organization { .. } => "org admin:org",
-        };
 
         let resp = self
-            .client
-            .post(format!(
-                "https://api.github.com/{api_path}/actions/runners/registration-token"
-            ))
-            .header("Authorization", format!("token {}", self.token))
-            .header("Accept", "application/vnd.github+json")
-            .header("User-Agent", "runner-mgr")
-            .send()
+            .send(
+                self.client
+                    .post(format!(
+                        "{}/{api_path}/actions/runners/registration-token",
+                        self.api_url
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
             .await
             .context("Failed to request registration token")?;
 
         if !resp.status().is_success() {
-            anyhow::bail!(
-                "Failed to get registration token ({}). Check {} exists and PAT has '{}' scope.",
-                resp.status(),
+            return Err(ApiError::from_runner_response(
+                "get a registration token",
+                resp,
                 scope,
-                scope_type
-            );
+                "write",
+            )
+            .await
+            .into());
         }
 
         resp.json()
@@ -319,21 +1393,72 @@ impl GitHubClient {
             .context("Failed to parse registration token")
     }
 
+    /// Register a just-in-time runner that runs a single job and then removes itself
+    pub async fn generate_jitconfig(
+        &self,
+        scope: &RunnerScope,
+        name: &str,
+        labels: &[String],
+        runner_group_id: u64,
+    ) -> Result<JitConfig> {
+        let resp = self
+            .send(
+                self.client
+                    .post(format!(
+                        "{}/{}/actions/runners/generate-jitconfig",
+                        self.api_url,
+                        scope.api_path()
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr")
+                    .json(&serde_json::json!({
+                        "name": name,
+                        "runner_group_id": runner_group_id,
+                        "labels": labels,
+                        "work_folder": "_work",
+                    })),
+            )
+            .await
+            .context("Failed to request just-in-time runner config")?;
+
+        if !resp.status().is_success() {
+            return Err(ApiError::from_runner_response(
+                "generate just-in-time runner config",
+                resp,
+                scope,
+                "write",
+            )
+            .await
+            .into());
+        }
+
+        resp.json()
+            .await
+            .context("Failed to parse just-in-time runner config")
+    }
+
     pub async fn get_remove_token(&self, scope: &RunnerScope) -> Result<RegistrationToken> {
         let api_path = scope.api_path();
         let resp = self
-            .client
-            .post(format!(
-                "https://api.github.com/{api_path}/actions/runners/remove-token"
-            ))
-            .header("Authorization", format!("token {}", self.token))
-            .header("Accept", "application/vnd.github+json")
-            .header("User-Agent", "runner-mgr")
-            .send()
+            .send(
+                self.client
+                    .post(format!(
+                        "{}/{api_path}/actions/runners/remove-token",
+                        self.api_url
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
             .await?;
 
         if !resp.status().is_success() {
-            anyhow::bail!("Failed to get remove token: {}", resp.status());
+            return Err(
+                ApiError::from_runner_response("get a remove token", resp, scope, "write")
+                    .await
+                    .into(),
+            );
         }
 
         resp.json().await.context("Failed to parse remove token")
@@ -341,20 +1466,102 @@ impl GitHubClient {
 
     pub async fn list_runners(&self, scope: &RunnerScope) -> Result<RunnerList> {
         let api_path = scope.api_path();
+        let mut list = RunnerList {
+            total_count: 0,
+            runners: Vec::new(),
+        };
+        // One request per 100 runners, so large orgs don't need a call per runner
+        for page in 1.. {
+            let resp = self
+                .send(
+                    self.client
+                        .get(format!("{}/{api_path}/actions/runners", self.api_url))
+                        .query(&[("per_page", "100"), ("page", &page.to_string())])
+                        .header("Authorization", format!("token {}", self.token))
+                        .header("Accept", "application/vnd.github+json")
+                        .header("User-Agent", "runner-mgr"),
+                )
+                .await?;
+
+            if !resp.status().is_success() {
+                return Err(
+                    ApiError::from_runner_response("list runners", resp, scope, "read")
+                        .await
+                        .into(),
+                );
+            }
+
+            let page: RunnerList = resp.json().await.context("Failed to parse runners list")?;
+            list.total_count = page.total_count;
+            let done = page.runners.is_empty();
+            list.runners.extend(page.runners);
+            if done || list.runners.len() as u64 >= list.total_count {
+                break;
+            }
+        }
+        Ok(list)
+    }
+
+    /// Delete a runner's registration from GitHub. The runner's machine isn't contacted,
+    /// so this is for runners whose host is gone.
+    pub async fn delete_runner(&self, scope: &RunnerScope, runner_id: u64) -> Result<()> {
         let resp = self
-            .client
-            .get(format!("https://api.github.com/{api_path}/actions/runners"))
-            .header("Authorization", format!("token {}", self.token))
-            .header("Accept", "application/vnd.github+json")
-            .header("User-Agent", "runner-mgr")
-            .send()
-            .await?;
+            .send(
+                self.client
+                    .delete(format!(
+                        "{}/{}/actions/runners/{runner_id}",
+                        self.api_url,
+                        scope.api_path()
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await
+            .context("Failed to request runner deletion")?;
 
         if !resp.status().is_success() {
-            anyhow::bail!("Failed to list runners: {}", resp.status());
+            return Err(
+                ApiError::from_runner_response("delete runner", resp, scope, "write")
+                    .await
+                    .into(),
+            );
         }
+        Ok(())
+    }
 
-        resp.json().await.context("Failed to parse runners list")
+    /// Replace the custom labels of a runner (GitHub's read-only labels stay), returning
+    /// all of its labels
+    pub async fn set_runner_labels(
+        &self,
+        scope: &RunnerScope,
+        runner_id: u64,
+        labels: &[String],
+    ) -> Result<RunnerLabelList> {
+        let resp = self
+            .send(
+                self.client
+                    .put(format!(
+                        "{}/{}/actions/runners/{runner_id}/labels",
+                        self.api_url,
+                        scope.api_path()
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr")
+                    .json(&serde_json::json!({ "labels": labels })),
+            )
+            .await
+            .context("Failed to update runner labels")?;
+
+        if !resp.status().is_success() {
+            return Err(
+                ApiError::from_runner_response("set runner labels", resp, scope, "write")
+                    .await
+                    .into(),
+            );
+        }
+        resp.json().await.context("Failed to parse runner labels")
     }
 
     /// List workflow runs for a repository (not supported for organizations)
@@ -365,36 +1572,187 @@ impl GitHubClient {
         count: u32,
     ) -> Result<WorkflowRunList> {
         let resp = self
-            .client
-            .get(format!(
-                "https://api.github.com/repos/{owner}/{repo}/actions/runs"
-            ))
-            .query(&[("per_page", &count.to_string())])
-            .header("Authorization", format!("token {}", self.token))
-            .header("Accept", "application/vnd.github+json")
-            .header("User-Agent", "runner-mgr")
-            .send()
+            .send(
+                self.client
+                    .get(format!(
+                        "{}/repos/{owner}/{repo}/actions/runs",
+                        self.api_url
+                    ))
+                    .query(&[("per_page", &count.to_string())])
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
             .await?;
 
         if !resp.status().is_success() {
-            anyhow::bail!("Failed to list workflow runs: {}", resp.status());
+            return Err(ApiError::from_response("list workflow runs", &resp).into());
         }
 
         resp.json().await.context("Failed to parse workflow runs")
     }
 
+    /// List the workflow runs of a repository that are waiting to start. The list's
+    /// `total_count` is the queue depth even when it exceeds the first 100 runs.
+    pub async fn list_queued_workflow_runs(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<WorkflowRunList> {
+        let resp = self
+            .send(
+                self.client
+                    .get(format!(
+                        "{}/repos/{owner}/{repo}/actions/runs",
+                        self.api_url
+                    ))
+                    .query(&[("status", "queued"), ("per_page", "100")])
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(ApiError::from_response("list queued workflow runs", &resp).into());
+        }
+
+        resp.json()
+            .await
+            .context("Failed to parse queued workflow runs")
+    }
+
+    /// List every job of a workflow run (latest attempt), 100 per request
+    pub async fn list_jobs_for_run(
+        &self,
+        owner: &str,
+        repo: &str,
+        run_id: u64,
+    ) -> Result<WorkflowJobList> {
+        let mut list = WorkflowJobList {
+            total_count: 0,
+            jobs: Vec::new(),
+        };
+        for page in 1.. {
+            let resp = self
+                .send(
+                    self.client
+                        .get(format!(
+                            "{}/repos/{owner}/{repo}/actions/runs/{run_id}/jobs",
+                            self.api_url
+                        ))
+                        .query(&[("per_page", "100"), ("page", &page.to_string())])
+                        .header("Authorization", format!("token {}", self.token))
+                        .header("Accept", "application/vnd.github+json")
+                        .header("User-Agent", "runner-mgr"),
+                )
+                .await?;
+
+            if !resp.status().is_success() {
+                return Err(ApiError::from_response("list workflow jobs", &resp).into());
+            }
+
+            let page: WorkflowJobList =
+                resp.json().await.context("Failed to parse workflow jobs")?;
+            list.total_count = page.total_count;
+            let done = page.jobs.is_empty();
+            list.jobs.extend(page.jobs);
+            if done || list.jobs.len() as u64 >= list.total_count {
+                break;
+            }
+        }
+        Ok(list)
+    }
+
+    /// The first 50 annotations of a check run, e.g. of a workflow job
+    pub async fn list_annotations(
+        &self,
+        owner: &str,
+        repo: &str,
+        check_run_id: u64,
+    ) -> Result<Vec<CheckAnnotation>> {
+        let resp = self
+            .send(
+                self.client
+                    .get(format!(
+                        "{}/repos/{owner}/{repo}/check-runs/{check_run_id}/annotations",
+                        self.api_url
+                    ))
+                    .query(&[("per_page", "50")])
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(ApiError::from_response("list check run annotations", &resp).into());
+        }
+
+        resp.json()
+            .await
+            .context("Failed to parse check run annotations")
+    }
+
+    /// Cancel a queued or in-progress workflow run
+    pub async fn cancel_workflow_run(&self, owner: &str, repo: &str, run_id: u64) -> Result<()> {
+        let resp = self
+            .send(
+                self.client
+                    .post(format!(
+                        "{}/repos/{owner}/{repo}/actions/runs/{run_id}/cancel",
+                        self.api_url
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await
+            .context("Failed to request workflow run cancellation")?;
+
+        if !resp.status().is_success() {
+            return Err(ApiError::from_response("cancel workflow run", &resp).into());
+        }
+        Ok(())
+    }
+
+    /// Re-run every job of a completed workflow run
+    pub async fn rerun_workflow_run(&self, owner: &str, repo: &str, run_id: u64) -> Result<()> {
+        let resp = self
+            .send(
+                self.client
+                    .post(format!(
+                        "{}/repos/{owner}/{repo}/actions/runs/{run_id}/rerun",
+                        self.api_url
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await
+            .context("Failed to request workflow re-run")?;
+
+        if !resp.status().is_success() {
+            return Err(ApiError::from_response("re-run workflow run", &resp).into());
+        }
+        Ok(())
+    }
+
     pub async fn get_latest_runner_version(&self) -> Result<String> {
         let resp = self
-            .client
-            .get("https://api.github.com/repos/actions/runner/releases/latest")
-            .header("Authorization", format!("token {}", self.token))
-            .header("Accept", "application/vnd.github+json")
-            .header("User-Agent", "runner-mgr")
-            .send()
+            .send(
+                self.client
+                    .get(format!(
+                        "{DEFAULT_API_URL}/repos/actions/runner/releases/latest"
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
             .await?;
 
         if !resp.status().is_success() {
-            anyhow::bail!("Failed to fetch runner releases: {}", resp.status());
+            return Err(ApiError::from_response("fetch runner releases", &resp).into());
         }
 
         let release: serde_json::Value = resp.json().await?;
@@ -406,4 +1764,26 @@ impl GitHubClient {
 
         Ok(tag)
     }
+
+    /// The `actions/runner` release for `version`, with its package checksums
+    pub async fn get_runner_release(&self, version: &str) -> Result<RunnerRelease> {
+        let resp = self
+            .send(
+                self.client
+                    .get(format!(
+                        "{DEFAULT_API_URL}/repos/actions/runner/releases/tags/v{version}"
+                    ))
+                    .header("Authorization", format!("token {}", self.token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("User-Agent", "runner-mgr"),
+            )
+            .await?;
+
+        if !resp.status().is_success() {
+            let action = format!("fetch runner release v{version}");
+            return Err(ApiError::from_response(&action, &resp).into());
+        }
+
+        Ok(resp.json().await?)
+    }
 }
@@ -0,0 +1,118 @@
+//! `/healthz` and `/readyz` for `runner-mgr serve`, so systemd or a container
+//! orchestrator can tell whether the collector loop is still polling and whether its
+//! database and GitHub are reachable
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use tokio::net::TcpListener;
+
+use crate::webhook::{self, Request};
+
+/// Shortest time without a finished poll before the collector counts as stuck
+pub const MIN_STALE_AFTER: Duration = Duration::from_mins(5);
+
+/// What the collector loop last reported about itself
+#[derive(Debug)]
+pub struct CollectorHealth {
+    started: Instant,
+    /// A poll that takes longer than this, or no poll at all, means the loop is stuck
+    stale_after: Duration,
+    last_poll: Option<Instant>,
+    database: Result<(), String>,
+    github: Result<(), String>,
+}
+
+impl CollectorHealth {
+    /// Health of a collector polling every `interval`, started now
+    pub fn new(interval: Duration) -> Self {
+        Self::started_at(Instant::now(), interval)
+    }
+
+    pub fn started_at(started: Instant, interval: Duration) -> Self {
+        Self {
+            started,
+            stale_after: (interval * 3).max(MIN_STALE_AFTER),
+            last_poll: None,
+            database: Err("not checked yet".to_string()),
+            github: Err("not checked yet".to_string()),
+        }
+    }
+
+    /// Record a finished poll and what it found out about the database and GitHub
+    pub fn record_poll(&mut self, at: Instant, database: &Result<()>, github: &Result<()>) {
+        let describe = |result: &Result<()>| result.as_ref().copied().map_err(|e| format!("{e:#}"));
+        self.last_poll = Some(at);
+        self.database = describe(database);
+        self.github = describe(github);
+    }
+
+    /// Whether the loop finished a poll (or started) recently enough
+    pub fn is_live(&self, now: Instant) -> bool {
+        let last = self.last_poll.unwrap_or(self.started);
+        now.saturating_duration_since(last) <= self.stale_after
+    }
+
+    /// Whether the collector is live, has polled, and the database and GitHub answered
+    pub fn is_ready(&self, now: Instant) -> bool {
+        self.is_live(now)
+            && self.last_poll.is_some()
+            && self.database.is_ok()
+            && self.github.is_ok()
+    }
+
+    /// The JSON body of both endpoints
+    pub fn report(&self, now: Instant) -> serde_json::Value {
+        let check = |result: &Result<(), String>| match result {
+            Ok(()) => json!({ "ok": true }),
+            Err(e) => json!({ "ok": false, "error": e }),
+        };
+        json!({
+            "live": self.is_live(now),
+            "ready": self.is_ready(now),
+            "uptime_seconds": now.saturating_duration_since(self.started).as_secs(),
+            "last_poll_seconds_ago": self
+                .last_poll
+                .map(|at| now.saturating_duration_since(at).as_secs()),
+            "database": check(&self.database),
+            "github": check(&self.github),
+        })
+    }
+}
+
+/// Status code and JSON body for a request to the health endpoints
+pub fn handle_request(request: &Request, health: &Mutex<CollectorHealth>) -> (u16, String) {
+    if request.method != "GET" && request.method != "HEAD" {
+        return (405, json!({ "error": "Only GET is supported" }).to_string());
+    }
+    let health = health
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let now = Instant::now();
+    let ok = match request.path.split('?').next() {
+        Some("/healthz") => health.is_live(now),
+        Some("/readyz") => health.is_ready(now),
+        _ => return (404, json!({ "error": "Not found" }).to_string()),
+    };
+    (if ok { 200 } else { 503 }, health.report(now).to_string())
+}
+
+/// Answer health checks on `listen` until the process is stopped
+pub async fn serve(listen: &str, health: Arc<Mutex<CollectorHealth>>) -> Result<()> {
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to listen on {listen}"))?;
+    println!("Serving /healthz and /readyz on {listen}");
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let health = Arc::clone(&health);
+        tokio::spawn(async move {
+            let handle = |request: Request| async move { handle_request(&request, &health) };
+            if let Err(e) = webhook::serve_connection(stream, "application/json", handle).await {
+                eprintln!("{peer}: {e:#}");
+            }
+        });
+    }
+}
@@ -0,0 +1,85 @@
+//! Ctrl-C / SIGTERM handling: undo partial work before the process exits
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+type Cleanup = Box<dyn FnOnce() + Send>;
+
+static CLEANUPS: Mutex<Vec<(u64, Cleanup)>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Exit code for a process stopped by SIGINT (128 + 2)
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Registered cleanup, run if the process is interrupted while the guard is alive.
+/// Dropping the guard (the operation finished or failed normally) disarms it.
+#[must_use = "the cleanup is disarmed as soon as the guard is dropped"]
+pub struct CleanupGuard {
+    id: u64,
+}
+
+impl Drop for CleanupGuard {
+    fn drop(&mut self) {
+        if let Ok(mut cleanups) = CLEANUPS.lock() {
+            cleanups.retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+/// Run `cleanup` if the process is interrupted before the returned guard is dropped
+pub fn on_interrupt(cleanup: impl FnOnce() + Send + 'static) -> CleanupGuard {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut cleanups) = CLEANUPS.lock() {
+        cleanups.push((id, Box::new(cleanup)));
+    }
+    CleanupGuard { id }
+}
+
+/// Whether a SIGINT or SIGTERM has been received
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Run all pending cleanups, most recent first. Each cleanup runs at most once.
+pub fn run_cleanups() {
+    let pending = match CLEANUPS.lock() {
+        Ok(mut cleanups) => std::mem::take(&mut *cleanups),
+        Err(_) => return,
+    };
+    for (_, cleanup) in pending.into_iter().rev() {
+        cleanup();
+    }
+}
+
+/// Listen for SIGINT and SIGTERM; on either, run the pending cleanups and exit.
+/// Must be called from within the tokio runtime.
+pub fn install() {
+    tokio::spawn(async {
+        #[cfg(unix)]
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()).ok();
+        #[cfg(unix)]
+        let sigterm = async {
+            match terminate.as_mut() {
+                Some(s) => {
+                    s.recv().await;
+                }
+                None => std::future::pending::<()>().await,
+            }
+        };
+        // Windows has no SIGTERM; only Ctrl-C is handled there
+        #[cfg(not(unix))]
+        let sigterm = std::future::pending::<()>();
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            () = sigterm => {}
+        }
+
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        eprintln!();
+        eprintln!("Interrupted, cleaning up...");
+        run_cleanups();
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    });
+}
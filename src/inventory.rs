@@ -0,0 +1,107 @@
+//! Fleet inventory: one row per instance, rendered as Markdown or CSV for wiki pages
+
+use std::fmt::Write as _;
+
+use crate::config::Config;
+use crate::metrics::MetricsDb;
+use crate::runner;
+use crate::state::StateDb;
+
+/// Days of status snapshots used for the uptime column
+pub const UPTIME_DAYS: i32 = 7;
+
+const HEADERS: [&str; 7] = [
+    "Scope",
+    "Host",
+    "Labels",
+    "Version",
+    "Status",
+    "Uptime (7d)",
+    "Notes",
+];
+
+/// One instance in the inventory
+#[derive(Debug, Clone)]
+pub struct InventoryRow {
+    pub scope: String,
+    pub host: String,
+    pub labels: Vec<String>,
+    pub version: Option<String>,
+    pub status: String,
+    /// Percentage of status snapshots that were online
+    pub uptime: Option<f64>,
+    pub note: Option<String>,
+}
+
+impl InventoryRow {
+    fn cells(&self) -> [String; 7] {
+        [
+            self.scope.clone(),
+            self.host.clone(),
+            self.labels.join(","),
+            self.version.clone().unwrap_or_else(|| "-".to_string()),
+            self.status.clone(),
+            self.uptime
+                .map_or_else(|| "-".to_string(), |u| format!("{u:.1}%")),
+            self.note.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+/// Gather a row for every instance on this host. Uptime and notes come from the
+/// metrics and state databases when they can be opened.
+pub fn collect(config: &Config) -> Vec<InventoryRow> {
+    let host =
+        hostname::get().map_or_else(|_| "-".to_string(), |h| h.to_string_lossy().to_string());
+    let metrics = MetricsDb::open().ok();
+    let state = StateDb::open().ok();
+
+    runner::list_instances(config)
+        .into_iter()
+        .map(|instance| InventoryRow {
+            scope: instance.scope.to_display(),
+            host: host.clone(),
+            labels: runner::read_labels(&instance.dir),
+            version: runner::installed_version(&instance.dir),
+            status: instance.status.to_string(),
+            uptime: metrics
+                .as_ref()
+                .and_then(|db| db.get_scope_metrics(&instance.scope, UPTIME_DAYS).ok())
+                .and_then(|m| m.runner_uptime),
+            note: state
+                .as_ref()
+                .and_then(|db| db.note(&instance.scope).ok().flatten()),
+        })
+        .collect()
+}
+
+/// Markdown table; pipes and line breaks in cells are escaped
+pub fn to_markdown(rows: &[InventoryRow]) -> String {
+    let escape = |cell: &str| cell.replace('|', "\\|").replace('\n', " ");
+    let mut out = String::new();
+    let _ = writeln!(out, "| {} |", HEADERS.join(" | "));
+    let _ = writeln!(out, "|{}", "---|".repeat(HEADERS.len()));
+    for row in rows {
+        let cells: Vec<String> = row.cells().iter().map(|c| escape(c)).collect();
+        let _ = writeln!(out, "| {} |", cells.join(" | "));
+    }
+    out
+}
+
+/// CSV (RFC 4180): cells containing commas, quotes or line breaks are quoted
+pub fn to_csv(rows: &[InventoryRow]) -> String {
+    let quote = |cell: &str| {
+        if cell.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.to_string()
+        }
+    };
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", HEADERS.join(","));
+    for row in rows {
+        let cells: Vec<String> = row.cells().iter().map(|c| quote(c)).collect();
+        let _ = writeln!(out, "{}", cells.join(","));
+    }
+    out
+}
@@ -0,0 +1,103 @@
+//! Structured runner logs from the systemd journal (`journalctl -o json`)
+
+use chrono::{DateTime, Local};
+use serde_json::Value;
+
+use crate::github::RunnerScope;
+
+/// Journal priority of informational messages, the default for anything without one
+pub const PRIORITY_INFO: u8 = 6;
+
+/// One journal record of a runner service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub timestamp: Option<DateTime<Local>>,
+    /// syslog priority: 0 (emergency) to 7 (debug)
+    pub priority: u8,
+    pub identifier: Option<String>,
+    pub message: String,
+}
+
+impl JournalEntry {
+    /// Short level name for the priority
+    pub fn level(&self) -> &'static str {
+        priority_label(self.priority)
+    }
+
+    /// `HH:MM:SS LEVEL message`, as shown in the logs view
+    pub fn display(&self) -> String {
+        let time = self.timestamp.map_or_else(
+            || "--:--:--".to_string(),
+            |t| t.format("%H:%M:%S").to_string(),
+        );
+        format!("{time} {:<7} {}", self.level(), self.message)
+    }
+}
+
+/// Short level name for a syslog priority
+pub fn priority_label(priority: u8) -> &'static str {
+    match priority {
+        0..=3 => "ERROR",
+        4 => "WARN",
+        5 => "NOTICE",
+        6 => "INFO",
+        _ => "DEBUG",
+    }
+}
+
+/// `SyslogIdentifier` runner-mgr gives an instance's service, so its lines are tagged
+/// with the instance rather than `runsvc.sh` or `Runner.Listener`
+pub fn syslog_identifier(scope: &RunnerScope) -> String {
+    format!("runner-mgr-{}", scope.to_dir_name())
+}
+
+/// systemd drop-in setting the instance's syslog identifier (`%` from escaped directory
+/// names doubled, since systemd reads it as a specifier)
+pub fn identifier_dropin(scope: &RunnerScope) -> String {
+    format!(
+        "# Managed by runner-mgr\n[Service]\nSyslogIdentifier={}\n",
+        syslog_identifier(scope).replace('%', "%%")
+    )
+}
+
+/// Journal fields are strings, or arrays of bytes when they aren't valid UTF-8
+fn field_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(bytes) => {
+            let bytes: Vec<u8> = bytes
+                .iter()
+                .filter_map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                .collect();
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        _ => None,
+    }
+}
+
+/// Parse one line of `journalctl -o json` output
+pub fn parse_entry(line: &str) -> Option<JournalEntry> {
+    let record: Value = serde_json::from_str(line).ok()?;
+    let field = |name: &str| record.get(name).and_then(field_text);
+
+    let message = field("MESSAGE")?;
+    let timestamp = field("__REALTIME_TIMESTAMP")
+        .and_then(|us| us.parse::<i64>().ok())
+        .and_then(DateTime::from_timestamp_micros)
+        .map(|t| t.with_timezone(&Local));
+    let priority = field("PRIORITY")
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(PRIORITY_INFO);
+
+    Some(JournalEntry {
+        timestamp,
+        priority,
+        identifier: field("SYSLOG_IDENTIFIER"),
+        message: message.trim_end().to_string(),
+    })
+}
+
+/// Parse `journalctl -o json` output, skipping lines that aren't journal records
+pub fn parse_entries(output: &str) -> Vec<JournalEntry> {
+    output.lines().filter_map(parse_entry).collect()
+}
@@ -0,0 +1,358 @@
+//! Runner label helpers: parsing, and checking labels against the host they run on
+
+use crate::github::Runner;
+
+/// Labels that name an operating system, keyed by the `Config::detect_os` value they imply
+const OS_LABELS: &[(&str, &[&str])] = &[
+    ("linux", &["linux"]),
+    ("darwin", &["macos", "osx", "darwin", "mac"]),
+    ("windows", &["windows", "win"]),
+];
+
+/// Labels that name a CPU architecture, keyed by the `Config::detect_arch` value they imply
+const ARCH_LABELS: &[(&str, &[&str])] = &[
+    ("x64", &["x64", "amd64", "x86_64"]),
+    ("arm64", &["arm64", "aarch64"]),
+    ("arm", &["arm", "armv7"]),
+    ("x86", &["x86", "i386", "i686"]),
+];
+
+/// Split a comma-separated label string, trimming whitespace and dropping empty
+/// entries and case-insensitive duplicates (first spelling wins)
+pub fn parse_labels(labels: &str) -> Vec<String> {
+    let mut parsed: Vec<String> = Vec::new();
+    for label in labels.split(',').map(str::trim).filter(|l| !l.is_empty()) {
+        if !parsed.iter().any(|p| p.eq_ignore_ascii_case(label)) {
+            parsed.push(label.to_string());
+        }
+    }
+    parsed
+}
+
+/// Merge label lists, keeping the first spelling of case-insensitive duplicates
+pub fn merge_labels(base: &[String], extra: &[String]) -> Vec<String> {
+    let mut merged = base.to_vec();
+    for label in extra {
+        if !merged.iter().any(|m| m.eq_ignore_ascii_case(label)) {
+            merged.push(label.clone());
+        }
+    }
+    merged
+}
+
+/// Join `labels` with commas in at most `width` characters: as many whole labels as fit,
+/// then how many were left out (`gpu,docker +2`). A first label too long to fit is cut
+/// short with `…`.
+pub fn fit_labels(labels: &[&str], width: usize) -> String {
+    let joined = labels.join(",");
+    if joined.chars().count() <= width {
+        return joined;
+    }
+    let more = |shown: usize| format!(" +{}", labels.len() - shown);
+    let mut shown = 0;
+    let mut len = 0;
+    for (i, label) in labels.iter().enumerate() {
+        let next = len + usize::from(i > 0) + label.chars().count();
+        if next + more(i + 1).len() > width {
+            break;
+        }
+        shown = i + 1;
+        len = next;
+    }
+    if shown > 0 {
+        return format!("{}{}", labels[..shown].join(","), more(shown));
+    }
+    let suffix = if labels.len() > 1 {
+        more(1)
+    } else {
+        String::new()
+    };
+    let first: String = labels[0]
+        .chars()
+        .take(width.saturating_sub(suffix.len() + 1))
+        .collect();
+    format!("{first}…{suffix}")
+}
+
+/// Expand `auto_labels` patterns such as `{os}`, `{arch}` and `{hostname}` for this host.
+/// `os` and `arch` use the `Config::detect_os`/`detect_arch` spelling; `darwin` becomes
+/// the conventional `macos` label. Patterns that expand to nothing are skipped.
+pub fn expand_auto_labels(
+    patterns: &[String],
+    os: &str,
+    arch: &str,
+    hostname: &str,
+) -> Vec<String> {
+    let os_label = if os == "darwin" { "macos" } else { os };
+    let hostname: String = hostname
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() || c == ',' {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    let expanded: Vec<String> = patterns
+        .iter()
+        .map(|p| {
+            p.replace("{os}", os_label)
+                .replace("{arch}", arch)
+                .replace("{hostname}", &hostname)
+        })
+        .collect();
+    parse_labels(&expanded.join(","))
+}
+
+fn classify(label: &str, table: &[(&'static str, &[&str])]) -> Option<&'static str> {
+    let label = label.to_lowercase();
+    table
+        .iter()
+        .find(|(_, aliases)| aliases.contains(&label.as_str()))
+        .map(|(canonical, _)| *canonical)
+}
+
+/// The operating system a label refers to (`linux`, `darwin`, `windows`), if any
+pub fn label_os(label: &str) -> Option<&'static str> {
+    classify(label, OS_LABELS)
+}
+
+/// The architecture a label refers to (`x64`, `arm64`, `arm`, `x86`), if any
+pub fn label_arch(label: &str) -> Option<&'static str> {
+    classify(label, ARCH_LABELS)
+}
+
+/// Report labels that name a different OS or architecture than the host.
+/// `host_os` and `host_arch` use the `Config::detect_os`/`detect_arch` spelling.
+pub fn host_mismatches(labels: &[String], host_os: &str, host_arch: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for label in labels {
+        if let Some(os) = label_os(label) {
+            if os != host_os {
+                warnings.push(format!(
+                    "label '{label}' targets {os} but this host is {host_os}"
+                ));
+            }
+        }
+        if let Some(arch) = label_arch(label) {
+            if arch != host_arch {
+                warnings.push(format!(
+                    "label '{label}' targets {arch} but this host is {host_arch}"
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+/// Report differences between the labels we registered and the labels GitHub
+/// has on record for the runner. GitHub adds its own default labels (`self-hosted`,
+/// OS and architecture), so only custom labels missing on either side are reported.
+pub fn record_mismatches(registered: &[String], github: &[String]) -> Vec<String> {
+    let contains = |set: &[String], label: &str| set.iter().any(|l| l.eq_ignore_ascii_case(label));
+
+    let mut warnings = Vec::new();
+    for label in registered {
+        if !contains(github, label) && !is_default_label(label) {
+            warnings.push(format!("registered label '{label}' is missing on GitHub"));
+        }
+    }
+    for label in github {
+        if !contains(registered, label) && !is_default_label(label) {
+            warnings.push(format!(
+                "GitHub has label '{label}' that was not registered here"
+            ));
+        }
+    }
+    warnings
+}
+
+/// Everything wrong with one runner's labels: the labels registered here, or those on
+/// GitHub's record of the runner (`record`, when it could be fetched), naming another OS
+/// or architecture than the host, and custom labels present on one side only
+pub fn runner_mismatches(
+    registered: &[String],
+    record: Option<&Runner>,
+    host_os: &str,
+    host_arch: &str,
+) -> Vec<String> {
+    let mut warnings = host_mismatches(registered, host_os, host_arch);
+    let Some(record) = record else {
+        return warnings;
+    };
+
+    let github: Vec<String> = record.labels.iter().map(|l| l.name.clone()).collect();
+    for w in host_mismatches(&github, host_os, host_arch) {
+        warnings.push(format!("GitHub {w}"));
+    }
+    if let Some(os) = label_os(&record.os) {
+        if os != host_os {
+            warnings.push(format!(
+                "GitHub reports os '{}' but this host is {host_os}",
+                record.os
+            ));
+        }
+    }
+    if !registered.is_empty() {
+        warnings.extend(record_mismatches(registered, &github));
+    }
+    warnings
+}
+
+/// Labels to record in `.labels` once a runner's custom labels on GitHub are `custom`:
+/// the default labels registered before (`self-hosted`, OS, architecture), then `custom`
+pub fn registered_after_edit(registered: &[String], custom: &[String]) -> Vec<String> {
+    let defaults: Vec<String> = registered
+        .iter()
+        .filter(|label| is_default_label(label))
+        .cloned()
+        .collect();
+    let base = merge_labels(&["self-hosted".to_string()], &defaults);
+    merge_labels(&base, custom)
+}
+
+/// Levenshtein distance between two labels, ignoring case
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Whether `label` is `self-hosted` or one of the OS and architecture names GitHub
+/// assigns by default
+fn is_default_label(label: &str) -> bool {
+    label.eq_ignore_ascii_case("self-hosted")
+        || label_os(label).is_some()
+        || label_arch(label).is_some()
+}
+
+/// The known label `label` is most likely a misspelling of, if any. Short labels are
+/// left alone, since `gpu` and `cpu` are both plausible; longer ones may be one edit
+/// away, and labels of 8 characters or more two.
+pub fn suggest_label<'a>(label: &str, known: &'a [String]) -> Option<&'a str> {
+    let length = label.chars().count();
+    if length < 4 || known.iter().any(|k| k.eq_ignore_ascii_case(label)) {
+        return None;
+    }
+    let max_distance = if length >= 8 { 2 } else { 1 };
+    known
+        .iter()
+        .map(|k| (edit_distance(label, k), k))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, k)| k.as_str())
+}
+
+/// What `labels audit` found wrong with one runner's labels on GitHub
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LabelAudit {
+    /// GitHub doesn't list `self-hosted`, so `runs-on: self-hosted` jobs skip the runner
+    pub missing_self_hosted: bool,
+    /// Labels that look like misspellings, with the known label they resemble
+    pub typos: Vec<(String, String)>,
+    /// Labels registered here that GitHub doesn't have
+    pub missing: Vec<String>,
+    /// Custom labels on GitHub that weren't registered here (typos aside)
+    pub unexpected: Vec<String>,
+}
+
+impl LabelAudit {
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// One line per finding
+    pub fn findings(&self) -> Vec<String> {
+        let mut findings = Vec::new();
+        if self.missing_self_hosted {
+            findings.push("missing 'self-hosted'".to_string());
+        }
+        for (label, suggestion) in &self.typos {
+            findings.push(format!("'{label}' looks like a typo of '{suggestion}'"));
+        }
+        for label in &self.missing {
+            findings.push(format!("registered label '{label}' is missing on GitHub"));
+        }
+        for label in &self.unexpected {
+            findings.push(format!("label '{label}' was not registered here"));
+        }
+        findings
+    }
+
+    /// The custom labels to set on GitHub to fix the runner, given its current custom
+    /// labels and all of its labels: typos replaced by their suggestion, missing labels
+    /// and `self-hosted` added. Unexpected labels are kept, as they may have been added
+    /// on purpose.
+    pub fn fixed_labels(&self, custom: &[String], github: &[String]) -> Vec<String> {
+        let has = |label: &str| github.iter().any(|l| l.eq_ignore_ascii_case(label));
+        let labels: Vec<String> = custom
+            .iter()
+            .filter(|l| !self.typos.iter().any(|(typo, _)| typo == *l))
+            .cloned()
+            .collect();
+        let mut additions: Vec<String> = Vec::new();
+        if self.missing_self_hosted {
+            additions.push("self-hosted".to_string());
+        }
+        additions.extend(
+            self.typos
+                .iter()
+                .map(|(_, suggestion)| suggestion)
+                .filter(|s| !has(s))
+                .cloned(),
+        );
+        additions.extend(self.missing.iter().cloned());
+        merge_labels(&labels, &additions)
+    }
+}
+
+/// Check a runner's labels on GitHub against those registered for it here and the
+/// labels `known` across the fleet (every instance's registered labels, profile labels
+/// and auto labels), besides the default ones. Without registered labels (imported runners), only `self-hosted`
+/// and typos are checked.
+pub fn audit_labels(github: &[String], registered: &[String], known: &[String]) -> LabelAudit {
+    let contains = |set: &[String], label: &str| set.iter().any(|l| l.eq_ignore_ascii_case(label));
+    let mut vocabulary: Vec<String> = std::iter::once("self-hosted")
+        .chain(
+            OS_LABELS
+                .iter()
+                .chain(ARCH_LABELS)
+                .flat_map(|(_, aliases)| aliases.iter().copied()),
+        )
+        .map(str::to_string)
+        .collect();
+    vocabulary.extend(known.iter().cloned());
+
+    let mut audit = LabelAudit {
+        missing_self_hosted: !contains(github, "self-hosted"),
+        ..Default::default()
+    };
+
+    for label in github {
+        if is_default_label(label) || contains(registered, label) {
+            continue;
+        }
+        if let Some(suggestion) = suggest_label(label, &vocabulary) {
+            audit.typos.push((label.clone(), suggestion.to_string()));
+        } else if !registered.is_empty() {
+            audit.unexpected.push(label.clone());
+        }
+    }
+    for label in registered {
+        if !contains(github, label) && !is_default_label(label) {
+            audit.missing.push(label.clone());
+        }
+    }
+    audit
+}
@@ -1,4 +1,39 @@
+pub mod autoscale;
+pub mod bootstrap;
+pub mod collector;
 pub mod config;
+pub mod credentials;
+pub mod deps;
+pub mod docker;
+pub mod doctor;
+pub mod dormant;
+pub mod download;
+pub mod failure;
+pub mod follow;
 pub mod github;
+pub mod health;
+pub mod interrupt;
+pub mod inventory;
+pub mod journal;
+pub mod labels;
 pub mod metrics;
+pub mod migrate;
+pub mod notify;
+pub mod ondemand;
+pub mod orphans;
+pub mod privilege;
+pub mod recording;
+pub mod remote;
 pub mod runner;
+pub mod scheduler;
+pub mod state;
+pub mod status_file;
+pub mod systemd;
+pub mod target;
+pub mod toolchain;
+pub mod updates;
+pub mod users;
+pub mod watch;
+pub mod watchdog;
+pub mod webhook;
+pub mod winsvc;
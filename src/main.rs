@@ -1,14 +1,53 @@
+mod autoscale;
+mod bootstrap;
+mod collector;
 mod config;
+mod credentials;
+mod deps;
+mod docker;
+mod doctor;
+mod dormant;
+mod download;
+mod failure;
+mod follow;
 mod github;
+mod health;
+mod interrupt;
+mod inventory;
+mod journal;
+mod labels;
 mod metrics;
+mod migrate;
+mod notify;
+mod ondemand;
+mod orphans;
+mod privilege;
+mod recording;
+mod remote;
 mod runner;
+mod scheduler;
+mod state;
+mod status_file;
+mod systemd;
+mod target;
+mod toolchain;
 mod tui;
+mod updates;
+mod users;
+mod watch;
+mod watchdog;
+mod webhook;
+mod winsvc;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use std::io::{self, Write};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
 use config::Config;
+use credentials::CredentialBackend;
+use failure::Failure;
 use github::{GitHubClient, RunnerScope};
 
 #[derive(Parser)]
@@ -22,6 +61,19 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Log every GitHub API call: method, URL, status, rate-limit headers and timing
+    #[arg(long, global = true)]
+    debug_http: bool,
+
+    /// Fail early instead of using sudo for steps that need elevation
+    #[arg(long, global = true)]
+    no_sudo: bool,
+
+    /// Use the config of a named account (default: `RUNNER_MGR_ACCOUNT` or
+    /// `RUNNER_MGR_PROFILE`, else the default account)
+    #[arg(long, global = true, value_name = "NAME")]
+    account: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -29,7 +81,10 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// First-time setup: configure PAT, runner user, and download runner binary
-    Init,
+    Init(InitArgs),
+
+    /// List the accounts configured on this machine (select one with --account)
+    Accounts,
 
     /// List your repos with runner status
     List,
@@ -37,53 +92,219 @@ enum Commands {
     /// Register a runner for a repo or organization and start it
     Add {
         /// Target: owner/repo for repository, org:name for organization
-        target: String,
+        /// (omit to pick from the repos you administer)
+        target: Option<String>,
         /// Comma-separated labels (default: self-hosted)
         #[arg(default_value = "self-hosted")]
         labels: String,
+        /// Don't append the configured automatic labels (os/arch/hostname)
+        #[arg(long)]
+        no_auto_labels: bool,
+        /// Apply a named profile from the config (labels, env, limits, hooks)
+        #[arg(long)]
+        profile: Option<String>,
+        /// Search your repos (and your organizations' repos) by name and pick one
+        #[arg(long, value_name = "QUERY", conflicts_with = "target")]
+        search: Option<String>,
+        /// Register this many parallel runners for the target (next free instances)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=50))]
+        count: Option<u32>,
+        /// Register an additional runner named <target>@<SUFFIX>
+        #[arg(long, value_name = "SUFFIX", conflicts_with = "count")]
+        name_suffix: Option<String>,
+    },
+
+    /// Print a shell script that installs and registers a runner on a new machine
+    /// (for cloud-init user-data, Terraform or autoscaling groups)
+    BootstrapScript(BootstrapArgs),
+
+    /// Run a single-use just-in-time runner in the foreground: it takes one job, then
+    /// removes itself (no registration token is stored on disk)
+    Jit {
+        /// Target: owner/repo for repository, org:name for organization
+        target: String,
+        /// Comma-separated labels (self-hosted is always included)
+        #[arg(long, default_value = "self-hosted")]
+        labels: String,
     },
 
     /// Stop, deregister, and remove a runner
     Remove {
-        /// Target: owner/repo for repository, org:name for organization
+        /// Target: owner/repo for repository, org:name for organization, with @name
+        /// for an additional instance
         target: String,
     },
 
     /// Start runner service(s)
     Start {
-        /// Target: owner/repo, org:name, or "all"
+        /// Target: owner/repo, org:name (every instance), owner/repo@name, or "all"
         target: String,
     },
 
     /// Stop runner service(s)
     Stop {
-        /// Target: owner/repo, org:name, or "all"
+        /// Target: owner/repo, org:name (every instance), owner/repo@name, or "all"
         target: String,
     },
 
     /// Restart runner service(s)
     Restart {
-        /// Target: owner/repo, org:name, or "all"
+        /// Target: owner/repo, org:name (every instance), owner/repo@name, or "all"
+        target: String,
+    },
+
+    /// Register runners again whose registration GitHub deleted or rejects, keeping
+    /// their instance directory
+    Reregister {
+        /// Target: owner/repo, org:name (every instance) or owner/repo@name
         target: String,
     },
 
     /// Show status of all configured runners
-    Status,
+    Status {
+        /// Roll up every runner of the configured scopes by host instead
+        #[arg(long)]
+        hosts: bool,
+        /// Show every label GitHub has for each runner, default ones included, in full
+        #[arg(long, conflicts_with = "hosts")]
+        wide: bool,
+    },
+
+    /// Check the config, PAT, runner template and every instance, and explain how to fix
+    /// what's wrong
+    Doctor,
+
+    /// Print a table of all instances for documentation (Markdown or CSV)
+    Inventory {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = InventoryFormat::Md)]
+        format: InventoryFormat,
+    },
+
+    /// Show or set the free-form note for a runner instance
+    Note {
+        /// Target: owner/repo or org:name
+        target: String,
+        /// New note (omit to show the current one)
+        text: Vec<String>,
+        /// Remove the note
+        #[arg(long, conflicts_with = "text")]
+        clear: bool,
+    },
+
+    /// Show, add or remove tags on a runner instance
+    Tag {
+        /// Target: owner/repo or org:name
+        target: String,
+        /// Tags to add (omit to list the current tags)
+        tags: Vec<String>,
+        /// Remove the given tags instead of adding them
+        #[arg(long, requires = "tags")]
+        remove: bool,
+    },
+
+    /// Check runner labels across the fleet, or change a runner's labels on GitHub
+    Labels {
+        #[command(subcommand)]
+        command: LabelsCommand,
+    },
+
+    /// List the runners registered on GitHub, or delete ones whose host is gone
+    GhRunners {
+        #[command(subcommand)]
+        command: GhRunnersCommand,
+    },
 
     /// Show recent runner logs
     Logs {
-        /// Target: owner/repo for repository, org:name for organization
+        /// Target: owner/repo for repository, org:name for organization, with @name
+        /// for an additional instance
         target: String,
         /// Number of lines to show
         #[arg(default_value = "50")]
         lines: u32,
     },
 
-    /// Update the runner binary template
-    Update,
+    /// Cancel or re-run a workflow run
+    Run {
+        #[command(subcommand)]
+        command: RunCommand,
+    },
+
+    /// Update the runner binary template, and optionally existing instances in place
+    Update {
+        /// Also update instances: owner/repo, org:name, owner/repo@name, or "all" (default)
+        #[arg(long, value_name = "TARGET", num_args = 0..=1, default_missing_value = "all")]
+        instances: Option<String>,
+    },
 
     /// Open the TUI dashboard
-    Dashboard,
+    Dashboard {
+        /// Save every GitHub API response the dashboard fetches into DIR
+        #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+        record: Option<PathBuf>,
+        /// Drive the dashboard from a recording made with --record, without network access
+        #[arg(long, value_name = "DIR")]
+        replay: Option<PathBuf>,
+    },
+
+    /// Print recorded metrics per scope and the status of configured SLOs
+    Report {
+        /// Days of history to summarize
+        #[arg(long, default_value = "7")]
+        days: i32,
+    },
+
+    /// Print the recorded metrics per scope: success rate, durations, uptime, queue wait
+    #[command(args_conflicts_with_subcommands = true)]
+    Metrics {
+        #[command(subcommand)]
+        command: Option<MetricsCommand>,
+        /// Repository (owner/repo) or organization (org:name); all recorded scopes if omitted
+        target: Option<String>,
+        /// Days of history to summarize
+        #[arg(long, default_value = "7")]
+        days: i32,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = MetricsFormat::Table)]
+        format: MetricsFormat,
+    },
+
+    /// Receive GitHub `workflow_job` webhooks and record job events in the metrics database
+    WebhookServer {
+        /// Address to listen on (default: the configured address, or 127.0.0.1:8787)
+        #[arg(long)]
+        listen: Option<String>,
+    },
+
+    /// Collect runner status and metrics without the dashboard, and keep the status file
+    /// up to date
+    #[command(args_conflicts_with_subcommands = true)]
+    Serve {
+        #[command(subcommand)]
+        command: Option<ServeCommand>,
+        /// Seconds between polls (default: the dashboard refresh interval)
+        #[arg(long)]
+        interval: Option<u64>,
+        /// Answer `/healthz` and `/readyz` on this address, e.g. 127.0.0.1:8788
+        #[arg(long)]
+        listen: Option<String>,
+    },
+
+    /// Restart runners whose service runs but GitHub reports offline, following the
+    /// [watchdog] config
+    Watch {
+        /// Seconds between checks
+        #[arg(long, default_value_t = watchdog::DEFAULT_INTERVAL_SECS)]
+        interval: u64,
+    },
+
+    /// Start and stop runners as jobs are queued, following the [autoscale] config
+    Autoscale {
+        /// Seconds between polls of the queued jobs
+        #[arg(long, default_value_t = autoscale::DEFAULT_INTERVAL_SECS)]
+        interval: u64,
+    },
 
     /// Import an existing runner directory
     Import {
@@ -92,108 +313,617 @@ enum Commands {
         /// Target: owner/repo or org:name (auto-detected if not provided)
         #[arg(short, long)]
         target: Option<String>,
+        /// Rename a directory in the instances directory to its instance name instead
+        /// of linking it (only for runners without an installed service)
+        #[arg(long)]
+        rename: bool,
     },
 
-    /// Scan for existing runner directories and optionally import them
-    Scan {
+    /// Show the language/toolchain versions visible to each runner
+    Toolchain {
+        /// Target: owner/repo or org:name (default: all runners)
+        target: Option<String>,
+    },
+
+    /// Check or repair the container engine used by container jobs
+    Docker {
+        #[command(subcommand)]
+        command: DockerCommand,
+    },
+
+    /// Start a runner only when work is queued instead of running it 24/7 (systemd)
+    OnDemand {
+        #[command(subcommand)]
+        command: OnDemandCommand,
+    },
+
+    /// Manage the runner's Linux prerequisites (ICU, OpenSSL, Kerberos, zlib)
+    Deps {
+        #[command(subcommand)]
+        command: DepsCommand,
+    },
+
+    /// Move manually installed runners under runner-mgr: scan, explain and import them,
+    /// then check that each one works
+    Migrate {
         /// Additional paths to scan (comma-separated)
         #[arg(short, long)]
         paths: Option<String>,
-        /// Import all discovered runners without prompting
+        /// Import without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Scan for existing runner directories and optionally import them
+    Scan(ScanArgs),
+}
+
+#[derive(clap::Args)]
+#[allow(clippy::struct_excessive_bools)]
+struct InitArgs {
+    /// GitHub Enterprise Server API root, e.g. `https://ghe.example.com/api/v3`
+    #[arg(long)]
+    api_url: Option<String>,
+    /// Operate entirely as the current user: no sudo, runner services installed as
+    /// systemd user units (Linux) or launch agents (macOS)
+    #[arg(long)]
+    rootless: bool,
+    /// GitHub PAT to use instead of prompting (or set `RUNNER_MGR_PAT`)
+    #[arg(long, value_name = "TOKEN")]
+    pat: Option<String>,
+    /// Runner user account (or set `RUNNER_MGR_RUNNER_USER`; default: github)
+    #[arg(long, value_name = "USER")]
+    runner_user: Option<String>,
+    /// Directory for runner instances (or set `RUNNER_MGR_INSTANCES_BASE`)
+    #[arg(long, value_name = "DIR")]
+    instances_base: Option<String>,
+    /// Don't prompt: keep the existing PAT, use defaults, create a missing runner user
+    #[arg(short, long)]
+    yes: bool,
+    /// Don't download the runner; check a template that's already in place, if any
+    #[arg(long)]
+    skip_download: bool,
+    /// Proxy for GitHub requests and runner registration, e.g. `http://proxy:3128`
+    /// (default: `HTTPS_PROXY`)
+    #[arg(long, value_name = "URL")]
+    proxy_url: Option<String>,
+    /// Store the PAT in the OS keychain instead of config.toml
+    #[arg(long)]
+    keyring: bool,
+}
+
+#[derive(clap::Args)]
+struct BootstrapArgs {
+    /// Target: owner/repo for repository, org:name for organization
+    target: String,
+    /// Comma-separated labels (self-hosted is always included)
+    #[arg(long, default_value = "self-hosted")]
+    labels: String,
+    /// Runner architecture on the new machine: x64 or arm64 (default: this host's)
+    #[arg(long)]
+    arch: Option<String>,
+    /// Account the runner runs as on the new machine (default: the configured runner user)
+    #[arg(long)]
+    user: Option<String>,
+    /// Install directory on the new machine
+    #[arg(long, default_value = "/opt/actions-runner")]
+    dir: String,
+    /// Register an ephemeral runner that exits after one job
+    #[arg(long)]
+    ephemeral: bool,
+    /// Write the script to a file (mode 0700) instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+#[derive(clap::Args)]
+struct ScanArgs {
+    /// Additional paths to scan (comma-separated)
+    #[arg(short, long)]
+    paths: Option<String>,
+    /// Also search this directory recursively (repeatable)
+    #[arg(long = "root", value_name = "DIR")]
+    roots: Vec<PathBuf>,
+    /// Levels below each --root (and each remote search root) to search
+    #[arg(long, default_value_t = 3)]
+    depth: u32,
+    /// Also search the hosts in `remote_hosts` over SSH
+    #[arg(long)]
+    remote: bool,
+    /// Also search this SSH destination (repeatable)
+    #[arg(long = "host", value_name = "DESTINATION")]
+    hosts: Vec<String>,
+    /// Import all discovered runners without prompting
+    #[arg(long)]
+    auto_import: bool,
+}
+
+#[derive(Subcommand)]
+enum DockerCommand {
+    /// Check that the docker/podman daemon is reachable from the runner user
+    Check,
+    /// Restart the docker/podman daemon
+    Restart,
+    /// Remove stopped containers and dangling images left behind by jobs
+    Prune {
+        /// Also remove unused volumes
+        #[arg(long)]
+        volumes: bool,
+        /// Only prune when the configured janitor schedule or disk threshold says so (for cron)
+        #[arg(long)]
+        if_due: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum InventoryFormat {
+    Md,
+    Csv,
+}
+
+#[derive(Subcommand)]
+enum OnDemandCommand {
+    /// Start the runner when its trigger file appears; stop it once idle
+    Enable {
+        /// Target: owner/repo, org:name (every instance) or owner/repo@name
+        target: String,
+        /// How often to check whether the runner is idle, in minutes
+        #[arg(long, default_value_t = 15)]
+        idle_minutes: u32,
+    },
+    /// Run the runner service 24/7 again
+    Disable {
+        /// Target: owner/repo, org:name (every instance) or owner/repo@name
+        target: String,
+    },
+    /// Queue work: create the trigger file that starts the runner
+    Trigger {
+        /// Target: owner/repo, org:name (every instance) or owner/repo@name
+        target: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum RunCommand {
+    /// Cancel a queued or in-progress workflow run
+    Cancel {
+        /// Repository: owner/repo
+        repo: String,
+        /// Workflow run ID (the number after /actions/runs/ in its URL)
+        run_id: u64,
+    },
+    /// Re-run every job of a completed workflow run
+    Rerun {
+        /// Repository: owner/repo
+        repo: String,
+        /// Workflow run ID (the number after /actions/runs/ in its URL)
+        run_id: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum LabelsCommand {
+    /// Compare every runner's labels on GitHub with the labels registered here and
+    /// report missing `self-hosted`, likely typos and unregistered labels
+    Audit {
+        /// Set the corrected labels on GitHub
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Add custom labels to runners without registering them again
+    Add {
+        /// Target: owner/repo, org:name (every instance), owner/repo@name, or "all"
+        target: String,
+        /// Comma-separated labels
+        labels: String,
+    },
+    /// Remove custom labels from runners
+    Remove {
+        /// Target: owner/repo, org:name (every instance), owner/repo@name, or "all"
+        target: String,
+        /// Comma-separated labels
+        labels: String,
+    },
+    /// Replace the custom labels of runners (GitHub's self-hosted, OS and architecture
+    /// labels stay)
+    Set {
+        /// Target: owner/repo, org:name (every instance), owner/repo@name, or "all"
+        target: String,
+        /// Comma-separated labels; empty to remove every custom label
+        labels: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum GhRunnersCommand {
+    /// List every runner GitHub has registered for a scope, and which instance on this
+    /// host each one belongs to
+    List {
+        /// Target: owner/repo, org:name, or "all" (every configured scope)
+        #[arg(default_value = "all")]
+        target: String,
+    },
+    /// Delete runner registrations from GitHub, leaving instance directories alone
+    Delete {
+        /// Target: owner/repo or org:name
+        target: String,
+        /// Names or IDs of the runners to delete
+        runners: Vec<String>,
+        /// Delete every offline runner (of the named ones, if any)
+        #[arg(long)]
+        offline: bool,
+        /// Delete without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MetricsFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Subcommand)]
+enum MetricsCommand {
+    /// Delete metrics older than the retention period and optionally shrink the database
+    Prune {
+        /// Days of history to keep (default: the configured `retention_days`)
+        #[arg(long)]
+        days: Option<u32>,
+        /// Rebuild the database file afterwards to return the freed space to the disk
         #[arg(long)]
-        auto_import: bool,
+        vacuum: bool,
+    },
+    /// Merge the recorded history of a renamed or transferred repository into its new name
+    MigrateScope {
+        /// Previous name: owner/repo or org:name
+        old: String,
+        /// Current name: owner/repo or org:name
+        new: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ServeCommand {
+    /// Install, enable and start a systemd unit (`Type=notify`, with a watchdog) running
+    /// `serve`
+    Install {
+        /// Seconds between polls (default: the dashboard refresh interval)
+        #[arg(long)]
+        interval: Option<u64>,
+        /// Answer `/healthz` and `/readyz` on this address, e.g. 127.0.0.1:8788
+        #[arg(long)]
+        listen: Option<String>,
+        /// Print the unit instead of installing it
+        #[arg(long)]
+        print: bool,
     },
 }
 
+#[derive(Subcommand)]
+enum DepsCommand {
+    /// Detect the distro and install the runner's dependencies
+    Install,
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    interrupt::install();
 
     // Enable verbose mode if requested
+    if cli.debug_http {
+        github::set_debug_http(true);
+    }
     if cli.verbose {
         runner::set_verbose(true);
     }
+    privilege::set_no_sudo(cli.no_sudo);
+    let account = flag_or_env(cli.account, config::ACCOUNT_ENV)
+        .or_else(|| flag_or_env(None, config::PROFILE_ENV));
+    if let Err(e) = config::set_account(account) {
+        eprintln!("error: {e:#}");
+        std::process::exit(failure::exit_code(&e));
+    }
+
+    if let Err(e) = run(cli.command, cli.verbose || cli.debug_http).await {
+        // A child process killed by Ctrl-C surfaces as an error; let the cleanup win
+        if interrupt::interrupted() {
+            interrupt::run_cleanups();
+            std::process::exit(interrupt::INTERRUPTED_EXIT_CODE);
+        }
+        eprintln!("error: {e:#}");
+        std::process::exit(failure::exit_code(&e));
+    }
+}
 
-    let result = match cli.command {
-        Commands::Init => cmd_init().await,
+async fn run(command: Commands, verbose: bool) -> Result<()> {
+    match command {
+        Commands::Init(args) => cmd_init(args).await,
+        Commands::Accounts => {
+            cmd_accounts();
+            Ok(())
+        }
         Commands::List => cmd_list().await,
-        Commands::Add { target, labels } => cmd_add(&target, &labels).await,
+        Commands::Add {
+            target,
+            labels,
+            no_auto_labels,
+            profile,
+            search,
+            count,
+            name_suffix,
+        } => {
+            cmd_add(
+                target.as_deref(),
+                search.as_deref(),
+                &labels,
+                no_auto_labels,
+                profile.as_deref(),
+                Instances { count, name_suffix },
+            )
+            .await
+        }
+        Commands::BootstrapScript(args) => cmd_bootstrap_script(args).await,
+        Commands::Jit { target, labels } => cmd_jit(&target, &labels).await,
         Commands::Remove { target } => cmd_remove(&target).await,
         Commands::Start { target } => cmd_start(&target),
         Commands::Stop { target } => cmd_stop(&target),
         Commands::Restart { target } => cmd_restart(&target),
-        Commands::Status => cmd_status(),
+        Commands::Reregister { target } => cmd_reregister(&target).await,
+        Commands::Status { hosts: false, wide } => cmd_status(wide).await,
+        Commands::Status { hosts: true, .. } => cmd_status_hosts().await,
+        Commands::Doctor => cmd_doctor().await,
+        Commands::Inventory { format } => cmd_inventory(format),
+        Commands::Note {
+            target,
+            text,
+            clear,
+        } => cmd_note(&target, &text, clear),
+        Commands::Tag {
+            target,
+            tags,
+            remove,
+        } => cmd_tag(&target, &tags, remove),
+        Commands::Labels { command } => cmd_labels(&command).await,
+        Commands::GhRunners { command } => cmd_gh_runners(&command).await,
         Commands::Logs { target, lines } => cmd_logs(&target, lines),
-        Commands::Update => cmd_update().await,
-        Commands::Dashboard => cmd_dashboard(cli.verbose).await,
-        Commands::Import { path, target } => cmd_import(&path, target.as_deref()),
-        Commands::Scan { paths, auto_import } => cmd_scan(paths.as_deref(), auto_import),
-    };
+        Commands::Run { command } => cmd_run(&command).await,
+        Commands::Update { instances } => cmd_update(instances.as_deref()).await,
+        Commands::Dashboard { record, replay } => {
+            cmd_dashboard(verbose, record.as_deref(), replay.as_deref()).await
+        }
+        Commands::Report { days } => cmd_report(days),
+        Commands::Metrics {
+            command: Some(command),
+            ..
+        } => cmd_metrics_command(&command),
+        Commands::Metrics {
+            command: None,
+            target,
+            days,
+            format,
+        } => cmd_metrics(target.as_deref(), days, format),
+        Commands::WebhookServer { listen } => cmd_webhook_server(listen).await,
+        Commands::Serve {
+            command:
+                Some(ServeCommand::Install {
+                    interval,
+                    listen,
+                    print,
+                }),
+            ..
+        } => cmd_serve_install(interval, listen, print),
+        Commands::Serve {
+            command: None,
+            interval,
+            listen,
+        } => cmd_serve(interval, listen.as_deref()).await,
+        Commands::Watch { interval } => cmd_watch(interval).await,
+        Commands::Autoscale { interval } => cmd_autoscale(interval).await,
+        Commands::Import {
+            path,
+            target,
+            rename,
+        } => cmd_import(&path, target.as_deref(), rename),
+        Commands::Migrate { paths, yes } => cmd_migrate(paths.as_deref(), yes).await,
+        Commands::Scan(args) => cmd_scan(&args),
+        Commands::Toolchain { target } => cmd_toolchain(target.as_deref()),
+        Commands::Docker { command } => cmd_docker(&command),
+        Commands::OnDemand { command } => cmd_on_demand(&command),
+        Commands::Deps { command } => cmd_deps(&command),
+    }
+}
 
-    if let Err(e) = result {
-        eprintln!("error: {e:#}");
-        std::process::exit(1);
+const DEFAULT_RUNNER_USER: &str = "github";
+
+/// `value`, or else the environment variable `var`; None when empty
+fn flag_or_env(value: Option<String>, var: &str) -> Option<String> {
+    value
+        .or_else(|| std::env::var(var).ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// List the default account and the named ones, marking the selected one
+fn cmd_accounts() {
+    let selected = config::account();
+    let accounts: Vec<(Option<String>, Config)> = std::iter::once(None)
+        .chain(config::list_accounts().into_iter().map(Some))
+        .filter_map(|name| {
+            let config = config::read_account(name.as_deref())?;
+            Some((name, config))
+        })
+        .collect();
+    if accounts.is_empty() {
+        println!("No accounts configured. Run: runner-mgr init");
+        return;
+    }
+    println!(
+        "  {:<20}  {:<20}  {:<30}  API",
+        "ACCOUNT", "GITHUB USER", "INSTANCES"
+    );
+    for (name, config) in accounts {
+        let marker = if name == selected { "*" } else { " " };
+        println!(
+            "{marker} {:<20}  {:<20}  {:<30}  {}",
+            name.as_deref().unwrap_or("(default)"),
+            config.github_user,
+            config.instances_base,
+            config
+                .github_api_url
+                .as_deref()
+                .unwrap_or(github::DEFAULT_API_URL)
+        );
     }
 }
 
 #[allow(clippy::too_many_lines)]
-async fn cmd_init() -> Result<()> {
+async fn cmd_init(args: InitArgs) -> Result<()> {
     println!("runner-mgr init");
     println!("===============");
     println!();
 
     let os = Config::detect_os();
     let arch = Config::detect_arch();
+    let yes = args.yes;
+    let pat_arg = flag_or_env(args.pat, config::PAT_ENV);
+    let runner_user_arg = flag_or_env(args.runner_user, config::RUNNER_USER_ENV);
+    let instances_base_arg = flag_or_env(args.instances_base, config::INSTANCES_BASE_ENV);
 
     // Check for existing PAT
     let mut pat = String::new();
-    if let Ok(existing) = Config::load() {
+    let existing = Config::load().ok();
+    let rootless = args.rootless || existing.as_ref().is_some_and(Config::rootless);
+    if rootless && os == "windows" {
+        anyhow::bail!("Rootless mode is not available on Windows: runners run as services");
+    }
+    let escalation = if rootless {
+        privilege::Escalation::None
+    } else {
+        existing
+            .as_ref()
+            .map_or_else(privilege::Escalation::detect, |c| c.escalation)
+    };
+    if rootless {
+        println!("Rootless mode: everything runs as the current user, without sudo.");
+    }
+    privilege::ensure(
+        escalation,
+        "create the runner directories and install the runner template",
+    )?;
+    let api_url = args
+        .api_url
+        .or_else(|| existing.as_ref().and_then(|c| c.github_api_url.clone()))
+        .map(|url| url.trim_end_matches('/').to_string());
+    let api_root = api_url.as_deref().unwrap_or(github::DEFAULT_API_URL);
+    if let Some(url) = &api_url {
+        println!("GitHub Enterprise Server API: {url}");
+    }
+    let proxy_url = args
+        .proxy_url
+        .or_else(|| existing.as_ref().and_then(|c| c.proxy_url.clone()));
+    let http = github::http_client(proxy_url.as_deref())?;
+    if let Some(url) = &proxy_url {
+        println!("Proxy: {url}");
+    }
+    if let Some(given) = pat_arg {
+        config::validate_pat_format(&given)
+            .with_context(|| format!("Invalid PAT from --pat or {}", config::PAT_ENV))?;
+        pat = given;
+    } else if let Some(existing) = &existing {
         println!("Existing config found.");
-        print!("Replace PAT? [y/N]: ");
-        io::stdout().flush()?;
-        let mut answer = String::new();
-        io::stdin().read_line(&mut answer)?;
-        if answer.trim() != "y" && answer.trim() != "Y" {
-            pat = existing.github_pat;
+        if yes {
+            println!("Keeping the existing PAT.");
+            pat.clone_from(&existing.github_pat);
+        } else {
+            print!("Replace PAT? [y/N]: ");
+            io::stdout().flush()?;
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer)?;
+            if answer.trim() != "y" && answer.trim() != "Y" {
+                pat.clone_from(&existing.github_pat);
+            }
         }
     }
 
-    if pat.is_empty() {
+    let user = if pat.is_empty() {
+        if yes {
+            anyhow::bail!(Failure::Auth(format!(
+                "No PAT to use: pass --pat or set {}",
+                config::PAT_ENV
+            )));
+        }
         println!("Enter a GitHub Personal Access Token (needs 'repo' scope).");
-        println!("Create one at: https://github.com/settings/tokens");
-        print!("PAT: ");
+        println!(
+            "Create one at: {}/settings/tokens",
+            github::web_url_for_api(api_root)
+        );
+        let (entered, user) = prompt_pat(&http, api_root).await?;
+        pat = entered;
+        user
+    } else {
+        println!("Validating token...");
+        GitHubClient::with_http(http.clone(), &pat, api_root)
+            .get_user()
+            .await
+            .context("Invalid token or network error")?
+    };
+    let client = GitHubClient::with_http(http, &pat, api_root);
+    println!("Authenticated as: {}", user.login);
+
+    // Runner user: rootless runners run as whoever runs runner-mgr
+    let runner_user = if rootless {
+        let current = users::current().context("Failed to determine the current user")?;
+        if let Some(user) = runner_user_arg.filter(|user| *user != current) {
+            anyhow::bail!("Rootless runners run as the current user ({current}), not {user}");
+        }
+        println!("Runner user account: {current}");
+        current
+    } else if let Some(runner_user) = runner_user_arg {
+        println!("Runner user account: {runner_user}");
+        runner_user
+    } else if yes {
+        println!("Runner user account: {DEFAULT_RUNNER_USER}");
+        DEFAULT_RUNNER_USER.to_string()
+    } else {
+        print!("Runner user account [{DEFAULT_RUNNER_USER}]: ");
         io::stdout().flush()?;
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        pat = input.trim().to_string();
-        if pat.is_empty() {
-            anyhow::bail!("PAT cannot be empty");
+        let mut runner_user = String::new();
+        io::stdin().read_line(&mut runner_user)?;
+        let runner_user = runner_user.trim();
+        if runner_user.is_empty() {
+            DEFAULT_RUNNER_USER.to_string()
+        } else {
+            runner_user.to_string()
         }
+    };
+    // Windows runners run as the service account chosen by `config.cmd`
+    if !rootless && os != "windows" && !users::exists(&runner_user) {
+        offer_create_user(escalation, &os, &runner_user, yes)?;
     }
 
-    // Validate token
-    println!("Validating token...");
-    let client = GitHubClient::new(&pat);
-    let user = client
-        .get_user()
-        .await
-        .context("Invalid token or network error")?;
-    println!("Authenticated as: {}", user.login);
-
-    // Runner user
-    print!("Runner user account [github]: ");
-    io::stdout().flush()?;
-    let mut runner_user = String::new();
-    io::stdin().read_line(&mut runner_user)?;
-    let runner_user = runner_user.trim();
-    let runner_user = if runner_user.is_empty() {
-        "github".to_string()
+    let instances_base = if let Some(base) = instances_base_arg {
+        base
+    } else if rootless {
+        Config::rootless_instances_base()
+            .to_string_lossy()
+            .into_owned()
     } else {
-        runner_user.to_string()
+        let base = Config::default_instances_base();
+        config::account().map_or_else(|| base.to_string(), |name| format!("{base}-{name}"))
     };
 
-    let instances_base = "/opt/github-runners".to_string();
-
+    let credential_backend = if args.keyring {
+        CredentialBackend::Keyring
+    } else {
+        existing
+            .as_ref()
+            .map(|c| c.credential_backend)
+            .unwrap_or_default()
+    };
     let config = Config {
         github_pat: pat.clone(),
         github_user: user.login,
@@ -201,107 +931,163 @@ async fn cmd_init() -> Result<()> {
         runner_os: os.clone(),
         runner_arch: arch.clone(),
         instances_base: instances_base.clone(),
+        escalation,
+        use_sudo: rootless.then_some(false),
+        github_api_url: api_url,
+        credential_backend,
+        proxy_url,
+        // Keep optional settings from an existing config
+        ..existing.unwrap_or_default()
     };
     config.save().context("Failed to save config")?;
     println!("Config written to {}", Config::config_file().display());
+    if credential_backend == CredentialBackend::Keyring {
+        println!("PAT stored in the OS keychain");
+    }
 
-    // Create instances base directory
-    let base = std::path::Path::new(&instances_base);
-    if !base.exists() {
-        println!("Creating runner instances directory: {instances_base}");
-        let status = std::process::Command::new("sudo")
-            .args(["mkdir", "-p", &instances_base])
-            .status()?;
-        if !status.success() {
-            anyhow::bail!("Failed to create instances directory");
+    if args.skip_download {
+        runner::create_instances_base(&config)?;
+        let listener = config.template_dir().join("bin").join("Runner.Listener");
+        if listener.exists() {
+            runner::validate_template(&config)?;
+        } else {
+            println!("Skipped the runner download; install it later with `runner-mgr update`.");
         }
-        let status = std::process::Command::new("sudo")
-            .args(["chown", &runner_user, &instances_base])
-            .status()?;
-        if !status.success() {
-            anyhow::bail!("Failed to set directory ownership");
+    } else {
+        runner::setup_host(&config, &client).await?;
+    }
+
+    println!();
+    if !yes && runner::list_instances(&config).is_empty() && io::stdin().is_terminal() {
+        if let Err(e) = offer_first_runner(&config).await {
+            eprintln!("error: {e:#}");
+            println!("Setup itself succeeded; register a runner later with `runner-mgr add`.");
         }
     }
 
-    // Download runner binary
-    let template_dir = config.template_dir();
     println!();
-    println!("Downloading latest GitHub Actions runner...");
+    println!("Init complete. Next steps:");
+    println!("  runner-mgr list              # see your repos");
+    println!("  runner-mgr add owner/repo    # register a runner");
+    println!("  runner-mgr dashboard         # open TUI dashboard");
+    if rootless && os == "linux" {
+        println!();
+        println!("User services stop when you log out. To keep runners up, run once:");
+        println!("  loginctl enable-linger {runner_user}");
+    }
 
-    let runner_pkg_os = if os == "darwin" { "osx" } else { "linux" };
+    Ok(())
+}
 
-    let latest_version = client
-        .get_latest_runner_version()
-        .await
-        .context("Failed to fetch latest runner version")?;
+/// Offer to register a runner right after init, so a fresh install ends with a working
+/// runner
+async fn offer_first_runner(config: &Config) -> Result<()> {
+    print!("Register your first runner now? [Y/n]: ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if matches!(answer.trim(), "n" | "N") {
+        return Ok(());
+    }
 
-    let download_url = format!(
-        "https://github.com/actions/runner/releases/download/v{latest_version}/actions-runner-{runner_pkg_os}-{arch}-{latest_version}.tar.gz"
-    );
+    print!("Repository (owner/repo or org:name, Enter to pick from your repositories): ");
+    io::stdout().flush()?;
+    let mut target = String::new();
+    io::stdin().read_line(&mut target)?;
+    let target = match target.trim() {
+        "" => pick_repo(config, None).await?,
+        t => t.to_string(),
+    };
+    let scope = RunnerScope::parse(&target)?;
+
+    print!("Extra labels (comma-separated, optional): ");
+    io::stdout().flush()?;
+    let mut labels = String::new();
+    io::stdin().read_line(&mut labels)?;
 
-    println!("Runner version: {latest_version}");
-    println!("Package: actions-runner-{runner_pkg_os}-{arch}-{latest_version}.tar.gz");
+    println!();
+    runner::add_runner(
+        config,
+        &runner::InstanceId::primary(scope),
+        labels.trim(),
+        None,
+    )
+    .await
+}
 
-    let template_str = template_dir.to_string_lossy().to_string();
-    let status = std::process::Command::new("sudo")
-        .args(["mkdir", "-p", &template_str])
-        .status()?;
-    if !status.success() {
-        anyhow::bail!("Failed to create template directory");
+/// Ask to create a missing runner user (locked password, home directory and,
+/// on Linux, optionally docker group membership). With `yes`, create it without the
+/// docker group and without asking.
+fn offer_create_user(
+    escalation: privilege::Escalation,
+    os: &str,
+    user: &str,
+    yes: bool,
+) -> Result<()> {
+    if !yes {
+        print!("User '{user}' does not exist. Create it? [Y/n]: ");
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if matches!(answer.trim(), "n" | "N") {
+            anyhow::bail!("Runner user '{user}' does not exist; create it and run init again");
+        }
     }
-    let status = std::process::Command::new("sudo")
-        .args(["chown", &runner_user, &template_str])
-        .status()?;
-    if !status.success() {
-        anyhow::bail!("Failed to set template directory ownership");
+
+    let mut docker_group = false;
+    if !yes && os != "darwin" && users::docker_group_exists() {
+        print!("Add '{user}' to the docker group (for container jobs)? [y/N]: ");
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        docker_group = matches!(answer.trim(), "y" | "Y");
     }
 
-    let tarball = format!("/tmp/actions-runner-{runner_pkg_os}-{arch}-{latest_version}.tar.gz");
+    println!("Creating user {user}...");
+    users::create(escalation, os, user, docker_group)?;
+    println!("Created {user} (password locked)");
+    Ok(())
+}
 
-    if std::path::Path::new(&tarball).exists() {
-        println!("Using cached download: {tarball}");
-    } else {
-        println!("Downloading...");
-        let status = std::process::Command::new("curl")
-            .args(["-fSL", "-o", &tarball, &download_url])
-            .status()
-            .context("Failed to download runner binary")?;
-        if !status.success() {
-            anyhow::bail!("Download failed");
-        }
-    }
-
-    println!("Extracting to {template_str}...");
-    let status = std::process::Command::new("sudo")
-        .args([
-            "-u",
-            &runner_user,
-            "tar",
-            "xzf",
-            &tarball,
-            "-C",
-            &template_str,
-        ])
-        .status()
-        .context("Failed to extract runner binary")?;
-    if !status.success() {
-        anyhow::bail!("Extraction failed");
-    }
+const PAT_ATTEMPTS: usize = 3;
 
-    println!();
-    println!("Init complete. Next steps:");
-    println!("  runner-mgr list              # see your repos");
-    println!("  runner-mgr add owner/repo    # register a runner");
-    println!("  runner-mgr dashboard         # open TUI dashboard");
+/// Prompt for a PAT without echoing it, retrying on malformed or rejected tokens
+async fn prompt_pat(http: &reqwest::Client, api_url: &str) -> Result<(String, github::User)> {
+    for attempt in 1..=PAT_ATTEMPTS {
+        let pat =
+            rpassword::prompt_password("PAT (input hidden): ").context("Failed to read PAT")?;
+        let pat = pat.trim();
 
-    Ok(())
+        if let Err(e) = config::validate_pat_format(pat) {
+            eprintln!("  {e}");
+        } else {
+            println!("Validating token...");
+            match GitHubClient::with_http(http.clone(), pat, api_url)
+                .get_user()
+                .await
+            {
+                Ok(user) => return Ok((pat.to_string(), user)),
+                Err(e) => eprintln!("  Invalid token or network error: {e}"),
+            }
+        }
+
+        if attempt < PAT_ATTEMPTS {
+            println!("Please try again ({attempt}/{PAT_ATTEMPTS}).");
+        }
+    }
+    anyhow::bail!(Failure::Auth(format!(
+        "No valid PAT entered after {PAT_ATTEMPTS} attempts"
+    )))
 }
 
 async fn cmd_list() -> Result<()> {
     let config = Config::load()?;
-    let client = GitHubClient::new(&config.github_pat);
+    let client = GitHubClient::for_config(&config);
 
-    println!("Fetching repositories for {}...", config.github_user);
+    println!(
+        "Fetching repositories {} can administer...",
+        config.github_user
+    );
     println!();
 
     let repos = client.list_repos().await?;
@@ -326,7 +1112,7 @@ async fn cmd_list() -> Result<()> {
         let visibility = if repo.private { "private" } else { "public" };
         let runner_status = instances
             .iter()
-            .find(|i| i.scope.to_display() == repo.full_name)
+            .find(|i| i.scope.to_display().eq_ignore_ascii_case(&repo.full_name))
             .map_or_else(|| "-".to_string(), |i| i.status.to_string());
 
         println!(
@@ -338,188 +1124,1583 @@ async fn cmd_list() -> Result<()> {
     Ok(())
 }
 
-async fn cmd_add(target: &str, labels: &str) -> Result<()> {
-    let scope = RunnerScope::parse(target)?;
-    let config = Config::load()?;
-    runner::add_runner(&config, &scope, labels).await
-}
+/// Let the user choose a repo without a runner: one they administer, or one whose
+/// name matches `search`
+async fn pick_repo(config: &Config, search: Option<&str>) -> Result<String> {
+    let client = GitHubClient::for_config(config);
+    let candidates = if let Some(query) = search {
+        println!("Searching repositories matching '{query}'...");
+        let results = client.search_repos(&config.github_user, query).await?;
+        if results.total_count > results.items.len() as u64 {
+            println!(
+                "{} matches; showing the first {}. Refine the query to narrow it down.",
+                results.total_count,
+                results.items.len()
+            );
+        }
+        results.items
+    } else {
+        println!("Fetching repositories you can administer...");
+        client.list_repos().await?
+    };
+    let instances = runner::list_instances(config);
+    let repos: Vec<String> = candidates
+        .into_iter()
+        .filter(|r| !r.archived)
+        .map(|r| r.full_name)
+        .filter(|name| {
+            !instances
+                .iter()
+                .any(|i| i.scope.to_display().eq_ignore_ascii_case(name))
+        })
+        .collect();
+    if repos.is_empty() {
+        anyhow::bail!("No repositories without a runner found. Pass a target explicitly.");
+    }
 
-async fn cmd_remove(target: &str) -> Result<()> {
-    let scope = RunnerScope::parse(target)?;
-    let config = Config::load()?;
-    runner::remove_runner(&config, &scope).await
+    println!();
+    for (i, name) in repos.iter().enumerate() {
+        println!("  {:>3}) {name}", i + 1);
+    }
+    println!();
+    print!("Add a runner for which repository? [1-{}]: ", repos.len());
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let choice: usize = answer
+        .trim()
+        .parse()
+        .ok()
+        .filter(|n| (1..=repos.len()).contains(n))
+        .context("Invalid selection")?;
+    Ok(repos[choice - 1].clone())
+}
+
+/// Which instances `add` registers: `--count` or `--name-suffix`
+struct Instances {
+    count: Option<u32>,
+    name_suffix: Option<String>,
+}
+
+impl Instances {
+    fn resolve(&self, config: &Config, target: &str) -> Result<Vec<runner::InstanceId>> {
+        let id = runner::InstanceId::parse(target)?;
+        match (&self.name_suffix, self.count) {
+            (Some(_), _) if id.name.is_some() => {
+                anyhow::bail!("Give the instance name either as {target} or with --name-suffix")
+            }
+            (Some(suffix), _) => {
+                runner::validate_instance_name(suffix)?;
+                Ok(vec![runner::InstanceId {
+                    scope: id.scope,
+                    name: Some(suffix.clone()),
+                }])
+            }
+            (None, Some(_)) if id.name.is_some() => {
+                anyhow::bail!("--count picks instance names itself; pass the target without @")
+            }
+            (None, Some(count)) => {
+                let existing: Vec<_> = runner::list_instances(config)
+                    .iter()
+                    .map(runner::RunnerInstance::id)
+                    .collect();
+                Ok(runner::next_instance_ids(&existing, &id.scope, count))
+            }
+            (None, None) => Ok(vec![id]),
+        }
+    }
+}
+
+async fn cmd_add(
+    target: Option<&str>,
+    search: Option<&str>,
+    labels: &str,
+    no_auto_labels: bool,
+    profile: Option<&str>,
+    instances: Instances,
+) -> Result<()> {
+    let mut config = Config::load()?;
+    let target = match target {
+        Some(t) => t.to_string(),
+        None => pick_repo(&config, search).await?,
+    };
+    let ids = instances.resolve(&config, &target)?;
+    privilege::ensure(
+        config.escalation,
+        "configure the runner and install its service",
+    )?;
+    if no_auto_labels {
+        config.auto_labels.clear();
+    }
+    let profile = match profile {
+        Some(name) => Some((name, config.profile(name)?.clone())),
+        None => None,
+    };
+    let profile = profile.as_ref().map(|(name, p)| (*name, p));
+    for (i, id) in ids.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        runner::add_runner(&config, id, labels, profile).await?;
+    }
+    Ok(())
+}
+
+async fn cmd_jit(target: &str, labels: &str) -> Result<()> {
+    let config = Config::load()?;
+    let scope = RunnerScope::parse(target)?;
+    privilege::ensure(config.escalation, "run the runner as the runner user")?;
+    runner::run_jit_runner(&config, &scope, labels).await
+}
+
+async fn cmd_bootstrap_script(args: BootstrapArgs) -> Result<()> {
+    let BootstrapArgs {
+        target,
+        labels,
+        arch,
+        user,
+        dir,
+        ephemeral,
+        output,
+    } = args;
+    let scope = RunnerScope::parse(&target)?;
+    let config = Config::load()?;
+    let arch = arch.unwrap_or_else(|| config.runner_arch.clone());
+    if arch != "x64" && arch != "arm64" {
+        anyhow::bail!("Unsupported architecture '{arch}' (expected x64 or arm64)");
+    }
+
+    // Status goes to stderr so the script can be piped or captured
+    let client = GitHubClient::for_config(&config);
+    eprintln!("Requesting registration token for {scope}...");
+    let reg = client.get_registration_token(&scope).await?;
+    let version = client
+        .get_latest_runner_version()
+        .await
+        .context("Failed to fetch latest runner version")?;
+
+    let requested = labels::parse_labels(&labels);
+    let labels = labels::merge_labels(&["self-hosted".to_string()], &requested).join(",");
+    let script = bootstrap::render(&bootstrap::BootstrapOptions {
+        scope,
+        web_url: config.web_url(),
+        token: reg.token,
+        expires_at: reg.expires_at.clone(),
+        labels,
+        arch,
+        version,
+        runner_user: user.unwrap_or_else(|| config.runner_user.clone()),
+        dir,
+        ephemeral,
+    });
+
+    if let Some(path) = output.as_deref() {
+        std::fs::write(path, &script).with_context(|| format!("Failed to write {path}"))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o700))?;
+        }
+        eprintln!("Wrote {path}");
+    } else {
+        print!("{script}");
+    }
+    if let Some(expires) = reg.expires_at {
+        eprintln!("The embedded registration token expires at {expires}.");
+    }
+    Ok(())
+}
+
+async fn cmd_remove(target: &str) -> Result<()> {
+    let config = Config::load()?;
+    let id = target::resolve_one(&runner::list_instances(&config), target)?;
+    privilege::ensure(config.escalation, "stop and uninstall the runner service")?;
+    runner::remove_runner(&config, &id).await
+}
+
+fn cmd_inventory(format: InventoryFormat) -> Result<()> {
+    let config = Config::load()?;
+    let rows = inventory::collect(&config);
+    match format {
+        InventoryFormat::Md => print!("{}", inventory::to_markdown(&rows)),
+        InventoryFormat::Csv => print!("{}", inventory::to_csv(&rows)),
+    }
+    Ok(())
+}
+
+/// Resolve a target to a configured instance for note/tag commands
+fn configured_scope(config: &Config, target: &str) -> Result<RunnerScope> {
+    target::resolve_scope(&runner::list_instances(config), target)
+}
+
+fn cmd_note(target: &str, text: &[String], clear: bool) -> Result<()> {
+    let config = Config::load()?;
+    let scope = configured_scope(&config, target)?;
+    let db = state::StateDb::open()?;
+    if clear {
+        db.set_note(&scope, "")?;
+        println!("Cleared note for {scope}");
+    } else if text.is_empty() {
+        match db.note(&scope)? {
+            Some(note) => println!("{note}"),
+            None => println!("No note for {scope}"),
+        }
+    } else {
+        db.set_note(&scope, &text.join(" "))?;
+        println!("Updated note for {scope}");
+    }
+    Ok(())
+}
+
+fn cmd_tag(target: &str, tags: &[String], remove: bool) -> Result<()> {
+    let config = Config::load()?;
+    let scope = configured_scope(&config, target)?;
+    let db = state::StateDb::open()?;
+    for tag in tags {
+        if remove {
+            db.remove_tag(&scope, tag)?;
+        } else {
+            db.add_tag(&scope, tag)?;
+        }
+    }
+    let current = db.tags(&scope)?;
+    if current.is_empty() {
+        println!("{scope}: no tags");
+    } else {
+        println!("{scope}: {}", current.join(", "));
+    }
+    Ok(())
+}
+
+/// Instances a start/stop/restart target refers to: `owner/repo@name` is one
+/// instance, a bare scope is every instance of it
+fn target_instances(config: &Config, target: &str) -> Result<Vec<runner::InstanceId>> {
+    target::resolve(&runner::list_instances(config), target)
 }
 
 fn cmd_start(target: &str) -> Result<()> {
     let config = Config::load()?;
+    privilege::ensure(config.escalation, "start runner services")?;
     if target == "all" {
         runner::start_all(&config);
         Ok(())
     } else {
-        let scope = RunnerScope::parse(target)?;
-        runner::start_runner(&config, &scope)
+        for id in target_instances(&config, target)? {
+            runner::start_runner(&config, &id)?;
+        }
+        Ok(())
     }
 }
 
 fn cmd_stop(target: &str) -> Result<()> {
     let config = Config::load()?;
+    privilege::ensure(config.escalation, "stop runner services")?;
     if target == "all" {
         runner::stop_all(&config);
         Ok(())
     } else {
-        let scope = RunnerScope::parse(target)?;
-        runner::stop_runner(&config, &scope)
+        for id in target_instances(&config, target)? {
+            runner::stop_runner(&config, &id)?;
+        }
+        Ok(())
     }
 }
 
 fn cmd_restart(target: &str) -> Result<()> {
     let config = Config::load()?;
+    privilege::ensure(config.escalation, "restart runner services")?;
     if target == "all" {
         runner::restart_all(&config);
         Ok(())
     } else {
-        let scope = RunnerScope::parse(target)?;
-        runner::restart_runner(&config, &scope)
+        for id in target_instances(&config, target)? {
+            runner::restart_runner(&config, &id)?;
+        }
+        Ok(())
     }
 }
 
-fn cmd_status() -> Result<()> {
+async fn cmd_reregister(target: &str) -> Result<()> {
+    let config = Config::load()?;
+    privilege::ensure(config.escalation, "register the runner again")?;
+    for id in target_instances(&config, target)? {
+        runner::reregister_runner(&config, &id).await?;
+    }
+    Ok(())
+}
+
+async fn cmd_status(wide: bool) -> Result<()> {
     let config = Config::load()?;
     let instances = runner::list_instances(&config);
+    let unrecognized = runner::list_unrecognized(&config);
+    let state = state::StateDb::open().ok();
+    let remote = state
+        .as_ref()
+        .and_then(|db| db.remote_instances().ok())
+        .unwrap_or_default();
 
     if instances.is_empty() {
+        if remote.is_empty() && unrecognized.is_empty() {
+            println!("No runners configured.");
+        }
+        print_unrecognized(&unrecognized);
+        if !remote.is_empty() {
+            if !unrecognized.is_empty() {
+                println!();
+            }
+            print_remote_instances(&remote);
+        }
+        return Ok(());
+    }
+
+    let client = GitHubClient::for_config(&config);
+    let latest = updates::latest_version(&client).await.ok();
+    let mut records: HashMap<RunnerScope, Vec<github::Runner>> = HashMap::new();
+    for scope in collector::scopes(&instances) {
+        if let Ok(list) = client.list_runners(&scope).await {
+            records.insert(scope, list.runners);
+        }
+    }
+
+    print_status_table(
+        &instances,
+        state.as_ref(),
+        &records,
+        latest.as_deref(),
+        wide,
+    );
+
+    let mut warnings: Vec<_> = instances
+        .iter()
+        .filter_map(|i| {
+            let problem = runner::credential_problem(&i.dir)?;
+            let id = i.id();
+            let hint = runner::reregister_hint(&id);
+            Some((
+                id,
+                format!("credentials: {problem}, the runner can't start ({hint})"),
+            ))
+        })
+        .collect();
+    warnings.extend(instances.iter().filter_map(|i| {
+        let runners = records.get(&i.scope)?;
+        if !runner::registration_missing(i, runners) || runner::credential_problem(&i.dir).is_some()
+        {
+            return None;
+        }
+        let id = i.id();
+        let hint = runner::reregister_hint(&id);
+        Some((
+            id,
+            format!("not registered on GitHub (the registration was deleted; {hint})"),
+        ))
+    }));
+    warnings.extend(label_warnings(&instances, &records));
+    warnings.extend(instances.iter().filter_map(|i| {
+        let failure = runner::pre_start_failure(&config, i)?;
+        let id = i.id();
+        Some((
+            id.clone(),
+            format!("{failure}, the runner can't start (see runner-mgr logs {id})"),
+        ))
+    }));
+    let docker_health = docker::check(&config);
+    let docker_down = matches!(docker_health, docker::DockerHealth::Unreachable { .. });
+    if !warnings.is_empty() || docker_down {
+        println!();
+        println!("Warnings:");
+        for (id, warning) in &warnings {
+            println!("  {id}: {warning}");
+        }
+        if docker_down {
+            println!("  host: {docker_health} (repair: runner-mgr docker restart)");
+        }
+    }
+
+    if !unrecognized.is_empty() {
+        println!();
+        print_unrecognized(&unrecognized);
+    }
+
+    if !remote.is_empty() {
+        println!();
+        print_remote_instances(&remote);
+    }
+
+    Ok(())
+}
+
+/// Directories in the instances directory that runner-mgr doesn't manage, with the
+/// commands that bring them under management
+fn print_unrecognized(dirs: &[runner::UnrecognizedDir]) {
+    if dirs.is_empty() {
+        return;
+    }
+    println!("Unrecognized directories in the instances directory (not managed):");
+    for dir in dirs {
+        let path = dir.path.display();
+        if let Some(scope) = &dir.scope {
+            println!("  {path}: registered for {scope}");
+            println!("    link it:   runner-mgr import {path}");
+            println!("    rename it: runner-mgr import {path} --rename");
+        } else {
+            println!("  {path}: no .runner file, scope unknown");
+            println!("    runner-mgr import {path} --target <owner/repo> [--rename]");
+        }
+    }
+}
+
+/// Runners imported by `scan --remote`, which are managed on their own hosts
+fn print_remote_instances(remote: &[remote::RemoteRunner]) {
+    println!("Remote runners (managed on their hosts):");
+    for runner in remote {
+        let agent = runner
+            .agent_name
+            .as_deref()
+            .map_or(String::new(), |n| format!(" ({n})"));
+        println!(
+            "  {:<40}  {}{agent}",
+            runner.scope.to_display(),
+            runner.location()
+        );
+    }
+}
+
+/// Days of history behind the uptime and failure rate of `status --hosts`
+const HOST_HISTORY_DAYS: i64 = 7;
+
+async fn cmd_doctor() -> Result<()> {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("Config");
+            println!("  {} {e:#}", doctor::Severity::Fail.mark());
+            println!("    fix: run `runner-mgr init`, or correct the file by hand");
+            anyhow::bail!("Some checks failed");
+        }
+    };
+
+    let sections = doctor::run(&config).await;
+    let mut worst = doctor::Severity::Ok;
+    for section in &sections {
+        println!("{}", section.title);
+        for finding in &section.findings {
+            println!("  {} {}", finding.severity.mark(), finding.detail);
+            if let Some(fix) = &finding.fix {
+                println!("    fix: {fix}");
+            }
+            worst = worst.max(finding.severity);
+        }
+        println!();
+    }
+    match worst {
+        doctor::Severity::Fail => anyhow::bail!("Some checks failed"),
+        doctor::Severity::Warn => println!("No failures, but see the warnings above."),
+        doctor::Severity::Ok => println!("Everything looks fine."),
+    }
+    Ok(())
+}
+
+/// Every runner GitHub reports for the configured scopes, grouped by host, with uptime
+/// and failure rate from the metrics recorded by the dashboard
+async fn cmd_status_hosts() -> Result<()> {
+    let config = Config::load()?;
+    let mut scopes: Vec<RunnerScope> = runner::list_instances(&config)
+        .into_iter()
+        .map(|i| i.scope)
+        .collect();
+    scopes.sort_by_key(RunnerScope::to_display);
+    scopes.dedup();
+    if scopes.is_empty() {
         println!("No runners configured.");
         return Ok(());
     }
 
-    println!("{:<40}  {:<10}  {:<20}", "TARGET", "STATUS", "SERVICE");
-    println!("{:<40}  {:<10}  {:<20}", "------", "------", "-------");
+    let client = GitHubClient::for_config(&config);
+    let mut current = Vec::new();
+    for scope in scopes {
+        match client.list_runners(&scope).await {
+            Ok(list) => current.push((scope, list.runners)),
+            Err(e) => eprintln!("warning: failed to list runners for {scope}: {e:#}"),
+        }
+    }
+    let history = metrics::MetricsDb::open()
+        .and_then(|db| db.get_runner_stats(HOST_HISTORY_DAYS))
+        .unwrap_or_default();
 
-    for instance in &instances {
+    for line in metrics::host_table(&metrics::summarize_hosts(&current, &history)) {
+        println!("{line}");
+    }
+    println!();
+    println!("Uptime and failure rate cover the last {HOST_HISTORY_DAYS} days recorded by the dashboard.");
+    Ok(())
+}
+
+/// The instances with their service, version, tags and custom labels on GitHub
+/// Width of the LABELS column of `status` without `--wide`
+const STATUS_LABELS_WIDTH: usize = 30;
+
+fn print_status_table(
+    instances: &[runner::RunnerInstance],
+    state: Option<&state::StateDb>,
+    records: &HashMap<RunnerScope, Vec<github::Runner>>,
+    latest: Option<&str>,
+    wide: bool,
+) {
+    println!(
+        "{:<40}  {:<10}  {:<20}  {:<26}  {:<16}  LABELS",
+        "TARGET", "STATUS", "SERVICE", "VERSION", "TAGS"
+    );
+    println!(
+        "{:<40}  {:<10}  {:<20}  {:<26}  {:<16}  ------",
+        "------", "------", "-------", "-------", "----"
+    );
+
+    let list_or_dash = |items: Vec<String>| {
+        if items.is_empty() {
+            "-".to_string()
+        } else {
+            items.join(",")
+        }
+    };
+    for instance in instances {
         let svc = instance.service_name.as_deref().unwrap_or("-");
+        let tags = state
+            .and_then(|db| db.tags(&instance.scope).ok())
+            .unwrap_or_default();
+        let version = runner::installed_version(&instance.dir);
+        // Labels as GitHub has them: custom ones fitted to the column, or all with --wide
+        let record = records
+            .get(&instance.scope)
+            .and_then(|runners| github::find_runner(runners, instance.runner_name.as_deref()));
+        let labels: Vec<&str> = record
+            .map(|record| {
+                record
+                    .labels
+                    .iter()
+                    .filter(|l| wide || l.is_custom())
+                    .map(|l| l.name.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let labels = if labels.is_empty() {
+            "-".to_string()
+        } else if wide {
+            labels.join(",")
+        } else {
+            labels::fit_labels(&labels, STATUS_LABELS_WIDTH)
+        };
         println!(
-            "{:<40}  {:<10}  {:<20}",
-            instance.scope, instance.status, svc
+            "{:<40}  {:<10}  {:<20}  {:<26}  {:<16}  {}",
+            instance.id(),
+            instance.status,
+            svc,
+            updates::version_label(version.as_deref(), latest),
+            list_or_dash(tags),
+            labels
         );
     }
+}
+
+/// Compare each instance's registered labels with this host and with the
+/// runner record in `records` (the scopes whose runners could be fetched)
+fn label_warnings(
+    instances: &[runner::RunnerInstance],
+    records: &HashMap<RunnerScope, Vec<github::Runner>>,
+) -> Vec<(runner::InstanceId, String)> {
+    let host_os = Config::detect_os();
+    let host_arch = Config::detect_arch();
+
+    let mut warnings = Vec::new();
+    for instance in instances {
+        let registered = runner::read_labels(&instance.dir);
+        let record = records
+            .get(&instance.scope)
+            .and_then(|runners| github::find_runner(runners, instance.runner_name.as_deref()));
+        for w in labels::runner_mismatches(&registered, record, &host_os, &host_arch) {
+            warnings.push((instance.id(), w));
+        }
+    }
+    warnings
+}
+
+/// Labels in use anywhere in the fleet: registered on an instance, in a profile, or
+/// added automatically. `labels audit` flags misspellings of them.
+fn fleet_labels(config: &Config, instances: &[runner::RunnerInstance]) -> Vec<String> {
+    let hostname = hostname::get().map_or_else(
+        |_| "runner".to_string(),
+        |h| h.to_string_lossy().to_string(),
+    );
+    let mut known: Vec<String> = instances
+        .iter()
+        .flat_map(|i| runner::read_labels(&i.dir))
+        .collect();
+    known.extend(config.profiles.values().flat_map(|p| p.labels.clone()));
+    known.extend(labels::expand_auto_labels(
+        &config.auto_labels,
+        &Config::detect_os(),
+        &Config::detect_arch(),
+        &hostname,
+    ));
+    known
+}
+
+async fn cmd_gh_runners(command: &GhRunnersCommand) -> Result<()> {
+    match command {
+        GhRunnersCommand::List { target } => cmd_gh_runners_list(target).await,
+        GhRunnersCommand::Delete {
+            target,
+            runners,
+            offline,
+            yes,
+        } => cmd_gh_runners_delete(target, runners, *offline, *yes).await,
+    }
+}
 
+async fn cmd_gh_runners_list(target: &str) -> Result<()> {
+    let config = Config::load()?;
+    let instances = runner::list_instances(&config);
+    let scopes = if target == "all" {
+        collector::scopes(&instances)
+    } else {
+        vec![RunnerScope::parse(target)?]
+    };
+    if scopes.is_empty() {
+        println!("No runners configured. Name a scope: runner-mgr gh-runners list owner/repo");
+        return Ok(());
+    }
+
+    let client = GitHubClient::for_config(&config);
+    for (i, scope) in scopes.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let list = client.list_runners(scope).await?;
+        println!("{scope}");
+        if list.runners.is_empty() {
+            println!("  No runners registered");
+            continue;
+        }
+        println!(
+            "  {:<12}  {:<32}  {:<8}  {:<4}  {:<30}  LABELS",
+            "ID", "NAME", "STATUS", "BUSY", "INSTANCE"
+        );
+        let local: Vec<_> = instances
+            .iter()
+            .filter(|instance| instance.scope == *scope)
+            .cloned()
+            .collect();
+        for record in &list.runners {
+            let instance = orphans::local_instance(record, &local)
+                .map_or("-".to_string(), |instance| instance.id().to_string());
+            let custom: Vec<&str> = record
+                .labels
+                .iter()
+                .filter(|l| l.is_custom())
+                .map(|l| l.name.as_str())
+                .collect();
+            println!(
+                "  {:<12}  {:<32}  {:<8}  {:<4}  {instance:<30}  {}",
+                record.id,
+                record.name,
+                record.status,
+                if record.busy { "yes" } else { "no" },
+                if custom.is_empty() {
+                    "-".to_string()
+                } else {
+                    labels::fit_labels(&custom, STATUS_LABELS_WIDTH)
+                }
+            );
+        }
+    }
     Ok(())
 }
 
-fn cmd_logs(target: &str, lines: u32) -> Result<()> {
+async fn cmd_gh_runners_delete(
+    target: &str,
+    names: &[String],
+    offline: bool,
+    yes: bool,
+) -> Result<()> {
+    let config = Config::load()?;
     let scope = RunnerScope::parse(target)?;
+    let local: Vec<_> = runner::list_instances(&config)
+        .into_iter()
+        .filter(|i| i.scope == scope)
+        .collect();
+    let client = GitHubClient::for_config(&config);
+    let list = client.list_runners(&scope).await?;
+    let selection = orphans::select(&list.runners, &local, names, offline)?;
+
+    for (record, why) in &selection.skipped {
+        println!("Skipping {} (ID {}): {why}", record.name, record.id);
+    }
+    if selection.delete.is_empty() {
+        println!("Nothing to delete from {scope}.");
+        return Ok(());
+    }
+    println!("Runners to delete from {scope}:");
+    for record in &selection.delete {
+        println!("  {} (ID {}, {})", record.name, record.id, record.status);
+    }
+    if !yes {
+        print!(
+            "Delete {} runner(s) from GitHub? [y/N]: ",
+            selection.delete.len()
+        );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y") {
+            println!("Nothing changed.");
+            return Ok(());
+        }
+    }
+
+    let mut failed = 0;
+    for record in &selection.delete {
+        match client.delete_runner(&scope, record.id).await {
+            Ok(()) => println!("Deleted {}", record.name),
+            Err(e) => {
+                eprintln!("Failed to delete {}: {e:#}", record.name);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{failed} runner(s) could not be deleted");
+    }
+    Ok(())
+}
+
+async fn cmd_labels(command: &LabelsCommand) -> Result<()> {
+    match command {
+        LabelsCommand::Audit { fix } => cmd_labels_audit(*fix).await,
+        LabelsCommand::Add { target, labels } => {
+            cmd_labels_edit(LabelEdit::Add, target, labels).await
+        }
+        LabelsCommand::Remove { target, labels } => {
+            cmd_labels_edit(LabelEdit::Remove, target, labels).await
+        }
+        LabelsCommand::Set { target, labels } => {
+            cmd_labels_edit(LabelEdit::Set, target, labels).await
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LabelEdit {
+    Add,
+    Remove,
+    Set,
+}
+
+async fn cmd_labels_edit(edit: LabelEdit, target: &str, labels: &str) -> Result<()> {
+    let config = Config::load()?;
+    let requested = labels::parse_labels(labels);
+    if requested.is_empty() && edit != LabelEdit::Set {
+        anyhow::bail!("No labels given");
+    }
+    let all = runner::list_instances(&config);
+    let instances: Vec<&runner::RunnerInstance> = if target == "all" {
+        all.iter().collect()
+    } else {
+        let ids = target::resolve(&all, target)?;
+        all.iter().filter(|i| ids.contains(&i.id())).collect()
+    };
+
+    let client = GitHubClient::for_config(&config);
+    let mut failed = 0;
+    for instance in &instances {
+        let id = instance.id();
+        match edit_runner_labels(&config, &client, instance, edit, &requested).await {
+            Ok(custom) if custom.is_empty() => println!("{id}: no custom labels"),
+            Ok(custom) => println!("{id}: {}", custom.join(",")),
+            Err(e) => {
+                eprintln!("{id}: {e:#}");
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "Failed to change the labels of {failed} of {} runner(s)",
+            instances.len()
+        );
+    }
+    Ok(())
+}
+
+/// Change the labels of `instance`'s runner on GitHub and record them in its `.labels`.
+/// Returns its custom labels afterwards.
+async fn edit_runner_labels(
+    config: &Config,
+    client: &GitHubClient,
+    instance: &runner::RunnerInstance,
+    edit: LabelEdit,
+    requested: &[String],
+) -> Result<Vec<String>> {
+    let scope = &instance.scope;
+    let list = client.list_runners(scope).await?;
+    let record = github::find_runner(&list.runners, instance.runner_name.as_deref())
+        .context("not registered on GitHub")?;
+    let updated = match edit {
+        LabelEdit::Add => {
+            client
+                .add_runner_labels(scope, record.id, requested)
+                .await?
+        }
+        LabelEdit::Set => {
+            client
+                .set_runner_labels(scope, record.id, requested)
+                .await?
+        }
+        LabelEdit::Remove => {
+            // Check every label before removing any
+            let mut remove = Vec::new();
+            for label in requested {
+                match record
+                    .labels
+                    .iter()
+                    .find(|l| l.name.eq_ignore_ascii_case(label))
+                {
+                    None => println!("{}: no label '{label}'", instance.id()),
+                    Some(l) if !l.is_custom() => {
+                        anyhow::bail!("'{}' is assigned by GitHub and can't be removed", l.name)
+                    }
+                    Some(l) => remove.push(l.name.as_str()),
+                }
+            }
+            let mut updated = None;
+            for label in remove {
+                updated = Some(client.remove_runner_label(scope, record.id, label).await?);
+            }
+            match updated {
+                Some(updated) => updated,
+                None => return Ok(custom_labels(&record.labels)),
+            }
+        }
+    };
+
+    let custom = custom_labels(&updated.labels);
+    let registered = labels::registered_after_edit(&runner::read_labels(&instance.dir), &custom);
+    runner::write_labels(config, &instance.dir, &registered)?;
+    Ok(custom)
+}
+
+fn custom_labels(labels: &[github::RunnerLabel]) -> Vec<String> {
+    labels
+        .iter()
+        .filter(|l| l.is_custom())
+        .map(|l| l.name.clone())
+        .collect()
+}
+
+async fn cmd_labels_audit(fix: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instances = runner::list_instances(&config);
+    if instances.is_empty() {
+        println!("No runners configured.");
+        return Ok(());
+    }
+
+    let known = fleet_labels(&config, &instances);
+
+    let client = GitHubClient::for_config(&config);
+    let mut runner_lists: HashMap<RunnerScope, Vec<github::Runner>> = HashMap::new();
+    let (mut flagged, mut fixed) = (0, 0);
+    for instance in &instances {
+        let id = instance.id();
+        if !runner_lists.contains_key(&instance.scope) {
+            match client.list_runners(&instance.scope).await {
+                Ok(list) => {
+                    runner_lists.insert(instance.scope.clone(), list.runners);
+                }
+                Err(e) => {
+                    eprintln!(
+                        "warning: failed to list runners for {}: {e:#}",
+                        instance.scope
+                    );
+                    continue;
+                }
+            }
+        }
+        let Some(record) = github::find_runner(
+            &runner_lists[&instance.scope],
+            instance.runner_name.as_deref(),
+        ) else {
+            println!("{id}: not registered on GitHub");
+            continue;
+        };
+
+        let github_labels: Vec<String> = record.labels.iter().map(|l| l.name.clone()).collect();
+        let registered = runner::read_labels(&instance.dir);
+        let audit = labels::audit_labels(&github_labels, &registered, &known);
+        if audit.is_clean() {
+            println!("{id}: ok");
+            continue;
+        }
+        flagged += 1;
+        println!("{id} ({}):", record.name);
+        for finding in audit.findings() {
+            println!("  {finding}");
+        }
+
+        let fixable =
+            audit.missing_self_hosted || !audit.typos.is_empty() || !audit.missing.is_empty();
+        if fix && fixable {
+            let custom: Vec<String> = record
+                .labels
+                .iter()
+                .filter(|l| l.is_custom())
+                .map(|l| l.name.clone())
+                .collect();
+            let labels = audit.fixed_labels(&custom, &github_labels);
+            match client
+                .set_runner_labels(&instance.scope, record.id, &labels)
+                .await
+            {
+                Ok(_) => {
+                    fixed += 1;
+                    println!("  fixed: custom labels are now {}", labels.join(","));
+                }
+                Err(e) => eprintln!("  error: failed to update labels: {e:#}"),
+            }
+        }
+    }
+
+    println!();
+    if flagged == 0 {
+        println!("All {} runners have consistent labels.", instances.len());
+    } else if fix {
+        println!(
+            "{flagged} of {} runners had label problems; fixed {fixed}.",
+            instances.len()
+        );
+    } else {
+        println!(
+            "{flagged} of {} runners have label problems. Run 'runner-mgr labels audit --fix' to \
+             correct typos and add missing labels.",
+            instances.len()
+        );
+    }
+    Ok(())
+}
+
+fn cmd_logs(target: &str, lines: u32) -> Result<()> {
     let config = Config::load()?;
-    let logs = runner::get_runner_logs(&config, &scope, lines)?;
+    let id = target::resolve_one(&runner::list_instances(&config), target)?;
+    if config.runner_os == "linux" {
+        privilege::ensure(config.escalation, "read the runner service journal")?;
+    }
+    let logs = runner::get_runner_logs(&config, &id, lines)?;
     println!("{logs}");
     Ok(())
 }
 
-async fn cmd_update() -> Result<()> {
+async fn cmd_run(command: &RunCommand) -> Result<()> {
+    let config = Config::load()?;
+    let client = GitHubClient::for_config(&config);
+    let (RunCommand::Cancel { repo, run_id } | RunCommand::Rerun { repo, run_id }) = command;
+    let RunnerScope::Repository { owner, repo } = RunnerScope::parse(repo)? else {
+        anyhow::bail!("Workflow runs belong to a repository: use owner/repo");
+    };
+    match command {
+        RunCommand::Cancel { .. } => {
+            client.cancel_workflow_run(&owner, &repo, *run_id).await?;
+            println!("Requested cancellation of run {run_id} in {owner}/{repo}");
+        }
+        RunCommand::Rerun { .. } => {
+            client.rerun_workflow_run(&owner, &repo, *run_id).await?;
+            println!("Re-running run {run_id} in {owner}/{repo}");
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_update(instances: Option<&str>) -> Result<()> {
     let config = Config::load()?;
-    let client = GitHubClient::new(&config.github_pat);
+    let client = GitHubClient::for_config(&config);
 
     println!("Checking for runner updates...");
 
     let latest_version = client.get_latest_runner_version().await?;
     println!("Latest:  {latest_version}");
 
-    let runner_pkg_os = if config.runner_os == "darwin" {
-        "osx"
-    } else {
-        "linux"
+    let outdated = match instances {
+        Some(target) => outdated_instances(&config, target, &latest_version)?,
+        None => Vec::new(),
     };
+    // In-place updates copy from the template, which is only replaced when outdated
+    let keep_template = instances.is_some()
+        && runner::installed_version(&config.template_dir())
+            .is_some_and(|v| !updates::is_outdated(&v, &latest_version));
+    if instances.is_some() && outdated.is_empty() && keep_template {
+        println!("The template and the selected instances already run {latest_version}");
+        return Ok(());
+    }
 
-    let download_url = format!(
-        "https://github.com/actions/runner/releases/download/v{}/actions-runner-{}-{}-{}.tar.gz",
-        latest_version, runner_pkg_os, config.runner_arch, latest_version
-    );
-
-    print!("Update template to {latest_version}? [y/N]: ");
+    let question = match (instances, keep_template) {
+        (None, _) => format!("Update template to {latest_version}?"),
+        (Some(_), true) => format!("Update {} instance(s) to {latest_version}?", outdated.len()),
+        (Some(_), false) => format!(
+            "Update the template and {} instance(s) to {latest_version}?",
+            outdated.len()
+        ),
+    };
+    print!("{question} [y/N]: ");
     io::stdout().flush()?;
     let mut confirm = String::new();
     io::stdin().read_line(&mut confirm)?;
     if confirm.trim() != "y" && confirm.trim() != "Y" {
         return Ok(());
     }
+    privilege::ensure(config.escalation, "update the runner binaries")?;
+
+    if !keep_template {
+        let archive = runner::download_runner_package(&config, &client, &latest_version).await?;
+        println!("Updating template...");
+        runner::replace_template(&config, &archive)?;
+        runner::validate_template(&config)?;
+        println!("Template updated to {latest_version}");
+    }
 
-    let tarball = format!(
-        "/tmp/actions-runner-{}-{}-{}.tar.gz",
-        runner_pkg_os, config.runner_arch, latest_version
-    );
+    if instances.is_none() {
+        println!();
+        println!("Note: Existing instances are NOT updated. To update them in place:");
+        println!("  runner-mgr update --instances [owner/repo|all]");
+        return Ok(());
+    }
 
-    println!("Downloading runner {latest_version}...");
-    let status = std::process::Command::new("curl")
-        .args(["-fSL", "-o", &tarball, &download_url])
-        .status()
-        .context("Failed to download runner")?;
-    if !status.success() {
-        anyhow::bail!("Download failed");
+    let mut failed = 0;
+    for instance in &outdated {
+        println!();
+        println!("Updating {}...", instance.id());
+        match runner::update_instance(&config, instance) {
+            Ok(()) => println!("{} updated to {latest_version}", instance.id()),
+            Err(e) => {
+                eprintln!("{}: {e:#}", instance.id());
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{failed} of {} instance(s) failed to update",
+            outdated.len()
+        );
     }
+    Ok(())
+}
+
+/// Instances selected by `target` that run an older runner than `latest` (or one whose
+/// version can't be read)
+fn outdated_instances(
+    config: &Config,
+    target: &str,
+    latest: &str,
+) -> Result<Vec<runner::RunnerInstance>> {
+    let all = runner::list_instances(config);
+    let selected: Vec<runner::RunnerInstance> = if target == "all" {
+        all
+    } else {
+        let ids = target::resolve(&all, target)?;
+        all.into_iter().filter(|i| ids.contains(&i.id())).collect()
+    };
+    let mut outdated = Vec::new();
+    for instance in selected {
+        match runner::installed_version(&instance.dir) {
+            Some(version) if !updates::is_outdated(&version, latest) => {
+                println!("{}: already {version}", instance.id());
+            }
+            version => {
+                println!(
+                    "{}: {} -> {latest}",
+                    instance.id(),
+                    version.as_deref().unwrap_or("unknown version")
+                );
+                outdated.push(instance);
+            }
+        }
+    }
+    Ok(outdated)
+}
+
+async fn cmd_webhook_server(listen: Option<String>) -> Result<()> {
+    let config = Config::load()?;
+    let settings = config.webhook.clone().unwrap_or_default();
+    let secret = std::env::var(webhook::SECRET_ENV)
+        .ok()
+        .or(settings.secret)
+        .filter(|s| !s.is_empty())
+        .with_context(|| {
+            format!(
+                "No webhook secret configured. Set [webhook] secret in {} or {}",
+                Config::config_file().display(),
+                webhook::SECRET_ENV
+            )
+        })?;
+    let listen = listen.unwrap_or(settings.listen);
+    webhook::serve(config, &listen, secret).await
+}
 
-    let template_str = config.template_dir().to_string_lossy().to_string();
+async fn cmd_serve(interval: Option<u64>, listen: Option<&str>) -> Result<()> {
+    let config = Config::load()?;
+    let interval = match interval {
+        Some(0) => anyhow::bail!("--interval must be at least 1 second"),
+        Some(secs) => std::time::Duration::from_secs(secs),
+        None => config.refresh_interval(),
+    };
+    collector::run(&config, interval, listen).await
+}
 
-    println!("Updating template...");
-    let status = std::process::Command::new("sudo")
-        .args(["rm", "-rf", &template_str])
-        .status()?;
-    if !status.success() {
-        anyhow::bail!("Failed to remove old template");
+fn cmd_serve_install(interval: Option<u64>, listen: Option<String>, print: bool) -> Result<()> {
+    let config = Config::load()?;
+    if interval == Some(0) {
+        anyhow::bail!("--interval must be at least 1 second");
+    }
+    let exe = std::env::current_exe().context("Failed to locate the runner-mgr binary")?;
+    let unit = systemd::ServeUnit {
+        exe,
+        account: config::account(),
+        // A system unit runs as the installing user, whose config and metrics it reads
+        user: if config.rootless() || privilege::is_root() {
+            None
+        } else {
+            users::current()
+        },
+        interval,
+        listen,
+        user_unit: config.rootless(),
     }
+    .render();
+    if print {
+        print!("{unit}");
+        return Ok(());
+    }
+    let path = systemd::install_serve(&config, &unit)?;
+    let scope = if config.rootless() { " --user" } else { "" };
+    let name = systemd::serve_unit_name(config::account().as_deref());
+    println!("Installed {} and started it", path.display());
+    println!("  Status: systemctl{scope} status {name}");
+    println!("  Logs:   journalctl{scope} -u {name}");
+    Ok(())
+}
 
-    let status = std::process::Command::new("sudo")
-        .args(["mkdir", "-p", &template_str])
-        .status()?;
-    if !status.success() {
-        anyhow::bail!("Failed to create template directory");
+async fn cmd_watch(interval: u64) -> Result<()> {
+    let config = Config::load()?;
+    if interval == 0 {
+        anyhow::bail!("--interval must be at least 1 second");
     }
+    privilege::ensure(config.escalation, "restart runner services")?;
+    watchdog::run(&config, std::time::Duration::from_secs(interval)).await
+}
 
-    let status = std::process::Command::new("sudo")
-        .args(["chown", &config.runner_user, &template_str])
-        .status()?;
-    if !status.success() {
-        anyhow::bail!("Failed to set ownership");
+async fn cmd_autoscale(interval: u64) -> Result<()> {
+    let config = Config::load()?;
+    if interval == 0 {
+        anyhow::bail!("--interval must be at least 1 second");
     }
+    privilege::ensure(config.escalation, "start and stop runner services")?;
+    autoscale::run(&config, std::time::Duration::from_secs(interval)).await
+}
 
-    let status = std::process::Command::new("sudo")
-        .args([
-            "-u",
-            &config.runner_user,
-            "tar",
-            "xzf",
-            &tarball,
-            "-C",
-            &template_str,
-        ])
-        .status()?;
-    if !status.success() {
-        anyhow::bail!("Extraction failed");
+fn cmd_report(days: i32) -> Result<()> {
+    let config = Config::load()?;
+    let db = metrics::MetricsDb::open()?;
+    // Scopes with objectives are listed even before anything was recorded for them
+    let mut scopes: Vec<String> = db.get_recorded_scopes()?;
+    scopes.extend(config.slo.keys().cloned());
+    // Scope names are case-insensitive: one row per scope however it was written
+    scopes.sort_by_key(|id| id.to_ascii_lowercase());
+    scopes.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+
+    let mut rows = Vec::new();
+    for id in scopes {
+        let Ok(scope) = RunnerScope::parse(&id) else {
+            continue;
+        };
+        let mut scope_metrics = db.get_scope_metrics(&scope, days)?;
+        if let Some(slo) = config.slo_for(&scope) {
+            scope_metrics.slo = db.get_slo_status(&scope, slo)?;
+        }
+        rows.push((id, scope_metrics));
     }
+    print!("{}", metrics::report::render(&rows, days));
+    Ok(())
+}
 
-    println!("Template updated to {latest_version}");
-    println!();
-    println!("Note: Existing instances are NOT updated. To update a runner:");
-    println!("  runner-mgr remove owner/repo && runner-mgr add owner/repo");
+fn cmd_metrics(target: Option<&str>, days: i32, format: MetricsFormat) -> Result<()> {
+    let db = metrics::MetricsDb::open()?;
+    let scopes = if let Some(target) = target {
+        vec![RunnerScope::parse(target)?]
+    } else {
+        let mut ids = db.get_recorded_scopes()?;
+        ids.sort_by_key(|id| id.to_ascii_lowercase());
+        ids.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        ids.iter()
+            .filter_map(|id| RunnerScope::parse(id).ok())
+            .collect()
+    };
 
+    let mut rows = Vec::new();
+    for scope in scopes {
+        let scope_metrics = db.get_scope_metrics(&scope, days)?;
+        rows.push((scope.to_display(), scope_metrics));
+    }
+    let out = match format {
+        MetricsFormat::Table => metrics::report::render_metrics(&rows, days),
+        MetricsFormat::Json => metrics::report::to_json(&rows, days),
+        MetricsFormat::Csv => metrics::report::to_csv(&rows),
+    };
+    print!("{out}");
     Ok(())
 }
 
-async fn cmd_dashboard(verbose: bool) -> Result<()> {
-    let config = Config::load()?;
+fn cmd_metrics_command(command: &MetricsCommand) -> Result<()> {
+    match command {
+        MetricsCommand::Prune { days, vacuum } => {
+            let retention = match days {
+                Some(days) => *days,
+                None => Config::load().unwrap_or_default().metrics_retention_days(),
+            };
+            if retention == 0 {
+                anyhow::bail!("retention_days is 0 (keep everything); pass --days to prune");
+            }
+            let before = metrics::MetricsDb::file_size();
+            let db = metrics::MetricsDb::open_unpruned()?;
+            let deleted = db.prune(retention)?;
+            if deleted.is_empty() {
+                println!("No metrics older than {retention} days.");
+            }
+            for (table, rows) in &deleted {
+                println!("Deleted {rows} rows from {table}");
+            }
+            if *vacuum {
+                db.vacuum()?;
+                println!(
+                    "Database size: {} KiB -> {} KiB",
+                    before / 1024,
+                    metrics::MetricsDb::file_size() / 1024
+                );
+            }
+            Ok(())
+        }
+        MetricsCommand::MigrateScope { old, new } => {
+            let from = RunnerScope::parse(old)?;
+            let to = RunnerScope::parse(new)?;
+            let db = metrics::MetricsDb::open()?;
+            let moved = db.migrate_scope(&from, &to)?;
+            if moved == 0 {
+                println!("No metrics recorded for {}.", from.to_display());
+            } else {
+                println!(
+                    "Moved {moved} metrics rows from {} to {}.",
+                    from.to_display(),
+                    to.to_display()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn cmd_dashboard(verbose: bool, record: Option<&Path>, replay: Option<&Path>) -> Result<()> {
+    if let Some(dir) = replay {
+        recording::start_replay(dir)?;
+        // Whoever made the recording, its runners are shown: no setup, token or sudo
+        let config = Config::load().unwrap_or_default();
+        return tui::run_dashboard(config, verbose).await;
+    }
+    if let Some(dir) = record {
+        recording::start_recording(dir)?;
+    }
+    let config = if Config::config_file().exists() {
+        Config::load()?
+    } else {
+        // First run: collect settings in the TUI instead of bailing
+        let Some(config) = tui::run_onboarding().await? else {
+            println!(
+                "Setup cancelled. Run `runner-mgr init` or `runner-mgr dashboard` to try again."
+            );
+            return Ok(());
+        };
+        config.save().context("Failed to save config")?;
+        println!("Config written to {}", Config::config_file().display());
+        privilege::ensure(
+            config.escalation,
+            "create the runner directories and install the runner template",
+        )?;
+        let client = GitHubClient::for_config(&config);
+        runner::setup_host(&config, &client).await?;
+        config
+    };
+    // Authenticate before entering raw mode: sudo can't prompt inside the TUI
+    if let Err(e) = privilege::ensure(
+        config.escalation,
+        "start, stop and restart runners from the dashboard",
+    ) {
+        eprintln!("warning: {e:#}");
+        eprintln!("Runner actions in the dashboard will fail.");
+    }
     tui::run_dashboard(config, verbose).await
 }
 
-fn cmd_import(path: &str, target: Option<&str>) -> Result<()> {
+fn cmd_import(path: &str, target: Option<&str>, rename: bool) -> Result<()> {
+    let config = Config::load()?;
+    privilege::ensure(
+        config.escalation,
+        "move the runner into the instances directory",
+    )?;
+    if rename {
+        runner::rename_into_instance(&config, path, target)
+    } else {
+        runner::import_runner(&config, path, target)
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+async fn cmd_migrate(extra_paths: Option<&str>, yes: bool) -> Result<()> {
+    let config = Config::load()?;
+
+    println!("Scanning for existing runner directories...");
+    let managed = runner::list_instances(&config);
+    let legacy = runner::legacy_instance_dirs(&config);
+    let unmanaged: Vec<_> = runner::scan_for_runners(extra_paths)
+        .into_iter()
+        .filter(|r| !managed.iter().any(|i| i.scope == r.scope))
+        .filter(|r| !legacy.iter().any(|l| l.id.scope == r.scope))
+        .collect();
+    if unmanaged.is_empty() && legacy.is_empty() {
+        println!("No unmanaged runners found. Use --paths to scan other directories.");
+        return Ok(());
+    }
+
+    // Instances named before scope names were escaped are linked under their new name
+    for dir in &legacy {
+        println!();
+        println!("{}", dir.id);
+        println!("  Path: {}", dir.path.display());
+        println!(
+            "  - Symlink {} -> {} (old-style instance name; not moved)",
+            dir.id.dir(&config).display(),
+            dir.path.display()
+        );
+    }
+
+    // Explain every import before changing anything
+    let mut plans = Vec::new();
+    for found in &unmanaged {
+        println!();
+        println!("{}", found.scope);
+        println!("  Path: {}", found.path.display());
+        let scope = found.scope.to_display();
+        match runner::plan_import(&config, &found.path.to_string_lossy(), Some(&scope)) {
+            Ok(plan) => {
+                for change in plan.describe() {
+                    println!("  - {change}");
+                }
+                plans.push(plan);
+            }
+            Err(e) => println!("  Skipped: {e:#}"),
+        }
+    }
+    if plans.is_empty() && legacy.is_empty() {
+        anyhow::bail!("None of the discovered runners can be imported");
+    }
+
+    println!();
+    if !yes {
+        print!("Import {} runner(s)? [y/N]: ", plans.len() + legacy.len());
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y") {
+            println!("Nothing changed.");
+            return Ok(());
+        }
+    }
+    privilege::ensure(
+        config.escalation,
+        "link the runners into the instances directory",
+    )?;
+
+    let mut imported = Vec::new();
+    for dir in &legacy {
+        println!();
+        println!("Linking {}...", dir.id);
+        match runner::relink_legacy_dir(&config, dir) {
+            Ok(()) => imported.push(dir.id.scope.clone()),
+            Err(e) => eprintln!("  Failed to link {}: {e:#}", dir.id),
+        }
+    }
+    for plan in &plans {
+        println!();
+        match runner::apply_import(&config, plan) {
+            Ok(()) => imported.push(plan.scope.clone()),
+            Err(e) => eprintln!("  Failed to import {}: {e:#}", plan.scope),
+        }
+    }
+
+    println!();
+    println!("Verifying migrated runners...");
+    let client = GitHubClient::for_config(&config);
+    let mut healthy = 0;
+    for scope in &imported {
+        let checks = migrate::verify(&config, &client, scope).await;
+        println!();
+        println!("{scope}");
+        for check in &checks {
+            let mark = if check.passed { "✓" } else { "✗" };
+            println!("  {mark} {:<9} {}", check.name, check.detail);
+        }
+        if checks.iter().all(|c| c.passed) {
+            healthy += 1;
+        }
+    }
+
+    println!();
+    let total = plans.len() + legacy.len();
+    let failed = total - imported.len();
+    println!(
+        "Migrated {} of {total} runner(s); {healthy} fully working.",
+        imported.len(),
+    );
+    if failed > 0 || healthy < imported.len() {
+        anyhow::bail!("Some runners need attention (see above)");
+    }
+    Ok(())
+}
+
+/// Search remote hosts for runner directories over SSH; `--auto-import` records them in
+/// the state database as remote-managed instances
+fn cmd_scan_remote(
+    configured: bool,
+    extra_hosts: &[String],
+    depth: u32,
+    auto_import: bool,
+) -> Result<()> {
     let config = Config::load()?;
-    runner::import_runner(&config, path, target)
+    let mut hosts = if configured {
+        config.remote_hosts.clone()
+    } else {
+        Vec::new()
+    };
+    for host in extra_hosts {
+        if !hosts.contains(host) {
+            hosts.push(host.clone());
+        }
+    }
+    if hosts.is_empty() {
+        anyhow::bail!(
+            "No remote hosts configured. Add remote_hosts to {} or pass --host",
+            Config::config_file().display()
+        );
+    }
+
+    println!();
+    println!("Scanning {} remote host(s) over SSH...", hosts.len());
+    let db = state::StateDb::open()?;
+    let imported = db.remote_instances()?;
+    let mut found = Vec::new();
+    for (host, result) in remote::scan_hosts(&hosts, depth) {
+        match result {
+            Ok(runners) if runners.is_empty() => println!("  {host}: no runners found"),
+            Ok(runners) => found.extend(runners),
+            Err(e) => eprintln!("  {host}: {e:#}"),
+        }
+    }
+    if found.is_empty() {
+        return Ok(());
+    }
+
+    let is_imported = |runner: &remote::RemoteRunner| {
+        imported
+            .iter()
+            .any(|i| i.host == runner.host && i.path == runner.path)
+    };
+    println!();
+    println!("Found {} remote runner(s):", found.len());
+    println!();
+    for runner in &found {
+        let status = if is_imported(runner) {
+            "[imported]"
+        } else {
+            "[not imported]"
+        };
+        let agent = runner
+            .agent_name
+            .as_deref()
+            .map_or(String::new(), |n| format!(" ({n})"));
+        println!("  {} {}{}", runner.scope, status, agent);
+        println!("    Location: {}", runner.location());
+    }
+
+    let new: Vec<_> = found.iter().filter(|r| !is_imported(r)).collect();
+    println!();
+    if new.is_empty() {
+        println!("All remote runners are already imported.");
+    } else if auto_import {
+        for runner in &new {
+            db.import_remote(runner)?;
+        }
+        println!(
+            "Imported {} remote runner(s). They are listed by `runner-mgr status` and stay managed on their hosts.",
+            new.len()
+        );
+    } else {
+        println!("{} remote runner(s) can be imported.", new.len());
+        println!();
+        println!("To import them:");
+        println!("  runner-mgr scan --remote --auto-import");
+    }
+    Ok(())
+}
+
+/// Local scan, then the remote hosts when asked for
+fn cmd_scan(args: &ScanArgs) -> Result<()> {
+    cmd_scan_local(
+        args.paths.as_deref(),
+        &args.roots,
+        args.depth,
+        args.auto_import,
+    )?;
+    if args.remote || !args.hosts.is_empty() {
+        cmd_scan_remote(args.remote, &args.hosts, args.depth, args.auto_import)?;
+    }
+    Ok(())
 }
 
-fn cmd_scan(extra_paths: Option<&str>, auto_import: bool) -> Result<()> {
+fn cmd_scan_local(
+    extra_paths: Option<&str>,
+    roots: &[PathBuf],
+    depth: u32,
+    auto_import: bool,
+) -> Result<()> {
     let config = Config::load()?;
 
     println!("Scanning for existing runner directories...");
     println!();
 
-    let discovered = runner::scan_for_runners(extra_paths);
+    let mut discovered = runner::scan_for_runners(extra_paths);
+    if !roots.is_empty() {
+        let show_progress = io::stderr().is_terminal();
+        let found = runner::scan_tree(roots, depth, |searched| {
+            if show_progress {
+                eprint!("\rSearched {searched} directories...");
+            }
+        });
+        if show_progress {
+            eprintln!();
+            println!();
+        }
+        for runner in found {
+            if !discovered.iter().any(|d| d.path == runner.path) {
+                discovered.push(runner);
+            }
+        }
+        discovered.sort_by(|a, b| a.path.cmp(&b.path));
+    }
 
     if discovered.is_empty() {
         println!("No runner directories found.");
@@ -533,6 +2714,7 @@ fn cmd_scan(extra_paths: Option<&str>, auto_import: bool) -> Result<()> {
         println!();
         println!("Options:");
         println!("  --paths <dir1,dir2>    Scan additional directories");
+        println!("  --root <dir> --depth <n>  Search a directory tree (default depth: 3)");
         println!(
             "  {}   Add persistent custom paths",
             config::ScanConfig::config_file().display()
@@ -601,3 +2783,148 @@ fn cmd_scan(extra_paths: Option<&str>, auto_import: bool) -> Result<()> {
 
     Ok(())
 }
+
+fn cmd_toolchain(target: Option<&str>) -> Result<()> {
+    let config = Config::load()?;
+    let instances = runner::list_instances(&config);
+
+    let selected: Vec<_> = if let Some(t) = target {
+        let scope = target::resolve_scope(&instances, t)?;
+        instances.into_iter().filter(|i| i.scope == scope).collect()
+    } else {
+        instances
+    };
+
+    if selected.is_empty() {
+        println!("No runners configured. Probing as {}:", config.runner_user);
+        println!();
+        print_toolchain(&toolchain::probe(&config, None));
+        return Ok(());
+    }
+
+    for instance in &selected {
+        println!("{}", instance.scope);
+        print_toolchain(&toolchain::probe(&config, Some(&instance.dir)));
+        println!();
+    }
+
+    Ok(())
+}
+
+fn print_toolchain(tools: &[toolchain::ToolVersion]) {
+    for tool in tools {
+        println!(
+            "  {:<10}  {}",
+            tool.tool(),
+            tool.version.as_deref().unwrap_or("not found")
+        );
+    }
+}
+
+fn cmd_on_demand(command: &OnDemandCommand) -> Result<()> {
+    let config = Config::load()?;
+    match command {
+        OnDemandCommand::Enable {
+            target,
+            idle_minutes,
+        } => {
+            let ids = target_instances(&config, target)?;
+            if *idle_minutes == 0 {
+                anyhow::bail!("--idle-minutes must be at least 1");
+            }
+            privilege::ensure(config.escalation, "install systemd units")?;
+            for id in &ids {
+                ondemand::enable(&config, id, *idle_minutes)?;
+                println!("{id} now starts on demand.");
+                println!(
+                    "Trigger file: {}",
+                    ondemand::trigger_path(&config, id).display()
+                );
+            }
+            Ok(())
+        }
+        OnDemandCommand::Disable { target } => {
+            let ids = target_instances(&config, target)?;
+            privilege::ensure(config.escalation, "remove systemd units")?;
+            for id in &ids {
+                ondemand::disable(&config, id)?;
+                println!("{id} runs continuously again.");
+            }
+            Ok(())
+        }
+        OnDemandCommand::Trigger { target } => {
+            let ids = target_instances(&config, target)?;
+            privilege::ensure(
+                config.escalation,
+                "write the trigger file as the runner user",
+            )?;
+            for id in &ids {
+                ondemand::trigger(&config, id)?;
+                println!("Queued work for {id}");
+            }
+            Ok(())
+        }
+    }
+}
+
+fn cmd_deps(command: &DepsCommand) -> Result<()> {
+    let config = Config::load()?;
+    match command {
+        DepsCommand::Install => {
+            privilege::ensure(config.escalation, "install system packages")?;
+            deps::install(&config)
+        }
+    }
+}
+
+fn cmd_docker(command: &DockerCommand) -> Result<()> {
+    let config = Config::load()?;
+    let health = docker::check(&config);
+
+    match command {
+        DockerCommand::Check => {
+            println!("Container engine ({}): {health}", config.runner_user);
+            if let docker::DockerHealth::Unreachable { engine, .. } = health {
+                println!();
+                println!("Container jobs on this host will fail. To repair:");
+                println!("  runner-mgr docker restart");
+                println!(
+                    "  ensure {} is in the docker group: sudo usermod -aG docker {}",
+                    config.runner_user, config.runner_user
+                );
+                anyhow::bail!("{engine} daemon not reachable");
+            }
+            Ok(())
+        }
+        DockerCommand::Restart => {
+            let Some(engine) = health.engine() else {
+                anyhow::bail!("No container engine installed for {}", config.runner_user);
+            };
+            privilege::ensure(config.escalation, &format!("restart the {engine} daemon"))?;
+            println!("Restarting {engine}...");
+            docker::restart_daemon(&config, engine)?;
+            println!("Container engine: {}", docker::check(&config));
+            Ok(())
+        }
+        DockerCommand::Prune { volumes, if_due } => {
+            privilege::ensure(
+                config.escalation,
+                "run container cleanup as the runner user",
+            )?;
+            if *if_due {
+                match docker::run_janitor_if_due(&config)? {
+                    Some(report) => println!("Janitor: {report}"),
+                    None => println!("Janitor: nothing to do (disabled or not due)"),
+                }
+                return Ok(());
+            }
+            let Some(engine) = health.engine() else {
+                anyhow::bail!("No container engine installed for {}", config.runner_user);
+            };
+            println!("Pruning {engine} resources...");
+            let report = docker::prune(&config, engine, *volumes)?;
+            println!("{report}");
+            Ok(())
+        }
+    }
+}
@@ -9,13 +9,174 @@
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 
-use crate::config::Config;
-use crate::github::{Runner, RunnerScope, WorkflowRun};
-
-use super::models::{DurationBucket, ScopeMetrics, Trend};
+use crate::config::{Config, SloConfig};
+use crate::github::{Runner, RunnerScope, WorkflowJob, WorkflowRun};
+use crate::updates::SelfUpdate;
+
+use super::hosts::RunnerStats;
+use super::models::{
+    bucket_ratios, percentile, Better, DailyMetrics, DurationBucket, DurationStats, JobRecord,
+    RunSpan, ScopeMetrics, TrendChange,
+};
+use super::slo::{SloObjective, SloStatus};
+use super::{bucket_peaks, RunnerFailures, QUEUE_HISTORY_BUCKETS};
+
+/// Offline snapshots this long after a self-update finished still belong to it, since
+/// GitHub reports the runner online again only after a short delay
+const SELF_UPDATE_GRACE_SECS: i64 = 120;
+
+/// Length assumed for a self-update whose end wasn't found in the logs
+const SELF_UPDATE_MAX_SECS: i64 = 15 * 60;
+
+/// Tables with a `scope_identifier` column
+const SCOPE_TABLES: &[&str] = &[
+    "workflow_runs",
+    "runner_snapshots",
+    "queue_samples",
+    "workflow_jobs",
+    "job_timings",
+    "runner_updates",
+    "daily_metrics",
+];
+
+/// Tables and indexes, created when missing
+const SCHEMA: &str = r"
+    -- Workflow run history
+    CREATE TABLE IF NOT EXISTS workflow_runs (
+        id INTEGER PRIMARY KEY,
+        github_run_id INTEGER NOT NULL,
+        scope_identifier TEXT NOT NULL,
+        status TEXT NOT NULL,
+        conclusion TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        duration_seconds INTEGER,
+        UNIQUE(github_run_id, scope_identifier)
+    );
+
+    -- Runner status snapshots for uptime
+    CREATE TABLE IF NOT EXISTS runner_snapshots (
+        id INTEGER PRIMARY KEY,
+        scope_identifier TEXT NOT NULL,
+        runner_id INTEGER NOT NULL,
+        runner_name TEXT NOT NULL,
+        status TEXT NOT NULL,
+        busy INTEGER NOT NULL,
+        recorded_at INTEGER NOT NULL
+    );
+
+    -- Queued run counts per poll, for queue depth history
+    CREATE TABLE IF NOT EXISTS queue_samples (
+        id INTEGER PRIMARY KEY,
+        scope_identifier TEXT NOT NULL,
+        queued INTEGER NOT NULL,
+        recorded_at INTEGER NOT NULL
+    );
+
+    -- Jobs of completed runs, with the runner that executed them
+    CREATE TABLE IF NOT EXISTS workflow_jobs (
+        id INTEGER PRIMARY KEY,
+        github_job_id INTEGER NOT NULL,
+        github_run_id INTEGER NOT NULL,
+        scope_identifier TEXT NOT NULL,
+        workflow_name TEXT,
+        job_name TEXT NOT NULL,
+        runner_name TEXT,
+        conclusion TEXT,
+        started_at TEXT,
+        completed_at TEXT,
+        recorded_at INTEGER NOT NULL,
+        labels TEXT,
+        queued_at TEXT,
+        queue_wait_seconds INTEGER,
+        UNIQUE(github_job_id, scope_identifier)
+    );
+
+    -- Job lifecycle times from workflow_job webhooks
+    CREATE TABLE IF NOT EXISTS job_timings (
+        id INTEGER PRIMARY KEY,
+        github_job_id INTEGER NOT NULL,
+        scope_identifier TEXT NOT NULL,
+        queued_at TEXT,
+        started_at TEXT,
+        completed_at TEXT,
+        recorded_at INTEGER NOT NULL,
+        UNIQUE(github_job_id, scope_identifier)
+    );
+
+    -- Runner self-updates found in the _diag logs, excluded from uptime
+    CREATE TABLE IF NOT EXISTS runner_updates (
+        id INTEGER PRIMARY KEY,
+        scope_identifier TEXT NOT NULL,
+        runner_name TEXT NOT NULL,
+        started_at INTEGER NOT NULL,
+        finished_at INTEGER,
+        version TEXT,
+        UNIQUE(scope_identifier, runner_name, started_at)
+    );
+
+    -- Daily aggregates of completed runs, by the day they were recorded
+    CREATE TABLE IF NOT EXISTS daily_metrics (
+        id INTEGER PRIMARY KEY,
+        scope_identifier TEXT NOT NULL,
+        date TEXT NOT NULL,
+        total_runs INTEGER,
+        successful_runs INTEGER,
+        failed_runs INTEGER,
+        avg_duration_seconds INTEGER,
+        runner_online_minutes INTEGER,
+        UNIQUE(scope_identifier, date)
+    );
+
+    -- Indexes for common queries
+    CREATE INDEX IF NOT EXISTS idx_workflow_runs_scope ON workflow_runs(scope_identifier);
+    CREATE INDEX IF NOT EXISTS idx_workflow_runs_recorded ON workflow_runs(recorded_at);
+    CREATE INDEX IF NOT EXISTS idx_runner_snapshots_scope ON runner_snapshots(scope_identifier);
+    CREATE INDEX IF NOT EXISTS idx_runner_snapshots_recorded ON runner_snapshots(recorded_at);
+    CREATE INDEX IF NOT EXISTS idx_queue_samples_scope_recorded ON queue_samples(scope_identifier, recorded_at);
+    CREATE INDEX IF NOT EXISTS idx_workflow_jobs_runner ON workflow_jobs(runner_name, completed_at);
+    CREATE INDEX IF NOT EXISTS idx_job_timings_scope_queued ON job_timings(scope_identifier, queued_at);
+    CREATE INDEX IF NOT EXISTS idx_runner_updates_scope ON runner_updates(scope_identifier, runner_name);
+    CREATE INDEX IF NOT EXISTS idx_daily_metrics_scope_date ON daily_metrics(scope_identifier, date);
+    CREATE INDEX IF NOT EXISTS idx_queue_samples_recorded ON queue_samples(recorded_at);
+    CREATE INDEX IF NOT EXISTS idx_workflow_jobs_recorded ON workflow_jobs(recorded_at);
+    CREATE INDEX IF NOT EXISTS idx_job_timings_recorded ON job_timings(recorded_at);
+";
+
+/// Columns added after their table was first created
+const ADDED_COLUMNS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "workflow_jobs",
+        &[
+            ("labels", "TEXT"),
+            ("queued_at", "TEXT"),
+            ("queue_wait_seconds", "INTEGER"),
+            ("error_message", "TEXT"),
+        ],
+    ),
+    (
+        "daily_metrics",
+        &[
+            ("p50_duration_seconds", "INTEGER"),
+            ("p90_duration_seconds", "INTEGER"),
+            ("p99_duration_seconds", "INTEGER"),
+        ],
+    ),
+];
+
+/// `user_version` once scope identifiers have been canonicalized
+const CANONICAL_SCOPES_VERSION: i64 = 1;
+
+/// Identifier rows of `scope` are stored under. GitHub names are case-insensitive, so
+/// the identifier is lowercased and `Owner/Repo` and `owner/repo` share one history.
+pub fn scope_key(scope: &RunnerScope) -> String {
+    scope.to_display().to_ascii_lowercase()
+}
 
 /// Database for storing metrics
 pub struct MetricsDb {
@@ -23,8 +184,19 @@ pub struct MetricsDb {
 }
 
 impl MetricsDb {
-    /// Open or create the metrics database
+    /// Open or create the metrics database, pruning rows older than the configured
+    /// retention
     pub fn open() -> Result<Self> {
+        let db = Self::open_unpruned()?;
+        let retention_days = Config::load().unwrap_or_default().metrics_retention_days();
+        if retention_days > 0 {
+            db.prune(retention_days)?;
+        }
+        Ok(db)
+    }
+
+    /// Open or create the metrics database without pruning it
+    pub fn open_unpruned() -> Result<Self> {
         let db_path = Self::db_path();
 
         // Ensure config directory exists
@@ -41,6 +213,29 @@ impl MetricsDb {
         Ok(db)
     }
 
+    /// A database that lives only as long as the value, e.g. for a replayed dashboard
+    pub fn open_in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open metrics database")?;
+        let db = Self { conn };
+        db.run_migrations()?;
+        Ok(db)
+    }
+
+    /// Read from the database file, to tell whether it is still accessible
+    pub fn check(&self) -> Result<()> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .context("Metrics database is not readable")?;
+        Ok(())
+    }
+
+    /// Size of the database file in bytes
+    pub fn file_size() -> u64 {
+        std::fs::metadata(Self::db_path()).map_or(0, |m| m.len())
+    }
+
     /// Get the database file path
     fn db_path() -> PathBuf {
         Config::config_dir().join("metrics.db")
@@ -48,68 +243,155 @@ impl MetricsDb {
 
     /// Run database migrations
     fn run_migrations(&self) -> Result<()> {
-        self.conn.execute_batch(
-            r"
-            -- Workflow run history
-            CREATE TABLE IF NOT EXISTS workflow_runs (
-                id INTEGER PRIMARY KEY,
-                github_run_id INTEGER NOT NULL,
-                scope_identifier TEXT NOT NULL,
-                status TEXT NOT NULL,
-                conclusion TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                recorded_at INTEGER NOT NULL,
-                duration_seconds INTEGER,
-                UNIQUE(github_run_id, scope_identifier)
-            );
-
-            -- Runner status snapshots for uptime
-            CREATE TABLE IF NOT EXISTS runner_snapshots (
-                id INTEGER PRIMARY KEY,
-                scope_identifier TEXT NOT NULL,
-                runner_id INTEGER NOT NULL,
-                runner_name TEXT NOT NULL,
-                status TEXT NOT NULL,
-                busy INTEGER NOT NULL,
-                recorded_at INTEGER NOT NULL
-            );
-
-            -- Daily aggregates for fast queries
-            -- TODO: Implement daily aggregation job to populate this table for faster queries
-            CREATE TABLE IF NOT EXISTS daily_metrics (
-                id INTEGER PRIMARY KEY,
-                scope_identifier TEXT NOT NULL,
-                date TEXT NOT NULL,
-                total_runs INTEGER,
-                successful_runs INTEGER,
-                failed_runs INTEGER,
-                avg_duration_seconds INTEGER,
-                runner_online_minutes INTEGER,
-                UNIQUE(scope_identifier, date)
-            );
-
-            -- Indexes for common queries
-            CREATE INDEX IF NOT EXISTS idx_workflow_runs_scope ON workflow_runs(scope_identifier);
-            CREATE INDEX IF NOT EXISTS idx_workflow_runs_recorded ON workflow_runs(recorded_at);
-            CREATE INDEX IF NOT EXISTS idx_runner_snapshots_scope ON runner_snapshots(scope_identifier);
-            CREATE INDEX IF NOT EXISTS idx_runner_snapshots_recorded ON runner_snapshots(recorded_at);
-            CREATE INDEX IF NOT EXISTS idx_daily_metrics_scope_date ON daily_metrics(scope_identifier, date);
-            ",
+        self.conn.execute_batch(SCHEMA)?;
+        for (table, columns) in ADDED_COLUMNS {
+            self.add_missing_columns(table, columns)?;
+        }
+
+        // Rows recorded before identifiers were canonicalized kept the case they were
+        // written in
+        let version: i64 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version < CANONICAL_SCOPES_VERSION {
+            self.canonicalize_scope_ids()?;
+            self.conn
+                .execute_batch(&format!("PRAGMA user_version = {CANONICAL_SCOPES_VERSION}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Merge every scope identifier into its lowercase form
+    fn canonicalize_scope_ids(&self) -> Result<()> {
+        for table in SCOPE_TABLES {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT DISTINCT scope_identifier FROM {table}
+                 WHERE scope_identifier != lower(scope_identifier)"
+            ))?;
+            let ids = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            for id in ids {
+                self.merge_scope_id(table, &id, &id.to_ascii_lowercase())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the rows of `table` recorded under `from` (exactly) to `to`. Rows that
+    /// already exist under `to`, such as the same run recorded under both names, are
+    /// kept and the duplicates under `from` dropped. Returns the number of rows moved.
+    fn merge_scope_id(&self, table: &str, from: &str, to: &str) -> Result<usize> {
+        let moved = self.conn.execute(
+            &format!(
+                "UPDATE OR IGNORE {table} SET scope_identifier = ?2 WHERE scope_identifier = ?1"
+            ),
+            params![from, to],
         )?;
+        self.conn.execute(
+            &format!("DELETE FROM {table} WHERE scope_identifier = ?1"),
+            params![from],
+        )?;
+        Ok(moved)
+    }
+
+    /// Delete rows recorded more than `days` ago. Returns the number of rows deleted per
+    /// table, leaving out tables with nothing to delete.
+    pub fn prune(&self, days: u32) -> Result<Vec<(&'static str, usize)>> {
+        let cutoff = Utc::now() - Duration::days(i64::from(days));
+        let timestamp = cutoff.timestamp();
+        let date = cutoff.format("%Y-%m-%d").to_string();
+        let statements: [(&'static str, &str, &dyn rusqlite::ToSql); 7] = [
+            ("workflow_runs", "recorded_at < ?1", &timestamp),
+            ("runner_snapshots", "recorded_at < ?1", &timestamp),
+            ("queue_samples", "recorded_at < ?1", &timestamp),
+            ("workflow_jobs", "recorded_at < ?1", &timestamp),
+            ("job_timings", "recorded_at < ?1", &timestamp),
+            ("runner_updates", "started_at < ?1", &timestamp),
+            ("daily_metrics", "date < ?1", &date),
+        ];
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut deleted = Vec::new();
+        for (table, condition, value) in statements {
+            let count = tx.execute(&format!("DELETE FROM {table} WHERE {condition}"), [value])?;
+            if count > 0 {
+                deleted.push((table, count));
+            }
+        }
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Rebuild the database file to give the space of deleted rows back to the disk
+    pub fn vacuum(&self) -> Result<()> {
+        self.conn.execute_batch("VACUUM")?;
+        Ok(())
+    }
 
+    /// Merge the history of `from` into `to`, for a repository that was renamed or
+    /// transferred. Returns the number of rows moved.
+    pub fn migrate_scope(&self, from: &RunnerScope, to: &RunnerScope) -> Result<usize> {
+        let to_id = scope_key(to);
+        let tx = self.conn.unchecked_transaction()?;
+        let mut moved = 0;
+        for table in SCOPE_TABLES {
+            // Any spelling of the old name, but not the rows already under the new one
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT DISTINCT scope_identifier FROM {table}
+                 WHERE scope_identifier = ?1 COLLATE NOCASE AND scope_identifier != ?2"
+            ))?;
+            let ids = stmt
+                .query_map(params![scope_key(from), to_id], |row| {
+                    row.get::<_, String>(0)
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            for id in ids {
+                moved += self.merge_scope_id(table, &id, &to_id)?;
+            }
+        }
+        tx.commit()?;
+        // Days both names had runs on were aggregated separately
+        self.refresh_daily_metrics(&to_id, 0)?;
+        Ok(moved)
+    }
+
+    /// Add the `columns` (name, type) that `table` doesn't have yet
+    fn add_missing_columns(&self, table: &str, columns: &[(&str, &str)]) -> Result<()> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({table})"))?;
+        let existing = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<HashSet<_>, _>>()?;
+        for (name, kind) in columns {
+            if !existing.contains(*name) {
+                self.conn
+                    .execute(&format!("ALTER TABLE {table} ADD COLUMN {name} {kind}"), [])?;
+            }
+        }
         Ok(())
     }
 
     /// Record workflow runs (upsert on `github_run_id` + scope)
     pub fn record_workflow_runs(&self, scope: &RunnerScope, runs: &[WorkflowRun]) -> Result<()> {
-        let scope_id = scope.to_display();
+        let scope_id = scope_key(scope);
         let now = Utc::now().timestamp();
 
         let tx = self.conn.unchecked_transaction()?;
 
+        // The earliest day whose aggregates change: runs recorded before move to today
+        let mut since = now;
         for run in runs {
             let duration = Self::calculate_duration(&run.created_at, &run.updated_at);
+            let previous: Option<i64> = tx
+                .query_row(
+                    "SELECT recorded_at FROM workflow_runs
+                     WHERE github_run_id = ?1 AND scope_identifier = ?2",
+                    params![run.id as i64, scope_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            since = since.min(previous.unwrap_or(now));
 
             tx.execute(
                 r"
@@ -136,6 +418,71 @@ impl MetricsDb {
             )?;
         }
 
+        tx.commit()?;
+        self.refresh_daily_metrics(&scope_id, since)
+    }
+
+    /// Recompute the `daily_metrics` rows of `scope_id` from the day of `since` (a Unix
+    /// timestamp; days are UTC) on
+    fn refresh_daily_metrics(&self, scope_id: &str, since: i64) -> Result<()> {
+        let start = since - since.rem_euclid(86_400);
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT date(recorded_at, 'unixepoch'), conclusion, duration_seconds
+            FROM workflow_runs
+            WHERE scope_identifier = ?1 COLLATE NOCASE AND recorded_at >= ?2 AND status = 'completed'
+            ",
+        )?;
+        let rows = stmt
+            .query_map(params![scope_id, start], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // (total, successful, failed, durations) per day
+        let mut days: BTreeMap<String, (u32, u32, u32, Vec<u32>)> = BTreeMap::new();
+        for (day, conclusion, duration) in rows {
+            let entry = days.entry(day).or_default();
+            entry.0 += 1;
+            match conclusion.as_deref() {
+                Some("success") => entry.1 += 1,
+                Some("failure") => entry.2 += 1,
+                _ => {}
+            }
+            if let Some(duration) = duration {
+                entry.3.push(duration as u32);
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let first_day = DateTime::from_timestamp(start, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%d")
+            .to_string();
+        tx.execute(
+            "DELETE FROM daily_metrics WHERE scope_identifier = ?1 AND date >= ?2",
+            params![scope_id, first_day],
+        )?;
+        for (day, (total, successful, failed, durations)) in days {
+            let stats = DurationStats::from_durations(durations);
+            tx.execute(
+                r"
+                INSERT INTO daily_metrics
+                    (scope_identifier, date, total_runs, successful_runs, failed_runs,
+                     avg_duration_seconds, p50_duration_seconds, p90_duration_seconds,
+                     p99_duration_seconds)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                ",
+                params![
+                    scope_id, day, total, successful, failed, stats.avg, stats.p50, stats.p90,
+                    stats.p99,
+                ],
+            )?;
+        }
         tx.commit()?;
         Ok(())
     }
@@ -150,8 +497,16 @@ impl MetricsDb {
 
     /// Record runner status snapshots
     pub fn record_runner_snapshots(&self, scope: &RunnerScope, runners: &[Runner]) -> Result<()> {
-        let scope_id = scope.to_display();
-        let now = Utc::now().timestamp();
+        self.insert_runner_snapshots(scope, runners, Utc::now().timestamp())
+    }
+
+    fn insert_runner_snapshots(
+        &self,
+        scope: &RunnerScope,
+        runners: &[Runner],
+        now: i64,
+    ) -> Result<()> {
+        let scope_id = scope_key(scope);
 
         let tx = self.conn.unchecked_transaction()?;
 
@@ -177,9 +532,435 @@ impl MetricsDb {
         Ok(())
     }
 
+    /// Record self-updates of the runner `runner_name` (upsert on their start, so scanning
+    /// the same logs again only fills in ends and versions)
+    pub fn record_self_updates(
+        &self,
+        scope: &RunnerScope,
+        runner_name: &str,
+        updates: &[SelfUpdate],
+    ) -> Result<()> {
+        let scope_id = scope_key(scope);
+        let tx = self.conn.unchecked_transaction()?;
+        for update in updates {
+            tx.execute(
+                r"
+                INSERT INTO runner_updates
+                    (scope_identifier, runner_name, started_at, finished_at, version)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT(scope_identifier, runner_name, started_at) DO UPDATE SET
+                    finished_at = COALESCE(excluded.finished_at, finished_at),
+                    version = COALESCE(excluded.version, version)
+                ",
+                params![
+                    scope_id,
+                    runner_name,
+                    update.started_at,
+                    update.finished_at,
+                    update.version,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Self-updates of the scope's runners started since `cutoff`
+    fn get_self_update_count(&self, scope_id: &str, cutoff: i64) -> Result<u32> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM runner_updates WHERE scope_identifier = ?1 COLLATE NOCASE AND started_at >= ?2",
+            params![scope_id, cutoff],
+            |row| row.get(0),
+        )?;
+        Ok(count as u32)
+    }
+
+    /// Record the jobs of a run (upsert on `github_job_id` + scope)
+    pub fn record_jobs(&self, scope: &RunnerScope, jobs: &[WorkflowJob]) -> Result<()> {
+        let scope_id = scope_key(scope);
+        let now = Utc::now().timestamp();
+
+        let tx = self.conn.unchecked_transaction()?;
+
+        for job in jobs {
+            tx.execute(
+                r"
+                INSERT INTO workflow_jobs
+                    (github_job_id, github_run_id, scope_identifier, workflow_name, job_name,
+                     runner_name, conclusion, started_at, completed_at, recorded_at,
+                     labels, queued_at, queue_wait_seconds)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+                ON CONFLICT(github_job_id, scope_identifier) DO UPDATE SET
+                    runner_name = excluded.runner_name,
+                    conclusion = excluded.conclusion,
+                    started_at = excluded.started_at,
+                    completed_at = excluded.completed_at,
+                    recorded_at = excluded.recorded_at,
+                    labels = excluded.labels,
+                    queued_at = COALESCE(excluded.queued_at, queued_at),
+                    queue_wait_seconds = COALESCE(excluded.queue_wait_seconds, queue_wait_seconds)
+                ",
+                params![
+                    job.id as i64,
+                    job.run_id as i64,
+                    scope_id,
+                    job.workflow_name,
+                    job.name,
+                    job.runner_name,
+                    job.conclusion,
+                    job.started_at,
+                    job.completed_at,
+                    now,
+                    job.labels.join(","),
+                    job.created_at,
+                    job.queue_wait_seconds(),
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record the first error line of failed jobs (job ID, line), see `github::first_error`
+    pub fn record_job_errors(&self, scope: &RunnerScope, errors: &[(u64, String)]) -> Result<()> {
+        let scope_id = scope_key(scope);
+        let tx = self.conn.unchecked_transaction()?;
+        for (job_id, error) in errors {
+            tx.execute(
+                "UPDATE workflow_jobs SET error_message = ?3
+                 WHERE github_job_id = ?1 AND scope_identifier = ?2",
+                params![*job_id as i64, scope_id, error],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record a `workflow_job` webhook event: the job itself, and its queued, started
+    /// and completed times (events can arrive out of order, so known times are kept)
+    pub fn record_job_event(&self, scope: &RunnerScope, job: &WorkflowJob) -> Result<()> {
+        self.record_jobs(scope, std::slice::from_ref(job))?;
+        self.conn.execute(
+            r"
+            INSERT INTO job_timings
+                (github_job_id, scope_identifier, queued_at, started_at, completed_at, recorded_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(github_job_id, scope_identifier) DO UPDATE SET
+                queued_at = COALESCE(excluded.queued_at, queued_at),
+                started_at = COALESCE(excluded.started_at, started_at),
+                completed_at = COALESCE(excluded.completed_at, completed_at),
+                recorded_at = excluded.recorded_at
+            ",
+            params![
+                job.id as i64,
+                scope_key(scope),
+                job.created_at,
+                job.started_at,
+                job.completed_at,
+                Utc::now().timestamp(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Number of jobs queued since `cutoff` whose wait for a runner is known, with the
+    /// average and 95th percentile of that wait
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn get_queue_wait(
+        &self,
+        scope_id: &str,
+        cutoff: i64,
+    ) -> Result<(u32, Option<u32>, Option<u32>)> {
+        let cutoff = DateTime::from_timestamp(cutoff, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT queue_wait_seconds
+            FROM workflow_jobs
+            WHERE scope_identifier = ?1 COLLATE NOCASE
+                AND queued_at >= ?2
+                AND queue_wait_seconds IS NOT NULL
+            ",
+        )?;
+        let mut waits = stmt
+            .query_map(params![scope_id, cutoff], |row| row.get::<_, i64>(0))?
+            .map(|wait| wait.map(|w| w.max(0) as u32))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let count = waits.len() as u32;
+        let avg = (count > 0).then(|| {
+            let total: u64 = waits.iter().map(|&w| u64::from(w)).sum();
+            (total as f64 / f64::from(count)).round() as u32
+        });
+        Ok((count, avg, percentile(&mut waits, 95.0)))
+    }
+
+    /// IDs of the runs whose jobs have been recorded
+    pub fn recorded_job_runs(&self) -> Result<HashSet<u64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT github_run_id FROM workflow_jobs")?;
+        let rows = stmt.query_map([], |row| Ok(row.get::<_, i64>(0)? as u64))?;
+        rows.collect::<Result<HashSet<_>, _>>().map_err(Into::into)
+    }
+
+    /// The last `limit` jobs executed by the runner named `runner_name`, newest first
+    pub fn get_runner_jobs(&self, runner_name: &str, limit: usize) -> Result<Vec<JobRecord>> {
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT scope_identifier, workflow_name, job_name, conclusion, started_at, completed_at,
+                labels, queue_wait_seconds, error_message, runner_name
+            FROM workflow_jobs
+            WHERE runner_name = ?1
+            ORDER BY COALESCE(completed_at, started_at) DESC
+            LIMIT ?2
+            ",
+        )?;
+
+        let rows = stmt.query_map(params![runner_name, limit as i64], Self::job_record)?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Last failed jobs of `scope`, newest first
+    pub fn get_scope_failures(&self, scope: &RunnerScope, limit: usize) -> Result<Vec<JobRecord>> {
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT scope_identifier, workflow_name, job_name, conclusion, started_at, completed_at,
+                labels, queue_wait_seconds, error_message, runner_name
+            FROM workflow_jobs
+            WHERE scope_identifier = ?1 COLLATE NOCASE
+                AND conclusion IN ('failure', 'timed_out', 'startup_failure')
+            ORDER BY COALESCE(completed_at, started_at) DESC
+            LIMIT ?2
+            ",
+        )?;
+
+        let rows = stmt.query_map(params![scope_key(scope), limit as i64], Self::job_record)?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// The recorded jobs of run `run_id` in `scope`, in the order they started
+    pub fn get_run_jobs(&self, scope: &RunnerScope, run_id: u64) -> Result<Vec<JobRecord>> {
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT scope_identifier, workflow_name, job_name, conclusion, started_at, completed_at,
+                labels, queue_wait_seconds, error_message, runner_name
+            FROM workflow_jobs
+            WHERE scope_identifier = ?1 COLLATE NOCASE AND github_run_id = ?2
+            ORDER BY started_at, github_job_id
+            ",
+        )?;
+
+        let rows = stmt.query_map(params![scope_key(scope), run_id as i64], Self::job_record)?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// A `JobRecord` from the columns selected by `get_runner_jobs`
+    fn job_record(row: &rusqlite::Row) -> rusqlite::Result<JobRecord> {
+        let time = |value: Option<String>| value.and_then(|t| t.parse().ok());
+        Ok(JobRecord {
+            scope: row.get(0)?,
+            workflow: row.get(1)?,
+            name: row.get(2)?,
+            conclusion: row.get(3)?,
+            started_at: time(row.get(4)?),
+            completed_at: time(row.get(5)?),
+            labels: row
+                .get::<_, Option<String>>(6)?
+                .map(|l| {
+                    l.split(',')
+                        .filter(|l| !l.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            queue_wait_seconds: row.get(7)?,
+            error: row.get(8)?,
+            runner: row.get(9)?,
+        })
+    }
+
+    /// Failed and concluded job counts per self-hosted runner over the last `days`
+    pub fn get_runner_failures(&self, days: i64) -> Result<Vec<RunnerFailures>> {
+        let cutoff = (Utc::now() - Duration::days(days))
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+        // GitHub-hosted runners are named "GitHub Actions <n>"
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT
+                runner_name,
+                COUNT(*) as total,
+                SUM(CASE WHEN conclusion IN ('failure', 'timed_out', 'startup_failure')
+                    THEN 1 ELSE 0 END) as failed
+            FROM workflow_jobs
+            WHERE runner_name IS NOT NULL
+                AND runner_name NOT LIKE 'GitHub Actions %'
+                AND conclusion IS NOT NULL
+                AND conclusion NOT IN ('cancelled', 'skipped')
+                AND completed_at >= ?1
+            GROUP BY runner_name
+            ORDER BY failed DESC, runner_name
+            ",
+        )?;
+
+        let rows = stmt.query_map(params![cutoff], |row| {
+            Ok(RunnerFailures {
+                runner_name: row.get(0)?,
+                total: row.get::<_, i64>(1)? as u32,
+                failed: row.get::<_, i64>(2)? as u32,
+            })
+        })?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Snapshot and job counts per runner over the last `days`, for the per-host
+    /// rollup. Offline snapshots during self-updates are left out, as in uptime.
+    pub fn get_runner_stats(&self, days: i64) -> Result<Vec<RunnerStats>> {
+        let since = Utc::now() - Duration::days(days);
+        let mut stats: BTreeMap<(String, String), RunnerStats> = BTreeMap::new();
+
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT
+                scope_identifier,
+                runner_name,
+                COUNT(*) as total,
+                COALESCE(SUM(CASE WHEN status = 'online' THEN 1 ELSE 0 END), 0) as online
+            FROM runner_snapshots s
+            WHERE recorded_at >= ?1
+                AND NOT (s.status != 'online' AND EXISTS (
+                    SELECT 1 FROM runner_updates u
+                    WHERE u.scope_identifier = s.scope_identifier COLLATE NOCASE
+                        AND u.runner_name = s.runner_name
+                        AND s.recorded_at >= u.started_at
+                        AND s.recorded_at <= COALESCE(u.finished_at + ?2, u.started_at + ?3)
+                ))
+            GROUP BY scope_identifier, runner_name
+            ",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                since.timestamp(),
+                SELF_UPDATE_GRACE_SECS,
+                SELF_UPDATE_MAX_SECS
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)? as u32,
+                    row.get::<_, i64>(3)? as u32,
+                ))
+            },
+        )?;
+        for row in rows {
+            let (scope, runner_name, total, online) = row?;
+            let entry = stats
+                .entry((scope.clone(), runner_name.clone()))
+                .or_insert_with(|| RunnerStats {
+                    scope,
+                    runner_name,
+                    ..Default::default()
+                });
+            entry.snapshots = total;
+            entry.online_snapshots = online;
+        }
+
+        // Same job outcomes as `get_runner_failures`
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT
+                scope_identifier,
+                runner_name,
+                COUNT(*) as total,
+                SUM(CASE WHEN conclusion IN ('failure', 'timed_out', 'startup_failure')
+                    THEN 1 ELSE 0 END) as failed
+            FROM workflow_jobs
+            WHERE runner_name IS NOT NULL
+                AND runner_name NOT LIKE 'GitHub Actions %'
+                AND conclusion IS NOT NULL
+                AND conclusion NOT IN ('cancelled', 'skipped')
+                AND completed_at >= ?1
+            GROUP BY scope_identifier, runner_name
+            ",
+        )?;
+        let rows = stmt.query_map(
+            params![since.format("%Y-%m-%dT%H:%M:%SZ").to_string()],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)? as u32,
+                    row.get::<_, i64>(3)? as u32,
+                ))
+            },
+        )?;
+        for row in rows {
+            let (scope, runner_name, total, failed) = row?;
+            let entry = stats
+                .entry((scope.clone(), runner_name.clone()))
+                .or_insert_with(|| RunnerStats {
+                    scope,
+                    runner_name,
+                    ..Default::default()
+                });
+            entry.jobs = total;
+            entry.failed_jobs = failed;
+        }
+
+        Ok(stats.into_values().collect())
+    }
+
+    /// Record how many runs of `scope` are queued
+    pub fn record_queue_depth(&self, scope: &RunnerScope, queued: u64) -> Result<()> {
+        let queued = i64::try_from(queued).unwrap_or(i64::MAX);
+        self.conn.execute(
+            r"
+            INSERT INTO queue_samples (scope_identifier, queued, recorded_at)
+            VALUES (?1, ?2, ?3)
+            ",
+            params![scope_key(scope), queued, Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    /// Peak queue depth per slice of `[cutoff, now)`
+    fn get_queue_history(&self, scope_id: &str, cutoff: i64) -> Result<Vec<u32>> {
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT recorded_at, queued
+            FROM queue_samples
+            WHERE scope_identifier = ?1 COLLATE NOCASE AND recorded_at >= ?2
+            ",
+        )?;
+        let samples = stmt
+            .query_map(params![scope_id, cutoff], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)? as u32))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(bucket_peaks(
+            &samples,
+            cutoff,
+            Utc::now().timestamp() + 1,
+            QUEUE_HISTORY_BUCKETS,
+        ))
+    }
+
     /// Get aggregated metrics for a scope
     pub fn get_scope_metrics(&self, scope: &RunnerScope, days: i32) -> Result<ScopeMetrics> {
-        let scope_id = scope.to_display();
+        let scope_id = scope_key(scope);
         let cutoff = (Utc::now() - Duration::days(i64::from(days))).timestamp();
         let previous_cutoff = (Utc::now() - Duration::days(i64::from(days * 2))).timestamp();
 
@@ -187,6 +968,10 @@ impl MetricsDb {
         let (total, successful, failed) = self.get_run_counts(&scope_id, cutoff)?;
         let durations = self.get_duration_stats(&scope_id, cutoff)?;
         let uptime = self.get_runner_uptime(&scope_id, cutoff)?;
+        let self_updates = self.get_self_update_count(&scope_id, cutoff)?;
+        let queue_history = self.get_queue_history(&scope_id, cutoff)?;
+        let (queue_wait_jobs, avg_queue_wait, p95_queue_wait) =
+            self.get_queue_wait(&scope_id, cutoff)?;
 
         // Get previous period stats for trends
         let (prev_total, prev_successful, _) =
@@ -197,10 +982,18 @@ impl MetricsDb {
             total_runs: total,
             successful_runs: successful,
             failed_runs: failed,
-            avg_duration_seconds: durations.0,
-            min_duration_seconds: durations.1,
-            max_duration_seconds: durations.2,
+            avg_duration_seconds: durations.avg,
+            min_duration_seconds: durations.min,
+            max_duration_seconds: durations.max,
+            p50_duration_seconds: durations.p50,
+            p90_duration_seconds: durations.p90,
+            p99_duration_seconds: durations.p99,
             runner_uptime: uptime,
+            self_updates,
+            queue_history,
+            queue_wait_jobs,
+            avg_queue_wait_seconds: avg_queue_wait,
+            p95_queue_wait_seconds: p95_queue_wait,
             ..Default::default()
         };
 
@@ -210,14 +1003,12 @@ impl MetricsDb {
         if total > 0 && prev_total > 0 {
             let current_rate = f64::from(successful) / f64::from(total);
             let prev_rate = f64::from(prev_successful) / f64::from(prev_total);
-            metrics.success_trend = Some(Self::calculate_trend(current_rate, prev_rate));
+            metrics.success_trend = TrendChange::between(current_rate, prev_rate, Better::Higher);
         }
 
-        if let (Some(current_avg), Some(prev_avg)) = (durations.0, prev_durations.0) {
-            metrics.duration_trend = Some(Self::calculate_trend(
-                f64::from(prev_avg), // inverted: lower duration is better
-                f64::from(current_avg),
-            ));
+        if let (Some(current_avg), Some(prev_avg)) = (durations.avg, prev_durations.avg) {
+            metrics.duration_trend =
+                TrendChange::between(f64::from(current_avg), f64::from(prev_avg), Better::Lower);
         }
 
         Ok(metrics)
@@ -232,7 +1023,7 @@ impl MetricsDb {
                 COALESCE(SUM(CASE WHEN conclusion = 'success' THEN 1 ELSE 0 END), 0) as successful,
                 COALESCE(SUM(CASE WHEN conclusion = 'failure' THEN 1 ELSE 0 END), 0) as failed
             FROM workflow_runs
-            WHERE scope_identifier = ?1 AND recorded_at >= ?2 AND status = 'completed'
+            WHERE scope_identifier = ?1 COLLATE NOCASE AND recorded_at >= ?2 AND status = 'completed'
             ",
         )?;
 
@@ -261,7 +1052,7 @@ impl MetricsDb {
                 COALESCE(SUM(CASE WHEN conclusion = 'success' THEN 1 ELSE 0 END), 0) as successful,
                 COALESCE(SUM(CASE WHEN conclusion = 'failure' THEN 1 ELSE 0 END), 0) as failed
             FROM workflow_runs
-            WHERE scope_identifier = ?1 AND recorded_at >= ?2 AND recorded_at < ?3 AND status = 'completed'
+            WHERE scope_identifier = ?1 COLLATE NOCASE AND recorded_at >= ?2 AND recorded_at < ?3 AND status = 'completed'
             ",
         )?;
 
@@ -277,34 +1068,8 @@ impl MetricsDb {
     }
 
     /// Get duration statistics since cutoff
-    fn get_duration_stats(
-        &self,
-        scope_id: &str,
-        cutoff: i64,
-    ) -> Result<(Option<u32>, Option<u32>, Option<u32>)> {
-        let mut stmt = self.conn.prepare(
-            r"
-            SELECT
-                AVG(duration_seconds) as avg_dur,
-                MIN(duration_seconds) as min_dur,
-                MAX(duration_seconds) as max_dur
-            FROM workflow_runs
-            WHERE scope_identifier = ?1
-                AND recorded_at >= ?2
-                AND status = 'completed'
-                AND duration_seconds IS NOT NULL
-            ",
-        )?;
-
-        let result = stmt.query_row(params![scope_id, cutoff], |row| {
-            Ok((
-                row.get::<_, Option<f64>>(0)?.map(|v| v as u32),
-                row.get::<_, Option<i64>>(1)?.map(|v| v as u32),
-                row.get::<_, Option<i64>>(2)?.map(|v| v as u32),
-            ))
-        })?;
-
-        Ok(result)
+    fn get_duration_stats(&self, scope_id: &str, cutoff: i64) -> Result<DurationStats> {
+        self.get_duration_stats_range(scope_id, cutoff, i64::MAX)
     }
 
     /// Get duration statistics for a date range
@@ -313,15 +1078,12 @@ impl MetricsDb {
         scope_id: &str,
         start: i64,
         end: i64,
-    ) -> Result<(Option<u32>, Option<u32>, Option<u32>)> {
+    ) -> Result<DurationStats> {
         let mut stmt = self.conn.prepare(
             r"
-            SELECT
-                AVG(duration_seconds) as avg_dur,
-                MIN(duration_seconds) as min_dur,
-                MAX(duration_seconds) as max_dur
+            SELECT duration_seconds
             FROM workflow_runs
-            WHERE scope_identifier = ?1
+            WHERE scope_identifier = ?1 COLLATE NOCASE
                 AND recorded_at >= ?2
                 AND recorded_at < ?3
                 AND status = 'completed'
@@ -329,38 +1091,172 @@ impl MetricsDb {
             ",
         )?;
 
-        let result = stmt.query_row(params![scope_id, start, end], |row| {
-            Ok((
-                row.get::<_, Option<f64>>(0)?.map(|v| v as u32),
-                row.get::<_, Option<i64>>(1)?.map(|v| v as u32),
-                row.get::<_, Option<i64>>(2)?.map(|v| v as u32),
-            ))
-        })?;
+        let durations = stmt
+            .query_map(params![scope_id, start, end], |row| {
+                Ok(row.get::<_, i64>(0)? as u32)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(result)
+        Ok(DurationStats::from_durations(durations))
     }
 
     /// Calculate runner uptime percentage
     fn get_runner_uptime(&self, scope_id: &str, cutoff: i64) -> Result<Option<f64>> {
+        let (total, online) = self.get_uptime_counts(scope_id, cutoff)?;
+        if total > 0 {
+            Ok(Some((online as f64 / total as f64) * 100.0))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Runner snapshots since cutoff: (total, online). Offline snapshots during a
+    /// runner's self-update are left out, so updates don't count as downtime.
+    fn get_uptime_counts(&self, scope_id: &str, cutoff: i64) -> Result<(i64, i64)> {
         let mut stmt = self.conn.prepare(
             r"
             SELECT
                 COUNT(*) as total,
                 COALESCE(SUM(CASE WHEN status = 'online' THEN 1 ELSE 0 END), 0) as online
-            FROM runner_snapshots
-            WHERE scope_identifier = ?1 AND recorded_at >= ?2
+            FROM runner_snapshots s
+            WHERE scope_identifier = ?1 COLLATE NOCASE AND recorded_at >= ?2
+                AND NOT (s.status != 'online' AND EXISTS (
+                    SELECT 1 FROM runner_updates u
+                    WHERE u.scope_identifier = s.scope_identifier COLLATE NOCASE
+                        AND u.runner_name = s.runner_name
+                        AND s.recorded_at >= u.started_at
+                        AND s.recorded_at <= COALESCE(u.finished_at + ?3, u.started_at + ?4)
+                ))
             ",
         )?;
 
-        let (total, online): (i64, i64) = stmt.query_row(params![scope_id, cutoff], |row| {
-            Ok((row.get(0)?, row.get(1)?))
-        })?;
+        let (total, online): (i64, i64) = stmt.query_row(
+            params![
+                scope_id,
+                cutoff,
+                SELF_UPDATE_GRACE_SECS,
+                SELF_UPDATE_MAX_SECS
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
 
-        if total > 0 {
-            Ok(Some((online as f64 / total as f64) * 100.0))
-        } else {
-            Ok(None)
+        Ok((total, online))
+    }
+
+    /// Runner uptime of `scope` in each of `buckets` slices of the last `days`, in percent.
+    /// Self-updates are left out as in `get_scope_metrics`; None for slices without
+    /// snapshots.
+    pub fn get_uptime_history(
+        &self,
+        scope: &RunnerScope,
+        days: i32,
+        buckets: usize,
+    ) -> Result<Vec<Option<f64>>> {
+        let now = Utc::now().timestamp();
+        let cutoff = now - i64::from(days) * 86_400;
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT recorded_at, status = 'online'
+            FROM runner_snapshots s
+            WHERE scope_identifier = ?1 COLLATE NOCASE AND recorded_at >= ?2
+                AND NOT (s.status != 'online' AND EXISTS (
+                    SELECT 1 FROM runner_updates u
+                    WHERE u.scope_identifier = s.scope_identifier COLLATE NOCASE
+                        AND u.runner_name = s.runner_name
+                        AND s.recorded_at >= u.started_at
+                        AND s.recorded_at <= COALESCE(u.finished_at + ?3, u.started_at + ?4)
+                ))
+            ",
+        )?;
+        let samples = stmt
+            .query_map(
+                params![
+                    scope_key(scope),
+                    cutoff,
+                    SELF_UPDATE_GRACE_SECS,
+                    SELF_UPDATE_MAX_SECS
+                ],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, bool>(1)?)),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(bucket_ratios(&samples, cutoff, now + 1, buckets))
+    }
+
+    /// Daily aggregates of `scope` for each of the last `days` days up to today (UTC),
+    /// oldest first; days without runs have zero counts
+    pub fn get_daily_metrics(&self, scope: &RunnerScope, days: i32) -> Result<Vec<DailyMetrics>> {
+        let today = Utc::now().date_naive();
+        let dates: Vec<String> = (0..i64::from(days.max(1)))
+            .rev()
+            .map(|back| {
+                (today - Duration::days(back))
+                    .format("%Y-%m-%d")
+                    .to_string()
+            })
+            .collect();
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT date, total_runs, successful_runs, failed_runs, avg_duration_seconds,
+                p50_duration_seconds, p90_duration_seconds
+            FROM daily_metrics
+            WHERE scope_identifier = ?1 COLLATE NOCASE AND date >= ?2
+            ",
+        )?;
+        let mut recorded: BTreeMap<String, DailyMetrics> = stmt
+            .query_map(params![scope_key(scope), dates[0]], |row| {
+                let count = |i| row.get::<_, Option<i64>>(i).map(|v| v.unwrap_or(0) as u32);
+                let seconds = |i| row.get::<_, Option<i64>>(i).map(|v| v.map(|v| v as u32));
+                Ok(DailyMetrics {
+                    date: row.get(0)?,
+                    total_runs: count(1)?,
+                    successful_runs: count(2)?,
+                    failed_runs: count(3)?,
+                    avg_duration_seconds: seconds(4)?,
+                    p50_duration_seconds: seconds(5)?,
+                    p90_duration_seconds: seconds(6)?,
+                })
+            })?
+            .map(|day| day.map(|day| (day.date.clone(), day)))
+            .collect::<Result<_, _>>()?;
+        Ok(dates
+            .into_iter()
+            .map(|date| {
+                recorded.remove(&date).unwrap_or(DailyMetrics {
+                    date,
+                    ..Default::default()
+                })
+            })
+            .collect())
+    }
+
+    /// Evaluate the scope's objectives over their rolling window
+    pub fn get_slo_status(&self, scope: &RunnerScope, slo: &SloConfig) -> Result<Vec<SloStatus>> {
+        let scope_id = scope_key(scope);
+        let cutoff = (Utc::now() - Duration::days(i64::from(slo.window_days))).timestamp();
+        let mut statuses = Vec::new();
+
+        if let Some(target) = slo.availability {
+            let (total, online) = self.get_uptime_counts(&scope_id, cutoff)?;
+            statuses.push(SloStatus::evaluate(
+                SloObjective::Availability,
+                target,
+                slo.window_days,
+                online as u64,
+                total as u64,
+            ));
+        }
+        if let Some(target) = slo.success_rate {
+            let (total, successful, _) = self.get_run_counts(&scope_id, cutoff)?;
+            statuses.push(SloStatus::evaluate(
+                SloObjective::SuccessRate,
+                target,
+                slo.window_days,
+                u64::from(successful),
+                u64::from(total),
+            ));
         }
+
+        Ok(statuses)
     }
 
     /// Get duration distribution buckets
@@ -369,7 +1265,7 @@ impl MetricsDb {
         scope: &RunnerScope,
         days: i32,
     ) -> Result<Vec<DurationBucket>> {
-        let scope_id = scope.to_display();
+        let scope_id = scope_key(scope);
         let cutoff = (Utc::now() - Duration::days(i64::from(days))).timestamp();
 
         let mut stmt = self.conn.prepare(
@@ -384,7 +1280,7 @@ impl MetricsDb {
                 END as bucket,
                 COUNT(*) as count
             FROM workflow_runs
-            WHERE scope_identifier = ?1
+            WHERE scope_identifier = ?1 COLLATE NOCASE
                 AND recorded_at >= ?2
                 AND status = 'completed'
                 AND duration_seconds IS NOT NULL
@@ -410,6 +1306,59 @@ impl MetricsDb {
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
+    /// Runs of every scope that were active at some point after `since`, for the
+    /// activity timeline. Runs that aren't completed are treated as running until now.
+    pub fn get_run_spans(&self, since: DateTime<Utc>) -> Result<Vec<RunSpan>> {
+        // Timestamps are stored as GitHub's ISO 8601 strings, which sort chronologically;
+        // look back a day further to catch long runs that started before `since`
+        let created_cutoff = (since - Duration::days(1))
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+        let now = Utc::now();
+
+        let mut stmt = self.conn.prepare(
+            r"
+            SELECT scope_identifier, status, conclusion, created_at, updated_at
+            FROM workflow_runs
+            WHERE created_at >= ?1
+            ORDER BY created_at
+            ",
+        )?;
+
+        let rows = stmt.query_map(params![created_cutoff], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+
+        let mut spans = Vec::new();
+        for row in rows {
+            let (scope, status, conclusion, created_at, updated_at) = row?;
+            let Ok(start) = created_at.parse::<DateTime<Utc>>() else {
+                continue;
+            };
+            let end = if status == "completed" {
+                updated_at.parse().unwrap_or(start)
+            } else {
+                now
+            };
+            if end >= since {
+                spans.push(RunSpan {
+                    scope,
+                    start,
+                    end,
+                    status,
+                    conclusion,
+                });
+            }
+        }
+        Ok(spans)
+    }
+
     /// Get all unique scopes that have recorded data
     pub fn get_recorded_scopes(&self) -> Result<Vec<String>> {
         let mut stmt = self.conn.prepare(
@@ -424,25 +1373,12 @@ impl MetricsDb {
 
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
-
-    /// Calculate trend from two values
-    fn calculate_trend(current: f64, previous: f64) -> Trend {
-        let diff = current - previous;
-        let threshold = 0.05; // 5% threshold for significance
-
-        if diff.abs() < threshold {
-            Trend::Stable
-        } else if diff > 0.0 {
-            Trend::Up
-        } else {
-            Trend::Down
-        }
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::metrics::models::Trend;
     use tempfile::TempDir;
 
     fn setup_test_db() -> (MetricsDb, TempDir) {
@@ -496,6 +1432,452 @@ mod tests {
         assert_eq!(metrics.total_runs, 2);
         assert_eq!(metrics.successful_runs, 1);
         assert_eq!(metrics.failed_runs, 1);
+        assert_eq!(metrics.p50_duration_seconds, Some(300));
+        assert_eq!(metrics.p90_duration_seconds, Some(600));
+
+        // Today's aggregate, recomputed when a run is recorded again
+        db.record_workflow_runs(&scope, &runs[..1]).unwrap();
+        let daily: (String, u32, u32, u32, u32) = db
+            .conn
+            .query_row(
+                "SELECT date, total_runs, successful_runs, p50_duration_seconds,
+                    p99_duration_seconds
+                 FROM daily_metrics WHERE scope_identifier = 'test/repo'",
+                [],
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            daily,
+            (Utc::now().format("%Y-%m-%d").to_string(), 2, 1, 300, 600)
+        );
+    }
+
+    #[test]
+    fn test_record_and_query_runner_jobs() {
+        let (db, _temp_dir) = setup_test_db();
+        let scope = RunnerScope::parse("test/repo").unwrap();
+
+        let job = |id: u64, runner: &str, completed_at: &str| WorkflowJob {
+            id,
+            name: format!("build-{id}"),
+            status: "completed".to_string(),
+            conclusion: Some("failure".to_string()),
+            labels: vec!["self-hosted".to_string()],
+            runner_name: Some(runner.to_string()),
+            html_url: None,
+            run_id: 7,
+            workflow_name: Some("CI".to_string()),
+            created_at: None,
+            started_at: Some("2024-01-01T10:00:00Z".to_string()),
+            completed_at: Some(completed_at.to_string()),
+            steps: Vec::new(),
+        };
+        let jobs = vec![
+            job(1, "ci-01", "2024-01-01T10:05:00Z"),
+            job(2, "ci-02", "2024-01-01T10:06:00Z"),
+            job(3, "ci-01", "2024-01-01T10:09:00Z"),
+        ];
+        db.record_jobs(&scope, &jobs).unwrap();
+        // Recording again updates rather than duplicates
+        db.record_jobs(&scope, &jobs).unwrap();
+
+        let history = db.get_runner_jobs("ci-01", 10).unwrap();
+        let names: Vec<&str> = history.iter().map(|j| j.name.as_str()).collect();
+        assert_eq!(names, vec!["build-3", "build-1"]);
+        assert_eq!(history[0].duration_seconds(), Some(540));
+        assert_eq!(history[0].labels, vec!["self-hosted"]);
+        assert_eq!(history[0].queue_wait_seconds, None);
+        assert_eq!(db.get_runner_jobs("ci-01", 1).unwrap().len(), 1);
+        assert!(db.recorded_job_runs().unwrap().contains(&7));
+
+        // Error lines stick when the jobs are recorded again
+        db.record_job_errors(&scope, &[(2, "Step 'test' failed".to_string())])
+            .unwrap();
+        db.record_jobs(&scope, &jobs).unwrap();
+        let run = db.get_run_jobs(&scope, 7).unwrap();
+        assert_eq!(run.len(), 3);
+        assert_eq!(run[1].name, "build-2");
+        assert_eq!(run[1].error.as_deref(), Some("Step 'test' failed"));
+        assert_eq!(run[1].runner.as_deref(), Some("ci-02"));
+        assert!(db.get_run_jobs(&scope, 8).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recorded_jobs_keep_labels_and_queue_wait() {
+        let (db, _temp_dir) = setup_test_db();
+        let scope = RunnerScope::parse("test/repo").unwrap();
+        let job = WorkflowJob {
+            id: 1,
+            name: "build".to_string(),
+            status: "completed".to_string(),
+            conclusion: Some("success".to_string()),
+            labels: vec!["self-hosted".to_string(), "linux".to_string()],
+            runner_name: Some("ci-01".to_string()),
+            html_url: None,
+            run_id: 3,
+            workflow_name: Some("CI".to_string()),
+            created_at: Some("2024-01-01T09:58:30Z".to_string()),
+            started_at: Some("2024-01-01T10:00:00Z".to_string()),
+            completed_at: Some("2024-01-01T10:05:00Z".to_string()),
+            steps: Vec::new(),
+        };
+        db.record_jobs(&scope, std::slice::from_ref(&job)).unwrap();
+        // A later copy without the queue time keeps the recorded wait
+        db.record_jobs(
+            &scope,
+            &[WorkflowJob {
+                created_at: None,
+                ..job
+            }],
+        )
+        .unwrap();
+
+        let history = db.get_runner_jobs("ci-01", 10).unwrap();
+        assert_eq!(history[0].labels, vec!["self-hosted", "linux"]);
+        assert_eq!(history[0].queue_wait_seconds, Some(90));
+    }
+
+    #[test]
+    fn test_runner_failures() {
+        let (db, _temp_dir) = setup_test_db();
+        let scope = RunnerScope::parse("test/repo").unwrap();
+        let recent = (Utc::now() - Duration::hours(1))
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+
+        let job = |id: u64, runner: &str, conclusion: &str| WorkflowJob {
+            id,
+            name: "build".to_string(),
+            status: "completed".to_string(),
+            conclusion: Some(conclusion.to_string()),
+            labels: Vec::new(),
+            runner_name: Some(runner.to_string()),
+            html_url: None,
+            run_id: id,
+            workflow_name: None,
+            created_at: None,
+            started_at: Some(recent.clone()),
+            completed_at: Some(recent.clone()),
+            steps: Vec::new(),
+        };
+        let jobs = vec![
+            job(1, "ci-01", "failure"),
+            job(2, "ci-01", "timed_out"),
+            job(3, "ci-01", "success"),
+            job(4, "ci-01", "cancelled"),
+            job(5, "ci-02", "success"),
+            job(6, "GitHub Actions 3", "failure"),
+        ];
+        db.record_jobs(&scope, &jobs).unwrap();
+
+        let failures = db.get_runner_failures(7).unwrap();
+        let summary: Vec<(&str, u32, u32)> = failures
+            .iter()
+            .map(|r| (r.runner_name.as_str(), r.failed, r.total))
+            .collect();
+        assert_eq!(summary, vec![("ci-01", 2, 3), ("ci-02", 0, 1)]);
+    }
+
+    #[test]
+    fn test_job_events_queue_wait() {
+        let (db, _temp_dir) = setup_test_db();
+        let scope = RunnerScope::parse("test/repo").unwrap();
+        let at = |minutes: i64| {
+            (Utc::now() - Duration::hours(1) + Duration::minutes(minutes))
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string()
+        };
+        let event = |id: u64, status: &str, started: Option<String>| WorkflowJob {
+            id,
+            name: "build".to_string(),
+            status: status.to_string(),
+            conclusion: None,
+            labels: vec!["self-hosted".to_string()],
+            runner_name: None,
+            html_url: None,
+            run_id: 9,
+            workflow_name: Some("CI".to_string()),
+            created_at: Some(at(0)),
+            started_at: started,
+            completed_at: None,
+            steps: Vec::new(),
+        };
+
+        db.record_job_event(&scope, &event(1, "queued", None))
+            .unwrap();
+        db.record_job_event(&scope, &event(1, "in_progress", Some(at(2))))
+            .unwrap();
+        // A late "queued" delivery doesn't erase the start time
+        db.record_job_event(&scope, &event(1, "queued", None))
+            .unwrap();
+        db.record_job_event(&scope, &event(2, "in_progress", Some(at(6))))
+            .unwrap();
+
+        let metrics = db.get_scope_metrics(&scope, 7).unwrap();
+        assert_eq!(metrics.queue_wait_jobs, 2);
+        assert_eq!(metrics.avg_queue_wait_seconds, Some(240));
+        assert_eq!(metrics.p95_queue_wait_seconds, Some(360));
+    }
+
+    #[test]
+    fn test_queue_wait_percentile() {
+        let mut waits: Vec<u32> = (1..=20).rev().collect();
+        assert_eq!(percentile(&mut waits, 95.0), Some(19));
+        assert_eq!(percentile(&mut waits, 50.0), Some(10));
+        assert_eq!(percentile(&mut [7], 95.0), Some(7));
+        assert_eq!(percentile(&mut [], 95.0), None);
+    }
+
+    #[test]
+    fn test_self_updates_excluded_from_uptime() {
+        let (db, _temp_dir) = setup_test_db();
+        let scope = RunnerScope::Repository {
+            owner: "test".to_string(),
+            repo: "uptime".to_string(),
+        };
+        let runner = |status: &str| Runner {
+            id: 1,
+            name: "host-test__uptime".to_string(),
+            os: "Linux".to_string(),
+            status: status.to_string(),
+            busy: false,
+            labels: Vec::new(),
+        };
+        let start = Utc::now().timestamp() - 3600;
+        for (offset, status) in [
+            (0, "online"),
+            (60, "offline"),
+            (120, "offline"),
+            (600, "offline"),
+            (660, "online"),
+        ] {
+            db.insert_runner_snapshots(&scope, &[runner(status)], start + offset)
+                .unwrap();
+        }
+
+        let metrics = db.get_scope_metrics(&scope, 1).unwrap();
+        assert_eq!(metrics.runner_uptime, Some(40.0));
+        assert_eq!(metrics.self_updates, 0);
+
+        // An update from 50s to 100s covers the snapshots up to the grace period
+        let update = SelfUpdate {
+            started_at: start + 50,
+            finished_at: Some(start + 100),
+            version: None,
+        };
+        db.record_self_updates(&scope, "host-test__uptime", &[update])
+            .unwrap();
+        let metrics = db.get_scope_metrics(&scope, 1).unwrap();
+        assert_eq!(metrics.self_updates, 1);
+        let uptime = metrics.runner_uptime.unwrap();
+        assert!((uptime - 200.0 / 3.0).abs() < 1e-9);
+
+        // Scanning again fills in the version without adding another update
+        let update = SelfUpdate {
+            started_at: start + 50,
+            finished_at: None,
+            version: Some("2.320.0".to_string()),
+        };
+        db.record_self_updates(&scope, "host-test__uptime", &[update])
+            .unwrap();
+        assert_eq!(db.get_scope_metrics(&scope, 1).unwrap().self_updates, 1);
+    }
+
+    #[test]
+    fn test_runner_stats() {
+        let (db, _temp_dir) = setup_test_db();
+        let scope = RunnerScope::Repository {
+            owner: "test".to_string(),
+            repo: "hosts".to_string(),
+        };
+        let runner = |status: &str| Runner {
+            id: 1,
+            name: "ci-01-test__hosts".to_string(),
+            os: "Linux".to_string(),
+            status: status.to_string(),
+            busy: false,
+            labels: Vec::new(),
+        };
+        let start = Utc::now().timestamp() - 3600;
+        for (offset, status) in [(0, "online"), (60, "offline"), (120, "online")] {
+            db.insert_runner_snapshots(&scope, &[runner(status)], start + offset)
+                .unwrap();
+        }
+
+        let stats = db.get_runner_stats(1).unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].scope, "test/hosts");
+        assert_eq!(stats[0].runner_name, "ci-01-test__hosts");
+        assert_eq!((stats[0].snapshots, stats[0].online_snapshots), (3, 2));
+        assert_eq!((stats[0].jobs, stats[0].failed_jobs), (0, 0));
+    }
+
+    #[test]
+    fn test_slo_status() {
+        use crate::metrics::SloState;
+
+        let (db, _temp_dir) = setup_test_db();
+        let scope = RunnerScope::Repository {
+            owner: "test".to_string(),
+            repo: "slo".to_string(),
+        };
+        let runner = |status: &str| Runner {
+            id: 1,
+            name: "host-test__slo".to_string(),
+            os: "Linux".to_string(),
+            status: status.to_string(),
+            busy: false,
+            labels: Vec::new(),
+        };
+        let start = Utc::now().timestamp() - 3600;
+        for i in 0..10 {
+            let status = if i == 3 { "offline" } else { "online" };
+            db.insert_runner_snapshots(&scope, &[runner(status)], start + i * 60)
+                .unwrap();
+        }
+        let run = |id: u64, conclusion: &str| WorkflowRun {
+            id,
+            name: Some("CI".to_string()),
+            status: "completed".to_string(),
+            conclusion: Some(conclusion.to_string()),
+            head_branch: Some("main".to_string()),
+            created_at: "2024-01-01T10:00:00Z".to_string(),
+            updated_at: "2024-01-01T10:05:00Z".to_string(),
+            html_url: format!("https://github.com/test/slo/actions/runs/{id}"),
+        };
+        db.record_workflow_runs(&scope, &[run(1, "success"), run(2, "failure")])
+            .unwrap();
+
+        let slo = SloConfig {
+            availability: Some(95.0),
+            success_rate: Some(50.0),
+            ..Default::default()
+        };
+        let statuses = db.get_slo_status(&scope, &slo).unwrap();
+        assert_eq!(statuses.len(), 2);
+        // 9 of 10 snapshots online against a 95% target
+        assert_eq!(statuses[0].actual, Some(90.0));
+        assert_eq!(statuses[0].state, SloState::Violated);
+        // Exactly on target: the whole budget is spent
+        assert_eq!(statuses[1].actual, Some(50.0));
+        assert_eq!(statuses[1].state, SloState::AtRisk);
+
+        let other = RunnerScope::Organization {
+            org: "quiet".to_string(),
+        };
+        let statuses = db.get_slo_status(&other, &slo).unwrap();
+        assert!(statuses.iter().all(|s| s.state == SloState::NoData));
+    }
+
+    fn run(id: u64, conclusion: &str) -> WorkflowRun {
+        WorkflowRun {
+            id,
+            name: Some("Test".to_string()),
+            status: "completed".to_string(),
+            conclusion: Some(conclusion.to_string()),
+            head_branch: Some("main".to_string()),
+            created_at: "2024-01-01T10:00:00Z".to_string(),
+            updated_at: "2024-01-01T10:05:00Z".to_string(),
+            html_url: format!("https://github.com/test/repo/actions/runs/{id}"),
+        }
+    }
+
+    #[test]
+    fn test_scope_ids_canonicalized() {
+        let (db, _temp_dir) = setup_test_db();
+        // Rows written before identifiers were lowercased, one of them twice
+        for (id, scope) in [(1, "Test/Repo"), (2, "TEST/repo"), (2, "test/repo")] {
+            db.conn
+                .execute(
+                    "INSERT INTO workflow_runs (github_run_id, scope_identifier, status,
+                        created_at, updated_at, recorded_at)
+                     VALUES (?1, ?2, 'completed', '', '', ?3)",
+                    params![id, scope, Utc::now().timestamp()],
+                )
+                .unwrap();
+        }
+
+        db.canonicalize_scope_ids().unwrap();
+        db.record_workflow_runs(
+            &RunnerScope::parse("Test/REPO").unwrap(),
+            &[run(3, "success")],
+        )
+        .unwrap();
+
+        assert_eq!(db.get_recorded_scopes().unwrap(), vec!["test/repo"]);
+        let scope = RunnerScope::parse("test/repo").unwrap();
+        assert_eq!(db.get_scope_metrics(&scope, 30).unwrap().total_runs, 3);
+    }
+
+    #[test]
+    fn test_migrate_scope() {
+        let (db, _temp_dir) = setup_test_db();
+        let old = RunnerScope::parse("olduser/repo").unwrap();
+        let new = RunnerScope::parse("NewOrg/repo").unwrap();
+        db.record_workflow_runs(&old, &[run(1, "success"), run(2, "failure")])
+            .unwrap();
+        // Run 2 was also seen under the new name after the transfer
+        db.record_workflow_runs(&new, &[run(2, "failure"), run(3, "success")])
+            .unwrap();
+
+        assert_eq!(db.migrate_scope(&old, &new).unwrap(), 1);
+        assert_eq!(db.get_recorded_scopes().unwrap(), vec!["neworg/repo"]);
+        let metrics = db.get_scope_metrics(&new, 30).unwrap();
+        assert_eq!(metrics.total_runs, 3);
+        assert_eq!(metrics.failed_runs, 1);
+        assert_eq!(db.migrate_scope(&old, &new).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_prune_old_rows() {
+        let (db, _temp_dir) = setup_test_db();
+        let scope = RunnerScope::parse("test/repo").unwrap();
+        db.record_workflow_runs(&scope, &[run(1, "success")])
+            .unwrap();
+        let old = (Utc::now() - Duration::days(40)).timestamp();
+        db.conn
+            .execute(
+                "INSERT INTO runner_snapshots
+                    (scope_identifier, runner_id, runner_name, status, busy, recorded_at)
+                 VALUES ('test/repo', 1, 'ci-01', 'online', 0, ?1)",
+                params![old],
+            )
+            .unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO daily_metrics (scope_identifier, date) VALUES ('test/repo', '2020-01-01')",
+                [],
+            )
+            .unwrap();
+
+        assert_eq!(
+            db.prune(30).unwrap(),
+            vec![("runner_snapshots", 1), ("daily_metrics", 1)]
+        );
+        assert!(db.prune(30).unwrap().is_empty());
+        assert_eq!(db.get_scope_metrics(&scope, 30).unwrap().total_runs, 1);
+        db.vacuum().unwrap();
+    }
+
+    #[test]
+    fn test_queue_depth_peak() {
+        let (db, _temp_dir) = setup_test_db();
+        let scope = RunnerScope::parse("test/repo").unwrap();
+        // Deeper than a single page of polled runs
+        db.record_queue_depth(&scope, 150).unwrap();
+        db.record_queue_depth(&scope, 3).unwrap();
+
+        let metrics = db.get_scope_metrics(&scope, 30).unwrap();
+        assert_eq!(metrics.queue_history.iter().max(), Some(&150));
     }
 
     #[test]
@@ -507,11 +1889,18 @@ mod tests {
 
     #[test]
     fn test_trend_calculation() {
-        // Clear upward trend (diff = 0.10, > 0.05 threshold)
-        assert_eq!(MetricsDb::calculate_trend(1.0, 0.90), Trend::Up);
-        // Clear downward trend (diff = -0.10, > 0.05 threshold)
-        assert_eq!(MetricsDb::calculate_trend(0.80, 0.90), Trend::Down);
-        // Within threshold (diff = 0.02, < 0.05 threshold)
-        assert_eq!(MetricsDb::calculate_trend(0.92, 0.90), Trend::Stable);
+        // 11% more successful runs is an improvement
+        let success = TrendChange::between(1.0, 0.90, Better::Higher).unwrap();
+        assert_eq!(success.direction(), Trend::Up);
+        assert_eq!(success.improved(), Some(true));
+        // 2% is within the threshold
+        let success = TrendChange::between(0.92, 0.90, Better::Higher).unwrap();
+        assert_eq!(success.direction(), Trend::Stable);
+        // Jobs taking 20s instead of 10s doubled their duration, which is worse
+        let duration = TrendChange::between(20.0, 10.0, Better::Lower).unwrap();
+        assert_eq!(duration.direction(), Trend::Up);
+        assert_eq!(duration.improved(), Some(false));
+        assert_eq!(duration.display(), "↑ 100%");
+        assert_eq!(TrendChange::between(5.0, 0.0, Better::Lower), None);
     }
 }
@@ -0,0 +1,128 @@
+//! Per-host rollups: runners grouped by the machine they run on
+
+use std::collections::BTreeMap;
+
+use crate::github::{Runner, RunnerScope};
+
+/// Recorded history of one runner: status snapshots and job outcomes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunnerStats {
+    /// Scope identifier as stored in the metrics DB (`owner/repo` or `org:name`)
+    pub scope: String,
+    pub runner_name: String,
+    /// Status snapshots, not counting offline ones during self-updates
+    pub snapshots: u32,
+    pub online_snapshots: u32,
+    /// Jobs that ran to a conclusion (cancelled and skipped jobs don't count)
+    pub jobs: u32,
+    pub failed_jobs: u32,
+}
+
+/// Status and history of every runner on one host
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostSummary {
+    pub host: String,
+    /// Runners GitHub currently reports for the host
+    pub runners: u32,
+    pub online: u32,
+    pub busy: u32,
+    pub snapshots: u32,
+    pub online_snapshots: u32,
+    pub jobs: u32,
+    pub failed_jobs: u32,
+}
+
+impl HostSummary {
+    /// Share of snapshots with the host's runners online, as a percentage
+    pub fn uptime(&self) -> Option<f64> {
+        (self.snapshots > 0)
+            .then(|| f64::from(self.online_snapshots) / f64::from(self.snapshots) * 100.0)
+    }
+
+    /// Failure rate of the host's jobs as a fraction (0.0 - 1.0)
+    pub fn failure_rate(&self) -> Option<f64> {
+        (self.jobs > 0).then(|| f64::from(self.failed_jobs) / f64::from(self.jobs))
+    }
+}
+
+/// Host a runner runs on. `add` registers runners as `<hostname>-<scope>[-<instance>]`,
+/// so the hostname is what precedes the scope's directory name; runners named any
+/// other way (or truncated past the scope) count as a host of their own.
+pub fn host_of(runner_name: &str, scope: &str) -> String {
+    let Ok(scope) = RunnerScope::parse(scope) else {
+        return runner_name.to_string();
+    };
+    let marker = format!("-{}", scope.to_dir_name());
+    runner_name
+        .match_indices(&marker)
+        .find(|&(at, _)| {
+            let rest = &runner_name[at + marker.len()..];
+            at > 0 && (rest.is_empty() || rest.starts_with('-'))
+        })
+        .map_or_else(
+            || runner_name.to_string(),
+            |(at, _)| runner_name[..at].to_string(),
+        )
+}
+
+fn host_entry(hosts: &mut BTreeMap<String, HostSummary>, host: String) -> &mut HostSummary {
+    hosts.entry(host.clone()).or_insert_with(|| HostSummary {
+        host,
+        ..Default::default()
+    })
+}
+
+/// Roll up the runners GitHub reports now and their recorded history by host, sorted
+/// by host name
+pub fn summarize_hosts(
+    current: &[(RunnerScope, Vec<Runner>)],
+    history: &[RunnerStats],
+) -> Vec<HostSummary> {
+    let mut hosts = BTreeMap::new();
+    for (scope, runners) in current {
+        let scope_id = scope.to_display();
+        for runner in runners {
+            let host = host_entry(&mut hosts, host_of(&runner.name, &scope_id));
+            host.runners += 1;
+            if runner.status == "online" {
+                host.online += 1;
+            }
+            if runner.busy {
+                host.busy += 1;
+            }
+        }
+    }
+    for stats in history {
+        let host = host_entry(&mut hosts, host_of(&stats.runner_name, &stats.scope));
+        host.snapshots += stats.snapshots;
+        host.online_snapshots += stats.online_snapshots;
+        host.jobs += stats.jobs;
+        host.failed_jobs += stats.failed_jobs;
+    }
+    hosts.into_values().collect()
+}
+
+/// Table of host summaries, one line per host after a header
+pub fn host_table(hosts: &[HostSummary]) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{:<32} {:>7} {:>7} {:>5} {:>8} {:>9} {:>7}",
+        "Host", "Runners", "Online", "Busy", "Uptime", "Failed", "Rate"
+    )];
+    for host in hosts {
+        let uptime = host
+            .uptime()
+            .map_or("-".to_string(), |u| format!("{u:.1}%"));
+        let rate = host
+            .failure_rate()
+            .map_or("-".to_string(), |r| format!("{:.1}%", r * 100.0));
+        lines.push(format!(
+            "{:<32} {:>7} {:>7} {:>5} {uptime:>8} {:>9} {rate:>7}",
+            host.host,
+            host.runners,
+            format!("{}/{}", host.online, host.runners),
+            host.busy,
+            format!("{}/{}", host.failed_jobs, host.jobs),
+        ));
+    }
+    lines
+}
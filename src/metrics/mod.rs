@@ -1,5 +1,13 @@
 mod db;
-mod models;
+pub mod hosts;
+pub mod models;
+pub mod report;
+pub mod slo;
 
 pub use db::MetricsDb;
-pub use models::{ScopeMetrics, Trend};
+pub use hosts::{host_table, summarize_hosts};
+pub use models::{
+    bucket_peaks, fleet_failure_rate, format_duration, stack_spans, DailyMetrics, DurationBucket,
+    JobRecord, RunSpan, RunnerFailures, ScopeMetrics, TrendChange, QUEUE_HISTORY_BUCKETS,
+};
+pub use slo::{worst_slo, SloState, SloStatus};
@@ -1,4 +1,8 @@
-/// Trend direction compared to previous period
+use chrono::{DateTime, Utc};
+
+use super::slo::SloStatus;
+
+/// Which way a metric moved compared to the previous period
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Trend {
     Up,
@@ -15,6 +19,71 @@ impl Trend {
             Self::Stable => "-",
         }
     }
+
+    /// Name used in machine-readable output
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Stable => "stable",
+        }
+    }
+}
+
+/// Whether a metric improves when it rises (success rate) or when it falls (durations)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Better {
+    Higher,
+    Lower,
+}
+
+/// Relative changes smaller than this many percent count as stable
+pub const TREND_THRESHOLD_PERCENT: f64 = 5.0;
+
+/// A metric's change against the previous period
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrendChange {
+    /// Change relative to the previous value, in percent (12.0 is 12% more)
+    pub percent: f64,
+    pub better: Better,
+}
+
+impl TrendChange {
+    /// The change from `previous` to `current`. None when `previous` is 0, as there's no
+    /// relative change from nothing.
+    pub fn between(current: f64, previous: f64, better: Better) -> Option<Self> {
+        (previous != 0.0).then(|| Self {
+            percent: (current - previous) / previous.abs() * 100.0,
+            better,
+        })
+    }
+
+    /// Which way the value moved
+    pub fn direction(self) -> Trend {
+        if self.percent.abs() < TREND_THRESHOLD_PERCENT {
+            Trend::Stable
+        } else if self.percent > 0.0 {
+            Trend::Up
+        } else {
+            Trend::Down
+        }
+    }
+
+    /// Whether the move is an improvement; None when stable
+    pub fn improved(self) -> Option<bool> {
+        match self.direction() {
+            Trend::Stable => None,
+            direction => Some((direction == Trend::Up) == (self.better == Better::Higher)),
+        }
+    }
+
+    /// Direction and size, e.g. `↑ 12%`; `-` when stable
+    pub fn display(self) -> String {
+        match self.direction() {
+            Trend::Stable => Trend::Stable.symbol().to_string(),
+            direction => format!("{} {:.0}%", direction.symbol(), self.percent.abs()),
+        }
+    }
 }
 
 /// Aggregated metrics for a single scope (repo or org)
@@ -28,18 +97,36 @@ pub struct ScopeMetrics {
     pub failed_runs: u32,
     /// Success rate as a percentage (0.0 - 100.0)
     pub success_rate: f64,
-    /// Trend compared to previous period
-    pub success_trend: Option<Trend>,
+    /// Change of the success rate against the previous period
+    pub success_trend: Option<TrendChange>,
     /// Average job duration in seconds
     pub avg_duration_seconds: Option<u32>,
     /// Minimum job duration in seconds
     pub min_duration_seconds: Option<u32>,
     /// Maximum job duration in seconds
     pub max_duration_seconds: Option<u32>,
-    /// Duration trend compared to previous period
-    pub duration_trend: Option<Trend>,
+    /// Median job duration in seconds
+    pub p50_duration_seconds: Option<u32>,
+    /// 90th percentile of job durations in seconds
+    pub p90_duration_seconds: Option<u32>,
+    /// 99th percentile of job durations in seconds
+    pub p99_duration_seconds: Option<u32>,
+    /// Change of the average duration against the previous period
+    pub duration_trend: Option<TrendChange>,
     /// Runner uptime percentage (0.0 - 100.0)
     pub runner_uptime: Option<f64>,
+    /// Runner self-updates in the period, whose offline time isn't counted in uptime
+    pub self_updates: u32,
+    /// Peak number of queued runs in each of `QUEUE_HISTORY_BUCKETS` slices of the period
+    pub queue_history: Vec<u32>,
+    /// Jobs whose wait for a runner is known
+    pub queue_wait_jobs: u32,
+    /// Average time jobs waited for a runner, from being queued to starting
+    pub avg_queue_wait_seconds: Option<u32>,
+    /// 95th percentile of the time jobs waited for a runner
+    pub p95_queue_wait_seconds: Option<u32>,
+    /// Configured service level objectives, evaluated over their own windows
+    pub slo: Vec<SloStatus>,
 }
 
 impl ScopeMetrics {
@@ -54,9 +141,278 @@ impl ScopeMetrics {
     }
 }
 
+/// Format a duration in seconds as human-readable
+pub fn format_duration(seconds: u32) -> String {
+    if seconds < 60 {
+        format!("{seconds}s")
+    } else if seconds < 3600 {
+        let mins = seconds / 60;
+        let secs = seconds % 60;
+        if secs == 0 {
+            format!("{mins}m")
+        } else {
+            format!("{mins}m {secs}s")
+        }
+    } else {
+        let hours = seconds / 3600;
+        let mins = (seconds % 3600) / 60;
+        format!("{hours}h {mins}m")
+    }
+}
+
+/// Slices the metrics period is divided into for the queue depth chart
+pub const QUEUE_HISTORY_BUCKETS: usize = 14;
+
+/// Peak value of `(timestamp, value)` samples in each of `buckets` equal slices of
+/// `[from, to)`. Slices without samples are 0.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_wrap
+)]
+pub fn bucket_peaks(samples: &[(i64, u32)], from: i64, to: i64, buckets: usize) -> Vec<u32> {
+    let mut peaks = vec![0; buckets];
+    let total = to - from;
+    if buckets == 0 || total <= 0 {
+        return peaks;
+    }
+    for &(at, value) in samples {
+        if at < from || at >= to {
+            continue;
+        }
+        let index = ((at - from) * buckets as i64 / total) as usize;
+        peaks[index] = peaks[index].max(value);
+    }
+    peaks
+}
+
+/// Percentage of `true` samples of `(timestamp, online)` in each of `buckets` equal slices
+/// of `[from, to)`. None for slices without samples.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_wrap
+)]
+pub fn bucket_ratios(
+    samples: &[(i64, bool)],
+    from: i64,
+    to: i64,
+    buckets: usize,
+) -> Vec<Option<f64>> {
+    let mut counts = vec![(0u32, 0u32); buckets];
+    let total = to - from;
+    if buckets == 0 || total <= 0 {
+        return vec![None; buckets];
+    }
+    for &(at, online) in samples {
+        if at < from || at >= to {
+            continue;
+        }
+        let index = ((at - from) * buckets as i64 / total) as usize;
+        counts[index].0 += 1;
+        counts[index].1 += u32::from(online);
+    }
+    counts
+        .into_iter()
+        .map(|(samples, online)| {
+            (samples > 0).then(|| f64::from(online) / f64::from(samples) * 100.0)
+        })
+        .collect()
+}
+
+/// Nearest-rank percentile (`0.0 < p <= 100.0`) of `values`, which are sorted in place.
+/// None when there are no values.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+pub fn percentile(values: &mut [u32], p: f64) -> Option<u32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let rank = (p / 100.0 * values.len() as f64).ceil() as usize;
+    Some(values[rank.clamp(1, values.len()) - 1])
+}
+
+/// A job recorded for the runner that executed it
+#[derive(Debug, Clone)]
+pub struct JobRecord {
+    pub scope: String,
+    pub workflow: Option<String>,
+    pub name: String,
+    pub conclusion: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Labels the job requested in `runs-on`
+    pub labels: Vec<String>,
+    /// Seconds from being queued to starting on the runner
+    pub queue_wait_seconds: Option<i64>,
+    /// First error line of a failed job (see `github::first_error`)
+    pub error: Option<String>,
+    pub runner: Option<String>,
+}
+
+impl JobRecord {
+    /// Seconds from start to completion, when both are known
+    pub fn duration_seconds(&self) -> Option<i64> {
+        Some((self.completed_at? - self.started_at?).num_seconds().max(0))
+    }
+}
+
+/// Runners need this many jobs before their failure rate is compared with the fleet's
+pub const MIN_BLAME_JOBS: u32 = 5;
+
+/// Job outcomes of one runner, for the failures-by-runner view
+#[derive(Debug, Clone)]
+pub struct RunnerFailures {
+    pub runner_name: String,
+    /// Jobs that ran to a conclusion (cancelled and skipped jobs don't count)
+    pub total: u32,
+    pub failed: u32,
+}
+
+impl RunnerFailures {
+    /// Failure rate as a fraction (0.0 - 1.0)
+    pub fn failure_rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            f64::from(self.failed) / f64::from(self.total)
+        }
+    }
+
+    /// Whether the runner fails significantly more often than `fleet_rate`: more than two
+    /// standard errors above it, so a couple of unlucky jobs on a quiet runner don't count
+    pub fn is_outlier(&self, fleet_rate: f64) -> bool {
+        if self.total < MIN_BLAME_JOBS {
+            return false;
+        }
+        let variance = fleet_rate * (1.0 - fleet_rate) / f64::from(self.total);
+        if variance == 0.0 {
+            return self.failure_rate() > fleet_rate;
+        }
+        (self.failure_rate() - fleet_rate) / variance.sqrt() > 2.0
+    }
+}
+
+/// Failure rate over every runner's jobs
+pub fn fleet_failure_rate(runners: &[RunnerFailures]) -> f64 {
+    let total: u32 = runners.iter().map(|r| r.total).sum();
+    let failed: u32 = runners.iter().map(|r| r.failed).sum();
+    if total == 0 {
+        0.0
+    } else {
+        f64::from(failed) / f64::from(total)
+    }
+}
+
+/// Summary of run durations in seconds; all None without any
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DurationStats {
+    pub avg: Option<u32>,
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+    pub p50: Option<u32>,
+    pub p90: Option<u32>,
+    pub p99: Option<u32>,
+}
+
+impl DurationStats {
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_durations(mut durations: Vec<u32>) -> Self {
+        if durations.is_empty() {
+            return Self::default();
+        }
+        let total: u64 = durations.iter().map(|&d| u64::from(d)).sum();
+        let avg = (total / durations.len() as u64) as u32;
+        Self {
+            avg: Some(avg),
+            p50: percentile(&mut durations, 50.0),
+            p90: percentile(&mut durations, 90.0),
+            p99: percentile(&mut durations, 99.0),
+            // Sorted by `percentile`
+            min: durations.first().copied(),
+            max: durations.last().copied(),
+        }
+    }
+}
+
+/// One day's aggregates of completed runs (UTC days)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DailyMetrics {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub total_runs: u32,
+    pub successful_runs: u32,
+    pub failed_runs: u32,
+    pub avg_duration_seconds: Option<u32>,
+    pub p50_duration_seconds: Option<u32>,
+    pub p90_duration_seconds: Option<u32>,
+}
+
 /// Duration distribution bucket
 #[derive(Debug, Clone)]
 pub struct DurationBucket {
     pub label: String,
     pub count: u32,
 }
+
+/// A workflow run's time span, for the activity timeline
+#[derive(Debug, Clone)]
+pub struct RunSpan {
+    pub scope: String,
+    pub start: DateTime<Utc>,
+    /// Last update of a completed run, or now for a run still in progress
+    pub end: DateTime<Utc>,
+    pub status: String,
+    pub conclusion: Option<String>,
+}
+
+impl RunSpan {
+    /// Columns `[first, last)` covered on a `width`-column axis running from `from` to
+    /// `to`, or None if the span lies outside it. Every visible span gets at least one
+    /// column, so short runs don't disappear.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn columns(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        width: usize,
+    ) -> Option<(usize, usize)> {
+        let total = (to - from).num_seconds();
+        if width == 0 || total <= 0 || self.end < from || self.start > to {
+            return None;
+        }
+        let column = |t: DateTime<Utc>| {
+            let offset = (t - from).num_seconds().clamp(0, total);
+            (offset as f64 / total as f64 * width as f64) as usize
+        };
+        let first = column(self.start).min(width - 1);
+        let last = column(self.end).clamp(first + 1, width);
+        Some((first, last))
+    }
+}
+
+/// Stack spans into rows in which no two spans overlap, so concurrent runs show up as
+/// parallel bars. Spans are placed in start order, each in the first row with room.
+pub fn stack_spans(spans: &[RunSpan]) -> Vec<Vec<RunSpan>> {
+    let mut sorted = spans.to_vec();
+    sorted.sort_by_key(|s| s.start);
+    let mut rows: Vec<Vec<RunSpan>> = Vec::new();
+    for span in sorted {
+        match rows
+            .iter_mut()
+            .find(|row| row.last().is_some_and(|last| last.end <= span.start))
+        {
+            Some(row) => row.push(span),
+            None => rows.push(vec![span]),
+        }
+    }
+    rows
+}
@@ -0,0 +1,236 @@
+//! Metrics reports for `runner-mgr report` and `runner-mgr metrics`
+
+use std::fmt::Write as _;
+
+use serde_json::json;
+
+use super::models::{format_duration, ScopeMetrics, TrendChange};
+use super::slo::worst_slo;
+
+/// Render per-scope metrics over the last `days`, followed by every configured
+/// objective with its compliance and remaining error budget
+pub fn render(scopes: &[(String, ScopeMetrics)], days: i32) -> String {
+    let mut out = format!("Metrics report (last {days} days)\n\n");
+    if scopes.is_empty() {
+        out.push_str("No metrics recorded yet. Open the dashboard to start collecting.\n");
+        return out;
+    }
+
+    let _ = writeln!(
+        out,
+        "{:<32} {:>6} {:>6} {:>8} {:>8}  SLO",
+        "Scope", "Runs", "Failed", "Success", "Uptime"
+    );
+    for (scope, m) in scopes {
+        let success = if m.total_runs > 0 {
+            format!("{:.1}%", m.success_rate)
+        } else {
+            "-".to_string()
+        };
+        let uptime = m
+            .runner_uptime
+            .map_or("-".to_string(), |u| format!("{u:.1}%"));
+        let slo = worst_slo(&m.slo).map_or("-", |s| s.state.label());
+        let _ = writeln!(
+            out,
+            "{scope:<32} {:>6} {:>6} {success:>8} {uptime:>8}  {slo}",
+            m.total_runs, m.failed_runs
+        );
+    }
+
+    if scopes.iter().all(|(_, m)| m.slo.is_empty()) {
+        return out;
+    }
+    out.push_str("\nService level objectives\n");
+    let _ = writeln!(
+        out,
+        "{:<32} {:<13} {:>7} {:>6} {:>7} {:>7}  Status",
+        "Scope", "Objective", "Target", "Window", "Actual", "Budget"
+    );
+    for (scope, m) in scopes {
+        for status in &m.slo {
+            let actual = status
+                .actual
+                .map_or("-".to_string(), |a| format!("{a:.2}%"));
+            let _ = writeln!(
+                out,
+                "{scope:<32} {:<13} {:>7} {:>6} {actual:>7} {:>7}  {}",
+                status.objective.label(),
+                format!("{}%", status.target),
+                format!("{}d", status.window_days),
+                status.budget_display(),
+                status.state.label()
+            );
+        }
+    }
+    out
+}
+
+/// Render the aggregates the dashboard's metrics panels show, one row per scope
+pub fn render_metrics(scopes: &[(String, ScopeMetrics)], days: i32) -> String {
+    let mut out = format!("Metrics (last {days} days)\n\n");
+    if scopes.is_empty() {
+        out.push_str("No metrics recorded yet. Open the dashboard to start collecting.\n");
+        return out;
+    }
+
+    let _ = writeln!(
+        out,
+        "{:<32} {:>6} {:>12} {:>13} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>6}",
+        "Scope",
+        "Runs",
+        "Success",
+        "Avg",
+        "p50",
+        "p90",
+        "Min",
+        "Max",
+        "Uptime",
+        "Wait",
+        "p95",
+        "Queue"
+    );
+    let with_trend = |value: String, trend: Option<TrendChange>| match trend {
+        Some(t) => format!("{value} {}", t.display()),
+        None => value,
+    };
+    let duration = |s: Option<u32>| s.map_or("-".to_string(), format_duration);
+    for (scope, m) in scopes {
+        let success = if m.total_runs > 0 {
+            with_trend(format!("{:.1}%", m.success_rate), m.success_trend)
+        } else {
+            "-".to_string()
+        };
+        let avg = with_trend(duration(m.avg_duration_seconds), m.duration_trend);
+        let uptime = m
+            .runner_uptime
+            .map_or("-".to_string(), |u| format!("{u:.1}%"));
+        let _ = writeln!(
+            out,
+            "{scope:<32} {:>6} {success:>12} {avg:>13} {:>8} {:>8} {:>8} {:>8} {uptime:>8} {:>8} \
+             {:>8} {:>6}",
+            m.total_runs,
+            duration(m.p50_duration_seconds),
+            duration(m.p90_duration_seconds),
+            duration(m.min_duration_seconds),
+            duration(m.max_duration_seconds),
+            duration(m.avg_queue_wait_seconds),
+            duration(m.p95_queue_wait_seconds),
+            peak_queue(m)
+        );
+    }
+    out
+}
+
+/// CSV columns of `to_csv`, matching the fields of `to_json`
+const CSV_HEADERS: &[&str] = &[
+    "scope",
+    "total_runs",
+    "successful_runs",
+    "failed_runs",
+    "success_rate",
+    "success_trend",
+    "success_change_percent",
+    "avg_duration_seconds",
+    "min_duration_seconds",
+    "max_duration_seconds",
+    "p50_duration_seconds",
+    "p90_duration_seconds",
+    "p99_duration_seconds",
+    "duration_trend",
+    "duration_change_percent",
+    "runner_uptime",
+    "self_updates",
+    "queue_wait_jobs",
+    "avg_queue_wait_seconds",
+    "p95_queue_wait_seconds",
+    "peak_queue",
+];
+
+/// One CSV row per scope; unknown values are empty cells
+pub fn to_csv(scopes: &[(String, ScopeMetrics)]) -> String {
+    let opt = |v: Option<u32>| v.map(|v| v.to_string()).unwrap_or_default();
+    let trend = |t: Option<TrendChange>| {
+        t.map(|t| t.direction().label())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let change =
+        |t: Option<TrendChange>| t.map(|t| format!("{:.1}", t.percent)).unwrap_or_default();
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", CSV_HEADERS.join(","));
+    for (scope, m) in scopes {
+        let success_rate = if m.total_runs > 0 {
+            format!("{:.2}", m.success_rate)
+        } else {
+            String::new()
+        };
+        let cells = [
+            scope.clone(),
+            m.total_runs.to_string(),
+            m.successful_runs.to_string(),
+            m.failed_runs.to_string(),
+            success_rate,
+            trend(m.success_trend),
+            change(m.success_trend),
+            opt(m.avg_duration_seconds),
+            opt(m.min_duration_seconds),
+            opt(m.max_duration_seconds),
+            opt(m.p50_duration_seconds),
+            opt(m.p90_duration_seconds),
+            opt(m.p99_duration_seconds),
+            trend(m.duration_trend),
+            change(m.duration_trend),
+            m.runner_uptime
+                .map(|u| format!("{u:.2}"))
+                .unwrap_or_default(),
+            m.self_updates.to_string(),
+            m.queue_wait_jobs.to_string(),
+            opt(m.avg_queue_wait_seconds),
+            opt(m.p95_queue_wait_seconds),
+            peak_queue(m).to_string(),
+        ];
+        let _ = writeln!(out, "{}", cells.join(","));
+    }
+    out
+}
+
+/// A JSON document with the period and one object per scope; unknown values are null
+pub fn to_json(scopes: &[(String, ScopeMetrics)], days: i32) -> String {
+    let rows: Vec<_> = scopes
+        .iter()
+        .map(|(scope, m)| {
+            json!({
+                "scope": scope,
+                "total_runs": m.total_runs,
+                "successful_runs": m.successful_runs,
+                "failed_runs": m.failed_runs,
+                "success_rate": (m.total_runs > 0).then_some(m.success_rate),
+                "success_trend": m.success_trend.map(|t| t.direction().label()),
+                "success_change_percent": m.success_trend.map(|t| t.percent),
+                "avg_duration_seconds": m.avg_duration_seconds,
+                "min_duration_seconds": m.min_duration_seconds,
+                "max_duration_seconds": m.max_duration_seconds,
+                "p50_duration_seconds": m.p50_duration_seconds,
+                "p90_duration_seconds": m.p90_duration_seconds,
+                "p99_duration_seconds": m.p99_duration_seconds,
+                "duration_trend": m.duration_trend.map(|t| t.direction().label()),
+                "duration_change_percent": m.duration_trend.map(|t| t.percent),
+                "runner_uptime": m.runner_uptime,
+                "self_updates": m.self_updates,
+                "queue_wait_jobs": m.queue_wait_jobs,
+                "avg_queue_wait_seconds": m.avg_queue_wait_seconds,
+                "p95_queue_wait_seconds": m.p95_queue_wait_seconds,
+                "peak_queue": peak_queue(m),
+                "queue_history": m.queue_history,
+            })
+        })
+        .collect();
+    let doc = json!({ "days": days, "scopes": rows });
+    format!("{doc:#}\n")
+}
+
+/// Most runs queued at once over the period
+fn peak_queue(m: &ScopeMetrics) -> u32 {
+    m.queue_history.iter().max().copied().unwrap_or(0)
+}
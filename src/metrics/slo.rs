@@ -0,0 +1,135 @@
+//! Service level objectives: compliance and error budgets over rolling windows
+
+/// Objectives with less than this share of their error budget left are at risk
+pub const AT_RISK_BUDGET: f64 = 0.25;
+
+/// What an objective measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SloObjective {
+    /// Share of runner snapshots with the runner online
+    Availability,
+    /// Share of completed workflow runs that succeeded
+    SuccessRate,
+}
+
+impl SloObjective {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Availability => "availability",
+            Self::SuccessRate => "success rate",
+        }
+    }
+}
+
+/// Compliance of an objective, ordered from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SloState {
+    /// Nothing recorded in the window
+    NoData,
+    Met,
+    /// Still met, but less than `AT_RISK_BUDGET` of the error budget is left
+    AtRisk,
+    Violated,
+}
+
+impl SloState {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::NoData => "no data",
+            Self::Met => "met",
+            Self::AtRisk => "at risk",
+            Self::Violated => "violated",
+        }
+    }
+
+    /// Get the display symbol for this state
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::NoData => "-",
+            Self::Met => "✓",
+            Self::AtRisk => "!",
+            Self::Violated => "✗",
+        }
+    }
+}
+
+/// One objective evaluated over its rolling window
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloStatus {
+    pub objective: SloObjective,
+    /// Target in percent (0.0 - 100.0)
+    pub target: f64,
+    pub window_days: u32,
+    /// Measured value in percent, when anything was recorded
+    pub actual: Option<f64>,
+    /// Share of the error budget left (1.0 = untouched, negative once overspent)
+    pub budget_remaining: Option<f64>,
+    pub state: SloState,
+}
+
+impl SloStatus {
+    /// Evaluate `target` against `good` out of `total` events. The error budget is the
+    /// number of bad events the target allows: `(100 - target)%` of `total`.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn evaluate(
+        objective: SloObjective,
+        target: f64,
+        window_days: u32,
+        good: u64,
+        total: u64,
+    ) -> Self {
+        if total == 0 {
+            return Self {
+                objective,
+                target,
+                window_days,
+                actual: None,
+                budget_remaining: None,
+                state: SloState::NoData,
+            };
+        }
+        let bad = total.saturating_sub(good) as f64;
+        let allowed = total as f64 * (100.0 - target).max(0.0) / 100.0;
+        let remaining = if allowed > 0.0 {
+            1.0 - bad / allowed
+        } else if bad == 0.0 {
+            1.0
+        } else {
+            // A 100% target has no budget: any bad event overspends it
+            -1.0
+        };
+        let state = if remaining < 0.0 {
+            SloState::Violated
+        } else if remaining < AT_RISK_BUDGET {
+            SloState::AtRisk
+        } else {
+            SloState::Met
+        };
+        Self {
+            objective,
+            target,
+            window_days,
+            actual: Some(good.min(total) as f64 / total as f64 * 100.0),
+            budget_remaining: Some(remaining),
+            state,
+        }
+    }
+
+    /// Remaining error budget as a percentage, e.g. `62%` or `-40%` once overspent
+    pub fn budget_display(&self) -> String {
+        self.budget_remaining
+            .map_or("-".to_string(), |b| format!("{:.0}%", b * 100.0))
+    }
+}
+
+/// The objective in the worst state, for a one-cell summary
+pub fn worst_slo(statuses: &[SloStatus]) -> Option<&SloStatus> {
+    statuses.iter().max_by(|a, b| {
+        a.state.cmp(&b.state).then(
+            // Among equal states, the one with less budget left
+            b.budget_remaining
+                .unwrap_or(f64::INFINITY)
+                .total_cmp(&a.budget_remaining.unwrap_or(f64::INFINITY)),
+        )
+    })
+}
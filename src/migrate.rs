@@ -0,0 +1,93 @@
+//! Guided migration of manually installed runners (`runner-mgr migrate`): the checks
+//! run on each runner after it has been imported
+
+use crate::config::Config;
+use crate::github::{find_runner, GitHubClient, Runner, RunnerScope};
+use crate::runner::{self, RunnerInstance, RunnerStatus};
+
+/// Outcome of one post-migration check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, passed: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Check a migrated runner: it is listed as an instance, its service is running, and
+/// GitHub shows it online. `runners` are the scope's runners on GitHub, or the error
+/// listing them.
+pub fn check_runner(
+    instance: Option<&RunnerInstance>,
+    runners: Result<&[Runner], String>,
+) -> Vec<Check> {
+    let Some(instance) = instance else {
+        return vec![Check::new(
+            "instance",
+            false,
+            "not listed under the instances directory",
+        )];
+    };
+    let mut checks = vec![Check::new(
+        "instance",
+        true,
+        instance.dir.display().to_string(),
+    )];
+
+    let scope = &instance.scope;
+    checks.push(match (&instance.service_name, &instance.status) {
+        (Some(svc), RunnerStatus::Running) => Check::new("service", true, format!("{svc} running")),
+        (Some(svc), status) => Check::new(
+            "service",
+            false,
+            format!("{svc} {status}; start it with `runner-mgr start {scope}`"),
+        ),
+        (None, _) => Check::new(
+            "service",
+            false,
+            "no service installed; run `sudo ./svc.sh install` in the runner directory",
+        ),
+    });
+
+    checks.push(match runners {
+        Err(e) => Check::new("github", false, format!("could not list runners: {e}")),
+        Ok(runners) => match find_runner(runners, instance.runner_name.as_deref()) {
+            Some(r) if r.status == "online" => {
+                Check::new("github", true, format!("{} online", r.name))
+            }
+            Some(r) => Check::new(
+                "github",
+                false,
+                format!("{} {}; check `runner-mgr logs {scope}`", r.name, r.status),
+            ),
+            None => Check::new(
+                "github",
+                false,
+                "not registered on GitHub; the runner may have been removed there",
+            ),
+        },
+    });
+    checks
+}
+
+/// Run the post-migration checks for `scope`
+pub async fn verify(config: &Config, client: &GitHubClient, scope: &RunnerScope) -> Vec<Check> {
+    let instance = runner::list_instances(config)
+        .into_iter()
+        .find(|i| &i.scope == scope);
+    let runners = client
+        .list_runners(scope)
+        .await
+        .map(|list| list.runners)
+        .map_err(|e| format!("{e:#}"));
+    check_runner(instance.as_ref(), runners.as_deref().map_err(Clone::clone))
+}
@@ -0,0 +1,450 @@
+//! Notifications (Slack, Discord, desktop, command hooks) for failed workflow runs that
+//! used self-hosted runners, runners going offline and runner services that stop on
+//! their own. The dashboard and `runner-mgr serve` both send them.
+
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::process::Command;
+
+use crate::config::NotifyConfig;
+use crate::github::{GitHubClient, Runner, RunnerScope, WorkflowJob, WorkflowRun};
+use crate::runner::{RunnerInstance, RunnerStatus};
+
+/// Conclusions that count as a failure
+const FAILED_CONCLUSIONS: &[&str] = &["failure", "timed_out", "startup_failure"];
+
+pub fn is_failed(conclusion: Option<&str>) -> bool {
+    conclusion.is_some_and(|c| FAILED_CONCLUSIONS.contains(&c))
+}
+
+/// Remembers which completed runs have been seen, so each failure is reported once.
+/// Runs that had already completed when a scope is first seen are not reported.
+#[derive(Debug, Default)]
+pub struct RunTracker {
+    seen: HashSet<u64>,
+    primed: HashSet<RunnerScope>,
+}
+
+impl RunTracker {
+    /// Failed runs of `scope` that completed since the last call
+    pub fn new_failures(&mut self, scope: &RunnerScope, runs: &[WorkflowRun]) -> Vec<WorkflowRun> {
+        let first = self.primed.insert(scope.clone());
+        let mut failures = Vec::new();
+        for run in runs.iter().filter(|r| r.status == "completed") {
+            if self.seen.insert(run.id) && !first && is_failed(run.conclusion.as_deref()) {
+                failures.push(run.clone());
+            }
+        }
+        failures
+    }
+}
+
+/// Remembers each runner's status, so a runner going offline is reported once per
+/// outage. Runners already offline when first seen are not reported.
+#[derive(Debug, Default)]
+pub struct RunnerTracker {
+    online: HashMap<(RunnerScope, String), bool>,
+}
+
+impl RunnerTracker {
+    /// Runners of `scope` that were online at the last call and are offline now
+    pub fn went_offline(&mut self, scope: &RunnerScope, runners: &[Runner]) -> Vec<Runner> {
+        let mut offline = Vec::new();
+        for runner in runners {
+            let online = runner.status == "online";
+            let was_online = self
+                .online
+                .insert((scope.clone(), runner.name.clone()), online);
+            if was_online == Some(true) && !online {
+                offline.push(runner.clone());
+            }
+        }
+        offline
+    }
+}
+
+/// Remembers the status of each local runner service, so a service that stops without
+/// runner-mgr stopping it is reported once. Services already stopped when first seen
+/// are not reported.
+#[derive(Debug, Default)]
+pub struct ServiceTracker {
+    running: HashMap<String, bool>,
+}
+
+impl ServiceTracker {
+    /// Instances whose service was running at the last call and is stopped now
+    pub fn crashed(&mut self, instances: &[RunnerInstance]) -> Vec<RunnerInstance> {
+        let mut crashed = Vec::new();
+        for instance in instances {
+            let Some((service, running)) = service_state(instance) else {
+                continue;
+            };
+            if self.running.insert(service, running) == Some(true) && !running {
+                crashed.push(instance.clone());
+            }
+        }
+        crashed
+    }
+
+    /// Record the statuses of `instances` without reporting changes, after runner-mgr
+    /// itself started or stopped services
+    pub fn observe(&mut self, instances: &[RunnerInstance]) {
+        for instance in instances {
+            if let Some((service, running)) = service_state(instance) {
+                self.running.insert(service, running);
+            }
+        }
+    }
+}
+
+/// Service name and whether it runs, for instances with a known service status
+fn service_state(instance: &RunnerInstance) -> Option<(String, bool)> {
+    let running = match instance.status {
+        RunnerStatus::Running => true,
+        RunnerStatus::Stopped => false,
+        RunnerStatus::NoService | RunnerStatus::Unknown => return None,
+    };
+    Some((instance.service_name.clone()?, running))
+}
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    RunFailed,
+    RunnerOffline,
+    ServiceCrashed,
+    RestartFailed,
+}
+
+impl Event {
+    /// Name in the `{event}` placeholder
+    pub fn name(self) -> &'static str {
+        match self {
+            Event::RunFailed => "run_failed",
+            Event::RunnerOffline => "runner_offline",
+            Event::ServiceCrashed => "service_crashed",
+            Event::RestartFailed => "restart_failed",
+        }
+    }
+
+    /// The hook configured for this event
+    fn hook(self, config: &NotifyConfig) -> Option<&str> {
+        match self {
+            Event::RunFailed => config.on_run_failed.as_deref(),
+            Event::RunnerOffline => config.on_runner_offline.as_deref(),
+            Event::ServiceCrashed => config.on_service_crashed.as_deref(),
+            Event::RestartFailed => config.on_restart_failed.as_deref(),
+        }
+    }
+}
+
+/// Whether notifications about `event` go anywhere
+pub fn wants(config: &NotifyConfig, event: Event) -> bool {
+    config.slack_webhook.is_some()
+        || config.discord_webhook.is_some()
+        || config.desktop
+        || event.hook(config).is_some()
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub event: Event,
+    pub scope: String,
+    pub title: String,
+    pub body: String,
+    /// Link to the workflow run, empty for events without one
+    pub url: String,
+    /// Further hook placeholders of the event (`runner`, `status`, `service`, `restarts`)
+    pub details: Vec<(&'static str, String)>,
+}
+
+impl Notification {
+    /// Placeholders available to hooks
+    fn context(&self) -> Vec<(&'static str, String)> {
+        let mut context = vec![
+            ("event", self.event.name().to_string()),
+            ("scope", self.scope.clone()),
+            ("title", self.title.clone()),
+            ("body", self.body.clone()),
+            ("url", self.url.clone()),
+        ];
+        context.extend(self.details.iter().cloned());
+        context
+    }
+}
+
+/// Notification for a failed run, or None unless at least one of the failed jobs ran
+/// on a self-hosted runner (failures on GitHub-hosted runners are not reported).
+/// `runner_names` are the runners registered for the scope.
+pub fn failure_notification(
+    scope: &RunnerScope,
+    run: &WorkflowRun,
+    jobs: &[WorkflowJob],
+    runner_names: &[String],
+) -> Option<Notification> {
+    let failed: Vec<String> = jobs
+        .iter()
+        .filter(|job| is_failed(job.conclusion.as_deref()) && job.is_self_hosted(runner_names))
+        .map(|job| match &job.runner_name {
+            Some(runner) => format!("{} (on {runner})", job.name),
+            None => job.name.clone(),
+        })
+        .collect();
+    if failed.is_empty() {
+        return None;
+    }
+    let name = run.name.as_deref().unwrap_or("workflow");
+    let branch = run
+        .head_branch
+        .as_ref()
+        .map(|b| format!(" on {b}"))
+        .unwrap_or_default();
+    Some(Notification {
+        event: Event::RunFailed,
+        scope: scope.to_display(),
+        title: format!("{scope}: {name} failed{branch}"),
+        body: format!("Failed on self-hosted runners: {}", failed.join(", ")),
+        url: run.html_url.clone(),
+        details: Vec::new(),
+    })
+}
+
+/// Notification for a runner GitHub now reports offline
+pub fn offline_notification(scope: &RunnerScope, runner: &Runner) -> Notification {
+    Notification {
+        event: Event::RunnerOffline,
+        scope: scope.to_display(),
+        title: format!("{scope}: runner {} went offline", runner.name),
+        body: format!("GitHub reports runner {} as {}", runner.name, runner.status),
+        url: String::new(),
+        details: vec![
+            ("runner", runner.name.clone()),
+            ("status", runner.status.clone()),
+        ],
+    }
+}
+
+/// Notification for a local runner service that stopped on its own
+pub fn crash_notification(instance: &RunnerInstance) -> Notification {
+    let service = instance.service_name.clone().unwrap_or_default();
+    let id = instance.id();
+    Notification {
+        event: Event::ServiceCrashed,
+        scope: instance.scope.to_display(),
+        title: format!("{id}: runner service stopped"),
+        body: format!("{service} was running and stopped without runner-mgr stopping it"),
+        url: String::new(),
+        details: vec![
+            ("runner", instance.runner_name.clone().unwrap_or_default()),
+            ("service", service),
+        ],
+    }
+}
+
+/// Notification for a runner the watchdog restarted `restarts` times without it coming
+/// back online
+pub fn restart_failed_notification(instance: &RunnerInstance, restarts: u32) -> Notification {
+    let id = instance.id();
+    let runner = instance.runner_name.clone().unwrap_or_default();
+    Notification {
+        event: Event::RestartFailed,
+        scope: instance.scope.to_display(),
+        title: format!("{id}: runner still offline after {restarts} restarts"),
+        body: format!("GitHub still reports runner {runner} offline; check its logs"),
+        url: String::new(),
+        details: vec![
+            ("runner", runner),
+            ("service", instance.service_name.clone().unwrap_or_default()),
+            ("restarts", restarts.to_string()),
+        ],
+    }
+}
+
+/// Slack incoming webhook payload
+pub fn slack_payload(notification: &Notification) -> serde_json::Value {
+    let link = if notification.url.is_empty() {
+        String::new()
+    } else {
+        format!("\n<{}|View run>", notification.url)
+    };
+    serde_json::json!({
+        "text": format!("*{}*\n{}{link}", notification.title, notification.body),
+    })
+}
+
+/// Discord webhook payload
+pub fn discord_payload(notification: &Notification) -> serde_json::Value {
+    let link = if notification.url.is_empty() {
+        String::new()
+    } else {
+        format!("\n<{}>", notification.url)
+    };
+    serde_json::json!({
+        "content": format!("**{}**\n{}{link}", notification.title, notification.body),
+    })
+}
+
+async fn post_webhook(service: &str, webhook: &str, payload: &serde_json::Value) -> Result<()> {
+    let resp = reqwest::Client::new()
+        .post(webhook)
+        .json(payload)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {service}"))?;
+    if !resp.status().is_success() {
+        anyhow::bail!("{service} webhook returned {}", resp.status());
+    }
+    Ok(())
+}
+
+fn send_desktop(notification: &Notification) -> Result<()> {
+    let status = if cfg!(target_os = "macos") {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                "display notification {} with title {}",
+                quote(&notification.body),
+                quote(&notification.title)
+            ))
+            .status()
+    } else {
+        Command::new("notify-send")
+            .arg(&notification.title)
+            .arg(&notification.body)
+            .status()
+    }
+    .context("Failed to run desktop notifier")?;
+    if !status.success() {
+        anyhow::bail!("Desktop notifier exited with {status}");
+    }
+    Ok(())
+}
+
+/// Split a hook command into words at whitespace. Single or double quotes keep a word
+/// together; there is no other shell syntax.
+pub fn split_command(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (_, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+/// The program and arguments of a hook, with `{name}` placeholders replaced from
+/// `context`. Values are substituted after splitting, so they are never parsed by a
+/// shell and a branch or runner name can't inject arguments.
+pub fn render_hook(template: &str, context: &[(&str, String)]) -> Vec<String> {
+    split_command(template)
+        .into_iter()
+        .map(|word| {
+            context.iter().fold(word, |word, (name, value)| {
+                word.replace(&format!("{{{name}}}"), value)
+            })
+        })
+        .collect()
+}
+
+/// Run a hook command, with the context also in `RUNNER_MGR_<NAME>` environment
+/// variables
+pub fn run_hook(template: &str, context: &[(&str, String)]) -> Result<()> {
+    let words = render_hook(template, context);
+    let Some((program, args)) = words.split_first() else {
+        anyhow::bail!("Notification hook is empty");
+    };
+    let mut command = Command::new(program);
+    command.args(args);
+    for (name, value) in context {
+        command.env(format!("RUNNER_MGR_{}", name.to_ascii_uppercase()), value);
+    }
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run notification hook {program}"))?;
+    if !status.success() {
+        anyhow::bail!("Notification hook {program} exited with {status}");
+    }
+    Ok(())
+}
+
+/// Send `notification` to every configured destination and run the hook of its event
+pub async fn send(config: &NotifyConfig, notification: &Notification) -> Result<()> {
+    if let Some(webhook) = &config.slack_webhook {
+        post_webhook("Slack", webhook, &slack_payload(notification)).await?;
+    }
+    if let Some(webhook) = &config.discord_webhook {
+        post_webhook("Discord", webhook, &discord_payload(notification)).await?;
+    }
+    if config.desktop {
+        send_desktop(notification)?;
+    }
+    if let Some(hook) = notification.event.hook(config) {
+        run_hook(hook, &notification.context())?;
+    }
+    Ok(())
+}
+
+/// Send each notification wanted by `config`. Returns log lines describing what was
+/// sent.
+pub async fn send_all(config: NotifyConfig, notifications: Vec<Notification>) -> Vec<String> {
+    let mut log = Vec::new();
+    for notification in notifications {
+        if !wants(&config, notification.event) {
+            continue;
+        }
+        match send(&config, &notification).await {
+            Ok(()) => log.push(format!("[notify] {}", notification.title)),
+            Err(e) => log.push(format!("[notify] failed to send: {e:#}")),
+        }
+    }
+    log
+}
+
+/// A failed run to check, with the names of the runners registered for its scope
+pub struct FailedRun {
+    pub scope: RunnerScope,
+    pub run: WorkflowRun,
+    pub runner_names: Vec<String>,
+}
+
+/// Look up the jobs of each failed run and notify about those that failed on
+/// self-hosted runners. Returns log lines describing what was sent.
+pub async fn notify_failures(
+    client: GitHubClient,
+    config: NotifyConfig,
+    failures: Vec<FailedRun>,
+) -> Vec<String> {
+    let mut log = Vec::new();
+    for failed in failures {
+        let RunnerScope::Repository { owner, repo } = &failed.scope else {
+            continue;
+        };
+        let jobs = match client.list_jobs_for_run(owner, repo, failed.run.id).await {
+            Ok(list) => list.jobs,
+            Err(e) => {
+                log.push(format!("[notify] {}: {e}", failed.scope));
+                continue;
+            }
+        };
+        let Some(notification) =
+            failure_notification(&failed.scope, &failed.run, &jobs, &failed.runner_names)
+        else {
+            continue;
+        };
+        match send(&config, &notification).await {
+            Ok(()) => log.push(format!("[notify] {}", notification.title)),
+            Err(e) => log.push(format!("[notify] failed to send: {e}")),
+        }
+    }
+    log
+}
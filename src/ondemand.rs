@@ -0,0 +1,241 @@
+//! On-demand runners (systemd only): the service is started by a path unit when a
+//! trigger file appears in the queue directory, and stopped by a timer once idle
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::failure::Failure;
+use crate::github::RunnerScope;
+use crate::runner::{self, InstanceId};
+
+/// Drop-in added to the runner service in on-demand mode
+const DROPIN_NAME: &str = "runner-mgr-ondemand.conf";
+
+/// Trigger file that starts the runner instance `id`. Whatever detects queued work
+/// (e.g. `webhook-server`) creates it; the service removes it when it starts.
+pub fn trigger_path(config: &Config, id: &InstanceId) -> PathBuf {
+    config.queue_dir().join(id.dir_name())
+}
+
+/// Unit files for on-demand mode as `(file name, contents)`, all installed in
+/// `/etc/systemd/system`. The service drop-in is named `<service>.service.d/...`.
+pub fn units(
+    service: &str,
+    trigger: &Path,
+    dir: &Path,
+    idle_minutes: u32,
+) -> Vec<(String, String)> {
+    let trigger = trigger.display();
+    // "[R]" keeps pgrep from matching the checking shell's own command line
+    let worker = dir.join("bin").join("[R]unner.Worker");
+    let worker = worker.display();
+    vec![
+        (
+            format!("{service}-ondemand.path"),
+            format!(
+                "[Unit]\n\
+                 Description=Start {service} when work is queued\n\
+                 \n\
+                 [Path]\n\
+                 PathExists={trigger}\n\
+                 Unit={service}.service\n\
+                 \n\
+                 [Install]\n\
+                 WantedBy=paths.target\n"
+            ),
+        ),
+        (
+            format!("{service}.service.d/{DROPIN_NAME}"),
+            // "+" runs the removal as root, regardless of the service's User=
+            format!("[Service]\nExecStartPre=+/bin/rm -f {trigger}\n"),
+        ),
+        (
+            format!("{service}-idle.service"),
+            // Stop unless a job is running (Runner.Worker only exists during a job)
+            // or more work was queued meanwhile
+            format!(
+                "[Unit]\n\
+                 Description=Stop {service} when idle\n\
+                 \n\
+                 [Service]\n\
+                 Type=oneshot\n\
+                 ExecStart=/bin/sh -c 'pgrep -f {worker} >/dev/null || [ -e {trigger} ] || systemctl stop {service}.service'\n"
+            ),
+        ),
+        (
+            format!("{service}-idle.timer"),
+            format!(
+                "[Unit]\n\
+                 Description=Check whether {service} is idle\n\
+                 \n\
+                 [Timer]\n\
+                 OnBootSec={idle_minutes}min\n\
+                 OnUnitActiveSec={idle_minutes}min\n\
+                 \n\
+                 [Install]\n\
+                 WantedBy=timers.target\n"
+            ),
+        ),
+    ]
+}
+
+fn service_for(config: &Config, id: &InstanceId) -> Result<(PathBuf, String)> {
+    if config.runner_os == "darwin" {
+        anyhow::bail!("On-demand mode requires systemd (Linux)");
+    }
+    if config.rootless() {
+        anyhow::bail!(
+            "On-demand mode needs system services; it isn't available with use_sudo = false"
+        );
+    }
+    let dir = id.dir(config);
+    if !dir.exists() {
+        anyhow::bail!(Failure::RunnerNotFound(format!(
+            "No runner configured for {id}"
+        )));
+    }
+    let service = runner::read_service_name(&dir)
+        .with_context(|| format!("No service installed for {id}"))?;
+    Ok((dir, service))
+}
+
+/// Switch a runner to on-demand mode: the service no longer starts at boot, and
+/// starts when its trigger file appears
+pub fn enable(config: &Config, id: &InstanceId, idle_minutes: u32) -> Result<()> {
+    let (dir, service) = service_for(config, id)?;
+    let trigger = trigger_path(config, id);
+    let queue = config.queue_dir();
+    let queue = queue.to_string_lossy();
+
+    runner::run_elevated(config, &["mkdir", "-p", &queue])?;
+    runner::run_elevated(config, &["chown", &config.runner_user, &queue])?;
+    runner::run_elevated(
+        config,
+        &[
+            "mkdir",
+            "-p",
+            &format!("/etc/systemd/system/{service}.service.d"),
+        ],
+    )?;
+    for (name, contents) in units(&service, &trigger, &dir, idle_minutes) {
+        let tmp = std::env::temp_dir().join(format!("runner-mgr-{}", name.replace('/', "-")));
+        fs::write(&tmp, contents).with_context(|| format!("Failed to write {name}"))?;
+        let result = runner::run_elevated(
+            config,
+            &[
+                "cp",
+                &tmp.to_string_lossy(),
+                &format!("/etc/systemd/system/{name}"),
+            ],
+        );
+        let _ = fs::remove_file(&tmp);
+        result?;
+    }
+
+    runner::run_elevated(config, &["systemctl", "daemon-reload"])?;
+    runner::run_elevated(
+        config,
+        &["systemctl", "disable", &format!("{service}.service")],
+    )?;
+    runner::run_elevated(
+        config,
+        &[
+            "systemctl",
+            "enable",
+            "--now",
+            &format!("{service}-ondemand.path"),
+            &format!("{service}-idle.timer"),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Whether on-demand units are installed for `service`
+pub fn is_enabled(service: &str) -> bool {
+    Path::new(&format!("/etc/systemd/system/{service}-ondemand.path")).exists()
+}
+
+/// Return a runner to always-on mode
+pub fn disable(config: &Config, id: &InstanceId) -> Result<()> {
+    let (_, service) = service_for(config, id)?;
+    remove_units(config, &service)?;
+    runner::run_elevated(
+        config,
+        &[
+            "systemctl",
+            "enable",
+            "--now",
+            &format!("{service}.service"),
+        ],
+    )
+}
+
+/// Stop and delete the on-demand units for `service`
+pub fn remove_units(config: &Config, service: &str) -> Result<()> {
+    let _ = runner::run_elevated(
+        config,
+        &[
+            "systemctl",
+            "disable",
+            "--now",
+            &format!("{service}-ondemand.path"),
+            &format!("{service}-idle.timer"),
+        ],
+    );
+    runner::run_elevated(
+        config,
+        &[
+            "rm",
+            "-f",
+            &format!("/etc/systemd/system/{service}-ondemand.path"),
+            &format!("/etc/systemd/system/{service}-idle.service"),
+            &format!("/etc/systemd/system/{service}-idle.timer"),
+            &format!("/etc/systemd/system/{service}.service.d/{DROPIN_NAME}"),
+        ],
+    )?;
+    runner::run_elevated(config, &["systemctl", "daemon-reload"])
+}
+
+/// Queue work for a runner by creating its trigger file (as the runner user)
+pub fn trigger(config: &Config, id: &InstanceId) -> Result<()> {
+    service_for(config, id)?;
+    let trigger = trigger_path(config, id);
+    runner::run_elevated(
+        config,
+        &[
+            "-u",
+            &config.runner_user,
+            "touch",
+            &trigger.to_string_lossy(),
+        ],
+    )
+}
+
+/// Queue work for every on-demand instance that can take a job of `scope`: the
+/// scope's own instances and those of its organization. The trigger files are
+/// created as the current user, who must be able to write to the queue directory.
+pub fn queue_work(config: &Config, scope: &RunnerScope) -> Result<Vec<InstanceId>> {
+    let organization = match scope {
+        RunnerScope::Repository { owner, .. } => {
+            Some(RunnerScope::Organization { org: owner.clone() })
+        }
+        RunnerScope::Organization { .. } => None,
+    };
+    let mut triggered = Vec::new();
+    for instance in runner::list_instances(config) {
+        if instance.scope != *scope && Some(&instance.scope) != organization.as_ref() {
+            continue;
+        }
+        if !instance.service_name.as_deref().is_some_and(is_enabled) {
+            continue;
+        }
+        let id = instance.id();
+        let trigger = trigger_path(config, &id);
+        fs::File::create(&trigger)
+            .with_context(|| format!("Failed to create {}", trigger.display()))?;
+        triggered.push(id);
+    }
+    Ok(triggered)
+}
@@ -0,0 +1,90 @@
+//! Runners registered on GitHub, and deleting the ones whose host is gone (`gh-runners`)
+
+use anyhow::Result;
+
+use crate::github::Runner;
+use crate::runner::RunnerInstance;
+
+/// The instance on this host registered as `runner`, if any
+pub fn local_instance<'a>(
+    runner: &Runner,
+    instances: &'a [RunnerInstance],
+) -> Option<&'a RunnerInstance> {
+    instances
+        .iter()
+        .find(|i| i.runner_name.as_deref() == Some(runner.name.as_str()))
+}
+
+/// Runners picked for deletion, and the ones left alone with why
+#[derive(Debug, Default)]
+pub struct Selection<'a> {
+    pub delete: Vec<&'a Runner>,
+    pub skipped: Vec<(&'a Runner, String)>,
+}
+
+/// Pick the runners of one scope to delete: those named in `names` (by name or ID), or
+/// every runner when none are named, limited to offline ones with `offline`. Busy
+/// runners and runners of an instance on this host (`instances`, of the same scope) are
+/// skipped; those have to go through `runner-mgr remove`.
+pub fn select<'a>(
+    runners: &'a [Runner],
+    instances: &[RunnerInstance],
+    names: &[String],
+    offline: bool,
+) -> Result<Selection<'a>> {
+    if names.is_empty() && !offline {
+        anyhow::bail!("Name the runners to delete, or pass --offline to delete every offline one");
+    }
+    let mut picked: Vec<&Runner> = Vec::new();
+    for name in names {
+        let runner = runners
+            .iter()
+            .find(|r| r.name == *name || r.id.to_string() == *name)
+            .ok_or_else(|| {
+                let known: Vec<&str> = runners.iter().map(|r| r.name.as_str()).collect();
+                anyhow::anyhow!(
+                    "No runner named '{name}' on GitHub (runners: {})",
+                    if known.is_empty() {
+                        "none".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                )
+            })?;
+        if !picked.iter().any(|p| p.id == runner.id) {
+            picked.push(runner);
+        }
+    }
+    if names.is_empty() {
+        picked = runners.iter().collect();
+    }
+
+    let mut selection = Selection::default();
+    for runner in picked {
+        if let Some(instance) = local_instance(runner, instances) {
+            selection.skipped.push((
+                runner,
+                format!(
+                    "belongs to {} on this host; use `runner-mgr remove {}`",
+                    instance.id(),
+                    instance.id()
+                ),
+            ));
+        } else if runner.busy {
+            selection
+                .skipped
+                .push((runner, "is running a job".to_string()));
+        } else if offline && runner.status != "offline" {
+            // Runners named on the command line say why they stay; the others just
+            // aren't offline
+            if !names.is_empty() {
+                selection
+                    .skipped
+                    .push((runner, format!("is {}", runner.status)));
+            }
+        } else {
+            selection.delete.push(runner);
+        }
+    }
+    Ok(selection)
+}
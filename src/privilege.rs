@@ -0,0 +1,226 @@
+//! Privilege escalation: pick the tool (sudo, doas, run0 or none), authenticate
+//! once per command and keep the credentials alive
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+static NO_SUDO: AtomicBool = AtomicBool::new(false);
+static KEEP_ALIVE_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// How often cached credentials are refreshed (sudo's default timeout is 5 minutes)
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_mins(1);
+
+/// Command used to run steps as root or as the runner user
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Escalation {
+    #[default]
+    Sudo,
+    Doas,
+    Run0,
+    /// Run commands directly (runner-mgr already runs as root or as the runner user)
+    None,
+}
+
+impl Escalation {
+    // Takes `&self` because serde's `skip_serializing_if` passes a reference
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The first escalation tool found on this host, falling back to sudo. On Windows
+    /// runner-mgr is run from an elevated prompt instead.
+    pub fn detect() -> Self {
+        if cfg!(windows) {
+            return Self::None;
+        }
+        [Self::Sudo, Self::Doas, Self::Run0]
+            .into_iter()
+            .find(|tool| {
+                Command::new("sh")
+                    .args(["-c", &format!("command -v {tool}")])
+                    .stdout(Stdio::null())
+                    .status()
+                    .is_ok_and(|s| s.success())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Translate a sudo-style invocation for this tool.
+    ///
+    /// `args` may start with the sudo flags `-H`, `-n` and `-u <user>`, followed by
+    /// the command. Returns the program to run and its arguments.
+    pub fn wrap<'a>(self, args: &[&'a str]) -> (&'a str, Vec<&'a str>) {
+        let mut non_interactive = false;
+        let mut set_home = false;
+        let mut user = None;
+        let mut rest = args;
+        loop {
+            match rest {
+                ["-H", tail @ ..] => {
+                    set_home = true;
+                    rest = tail;
+                }
+                ["-n", tail @ ..] => {
+                    non_interactive = true;
+                    rest = tail;
+                }
+                ["-u", u, tail @ ..] => {
+                    user = Some(*u);
+                    rest = tail;
+                }
+                _ => break,
+            }
+        }
+
+        let mut wrapped = Vec::new();
+        let program = match self {
+            Self::Sudo => {
+                if set_home {
+                    wrapped.push("-H");
+                }
+                if non_interactive {
+                    wrapped.push("-n");
+                }
+                "sudo"
+            }
+            Self::Doas => {
+                if non_interactive {
+                    wrapped.push("-n");
+                }
+                "doas"
+            }
+            Self::Run0 => {
+                if non_interactive {
+                    wrapped.push("--no-ask-password");
+                }
+                "run0"
+            }
+            Self::None => {
+                let Some((program, tail)) = rest.split_first() else {
+                    return ("true", Vec::new());
+                };
+                return (program, tail.to_vec());
+            }
+        };
+        if let Some(u) = user {
+            wrapped.extend(["-u", u]);
+        }
+        wrapped.extend_from_slice(rest);
+        (program, wrapped)
+    }
+
+    /// Build a `Command` for a sudo-style invocation (see [`Escalation::wrap`])
+    pub fn command(self, args: &[&str]) -> Command {
+        let (program, args) = self.wrap(args);
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        cmd
+    }
+}
+
+impl fmt::Display for Escalation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Sudo => "sudo",
+            Self::Doas => "doas",
+            Self::Run0 => "run0",
+            Self::None => "none",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Assert that no step needs elevation: commands that would escalate fail up front
+pub fn set_no_sudo(enabled: bool) {
+    NO_SUDO.store(enabled, Ordering::SeqCst);
+}
+
+pub fn no_sudo() -> bool {
+    NO_SUDO.load(Ordering::SeqCst)
+}
+
+/// Whether we are already running as root
+pub fn is_root() -> bool {
+    Command::new("id")
+        .arg("-u")
+        .output()
+        .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).trim() == "0")
+}
+
+/// Whether the tool can run without prompting (credentials cached or passwordless)
+fn cached(escalation: Escalation) -> bool {
+    escalation
+        .command(&["-n", "true"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Make sure elevation can be used for the rest of this command.
+///
+/// `reason` completes the sentence "runner-mgr needs administrator access to ..."
+/// and is shown before the password prompt. sudo credentials are validated once
+/// with `sudo -v` (doas with `doas true`, which caches with `persist`) and sudo is
+/// refreshed in the background so later steps don't prompt again. run0 authenticates
+/// each call through polkit, so only the explanation is shown.
+pub fn ensure(escalation: Escalation, reason: &str) -> Result<()> {
+    if escalation == Escalation::None || is_root() {
+        return Ok(());
+    }
+    if no_sudo() {
+        anyhow::bail!(
+            "Elevation required to {reason} ({escalation}), but --no-sudo was given. \
+             Run as root or without --no-sudo."
+        );
+    }
+    if cached(escalation) {
+        start_keep_alive(escalation);
+        return Ok(());
+    }
+
+    println!("runner-mgr needs administrator access to {reason}.");
+    let validate: &[&str] = match escalation {
+        Escalation::Sudo => &["sudo", "-v"],
+        Escalation::Doas => &["doas", "true"],
+        Escalation::Run0 | Escalation::None => return Ok(()),
+    };
+    println!(
+        "{escalation} will ask for your password once; it is kept alive until this command finishes."
+    );
+    let status = Command::new(validate[0])
+        .args(&validate[1..])
+        .status()
+        .with_context(|| format!("Failed to run {escalation}"))?;
+    if !status.success() {
+        anyhow::bail!("{escalation} authentication failed");
+    }
+
+    start_keep_alive(escalation);
+    Ok(())
+}
+
+/// Refresh cached sudo credentials in the background until the process exits
+fn start_keep_alive(escalation: Escalation) {
+    if escalation != Escalation::Sudo || KEEP_ALIVE_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| loop {
+        std::thread::sleep(KEEP_ALIVE_INTERVAL);
+        let refreshed = Command::new("sudo")
+            .args(["-n", "-v"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success());
+        if !refreshed {
+            break;
+        }
+    });
+}
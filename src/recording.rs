@@ -0,0 +1,299 @@
+//! Recording of the GitHub API responses the dashboard fetches (`dashboard --record`)
+//! and replay of them without network access (`dashboard --replay`), so a rendering
+//! problem seen with someone's fleet can be reproduced from their recording.
+//!
+//! A recording is a directory: `instances.json` with the local runner instances, and
+//! `responses/` with one numbered JSON file per API response in the order they arrived.
+//! Replay answers each request with the next recorded response to the same request and
+//! keeps repeating the last one once they run out.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use anyhow::{Context, Result};
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+
+use crate::github::RunnerScope;
+use crate::runner::{self, RunnerInstance, RunnerStatus};
+
+/// Response headers kept in a recording: those the dashboard reads. Request headers,
+/// and with them the token, are never recorded.
+const RECORDED_HEADERS: &[&str] = &[
+    "github-authentication-token-expiration",
+    "retry-after",
+    "x-accepted-github-permissions",
+    "x-accepted-oauth-scopes",
+    "x-oauth-scopes",
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-reset",
+    "x-ratelimit-resource",
+    "x-ratelimit-used",
+];
+
+const RESPONSES_DIR: &str = "responses";
+const INSTANCES_FILE: &str = "instances.json";
+
+/// One recorded API response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exchange {
+    pub method: String,
+    /// Request URL without the API root, e.g. `/repos/owner/repo/actions/runners`
+    pub path: String,
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+impl Exchange {
+    fn into_response(self) -> Response {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        Response::from(
+            builder
+                .body(self.body)
+                .unwrap_or_else(|_| http::Response::new(String::new())),
+        )
+    }
+}
+
+/// A local instance as written to `instances.json`
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedInstance {
+    scope: String,
+    name: Option<String>,
+    dir: PathBuf,
+    service_name: Option<String>,
+    status: RunnerStatus,
+    runner_name: Option<String>,
+}
+
+/// Writes a recording into a directory
+pub struct Recorder {
+    dir: PathBuf,
+    /// Number of the next response file
+    next: Mutex<u64>,
+}
+
+impl Recorder {
+    /// Start a recording in `dir`, which must not hold one already
+    pub fn create(dir: &Path) -> Result<Self> {
+        let responses = dir.join(RESPONSES_DIR);
+        if responses.exists() {
+            anyhow::bail!("{} already contains a recording", dir.display());
+        }
+        fs::create_dir_all(&responses)
+            .with_context(|| format!("Failed to create {}", responses.display()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            next: Mutex::new(1),
+        })
+    }
+
+    pub fn save(&self, exchange: &Exchange) -> Result<()> {
+        let mut next = self.next.lock().unwrap_or_else(PoisonError::into_inner);
+        let path = self
+            .dir
+            .join(RESPONSES_DIR)
+            .join(format!("{:06}.json", *next));
+        fs::write(&path, serde_json::to_string_pretty(exchange)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        *next += 1;
+        Ok(())
+    }
+
+    /// Replace the recorded instances with `instances`
+    pub fn save_instances(&self, instances: &[RunnerInstance]) -> Result<()> {
+        let recorded: Vec<RecordedInstance> = instances
+            .iter()
+            .map(|i| RecordedInstance {
+                scope: i.scope.to_display(),
+                name: i.name.clone(),
+                dir: i.dir.clone(),
+                service_name: i.service_name.clone(),
+                status: i.status.clone(),
+                runner_name: i.runner_name.clone(),
+            })
+            .collect();
+        let path = self.dir.join(INSTANCES_FILE);
+        fs::write(&path, serde_json::to_string_pretty(&recorded)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Recorded responses per method and path, with the number of those already replayed
+type Responses = HashMap<(String, String), (Vec<Exchange>, usize)>;
+
+/// A recording loaded for replay
+pub struct Replay {
+    responses: Mutex<Responses>,
+    instances: Vec<RunnerInstance>,
+}
+
+impl Replay {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let responses_dir = dir.join(RESPONSES_DIR);
+        let mut files: Vec<PathBuf> = fs::read_dir(&responses_dir)
+            .with_context(|| format!("No recording in {}", dir.display()))?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+        let mut responses = Responses::new();
+        for path in files {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let exchange: Exchange = serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            responses
+                .entry((exchange.method.clone(), exchange.path.clone()))
+                .or_default()
+                .0
+                .push(exchange);
+        }
+
+        let path = dir.join(INSTANCES_FILE);
+        let instances = match fs::read_to_string(&path) {
+            Ok(content) => {
+                let recorded: Vec<RecordedInstance> = serde_json::from_str(&content)
+                    .with_context(|| format!("Failed to parse {}", path.display()))?;
+                recorded
+                    .into_iter()
+                    .map(|i| {
+                        Ok(RunnerInstance {
+                            scope: RunnerScope::parse(&i.scope)?,
+                            name: i.name,
+                            dir: i.dir,
+                            service_name: i.service_name,
+                            status: i.status,
+                            runner_name: i.runner_name,
+                        })
+                    })
+                    .collect::<Result<_>>()?
+            }
+            Err(_) => Vec::new(),
+        };
+        Ok(Self {
+            responses: Mutex::new(responses),
+            instances,
+        })
+    }
+
+    /// The next recorded response to `method path`; the last one again once all have
+    /// been replayed
+    pub fn next(&self, method: &str, path: &str) -> Option<Exchange> {
+        let mut responses = self
+            .responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let (exchanges, replayed) = responses.get_mut(&(method.to_string(), path.to_string()))?;
+        let exchange = exchanges
+            .get(*replayed)
+            .or_else(|| exchanges.last())?
+            .clone();
+        *replayed += 1;
+        Some(exchange)
+    }
+
+    pub fn instances(&self) -> &[RunnerInstance] {
+        &self.instances
+    }
+}
+
+enum Mode {
+    Record(Recorder),
+    Replay(Replay),
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+/// Record the API responses of this process into `dir`
+pub fn start_recording(dir: &Path) -> Result<()> {
+    let recorder = Recorder::create(dir)?;
+    MODE.set(Mode::Record(recorder))
+        .map_err(|_| anyhow::anyhow!("Already recording or replaying"))
+}
+
+/// Answer the API requests of this process from the recording in `dir`
+pub fn start_replay(dir: &Path) -> Result<()> {
+    let replay = Replay::load(dir)?;
+    MODE.set(Mode::Replay(replay))
+        .map_err(|_| anyhow::anyhow!("Already recording or replaying"))
+}
+
+pub fn replaying() -> bool {
+    matches!(MODE.get(), Some(Mode::Replay(_)))
+}
+
+/// The recorded response to `method path` while replaying (404 if there is none), or
+/// None when not replaying
+pub fn replay(method: &str, path: &str) -> Option<Response> {
+    let Some(Mode::Replay(replay)) = MODE.get() else {
+        return None;
+    };
+    let exchange = replay.next(method, path).unwrap_or_else(|| Exchange {
+        method: method.to_string(),
+        path: path.to_string(),
+        status: 404,
+        headers: BTreeMap::new(),
+        body: r#"{"message":"Not in the recording"}"#.to_string(),
+    });
+    Some(exchange.into_response())
+}
+
+/// Save `resp` to the recording when recording, handing back a response with the same
+/// status, headers and body. Only GET responses are saved: other requests return tokens
+/// or change something.
+pub async fn record(method: &str, path: &str, resp: Response) -> reqwest::Result<Response> {
+    let Some(Mode::Record(recorder)) = MODE.get() else {
+        return Ok(resp);
+    };
+    if method != "GET" {
+        return Ok(resp);
+    }
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let body = resp.bytes().await?;
+    let exchange = Exchange {
+        method: method.to_string(),
+        path: path.to_string(),
+        status: status.as_u16(),
+        headers: RECORDED_HEADERS
+            .iter()
+            .filter_map(|&name| {
+                let value = headers.get(name)?.to_str().ok()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+    if let Err(e) = recorder.save(&exchange) {
+        runner::log_line(&format!("Recording: {e:#}"));
+    }
+    let mut rebuilt = http::Response::new(body);
+    *rebuilt.status_mut() = status;
+    *rebuilt.headers_mut() = headers;
+    Ok(Response::from(rebuilt))
+}
+
+/// Save the local instances to the recording when recording
+pub fn record_instances(instances: &[RunnerInstance]) {
+    if let Some(Mode::Record(recorder)) = MODE.get() {
+        if let Err(e) = recorder.save_instances(instances) {
+            runner::log_line(&format!("Recording: {e:#}"));
+        }
+    }
+}
+
+/// The recorded local instances while replaying
+pub fn replayed_instances() -> Option<Vec<RunnerInstance>> {
+    match MODE.get() {
+        Some(Mode::Replay(replay)) => Some(replay.instances().to_vec()),
+        _ => None,
+    }
+}
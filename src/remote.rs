@@ -0,0 +1,130 @@
+//! Runner discovery on other machines over SSH
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::github::RunnerScope;
+
+/// Levels below each remote search root where runner directories are looked for
+pub const DEFAULT_REMOTE_DEPTH: u32 = 3;
+
+/// Directories searched on a remote host (`$HOME` is the SSH user's home)
+const REMOTE_ROOTS: &str = r#""$HOME" /opt /home /srv"#;
+
+/// A runner directory found on a remote host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteRunner {
+    /// SSH destination the runner was found on, as configured (`user@host` or an alias)
+    pub host: String,
+    pub path: String,
+    pub scope: RunnerScope,
+    pub agent_name: Option<String>,
+}
+
+impl RemoteRunner {
+    /// `host:path`, as shown by `scan`
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.host, self.path)
+    }
+}
+
+/// Shell script run on a remote host: prints one `<dir>\t<.runner JSON>` line per
+/// directory up to `depth` levels below the search roots that holds a configured runner
+pub fn scan_script(depth: u32) -> String {
+    format!(
+        r#"find {REMOTE_ROOTS} -maxdepth {} -name .runner -type f 2>/dev/null | sort -u |
+while IFS= read -r f; do
+  d=$(dirname "$f")
+  [ -f "$d/config.sh" ] || continue
+  printf '%s\t' "$d"
+  tr -d '\r\n' < "$f"
+  echo
+done
+"#,
+        depth + 1
+    )
+}
+
+/// Parse the output of `scan_script`. Lines without a parsable GitHub URL are skipped.
+pub fn parse_scan_output(host: &str, output: &str) -> Vec<RemoteRunner> {
+    #[derive(serde::Deserialize)]
+    struct RunnerConfig {
+        #[serde(rename = "gitHubUrl")]
+        github_url: Option<String>,
+        #[serde(rename = "agentName")]
+        agent_name: Option<String>,
+    }
+
+    output
+        .lines()
+        .filter_map(|line| {
+            let (path, json) = line.split_once('\t')?;
+            let json = json.trim().trim_start_matches('\u{feff}');
+            let config: RunnerConfig = serde_json::from_str(json).ok()?;
+            let scope = RunnerScope::from_github_url(&config.github_url?).ok()?;
+            Some(RemoteRunner {
+                host: host.to_string(),
+                path: path.to_string(),
+                scope,
+                agent_name: config.agent_name,
+            })
+        })
+        .collect()
+}
+
+/// Search `host` for runner directories over SSH. Runs non-interactively: a host that
+/// needs a password or an unknown host key fails instead of prompting.
+pub fn scan_host(host: &str, depth: u32) -> Result<Vec<RemoteRunner>> {
+    let mut child = Command::new("ssh")
+        .args([
+            "-o",
+            "BatchMode=yes",
+            "-o",
+            "ConnectTimeout=10",
+            host,
+            "sh",
+            "-s",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run ssh")?;
+    child
+        .stdin
+        .take()
+        .context("ssh has no input")?
+        .write_all(scan_script(depth).as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "ssh {host} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse_scan_output(
+        host,
+        &String::from_utf8_lossy(&output.stdout),
+    ))
+}
+
+/// Search every host in parallel; results are in the order of `hosts`
+pub fn scan_hosts(hosts: &[String], depth: u32) -> Vec<(String, Result<Vec<RemoteRunner>>)> {
+    std::thread::scope(|s| {
+        let handles: Vec<_> = hosts
+            .iter()
+            .map(|host| s.spawn(move || scan_host(host, depth)))
+            .collect();
+        hosts
+            .iter()
+            .zip(handles)
+            .map(|(host, handle)| {
+                let result = handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("Scan of {host} panicked")));
+                (host.clone(), result)
+            })
+            .collect()
+    })
+}
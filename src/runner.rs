@@ -1,13 +1,20 @@
 use anyhow::{Context, Result};
+use std::fmt::Write as _;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::SyncSender;
 use std::sync::Mutex;
 
-use crate::config::{Config, ScanConfig};
-use crate::github::{GitHubClient, RunnerScope};
+use crate::config::{Config, PreStartConfig, RunnerProfile, ScanConfig};
+use crate::download;
+use crate::failure::Failure;
+use crate::follow::{self, LogFollow};
+use crate::github::{self, GitHubClient, RunnerScope, DEFAULT_RUNNER_GROUP_ID};
+use crate::interrupt;
+use crate::journal;
+use crate::winsvc;
 
 /// Max depth for scanning ~/Developer directory (more conservative for default path)
 const DEFAULT_DEVELOPER_SCAN_DEPTH: u32 = 3;
@@ -15,6 +22,9 @@ const DEFAULT_DEVELOPER_SCAN_DEPTH: u32 = 3;
 /// Max depth for user-specified recursive paths (more generous for explicit choices)
 const USER_RECURSIVE_SCAN_DEPTH: u32 = 5;
 
+/// How often `scan_tree` reports progress
+const SCAN_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
 static VERBOSE: AtomicBool = AtomicBool::new(false);
 static LOG_SENDER: Mutex<Option<SyncSender<String>>> = Mutex::new(None);
 
@@ -35,7 +45,7 @@ pub fn set_log_sender(sender: Option<SyncSender<String>>) {
 }
 
 /// Log a verbose message - sends to both stderr and optional channel
-fn verbose_log(msg: &str) {
+pub(crate) fn log_line(msg: &str) {
     eprintln!("{msg}");
     if let Ok(guard) = LOG_SENDER.lock() {
         if let Some(sender) = guard.as_ref() {
@@ -45,15 +55,155 @@ fn verbose_log(msg: &str) {
     }
 }
 
+/// Longest instance name accepted by `add --name-suffix`
+const MAX_INSTANCE_NAME_LEN: usize = 32;
+
+/// One runner instance: its scope plus, for additional runners of the same scope, an
+/// instance name. Written `owner/repo@name` (or `org:name@name`) on the command line and
+/// in the instance directory name; a scope's first runner has no name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InstanceId {
+    pub scope: RunnerScope,
+    pub name: Option<String>,
+}
+
+impl InstanceId {
+    /// A scope's first (unnamed) instance
+    pub fn primary(scope: RunnerScope) -> Self {
+        Self { scope, name: None }
+    }
+
+    /// Parse `owner/repo`, `org:name`, or either with an `@instance` suffix
+    pub fn parse(target: &str) -> Result<Self> {
+        match target.rsplit_once('@') {
+            Some((scope, name)) => {
+                validate_instance_name(name)?;
+                Ok(Self {
+                    scope: RunnerScope::parse(scope)?,
+                    name: Some(name.to_string()),
+                })
+            }
+            None => Ok(Self::primary(RunnerScope::parse(target)?)),
+        }
+    }
+
+    /// Instance directory name: the scope's, then `@name`
+    pub fn dir_name(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{}@{name}", self.scope.to_dir_name()),
+            None => self.scope.to_dir_name(),
+        }
+    }
+
+    pub fn from_dir_name(dir_name: &str) -> Option<Self> {
+        match dir_name.rsplit_once('@') {
+            Some((scope, name)) => {
+                validate_instance_name(name).ok()?;
+                Some(Self {
+                    scope: RunnerScope::from_dir_name(scope)?,
+                    name: Some(name.to_string()),
+                })
+            }
+            None => Some(Self::primary(RunnerScope::from_dir_name(dir_name)?)),
+        }
+    }
+
+    pub fn dir(&self, config: &Config) -> PathBuf {
+        config.instance_path(&self.dir_name())
+    }
+
+    /// Name `add` registers the instance under: `default_runner_name`, then `-<name>`
+    pub fn runner_name(&self, hostname: &str) -> String {
+        let base = format!("{hostname}-{}", self.scope.to_dir_name());
+        match &self.name {
+            Some(name) => {
+                // Keep the instance name when truncating, so runners stay distinct
+                let suffix = format!("-{name}");
+                let base: String = base
+                    .chars()
+                    .take(64usize.saturating_sub(suffix.len()))
+                    .collect();
+                format!("{base}{suffix}")
+            }
+            None => base.chars().take(64).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for InstanceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}@{name}", self.scope),
+            None => write!(f, "{}", self.scope),
+        }
+    }
+}
+
+/// Instance names are letters, digits, `-` and `_`, so they fit directory, runner and
+/// service names
+pub fn validate_instance_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_INSTANCE_NAME_LEN {
+        anyhow::bail!("Instance name must be 1 to {MAX_INSTANCE_NAME_LEN} characters");
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!("Instance name '{name}' may only contain letters, digits, '-' and '_'");
+    }
+    Ok(())
+}
+
+/// IDs for `count` new instances of `scope`: the unnamed instance if it is free, then
+/// numbered ones (`@2`, `@3`, ...) skipping those in `existing`
+pub fn next_instance_ids(
+    existing: &[InstanceId],
+    scope: &RunnerScope,
+    count: u32,
+) -> Vec<InstanceId> {
+    let taken = |id: &InstanceId| existing.contains(id);
+    let mut ids = Vec::new();
+    let primary = InstanceId::primary(scope.clone());
+    if !taken(&primary) && count > 0 {
+        ids.push(primary);
+    }
+    let mut index = 2u32;
+    while ids.len() < count as usize {
+        let id = InstanceId {
+            scope: scope.clone(),
+            name: Some(index.to_string()),
+        };
+        if !taken(&id) {
+            ids.push(id);
+        }
+        index += 1;
+    }
+    ids
+}
+
 #[derive(Debug, Clone)]
 pub struct RunnerInstance {
     pub scope: RunnerScope,
+    /// Instance name for additional runners of the scope (see `InstanceId`)
+    pub name: Option<String>,
     pub dir: PathBuf,
     pub service_name: Option<String>,
     pub status: RunnerStatus,
+    /// Name the runner is registered under on GitHub (from `.runner`)
+    pub runner_name: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl RunnerInstance {
+    pub fn id(&self) -> InstanceId {
+        InstanceId {
+            scope: self.scope.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum RunnerStatus {
     Running,
     Stopped,
@@ -94,27 +244,188 @@ pub fn list_instances(config: &Config) -> Vec<RunnerInstance> {
             continue;
         };
 
-        // Parse the directory name into a RunnerScope
-        let Some(scope) = RunnerScope::from_dir_name(&name) else {
+        // Parse the directory name into a scope and instance name
+        let Some(id) = InstanceId::from_dir_name(&name) else {
             continue;
         };
 
         let service_name = read_service_name(&path);
         let status = check_service_status(config, service_name.as_deref());
+        let runner_name = read_agent_name(&path);
 
         instances.push(RunnerInstance {
-            scope,
+            scope: id.scope,
+            name: id.name,
             dir: path,
             service_name,
             status,
+            runner_name,
         });
     }
 
-    instances.sort_by(|a, b| a.scope.to_display().cmp(&b.scope.to_display()));
+    // A scope's unnamed instance first, then numbered ones in order
+    instances.sort_by_key(|a| {
+        let name = a
+            .name
+            .as_deref()
+            .map(|n| (n.parse::<u32>().unwrap_or(u32::MAX), n.to_string()));
+        (a.scope.to_display(), name)
+    });
     instances
 }
 
-fn read_service_name(dir: &Path) -> Option<String> {
+/// A directory in the instances directory whose name isn't an instance name, so
+/// `list_instances` skips it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnrecognizedDir {
+    pub path: PathBuf,
+    /// The scope its `.runner` file registered it for, if it has one
+    pub scope: Option<RunnerScope>,
+}
+
+/// Directories in the instances directory that aren't managed under an instance name.
+/// A directory that a managed instance links to (after `import`) is managed.
+pub fn list_unrecognized(config: &Config) -> Vec<UnrecognizedDir> {
+    let Ok(entries) = fs::read_dir(config.instances_dir()) else {
+        return Vec::new();
+    };
+    let mut managed = std::collections::HashSet::new();
+    let mut unrecognized = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let recognized = entry
+            .file_name()
+            .to_str()
+            .and_then(InstanceId::from_dir_name)
+            .is_some();
+        if recognized {
+            if let Ok(target) = path.canonicalize() {
+                managed.insert(target);
+            }
+        } else {
+            unrecognized.push(path);
+        }
+    }
+
+    let mut dirs: Vec<_> = unrecognized
+        .into_iter()
+        .filter(|path| !path.canonicalize().is_ok_and(|p| managed.contains(&p)))
+        .map(|path| {
+            let scope = fs::read_to_string(path.join(".runner"))
+                .ok()
+                .and_then(|c| parse_scope_from_runner_config_at(&c, &config.web_url()).ok());
+            UnrecognizedDir { path, scope }
+        })
+        .collect();
+    dirs.sort_by(|a, b| a.path.cmp(&b.path));
+    dirs
+}
+
+/// An instance directory named by an older runner-mgr, before scope names were escaped,
+/// whose name no longer parses back to its scope
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyDir {
+    pub path: PathBuf,
+    /// The instance it belongs to, from its `.runner` file and `@name` suffix
+    pub id: InstanceId,
+}
+
+/// Unrecognized instance directories whose name is the old, unescaped name of the scope
+/// in their `.runner` file, and whose current instance name is still free
+pub fn legacy_instance_dirs(config: &Config) -> Vec<LegacyDir> {
+    list_unrecognized(config)
+        .into_iter()
+        .filter_map(|dir| {
+            let scope = dir.scope?;
+            let file_name = dir.path.file_name()?.to_str()?;
+            let (base, name) = match file_name.rsplit_once('@') {
+                Some((base, name)) if validate_instance_name(name).is_ok() => {
+                    (base, Some(name.to_string()))
+                }
+                _ => (file_name, None),
+            };
+            let legacy_name = match &scope {
+                RunnerScope::Repository { owner, repo } => format!("{owner}__{repo}"),
+                RunnerScope::Organization { org } => format!("org__{org}"),
+            };
+            if base != legacy_name {
+                return None;
+            }
+            let id = InstanceId { scope, name };
+            (!id.dir(config).exists()).then_some(LegacyDir { path: dir.path, id })
+        })
+        .collect()
+}
+
+/// Link a legacy directory under its current instance name. The directory stays where
+/// it is, so its service keeps working.
+pub fn relink_legacy_dir(config: &Config, dir: &LegacyDir) -> Result<()> {
+    link_instance(config, &dir.path, &dir.id.dir(config))
+}
+
+/// Give a runner directory inside the instances directory its instance name by
+/// renaming it. Runners with an installed service can't be renamed, since the service
+/// runs them from their current path; `import` links them instead.
+pub fn rename_into_instance(
+    config: &Config,
+    path: &str,
+    scope_override: Option<&str>,
+) -> Result<()> {
+    let plan = plan_import(config, path, scope_override)?;
+    let instances_dir = config
+        .instances_dir()
+        .canonicalize()
+        .context("Failed to resolve the instances directory")?;
+    if plan.source.parent() != Some(instances_dir.as_path()) {
+        anyhow::bail!(
+            "--rename only applies to directories in {}; import {} without it",
+            instances_dir.display(),
+            plan.source.display()
+        );
+    }
+    if let Some(svc) = &plan.service {
+        anyhow::bail!(
+            "{} runs as service {svc} from its current path; import it without --rename \
+             to link it under its instance name instead",
+            plan.source.display()
+        );
+    }
+
+    println!(
+        "Renaming {} to {}...",
+        plan.source.display(),
+        plan.target.display()
+    );
+    if config.runner_os == "windows" {
+        run_cmd(
+            "cmd",
+            &[
+                "/C",
+                "move",
+                &plan.source.to_string_lossy(),
+                &plan.target.to_string_lossy(),
+            ],
+        )?;
+    } else {
+        run_elevated(
+            config,
+            &[
+                "-u",
+                &config.runner_user,
+                "mv",
+                &plan.source.to_string_lossy(),
+                &plan.target.to_string_lossy(),
+            ],
+        )?;
+    }
+    println!("Runner directory is now managed as {}", plan.scope);
+    Ok(())
+}
+
+pub(crate) fn read_service_name(dir: &Path) -> Option<String> {
     let service_file = dir.join(".service");
     fs::read_to_string(service_file)
         .ok()
@@ -122,20 +433,132 @@ fn read_service_name(dir: &Path) -> Option<String> {
         .filter(|s| !s.is_empty())
 }
 
+/// Files `config.sh` writes when a runner registers; the runner can't start without them
+pub const CREDENTIAL_FILES: [&str; 2] = [".credentials", ".credentials_rsaparams"];
+
+/// What is wrong with a registered runner's credentials, if anything: a credential
+/// file that is missing, empty or (when readable by us) not JSON. Files we aren't
+/// allowed to read are fine, since they belong to the runner user. Directories that
+/// were never registered (no `.runner`) have no credentials to check.
+pub fn credential_problem(dir: &Path) -> Option<String> {
+    if !dir.join(".runner").exists() {
+        return None;
+    }
+    for name in CREDENTIAL_FILES {
+        let path = dir.join(name);
+        match fs::metadata(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => continue,
+            Err(_) => return Some(format!("{name} is missing")),
+            Ok(meta) if meta.len() == 0 => return Some(format!("{name} is empty")),
+            Ok(_) => {}
+        }
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let content = content.strip_prefix('\u{feff}').unwrap_or(&content);
+                if serde_json::from_str::<serde_json::Value>(content).is_err() {
+                    return Some(format!("{name} is corrupt"));
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {}
+            Err(e) => return Some(format!("{name} is unreadable: {e}")),
+        }
+    }
+    None
+}
+
+/// How to recover an instance whose credentials are lost: register it again
+pub fn reregister_hint(id: &InstanceId) -> String {
+    format!("re-register it: runner-mgr reregister {id}")
+}
+
+/// Whether the instance was registered (it has a `.runner`) but is missing from its
+/// scope's runners on GitHub: the registration was deleted, e.g. after the runner was
+/// offline for weeks or removed in the web UI
+pub fn registration_missing(instance: &RunnerInstance, runners: &[github::Runner]) -> bool {
+    instance
+        .runner_name
+        .as_ref()
+        .is_some_and(|name| !runners.iter().any(|r| r.name == *name))
+}
+
+/// Whether runner log output shows GitHub rejecting the runner's registration, which
+/// restarting doesn't fix
+pub fn registration_revoked(log: &str) -> bool {
+    const MARKERS: [&str; 4] = [
+        "registration has been deleted",
+        "registration was not found",
+        "taskagentnotfoundexception",
+        "invalid_client",
+    ];
+    let lower = log.to_lowercase();
+    MARKERS.iter().any(|marker| lower.contains(marker))
+}
+
+/// Record `labels` in `.labels`, after they were changed on GitHub
+pub fn write_labels(config: &Config, dir: &Path, labels: &[String]) -> Result<()> {
+    write_instance_file(config, dir, ".labels", &labels.join(","))
+}
+
+/// Read the labels recorded in `.labels` when the runner was registered
+pub fn read_labels(dir: &Path) -> Vec<String> {
+    fs::read_to_string(dir.join(".labels"))
+        .map(|s| crate::labels::parse_labels(s.trim()))
+        .unwrap_or_default()
+}
+
+/// Runner version of an instance, from `bin/Runner.Listener --version`. Runs as the
+/// current user, so it needs read access to the instance directory.
+pub fn installed_version(dir: &Path) -> Option<String> {
+    let output = Command::new(dir.join("bin").join("Runner.Listener"))
+        .arg("--version")
+        .output()
+        .ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !version.is_empty()).then_some(version)
+}
+
+/// Name `add` registers a runner under: `<hostname>-<scope dir name>`, at most 64
+/// characters
+pub fn default_runner_name(hostname: &str, scope: &RunnerScope) -> String {
+    format!("{hostname}-{}", scope.to_dir_name())
+        .chars()
+        .take(64)
+        .collect()
+}
+
+/// Read the registered runner name (`agentName`) from the `.runner` file
+pub fn read_agent_name(dir: &Path) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct RunnerConfig {
+        #[serde(rename = "agentName")]
+        agent_name: Option<String>,
+    }
+
+    let content = fs::read_to_string(dir.join(".runner")).ok()?;
+    // Strip UTF-8 BOM if present
+    let content = content.strip_prefix('\u{feff}').unwrap_or(&content);
+    serde_json::from_str::<RunnerConfig>(content)
+        .ok()?
+        .agent_name
+}
+
 fn check_service_status(config: &Config, service_name: Option<&str>) -> RunnerStatus {
     let Some(svc) = service_name else {
         return RunnerStatus::NoService;
     };
 
-    if config.runner_os == "darwin" {
+    if config.runner_os == "windows" {
+        winsvc::service_status(svc)
+    } else if config.runner_os == "darwin" {
         // For LaunchAgents, we need to check the correct gui/<uid> domain
         let Ok((_, service_target)) = parse_macos_service(svc, &config.runner_user) else {
             return RunnerStatus::Unknown;
         };
 
         // Use launchctl print to check if service is loaded and running
-        let output = Command::new("sudo")
-            .args(["launchctl", "print", &service_target])
+        let output = config
+            .escalation
+            .command(&["launchctl", "print", &service_target])
             .output();
 
         match output {
@@ -154,9 +577,11 @@ fn check_service_status(config: &Config, service_name: Option<&str>) -> RunnerSt
             _ => RunnerStatus::Stopped,
         }
     } else {
-        let output = Command::new("systemctl")
-            .args(["is-active", "--quiet", svc])
-            .output();
+        let mut systemctl = Command::new("systemctl");
+        if config.rootless() {
+            systemctl.arg("--user");
+        }
+        let output = systemctl.args(["is-active", "--quiet", svc]).output();
 
         match output {
             Ok(o) if o.status.success() => RunnerStatus::Running,
@@ -196,148 +621,1052 @@ fn parse_macos_service(service_name: &str, runner_user: &str) -> Result<(String,
         let uid = get_user_uid(runner_user)?;
         format!("gui/{uid}/{service_label}")
     } else {
-        // LaunchDaemon: use system domain
-        format!("system/{service_label}")
+        // LaunchDaemon: use system domain
+        format!("system/{service_label}")
+    };
+
+    Ok((service_label, service_target))
+}
+
+/// Get the UID for a given username
+fn get_user_uid(username: &str) -> Result<u32> {
+    let output = Command::new("id")
+        .args(["-u", username])
+        .output()
+        .context("Failed to get user UID")?;
+
+    if !output.status.success() {
+        anyhow::bail!("Failed to get UID for user {username}");
+    }
+
+    let uid_str = String::from_utf8_lossy(&output.stdout);
+    uid_str.trim().parse::<u32>().context("Failed to parse UID")
+}
+
+/// File name of the runner release package for `os` (`linux`, `darwin` or `windows`)
+/// and `arch`
+pub fn runner_package(os: &str, arch: &str, version: &str) -> String {
+    match os {
+        "darwin" => format!("actions-runner-osx-{arch}-{version}.tar.gz"),
+        "windows" => format!("actions-runner-win-{arch}-{version}.zip"),
+        _ => format!("actions-runner-linux-{arch}-{version}.tar.gz"),
+    }
+}
+
+/// Download URL of a runner release package
+pub fn runner_package_url(package: &str, version: &str) -> String {
+    format!("https://github.com/actions/runner/releases/download/v{version}/{package}")
+}
+
+/// Download the runner package for this host to the temp directory, reusing an earlier
+/// download of the same version. The package is checked against the SHA-256 published
+/// with the release; a cached download that doesn't match is fetched again. Returns its
+/// path.
+pub async fn download_runner_package(
+    config: &Config,
+    client: &GitHubClient,
+    version: &str,
+) -> Result<PathBuf> {
+    let package = runner_package(&config.runner_os, &config.runner_arch, version);
+    let archive = std::env::temp_dir().join(&package);
+    let sha256 = match client.get_runner_release(version).await {
+        Ok(release) => release.package_sha256(&package),
+        Err(e) => {
+            eprintln!("warning: could not fetch release checksums: {e:#}");
+            None
+        }
+    };
+    if sha256.is_none() {
+        eprintln!("warning: no published SHA-256 for {package}; skipping verification");
+    }
+
+    if archive.exists() {
+        let check = sha256
+            .as_deref()
+            .map_or(Ok(()), |hex| download::verify_sha256(&archive, hex));
+        if let Err(e) = check {
+            eprintln!("Cached download is invalid ({e:#}); downloading again");
+            fs::remove_file(&archive)?;
+        } else {
+            println!("Using cached download: {}", archive.display());
+            return Ok(archive);
+        }
+    }
+    println!("Downloading {package}...");
+    download::download(
+        client.http(),
+        &runner_package_url(&package, version),
+        &archive,
+        sha256.as_deref(),
+    )
+    .await
+    .context("Failed to download runner binary")?;
+    Ok(archive)
+}
+
+/// Create `dir` and its parents, owned by the runner user
+fn create_owned_dir(config: &Config, dir: &str) -> Result<()> {
+    if config.runner_os == "windows" {
+        // The service account is chosen by `config.cmd`; directories keep default ACLs
+        return fs::create_dir_all(dir).with_context(|| format!("Failed to create {dir}"));
+    }
+    run_elevated(config, &["mkdir", "-p", dir])?;
+    run_elevated(config, &["chown", &config.runner_user, dir])
+        .with_context(|| format!("Failed to set ownership of {dir}"))
+}
+
+/// Unpack a runner package into the template directory as the runner user: `tar` for
+/// the Linux and macOS packages, `Expand-Archive` for the Windows `.zip`
+fn extract_package(config: &Config, archive: &Path) -> Result<()> {
+    let template_dir = config.template_dir();
+    println!("Extracting to {}...", template_dir.display());
+    if config.runner_os == "windows" {
+        return winsvc::expand_archive(archive, &template_dir);
+    }
+    run_elevated(
+        config,
+        &[
+            "-u",
+            &config.runner_user,
+            "tar",
+            "xzf",
+            &archive.to_string_lossy(),
+            "-C",
+            &template_dir.to_string_lossy(),
+        ],
+    )
+    .context("Extraction failed")
+}
+
+/// Replace the runner template with the package at `archive`
+pub fn replace_template(config: &Config, archive: &Path) -> Result<()> {
+    let template_dir = config.template_dir();
+    if template_dir.exists() {
+        if config.runner_os == "windows" {
+            fs::remove_dir_all(&template_dir).context("Failed to remove old template")?;
+        } else {
+            run_elevated(config, &["rm", "-rf", &template_dir.to_string_lossy()])
+                .context("Failed to remove old template")?;
+        }
+    }
+    create_owned_dir(config, &template_dir.to_string_lossy())
+        .context("Failed to create template directory")?;
+    extract_package(config, archive)
+}
+
+/// Create the instances directory and install the runner template.
+/// Shared by `init` and the dashboard's first-run wizard.
+pub async fn setup_host(config: &Config, client: &GitHubClient) -> Result<()> {
+    create_instances_base(config)?;
+
+    // Download runner binary
+    println!();
+    println!("Downloading latest GitHub Actions runner...");
+
+    let latest_version = client
+        .get_latest_runner_version()
+        .await
+        .context("Failed to fetch latest runner version")?;
+
+    println!("Runner version: {latest_version}");
+    println!(
+        "Package: {}",
+        runner_package(&config.runner_os, &config.runner_arch, &latest_version)
+    );
+
+    create_owned_dir(config, &config.template_dir().to_string_lossy())
+        .context("Failed to create template directory")?;
+    let archive = download_runner_package(config, client, &latest_version).await?;
+    extract_package(config, &archive)?;
+
+    validate_template(config)?;
+
+    Ok(())
+}
+
+/// Create the instances base directory, owned by the runner user, if it doesn't exist
+pub fn create_instances_base(config: &Config) -> Result<()> {
+    let instances_base = &config.instances_base;
+    if !Path::new(instances_base).exists() {
+        println!("Creating runner instances directory: {instances_base}");
+        create_owned_dir(config, instances_base).context("Failed to create instances directory")?;
+    }
+    Ok(())
+}
+
+/// Run the template's `Runner.Listener --version` as the runner user to confirm the
+/// binary executes on this host. Returns the reported version; on failure the error
+/// lists the likely causes with install hints.
+pub fn validate_template(config: &Config) -> Result<String> {
+    let listener = config.template_dir().join("bin").join("Runner.Listener");
+    let listener = listener.to_string_lossy();
+    println!("Checking that the runner binary runs on this host...");
+    let output = config
+        .escalation
+        .command(&["-u", &config.runner_user, &listener, "--version"])
+        .output()
+        .context("Failed to run Runner.Listener")?;
+    if output.status.success() {
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        println!("Runner binary OK (version {version})");
+        return Ok(version);
+    }
+
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut message = format!(
+        "The runner binary failed to start ({}):\n{}",
+        output.status,
+        combined.trim()
+    );
+    for hint in diagnose_listener_failure(&combined, &config.runner_arch) {
+        let _ = write!(message, "\n  - {hint}");
+    }
+    anyhow::bail!(message)
+}
+
+/// Map `Runner.Listener` failure output to likely causes and fixes
+pub fn diagnose_listener_failure(output: &str, runner_arch: &str) -> Vec<String> {
+    let lower = output.to_lowercase();
+    let mut hints = Vec::new();
+    if lower.contains("exec format error") || lower.contains("cannot execute binary file") {
+        hints.push(format!(
+            "Wrong architecture: the template was downloaded for {runner_arch}. Set \
+             runner_arch in config.toml to this host's architecture (x64 or arm64) and run \
+             `runner-mgr update`."
+        ));
+    }
+    if lower.contains("icu") {
+        hints.push(
+            "ICU is missing: run `runner-mgr deps install`, or install libicu (apt: \
+             libicu-dev, dnf: libicu, apk: icu-libs)."
+                .to_string(),
+        );
+    }
+    if lower.contains("libssl") || lower.contains("openssl") {
+        hints.push(
+            "OpenSSL is missing: run `runner-mgr deps install`, or install libssl (apt: \
+             libssl3 or libssl1.1, dnf: openssl-libs)."
+                .to_string(),
+        );
+    }
+    if lower.contains("libkrb5") || lower.contains("gssapi") {
+        hints.push(
+            "Kerberos libraries are missing: run `runner-mgr deps install`, or install \
+             libkrb5 (apt: libkrb5-3, dnf: krb5-libs)."
+                .to_string(),
+        );
+    }
+    if lower.contains("permission denied") {
+        hints.push(
+            "The runner user cannot execute the template: check that it owns the template \
+             directory."
+                .to_string(),
+        );
+    }
+    if hints.is_empty() {
+        hints.push(
+            "Run `runner-mgr deps install` to install the runner's prerequisites.".to_string(),
+        );
+    }
+    hints
+}
+
+/// Merge variables into the contents of a runner `.env` file.
+/// Existing lines for the same keys are replaced; other lines are kept in order.
+pub fn merge_env_file(existing: &str, vars: &std::collections::BTreeMap<String, String>) -> String {
+    let mut lines: Vec<String> = existing
+        .lines()
+        .filter(|line| {
+            let key = line.split('=').next().unwrap_or("").trim();
+            !vars.contains_key(key)
+        })
+        .map(ToString::to_string)
+        .collect();
+    lines.extend(vars.iter().map(|(k, v)| format!("{k}={v}")));
+    let mut merged = lines.join("\n");
+    merged.push('\n');
+    merged
+}
+
+/// Render a systemd drop-in with the profile's resource limits, if it sets any
+pub fn resource_limits_dropin(profile: &RunnerProfile) -> Option<String> {
+    if profile.memory_max.is_none() && profile.cpu_quota.is_none() {
+        return None;
+    }
+    let mut dropin = String::from("# Managed by runner-mgr\n[Service]\n");
+    if let Some(mem) = &profile.memory_max {
+        let _ = writeln!(dropin, "MemoryMax={mem}");
+    }
+    if let Some(cpu) = &profile.cpu_quota {
+        let _ = writeln!(dropin, "CPUQuota={cpu}");
+    }
+    Some(dropin)
+}
+
+/// Drop-in holding an instance's pre-start hooks
+const PRE_START_DROPIN: &str = "runner-mgr-prestart.conf";
+
+/// `command` as a `/bin/sh -c` command line for systemd, which unquotes C-style escapes
+/// and expands `%` specifiers and `$` variables
+pub fn systemd_shell_command(command: &str) -> String {
+    let mut quoted = String::new();
+    for c in command.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\n' => quoted.push_str("\\n"),
+            '%' => quoted.push_str("%%"),
+            '$' => quoted.push_str("$$"),
+            c => quoted.push(c),
+        }
+    }
+    format!("/bin/sh -c '{quoted}'")
+}
+
+/// Render a systemd drop-in running the pre-start `hooks` (`ExecStartPre=`), if there
+/// are any. `+` runs a command as root even though the unit runs as the runner user.
+pub fn pre_start_dropin(hooks: &PreStartConfig) -> Option<String> {
+    if hooks.commands.is_empty() {
+        return None;
+    }
+    let prefix = if hooks.as_root { "+" } else { "" };
+    let mut dropin = String::from("# Managed by runner-mgr\n[Service]\n");
+    for command in &hooks.commands {
+        let _ = writeln!(
+            dropin,
+            "ExecStartPre={prefix}{}",
+            systemd_shell_command(command)
+        );
+    }
+    Some(dropin)
+}
+
+/// Install, update or remove the pre-start drop-in of the instance in `dir` to match
+/// the config (Linux only)
+fn sync_pre_start(config: &Config, id: &InstanceId, dir: &Path) -> Result<()> {
+    if config.runner_os != "linux" {
+        return Ok(());
+    }
+    let Some(service) = read_service_name(dir) else {
+        return Ok(());
+    };
+    let path = systemd_unit_dir(config)
+        .join(format!("{service}.service.d"))
+        .join(PRE_START_DROPIN);
+    let wanted = config.pre_start_for(id).and_then(pre_start_dropin);
+    if wanted == fs::read_to_string(&path).ok() {
+        return Ok(());
+    }
+    if let Some(dropin) = wanted {
+        return install_dropin(config, &service, PRE_START_DROPIN, &dropin);
+    }
+    if config.rootless() {
+        fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    } else {
+        run_elevated(config, &["rm", "-f", &path.to_string_lossy()])?;
+    }
+    systemctl(config, &["daemon-reload"])
+}
+
+/// Run the instance's pre-start hooks ourselves (macOS: launchd has no
+/// `ExecStartPre`, so they only run when runner-mgr starts the runner)
+fn run_pre_start_hooks(config: &Config, id: &InstanceId, dir: &Path) -> Result<()> {
+    let Some(hooks) = config.pre_start_for(id) else {
+        return Ok(());
+    };
+    for command in &hooks.commands {
+        let mut args = Vec::new();
+        if !hooks.as_root {
+            args.extend(["-H", "-u", config.runner_user.as_str()]);
+        }
+        args.extend(["/bin/sh", "-c", command.as_str()]);
+        run_elevated_in_dir(config, dir, &args)
+            .with_context(|| format!("Pre-start hook failed: {command}"))?;
+    }
+    Ok(())
+}
+
+/// The first failed command in `systemctl show -p ExecStartPre --value` output, with
+/// how it failed (e.g. "exit status 1")
+pub fn parse_pre_start_failure(output: &str) -> Option<(String, String)> {
+    output.split('{').skip(1).find_map(|entry| {
+        let field = |name: &str| {
+            entry
+                .split(" ; ")
+                .find_map(|part| part.trim().strip_prefix(name))
+                .map(|value| value.trim_end_matches('}').trim().to_string())
+        };
+        let status = field("status=")?;
+        // `status=1/FAILURE`: keep the number
+        let status = status.split('/').next().unwrap_or_default().to_string();
+        let failure = match field("code=")?.as_str() {
+            "exited" if status != "0" => format!("exit status {status}"),
+            "killed" | "dumped" => format!("killed by signal {status}"),
+            _ => return None,
+        };
+        let argv = field("argv[]=").unwrap_or_default();
+        let command = argv
+            .strip_prefix("/bin/sh -c ")
+            .unwrap_or(&argv)
+            .to_string();
+        Some((command, failure))
+    })
+}
+
+/// Why the instance's pre-start hooks kept its runner from starting the last time, if
+/// they did (Linux)
+pub fn pre_start_failure(config: &Config, instance: &RunnerInstance) -> Option<String> {
+    if config.runner_os != "linux" || config.pre_start_for(&instance.id()).is_none() {
+        return None;
+    }
+    let service = instance.service_name.as_ref()?;
+    let mut command = Command::new("systemctl");
+    if config.rootless() {
+        command.arg("--user");
+    }
+    let output = command
+        .args([
+            "show",
+            &format!("{service}.service"),
+            "-p",
+            "ExecStartPre",
+            "--value",
+        ])
+        .output()
+        .ok()?;
+    let (hook, failure) = parse_pre_start_failure(&String::from_utf8_lossy(&output.stdout))?;
+    Some(format!("pre-start hook `{hook}` failed ({failure})"))
+}
+
+/// Variables for the `.env` of the instance in `dir`: the `[runner_env]` settings (with
+/// `proxy_url` as the default proxy), then the profile's environment and hook settings
+/// (which win on conflicts)
+pub fn instance_env_vars(
+    config: &Config,
+    dir: &Path,
+    profile: Option<&RunnerProfile>,
+) -> std::collections::BTreeMap<String, String> {
+    let mut vars = config.runner_proxy_vars();
+    if let Some(env) = &config.runner_env {
+        vars.extend(env.env_vars(dir));
+    }
+    if let Some(profile) = profile {
+        vars.extend(profile.env_vars());
+    }
+    vars
+}
+
+/// `env NAME=value ...` setting the proxy variables for `config.sh` and `run.sh`, which
+/// sudo and doas don't pass on: the runners' proxy settings, else the ones runner-mgr was
+/// started with. Empty without a proxy.
+pub fn proxy_env_args(config: &Config) -> Vec<String> {
+    let mut vars = config.runner_proxy_vars();
+    for key in ["http_proxy", "https_proxy", "no_proxy"] {
+        if vars.contains_key(key) {
+            continue;
+        }
+        let value = std::env::var(key.to_uppercase())
+            .or_else(|_| std::env::var(key))
+            .ok()
+            .filter(|v| !v.is_empty());
+        if let Some(value) = value {
+            vars.insert(key.to_string(), value);
+        }
+    }
+    if vars.is_empty() {
+        return Vec::new();
+    }
+    std::iter::once("env".to_string())
+        .chain(vars.iter().map(|(key, value)| format!("{key}={value}")))
+        .collect()
+}
+
+/// Write the instance's environment (see `instance_env_vars`) into its `.env`
+fn apply_instance_env(config: &Config, dir: &Path, profile: Option<&RunnerProfile>) -> Result<()> {
+    let vars = instance_env_vars(config, dir, profile);
+    if vars.is_empty() {
+        return Ok(());
+    }
+    let existing = fs::read_to_string(dir.join(".env")).unwrap_or_default();
+    write_instance_file(config, dir, ".env", &merge_env_file(&existing, &vars))
+}
+
+/// Install a systemd drop-in with the profile's resource limits (Linux only)
+fn apply_profile_limits(config: &Config, dir: &Path, profile: &RunnerProfile) -> Result<()> {
+    let Some(dropin) = resource_limits_dropin(profile) else {
+        return Ok(());
+    };
+    if config.runner_os == "darwin" {
+        println!("  Note: resource limits are only supported with systemd; skipping");
+        return Ok(());
+    }
+    let Some(service) = read_service_name(dir) else {
+        anyhow::bail!("Cannot apply resource limits: no service installed");
+    };
+
+    install_dropin(config, &service, "runner-mgr-limits.conf", &dropin)
+}
+
+/// Tag the instance's journal lines with its own syslog identifier (Linux only)
+fn apply_journal_identifier(config: &Config, dir: &Path, scope: &RunnerScope) -> Result<()> {
+    if config.runner_os == "darwin" {
+        return Ok(());
+    }
+    let Some(service) = read_service_name(dir) else {
+        return Ok(());
+    };
+    install_dropin(
+        config,
+        &service,
+        "runner-mgr-journal.conf",
+        &journal::identifier_dropin(scope),
+    )
+}
+
+/// Install `content` as the drop-in `name` of `service` and reload systemd
+fn install_dropin(config: &Config, service: &str, name: &str, content: &str) -> Result<()> {
+    let dropin_dir = systemd_unit_dir(config).join(format!("{service}.service.d"));
+    let dropin_dir = dropin_dir.to_string_lossy();
+    run_elevated(config, &["mkdir", "-p", &dropin_dir])?;
+    let tmp = std::env::temp_dir().join(format!("runner-mgr-{service}-{name}"));
+    fs::write(&tmp, content).with_context(|| format!("Failed to write drop-in {name}"))?;
+    let result = run_elevated(
+        config,
+        &[
+            "cp",
+            &tmp.to_string_lossy(),
+            &format!("{dropin_dir}/{name}"),
+        ],
+    );
+    let _ = fs::remove_file(&tmp);
+    result?;
+    systemctl(config, &["daemon-reload"])
+}
+
+/// Where systemd units are installed: the system directory, or the user's own in
+/// rootless mode
+pub(crate) fn systemd_unit_dir(config: &Config) -> PathBuf {
+    if config.rootless() {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from(".config"))
+            .join("systemd")
+            .join("user")
+    } else {
+        PathBuf::from("/etc/systemd/system")
+    }
+}
+
+/// Run `systemctl`: elevated against the system manager, or as the current user
+/// against the user manager (`--user`) in rootless mode
+pub(crate) fn systemctl(config: &Config, args: &[&str]) -> Result<()> {
+    if config.rootless() {
+        let mut user_args = vec!["--user"];
+        user_args.extend_from_slice(args);
+        run_cmd("systemctl", &user_args)
+    } else {
+        let mut system_args = vec!["systemctl"];
+        system_args.extend_from_slice(args);
+        run_elevated(config, &system_args)
+    }
+}
+
+/// Unit name of a rootless runner's systemd user service. `svc.sh` only installs
+/// system units, so runner-mgr names its own: `actions.runner.<instance dir>`, with
+/// the characters systemd doesn't allow (and the template marker `@`) replaced.
+pub fn user_unit_name(id: &InstanceId) -> String {
+    let name: String = id
+        .dir_name()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.') {
+                c
+            } else {
+                '.'
+            }
+        })
+        .collect();
+    format!("actions.runner.{name}")
+}
+
+/// systemd user unit running the runner in `dir` through its `runsvc.sh`, like the
+/// system unit `svc.sh install` writes
+pub fn user_unit(id: &InstanceId, dir: &Path) -> String {
+    let dir = dir.display();
+    format!(
+        "[Unit]\n\
+         Description=GitHub Actions Runner ({id})\n\
+         After=network-online.target\n\
+         \n\
+         [Service]\n\
+         ExecStart={dir}/runsvc.sh\n\
+         WorkingDirectory={dir}\n\
+         KillMode=process\n\
+         KillSignal=SIGTERM\n\
+         TimeoutStopSec=5min\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n"
+    )
+}
+
+/// Install and enable the systemd user unit for a rootless runner (Linux)
+fn install_user_service(config: &Config, id: &InstanceId, dir: &Path) -> Result<()> {
+    fs::copy(dir.join("bin").join("runsvc.sh"), dir.join("runsvc.sh"))
+        .context("Failed to copy runsvc.sh")?;
+    let unit_dir = systemd_unit_dir(config);
+    fs::create_dir_all(&unit_dir)
+        .with_context(|| format!("Failed to create {}", unit_dir.display()))?;
+    let unit = user_unit_name(id);
+    fs::write(unit_dir.join(format!("{unit}.service")), user_unit(id, dir))
+        .with_context(|| format!("Failed to write unit {unit}.service"))?;
+    write_instance_file(config, dir, ".service", &unit)?;
+    systemctl(config, &["daemon-reload"])?;
+    systemctl(config, &["enable", &format!("{unit}.service")])
+}
+
+/// Stop, disable and delete a rootless runner's systemd user unit (Linux)
+fn uninstall_user_service(config: &Config, service: &str) {
+    let unit = format!("{service}.service");
+    let _ = systemctl(config, &["disable", "--now", &unit]);
+    let unit_dir = systemd_unit_dir(config);
+    let _ = fs::remove_file(unit_dir.join(&unit));
+    let _ = fs::remove_dir_all(unit_dir.join(format!("{unit}.d")));
+    let _ = systemctl(config, &["daemon-reload"]);
+}
+
+/// Labels a new runner registers with: self-hosted, then the requested labels, then the
+/// profile's, then any auto labels
+fn registration_labels(
+    config: &Config,
+    requested: &str,
+    profile_labels: &[String],
+    hostname: &str,
+) -> Vec<String> {
+    let requested = crate::labels::parse_labels(requested);
+    let mut labels = crate::labels::merge_labels(&["self-hosted".to_string()], &requested);
+    labels = crate::labels::merge_labels(&labels, profile_labels);
+    if !config.auto_labels.is_empty() {
+        let auto = crate::labels::expand_auto_labels(
+            &config.auto_labels,
+            &Config::detect_os(),
+            &Config::detect_arch(),
+            hostname,
+        );
+        labels = crate::labels::merge_labels(&labels, &auto);
+    }
+    labels
+}
+
+/// Create `dir` owned by the runner user and copy the runner template into it
+fn copy_template(config: &Config, dir: &Path) -> Result<()> {
+    run_elevated(config, &["mkdir", "-p", &dir.to_string_lossy()])?;
+    run_elevated(
+        config,
+        &["chown", &config.runner_user, &dir.to_string_lossy()],
+    )?;
+    run_elevated(
+        config,
+        &[
+            "-u",
+            &config.runner_user,
+            "cp",
+            "-a",
+            &format!("{}/.", &config.template_dir().to_string_lossy()),
+            &format!("{}/", dir.to_string_lossy()),
+        ],
+    )
+}
+
+/// Whether `name` in an instance directory holds runner binaries: `bin` and `externals`,
+/// or the versioned copies (`bin.2.321.0`) a self-updated runner links them to
+pub fn is_package_dir(name: &str) -> bool {
+    ["bin", "externals"].iter().any(|dir| {
+        name == *dir
+            || name
+                .strip_prefix(dir)
+                .and_then(|rest| rest.strip_prefix('.'))
+                .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+    })
+}
+
+/// Replace the runner binaries in `dir` with the template's. Everything the template
+/// doesn't ship (`.runner`, `.credentials`, `.env`, `.service`, `_work`, `_diag`) is
+/// left alone.
+fn replace_binaries(config: &Config, dir: &Path) -> Result<()> {
+    let entries = fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+    for entry in entries.flatten() {
+        if is_package_dir(&entry.file_name().to_string_lossy()) {
+            run_elevated(config, &["rm", "-rf", &entry.path().to_string_lossy()])?;
+        }
+    }
+    run_elevated(
+        config,
+        &[
+            "-u",
+            &config.runner_user,
+            "cp",
+            "-a",
+            &format!("{}/.", &config.template_dir().to_string_lossy()),
+            &format!("{}/", dir.to_string_lossy()),
+        ],
+    )?;
+    // The service runs the copy of runsvc.sh `svc.sh install` made
+    if dir.join("runsvc.sh").exists() {
+        run_elevated(
+            config,
+            &[
+                "-u",
+                &config.runner_user,
+                "cp",
+                &dir.join("bin").join("runsvc.sh").to_string_lossy(),
+                &dir.join("runsvc.sh").to_string_lossy(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Update `instance` in place to the template's runner version: stop its service, copy
+/// the new binaries in, and start it again if it was running. Its registration is kept.
+pub fn update_instance(config: &Config, instance: &RunnerInstance) -> Result<()> {
+    ensure_unix_host(config, "update runners in place")?;
+    let id = instance.id();
+    let running = instance.status == RunnerStatus::Running;
+    if running {
+        stop_runner(config, &id)?;
+    }
+    println!(
+        "Copying the new runner binaries into {}...",
+        instance.dir.display()
+    );
+    let copied =
+        replace_binaries(config, &instance.dir).with_context(|| format!("Failed to update {id}"));
+    let started = if running {
+        start_runner(config, &id)
+    } else {
+        Ok(())
     };
+    copied.and(started)
+}
 
-    Ok((service_label, service_target))
+/// Name of a just-in-time runner started at `timestamp`: `<hostname>-jit-<timestamp>`,
+/// at most 64 characters (the timestamp is kept, so names stay unique)
+pub fn jit_runner_name(hostname: &str, timestamp: i64) -> String {
+    let suffix = format!("-jit-{timestamp}");
+    let host: String = hostname
+        .chars()
+        .take(64usize.saturating_sub(suffix.len()))
+        .collect();
+    format!("{host}{suffix}")
 }
 
-/// Get the UID for a given username
-fn get_user_uid(username: &str) -> Result<u32> {
-    let output = Command::new("id")
-        .args(["-u", username])
-        .output()
-        .context("Failed to get user UID")?;
+/// Register a single-use runner with a just-in-time config and run it in the
+/// foreground until it has taken one job. The JIT config is only passed to `run.sh`
+/// on its command line, so no registration credential is written to disk; the
+/// runner's directory is removed when it exits.
+pub async fn run_jit_runner(config: &Config, scope: &RunnerScope, labels: &str) -> Result<()> {
+    ensure_unix_host(config, "run just-in-time runners")?;
+    let hostname = hostname::get().map_or_else(
+        |_| "runner".to_string(),
+        |h| h.to_string_lossy().to_string(),
+    );
+    let name = jit_runner_name(&hostname, chrono::Utc::now().timestamp_millis());
+    let labels = registration_labels(config, labels, &[], &hostname);
+
+    let client = GitHubClient::for_config(config);
+    println!("Requesting just-in-time config for {scope}...");
+    let jit = client
+        .generate_jitconfig(scope, &name, &labels, DEFAULT_RUNNER_GROUP_ID)
+        .await?;
+
+    let dir = config.jit_dir().join(&name);
+    println!("Creating single-use runner at {}...", dir.display());
+    let cleanup = {
+        let escalation = config.escalation;
+        let dir = dir.to_string_lossy().to_string();
+        interrupt::on_interrupt(move || {
+            let _ = escalation.command(&["rm", "-rf", &dir]).status();
+        })
+    };
+    copy_template(config, &dir)?;
+    apply_instance_env(config, &dir, None)?;
 
-    if !output.status.success() {
-        anyhow::bail!("Failed to get UID for user {username}");
+    println!(
+        "Runner {name} is waiting for a job (labels: {}). Press Ctrl-C to give up.",
+        labels.join(",")
+    );
+    // Not run through run_cmd: verbose mode would log the JIT config
+    let run_sh = dir.join("run.sh");
+    let run_sh = run_sh.to_string_lossy();
+    let proxy_env = proxy_env_args(config);
+    let mut args = vec!["-u", config.runner_user.as_str()];
+    args.extend(proxy_env.iter().map(String::as_str));
+    args.extend([&*run_sh, "--jitconfig", &jit.encoded_jit_config]);
+    let (program, args) = config.escalation.wrap(&args);
+    let status = Command::new(program)
+        .args(&args)
+        .current_dir(&dir)
+        .status()
+        .with_context(|| format!("Failed to run {run_sh}"));
+
+    let removed = run_elevated(config, &["rm", "-rf", &dir.to_string_lossy()]);
+    drop(cleanup);
+    let status = status?;
+    removed.context("Failed to remove the runner directory")?;
+    if !status.success() {
+        anyhow::bail!("Runner exited with {status}");
     }
+    println!("Runner {name} finished its job and was removed from GitHub.");
+    Ok(())
+}
 
-    let uid_str = String::from_utf8_lossy(&output.stdout);
-    uid_str.trim().parse::<u32>().context("Failed to parse UID")
+/// `add`, `remove` and `jit` drive `config.sh` and `svc.sh`, which the Windows package
+/// doesn't have: there runners are configured with `config.cmd` and then imported
+fn ensure_unix_host(config: &Config, action: &str) -> Result<()> {
+    if config.runner_os == "windows" {
+        anyhow::bail!(
+            "Cannot {action} on Windows. Configure the runner with `config.cmd --runasservice` \
+             (or remove it with `config.cmd remove`), then use `runner-mgr import <dir>`"
+        );
+    }
+    Ok(())
+}
+
+/// Register the runner in `dir` with `config.sh`, replacing a runner of the same name
+fn configure_runner(
+    config: &Config,
+    dir: &Path,
+    scope: &RunnerScope,
+    token: &str,
+    runner_name: &str,
+    labels: &str,
+) -> Result<()> {
+    let config_sh = dir.join("config.sh");
+    let config_sh = config_sh.to_string_lossy();
+    let url = scope.web_url(&config.web_url());
+    let proxy_env = proxy_env_args(config);
+    let mut args = vec!["-u", config.runner_user.as_str()];
+    args.extend(proxy_env.iter().map(String::as_str));
+    args.extend([
+        &*config_sh,
+        "--url",
+        &url,
+        "--token",
+        token,
+        "--name",
+        runner_name,
+        "--labels",
+        labels,
+        "--unattended",
+        "--replace",
+    ]);
+    let work_folder = config
+        .runner_env
+        .as_ref()
+        .and_then(|env| env.work_folder(dir));
+    if let Some(folder) = &work_folder {
+        args.extend(["--work", folder]);
+    }
+    run_elevated(config, &args)
 }
 
-pub async fn add_runner(config: &Config, scope: &RunnerScope, labels: &str) -> Result<()> {
-    let dir = config.instance_dir(scope);
+#[allow(clippy::too_many_lines)]
+pub async fn add_runner(
+    config: &Config,
+    id: &InstanceId,
+    labels: &str,
+    profile: Option<(&str, &RunnerProfile)>,
+) -> Result<()> {
+    ensure_unix_host(config, "register runners")?;
+    let scope = &id.scope;
+    let dir = id.dir(config);
 
     if dir.exists() {
-        anyhow::bail!("Runner already configured for {scope}. Use 'remove' first.");
+        anyhow::bail!("Runner already configured for {id}. Use 'remove' first.");
     }
 
-    println!("Adding runner for {scope}...");
+    println!("Adding runner for {id}...");
+
+    let hostname = hostname::get().map_or_else(
+        |_| "runner".to_string(),
+        |h| h.to_string_lossy().to_string(),
+    );
 
-    let mut labels = labels.to_string();
-    if !labels.contains("self-hosted") {
-        labels = format!("self-hosted,{labels}");
+    let profile_labels = profile
+        .map(|(_, p)| p.labels.as_slice())
+        .unwrap_or_default();
+    let labels = registration_labels(config, labels, profile_labels, &hostname).join(",");
+
+    let client = GitHubClient::for_config(config);
+
+    // Collaborator and organization repos need admin rights. If the lookup itself
+    // fails, the registration token request below reports the problem.
+    if let RunnerScope::Repository { owner, repo } = scope {
+        if let Ok(r) = client.get_repo(owner, repo).await {
+            if !r.is_admin() {
+                anyhow::bail!(
+                    "You don't have admin rights on {scope}. Ask an owner to grant admin \
+                     access (required to register runners)."
+                );
+            }
+        }
     }
 
     // Get registration token
     println!("Requesting registration token...");
-    let client = GitHubClient::new(&config.github_pat);
     let reg = client.get_registration_token(scope).await?;
 
-    // Create instance directory from template
+    // Create instance directory from template. If interrupted before the runner
+    // is configured, remove the half-created directory so `add` can be retried.
     println!("Creating runner instance at {}...", dir.display());
-    run_cmd("sudo", &["mkdir", "-p", &dir.to_string_lossy()])?;
-    run_cmd(
-        "sudo",
-        &["chown", &config.runner_user, &dir.to_string_lossy()],
-    )?;
-    run_cmd(
-        "sudo",
-        &[
-            "-u",
-            &config.runner_user,
-            "cp",
-            "-a",
-            &format!("{}/.", &config.template_dir().to_string_lossy()),
-            &format!("{}/", dir.to_string_lossy()),
-        ],
-    )?;
+    let partial_instance = {
+        let escalation = config.escalation;
+        let dir = dir.to_string_lossy().to_string();
+        interrupt::on_interrupt(move || {
+            let _ = escalation.command(&["rm", "-rf", &dir]).status();
+        })
+    };
+    copy_template(config, &dir)?;
 
     // Configure the runner
-    let hostname = hostname::get().map_or_else(
-        |_| "runner".to_string(),
-        |h| h.to_string_lossy().to_string(),
-    );
-    let safe_name = scope.to_dir_name();
-    let runner_name = format!("{hostname}-{safe_name}");
-    let runner_name = &runner_name[..runner_name.len().min(64)];
+    let runner_name = id.runner_name(&hostname);
+    let runner_name = runner_name.as_str();
 
     println!("Configuring runner (name: {runner_name})...");
-    let config_sh = dir.join("config.sh");
-    run_cmd(
-        "sudo",
-        &[
-            "-u",
-            &config.runner_user,
-            &config_sh.to_string_lossy(),
-            "--url",
-            &scope.github_url(),
-            "--token",
-            &reg.token,
-            "--name",
-            runner_name,
-            "--labels",
-            &labels,
-            "--unattended",
-            "--replace",
-        ],
-    )?;
+    configure_runner(config, &dir, scope, &reg.token, runner_name, &labels)?;
+    drop(partial_instance);
+
+    // Remember the registered labels so status can check them against the host
+    write_instance_file(config, &dir, ".labels", &labels)?;
+
+    if let Some((name, _)) = profile {
+        println!("Applying profile '{name}'...");
+        write_instance_file(config, &dir, ".profile", name)?;
+    }
+    apply_instance_env(config, &dir, profile.map(|(_, p)| p))?;
 
     // Install service
     // On macOS, run svc.sh AS the runner user to access ~/Library/LaunchAgents/
     // Use -H to set HOME to the runner user's home directory
-    // On Linux, run as root to access /etc/systemd/system/
+    // On Linux, run as root to access /etc/systemd/system/, or install a user unit
+    // in rootless mode
     println!("Installing service (user: {})...", config.runner_user);
     let svc_sh = dir.join("svc.sh");
     let svc_sh_path = svc_sh.to_string_lossy();
-    if config.runner_os == "darwin" {
-        run_cmd_in_dir(
+    let user_service = config.rootless() && config.runner_os != "darwin";
+    if user_service {
+        install_user_service(config, id, &dir)?;
+    } else if config.runner_os == "darwin" {
+        run_elevated_in_dir(
+            config,
             &dir,
-            "sudo",
             &["-H", "-u", &config.runner_user, &svc_sh_path, "install"],
         )?;
     } else {
-        run_cmd_in_dir(
+        run_elevated_in_dir(
+            config,
             &dir,
-            "sudo",
             &[&svc_sh_path, "install", &config.runner_user],
         )?;
     }
 
+    apply_journal_identifier(config, &dir, scope)?;
+    if let Some((_, p)) = profile {
+        apply_profile_limits(config, &dir, p)?;
+    }
+    sync_pre_start(config, id, &dir)?;
+
     // Start service
     // On macOS, run as runner user for LaunchAgent (with -H for correct HOME)
     // On Linux, run as root for systemd
     println!("Starting service...");
-    if config.runner_os == "darwin" {
-        run_cmd_in_dir(
+    if user_service {
+        let unit = format!("{}.service", user_unit_name(id));
+        systemctl(config, &["start", &unit])?;
+    } else if config.runner_os == "darwin" {
+        run_pre_start_hooks(config, id, &dir)?;
+        run_elevated_in_dir(
+            config,
             &dir,
-            "sudo",
             &["-H", "-u", &config.runner_user, &svc_sh_path, "start"],
         )?;
     } else {
-        run_cmd_in_dir(&dir, "sudo", &[&svc_sh_path, "start"])?;
+        run_elevated_in_dir(config, &dir, &[&svc_sh_path, "start"])?;
     }
 
     println!();
-    println!("Runner registered and running for {scope}");
+    println!("Runner registered and running for {id}");
     println!("  Instance: {}", dir.display());
     println!("  Labels:   {labels}");
     println!("  Name:     {runner_name}");
+    if let Some((name, _)) = profile {
+        println!("  Profile:  {name}");
+    }
+
+    Ok(())
+}
+
+/// Register an instance again after its registration was deleted on GitHub or its
+/// credentials were revoked: a fresh token, the same runner name and labels, and the
+/// instance directory (service, `.env`, work folder) kept as it is
+pub async fn reregister_runner(config: &Config, id: &InstanceId) -> Result<()> {
+    ensure_unix_host(config, "re-register runners")?;
+    let dir = id.dir(config);
+    if !dir.exists() {
+        anyhow::bail!(Failure::RunnerNotFound(format!(
+            "No runner configured for {id}"
+        )));
+    }
+    let hostname = hostname::get().map_or_else(
+        |_| "runner".to_string(),
+        |h| h.to_string_lossy().to_string(),
+    );
+    let runner_name = read_agent_name(&dir).unwrap_or_else(|| id.runner_name(&hostname));
+    let labels = read_labels(&dir);
+    let labels = if labels.is_empty() {
+        "self-hosted".to_string()
+    } else {
+        labels.join(",")
+    };
 
+    let client = GitHubClient::for_config(config);
+    println!("Requesting registration token...");
+    let reg = client.get_registration_token(&id.scope).await?;
+
+    let _ = stop_runner(config, id);
+    // config.sh refuses to configure a runner that is still configured locally
+    println!("Removing the old registration of {id}...");
+    let files: Vec<String> = std::iter::once(".runner")
+        .chain(CREDENTIAL_FILES)
+        .map(|name| dir.join(name).to_string_lossy().into_owned())
+        .collect();
+    let mut args = vec!["rm", "-f"];
+    args.extend(files.iter().map(String::as_str));
+    run_elevated(config, &args)?;
+
+    println!("Registering {id} again (name: {runner_name})...");
+    configure_runner(config, &dir, &id.scope, &reg.token, &runner_name, &labels)?;
+    start_runner(config, id)?;
+    println!("Runner re-registered and running for {id}");
     Ok(())
 }
 
-pub async fn remove_runner(config: &Config, scope: &RunnerScope) -> Result<()> {
-    let dir = config.instance_dir(scope);
+pub async fn remove_runner(config: &Config, id: &InstanceId) -> Result<()> {
+    let scope = &id.scope;
+    let dir = id.dir(config);
 
     if !dir.exists() {
-        anyhow::bail!("No runner configured for {scope}");
+        anyhow::bail!(Failure::RunnerNotFound(format!(
+            "No runner configured for {id}"
+        )));
     }
+    ensure_unix_host(config, "remove runners")?;
 
-    println!("Removing runner for {scope}...");
+    println!("Removing runner for {id}...");
 
     let svc_sh = dir.join("svc.sh");
     let svc_sh_path = svc_sh.to_string_lossy();
@@ -345,77 +1674,102 @@ pub async fn remove_runner(config: &Config, scope: &RunnerScope) -> Result<()> {
     // Stop service
     // On macOS, run as runner user for LaunchAgent (with -H for correct HOME)
     // On Linux, run as root for systemd
-    if dir.join(".service").exists() {
+    if let Some(service) = read_service_name(&dir) {
+        if config.runner_os != "darwin" && crate::ondemand::is_enabled(&service) {
+            println!("Removing on-demand units...");
+            let _ = crate::ondemand::remove_units(config, &service);
+        }
+    }
+
+    let user_service = config.rootless() && config.runner_os != "darwin";
+    if let Some(service) = read_service_name(&dir).filter(|_| user_service) {
+        println!("Removing user service...");
+        uninstall_user_service(config, &service);
+    } else if dir.join(".service").exists() {
         println!("Stopping service...");
         if config.runner_os == "darwin" {
-            let _ = run_cmd_in_dir(
+            let _ = run_elevated_in_dir(
+                config,
                 &dir,
-                "sudo",
                 &["-H", "-u", &config.runner_user, &svc_sh_path, "stop"],
             );
         } else {
-            let _ = run_cmd_in_dir(&dir, "sudo", &[&svc_sh_path, "stop"]);
+            let _ = run_elevated_in_dir(config, &dir, &[&svc_sh_path, "stop"]);
         }
 
         println!("Uninstalling service...");
         if config.runner_os == "darwin" {
-            let _ = run_cmd_in_dir(
+            let _ = run_elevated_in_dir(
+                config,
                 &dir,
-                "sudo",
                 &["-H", "-u", &config.runner_user, &svc_sh_path, "uninstall"],
             );
         } else {
-            let _ = run_cmd_in_dir(&dir, "sudo", &[&svc_sh_path, "uninstall"]);
+            let _ = run_elevated_in_dir(config, &dir, &[&svc_sh_path, "uninstall"]);
         }
     }
 
     // Deregister from GitHub
     println!("Deregistering runner from GitHub...");
-    let client = GitHubClient::new(&config.github_pat);
+    let client = GitHubClient::for_config(config);
     if let Ok(token) = client.get_remove_token(scope).await {
         let config_sh = dir.join("config.sh");
-        let _ = run_cmd(
-            "sudo",
-            &[
-                "-u",
-                &config.runner_user,
-                &config_sh.to_string_lossy(),
-                "remove",
-                "--token",
-                &token.token,
-            ],
-        );
+        let config_sh = config_sh.to_string_lossy();
+        let proxy_env = proxy_env_args(config);
+        let mut args = vec!["-u", config.runner_user.as_str()];
+        args.extend(proxy_env.iter().map(String::as_str));
+        args.extend([&*config_sh, "remove", "--token", &token.token]);
+        let _ = run_elevated(config, &args);
     }
 
     // Clean up
     println!("Removing instance directory...");
-    run_cmd("sudo", &["rm", "-rf", &dir.to_string_lossy()])?;
+    run_elevated(config, &["rm", "-rf", &dir.to_string_lossy()])?;
+    // Notes and tags belong to the scope: keep them while it has other instances
+    if !list_instances(config).iter().any(|i| &i.scope == scope) {
+        if let Ok(db) = crate::state::StateDb::open() {
+            let _ = db.forget(scope);
+        }
+    }
 
-    println!("Runner removed for {scope}");
+    println!("Runner removed for {id}");
     Ok(())
 }
 
-pub fn start_runner(config: &Config, scope: &RunnerScope) -> Result<()> {
-    let dir = config.instance_dir(scope);
+pub fn start_runner(config: &Config, id: &InstanceId) -> Result<()> {
+    let dir = id.dir(config);
     if !dir.exists() {
-        anyhow::bail!("No runner configured for {scope}");
+        anyhow::bail!(Failure::RunnerNotFound(format!(
+            "No runner configured for {id}"
+        )));
+    }
+    if let Some(problem) = credential_problem(&dir) {
+        anyhow::bail!(
+            "{id} can't start: {problem}, so its registration is lost; {}",
+            reregister_hint(id)
+        );
     }
 
     // Get service name
     let instances = list_instances(config);
     let instance = instances
         .iter()
-        .find(|i| &i.scope == scope)
-        .ok_or_else(|| anyhow::anyhow!("Runner not found for {scope}"))?;
+        .find(|i| i.id() == *id)
+        .ok_or_else(|| Failure::RunnerNotFound(format!("Runner not found for {id}")))?;
 
     let service_name = instance
         .service_name
         .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("No service configured for {scope}"))?;
+        .ok_or_else(|| anyhow::anyhow!("No service configured for {id}"))?;
 
-    println!("Starting {scope}...");
+    println!("Starting {id}...");
 
-    if config.runner_os == "darwin" {
+    if config.runner_os == "windows" {
+        winsvc::start_service(service_name).with_context(|| {
+            Failure::ServiceControl("Failed to start runner service".to_string())
+        })?;
+    } else if config.runner_os == "darwin" {
+        run_pre_start_hooks(config, id, &dir)?;
         // macOS: use launchctl to start the service
         // The service could be a LaunchAgent (user) or LaunchDaemon (system)
         let (service_label, service_target) =
@@ -438,177 +1792,242 @@ pub fn start_runner(config: &Config, scope: &RunnerScope) -> Result<()> {
             "system".to_string()
         };
 
-        run_cmd("sudo", &["launchctl", "kickstart", "-k", &service_target])
+        run_elevated(config, &["launchctl", "kickstart", "-k", &service_target])
             .or_else(|_| {
                 // Fallback: try bootstrapping the plist if kickstart fails
                 // This loads and starts the service in the correct domain
                 if is_plist && plist_path.exists() {
-                    run_cmd("sudo", &["launchctl", "bootstrap", &domain, service_name])
+                    run_elevated(config, &["launchctl", "bootstrap", &domain, service_name])
                 } else {
                     Err(anyhow::anyhow!("Failed to start service {service_label}"))
                 }
             })
-            .context("Failed to start runner service")?;
+            .with_context(|| {
+                Failure::ServiceControl("Failed to start runner service".to_string())
+            })?;
     } else {
-        // Linux: use systemctl for system service
-        // The service runs as the user specified in the unit file's User= directive
-        run_cmd(
-            "sudo",
-            &["systemctl", "start", &format!("{service_name}.service")],
-        )
-        .context("Failed to start runner service")?;
+        // Linux: use systemctl for system service (user service in rootless mode)
+        // The service runs as the user specified in the unit file's User= directive.
+        // Hooks edited since the last start take effect now.
+        sync_pre_start(config, id, &dir)?;
+        systemctl(config, &["start", &format!("{service_name}.service")]).with_context(|| {
+            Failure::ServiceControl("Failed to start runner service".to_string())
+        })?;
     }
     Ok(())
 }
 
-pub fn stop_runner(config: &Config, scope: &RunnerScope) -> Result<()> {
-    let dir = config.instance_dir(scope);
+pub fn stop_runner(config: &Config, id: &InstanceId) -> Result<()> {
+    let dir = id.dir(config);
     if !dir.exists() {
-        anyhow::bail!("No runner configured for {scope}");
+        anyhow::bail!(Failure::RunnerNotFound(format!(
+            "No runner configured for {id}"
+        )));
     }
 
     // Get service name
     let instances = list_instances(config);
     let instance = instances
         .iter()
-        .find(|i| &i.scope == scope)
-        .ok_or_else(|| anyhow::anyhow!("Runner not found for {scope}"))?;
+        .find(|i| i.id() == *id)
+        .ok_or_else(|| Failure::RunnerNotFound(format!("Runner not found for {id}")))?;
 
     let service_name = instance
         .service_name
         .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("No service configured for {scope}"))?;
+        .ok_or_else(|| anyhow::anyhow!("No service configured for {id}"))?;
 
-    println!("Stopping {scope}...");
+    println!("Stopping {id}...");
 
-    if config.runner_os == "darwin" {
+    if config.runner_os == "windows" {
+        winsvc::stop_service(service_name).with_context(|| {
+            Failure::ServiceControl("Failed to stop runner service".to_string())
+        })?;
+    } else if config.runner_os == "darwin" {
         // macOS: use launchctl to stop the service
         let (service_label, service_target) =
             parse_macos_service(service_name, &config.runner_user)?;
 
         // Use launchctl kill to stop the service (keeps it loaded)
         // or bootout to stop and unload (for full stop)
-        run_cmd("sudo", &["launchctl", "kill", "SIGTERM", &service_target])
+        run_elevated(config, &["launchctl", "kill", "SIGTERM", &service_target])
             .or_else(|_| {
                 // Fallback: try bootout to stop and unload
-                run_cmd("sudo", &["launchctl", "bootout", &service_target])
+                run_elevated(config, &["launchctl", "bootout", &service_target])
             })
             .map_err(|_| anyhow::anyhow!("Failed to stop service {service_label}"))
-            .context("Failed to stop runner service")?;
+            .with_context(|| {
+                Failure::ServiceControl("Failed to stop runner service".to_string())
+            })?;
     } else {
-        // Linux: use systemctl for system service
-        run_cmd(
-            "sudo",
-            &["systemctl", "stop", &format!("{service_name}.service")],
-        )
-        .context("Failed to stop runner service")?;
+        // Linux: use systemctl for system service (user service in rootless mode)
+        systemctl(config, &["stop", &format!("{service_name}.service")]).with_context(|| {
+            Failure::ServiceControl("Failed to stop runner service".to_string())
+        })?;
     }
     Ok(())
 }
 
-pub fn restart_runner(config: &Config, scope: &RunnerScope) -> Result<()> {
-    stop_runner(config, scope)?;
-    start_runner(config, scope)?;
+pub fn restart_runner(config: &Config, id: &InstanceId) -> Result<()> {
+    stop_runner(config, id)?;
+    start_runner(config, id)?;
     Ok(())
 }
 
 pub fn start_all(config: &Config) {
     for instance in list_instances(config) {
-        if let Err(e) = start_runner(config, &instance.scope) {
-            eprintln!("Failed to start {}: {e}", instance.scope);
+        if let Err(e) = start_runner(config, &instance.id()) {
+            eprintln!("Failed to start {}: {e}", instance.id());
         }
     }
 }
 
 pub fn stop_all(config: &Config) {
     for instance in list_instances(config) {
-        if let Err(e) = stop_runner(config, &instance.scope) {
-            eprintln!("Failed to stop {}: {e}", instance.scope);
+        if let Err(e) = stop_runner(config, &instance.id()) {
+            eprintln!("Failed to stop {}: {e}", instance.id());
         }
     }
 }
 
 pub fn restart_all(config: &Config) {
     for instance in list_instances(config) {
-        if let Err(e) = restart_runner(config, &instance.scope) {
-            eprintln!("Failed to restart {}: {e}", instance.scope);
+        if let Err(e) = restart_runner(config, &instance.id()) {
+            eprintln!("Failed to restart {}: {e}", instance.id());
         }
     }
 }
 
-pub fn get_runner_logs(config: &Config, scope: &RunnerScope, lines: u32) -> Result<String> {
-    let dir = config.instance_dir(scope);
+/// The last `lines` journal entries of the instance's systemd service, or None if the
+/// instance has no systemd service (macOS, Windows, or a runner without `svc.sh install`)
+pub fn get_runner_journal(
+    config: &Config,
+    id: &InstanceId,
+    lines: u32,
+) -> Result<Option<Vec<journal::JournalEntry>>> {
+    let dir = id.dir(config);
     if !dir.exists() {
-        anyhow::bail!("No runner configured for {scope}");
+        anyhow::bail!(Failure::RunnerNotFound(format!(
+            "No runner configured for {id}"
+        )));
+    }
+    if config.runner_os == "darwin" || config.runner_os == "windows" {
+        return Ok(None);
+    }
+    let Some(service) = read_service_name(&dir) else {
+        return Ok(None);
+    };
+    let lines = lines.to_string();
+    let output = config
+        .escalation
+        .command(&journalctl_args(
+            config,
+            &["-u", &service, "-n", &lines, "-o", "json", "--no-pager"],
+        ))
+        .output()
+        .context("Failed to run journalctl")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "journalctl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
     }
+    Ok(Some(journal::parse_entries(&String::from_utf8_lossy(
+        &output.stdout,
+    ))))
+}
 
-    if config.runner_os == "darwin" {
-        // macOS: read from _diag directory
-        let diag_dir = dir.join("_diag");
-        if diag_dir.exists() {
-            let mut log_files: Vec<_> = fs::read_dir(&diag_dir)?
-                .flatten()
-                .filter(|e| {
-                    e.file_name().to_string_lossy().starts_with("Runner_")
-                        && e.file_name().to_string_lossy().ends_with(".log")
-                })
-                .collect();
-            log_files.sort_by_key(|e| {
-                std::cmp::Reverse(e.metadata().ok().and_then(|m| m.modified().ok()))
-            });
-
-            if let Some(log_file) = log_files.first() {
-                let content = fs::read_to_string(log_file.path())?;
-                let log_lines: Vec<&str> = content.lines().collect();
-                let start = log_lines.len().saturating_sub(lines as usize);
-                return Ok(log_lines[start..].join("\n"));
-            }
-        }
-        Ok("No runner logs found.".to_string())
+/// `journalctl` followed by `args`, reading the user journal in rootless mode
+fn journalctl_args<'a>(config: &Config, args: &[&'a str]) -> Vec<&'a str> {
+    let mut full = vec!["journalctl"];
+    if config.rootless() {
+        full.push("--user");
+    }
+    full.extend_from_slice(args);
+    full
+}
+
+/// The last `lines` lines of the newest `_diag/Runner_*.log` in `dir`, if there is one
+fn read_diag_log(dir: &Path, lines: u32) -> Result<Option<String>> {
+    let Some(log_file) = follow::newest_diag_log(dir) else {
+        return Ok(None);
+    };
+    let content = fs::read_to_string(log_file)?;
+    let log_lines: Vec<&str> = content.lines().collect();
+    let start = log_lines.len().saturating_sub(lines as usize);
+    Ok(Some(log_lines[start..].join("\n")))
+}
+
+pub fn get_runner_logs(config: &Config, id: &InstanceId, lines: u32) -> Result<String> {
+    let dir = id.dir(config);
+    if !dir.exists() {
+        anyhow::bail!(Failure::RunnerNotFound(format!(
+            "No runner configured for {id}"
+        )));
+    }
+
+    // macOS and Windows services don't log to a journal: read from the _diag directory
+    if config.runner_os == "darwin" || config.runner_os == "windows" {
+        return Ok(
+            read_diag_log(&dir, lines)?.unwrap_or_else(|| "No runner logs found.".to_string())
+        );
+    }
+
+    // Linux: use journalctl
+    if let Some(svc) = read_service_name(&dir) {
+        let lines = lines.to_string();
+        let output = config
+            .escalation
+            .command(&journalctl_args(
+                config,
+                &["-u", &svc, "-n", &lines, "--no-pager"],
+            ))
+            .output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        // Linux: use journalctl
-        let service = read_service_name(&dir);
-        if let Some(svc) = service {
-            let output = Command::new("sudo")
-                .args([
-                    "journalctl",
-                    "-u",
-                    &svc,
-                    "-n",
-                    &lines.to_string(),
-                    "--no-pager",
-                ])
-                .output()?;
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            // Fallback to _diag
-            let diag_dir = dir.join("_diag");
-            if diag_dir.exists() {
-                let mut log_files: Vec<_> = fs::read_dir(&diag_dir)?
-                    .flatten()
-                    .filter(|e| {
-                        e.file_name().to_string_lossy().starts_with("Runner_")
-                            && e.file_name().to_string_lossy().ends_with(".log")
-                    })
-                    .collect();
-                log_files.sort_by_key(|e| {
-                    std::cmp::Reverse(e.metadata().ok().and_then(|m| m.modified().ok()))
-                });
-                if let Some(log_file) = log_files.first() {
-                    let content = fs::read_to_string(log_file.path())?;
-                    let log_lines: Vec<&str> = content.lines().collect();
-                    let start = log_lines.len().saturating_sub(lines as usize);
-                    return Ok(log_lines[start..].join("\n"));
-                }
-            }
-            Ok("No logs found.".to_string())
-        }
+        // Fallback to _diag
+        Ok(read_diag_log(&dir, lines)?.unwrap_or_else(|| "No logs found.".to_string()))
+    }
+}
+
+/// Follow the instance's logs as they're written, starting with the last `lines`: the
+/// journal of its systemd service, or else its newest `_diag` log
+pub fn follow_runner_logs(config: &Config, id: &InstanceId, lines: u32) -> Result<LogFollow> {
+    let dir = id.dir(config);
+    if !dir.exists() {
+        anyhow::bail!(Failure::RunnerNotFound(format!(
+            "No runner configured for {id}"
+        )));
     }
+    let service = if config.runner_os == "darwin" || config.runner_os == "windows" {
+        None
+    } else {
+        read_service_name(&dir)
+    };
+    let Some(service) = service else {
+        return Ok(LogFollow::diag(&dir, lines as usize));
+    };
+    let lines = lines.to_string();
+    LogFollow::command(config.escalation.command(&journalctl_args(
+        config,
+        &["-u", &service, "-n", &lines, "-f", "--no-pager"],
+    )))
 }
 
-fn run_cmd(program: &str, args: &[&str]) -> Result<()> {
+/// Run a sudo-style invocation with the configured escalation tool
+pub(crate) fn run_elevated(config: &Config, args: &[&str]) -> Result<()> {
+    let (program, args) = config.escalation.wrap(args);
+    run_cmd(program, &args)
+}
+
+fn run_elevated_in_dir(config: &Config, dir: &Path, args: &[&str]) -> Result<()> {
+    let (program, args) = config.escalation.wrap(args);
+    run_cmd_in_dir(dir, program, &args)
+}
+
+pub(crate) fn run_cmd(program: &str, args: &[&str]) -> Result<()> {
     if is_verbose() {
-        verbose_log(&format!(
+        log_line(&format!(
             "[verbose] Running: {} {}",
             program,
             args.join(" ")
@@ -622,18 +2041,18 @@ fn run_cmd(program: &str, args: &[&str]) -> Result<()> {
 
     if is_verbose() {
         if !output.stdout.is_empty() {
-            verbose_log(&format!(
+            log_line(&format!(
                 "[verbose] stdout: {}",
                 String::from_utf8_lossy(&output.stdout)
             ));
         }
         if !output.stderr.is_empty() {
-            verbose_log(&format!(
+            log_line(&format!(
                 "[verbose] stderr: {}",
                 String::from_utf8_lossy(&output.stderr)
             ));
         }
-        verbose_log(&format!("[verbose] exit code: {:?}", output.status.code()));
+        log_line(&format!("[verbose] exit code: {:?}", output.status.code()));
     }
 
     if !output.status.success() {
@@ -649,9 +2068,51 @@ fn run_cmd(program: &str, args: &[&str]) -> Result<()> {
     Ok(())
 }
 
+/// Write a file inside an instance directory as the runner user
+fn write_instance_file(config: &Config, dir: &Path, name: &str, contents: &str) -> Result<()> {
+    let path = dir.join(name);
+    let path = path.to_string_lossy();
+    let args = ["-u", config.runner_user.as_str(), "tee", path.as_ref()];
+
+    if is_verbose() {
+        log_line(&format!(
+            "[verbose] Running: {} {}",
+            config.escalation,
+            args.join(" ")
+        ));
+    }
+
+    let mut child = config
+        .escalation
+        .command(&args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "Failed to execute: {} {}",
+                config.escalation,
+                args.join(" ")
+            )
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        use std::io::Write;
+        stdin.write_all(contents.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to write {path}\n{stderr}");
+    }
+    Ok(())
+}
+
 fn run_cmd_in_dir(dir: &Path, program: &str, args: &[&str]) -> Result<()> {
     if is_verbose() {
-        verbose_log(&format!(
+        log_line(&format!(
             "[verbose] Running in {}: {} {}",
             dir.display(),
             program,
@@ -667,18 +2128,18 @@ fn run_cmd_in_dir(dir: &Path, program: &str, args: &[&str]) -> Result<()> {
 
     if is_verbose() {
         if !output.stdout.is_empty() {
-            verbose_log(&format!(
+            log_line(&format!(
                 "[verbose] stdout: {}",
                 String::from_utf8_lossy(&output.stdout)
             ));
         }
         if !output.stderr.is_empty() {
-            verbose_log(&format!(
+            log_line(&format!(
                 "[verbose] stderr: {}",
                 String::from_utf8_lossy(&output.stderr)
             ));
         }
-        verbose_log(&format!("[verbose] exit code: {:?}", output.status.code()));
+        log_line(&format!("[verbose] exit code: {:?}", output.status.code()));
     }
 
     if !output.status.success() {
@@ -688,8 +2149,102 @@ fn run_cmd_in_dir(dir: &Path, program: &str, args: &[&str]) -> Result<()> {
     Ok(())
 }
 
-/// Import an existing runner directory into runner-mgr management
-pub fn import_runner(config: &Config, path: &str, scope_override: Option<&str>) -> Result<()> {
+/// Where exported logs go by default: `runner-mgr-<instance>-<timestamp>.log` in `dir`
+pub fn log_export_path(dir: &Path, id: &InstanceId, timestamp: &str) -> PathBuf {
+    dir.join(format!("runner-mgr-{}-{timestamp}.log", id.dir_name()))
+}
+
+/// Directory exported logs are saved to by default: Downloads, else the home directory
+pub fn log_export_dir() -> PathBuf {
+    dirs::download_dir()
+        .filter(|d| d.is_dir())
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// `path` with a leading `~/` replaced by the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Open `path` for appending (see `expand_home`), creating it and its directory
+pub fn open_log_file(path: &str) -> Result<fs::File> {
+    let path = expand_home(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+/// Write exported logs to `path` (see `expand_home`), creating its directory. Returns
+/// the path written.
+pub fn export_logs(path: &str, content: &str) -> Result<PathBuf> {
+    let path = expand_home(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let mut content = content.to_string();
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// What importing a runner directory will do, worked out before anything changes
+#[derive(Debug, Clone)]
+pub struct ImportPlan {
+    pub scope: RunnerScope,
+    /// The runner directory (absolute); it stays where it is
+    pub source: PathBuf,
+    /// Instance path that becomes a symlink to `source`
+    pub target: PathBuf,
+    /// Existing service adopted by the instance
+    pub service: Option<String>,
+    /// Whether `.service` has to be written to record `service`
+    pub writes_service_file: bool,
+}
+
+impl ImportPlan {
+    /// The changes the import makes, one per line
+    pub fn describe(&self) -> Vec<String> {
+        let mut changes = vec![format!(
+            "Symlink {} -> {} (the runner's files are not moved or copied)",
+            self.target.display(),
+            self.source.display()
+        )];
+        match &self.service {
+            Some(svc) if self.writes_service_file => changes.push(format!(
+                "Adopt the existing service {svc} (recorded in {})",
+                self.source.join(".service").display()
+            )),
+            Some(svc) => changes.push(format!("Adopt the existing service {svc}")),
+            None => changes.push(
+                "No service detected: start/stop won't work until the runner is installed \
+                 as a service (svc.sh install)"
+                    .to_string(),
+            ),
+        }
+        changes
+    }
+}
+
+/// Check a runner directory and work out how it would be imported, without changing
+/// anything
+pub fn plan_import(
+    config: &Config,
+    path: &str,
+    scope_override: Option<&str>,
+) -> Result<ImportPlan> {
     let source_path = Path::new(path);
 
     // Expand ~ to home directory
@@ -722,7 +2277,7 @@ pub fn import_runner(config: &Config, path: &str, scope_override: Option<&str>)
         if runner_file.exists() {
             let content =
                 fs::read_to_string(&runner_file).context("Failed to read .runner file")?;
-            parse_scope_from_runner_config(&content)?
+            parse_scope_from_runner_config_at(&content, &config.web_url())?
         } else {
             anyhow::bail!(
                 "Could not auto-detect scope. No .runner file found.\n\
@@ -731,59 +2286,84 @@ pub fn import_runner(config: &Config, path: &str, scope_override: Option<&str>)
         }
     };
 
-    println!("Importing runner for {scope}...");
-    println!("  Source: {}", source_path.display());
-
     // Check if already managed
-    let target_dir = config.instance_dir(&scope);
-    if target_dir.exists() {
+    let target = config.instance_dir(&scope);
+    if target.exists() {
         anyhow::bail!(
             "Runner already configured for {} at {}",
             scope,
-            target_dir.display()
+            target.display()
         );
     }
 
-    // Create instances directory if needed
-    let instances_dir = config.instances_dir();
-    if !instances_dir.exists() {
-        run_cmd("sudo", &["mkdir", "-p", &instances_dir.to_string_lossy()])?;
+    let source = source_path
+        .canonicalize()
+        .context("Failed to get absolute path of source directory")?;
+    let service = detect_service_name(&source, config);
+    Ok(ImportPlan {
+        writes_service_file: service.is_some() && !source.join(".service").exists(),
+        scope,
+        source,
+        target,
+        service,
+    })
+}
+
+/// Link an instance path to an existing runner directory: a symlink, or on Windows a
+/// directory junction (which, unlike a symlink, needs no extra privilege)
+fn link_instance(config: &Config, source: &Path, target: &Path) -> Result<()> {
+    if config.runner_os == "windows" {
         run_cmd(
-            "sudo",
+            "cmd",
+            &[
+                "/C",
+                "mklink",
+                "/J",
+                &target.to_string_lossy(),
+                &source.to_string_lossy(),
+            ],
+        )
+    } else {
+        run_elevated(
+            config,
             &[
-                "chown",
+                "-u",
                 &config.runner_user,
-                &instances_dir.to_string_lossy(),
+                "ln",
+                "-s",
+                &source.to_string_lossy(),
+                &target.to_string_lossy(),
             ],
-        )?;
+        )
     }
+}
 
-    // Create symlink to existing runner
-    println!("Creating symlink...");
-    let source_abs = source_path
-        .canonicalize()
-        .context("Failed to get absolute path of source directory")?;
+/// Import an existing runner directory into runner-mgr management
+pub fn import_runner(config: &Config, path: &str, scope_override: Option<&str>) -> Result<()> {
+    let plan = plan_import(config, path, scope_override)?;
+    apply_import(config, &plan)
+}
 
-    run_cmd(
-        "sudo",
-        &[
-            "-u",
-            &config.runner_user,
-            "ln",
-            "-s",
-            &source_abs.to_string_lossy(),
-            &target_dir.to_string_lossy(),
-        ],
-    )?;
+/// Perform an import worked out by `plan_import`
+pub fn apply_import(config: &Config, plan: &ImportPlan) -> Result<()> {
+    let scope = &plan.scope;
+    println!("Importing runner for {scope}...");
+    println!("  Source: {}", plan.source.display());
+
+    // Create instances directory if needed
+    let instances_dir = config.instances_dir();
+    if !instances_dir.exists() {
+        create_owned_dir(config, &instances_dir.to_string_lossy())?;
+    }
+
+    println!("Creating symlink...");
+    link_instance(config, &plan.source, &plan.target)?;
 
-    // Detect service name
-    let service_name = detect_service_name(&source_path, config);
-    if let Some(ref svc) = service_name {
+    if let Some(ref svc) = plan.service {
         println!("  Detected service: {svc}");
         // Write .service file if not already present
-        let service_file = source_path.join(".service");
-        if !service_file.exists() {
-            fs::write(&service_file, svc).ok();
+        if plan.writes_service_file {
+            fs::write(plan.source.join(".service"), svc).ok();
         }
     }
 
@@ -791,10 +2371,10 @@ pub fn import_runner(config: &Config, path: &str, scope_override: Option<&str>)
     println!("Runner imported for {scope}");
     println!(
         "  Instance: {} -> {}",
-        target_dir.display(),
-        source_abs.display()
+        plan.target.display(),
+        plan.source.display()
     );
-    if let Some(svc) = service_name {
+    if let Some(svc) = &plan.service {
         println!("  Service:  {svc}");
     } else {
         println!("  Service:  (not detected - runner may not be installed as service)");
@@ -805,6 +2385,11 @@ pub fn import_runner(config: &Config, path: &str, scope_override: Option<&str>)
 
 /// Parse scope (repository or organization) from .runner JSON config
 pub fn parse_scope_from_runner_config(content: &str) -> Result<RunnerScope> {
+    parse_scope_from_runner_config_at(content, github::DEFAULT_WEB_URL)
+}
+
+/// Parse scope from a .runner config registered with the GitHub instance at `web_url`
+pub fn parse_scope_from_runner_config_at(content: &str, web_url: &str) -> Result<RunnerScope> {
     // The .runner file is JSON with a "gitHubUrl" field like "https://github.com/owner/repo"
     // or "https://github.com/org" for organization runners
     #[derive(serde::Deserialize)]
@@ -823,7 +2408,7 @@ pub fn parse_scope_from_runner_config(content: &str) -> Result<RunnerScope> {
         .github_url
         .ok_or_else(|| anyhow::anyhow!("No gitHubUrl found in .runner file"))?;
 
-    RunnerScope::from_github_url(&url)
+    RunnerScope::from_web_url(&url, web_url)
 }
 
 /// Legacy function for backward compatibility - parses repository from .runner config
@@ -1078,16 +2663,7 @@ fn scan_directory_recursive(dir: &Path, paths: &mut Vec<PathBuf>, max_depth: u32
             continue;
         }
 
-        // Skip hidden directories and common non-runner directories
-        let name = entry.file_name().to_string_lossy().to_string();
-        if name.starts_with('.')
-            || name == "node_modules"
-            || name == "target"
-            || name == "build"
-            || name == "dist"
-            || name == "vendor"
-            || name == "Pods"
-        {
+        if skip_scan_dir(&entry.file_name().to_string_lossy()) {
             continue;
         }
 
@@ -1099,6 +2675,101 @@ fn scan_directory_recursive(dir: &Path, paths: &mut Vec<PathBuf>, max_depth: u32
     }
 }
 
+/// Hidden directories and common non-runner directories, never searched for runners
+fn skip_scan_dir(name: &str) -> bool {
+    name.starts_with('.')
+        || matches!(
+            name,
+            "node_modules" | "target" | "build" | "dist" | "vendor" | "Pods"
+        )
+}
+
+/// Subdirectories of `dir` worth searching. Symlinks aren't followed, so a link back
+/// up the tree can't make the search loop.
+fn scan_subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| !skip_scan_dir(&e.file_name().to_string_lossy()))
+        .map(|e| e.path())
+        .collect()
+}
+
+/// Search `dir` and, `depth` levels below it, its subdirectories. A runner
+/// directory's own subdirectories (`_work`, `_diag`) aren't searched.
+fn scan_tree_dir(
+    dir: &Path,
+    depth: u32,
+    searched: &AtomicUsize,
+    found: &Mutex<Vec<DiscoveredRunner>>,
+) {
+    searched.fetch_add(1, Ordering::Relaxed);
+    if let Some(runner) = validate_runner_directory(dir) {
+        found.lock().unwrap().push(runner);
+        return;
+    }
+    if depth == 0 {
+        return;
+    }
+    for sub in scan_subdirectories(dir) {
+        scan_tree_dir(&sub, depth - 1, searched, found);
+    }
+}
+
+/// Search `roots` for runner directories up to `depth` levels below them. The
+/// subdirectories of the roots are searched in parallel, on as many threads as there
+/// are cores; `progress` is called on the calling thread with the number of
+/// directories searched so far, every `SCAN_PROGRESS_INTERVAL` and once at the end.
+pub fn scan_tree(roots: &[PathBuf], depth: u32, progress: impl Fn(usize)) -> Vec<DiscoveredRunner> {
+    let searched = AtomicUsize::new(0);
+    let found = Mutex::new(Vec::new());
+    let mut queue = Vec::new();
+    for root in roots {
+        let Ok(root) = root.canonicalize() else {
+            continue;
+        };
+        searched.fetch_add(1, Ordering::Relaxed);
+        if let Some(runner) = validate_runner_directory(&root) {
+            found.lock().unwrap().push(runner);
+        } else if depth > 0 {
+            queue.extend(scan_subdirectories(&root));
+        }
+    }
+
+    let workers = std::thread::available_parallelism()
+        .map_or(4, std::num::NonZeroUsize::get)
+        .min(queue.len());
+    let queue = Mutex::new(queue);
+    std::thread::scope(|s| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                s.spawn(|| loop {
+                    let Some(dir) = queue.lock().unwrap().pop() else {
+                        break;
+                    };
+                    scan_tree_dir(&dir, depth - 1, &searched, &found);
+                })
+            })
+            .collect();
+        while !handles
+            .iter()
+            .all(std::thread::ScopedJoinHandle::is_finished)
+        {
+            progress(searched.load(Ordering::Relaxed));
+            std::thread::sleep(SCAN_PROGRESS_INTERVAL);
+        }
+    });
+    progress(searched.load(Ordering::Relaxed));
+
+    let mut discovered = found.into_inner().unwrap();
+    discovered.sort_by(|a, b| a.path.cmp(&b.path));
+    discovered.dedup_by(|a, b| a.path == b.path);
+    discovered
+}
+
 /// Helper to expand a path string and add it to the scan list
 fn add_path_to_scan(path_str: &str, paths: &mut Vec<PathBuf>) {
     if path_str.is_empty() {
@@ -0,0 +1,192 @@
+//! Process-wide pacing of GitHub API requests. Every `GitHubClient` sends through one
+//! scheduler that caps how many requests are in flight and how many start per minute,
+//! so operations across dozens of scopes stay under GitHub's secondary rate limits
+//! instead of being cut off halfway through. Rate-limited and failed (5xx) requests
+//! are retried with exponential backoff, and the remaining quota is tracked for the
+//! dashboard.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+/// Longest pause honored from a `retry-after` header
+pub const MAX_PAUSE: Duration = Duration::from_mins(5);
+
+/// Retries of a request GitHub rate limited or failed with a server error
+pub const MAX_RETRIES: u32 = 3;
+
+/// Backoff before the first retry of a server error, doubling for each further one
+pub const BASE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Limits on the API requests of this process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// Requests in flight at once
+    pub max_concurrent: usize,
+    /// Requests started per minute
+    pub per_minute: u32,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 8,
+            per_minute: 600,
+        }
+    }
+}
+
+/// Hands out request slots within the `RequestLimits`
+pub struct Scheduler {
+    permits: Semaphore,
+    /// Time between request starts
+    interval: Duration,
+    /// Earliest start of the next request
+    next_start: Mutex<Instant>,
+}
+
+impl Scheduler {
+    pub fn new(limits: RequestLimits) -> Self {
+        Self {
+            permits: Semaphore::new(limits.max_concurrent.max(1)),
+            interval: Duration::from_mins(1) / limits.per_minute.max(1),
+            next_start: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for a free slot and the next start time. The request counts as in flight
+    /// until the permit is dropped. (The semaphore is never closed, so there always is
+    /// one.)
+    pub async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let permit = self.permits.acquire().await.ok();
+        let start = {
+            let mut next = self
+                .next_start
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            let start = (*next).max(Instant::now());
+            *next = start + self.interval;
+            start
+        };
+        tokio::time::sleep_until(start).await;
+        permit
+    }
+
+    /// Hold back every request not yet started for `duration`, after GitHub asked us to
+    /// slow down
+    pub fn pause(&self, duration: Duration) {
+        let mut next = self
+            .next_start
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *next = (*next).max(Instant::now() + duration.min(MAX_PAUSE));
+    }
+}
+
+static SCHEDULER: OnceLock<Scheduler> = OnceLock::new();
+
+/// Set the limits of the process-wide scheduler. Only the first call has an effect:
+/// requests already paced under one set of limits keep them.
+pub fn configure(limits: RequestLimits) {
+    let _ = SCHEDULER.get_or_init(|| Scheduler::new(limits));
+}
+
+/// The process-wide scheduler, with default limits if none were configured
+pub fn global() -> &'static Scheduler {
+    SCHEDULER.get_or_init(|| Scheduler::new(RequestLimits::default()))
+}
+
+/// How long to wait before retrying a response GitHub rate limited: its `retry-after`
+/// seconds on a 403 or 429, at most `MAX_PAUSE`
+pub fn retry_after(status: u16, retry_after: Option<&str>) -> Option<Duration> {
+    if status != 403 && status != 429 {
+        return None;
+    }
+    let seconds: u64 = retry_after?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_PAUSE))
+}
+
+/// Wait before retry number `attempt` (0 for the first), with jitter so clients that
+/// failed together don't retry together: between half and all of `BASE_BACKOFF *
+/// 2^attempt`
+pub fn backoff(attempt: u32) -> Duration {
+    let full = BASE_BACKOFF.saturating_mul(2u32.saturating_pow(attempt));
+    let jitter = RandomState::new().build_hasher().finish() % 1000;
+    full / 2 + full / 2 * u32::try_from(jitter).unwrap_or(0) / 1000
+}
+
+/// How long to wait before retrying a response, if it's worth retrying: `retry-after`
+/// on a rate limit, the reset time of an exhausted quota when it's within `MAX_PAUSE`,
+/// or the `backoff` for 429s without either header and for 5xx server errors.
+/// GitHub may have applied a request that ended in a 5xx (e.g. a proxy's 502), so those
+/// are only retried when `idempotent`; a rate-limited request was never processed.
+pub fn retry_delay(
+    status: u16,
+    idempotent: bool,
+    retry_after_header: Option<&str>,
+    remaining: Option<&str>,
+    reset: Option<&str>,
+    now: i64,
+    attempt: u32,
+) -> Option<Duration> {
+    if let Some(wait) = retry_after(status, retry_after_header) {
+        return Some(wait);
+    }
+    let exhausted = remaining.is_some_and(|r| r.trim() == "0");
+    if (status == 403 || status == 429) && exhausted {
+        let reset: i64 = reset?.trim().parse().ok()?;
+        let wait = Duration::from_secs(u64::try_from(reset - now).unwrap_or(0) + 1);
+        return (wait <= MAX_PAUSE).then_some(wait);
+    }
+    let server_error = matches!(status, 500 | 502 | 503 | 504);
+    (status == 429 || server_error && idempotent).then(|| backoff(attempt))
+}
+
+/// The primary rate limit as of the latest API response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    /// Unix timestamp at which the quota resets
+    pub reset: i64,
+}
+
+static RATE_LIMIT: Mutex<Option<RateLimit>> = Mutex::new(None);
+
+/// Remember the quota from a response's `x-ratelimit-*` headers (REST `core` quota only:
+/// search and GraphQL have their own)
+pub fn record_rate_limit(
+    resource: Option<&str>,
+    limit: Option<&str>,
+    remaining: Option<&str>,
+    reset: Option<&str>,
+) {
+    if resource.is_some_and(|r| r != "core") {
+        return;
+    }
+    let parse = |v: Option<&str>| v.and_then(|v| v.trim().parse().ok());
+    let (Some(limit), Some(remaining), Some(reset)) =
+        (parse(limit), parse(remaining), parse(reset))
+    else {
+        return;
+    };
+    let reset = i64::try_from(reset).unwrap_or(i64::MAX);
+    *RATE_LIMIT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(RateLimit {
+        limit,
+        remaining,
+        reset,
+    });
+}
+
+/// The quota recorded last, if any response carried one
+pub fn rate_limit() -> Option<RateLimit> {
+    *RATE_LIMIT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
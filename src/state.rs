@@ -0,0 +1,222 @@
+//! Operational state that isn't runner configuration: notes and tags per instance, and
+//! runners on other hosts imported by `scan --remote`
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::github::RunnerScope;
+use crate::remote::RemoteRunner;
+
+/// Database for per-instance state
+pub struct StateDb {
+    conn: Connection,
+}
+
+impl StateDb {
+    /// Open or create the state database in the config directory
+    pub fn open() -> Result<Self> {
+        let db_path = Self::db_path();
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        Self::open_at(&db_path)
+    }
+
+    /// Open or create a state database at `path`
+    pub fn open_at(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open state database at {}", path.display()))?;
+        let db = Self { conn };
+        db.run_migrations()?;
+        Ok(db)
+    }
+
+    fn db_path() -> PathBuf {
+        Config::config_dir().join("state.db")
+    }
+
+    fn run_migrations(&self) -> Result<()> {
+        self.conn.execute_batch(
+            r"
+            -- Free-form note per instance
+            CREATE TABLE IF NOT EXISTS instance_notes (
+                scope_identifier TEXT PRIMARY KEY,
+                note TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+
+            -- Tags per instance
+            CREATE TABLE IF NOT EXISTS instance_tags (
+                scope_identifier TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                UNIQUE(scope_identifier, tag)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_instance_tags_tag ON instance_tags(tag);
+
+            -- Runners on other hosts, managed there
+            CREATE TABLE IF NOT EXISTS remote_instances (
+                host TEXT NOT NULL,
+                path TEXT NOT NULL,
+                scope_identifier TEXT NOT NULL,
+                agent_name TEXT,
+                imported_at INTEGER NOT NULL,
+                PRIMARY KEY(host, path)
+            );
+            ",
+        )?;
+        Ok(())
+    }
+
+    /// Set the note for an instance; an empty note removes it
+    pub fn set_note(&self, scope: &RunnerScope, note: &str) -> Result<()> {
+        let note = note.trim();
+        if note.is_empty() {
+            self.conn.execute(
+                "DELETE FROM instance_notes WHERE scope_identifier = ?1",
+                params![scope.to_display()],
+            )?;
+            return Ok(());
+        }
+        self.conn.execute(
+            r"
+            INSERT INTO instance_notes (scope_identifier, note, updated_at)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(scope_identifier) DO UPDATE SET
+                note = excluded.note,
+                updated_at = excluded.updated_at
+            ",
+            params![scope.to_display(), note, Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    pub fn note(&self, scope: &RunnerScope) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT note FROM instance_notes WHERE scope_identifier = ?1",
+                params![scope.to_display()],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    pub fn add_tag(&self, scope: &RunnerScope, tag: &str) -> Result<()> {
+        let tag = validate_tag(tag)?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO instance_tags (scope_identifier, tag) VALUES (?1, ?2)",
+            params![scope.to_display(), tag],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_tag(&self, scope: &RunnerScope, tag: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM instance_tags WHERE scope_identifier = ?1 AND tag = ?2",
+            params![scope.to_display(), tag.trim()],
+        )?;
+        Ok(())
+    }
+
+    /// Tags for an instance, sorted
+    pub fn tags(&self, scope: &RunnerScope) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tag FROM instance_tags WHERE scope_identifier = ?1 ORDER BY tag")?;
+        let tags = stmt
+            .query_map(params![scope.to_display()], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(tags)
+    }
+
+    /// Every tag in use, sorted
+    pub fn all_tags(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT tag FROM instance_tags ORDER BY tag")?;
+        let tags = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(tags)
+    }
+
+    /// Remove the notes and tags of an instance (when the runner is removed)
+    pub fn forget(&self, scope: &RunnerScope) -> Result<()> {
+        let scope_id = scope.to_display();
+        self.conn.execute(
+            "DELETE FROM instance_notes WHERE scope_identifier = ?1",
+            params![scope_id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM instance_tags WHERE scope_identifier = ?1",
+            params![scope_id],
+        )?;
+        Ok(())
+    }
+
+    /// Record a runner found on another host; importing it again updates its scope and
+    /// name
+    pub fn import_remote(&self, runner: &RemoteRunner) -> Result<()> {
+        self.conn.execute(
+            r"
+            INSERT INTO remote_instances (host, path, scope_identifier, agent_name, imported_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(host, path) DO UPDATE SET
+                scope_identifier = excluded.scope_identifier,
+                agent_name = excluded.agent_name
+            ",
+            params![
+                runner.host,
+                runner.path,
+                runner.scope.to_display(),
+                runner.agent_name,
+                Utc::now().timestamp()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Imported remote runners, sorted by host and path
+    pub fn remote_instances(&self) -> Result<Vec<RemoteRunner>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT host, path, scope_identifier, agent_name FROM remote_instances ORDER BY host, path",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(host, path, scope, agent_name)| {
+                Some(RemoteRunner {
+                    host,
+                    path,
+                    scope: RunnerScope::parse(&scope).ok()?,
+                    agent_name,
+                })
+            })
+            .collect())
+    }
+}
+
+/// Tags are single words: no whitespace or commas
+pub fn validate_tag(tag: &str) -> Result<&str> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        anyhow::bail!("Tag cannot be empty");
+    }
+    if tag.chars().any(|c| c.is_whitespace() || c == ',') {
+        anyhow::bail!("Invalid tag '{tag}': tags cannot contain spaces or commas");
+    }
+    Ok(tag)
+}
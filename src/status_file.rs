@@ -0,0 +1,180 @@
+//! Status file for external status pages: overall fleet health and the health of each
+//! scope as JSON, plus an optional shields.io endpoint badge, rewritten after every
+//! refresh so a status page can show runner health without calling the GitHub API
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use crate::config::StatusFileConfig;
+use crate::github::{Runner, RunnerScope};
+
+/// Health of a scope or of the whole fleet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    /// Every runner is online
+    Ok,
+    /// Some runners are offline
+    Degraded,
+    /// No runner is online
+    Down,
+    /// GitHub couldn't be asked
+    Unknown,
+}
+
+impl Health {
+    pub fn label(self) -> &'static str {
+        match self {
+            Health::Ok => "ok",
+            Health::Degraded => "degraded",
+            Health::Down => "down",
+            Health::Unknown => "unknown",
+        }
+    }
+
+    /// shields.io color for the badge
+    pub fn color(self) -> &'static str {
+        match self {
+            Health::Ok => "brightgreen",
+            Health::Degraded => "yellow",
+            Health::Down => "red",
+            Health::Unknown => "lightgrey",
+        }
+    }
+}
+
+/// Runner counts and health of one scope
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeStatus {
+    pub scope: String,
+    pub health: Health,
+    pub online: usize,
+    pub busy: usize,
+    pub total: usize,
+}
+
+impl ScopeStatus {
+    /// Status of `scope` from its runners on GitHub, `None` when they couldn't be fetched
+    pub fn new(scope: &RunnerScope, runners: Option<&[Runner]>) -> Self {
+        let scope = scope.to_display();
+        let Some(runners) = runners else {
+            return Self {
+                scope,
+                health: Health::Unknown,
+                online: 0,
+                busy: 0,
+                total: 0,
+            };
+        };
+        let online = runners.iter().filter(|r| r.status == "online").count();
+        let busy = runners.iter().filter(|r| r.busy).count();
+        let total = runners.len();
+        let health = if online == 0 {
+            Health::Down
+        } else if online < total {
+            Health::Degraded
+        } else {
+            Health::Ok
+        };
+        Self {
+            scope,
+            health,
+            online,
+            busy,
+            total,
+        }
+    }
+}
+
+/// Status of every scope the collector polled
+#[derive(Debug, Clone, Default)]
+pub struct FleetStatus {
+    pub scopes: Vec<ScopeStatus>,
+}
+
+impl FleetStatus {
+    /// `Ok` or `Down` when every scope is, `Unknown` when none could be polled, and
+    /// `Degraded` for anything in between
+    pub fn health(&self) -> Health {
+        let Some(first) = self.scopes.first() else {
+            return Health::Unknown;
+        };
+        if self.scopes.iter().all(|s| s.health == first.health) {
+            first.health
+        } else {
+            Health::Degraded
+        }
+    }
+
+    pub fn online(&self) -> usize {
+        self.scopes.iter().map(|s| s.online).sum()
+    }
+
+    pub fn total(&self) -> usize {
+        self.scopes.iter().map(|s| s.total).sum()
+    }
+
+    /// The status file document
+    pub fn to_json(&self, updated_at: DateTime<Utc>) -> Value {
+        let scopes: Vec<Value> = self
+            .scopes
+            .iter()
+            .map(|s| {
+                json!({
+                    "scope": s.scope,
+                    "status": s.health.label(),
+                    "online": s.online,
+                    "busy": s.busy,
+                    "total": s.total,
+                })
+            })
+            .collect();
+        json!({
+            "updated_at": updated_at.to_rfc3339(),
+            "status": self.health().label(),
+            "online": self.online(),
+            "total": self.total(),
+            "scopes": scopes,
+        })
+    }
+
+    /// A shields.io endpoint badge (`https://img.shields.io/endpoint?url=...`)
+    pub fn badge(&self) -> Value {
+        let health = self.health();
+        let message = if health == Health::Unknown {
+            health.label().to_string()
+        } else {
+            format!("{}/{} online", self.online(), self.total())
+        };
+        json!({
+            "schemaVersion": 1,
+            "label": "runners",
+            "message": message,
+            "color": health.color(),
+        })
+    }
+}
+
+/// Write the status file and badge configured in `settings`
+pub fn write(settings: &StatusFileConfig, status: &FleetStatus) -> Result<()> {
+    write_json(Path::new(&settings.path), &status.to_json(Utc::now()))?;
+    if let Some(badge) = &settings.badge {
+        write_json(Path::new(badge), &status.badge())?;
+    }
+    Ok(())
+}
+
+/// Replace `path` in one step, so a status page never reads a half-written file
+fn write_json(path: &Path, doc: &Value) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let partial = path.with_extension("json.partial");
+    fs::write(&partial, format!("{doc:#}\n"))
+        .with_context(|| format!("Failed to write {}", partial.display()))?;
+    fs::rename(&partial, path).with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,199 @@
+//! systemd integration of `runner-mgr serve`: the `sd_notify` protocol for `Type=notify`
+//! units with a watchdog, and the unit `runner-mgr serve install` writes
+
+use std::ffi::OsStr;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::config::Config;
+use crate::runner;
+
+/// Name of the unit `runner-mgr serve install` writes
+pub const SERVE_UNIT: &str = "runner-mgr-serve.service";
+
+/// Name of the serve unit of `account` (None: the default account)
+pub fn serve_unit_name(account: Option<&str>) -> String {
+    match account {
+        Some(name) => format!("runner-mgr-serve-{name}.service"),
+        None => SERVE_UNIT.to_string(),
+    }
+}
+
+/// `WatchdogSec=` of that unit. The collector pings while `/healthz` would pass, so a
+/// stuck loop is restarted this long after it counts as stuck.
+pub const WATCHDOG_SEC: u64 = 60;
+
+/// Send `state` (e.g. `READY=1`) to the service manager. Returns whether there was one
+/// listening, i.e. `NOTIFY_SOCKET` is set.
+pub fn notify(state: &str) -> Result<bool> {
+    let Some(socket) = std::env::var_os("NOTIFY_SOCKET") else {
+        return Ok(false);
+    };
+    send(&socket, state).with_context(|| {
+        format!(
+            "Failed to notify systemd on {}",
+            Path::new(&socket).display()
+        )
+    })?;
+    Ok(true)
+}
+
+#[cfg(unix)]
+fn send(socket: &OsStr, state: &str) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    let datagram = UnixDatagram::unbound()?;
+    // `@` starts a name in Linux's abstract socket namespace
+    if let Some(name) = socket.as_bytes().strip_prefix(b"@") {
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            datagram.send_to_addr(state.as_bytes(), &addr)?;
+            return Ok(());
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = name;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "abstract sockets only exist on Linux",
+            ));
+        }
+    }
+    datagram.send_to(state.as_bytes(), socket)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send(_socket: &OsStr, _state: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "systemd notifications need Unix sockets",
+    ))
+}
+
+/// How often to ping a watchdog of `usec` microseconds (`WATCHDOG_USEC`): half of it, as
+/// systemd recommends. None without a watchdog, or when `pid` (`WATCHDOG_PID`) names
+/// another process than `own_pid`.
+pub fn parse_watchdog(usec: Option<&str>, pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    if let Some(pid) = pid {
+        if pid.trim().parse::<u32>().ok()? != own_pid {
+            return None;
+        }
+    }
+    let usec = usec?.trim().parse::<u64>().ok().filter(|&usec| usec > 0)?;
+    Some(Duration::from_micros(usec) / 2)
+}
+
+/// How often this process has to ping systemd's watchdog, if it has one
+pub fn watchdog_interval() -> Option<Duration> {
+    parse_watchdog(
+        std::env::var("WATCHDOG_USEC").ok().as_deref(),
+        std::env::var("WATCHDOG_PID").ok().as_deref(),
+        std::process::id(),
+    )
+}
+
+/// How `runner-mgr serve` is started by its unit
+#[derive(Debug, Clone, Default)]
+pub struct ServeUnit {
+    /// The runner-mgr binary
+    pub exe: PathBuf,
+    /// Account to run as (`User=`); None for user units and when installed as root
+    pub user: Option<String>,
+    /// runner-mgr account (`--account`) whose config it uses
+    pub account: Option<String>,
+    pub interval: Option<u64>,
+    pub listen: Option<String>,
+    /// Whether it's a unit of the user's own service manager (rootless mode)
+    pub user_unit: bool,
+}
+
+impl ServeUnit {
+    /// The `ExecStart=` command line
+    pub fn exec_start(&self) -> String {
+        let exe = self.exe.display().to_string();
+        let mut command = if exe.contains(char::is_whitespace) {
+            format!("\"{exe}\" serve")
+        } else {
+            format!("{exe} serve")
+        };
+        if let Some(account) = &self.account {
+            let _ = write!(command, " --account {account}");
+        }
+        if let Some(interval) = self.interval {
+            let _ = write!(command, " --interval {interval}");
+        }
+        if let Some(listen) = &self.listen {
+            let _ = write!(command, " --listen {listen}");
+        }
+        command
+    }
+
+    /// The unit file
+    pub fn render(&self) -> String {
+        let user = self
+            .user
+            .as_ref()
+            .map(|user| format!("User={user}\n"))
+            .unwrap_or_default();
+        let target = if self.user_unit {
+            "default.target"
+        } else {
+            "multi-user.target"
+        };
+        format!(
+            "# Managed by runner-mgr\n\
+             [Unit]\n\
+             Description=runner-mgr collector (runner status, metrics and notifications)\n\
+             Wants=network-online.target\n\
+             After=network-online.target\n\
+             \n\
+             [Service]\n\
+             Type=notify\n\
+             NotifyAccess=main\n\
+             ExecStart={}\n\
+             {user}\
+             Restart=on-failure\n\
+             RestartSec=10\n\
+             WatchdogSec={WATCHDOG_SEC}\n\
+             \n\
+             [Install]\n\
+             WantedBy={target}\n",
+            self.exec_start()
+        )
+    }
+}
+
+/// Write `unit` as the serve unit, then enable and start it (Linux). Returns its path.
+pub fn install_serve(config: &Config, unit: &str) -> Result<PathBuf> {
+    if config.runner_os != "linux" {
+        anyhow::bail!("`serve install` writes a systemd unit, which needs Linux");
+    }
+    let name = serve_unit_name(crate::config::account().as_deref());
+    let unit_dir = runner::systemd_unit_dir(config);
+    let path = unit_dir.join(&name);
+    if config.rootless() {
+        fs::create_dir_all(&unit_dir)
+            .with_context(|| format!("Failed to create {}", unit_dir.display()))?;
+        fs::write(&path, unit).with_context(|| format!("Failed to write {}", path.display()))?;
+    } else {
+        let tmp = std::env::temp_dir().join(format!("runner-mgr-{name}"));
+        fs::write(&tmp, unit).with_context(|| format!("Failed to write {name}"))?;
+        let result = runner::run_elevated(
+            config,
+            &["cp", &tmp.to_string_lossy(), &path.to_string_lossy()],
+        );
+        let _ = fs::remove_file(&tmp);
+        result?;
+    }
+    runner::systemctl(config, &["daemon-reload"])?;
+    runner::systemctl(config, &["enable", "--now", &name])?;
+    Ok(path)
+}
@@ -0,0 +1,111 @@
+//! Resolution of command-line targets (`owner/repo`, `org:name`, either with `@name`)
+//! against the configured instances, with "did you mean" suggestions for typos
+
+use anyhow::{anyhow, Result};
+
+use crate::failure::Failure;
+use crate::github::RunnerScope;
+use crate::runner::{InstanceId, RunnerInstance};
+
+/// Instances `target` refers to: `owner/repo@name` is one instance, a bare scope is
+/// every instance of it
+pub fn resolve(instances: &[RunnerInstance], target: &str) -> Result<Vec<InstanceId>> {
+    let id = parse(instances, target)?;
+    let ids: Vec<InstanceId> = instances
+        .iter()
+        .map(RunnerInstance::id)
+        .filter(|i| match id.name {
+            Some(_) => *i == id,
+            None => i.scope == id.scope,
+        })
+        .collect();
+    if ids.is_empty() {
+        return Err(not_configured(instances, &id, target));
+    }
+    Ok(ids)
+}
+
+/// The one instance `target` names; a bare scope is its first (unnamed) instance
+pub fn resolve_one(instances: &[RunnerInstance], target: &str) -> Result<InstanceId> {
+    let id = parse(instances, target)?;
+    if !instances.iter().any(|i| i.id() == id) {
+        return Err(not_configured(instances, &id, target));
+    }
+    Ok(id)
+}
+
+/// The scope `target` names, which has at least one instance
+pub fn resolve_scope(instances: &[RunnerInstance], target: &str) -> Result<RunnerScope> {
+    let scope = RunnerScope::parse(target).map_err(|e| with_suggestion(e, instances, target))?;
+    if !instances.iter().any(|i| i.scope == scope) {
+        return Err(not_configured(
+            instances,
+            &InstanceId::primary(scope),
+            target,
+        ));
+    }
+    Ok(scope)
+}
+
+fn parse(instances: &[RunnerInstance], target: &str) -> Result<InstanceId> {
+    InstanceId::parse(target).map_err(|e| with_suggestion(e, instances, target))
+}
+
+fn not_configured(instances: &[RunnerInstance], id: &InstanceId, target: &str) -> anyhow::Error {
+    let message = format!("No runner configured for {id}");
+    Failure::RunnerNotFound(match suggest(instances, target) {
+        Some(suggestion) => format!("{message}; did you mean {suggestion}?"),
+        None => message,
+    })
+    .into()
+}
+
+fn with_suggestion(e: anyhow::Error, instances: &[RunnerInstance], target: &str) -> anyhow::Error {
+    match suggest(instances, target) {
+        Some(suggestion) => anyhow!("{e:#}; did you mean {suggestion}?"),
+        None => e,
+    }
+}
+
+/// The configured instance or scope closest to `target`: the same one in other case,
+/// the one whose repository or organization name `target` is, or one a few typos away
+pub fn suggest(instances: &[RunnerInstance], target: &str) -> Option<String> {
+    let mut candidates: Vec<String> = instances
+        .iter()
+        .flat_map(|i| [i.scope.to_display(), i.id().to_string()])
+        .filter(|candidate| candidate != target)
+        .collect();
+    let target = target.to_lowercase();
+    candidates.sort();
+    candidates.dedup();
+    let max_typos = (target.chars().count() / 4).max(1);
+    candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let full = candidate.to_lowercase();
+            // `repo@name` or `name@name`: the target without its owner or `org:`
+            let short = full
+                .split_once('/')
+                .or_else(|| full.split_once(':'))
+                .map_or(full.as_str(), |(_, rest)| rest);
+            let typos = edit_distance(&target, &full).min(edit_distance(&target, short));
+            (typos <= max_typos).then_some((typos, candidate))
+        })
+        .min_by_key(|(typos, _)| *typos)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance: single-character insertions, deletions and substitutions
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != *cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,85 @@
+//! Toolchain inventory: probe which language runtimes and tools a runner can see
+
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+
+/// Result of running a single probe command
+#[derive(Debug, Clone)]
+pub struct ToolVersion {
+    /// The probe command as configured (e.g. `node --version`)
+    pub command: String,
+    /// First non-empty line of output, or `None` if the command failed
+    pub version: Option<String>,
+}
+
+impl ToolVersion {
+    /// Tool name shown in tables: the first word of the probe command
+    pub fn tool(&self) -> &str {
+        self.command
+            .split_whitespace()
+            .next()
+            .unwrap_or(&self.command)
+    }
+}
+
+/// Run every configured probe as the runner user.
+///
+/// When `instance_dir` is given, the probe uses the PATH the runner recorded in
+/// its `.path` file at configuration time, so results match what jobs see.
+pub fn probe(config: &Config, instance_dir: Option<&Path>) -> Vec<ToolVersion> {
+    let path_env = instance_dir.and_then(read_runner_path);
+
+    config
+        .toolchain_commands()
+        .into_iter()
+        .map(|command| {
+            let version = run_probe(config, instance_dir, path_env.as_deref(), &command);
+            ToolVersion { command, version }
+        })
+        .collect()
+}
+
+fn read_runner_path(dir: &Path) -> Option<String> {
+    fs::read_to_string(dir.join(".path"))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn run_probe(
+    config: &Config,
+    dir: Option<&Path>,
+    path_env: Option<&str>,
+    command: &str,
+) -> Option<String> {
+    let path_arg = path_env.map(|path| format!("PATH={path}"));
+    let mut args: Vec<&str> = vec!["-n", "-u", &config.runner_user];
+    if let Some(path) = &path_arg {
+        args.extend(["env", path]);
+    }
+    args.extend(["sh", "-lc", command]);
+
+    let mut cmd = config.escalation.command(&args);
+    if let Some(d) = dir {
+        cmd.current_dir(d);
+    }
+
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // Some tools (java -version) print their version on stderr
+    first_line(&output.stdout).or_else(|| first_line(&output.stderr))
+}
+
+/// First non-empty, trimmed line of command output
+pub fn first_line(output: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(ToString::to_string)
+}
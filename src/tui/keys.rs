@@ -0,0 +1,162 @@
+//! Key handling: map key presses (including multi-key sequences like `g g`) to actions
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::time::{Duration, Instant};
+
+/// How long the first key of a sequence waits for the second
+const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Something the dashboard can do in response to keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    NextPanel,
+    FocusMetrics,
+    Up,
+    Down,
+    Top,
+    Bottom,
+    Select,
+    ToggleRunner,
+    StartRunner,
+    StopRunner,
+    RestartRunner,
+    ShowLogs,
+    LiveLogs,
+    ShowLabels,
+    OpenInBrowser,
+    OpenMenu,
+    ShowHistory,
+    ShowErrors,
+    ShowTimeline,
+    ShowBlame,
+    ShowHosts,
+    CycleTagFilter,
+    ToggleWatchdog,
+    RemoveRunner,
+    CancelRun,
+    RerunRun,
+    Refresh,
+    StartAll,
+    StopAll,
+    ToggleLogs,
+    ClearLogs,
+    ScrollLogsUp,
+    ScrollLogsDown,
+    ToggleFollowLogs,
+}
+
+impl Action {
+    /// Whether holding the key down should repeat the action. Anything that
+    /// changes runner state only fires once per press.
+    pub fn repeatable(self) -> bool {
+        matches!(
+            self,
+            Action::Up | Action::Down | Action::ScrollLogsUp | Action::ScrollLogsDown
+        )
+    }
+
+    /// Whether the action starts, stops or removes runners or changes workflow runs,
+    /// which a replayed dashboard doesn't do
+    pub fn changes_state(self) -> bool {
+        matches!(
+            self,
+            Action::ToggleRunner
+                | Action::StartRunner
+                | Action::StopRunner
+                | Action::RestartRunner
+                | Action::ToggleWatchdog
+                | Action::RemoveRunner
+                | Action::CancelRun
+                | Action::RerunRun
+                | Action::StartAll
+                | Action::StopAll
+        )
+    }
+}
+
+/// Two-key sequences. The first key of a sequence must not also be a single-key binding.
+const SEQUENCES: &[(char, char, Action)] =
+    &[('g', 'g', Action::Top), ('d', 'd', Action::RemoveRunner)];
+
+fn single_key(code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+    let action = match code {
+        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+        KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+        KeyCode::Tab => Action::NextPanel,
+        KeyCode::Char('m') => Action::OpenMenu,
+        KeyCode::Char('M') => Action::FocusMetrics,
+        KeyCode::Up | KeyCode::Char('k') => Action::Up,
+        KeyCode::Down | KeyCode::Char('j') => Action::Down,
+        KeyCode::Home => Action::Top,
+        KeyCode::End | KeyCode::Char('G') => Action::Bottom,
+        KeyCode::Enter => Action::Select,
+        KeyCode::Char('s') => Action::ToggleRunner,
+        KeyCode::Char('l') => Action::LiveLogs,
+        KeyCode::Char('r') => Action::Refresh,
+        KeyCode::Char('S') => Action::StartAll,
+        KeyCode::Char('X') => Action::StopAll,
+        KeyCode::Char('v') => Action::ToggleLogs,
+        KeyCode::Char('H') => Action::ShowHistory,
+        KeyCode::Char('e') => Action::ShowErrors,
+        KeyCode::Char('T') => Action::ShowTimeline,
+        KeyCode::Char('B') => Action::ShowBlame,
+        KeyCode::Char('h') => Action::ShowHosts,
+        KeyCode::Char('t') => Action::CycleTagFilter,
+        KeyCode::Char('W') => Action::ToggleWatchdog,
+        KeyCode::Char('x') => Action::CancelRun,
+        KeyCode::Char('R') => Action::RerunRun,
+        KeyCode::Char('c') => Action::ClearLogs,
+        KeyCode::PageUp => Action::ScrollLogsUp,
+        KeyCode::PageDown => Action::ScrollLogsDown,
+        KeyCode::Char('F') => Action::ToggleFollowLogs,
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// Resolves key events to actions, tracking the first key of a pending sequence
+#[derive(Debug, Default)]
+pub struct KeyDispatcher {
+    pending: Option<(char, Instant)>,
+}
+
+impl KeyDispatcher {
+    /// The first key of a sequence waiting for its second key, for the status bar
+    pub fn pending(&self) -> Option<char> {
+        self.pending
+            .filter(|(_, at)| at.elapsed() < SEQUENCE_TIMEOUT)
+            .map(|(c, _)| c)
+    }
+
+    pub fn dispatch(&mut self, key: KeyEvent) -> Option<Action> {
+        if key.kind == KeyEventKind::Release {
+            return None;
+        }
+        let repeat = key.kind == KeyEventKind::Repeat;
+        let plain = !key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+        if let (Some(first), KeyCode::Char(second), true) = (self.pending(), key.code, plain) {
+            self.pending = None;
+            if let Some(&(_, _, action)) = SEQUENCES
+                .iter()
+                .find(|(a, b, _)| *a == first && *b == second)
+            {
+                return Some(action);
+            }
+            // Not a sequence: fall through and treat the key on its own
+        }
+        self.pending = None;
+
+        if let (KeyCode::Char(c), true, false) = (key.code, plain, repeat) {
+            if SEQUENCES.iter().any(|(first, _, _)| *first == c) {
+                self.pending = Some((c, Instant::now()));
+                return None;
+            }
+        }
+
+        single_key(key.code, key.modifiers).filter(|action| !repeat || action.repeatable())
+    }
+}
@@ -0,0 +1,162 @@
+//! Full-screen live log of one runner, opened with `l`
+
+use crossterm::event::KeyCode;
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+use super::super::follow::LogFollow;
+use super::super::runner::InstanceId;
+
+/// Lines loaded when the view opens
+pub const LIVE_LOG_BACKLOG: u32 = 500;
+
+/// Lines kept in the view; older lines are dropped as new ones arrive
+const MAX_LIVE_LOG_LINES: usize = 10_000;
+
+/// Lines `PageUp`/`PageDown` scroll
+const PAGE: usize = 20;
+
+pub struct LiveLog {
+    pub id: InstanceId,
+    pub lines: VecDeque<String>,
+    /// First line shown
+    pub scroll: usize,
+    /// Keep the newest lines in view as they arrive
+    pub follow: bool,
+    /// Rows available for text at the last draw
+    pub visible: Cell<usize>,
+    /// Search being typed after `/`
+    pub search_input: Option<String>,
+    /// Search highlighted in the view and stepped through with `n`/`N`
+    pub query: Option<String>,
+    source: LogFollow,
+}
+
+impl LiveLog {
+    pub fn new(id: InstanceId, source: LogFollow) -> Self {
+        Self {
+            id,
+            lines: VecDeque::new(),
+            scroll: 0,
+            follow: true,
+            visible: Cell::new(0),
+            search_input: None,
+            query: None,
+            source,
+        }
+    }
+
+    /// Add the lines that arrived since the last poll
+    pub fn poll(&mut self) {
+        let new = self.source.lines();
+        if new.is_empty() {
+            return;
+        }
+        self.lines.extend(new);
+        let excess = self.lines.len().saturating_sub(MAX_LIVE_LOG_LINES);
+        self.lines.drain(..excess);
+        // Keep the same lines in view while scrolled back
+        self.scroll = self.scroll.saturating_sub(excess);
+        if self.follow {
+            self.scroll = self.max_scroll();
+        }
+    }
+
+    pub fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(self.visible.get().max(1))
+    }
+
+    /// Whether `line` contains the active search (case-insensitive)
+    pub fn matches(&self, line: &str) -> bool {
+        self.query
+            .as_deref()
+            .is_some_and(|q| line.to_lowercase().contains(&q.to_lowercase()))
+    }
+
+    /// Scroll to the next (or previous) line matching the search, wrapping around.
+    /// Returns false when no line matches.
+    fn find(&mut self, forward: bool) -> bool {
+        let len = self.lines.len();
+        if len == 0 || self.query.is_none() {
+            return false;
+        }
+        let start = self.scroll.min(len - 1);
+        let found = (1..=len)
+            .map(|step| {
+                if forward {
+                    (start + step) % len
+                } else {
+                    (start + len - step) % len
+                }
+            })
+            .find(|&i| self.matches(&self.lines[i]));
+        let Some(i) = found else {
+            return false;
+        };
+        self.scroll = i.min(self.max_scroll());
+        self.follow = false;
+        true
+    }
+
+    /// Handle a key in the view; `KeyResult::Close` asks the caller to close it
+    pub fn handle_key(&mut self, code: KeyCode) -> KeyResult {
+        if let Some(input) = self.search_input.as_mut() {
+            match code {
+                KeyCode::Esc => self.search_input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Enter => {
+                    let query = self.search_input.take().unwrap_or_default();
+                    self.query = (!query.is_empty()).then_some(query);
+                    if self.query.is_some() && !self.find(true) {
+                        return KeyResult::Status("Pattern not found".to_string());
+                    }
+                }
+                _ => {}
+            }
+            return KeyResult::None;
+        }
+
+        let max = self.max_scroll();
+        self.scroll = self.scroll.min(max);
+        match code {
+            KeyCode::Esc | KeyCode::Char('q' | 'l') => return KeyResult::Close,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_to(self.scroll.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_to(self.scroll + 1),
+            KeyCode::PageUp => self.scroll_to(self.scroll.saturating_sub(PAGE)),
+            KeyCode::PageDown => self.scroll_to(self.scroll + PAGE),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll_to(0),
+            KeyCode::End | KeyCode::Char('G') => self.scroll_to(max),
+            KeyCode::Char('f') => {
+                self.follow = !self.follow;
+                if self.follow {
+                    self.scroll = max;
+                }
+                let state = if self.follow { "on" } else { "off" };
+                return KeyResult::Status(format!("Follow {state}"));
+            }
+            KeyCode::Char('/') => self.search_input = Some(String::new()),
+            KeyCode::Char(c @ ('n' | 'N')) if self.query.is_some() && !self.find(c == 'n') => {
+                return KeyResult::Status("Pattern not found".to_string());
+            }
+            _ => {}
+        }
+        KeyResult::None
+    }
+
+    /// Scroll to `line`; following resumes once the newest line is in view
+    fn scroll_to(&mut self, line: usize) {
+        let max = self.max_scroll();
+        self.scroll = line.min(max);
+        self.follow = self.scroll == max;
+    }
+}
+
+/// What the dashboard should do after a key in the live log
+pub enum KeyResult {
+    None,
+    Status(String),
+    Close,
+}
@@ -1,26 +1,76 @@
 mod charts;
+mod keys;
+mod livelog;
 mod ui;
+mod wizard;
 
 use anyhow::Result;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyModifiers},
+    cursor,
+    event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::collections::VecDeque;
-use std::io;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, Write as _};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Once};
 use std::time::{Duration, Instant};
 
-use super::config::Config;
-use super::github::{GitHubClient, Runner, RunnerScope, WorkflowRun};
-use super::metrics::{MetricsDb, ScopeMetrics};
-use super::runner::{self, RunnerInstance};
+use super::config::{Config, NotifyConfig, VerboseLogConfig};
+use super::docker::{self, DockerHealth, PruneReport};
+use super::dormant::{self, DormantScopes};
+use super::github::{
+    find_runner, first_error, ApiError, GitHubClient, Runner, RunnerScope, WorkflowJob, WorkflowRun,
+};
+use super::interrupt::{self, CleanupGuard};
+use super::journal::{self, JournalEntry};
+use super::metrics::{
+    fleet_failure_rate, host_table, summarize_hosts, DailyMetrics, DurationBucket, JobRecord,
+    MetricsDb, RunSpan, ScopeMetrics,
+};
+use super::notify::{self, FailedRun, RunTracker, RunnerTracker, ServiceTracker};
+use super::recording;
+use super::runner::{self, InstanceId, RunnerInstance};
+use super::state::StateDb;
+use super::status_file::{self, FleetStatus, ScopeStatus};
+use super::toolchain::{self, ToolVersion};
+use super::updates::{self, SelfUpdate};
+use super::watch::{DirWatcher, FileWatcher};
+use super::watchdog::Watchdog;
+use keys::{Action, KeyDispatcher};
+use livelog::LiveLog;
+use wizard::Wizard;
+
+/// Lines `PageUp`/`PageDown` scroll the verbose log panel
+const LOG_SCROLL_STEP: usize = 5;
+
+const MAX_STATUS_HISTORY: usize = 100;
+
+/// Consecutive failed refreshes before a scope is flagged in the Runners panel
+pub const ERROR_THRESHOLD: u32 = 2;
+
+/// Longest a failing scope goes without being polled
+const MAX_BACKOFF: Duration = Duration::from_mins(10);
 
-const MAX_LOG_LINES: usize = 100;
+/// Scopes fetched from GitHub at once during a refresh
+const MAX_CONCURRENT_SCOPES: usize = 8;
 
-const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+/// Failed jobs per run whose annotations are fetched, to bound the API calls
+const MAX_ANNOTATED_JOBS: usize = 5;
+
+/// How long quitting waits for background work (e.g. a janitor prune) to finish
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Panel {
@@ -29,35 +79,715 @@ pub enum Panel {
     Metrics,
 }
 
+/// Actions available for the selected item, opened with `m`
+pub struct ContextMenu {
+    pub title: String,
+    pub items: Vec<(&'static str, Action)>,
+    pub selected: usize,
+}
+
+/// Scrollable read-only text popup (runner logs, labels)
+pub struct TextView {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub scroll: usize,
+    /// Rows available for text at the last draw, so scrolling stops at the last page
+    pub visible: Cell<usize>,
+    /// Journal entries behind `lines`, for logs read from the systemd journal
+    pub journal: Option<JournalLog>,
+    /// Runner whose logs are shown, for log views (which can be exported)
+    pub logs_of: Option<InstanceId>,
+    /// Path being typed for an export, opened with `w` or `W`
+    pub save_prompt: Option<SavePrompt>,
+}
+
+/// Export prompt of a logs view
+pub struct SavePrompt {
+    pub path: String,
+    /// Export the runner's full log rather than the loaded lines
+    pub full: bool,
+}
+
+/// Structured log lines shown in a `TextView`, filtered by priority
+pub struct JournalLog {
+    pub entries: Vec<JournalEntry>,
+    /// Least severe priority shown (7 shows everything)
+    pub max_priority: u8,
+    /// Priority of each entry in the view's `lines`
+    pub priorities: Vec<u8>,
+}
+
+impl JournalLog {
+    /// The filter, e.g. `all` or `WARN+` (warnings and anything more severe)
+    pub fn filter_label(&self) -> String {
+        if self.max_priority >= 7 {
+            "all".to_string()
+        } else {
+            format!("{}+", journal::priority_label(self.max_priority))
+        }
+    }
+}
+
+/// Priority filters cycled with `f` in a journal logs view: all, info, warnings, errors
+const JOURNAL_FILTERS: [u8; 4] = [7, 6, 4, 3];
+
+impl TextView {
+    fn new(title: String, lines: Vec<String>) -> Self {
+        Self {
+            title,
+            lines,
+            scroll: 0,
+            visible: Cell::new(0),
+            journal: None,
+            logs_of: None,
+            save_prompt: None,
+        }
+    }
+
+    /// A logs view of journal entries, showing everything at first
+    fn journal(title: String, entries: Vec<JournalEntry>) -> Self {
+        let mut view = Self::new(title, Vec::new());
+        view.journal = Some(JournalLog {
+            entries,
+            max_priority: JOURNAL_FILTERS[0],
+            priorities: Vec::new(),
+        });
+        view.apply_journal_filter();
+        view
+    }
+
+    /// Rebuild `lines` from the journal entries that pass the priority filter
+    fn apply_journal_filter(&mut self) {
+        let Some(journal) = self.journal.as_mut() else {
+            return;
+        };
+        let shown: Vec<&JournalEntry> = journal
+            .entries
+            .iter()
+            .filter(|e| e.priority <= journal.max_priority)
+            .collect();
+        self.lines = shown.iter().map(|e| e.display()).collect();
+        journal.priorities = shown.iter().map(|e| e.priority).collect();
+    }
+
+    /// Step a journal view to the next priority filter
+    fn cycle_journal_filter(&mut self) -> Option<String> {
+        let journal = self.journal.as_mut()?;
+        let next = JOURNAL_FILTERS
+            .iter()
+            .position(|&p| p == journal.max_priority)
+            .map_or(0, |i| (i + 1) % JOURNAL_FILTERS.len());
+        journal.max_priority = JOURNAL_FILTERS[next];
+        let label = journal.filter_label();
+        self.apply_journal_filter();
+        self.scroll = usize::MAX;
+        Some(label)
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(self.visible.get().max(1))
+    }
+}
+
+/// Action waiting for a y/N answer in the status bar
+pub struct Confirm {
+    pub prompt: String,
+    pub action: ConfirmAction,
+}
+
+pub enum ConfirmAction {
+    RemoveRunner(InstanceId),
+    CancelRun(RunnerScope, u64),
+}
+
+/// What to do with a workflow run selected in the Workflows panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunAction {
+    Cancel,
+    Rerun,
+}
+
+/// GitHub data fetched by a background refresh
+pub struct RefreshData {
+    pub github_runners: Vec<(RunnerScope, Vec<Runner>)>,
+    pub workflow_runs: Vec<(RunnerScope, Vec<WorkflowRun>)>,
+    /// Workflow runs waiting to start, per repository
+    pub queued_runs: Vec<(RunnerScope, u64)>,
+    /// API failures by scope (the last failure wins if several calls failed)
+    pub errors: HashMap<RunnerScope, anyhow::Error>,
+    /// Installed runner versions of the probed instances
+    pub versions: Vec<(RunnerScope, String)>,
+    /// Self-updates found in the instances' `_diag` logs, by registered runner name
+    pub self_updates: Vec<(RunnerScope, String, Vec<SelfUpdate>)>,
+    pub latest_version: Option<String>,
+    /// Jobs of newly completed runs, by run ID
+    pub jobs: Vec<(RunnerScope, u64, Vec<WorkflowJob>)>,
+    /// First error line of the failed ones among those jobs, by job ID
+    pub job_errors: Vec<(RunnerScope, Vec<(u64, String)>)>,
+    /// Repositories checked for being archived or disabled
+    pub dormancy: Vec<(RunnerScope, dormant::Check)>,
+}
+
+/// A scope whose API calls keep failing
+pub struct ScopeError {
+    /// Consecutive refreshes that failed
+    pub failures: u32,
+    pub since: chrono::DateTime<chrono::Local>,
+    pub message: String,
+    /// HTTP details, when GitHub answered at all
+    pub api: Option<ApiError>,
+    /// The scope is skipped by background refreshes until then
+    pub retry_at: Instant,
+}
+
+impl ScopeError {
+    /// GitHub answered that the scope is gone or the token can't reach it: retrying
+    /// won't help until the user acts
+    pub fn unreachable(&self) -> bool {
+        self.api
+            .as_ref()
+            .is_some_and(|api| matches!(api.status, 401 | 403 | 404) && !api.is_rate_limited())
+    }
+
+    /// Poll interval after `failures` consecutive failures: doubles from the normal
+    /// refresh `interval` up to `MAX_BACKOFF`
+    fn backoff(failures: u32, interval: Duration) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1).min(16));
+        interval.saturating_mul(factor).min(MAX_BACKOFF)
+    }
+
+    fn suggestion(&self, web_url: &str) -> String {
+        match &self.api {
+            Some(api) => api.suggestion(web_url),
+            None => "Could not reach GitHub. Check network connectivity, DNS and proxy settings."
+                .to_string(),
+        }
+    }
+
+    /// Lines for the error detail view; `web_url` is the GitHub instance's web UI
+    fn describe(&self, scope: &RunnerScope, web_url: &str) -> Vec<String> {
+        let mut lines = vec![
+            format!("Scope: {}", scope.to_display()),
+            format!(
+                "Failing since {} ({} consecutive refreshes)",
+                self.since.format("%H:%M:%S"),
+                self.failures
+            ),
+            format!("Error: {}", self.message),
+        ];
+        if let Some(api) = &self.api {
+            lines.push(format!("HTTP status: {}", api.status));
+            if let Some(remaining) = api.rate_limit_remaining {
+                let reset = api
+                    .rate_limit_reset
+                    .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                    .map(|t| {
+                        format!(
+                            ", resets at {}",
+                            t.with_timezone(&chrono::Local).format("%H:%M:%S")
+                        )
+                    })
+                    .unwrap_or_default();
+                lines.push(format!("Rate limit: {remaining} remaining{reset}"));
+            }
+        }
+        lines.push(format!("Suggested fix: {}", self.suggestion(web_url)));
+        if self.unreachable() {
+            lines.push(
+                "If the repository was deleted or access is gone for good, remove the runner \
+                 with d d."
+                    .to_string(),
+            );
+        }
+        let retry = self.retry_at.saturating_duration_since(Instant::now());
+        if retry > Duration::ZERO {
+            lines.push(format!(
+                "Next retry in {}s (press r to retry now)",
+                retry.as_secs()
+            ));
+        }
+        lines
+    }
+}
+
+/// GitHub data of one scope, fetched by `fetch_scope`
+struct ScopeData {
+    runners: Vec<Runner>,
+    /// None for organizations, which have no workflow runs
+    workflow_runs: Option<Vec<WorkflowRun>>,
+    /// Queued workflow runs (None for organizations, or when the call failed)
+    queued_runs: Option<u64>,
+    jobs: Vec<(u64, Vec<WorkflowJob>)>,
+    job_errors: Vec<(u64, String)>,
+    /// Result of checking whether the repository is dormant, if it was due
+    dormancy: Option<dormant::Check>,
+    error: Option<anyhow::Error>,
+}
+
+/// Fetch the runners of `scope` and, for a repository, its recent and queued workflow
+/// runs and the jobs of completed runs not in `jobs_recorded`. The repository is checked
+/// for being dormant first if `recheck`; the runs of a dormant one (`paused` until a
+/// check says otherwise) aren't fetched.
+async fn fetch_scope(
+    client: &GitHubClient,
+    scope: &RunnerScope,
+    jobs_recorded: &HashSet<u64>,
+    recheck: bool,
+    paused: bool,
+) -> ScopeData {
+    let mut data = ScopeData {
+        runners: Vec::new(),
+        workflow_runs: None,
+        queued_runs: None,
+        jobs: Vec::new(),
+        job_errors: Vec::new(),
+        dormancy: None,
+        error: None,
+    };
+    match client.list_runners(scope).await {
+        Ok(list) => data.runners = list.runners,
+        Err(e) => data.error = Some(e),
+    }
+
+    let mut paused = paused;
+    if recheck {
+        let check = dormant::check(client, scope).await;
+        match check {
+            dormant::Check::Active => paused = false,
+            dormant::Check::Dormant(_) => paused = true,
+            dormant::Check::Unknown => {}
+        }
+        data.dormancy = Some(check);
+    }
+    if paused {
+        return data;
+    }
+
+    // Only fetch workflow runs for repositories, not organizations
+    if let RunnerScope::Repository { owner, repo } = scope {
+        match client.list_workflow_runs(owner, repo, 5).await {
+            Ok(list) => {
+                for run in &list.workflow_runs {
+                    if run.status != "completed" || jobs_recorded.contains(&run.id) {
+                        continue;
+                    }
+                    match client.list_jobs_for_run(owner, repo, run.id).await {
+                        Ok(list) => {
+                            data.job_errors
+                                .extend(job_errors(client, owner, repo, &list.jobs).await);
+                            data.jobs.push((run.id, list.jobs));
+                        }
+                        Err(e) => {
+                            data.error = Some(e);
+                            break;
+                        }
+                    }
+                }
+                data.workflow_runs = Some(list.workflow_runs);
+            }
+            Err(e) => {
+                data.workflow_runs = Some(Vec::new());
+                data.error = Some(e);
+            }
+        }
+        match client.list_queued_workflow_runs(owner, repo).await {
+            Ok(list) => data.queued_runs = Some(list.total_count),
+            Err(e) => data.error = Some(e),
+        }
+    }
+    data
+}
+
+/// The first error line of the failed `jobs` (job ID, line), see `first_error`
+async fn job_errors(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    jobs: &[WorkflowJob],
+) -> Vec<(u64, String)> {
+    let mut errors = Vec::new();
+    for job in jobs
+        .iter()
+        .filter(|job| job.failed())
+        .take(MAX_ANNOTATED_JOBS)
+    {
+        // Without access to annotations (fine-grained tokens need Checks: read) the
+        // failed step still narrows it down
+        let annotations = client
+            .list_annotations(owner, repo, job.id)
+            .await
+            .unwrap_or_default();
+        if let Some(error) = first_error(job, &annotations) {
+            errors.push((job.id, error));
+        }
+    }
+    errors
+}
+
+/// Fetch runner status and recent workflow runs for each configured scope, the jobs of
+/// completed runs not in `jobs_recorded`, the installed versions of the instances in
+/// `probe`, and the self-updates of the `local` instances (scope, runner name, directory).
+/// Repositories in `recheck` are checked for being dormant; runs aren't fetched for
+/// dormant ones, starting with those in `paused`. Up to `MAX_CONCURRENT_SCOPES` scopes
+/// are fetched at once; results are merged as they arrive and returned in the order of
+/// `scopes`.
+async fn fetch_github_data(
+    client: GitHubClient,
+    scopes: Vec<RunnerScope>,
+    probe: Vec<(RunnerScope, PathBuf)>,
+    local: Vec<(RunnerScope, String, PathBuf)>,
+    jobs_recorded: HashSet<u64>,
+    recheck: HashSet<RunnerScope>,
+    paused: HashSet<RunnerScope>,
+) -> RefreshData {
+    let jobs_recorded = Arc::new(jobs_recorded);
+    let permits = Arc::new(tokio::sync::Semaphore::new(MAX_CONCURRENT_SCOPES));
+    let rate_limited = Arc::new(AtomicBool::new(false));
+    let mut tasks = tokio::task::JoinSet::new();
+    for (index, scope) in scopes.into_iter().enumerate() {
+        let recheck = recheck.contains(&scope);
+        let paused = paused.contains(&scope);
+        let client = client.clone();
+        let jobs_recorded = Arc::clone(&jobs_recorded);
+        let permits = Arc::clone(&permits);
+        let rate_limited = Arc::clone(&rate_limited);
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await.ok()?;
+            // Don't keep calling GitHub once it has rate limited us; the remaining
+            // scopes keep their last data until the pause ends
+            if rate_limited.load(Ordering::SeqCst) {
+                return None;
+            }
+            let data = fetch_scope(&client, &scope, &jobs_recorded, recheck, paused).await;
+            if data.error.as_ref().is_some_and(is_rate_limited) {
+                rate_limited.store(true, Ordering::SeqCst);
+            }
+            Some((index, scope, data))
+        });
+    }
+
+    let mut fetched = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        if let Ok(Some(scope_data)) = result {
+            fetched.push(scope_data);
+        }
+    }
+    fetched.sort_by_key(|(index, _, _)| *index);
+
+    let mut github_runners = Vec::new();
+    let mut workflow_runs = Vec::new();
+    let mut queued_runs = Vec::new();
+    let mut jobs = Vec::new();
+    let mut job_errors = Vec::new();
+    let mut dormancy = Vec::new();
+    let mut errors = HashMap::new();
+    for (_, scope, data) in fetched {
+        if let Some(check) = data.dormancy {
+            dormancy.push((scope.clone(), check));
+        }
+        github_runners.push((scope.clone(), data.runners));
+        if let Some(runs) = data.workflow_runs {
+            workflow_runs.push((scope.clone(), runs));
+        }
+        if let Some(count) = data.queued_runs {
+            queued_runs.push((scope.clone(), count));
+        }
+        jobs.extend(
+            data.jobs
+                .into_iter()
+                .map(|(run_id, run_jobs)| (scope.clone(), run_id, run_jobs)),
+        );
+        if !data.job_errors.is_empty() {
+            job_errors.push((scope.clone(), data.job_errors));
+        }
+        if let Some(e) = data.error {
+            errors.insert(scope, e);
+        }
+    }
+
+    let latest_version = updates::latest_version(&client).await.ok();
+    let (versions, self_updates) = tokio::task::spawn_blocking(move || {
+        let versions = probe
+            .into_iter()
+            .filter_map(|(scope, dir)| Some((scope, runner::installed_version(&dir)?)))
+            .collect();
+        let self_updates = local
+            .into_iter()
+            .map(|(scope, name, dir)| (scope, name, updates::read_self_updates(&dir)))
+            .filter(|(_, _, found)| !found.is_empty())
+            .collect();
+        (versions, self_updates)
+    })
+    .await
+    .unwrap_or_default();
+
+    RefreshData {
+        github_runners,
+        workflow_runs,
+        queued_runs,
+        errors,
+        versions,
+        self_updates,
+        latest_version,
+        jobs,
+        job_errors,
+        dormancy,
+    }
+}
+
+fn is_rate_limited(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<ApiError>()
+        .is_some_and(ApiError::is_rate_limited)
+}
+
+/// Days of jobs aggregated by the failures-by-runner view
+pub const BLAME_DAYS: i64 = 7;
+
+/// Hours of history shown by the activity timeline
+pub const TIMELINE_HOURS: i64 = 24;
+
+/// Workflow runs of the last `TIMELINE_HOURS` on a time axis, opened with `T`
+pub struct Timeline {
+    pub spans: Vec<RunSpan>,
+    /// Right edge of the time axis (when the view was opened)
+    pub to: chrono::DateTime<chrono::Utc>,
+}
+
+/// Days of history behind the Metrics panel
+pub const METRICS_DAYS: i32 = 7;
+
+/// Failed jobs listed in the metrics drill-down
+pub const METRICS_DETAIL_FAILURES: usize = 10;
+
+/// Slices of the drill-down's uptime timeline: 6 hours each over `METRICS_DAYS`
+pub const UPTIME_TIMELINE_SLICES: usize = 28;
+
+/// Drill-down for one scope of the Metrics panel, opened with Enter
+pub struct MetricsDetail {
+    pub scope: RunnerScope,
+    pub metrics: ScopeMetrics,
+    /// Each of the last `METRICS_DAYS` days, oldest first
+    pub daily: Vec<DailyMetrics>,
+    pub durations: Vec<DurationBucket>,
+    /// Runner uptime per slice of the period, in percent
+    pub uptime: Vec<Option<f64>>,
+    /// Last failed jobs, newest first
+    pub failures: Vec<JobRecord>,
+}
+
+impl MetricsDetail {
+    fn load(db: &MetricsDb, scope: RunnerScope, metrics: ScopeMetrics) -> Result<Self> {
+        Ok(Self {
+            daily: db.get_daily_metrics(&scope, METRICS_DAYS)?,
+            durations: db.get_duration_distribution(&scope, METRICS_DAYS)?,
+            uptime: db.get_uptime_history(&scope, METRICS_DAYS, UPTIME_TIMELINE_SLICES)?,
+            failures: db.get_scope_failures(&scope, METRICS_DETAIL_FAILURES)?,
+            scope,
+            metrics,
+        })
+    }
+}
+
+/// Detail view for a workflow run, opened with Enter on the Workflows panel
+pub struct RunDetail {
+    pub scope: RunnerScope,
+    pub run: WorkflowRun,
+    /// Its jobs as recorded when the run completed, in the order they started
+    pub jobs: Vec<JobRecord>,
+}
+
+/// Detail view for a single runner instance, opened with Enter on the Runners panel
+pub struct RunnerDetail {
+    pub id: InstanceId,
+    /// Name the instance is registered under on GitHub
+    pub runner_name: String,
+    pub toolchain: Vec<ToolVersion>,
+    pub docker: DockerHealth,
+    /// Missing or damaged credential files (see `runner::credential_problem`)
+    pub credentials: Option<String>,
+    pub note: Option<String>,
+    pub tags: Vec<String>,
+    /// Last jobs the runner executed, newest first
+    pub jobs: Vec<JobRecord>,
+}
+
+/// Jobs listed in the runner detail view
+pub const RUNNER_JOB_HISTORY: usize = 10;
+
+/// Verbose log panel: the newest `capacity` lines, optionally appended to a file
+pub struct LogPanel {
+    pub messages: VecDeque<String>,
+    /// Lines scrolled back from the newest
+    pub scroll: usize,
+    /// Keep the newest line in view as lines arrive
+    pub follow: bool,
+    pub capacity: usize,
+    file: Option<std::fs::File>,
+}
+
+impl LogPanel {
+    fn new(settings: &VerboseLogConfig) -> Self {
+        let file = settings
+            .file
+            .as_deref()
+            .and_then(|path| match runner::open_log_file(path) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    eprintln!("Warning: {e:#}");
+                    None
+                }
+            });
+        Self {
+            messages: VecDeque::new(),
+            scroll: 0,
+            follow: true,
+            capacity: settings.max_lines.max(1),
+            file,
+        }
+    }
+
+    /// Add a line, dropping the oldest beyond `capacity`. A failed write to the log
+    /// file is returned once, and the file is not written again.
+    fn push(&mut self, msg: String) -> io::Result<()> {
+        let written = match self.file.as_mut() {
+            Some(file) => {
+                let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+                writeln!(file, "{time} {msg}")
+            }
+            None => Ok(()),
+        };
+        if written.is_err() {
+            self.file = None;
+        }
+        self.messages.push_back(msg);
+        // O(1) with VecDeque
+        if self.messages.len() > self.capacity {
+            self.messages.pop_front();
+        }
+        // Keep the same lines in view while scrolled back
+        if !self.follow {
+            self.scroll = (self.scroll + 1).min(self.messages.len().saturating_sub(1));
+        }
+        written
+    }
+
+    /// Keep at most `capacity` lines, dropping the oldest
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        let excess = self.messages.len().saturating_sub(self.capacity);
+        self.messages.drain(..excess);
+        self.scroll = self.scroll.min(self.messages.len().saturating_sub(1));
+    }
+
+    fn clear(&mut self) {
+        self.messages.clear();
+        self.scroll = 0;
+        self.follow = true;
+    }
+
+    /// Scrolling back pauses following
+    fn scroll_up(&mut self) {
+        let max_scroll = self.messages.len().saturating_sub(1);
+        self.scroll = (self.scroll + LOG_SCROLL_STEP).min(max_scroll);
+        self.follow = false;
+    }
+
+    /// Scrolling back down to the newest line follows again
+    fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_sub(LOG_SCROLL_STEP);
+        if self.scroll == 0 {
+            self.follow = true;
+        }
+    }
+}
+
 pub struct App {
     pub config: Config,
+    /// Runner names registered by `add` start with the hostname
+    pub hostname: String,
     pub client: GitHubClient,
     pub instances: Vec<RunnerInstance>,
     pub github_runners: Vec<(RunnerScope, Vec<Runner>)>,
     pub workflow_runs: Vec<(RunnerScope, Vec<WorkflowRun>)>,
+    /// Workflow runs waiting to start, per repository (Queued column)
+    pub queued_runs: HashMap<RunnerScope, u64>,
     pub scope_metrics: Vec<(RunnerScope, ScopeMetrics)>,
     pub selected_runner: usize,
     pub selected_workflow: usize,
     pub selected_metric: usize,
     pub active_panel: Panel,
-    pub last_refresh: Instant,
+    /// When the last refresh started; None until the first one, and to refresh on the
+    /// next pass
+    pub last_refresh: Option<Instant>,
     pub status_message: Option<(String, Instant)>,
+    pub status_history: VecDeque<(chrono::DateTime<chrono::Local>, String)>,
+    /// Scopes whose last refresh failed
+    pub scope_errors: HashMap<RunnerScope, ScopeError>,
+    /// GitHub rate limited the token: no API calls for any scope until then
+    pub paused_until: Option<Instant>,
+    /// Installed runner version per instance
+    pub runner_versions: HashMap<RunnerScope, String>,
+    /// Latest runner release (see `updates::latest_version`)
+    pub latest_version: Option<String>,
     pub loading: bool,
     pub should_quit: bool,
     pub error: Option<String>,
     pub show_logs: bool,
-    pub log_messages: VecDeque<String>,
+    pub logs: LogPanel,
     pub log_receiver: Option<Receiver<String>>,
-    pub log_scroll: usize,
     pub metrics_db: Option<MetricsDb>,
+    /// Notes and tags (see `runner-mgr note` / `runner-mgr tag`)
+    pub state_db: Option<StateDb>,
+    /// Only show instances with this tag
+    pub tag_filter: Option<String>,
+    /// Restarts runners that run but are offline on GitHub, while toggled on (`W`)
+    pub watchdog: Option<Watchdog>,
+    /// Repositories found archived or disabled, whose runs aren't polled
+    pub dormant: DormantScopes,
+    pub detail: Option<RunnerDetail>,
+    pub metrics_detail: Option<MetricsDetail>,
+    pub run_detail: Option<RunDetail>,
+    pub timeline: Option<Timeline>,
+    pub janitor_task: Option<tokio::task::JoinHandle<Result<Option<PruneReport>>>>,
+    /// Completed runs already checked for failure notifications
+    pub run_tracker: RunTracker,
+    /// Runner statuses at the last refresh, for offline notifications
+    pub runner_tracker: RunnerTracker,
+    /// Local service statuses at the last refresh, for crash notifications
+    pub service_tracker: ServiceTracker,
+    pub notify_tasks: Vec<tokio::task::JoinHandle<Vec<String>>>,
+    pub refresh_task: Option<tokio::task::JoinHandle<RefreshData>>,
+    /// Runs whose jobs are in the metrics DB, so they aren't fetched again
+    pub jobs_recorded: HashSet<u64>,
+    pub keys: KeyDispatcher,
+    pub menu: Option<ContextMenu>,
+    pub text_view: Option<TextView>,
+    /// Full-screen log of one runner, following new lines (`l`)
+    pub live_log: Option<LiveLog>,
+    pub confirm: Option<Confirm>,
+    /// Runner removal confirmed by the user, performed by the event loop
+    pub pending_remove: Option<InstanceId>,
+    /// Workflow run cancel/re-run requested by the user, performed by the event loop
+    pub pending_run: Option<(RunnerScope, u64, RunAction)>,
+    /// The config file, reloaded when it changes (see `poll_config`)
+    pub config_watcher: FileWatcher,
+    /// The instances directory, re-listed when runners are added or removed outside
+    /// the dashboard (see `poll_instances`)
+    pub instances_watcher: DirWatcher,
 }
 
 impl App {
     pub fn new(config: Config) -> Self {
-        let client = GitHubClient::new(&config.github_pat);
+        let client = GitHubClient::for_config(&config);
 
-        // Try to open metrics DB, log error but don't fail
-        let metrics_db = match MetricsDb::open() {
+        // Try to open metrics DB, log error but don't fail. A replayed recording keeps
+        // its metrics in memory rather than mixing them into this host's history.
+        let opened = if recording::replaying() {
+            MetricsDb::open_in_memory()
+        } else {
+            MetricsDb::open()
+        };
+        let metrics_db = match opened {
             Ok(db) => Some(db),
             Err(e) => {
                 eprintln!("Warning: Failed to open metrics database: {e}");
@@ -65,235 +795,1134 @@ impl App {
             }
         };
 
+        let logs = LogPanel::new(&config.verbose_log.clone().unwrap_or_default());
+
+        let jobs_recorded = metrics_db
+            .as_ref()
+            .and_then(|db| db.recorded_job_runs().ok())
+            .unwrap_or_default();
+        let instances_watcher = DirWatcher::new(config.instances_dir());
+
         Self {
             config,
+            hostname: hostname::get().map_or_else(
+                |_| "runner".to_string(),
+                |h| h.to_string_lossy().to_string(),
+            ),
             client,
             instances: Vec::new(),
             github_runners: Vec::new(),
             workflow_runs: Vec::new(),
+            queued_runs: HashMap::new(),
             scope_metrics: Vec::new(),
             selected_runner: 0,
             selected_workflow: 0,
             selected_metric: 0,
             active_panel: Panel::Runners,
-            last_refresh: Instant::now().checked_sub(REFRESH_INTERVAL).unwrap(), // force initial refresh
+            last_refresh: None,
             status_message: None,
+            status_history: VecDeque::new(),
+            scope_errors: HashMap::new(),
+            paused_until: None,
+            runner_versions: HashMap::new(),
+            latest_version: None,
             loading: false,
             should_quit: false,
             error: None,
             show_logs: false,
-            log_messages: VecDeque::new(),
+            logs,
             log_receiver: None,
-            log_scroll: 0,
             metrics_db,
+            state_db: StateDb::open().ok(),
+            tag_filter: None,
+            watchdog: None,
+            dormant: DormantScopes::default(),
+            detail: None,
+            metrics_detail: None,
+            run_detail: None,
+            timeline: None,
+            janitor_task: None,
+            run_tracker: RunTracker::default(),
+            runner_tracker: RunnerTracker::default(),
+            service_tracker: ServiceTracker::default(),
+            notify_tasks: Vec::new(),
+            refresh_task: None,
+            jobs_recorded,
+            keys: KeyDispatcher::default(),
+            menu: None,
+            text_view: None,
+            live_log: None,
+            confirm: None,
+            pending_remove: None,
+            pending_run: None,
+            config_watcher: FileWatcher::new(Config::config_file()),
+            instances_watcher,
+        }
+    }
+
+    /// Drain any pending log messages from the receiver
+    fn drain_logs(&mut self) {
+        let messages: Vec<String> = match self.log_receiver {
+            Some(ref receiver) => receiver.try_iter().collect(),
+            None => return,
+        };
+        for msg in messages {
+            self.push_log(msg);
+        }
+    }
+
+    /// Start a background refresh of local instances and GitHub data.
+    /// Results are applied (and recorded to the metrics DB) by `poll_refresh`.
+    pub fn start_refresh(&mut self) {
+        self.last_refresh = Some(Instant::now());
+        if self.refresh_task.is_some() {
+            return;
+        }
+        // Refresh local instances
+        self.load_instances();
+        if self.rate_limit_pause().is_some() {
+            return;
+        }
+        self.loading = true;
+        self.error = None;
+        let now = Instant::now();
+        // A scope with several instances is fetched once
+        let mut scopes: Vec<RunnerScope> = Vec::new();
+        for instance in &self.instances {
+            let due = self
+                .scope_errors
+                .get(&instance.scope)
+                .is_none_or(|err| err.retry_at <= now);
+            if due && !scopes.contains(&instance.scope) {
+                scopes.push(instance.scope.clone());
+            }
+        }
+        // Versions only change when a runner updates, so only outdated or unknown
+        // ones are probed again
+        let probe = self
+            .instances
+            .iter()
+            .filter(|i| !self.runner_versions.contains_key(&i.scope) || self.update_available(i))
+            .map(|i| (i.scope.clone(), i.dir.clone()))
+            .collect();
+        let local = self
+            .instances
+            .iter()
+            .map(|i| (i.scope.clone(), self.runner_name(i), i.dir.clone()))
+            .collect();
+        let recheck = scopes
+            .iter()
+            .filter(|scope| self.dormant.due(scope, now))
+            .cloned()
+            .collect();
+        let paused = scopes
+            .iter()
+            .filter(|scope| self.dormant.reason(scope).is_some())
+            .cloned()
+            .collect();
+        let client = self.client.clone();
+        self.refresh_task = Some(tokio::spawn(fetch_github_data(
+            client,
+            scopes,
+            probe,
+            local,
+            self.jobs_recorded.clone(),
+            recheck,
+            paused,
+        )));
+    }
+
+    /// Apply a finished background refresh
+    async fn poll_refresh(&mut self) {
+        if !self
+            .refresh_task
+            .as_ref()
+            .is_some_and(tokio::task::JoinHandle::is_finished)
+        {
+            return;
+        }
+        let Some(task) = self.refresh_task.take() else {
+            return;
+        };
+        match task.await {
+            Ok(data) => self.apply_refresh(data),
+            Err(e) => {
+                self.loading = false;
+                self.set_status(format!("Refresh failed: {e}"));
+            }
+        }
+    }
+
+    fn apply_refresh(&mut self, data: RefreshData) {
+        // Record snapshots to the metrics DB
+        if let Some(ref db) = self.metrics_db {
+            for (scope, runners) in &data.github_runners {
+                let _ = db.record_runner_snapshots(scope, runners);
+            }
+            for (scope, runs) in &data.workflow_runs {
+                let _ = db.record_workflow_runs(scope, runs);
+            }
+            for (scope, queued) in &data.queued_runs {
+                let _ = db.record_queue_depth(scope, *queued);
+            }
+            for (scope, run_id, jobs) in &data.jobs {
+                if db.record_jobs(scope, jobs).is_ok() {
+                    self.jobs_recorded.insert(*run_id);
+                }
+            }
+            for (scope, errors) in &data.job_errors {
+                let _ = db.record_job_errors(scope, errors);
+            }
+            for (scope, runner_name, updates) in &data.self_updates {
+                let _ = db.record_self_updates(scope, runner_name, updates);
+            }
+        }
+
+        // Compute scope metrics from DB
+        let mut scope_metrics = Vec::new();
+        if let Some(ref db) = self.metrics_db {
+            for (scope, _) in &data.github_runners {
+                match db.get_scope_metrics(scope, METRICS_DAYS) {
+                    Ok(mut metrics) => {
+                        if let Some(slo) = self.config.slo_for(scope) {
+                            metrics.slo = db.get_slo_status(scope, slo).unwrap_or_default();
+                        }
+                        scope_metrics.push((scope.clone(), metrics));
+                    }
+                    Err(_) => {
+                        // Use default metrics on error
+                        scope_metrics.push((scope.clone(), ScopeMetrics::default()));
+                    }
+                }
+            }
+        }
+
+        self.start_notify(&data);
+        self.start_status_notify(&data);
+        self.runner_versions.extend(data.versions.iter().cloned());
+        if data.latest_version.is_some() {
+            self.latest_version = data.latest_version.clone();
+        }
+
+        let polled: Vec<RunnerScope> = data
+            .github_runners
+            .iter()
+            .map(|(scope, _)| scope.clone())
+            .collect();
+        self.track_errors(&polled, data.errors);
+
+        // Scopes skipped while backing off keep their last known data
+        let mut github_runners = data.github_runners;
+        github_runners.extend(
+            self.github_runners
+                .drain(..)
+                .filter(|(scope, _)| !polled.contains(scope)),
+        );
+        let mut workflow_runs = data.workflow_runs;
+        workflow_runs.extend(
+            self.workflow_runs
+                .drain(..)
+                .filter(|(scope, _)| !polled.contains(scope)),
+        );
+
+        self.queued_runs.extend(data.queued_runs);
+        let now = Instant::now();
+        for (scope, check) in data.dormancy {
+            if let Some(reason) = self.dormant.record(&scope, check, now) {
+                self.set_status(dormant::suggestion(&scope, reason));
+            }
+        }
+        for (scope, _) in self.dormant.dormant() {
+            self.queued_runs.remove(scope);
+        }
+
+        self.github_runners = github_runners;
+        self.workflow_runs = workflow_runs;
+        self.scope_metrics = scope_metrics;
+        self.loading = false;
+
+        self.write_status_file();
+        self.run_watchdog();
+        self.start_janitor();
+    }
+
+    /// Rewrite the configured status file. Scopes whose last poll failed are reported
+    /// as unknown rather than with stale runner data.
+    fn write_status_file(&mut self) {
+        let Some(settings) = &self.config.status_file else {
+            return;
+        };
+        if recording::replaying() {
+            return;
+        }
+        let status = FleetStatus {
+            scopes: self
+                .github_runners
+                .iter()
+                .map(|(scope, runners)| {
+                    let fetched = !self.scope_errors.contains_key(scope);
+                    ScopeStatus::new(scope, fetched.then_some(runners.as_slice()))
+                })
+                .collect(),
+        };
+        if let Err(e) = status_file::write(settings, &status) {
+            self.set_status(format!("Status file: {e:#}"));
+        }
+    }
+
+    /// Update per-scope failure counts for the scopes polled by a refresh. A scope's
+    /// first failure is reported in the status bar; after `ERROR_THRESHOLD` failures it
+    /// is flagged in the Runners panel until a refresh succeeds. Failing scopes are
+    /// polled less and less often (see `ScopeError::backoff`).
+    fn track_errors(
+        &mut self,
+        polled: &[RunnerScope],
+        errors: HashMap<RunnerScope, anyhow::Error>,
+    ) {
+        let interval = self.config.refresh_interval();
+        let recovered: Vec<RunnerScope> = self
+            .scope_errors
+            .keys()
+            .filter(|scope| polled.contains(scope) && !errors.contains_key(*scope))
+            .cloned()
+            .collect();
+        for scope in recovered {
+            if let Some(err) = self.scope_errors.remove(&scope) {
+                if err.failures >= ERROR_THRESHOLD {
+                    self.set_status(format!("{scope}: GitHub API calls recovered"));
+                }
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let pause = errors
+            .values()
+            .filter_map(|e| e.downcast_ref::<ApiError>()?.pause(now))
+            .max();
+        if let Some(pause) = pause {
+            self.paused_until = Some(Instant::now() + pause);
+            self.set_status(format!(
+                "GitHub rate limit hit, pausing API calls for {}s",
+                pause.as_secs()
+            ));
+        }
+
+        for (scope, e) in errors {
+            let entry = self
+                .scope_errors
+                .entry(scope.clone())
+                .or_insert_with(|| ScopeError {
+                    failures: 0,
+                    since: chrono::Local::now(),
+                    message: String::new(),
+                    api: None,
+                    retry_at: Instant::now(),
+                });
+            entry.failures += 1;
+            entry.message = format!("{e:#}");
+            entry.api = e.downcast_ref::<ApiError>().cloned();
+            entry.retry_at = Instant::now() + ScopeError::backoff(entry.failures, interval);
+            let failures = entry.failures;
+            let unreachable = entry.unreachable();
+            if pause.is_some() {
+                // Already reported as a rate limit
+            } else if failures == 1 {
+                self.set_status(format!("Error refreshing {scope}: {e:#}"));
+            } else if failures == ERROR_THRESHOLD && unreachable {
+                self.set_status(format!(
+                    "{scope} is unreachable, press e for details or d d to remove it"
+                ));
+            } else if failures == ERROR_THRESHOLD {
+                self.set_status(format!(
+                    "{scope}: GitHub API calls failing, press e for details"
+                ));
+            }
+        }
+    }
+
+    /// Time left until API calls resume after a rate limit
+    pub fn rate_limit_pause(&self) -> Option<Duration> {
+        self.paused_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .filter(|left| !left.is_zero())
+    }
+
+    /// Open the error detail view for the selected scope, or for every failing scope
+    fn show_errors_view(&mut self) {
+        let mut failing: Vec<(&RunnerScope, &ScopeError)> = match self.selected_scope() {
+            Some(scope) if self.scope_errors.contains_key(scope) => {
+                vec![(scope, &self.scope_errors[scope])]
+            }
+            _ => self.scope_errors.iter().collect(),
+        };
+        if failing.is_empty() {
+            self.set_status("No API errors".to_string());
+            return;
+        }
+        failing.sort_by_key(|(scope, _)| scope.to_display());
+        let web_url = self.config.web_url();
+        let mut lines = Vec::new();
+        for (scope, err) in failing {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.extend(err.describe(scope, &web_url));
+        }
+        self.text_view = Some(TextView::new("API errors".to_string(), lines));
+    }
+
+    /// Stop background work before the terminal is restored: cancel an in-flight
+    /// refresh (a result that already completed is still recorded) and give a
+    /// running janitor prune time to finish instead of abandoning it mid-way.
+    async fn shutdown(&mut self) {
+        if let Some(task) = self.refresh_task.take() {
+            if task.is_finished() {
+                if let Ok(data) = task.await {
+                    self.apply_refresh(data);
+                }
+            } else {
+                task.abort();
+            }
+        }
+        // A refresh that just finished may have started the janitor
+        if let Some(task) = self.janitor_task.take() {
+            let _ = tokio::time::timeout(SHUTDOWN_TIMEOUT, task).await;
+        }
+        for task in self.notify_tasks.drain(..) {
+            let _ = tokio::time::timeout(SHUTDOWN_TIMEOUT, task).await;
+        }
+    }
+
+    /// Check newly failed runs in the background and notify about those that failed
+    /// on self-hosted runners, if notifications are configured
+    fn start_notify(&mut self, data: &RefreshData) {
+        if recording::replaying() {
+            return;
+        }
+        let Some(config) = self
+            .config
+            .notifications
+            .clone()
+            .filter(|n| notify::wants(n, notify::Event::RunFailed))
+        else {
+            return;
+        };
+        let mut failures = Vec::new();
+        for (scope, runs) in &data.workflow_runs {
+            let runner_names: Vec<String> = data
+                .github_runners
+                .iter()
+                .filter(|(s, _)| s == scope)
+                .flat_map(|(_, runners)| runners.iter().map(|r| r.name.clone()))
+                .collect();
+            for run in self.run_tracker.new_failures(scope, runs) {
+                failures.push(FailedRun {
+                    scope: scope.clone(),
+                    run,
+                    runner_names: runner_names.clone(),
+                });
+            }
+        }
+        if failures.is_empty() {
+            return;
+        }
+        let client = self.client.clone();
+        self.notify_tasks.push(tokio::spawn(notify::notify_failures(
+            client, config, failures,
+        )));
+    }
+
+    /// Notify in the background about runners that went offline and local services that
+    /// stopped on their own since the last refresh
+    fn start_status_notify(&mut self, data: &RefreshData) {
+        let mut notifications = Vec::new();
+        for (scope, runners) in &data.github_runners {
+            for runner in self.runner_tracker.went_offline(scope, runners) {
+                notifications.push(notify::offline_notification(scope, &runner));
+            }
+        }
+        for instance in self.service_tracker.crashed(&self.instances) {
+            notifications.push(notify::crash_notification(&instance));
+        }
+        if recording::replaying() {
+            return;
+        }
+        let Some(config) = self
+            .config
+            .notifications
+            .clone()
+            .filter(NotifyConfig::is_enabled)
+        else {
+            return;
+        };
+        if notifications.is_empty() {
+            return;
+        }
+        self.notify_tasks
+            .push(tokio::spawn(notify::send_all(config, notifications)));
+    }
+
+    /// Log the results of finished notification tasks
+    async fn poll_notify(&mut self) {
+        let (finished, running): (Vec<_>, Vec<_>) = self
+            .notify_tasks
+            .drain(..)
+            .partition(tokio::task::JoinHandle::is_finished);
+        self.notify_tasks = running;
+        for task in finished {
+            if let Ok(lines) = task.await {
+                for line in lines {
+                    self.push_log(line);
+                }
+            }
+        }
+    }
+
+    fn push_log(&mut self, msg: String) {
+        if let Err(e) = self.logs.push(msg) {
+            self.set_status(format!("Stopped writing the log file: {e}"));
+        }
+    }
+
+    /// Kick off the container janitor in the background if it is enabled
+    fn start_janitor(&mut self) {
+        let enabled = self.config.janitor.as_ref().is_some_and(|j| j.enabled);
+        if !enabled || self.janitor_task.is_some() {
+            return;
+        }
+        let config = self.config.clone();
+        self.janitor_task = Some(tokio::task::spawn_blocking(move || {
+            docker::run_janitor_if_due(&config)
+        }));
+    }
+
+    /// Report the result of a finished janitor run
+    async fn poll_janitor(&mut self) {
+        if !self
+            .janitor_task
+            .as_ref()
+            .is_some_and(tokio::task::JoinHandle::is_finished)
+        {
+            return;
+        }
+        let Some(task) = self.janitor_task.take() else {
+            return;
+        };
+        let msg = match task.await {
+            Ok(Ok(Some(report))) => format!("[janitor] {report}"),
+            Ok(Ok(None)) => return,
+            Ok(Err(e)) => format!("[janitor] prune failed: {e}"),
+            Err(e) => format!("[janitor] task failed: {e}"),
+        };
+        self.push_log(msg.clone());
+        self.set_status(msg);
+    }
+
+    fn set_status(&mut self, msg: String) {
+        self.status_history
+            .push_back((chrono::Local::now(), msg.clone()));
+        if self.status_history.len() > MAX_STATUS_HISTORY {
+            self.status_history.pop_front();
+        }
+        self.status_message = Some((msg, Instant::now()));
+    }
+
+    /// Open the failures-by-runner view from the jobs recorded in the metrics DB
+    fn show_blame(&mut self) {
+        let Some(db) = &self.metrics_db else {
+            self.set_status("Metrics database unavailable".to_string());
+            return;
+        };
+        let runners = match db.get_runner_failures(BLAME_DAYS) {
+            Ok(runners) => runners,
+            Err(e) => {
+                self.set_status(format!("Failed to load jobs: {e}"));
+                return;
+            }
+        };
+        let fleet_rate = fleet_failure_rate(&runners);
+        let mut lines = Vec::new();
+        if runners.is_empty() {
+            lines.push("No jobs recorded yet".to_string());
+        } else {
+            lines.push(format!(
+                "Fleet failure rate: {:.1}% over {} runners",
+                fleet_rate * 100.0,
+                runners.len()
+            ));
+            lines.push(String::new());
+            lines.push(format!("  {:<40} {:>9} {:>7}", "Runner", "Failed", "Rate"));
+        }
+        let mut sorted = runners;
+        sorted.sort_by(|a, b| b.failure_rate().total_cmp(&a.failure_rate()));
+        for runner in &sorted {
+            let marker = if runner.is_outlier(fleet_rate) {
+                "⚠"
+            } else {
+                " "
+            };
+            lines.push(format!(
+                "{marker} {:<40} {:>9} {:>6.1}%",
+                runner.runner_name,
+                format!("{}/{}", runner.failed, runner.total),
+                runner.failure_rate() * 100.0
+            ));
+        }
+        if sorted.iter().any(|r| r.is_outlier(fleet_rate)) {
+            lines.push(String::new());
+            lines.push(
+                "⚠ fails significantly more often than the fleet: check the machine's disk, \
+                 network and toolchain"
+                    .to_string(),
+            );
+        }
+        self.text_view = Some(TextView::new(
+            format!("Failures by runner ({BLAME_DAYS} days)"),
+            lines,
+        ));
+    }
+
+    /// Open the per-host view: the runners GitHub reports now, grouped by the machine
+    /// they run on, with uptime and failure rate from the metrics DB
+    fn show_hosts(&mut self) {
+        let history = match &self.metrics_db {
+            Some(db) => db.get_runner_stats(BLAME_DAYS).unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let hosts = summarize_hosts(&self.github_runners, &history);
+        let lines = if hosts.is_empty() {
+            vec!["No runners reported yet".to_string()]
+        } else {
+            host_table(&hosts)
+        };
+        self.text_view = Some(TextView::new(format!("Hosts ({BLAME_DAYS} days)"), lines));
+    }
+
+    fn show_status_history(&mut self) {
+        let lines: Vec<String> = self
+            .status_history
+            .iter()
+            .map(|(at, msg)| format!("{}  {msg}", at.format("%H:%M:%S")))
+            .collect();
+        let mut view = TextView::new("Status history".to_string(), lines);
+        // Newest messages are at the bottom
+        view.scroll = usize::MAX;
+        self.text_view = Some(view);
+    }
+
+    /// Open the detail view for the selected runner, probing its toolchain
+    fn open_detail(&mut self) {
+        let Some(instance) = self.instances.get(self.selected_runner) else {
+            return;
+        };
+        let id = instance.id();
+        let runner_name = self.runner_name(instance);
+        let toolchain = toolchain::probe(&self.config, Some(&instance.dir));
+        let docker = docker::check(&self.config);
+        let credentials = runner::credential_problem(&instance.dir);
+        let jobs = self
+            .metrics_db
+            .as_ref()
+            .and_then(|db| db.get_runner_jobs(&runner_name, RUNNER_JOB_HISTORY).ok())
+            .unwrap_or_default();
+        let (note, tags) = match &self.state_db {
+            Some(db) => (
+                db.note(&id.scope).ok().flatten(),
+                db.tags(&id.scope).unwrap_or_default(),
+            ),
+            None => (None, Vec::new()),
+        };
+        self.detail = Some(RunnerDetail {
+            id,
+            runner_name,
+            toolchain,
+            docker,
+            credentials,
+            note,
+            tags,
+            jobs,
+        });
+    }
+
+    /// Name `instance` is registered under: from its `.runner` file, or the name `add`
+    /// would have used if the file can't be read
+    pub fn runner_name(&self, instance: &RunnerInstance) -> String {
+        instance
+            .runner_name
+            .clone()
+            .unwrap_or_else(|| instance.id().runner_name(&self.hostname))
+    }
+
+    /// The GitHub record of `instance` among its scope's `runners`. If the registered
+    /// name is unknown and no record has the default name, a scope's only runner is used.
+    pub fn github_record<'a>(
+        &self,
+        instance: &RunnerInstance,
+        runners: &'a [Runner],
+    ) -> Option<&'a Runner> {
+        let record = find_runner(runners, Some(&self.runner_name(instance)));
+        if instance.runner_name.is_some() {
+            record
+        } else {
+            record.or_else(|| find_runner(runners, None))
+        }
+    }
+
+    /// Whether a newer runner release than the instance's installed version exists
+    pub fn update_available(&self, instance: &RunnerInstance) -> bool {
+        match (
+            self.runner_versions.get(&instance.scope),
+            &self.latest_version,
+        ) {
+            (Some(installed), Some(latest)) => updates::is_outdated(installed, latest),
+            _ => false,
+        }
+    }
+
+    /// Open the activity timeline from the workflow runs recorded in the metrics DB
+    /// Open the drill-down for the selected scope of the Metrics panel
+    fn open_metrics_detail(&mut self) {
+        let Some((scope, metrics)) = self.scope_metrics.get(self.selected_metric).cloned() else {
+            return;
+        };
+        let Some(db) = &self.metrics_db else {
+            self.set_status("Metrics database unavailable".to_string());
+            return;
+        };
+        let name = scope.to_display();
+        match MetricsDetail::load(db, scope, metrics) {
+            Ok(detail) => self.metrics_detail = Some(detail),
+            Err(e) => self.set_status(format!("Failed to load metrics of {name}: {e}")),
+        }
+    }
+
+    fn open_run_detail(&mut self) {
+        let Some((scope, run)) = self.selected_workflow_run_scope() else {
+            return;
+        };
+        let (scope, run) = (scope.clone(), run.clone());
+        let jobs = match &self.metrics_db {
+            Some(db) => match db.get_run_jobs(&scope, run.id) {
+                Ok(jobs) => jobs,
+                Err(e) => {
+                    self.set_status(format!("Failed to load jobs of run {}: {e}", run.id));
+                    return;
+                }
+            },
+            None => Vec::new(),
+        };
+        self.run_detail = Some(RunDetail { scope, run, jobs });
+    }
+
+    fn open_timeline(&mut self) {
+        let Some(db) = &self.metrics_db else {
+            self.set_status("Metrics database unavailable".to_string());
+            return;
+        };
+        let to = chrono::Utc::now();
+        match db.get_run_spans(to - chrono::Duration::hours(TIMELINE_HOURS)) {
+            Ok(spans) => self.timeline = Some(Timeline { spans, to }),
+            Err(e) => self.set_status(format!("Failed to load workflow runs: {e}")),
+        }
+    }
+
+    /// Re-list local instances, keeping only those matching the tag filter
+    fn load_instances(&mut self) {
+        self.instances =
+            recording::replayed_instances().unwrap_or_else(|| runner::list_instances(&self.config));
+        recording::record_instances(&self.instances);
+        if let (Some(tag), Some(db)) = (&self.tag_filter, &self.state_db) {
+            self.instances
+                .retain(|i| db.tags(&i.scope).is_ok_and(|tags| tags.contains(tag)));
+        }
+        self.selected_runner = self
+            .selected_runner
+            .min(self.instances.len().saturating_sub(1));
+    }
+
+    /// Re-list local instances after starting or stopping services, so the services the
+    /// user stopped aren't reported as crashed
+    fn reload_controlled_instances(&mut self) {
+        self.load_instances();
+        self.service_tracker.observe(&self.instances);
+    }
+
+    /// Re-list local instances when the instances directory changes, e.g. after `add`
+    /// or `remove` in another terminal. Only local state is read: GitHub data for a
+    /// new runner arrives with the next refresh.
+    fn poll_instances(&mut self) {
+        if self.instances_watcher.changed() {
+            self.load_instances();
+        }
+    }
+
+    /// Refresh now (`r`), including scopes that are backing off, unless GitHub rate
+    /// limited the token
+    fn force_refresh(&mut self) {
+        if let Some(left) = self.rate_limit_pause() {
+            self.set_status(format!(
+                "Rate limited by GitHub, API calls resume in {}s",
+                left.as_secs()
+            ));
+            return;
+        }
+        self.refresh_now();
+        for err in self.scope_errors.values_mut() {
+            err.retry_at = Instant::now();
+        }
+        self.set_status("Refreshing...".to_string());
+    }
+
+    /// Make the event loop start a refresh on its next pass
+    fn refresh_now(&mut self) {
+        self.last_refresh = None;
+    }
+
+    /// Apply edits to the config file. Everything but the GitHub credentials takes
+    /// effect at once; a file that no longer parses is reported and ignored.
+    fn poll_config(&mut self) {
+        if !self.config_watcher.changed() {
+            return;
+        }
+        let config = match Config::load() {
+            Ok(config) => config,
+            Err(e) => {
+                self.set_status(format!("Config not reloaded: {e:#}"));
+                return;
+            }
+        };
+        self.config = self.config.reloaded(config);
+        self.logs.set_capacity(
+            self.config
+                .verbose_log
+                .as_ref()
+                .map_or_else(|| VerboseLogConfig::default().max_lines, |v| v.max_lines),
+        );
+
+        let before: Vec<RunnerScope> = self.instances.iter().map(|i| i.scope.clone()).collect();
+        if self.instances_watcher.path() != self.config.instances_dir() {
+            self.instances_watcher = DirWatcher::new(self.config.instances_dir());
+        }
+        self.load_instances();
+        if self.instances.iter().map(|i| &i.scope).ne(before.iter()) {
+            // Fetch GitHub data for the new set of instances
+            self.refresh_now();
+        }
+        self.set_status(format!(
+            "Config reloaded from {}",
+            self.config_watcher.path().display()
+        ));
+    }
+
+    /// Step the Runners panel filter through the tags in use, then back to all
+    fn cycle_tag_filter(&mut self) {
+        let tags = self
+            .state_db
+            .as_ref()
+            .and_then(|db| db.all_tags().ok())
+            .unwrap_or_default();
+        if tags.is_empty() {
+            self.tag_filter = None;
+            self.set_status("No tags defined (runner-mgr tag <target> <tag>)".to_string());
+            return;
+        }
+        let next = match &self.tag_filter {
+            None => 0,
+            Some(current) => tags.iter().position(|t| t == current).map_or(0, |i| i + 1),
+        };
+        self.tag_filter = tags.get(next).cloned();
+        self.load_instances();
+        match &self.tag_filter {
+            Some(tag) => self.set_status(format!("Showing runners tagged {tag}")),
+            None => self.set_status("Showing all runners".to_string()),
         }
     }
 
-    /// Drain any pending log messages from the receiver
-    fn drain_logs(&mut self) {
-        if let Some(ref receiver) = self.log_receiver {
-            while let Ok(msg) = receiver.try_recv() {
-                self.log_messages.push_back(msg);
-                // Keep only the last MAX_LOG_LINES (O(1) with VecDeque)
-                if self.log_messages.len() > MAX_LOG_LINES {
-                    self.log_messages.pop_front();
-                }
-            }
+    fn toggle_watchdog(&mut self) {
+        if self.watchdog.take().is_some() {
+            self.set_status("Watchdog off".to_string());
+        } else {
+            let settings = self.config.watchdog.clone().unwrap_or_default();
+            self.watchdog = Some(Watchdog::new(settings));
+            self.set_status(
+                "Watchdog on: restarting runners that are offline on GitHub".to_string(),
+            );
         }
     }
 
-    pub async fn refresh_data(&mut self) {
-        self.loading = true;
-        self.error = None;
+    /// Restart the runners the watchdog finds offline after a refresh, and notify about
+    /// those still offline after repeated restarts
+    fn run_watchdog(&mut self) {
+        let Some(watchdog) = self.watchdog.as_mut() else {
+            return;
+        };
+        let now = Instant::now();
+        let check = watchdog.check(now, &self.instances, &self.github_runners);
+        let mut messages = Vec::new();
+        for instance in &check.restart {
+            let id = instance.id();
+            match runner::restart_runner(&self.config, &id) {
+                Ok(()) => messages.push(format!("Watchdog restarted {id} (offline on GitHub)")),
+                Err(e) => messages.push(format!("Watchdog failed to restart {id}: {e}")),
+            }
+            watchdog.restarted(&id, now);
+        }
+        let mut notifications = Vec::new();
+        for (instance, restarts) in &check.gave_up {
+            messages.push(format!(
+                "{} still offline after {restarts} restarts",
+                instance.id()
+            ));
+            notifications.push(notify::restart_failed_notification(instance, *restarts));
+        }
+        for instance in &check.unregistered {
+            let id = instance.id();
+            messages.push(format!(
+                "{id} is no longer registered on GitHub; {}",
+                runner::reregister_hint(&id)
+            ));
+        }
+        for msg in messages {
+            self.push_log(msg.clone());
+            self.set_status(msg);
+        }
+        if !check.restart.is_empty() {
+            self.reload_controlled_instances();
+        }
+        if let Some(config) = self
+            .config
+            .notifications
+            .clone()
+            .filter(|_| !notifications.is_empty())
+        {
+            self.notify_tasks
+                .push(tokio::spawn(notify::send_all(config, notifications)));
+        }
+    }
 
-        // Refresh local instances
-        self.instances = runner::list_instances(&self.config);
-
-        // Collect scopes upfront to avoid borrow conflicts
-        let scopes: Vec<RunnerScope> = self.instances.iter().map(|i| i.scope.clone()).collect();
-
-        // Refresh GitHub runner status and workflow runs for each configured scope
-        let mut github_runners = Vec::new();
-        let mut workflow_runs = Vec::new();
-        let mut last_error: Option<String> = None;
-
-        for scope in &scopes {
-            match self.client.list_runners(scope).await {
-                Ok(list) => {
-                    // Record runner snapshots to metrics DB (non-blocking)
-                    if let Some(ref db) = self.metrics_db {
-                        let _ = db.record_runner_snapshots(scope, &list.runners);
-                    }
-                    github_runners.push((scope.clone(), list.runners));
-                }
-                Err(e) => {
-                    github_runners.push((scope.clone(), Vec::new()));
-                    last_error = Some(format!("Error fetching runners for {scope}: {e}"));
-                }
+    /// Repair action from the detail view: restart the container engine daemon
+    fn restart_docker(&mut self) {
+        let Some(engine) = self.detail.as_ref().and_then(|d| d.docker.engine()) else {
+            self.set_status("No container engine installed".to_string());
+            return;
+        };
+        match docker::restart_daemon(&self.config, engine) {
+            Ok(()) => {
+                self.open_detail();
+                self.set_status(format!("Restarted {engine}"));
             }
+            Err(e) => self.set_status(format!("Error restarting {engine}: {e}")),
+        }
+    }
 
-            // Only fetch workflow runs for repositories, not organizations
-            if let RunnerScope::Repository { owner, repo } = scope {
-                match self.client.list_workflow_runs(owner, repo, 5).await {
-                    Ok(list) => {
-                        // Record workflow runs to metrics DB (non-blocking)
-                        if let Some(ref db) = self.metrics_db {
-                            let _ = db.record_workflow_runs(scope, &list.workflow_runs);
-                        }
-                        workflow_runs.push((scope.clone(), list.workflow_runs));
-                    }
-                    Err(e) => {
-                        workflow_runs.push((scope.clone(), Vec::new()));
-                        last_error = Some(format!("Error fetching runs for {scope}: {e}"));
-                    }
-                }
+    /// Keys handled while the runner detail view is open
+    fn handle_detail_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc | KeyCode::Enter => self.detail = None,
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Char('p') => {
+                self.open_detail();
+                self.set_status("Toolchain re-probed".to_string());
             }
+            KeyCode::Char('D') => self.restart_docker(),
+            _ => {}
         }
+    }
 
-        // Compute scope metrics from DB
-        let mut scope_metrics = Vec::new();
-        if let Some(ref db) = self.metrics_db {
-            for scope in &scopes {
-                match db.get_scope_metrics(scope, 7) {
-                    Ok(metrics) => scope_metrics.push((scope.clone(), metrics)),
-                    Err(_) => {
-                        // Use default metrics on error
-                        scope_metrics.push((scope.clone(), ScopeMetrics::default()));
-                    }
-                }
+    /// Keys answering a y/N confirmation prompt
+    fn handle_confirm_key(&mut self, confirm: Confirm, code: KeyCode) {
+        if !matches!(code, KeyCode::Char('y' | 'Y')) {
+            self.set_status("Cancelled".to_string());
+            return;
+        }
+        match confirm.action {
+            ConfirmAction::RemoveRunner(id) => self.pending_remove = Some(id),
+            ConfirmAction::CancelRun(scope, run_id) => {
+                self.pending_run = Some((scope, run_id, RunAction::Cancel));
             }
         }
+    }
 
-        if let Some(err) = last_error {
-            self.set_status(err);
+    /// Keys handled while the per-scope metrics drill-down is open
+    fn handle_metrics_detail_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc | KeyCode::Enter => self.metrics_detail = None,
+            KeyCode::Char('q') => self.should_quit = true,
+            _ => {}
         }
+    }
 
-        self.github_runners = github_runners;
-        self.workflow_runs = workflow_runs;
-        self.scope_metrics = scope_metrics;
-        self.last_refresh = Instant::now();
-        self.loading = false;
+    /// Keys handled while the workflow run detail view is open
+    fn handle_run_detail_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc | KeyCode::Enter => self.run_detail = None,
+            KeyCode::Char('o') => {
+                let Some(url) = self.run_detail.as_ref().map(|d| d.run.html_url.clone()) else {
+                    return;
+                };
+                match open_url(&self.config, &url) {
+                    Ok(()) => self.set_status(format!("Opened {url}")),
+                    Err(e) => self.set_status(format!("Could not open browser: {e}")),
+                }
+            }
+            KeyCode::Char('q') => self.should_quit = true,
+            _ => {}
+        }
     }
 
-    fn set_status(&mut self, msg: String) {
-        self.status_message = Some((msg, Instant::now()));
+    /// Keys handled while the activity timeline is open
+    fn handle_timeline_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('T') => self.timeline = None,
+            KeyCode::Char('q') => self.should_quit = true,
+            _ => {}
+        }
     }
 
-    #[allow(clippy::too_many_lines)]
-    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+    fn handle_key(&mut self, key: KeyEvent) {
         // Clear expired status messages
         if let Some((_, time)) = &self.status_message {
             if time.elapsed() > Duration::from_secs(5) {
                 self.status_message = None;
             }
         }
+        if key.kind == KeyEventKind::Release {
+            return;
+        }
 
-        match code {
-            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
-            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
-                self.should_quit = true;
+        if let Some(confirm) = self.confirm.take() {
+            self.handle_confirm_key(confirm, key.code);
+            return;
+        }
+
+        if let Some(log) = self.live_log.as_mut() {
+            match log.handle_key(key.code) {
+                livelog::KeyResult::None => {}
+                livelog::KeyResult::Status(msg) => self.set_status(msg),
+                livelog::KeyResult::Close => self.live_log = None,
             }
-            KeyCode::Tab => {
+            return;
+        }
+        if self.text_view.is_some() {
+            self.handle_text_view_key(key.code);
+            return;
+        }
+        if self.menu.is_some() {
+            self.handle_menu_key(key.code);
+            return;
+        }
+        if self.detail.is_some() {
+            self.handle_detail_key(key.code);
+            return;
+        }
+        if self.metrics_detail.is_some() {
+            self.handle_metrics_detail_key(key.code);
+            return;
+        }
+        if self.run_detail.is_some() {
+            self.handle_run_detail_key(key.code);
+            return;
+        }
+        if self.timeline.is_some() {
+            self.handle_timeline_key(key.code);
+            return;
+        }
+
+        if let Some(action) = self.keys.dispatch(key) {
+            self.perform(action);
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        if action.changes_state() && recording::replaying() {
+            self.set_status("Not available while replaying a recording".to_string());
+            return;
+        }
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::NextPanel => {
                 self.active_panel = match self.active_panel {
                     Panel::Runners => Panel::Workflows,
                     Panel::Workflows => Panel::Metrics,
                     Panel::Metrics => Panel::Runners,
                 };
             }
-            KeyCode::Char('m') => {
-                // Quick switch to Metrics panel
-                self.active_panel = Panel::Metrics;
+            Action::FocusMetrics => self.active_panel = Panel::Metrics,
+            Action::Up => {
+                let selected = self.selected_mut();
+                *selected = selected.saturating_sub(1);
             }
-            KeyCode::Up | KeyCode::Char('k') => match self.active_panel {
-                Panel::Runners => {
-                    if self.selected_runner > 0 {
-                        self.selected_runner -= 1;
-                    }
-                }
-                Panel::Workflows => {
-                    if self.selected_workflow > 0 {
-                        self.selected_workflow -= 1;
-                    }
-                }
-                Panel::Metrics => {
-                    if self.selected_metric > 0 {
-                        self.selected_metric -= 1;
-                    }
-                }
-            },
-            KeyCode::Down | KeyCode::Char('j') => match self.active_panel {
-                Panel::Runners => {
-                    let max = self.instances.len().saturating_sub(1);
-                    if self.selected_runner < max {
-                        self.selected_runner += 1;
-                    }
-                }
-                Panel::Workflows => {
-                    let max = self
-                        .workflow_runs
-                        .iter()
-                        .map(|(_, runs)| runs.len())
-                        .sum::<usize>()
-                        .saturating_sub(1);
-                    if self.selected_workflow < max {
-                        self.selected_workflow += 1;
-                    }
-                }
-                Panel::Metrics => {
-                    let max = self.scope_metrics.len().saturating_sub(1);
-                    if self.selected_metric < max {
-                        self.selected_metric += 1;
-                    }
-                }
-            },
-            KeyCode::Char('s') => {
-                if self.active_panel == Panel::Runners && !self.instances.is_empty() {
-                    let scope = self.instances[self.selected_runner].scope.clone();
-                    let status = &self.instances[self.selected_runner].status;
-                    match status {
-                        runner::RunnerStatus::Running => {
-                            match runner::stop_runner(&self.config, &scope) {
-                                Ok(()) => self.set_status(format!("Stopped {scope}")),
-                                Err(e) => {
-                                    self.set_status(format!("Error stopping {scope}: {e}"));
-                                }
-                            }
-                        }
-                        runner::RunnerStatus::Stopped => {
-                            match runner::start_runner(&self.config, &scope) {
-                                Ok(()) => self.set_status(format!("Started {scope}")),
-                                Err(e) => {
-                                    self.set_status(format!("Error starting {scope}: {e}"));
-                                }
-                            }
-                        }
-                        _ => {
-                            self.set_status(format!("Cannot toggle {scope} (status: {status})"));
-                        }
-                    }
-                    // Refresh local status immediately
-                    self.instances = runner::list_instances(&self.config);
+            Action::Down => {
+                let max = self.selection_len().saturating_sub(1);
+                let selected = self.selected_mut();
+                if *selected < max {
+                    *selected += 1;
                 }
             }
-            KeyCode::Char('r') => {
-                // Force refresh
-                self.last_refresh = Instant::now().checked_sub(REFRESH_INTERVAL).unwrap();
-                self.set_status("Refreshing...".to_string());
+            Action::Top => *self.selected_mut() = 0,
+            Action::Bottom => *self.selected_mut() = self.selection_len().saturating_sub(1),
+            Action::Select if self.active_panel == Panel::Runners => self.open_detail(),
+            Action::Select if self.active_panel == Panel::Metrics => self.open_metrics_detail(),
+            Action::Select if self.active_panel == Panel::Workflows => self.open_run_detail(),
+            Action::ToggleRunner
+                if self.active_panel == Panel::Runners && !self.instances.is_empty() =>
+            {
+                self.toggle_selected_runner();
             }
-            KeyCode::Char('S') => {
-                // Start all
+            Action::StartRunner | Action::StopRunner | Action::RestartRunner => {
+                self.control_selected_runner(action);
+            }
+            Action::ShowLogs => self.show_logs_view(),
+            Action::LiveLogs => self.open_live_log(),
+            Action::ShowLabels => self.show_labels_view(),
+            Action::OpenInBrowser => self.open_in_browser(),
+            Action::OpenMenu => self.menu = self.context_menu(),
+            Action::ShowHistory => self.show_status_history(),
+            Action::ShowBlame => self.show_blame(),
+            Action::ShowHosts => self.show_hosts(),
+            Action::ShowErrors => self.show_errors_view(),
+            Action::ShowTimeline => self.open_timeline(),
+            Action::CycleTagFilter => self.cycle_tag_filter(),
+            Action::ToggleWatchdog => self.toggle_watchdog(),
+            Action::RemoveRunner
+                if self.active_panel == Panel::Runners && !self.instances.is_empty() =>
+            {
+                let id = self.instances[self.selected_runner].id();
+                self.confirm = Some(Confirm {
+                    prompt: format!("Remove runner {id}? (y/N)"),
+                    action: ConfirmAction::RemoveRunner(id),
+                });
+            }
+            Action::ToggleLogs
+            | Action::ClearLogs
+            | Action::ScrollLogsUp
+            | Action::ScrollLogsDown
+            | Action::ToggleFollowLogs => self.perform_log_action(action),
+            Action::CancelRun if self.active_panel == Panel::Workflows => {
+                self.request_run_action(RunAction::Cancel);
+            }
+            Action::RerunRun if self.active_panel == Panel::Workflows => {
+                self.request_run_action(RunAction::Rerun);
+            }
+            Action::Refresh => self.force_refresh(),
+            Action::StartAll => {
                 runner::start_all(&self.config);
                 self.set_status("Started all runners".to_string());
-                self.instances = runner::list_instances(&self.config);
+                self.reload_controlled_instances();
             }
-            KeyCode::Char('X') => {
-                // Stop all
+            Action::StopAll => {
                 runner::stop_all(&self.config);
                 self.set_status("Stopped all runners".to_string());
-                self.instances = runner::list_instances(&self.config);
+                self.reload_controlled_instances();
             }
-            KeyCode::Char('v') => {
-                // Toggle verbose log panel
+            _ => {}
+        }
+    }
+
+    /// Actions on the verbose log panel; all but showing it need the panel open
+    fn perform_log_action(&mut self, action: Action) {
+        match action {
+            Action::ToggleLogs => {
                 self.show_logs = !self.show_logs;
                 if self.show_logs {
                     self.set_status("Logs panel shown (verbose mode)".to_string());
@@ -301,45 +1930,445 @@ impl App {
                     self.set_status("Logs panel hidden".to_string());
                 }
             }
-            KeyCode::Char('c') => {
-                // Clear logs
-                if self.show_logs {
-                    self.log_messages.clear();
-                    self.log_scroll = 0;
-                    self.set_status("Logs cleared".to_string());
+            Action::ClearLogs if self.show_logs => {
+                self.logs.clear();
+                self.set_status("Logs cleared".to_string());
+            }
+            Action::ScrollLogsUp if self.show_logs => self.logs.scroll_up(),
+            Action::ScrollLogsDown if self.show_logs => self.logs.scroll_down(),
+            Action::ToggleFollowLogs if self.show_logs => {
+                self.logs.follow = !self.logs.follow;
+                if self.logs.follow {
+                    self.logs.scroll = 0;
+                    self.set_status("Following new log lines".to_string());
+                } else {
+                    self.set_status("Log panel paused".to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_menu_key(&mut self, code: KeyCode) {
+        let Some(menu) = self.menu.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Esc | KeyCode::Char('m' | 'q') => self.menu = None,
+            KeyCode::Up | KeyCode::Char('k') => menu.selected = menu.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                menu.selected = (menu.selected + 1).min(menu.items.len().saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                let action = menu.items.get(menu.selected).map(|(_, a)| *a);
+                self.menu = None;
+                if let Some(action) = action {
+                    self.perform(action);
                 }
             }
-            KeyCode::PageUp => {
-                // Scroll logs up
-                if self.show_logs && self.log_scroll > 0 {
-                    self.log_scroll = self.log_scroll.saturating_sub(5);
+            _ => {}
+        }
+    }
+
+    /// Keys typed into a logs view's export prompt
+    fn handle_save_prompt_key(&mut self, code: KeyCode) {
+        let Some(view) = self.text_view.as_mut() else {
+            return;
+        };
+        let Some(prompt) = view.save_prompt.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => view.save_prompt = None,
+            KeyCode::Backspace => {
+                prompt.path.pop();
+            }
+            KeyCode::Char(c) => prompt.path.push(c),
+            KeyCode::Enter => {
+                if let Some(prompt) = view.save_prompt.take() {
+                    self.export_logs(&prompt);
                 }
             }
-            KeyCode::PageDown => {
-                // Scroll logs down
-                if self.show_logs {
-                    let max_scroll = self.log_messages.len().saturating_sub(1);
-                    self.log_scroll = (self.log_scroll + 5).min(max_scroll);
+            _ => {}
+        }
+    }
+
+    /// Write the logs view's lines, or the runner's full log, to the prompt's path
+    fn export_logs(&mut self, prompt: &SavePrompt) {
+        let Some(view) = &self.text_view else {
+            return;
+        };
+        let content = match (&view.logs_of, prompt.full) {
+            (Some(id), true) => match runner::get_runner_logs(&self.config, id, u32::MAX) {
+                Ok(logs) => logs,
+                Err(e) => {
+                    self.set_status(format!("Error reading logs for {id}: {e}"));
+                    return;
+                }
+            },
+            _ => view.lines.join("\n"),
+        };
+        match runner::export_logs(&prompt.path, &content) {
+            Ok(path) => self.set_status(format!("Saved logs to {}", path.display())),
+            Err(e) => self.set_status(format!("Error saving logs: {e:#}")),
+        }
+    }
+
+    /// Start an export prompt in a logs view, with a path in the Downloads directory
+    fn open_save_prompt(&mut self, full: bool) {
+        let Some(view) = self.text_view.as_mut() else {
+            return;
+        };
+        let Some(id) = &view.logs_of else {
+            return;
+        };
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let path = runner::log_export_path(&runner::log_export_dir(), id, &timestamp);
+        view.save_prompt = Some(SavePrompt {
+            path: path.to_string_lossy().to_string(),
+            full,
+        });
+    }
+
+    fn handle_text_view_key(&mut self, code: KeyCode) {
+        if self
+            .text_view
+            .as_ref()
+            .is_some_and(|v| v.save_prompt.is_some())
+        {
+            self.handle_save_prompt_key(code);
+            return;
+        }
+        if let KeyCode::Char(c @ ('w' | 'W')) = code {
+            self.open_save_prompt(c == 'W');
+            return;
+        }
+        let Some(view) = self.text_view.as_mut() else {
+            return;
+        };
+        let max = view.max_scroll();
+        view.scroll = view.scroll.min(max);
+        match code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => self.text_view = None,
+            KeyCode::Up | KeyCode::Char('k') => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => view.scroll = (view.scroll + 1).min(max),
+            KeyCode::PageUp => view.scroll = view.scroll.saturating_sub(10),
+            KeyCode::PageDown => view.scroll = (view.scroll + 10).min(max),
+            KeyCode::Home | KeyCode::Char('g') => view.scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => view.scroll = max,
+            KeyCode::Char('f') => {
+                if let Some(label) = view.cycle_journal_filter() {
+                    self.set_status(format!("Log filter: {label}"));
                 }
             }
             _ => {}
         }
     }
+
+    /// Menu of actions for the selected item in the focused panel
+    fn context_menu(&self) -> Option<ContextMenu> {
+        let (title, mut items) = match self.active_panel {
+            Panel::Runners => (
+                self.selected_scope()?.to_display(),
+                vec![
+                    ("Details", Action::Select),
+                    ("Start", Action::StartRunner),
+                    ("Stop", Action::StopRunner),
+                    ("Restart", Action::RestartRunner),
+                    ("Logs", Action::ShowLogs),
+                    ("Live logs", Action::LiveLogs),
+                    ("Labels", Action::ShowLabels),
+                    ("Remove", Action::RemoveRunner),
+                    ("Open in browser", Action::OpenInBrowser),
+                ],
+            ),
+            Panel::Workflows => (
+                self.selected_workflow_run()?
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| "workflow run".to_string()),
+                vec![
+                    ("Details", Action::Select),
+                    ("Cancel", Action::CancelRun),
+                    ("Re-run", Action::RerunRun),
+                    ("Open in browser", Action::OpenInBrowser),
+                ],
+            ),
+            Panel::Metrics => (
+                self.selected_scope()?.to_display(),
+                vec![
+                    ("Open in browser", Action::OpenInBrowser),
+                    ("Refresh", Action::Refresh),
+                ],
+            ),
+        };
+        if self
+            .selected_scope()
+            .is_some_and(|scope| self.scope_errors.contains_key(scope))
+        {
+            items.push(("API errors", Action::ShowErrors));
+        }
+        Some(ContextMenu {
+            title,
+            items,
+            selected: 0,
+        })
+    }
+
+    /// Instance of the selected row in the Runners panel
+    fn selected_instance_id(&self) -> Option<InstanceId> {
+        if self.active_panel != Panel::Runners {
+            return None;
+        }
+        self.instances
+            .get(self.selected_runner)
+            .map(RunnerInstance::id)
+    }
+
+    /// Scope of the selected row in the Runners or Metrics panel
+    fn selected_scope(&self) -> Option<&RunnerScope> {
+        match self.active_panel {
+            Panel::Runners => self.instances.get(self.selected_runner).map(|i| &i.scope),
+            Panel::Metrics => self.scope_metrics.get(self.selected_metric).map(|(s, _)| s),
+            Panel::Workflows => None,
+        }
+    }
+
+    fn selected_workflow_run(&self) -> Option<&WorkflowRun> {
+        self.selected_workflow_run_scope().map(|(_, run)| run)
+    }
+
+    /// The selected row in the Workflows panel with the repository it belongs to
+    fn selected_workflow_run_scope(&self) -> Option<(&RunnerScope, &WorkflowRun)> {
+        self.workflow_runs
+            .iter()
+            .flat_map(|(scope, runs)| runs.iter().map(move |run| (scope, run)))
+            .nth(self.selected_workflow)
+    }
+
+    /// Cancel (after confirmation) or re-run the selected workflow run
+    fn request_run_action(&mut self, action: RunAction) {
+        let Some((scope, run)) = self.selected_workflow_run_scope() else {
+            return;
+        };
+        let (scope, run_id, status) = (scope.clone(), run.id, run.status.clone());
+        let (can_cancel, can_rerun) = (run.can_cancel(), run.can_rerun());
+        let name = run.name.clone().unwrap_or_else(|| format!("run {run_id}"));
+        match action {
+            RunAction::Cancel if !can_cancel => {
+                self.set_status(format!("{name} has already completed"));
+            }
+            RunAction::Rerun if !can_rerun => {
+                self.set_status(format!("{name} is still {status}"));
+            }
+            RunAction::Cancel => {
+                self.confirm = Some(Confirm {
+                    prompt: format!("Cancel {name} in {scope}? (y/N)"),
+                    action: ConfirmAction::CancelRun(scope, run_id),
+                });
+            }
+            RunAction::Rerun => self.pending_run = Some((scope, run_id, RunAction::Rerun)),
+        }
+    }
+
+    async fn run_action(&mut self, scope: &RunnerScope, run_id: u64, action: RunAction) {
+        let RunnerScope::Repository { owner, repo } = scope else {
+            return;
+        };
+        let (done, verb, result) = match action {
+            RunAction::Cancel => (
+                "Requested cancellation of",
+                "cancelling",
+                self.client.cancel_workflow_run(owner, repo, run_id).await,
+            ),
+            RunAction::Rerun => (
+                "Re-running",
+                "re-running",
+                self.client.rerun_workflow_run(owner, repo, run_id).await,
+            ),
+        };
+        match result {
+            Ok(()) => {
+                self.set_status(format!("{done} run {run_id} in {scope}"));
+                self.refresh_now();
+            }
+            Err(e) => self.set_status(format!("Error {verb} run {run_id}: {e}")),
+        }
+    }
+
+    fn control_selected_runner(&mut self, action: Action) {
+        let Some(id) = self.selected_instance_id() else {
+            return;
+        };
+        let (done, verb, result) = match action {
+            Action::StartRunner => (
+                "Started",
+                "starting",
+                runner::start_runner(&self.config, &id),
+            ),
+            Action::StopRunner => (
+                "Stopped",
+                "stopping",
+                runner::stop_runner(&self.config, &id),
+            ),
+            _ => (
+                "Restarted",
+                "restarting",
+                runner::restart_runner(&self.config, &id),
+            ),
+        };
+        match result {
+            Ok(()) => self.set_status(format!("{done} {id}")),
+            Err(e) => self.set_status(format!("Error {verb} {id}: {e}")),
+        }
+        self.reload_controlled_instances();
+    }
+
+    fn show_logs_view(&mut self) {
+        let Some(id) = self.selected_instance_id() else {
+            return;
+        };
+        let title = format!("Logs: {id}");
+        match runner::get_runner_journal(&self.config, &id, 200) {
+            Ok(Some(entries)) => {
+                let mut view = TextView::journal(title, entries);
+                view.logs_of = Some(id);
+                view.scroll = usize::MAX;
+                self.text_view = Some(view);
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => runner::log_line(&format!("Structured logs unavailable for {id}: {e}")),
+        }
+        match runner::get_runner_logs(&self.config, &id, 200) {
+            Ok(logs) => {
+                let lines: Vec<String> = logs.lines().map(ToString::to_string).collect();
+                let mut view = TextView::new(title, lines);
+                view.logs_of = Some(id);
+                // Start at the end, where the newest lines are (clamped when drawn)
+                view.scroll = usize::MAX;
+                self.text_view = Some(view);
+            }
+            Err(e) => self.set_status(format!("Error reading logs for {id}: {e}")),
+        }
+    }
+
+    /// Open the full-screen live log of the selected runner
+    fn open_live_log(&mut self) {
+        let Some(id) = self.selected_instance_id() else {
+            return;
+        };
+        match runner::follow_runner_logs(&self.config, &id, livelog::LIVE_LOG_BACKLOG) {
+            Ok(source) => self.live_log = Some(LiveLog::new(id, source)),
+            Err(e) => self.set_status(format!("Error following logs for {id}: {e:#}")),
+        }
+    }
+
+    fn show_labels_view(&mut self) {
+        let Some(instance) = self.instances.get(self.selected_runner) else {
+            return;
+        };
+        let registered = runner::read_labels(&instance.dir);
+        let mut lines = vec![format!(
+            "Registered: {}",
+            if registered.is_empty() {
+                "-".to_string()
+            } else {
+                registered.join(", ")
+            }
+        )];
+        let records = self
+            .github_runners
+            .iter()
+            .find(|(scope, _)| *scope == instance.scope)
+            .map(|(_, runners)| runners.as_slice())
+            .unwrap_or_default();
+        for r in records {
+            let labels: Vec<&str> = r.labels.iter().map(|l| l.name.as_str()).collect();
+            lines.push(format!("GitHub ({}): {}", r.name, labels.join(", ")));
+        }
+        self.text_view = Some(TextView::new(
+            format!("Labels: {}", instance.scope.to_display()),
+            lines,
+        ));
+    }
+
+    fn open_in_browser(&mut self) {
+        let url = match self.active_panel {
+            Panel::Runners => self
+                .selected_scope()
+                .map(|s| s.settings_url(&self.config.web_url())),
+            Panel::Metrics => self
+                .selected_scope()
+                .map(|s| format!("{}/actions", s.web_url(&self.config.web_url()))),
+            Panel::Workflows => self.selected_workflow_run().map(|r| r.html_url.clone()),
+        };
+        let Some(url) = url else {
+            return;
+        };
+        match open_url(&self.config, &url) {
+            Ok(()) => self.set_status(format!("Opened {url}")),
+            Err(e) => self.set_status(format!("Could not open browser: {e}")),
+        }
+    }
+
+    /// Selection index for the focused panel
+    fn selected_mut(&mut self) -> &mut usize {
+        match self.active_panel {
+            Panel::Runners => &mut self.selected_runner,
+            Panel::Workflows => &mut self.selected_workflow,
+            Panel::Metrics => &mut self.selected_metric,
+        }
+    }
+
+    /// Number of selectable rows in the focused panel
+    fn selection_len(&self) -> usize {
+        match self.active_panel {
+            Panel::Runners => self.instances.len(),
+            Panel::Workflows => self.workflow_runs.iter().map(|(_, runs)| runs.len()).sum(),
+            Panel::Metrics => self.scope_metrics.len(),
+        }
+    }
+
+    fn toggle_selected_runner(&mut self) {
+        let id = self.instances[self.selected_runner].id();
+        let status = &self.instances[self.selected_runner].status;
+        match status {
+            runner::RunnerStatus::Running => match runner::stop_runner(&self.config, &id) {
+                Ok(()) => self.set_status(format!("Stopped {id}")),
+                Err(e) => self.set_status(format!("Error stopping {id}: {e}")),
+            },
+            runner::RunnerStatus::Stopped => match runner::start_runner(&self.config, &id) {
+                Ok(()) => self.set_status(format!("Started {id}")),
+                Err(e) => self.set_status(format!("Error starting {id}: {e}")),
+            },
+            _ => {
+                self.set_status(format!("Cannot toggle {id} (status: {status})"));
+            }
+        }
+        // Refresh local status immediately
+        self.reload_controlled_instances();
+    }
+
+    /// Remove a runner confirmed with `d d`. Runs on the UI task because it
+    /// talks to GitHub and the service manager.
+    async fn remove_runner(&mut self, id: &InstanceId) {
+        match runner::remove_runner(&self.config, id).await {
+            Ok(()) => self.set_status(format!("Removed {id}")),
+            Err(e) => self.set_status(format!("Error removing {id}: {e}")),
+        }
+        self.load_instances();
+    }
 }
 
 pub async fn run_dashboard(config: Config, verbose: bool) -> Result<()> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let (mut terminal, _restore_guard) = setup_terminal()?;
 
     let mut app = App::new(config);
 
     // Set up log channel for verbose output (bounded to prevent memory leaks)
     if verbose {
-        let (sender, receiver) = mpsc::sync_channel(MAX_LOG_LINES);
+        let (sender, receiver) = mpsc::sync_channel(app.logs.capacity);
         runner::set_log_sender(Some(sender));
         app.log_receiver = Some(receiver);
         app.show_logs = true; // Auto-show logs panel when verbose
@@ -352,35 +2381,167 @@ pub async fn run_dashboard(config: Config, verbose: bool) -> Result<()> {
 
     // Restore terminal
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        PopKeyboardEnhancementFlags,
+        LeaveAlternateScreen
+    )?;
+    terminal.show_cursor()?;
+
+    result
+}
+
+/// Open a URL in the desktop browser without blocking the UI
+fn open_url(config: &Config, url: &str) -> Result<()> {
+    let opener = if config.runner_os == "darwin" {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(opener)
+        .arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+/// Enter raw mode and the alternate screen. The returned guard restores the
+/// terminal if the process is killed by SIGINT/SIGTERM while the TUI is up;
+/// a panic hook does the same for crashes.
+fn setup_terminal() -> Result<(Terminal<CrosstermBackend<io::Stdout>>, CleanupGuard)> {
+    install_panic_hook();
+    let guard = interrupt::on_interrupt(restore_terminal);
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    // Distinguish held-down keys from fresh presses where the terminal supports it
+    if supports_keyboard_enhancement().unwrap_or(false) {
+        execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )?;
+    }
+    let backend = CrosstermBackend::new(stdout);
+    Ok((Terminal::new(backend)?, guard))
+}
+
+/// Best-effort terminal restore for crash and signal paths
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        PopKeyboardEnhancementFlags,
+        LeaveAlternateScreen,
+        cursor::Show
+    );
+}
+
+fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            // Restore first so the panic message is readable
+            restore_terminal();
+            default_hook(info);
+        }));
+    });
+}
+
+/// Run the first-run setup wizard. Returns the collected config, or `None` if cancelled.
+pub async fn run_onboarding() -> Result<Option<Config>> {
+    let (mut terminal, _restore_guard) = setup_terminal()?;
+
+    let result = run_wizard(&mut terminal).await;
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        PopKeyboardEnhancementFlags,
+        LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
 
     result
 }
 
+async fn run_wizard(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+) -> Result<Option<Config>> {
+    let mut wizard = Wizard::new();
+    loop {
+        terminal.draw(|f| ui::draw_wizard(f, &wizard))?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Release && wizard.handle_key(key.code, key.modifiers) {
+                terminal.draw(|f| ui::draw_wizard(f, &wizard))?;
+                wizard.validate_token().await;
+            }
+        }
+
+        if wizard.cancelled {
+            return Ok(None);
+        }
+        if wizard.finished {
+            return Ok(Some(wizard.config()));
+        }
+    }
+}
+
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
 ) -> Result<()> {
     loop {
         // Auto-refresh
-        if app.last_refresh.elapsed() >= REFRESH_INTERVAL {
-            app.refresh_data().await;
+        app.poll_config();
+        app.poll_instances();
+        if app
+            .last_refresh
+            .is_none_or(|last| last.elapsed() >= app.config.refresh_interval())
+        {
+            app.start_refresh();
         }
+        app.poll_refresh().await;
 
         // Drain any pending log messages
         app.drain_logs();
+        if let Some(log) = app.live_log.as_mut() {
+            log.poll();
+        }
+        app.poll_janitor().await;
+        app.poll_notify().await;
 
         terminal.draw(|f| ui::draw(f, app))?;
 
         // Poll for events with a short timeout so we can refresh
         if event::poll(Duration::from_millis(250))? {
             if let Event::Key(key) = event::read()? {
-                app.handle_key(key.code, key.modifiers);
+                app.handle_key(key);
             }
         }
 
+        if let Some(id) = app.pending_remove.take() {
+            app.set_status(format!("Removing {id}..."));
+            terminal.draw(|f| ui::draw(f, app))?;
+            app.remove_runner(&id).await;
+            // Removal prints progress to stdout; repaint the whole screen
+            terminal.clear()?;
+        }
+
+        if let Some((scope, run_id, action)) = app.pending_run.take() {
+            app.run_action(&scope, run_id, action).await;
+        }
+
         if app.should_quit {
+            if app.janitor_task.is_some() {
+                app.set_status("Waiting for container cleanup to finish...".to_string());
+                terminal.draw(|f| ui::draw(f, app))?;
+            }
+            app.shutdown().await;
             return Ok(());
         }
     }
@@ -2,14 +2,27 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Tabs},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Tabs},
     Frame,
 };
 
+use super::super::docker::DockerHealth;
 use super::super::github::RunnerScope;
-use super::super::metrics::Trend;
-use super::super::runner::RunnerStatus;
-use super::{App, Panel};
+use super::super::labels;
+use super::super::metrics::{
+    format_duration, stack_spans, worst_slo, JobRecord, RunSpan, ScopeMetrics, SloState, SloStatus,
+    TrendChange, QUEUE_HISTORY_BUCKETS,
+};
+use super::super::notify;
+use super::super::recording;
+use super::super::runner::{self, RunnerInstance, RunnerStatus};
+use super::super::scheduler;
+use super::livelog::LiveLog;
+use super::wizard::{Wizard, WizardStep};
+use super::{
+    App, ContextMenu, MetricsDetail, Panel, RunDetail, RunnerDetail, TextView, Timeline,
+    ERROR_THRESHOLD, METRICS_DAYS, TIMELINE_HOURS,
+};
 
 pub fn draw(f: &mut Frame, app: &App) {
     let chunks = if app.show_logs {
@@ -42,6 +55,897 @@ pub fn draw(f: &mut Frame, app: &App) {
     } else {
         draw_status_bar(f, app, chunks[2]);
     }
+
+    if app.detail.is_some() {
+        draw_runner_detail(f, app);
+    }
+    if let Some(detail) = &app.metrics_detail {
+        draw_metrics_detail(f, detail);
+    }
+    if let Some(detail) = &app.run_detail {
+        draw_run_detail(f, detail);
+    }
+    if let Some(timeline) = &app.timeline {
+        draw_timeline(f, timeline);
+    }
+    if let Some(menu) = &app.menu {
+        draw_context_menu(f, menu);
+    }
+    if let Some(view) = &app.text_view {
+        draw_text_view(f, view);
+    }
+    if let Some(log) = &app.live_log {
+        draw_live_log(f, app, log);
+    }
+}
+
+fn draw_context_menu(f: &mut Frame, menu: &ContextMenu) {
+    let lines: Vec<Line> = menu
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, (label, _))| {
+            if i == menu.selected {
+                Line::from(Span::styled(
+                    format!("▶ {label}"),
+                    Style::default()
+                        .bg(Color::DarkGray)
+                        .add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from(format!("  {label}"))
+            }
+        })
+        .collect();
+
+    let full = f.area();
+    let width = 36.min(full.width);
+    let height = (u16::try_from(lines.len()).unwrap_or(u16::MAX) + 2).min(full.height);
+    let area = Rect::new(
+        full.x + full.width.saturating_sub(width) / 2,
+        full.y + full.height.saturating_sub(height) / 2,
+        width,
+        height,
+    );
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(" {} ", truncate(&menu.title, 30)))
+            .title_bottom(" Enter run, Esc close "),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(widget, area);
+}
+
+fn draw_text_view(f: &mut Frame, view: &TextView) {
+    let area = centered_rect(85, 80, f.area());
+    // Keep the last page full when scrolled to the end
+    let visible = usize::from(area.height.saturating_sub(2));
+    view.visible.set(visible);
+    let start = view.scroll.min(view.lines.len().saturating_sub(visible));
+    let priorities = view.journal.as_ref().map(|j| j.priorities.as_slice());
+    let lines: Vec<Line> = view
+        .lines
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(visible)
+        .map(|(i, l)| match priorities.and_then(|p| p.get(i)) {
+            Some(&priority) => Line::from(Span::styled(l.clone(), priority_style(priority))),
+            None => Line::from(l.clone()),
+        })
+        .collect();
+
+    let save = if view.logs_of.is_some() {
+        ", w/W save loaded/full"
+    } else {
+        ""
+    };
+    let title = match (&view.save_prompt, &view.journal) {
+        (Some(prompt), _) => format!(
+            " Save {} to: {}_ [Enter save, Esc cancel] ",
+            if prompt.full {
+                "full log"
+            } else {
+                "loaded lines"
+            },
+            prompt.path
+        ),
+        (None, Some(journal)) => format!(
+            " {} [Esc close, j/k scroll, f filter: {}{save}] ",
+            view.title,
+            journal.filter_label()
+        ),
+        (None, None) => format!(" {} [Esc close, j/k scroll{save}] ", view.title),
+    };
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(title),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(widget, area);
+}
+
+/// Full-screen live log: the search prompt or status message and the keys at the bottom
+fn draw_live_log(f: &mut Frame, app: &App, log: &LiveLog) {
+    let area = f.area();
+    let visible = usize::from(area.height.saturating_sub(2));
+    log.visible.set(visible);
+    let start = if log.follow {
+        log.max_scroll()
+    } else {
+        log.scroll.min(log.max_scroll())
+    };
+    let lines: Vec<Line> = log
+        .lines
+        .iter()
+        .skip(start)
+        .take(visible)
+        .map(|l| {
+            if log.matches(l) {
+                Line::from(Span::styled(
+                    l.clone(),
+                    Style::default().fg(Color::Black).bg(Color::Yellow),
+                ))
+            } else {
+                Line::from(l.clone())
+            }
+        })
+        .collect();
+
+    let state = if log.follow { "following" } else { "paused" };
+    let title = format!(
+        " Live log: {} [{state}, {} lines] ",
+        log.id,
+        log.lines.len()
+    );
+    let bottom = match (&log.search_input, &app.status_message) {
+        (Some(input), _) => format!(" /{input}_ [Enter search, Esc cancel] "),
+        (None, Some((msg, _))) => format!(" {msg} "),
+        (None, None) => {
+            " Esc close, j/k scroll, f follow, / search, n/N next/prev match ".to_string()
+        }
+    };
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(title)
+            .title_bottom(bottom),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(widget, area);
+}
+
+/// Color of a journal line by syslog priority
+fn priority_style(priority: u8) -> Style {
+    match priority {
+        0..=3 => Style::default().fg(Color::Red),
+        4 => Style::default().fg(Color::Yellow),
+        5 => Style::default().fg(Color::Cyan),
+        6 => Style::default(),
+        _ => Style::default().fg(Color::DarkGray),
+    }
+}
+
+/// Width of the scope column in the activity timeline
+const TIMELINE_LABEL_WIDTH: usize = 24;
+
+fn span_color(span: &RunSpan) -> Color {
+    if span.status != "completed" {
+        return Color::Yellow;
+    }
+    match span.conclusion.as_deref() {
+        Some("success") => Color::Green,
+        Some("failure" | "timed_out" | "startup_failure") => Color::Red,
+        _ => Color::DarkGray,
+    }
+}
+
+/// One timeline row: `spans` drawn as bars across `width` columns
+fn timeline_bars(spans: &[RunSpan], timeline: &Timeline, width: usize) -> Vec<Span<'static>> {
+    let from = timeline.to - chrono::Duration::hours(TIMELINE_HOURS);
+    let mut cells: Vec<Option<Color>> = vec![None; width];
+    for span in spans {
+        if let Some((first, last)) = span.columns(from, timeline.to, width) {
+            for cell in &mut cells[first..last] {
+                *cell = Some(span_color(span));
+            }
+        }
+    }
+    // Merge runs of equal cells into one span each
+    let mut out = Vec::new();
+    let mut start = 0;
+    while start < width {
+        let color = cells[start];
+        let len = cells[start..].iter().take_while(|c| **c == color).count();
+        out.push(match color {
+            Some(color) => Span::styled("█".repeat(len), Style::default().fg(color)),
+            None => Span::styled("·".repeat(len), Style::default().fg(Color::DarkGray)),
+        });
+        start += len;
+    }
+    out
+}
+
+/// Time axis labels under the timeline bars, every quarter of the window
+fn timeline_axis(width: usize) -> String {
+    let mut axis = vec![' '; width];
+    for quarter in 0..=4 {
+        let hours = TIMELINE_HOURS - TIMELINE_HOURS * quarter / 4;
+        let label: Vec<char> = if hours == 0 {
+            "now".chars().collect()
+        } else {
+            format!("-{hours}h").chars().collect()
+        };
+        let pos = (width * usize::try_from(quarter).unwrap_or(0) / 4)
+            .min(width.saturating_sub(label.len()));
+        for (i, c) in label.into_iter().enumerate() {
+            if let Some(cell) = axis.get_mut(pos + i) {
+                *cell = c;
+            }
+        }
+    }
+    axis.into_iter().collect()
+}
+
+fn draw_timeline(f: &mut Frame, timeline: &Timeline) {
+    let area = centered_rect(90, 80, f.area());
+    let inner_width = usize::from(area.width.saturating_sub(2));
+    let bar_width = inner_width.saturating_sub(TIMELINE_LABEL_WIDTH + 1);
+
+    let mut scopes: Vec<&str> = timeline.spans.iter().map(|s| s.scope.as_str()).collect();
+    scopes.sort_unstable();
+    scopes.dedup();
+
+    let mut lines = Vec::new();
+    for scope in &scopes {
+        let spans: Vec<RunSpan> = timeline
+            .spans
+            .iter()
+            .filter(|s| s.scope == *scope)
+            .cloned()
+            .collect();
+        for (i, row) in stack_spans(&spans).iter().enumerate() {
+            let label = if i == 0 {
+                truncate(scope, TIMELINE_LABEL_WIDTH)
+            } else {
+                String::new()
+            };
+            let mut line = vec![Span::raw(format!("{label:<TIMELINE_LABEL_WIDTH$} "))];
+            line.extend(timeline_bars(row, timeline, bar_width));
+            lines.push(Line::from(line));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("No workflow runs recorded in the last {TIMELINE_HOURS}h"),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    // Keep the axis visible below as many rows as fit
+    let visible = usize::from(area.height.saturating_sub(3));
+    lines.truncate(visible);
+    lines.push(Line::from(Span::styled(
+        format!("{:<TIMELINE_LABEL_WIDTH$} {}", "", timeline_axis(bar_width)),
+        Style::default().fg(Color::Gray),
+    )));
+
+    let title = Line::from(vec![
+        Span::raw(format!(" Activity (last {TIMELINE_HOURS}h) ")),
+        Span::styled("█", Style::default().fg(Color::Green)),
+        Span::raw(" success "),
+        Span::styled("█", Style::default().fg(Color::Red)),
+        Span::raw(" failure "),
+        Span::styled("█", Style::default().fg(Color::Yellow)),
+        Span::raw(" running "),
+        Span::styled("█", Style::default().fg(Color::DarkGray)),
+        Span::raw(" other [Esc close] "),
+    ]);
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(title),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(widget, area);
+}
+
+/// Centered rectangle taking the given percentage of the available area
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+fn section_title(title: &str) -> Line<'static> {
+    Line::from(Span::styled(
+        title.to_string(),
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    ))
+}
+
+fn detail_field(name: &str, value: String) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("  {name:<10}"), Style::default().fg(Color::Gray)),
+        Span::raw(value),
+    ])
+}
+
+fn draw_runner_detail(f: &mut Frame, app: &App) {
+    let Some(detail) = &app.detail else {
+        return;
+    };
+    let instance = app.instances.iter().find(|i| i.id() == detail.id);
+
+    let mut lines = vec![section_title("Instance")];
+    if let Some(instance) = instance {
+        lines.push(detail_field(
+            "Directory",
+            instance.dir.display().to_string(),
+        ));
+        lines.push(detail_field(
+            "Service",
+            instance
+                .service_name
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+        ));
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", "Status"),
+                Style::default().fg(Color::Gray),
+            ),
+            status_colored(&instance.status),
+        ]));
+        if let Some(problem) = &detail.credentials {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<10}", "Creds"),
+                    Style::default().fg(Color::Gray),
+                ),
+                Span::styled(
+                    format!("{problem}; {}", runner::reregister_hint(&detail.id)),
+                    Style::default().fg(Color::Red),
+                ),
+            ]));
+        }
+        if let Some(version) = app.runner_versions.get(&instance.scope) {
+            let value = match &app.latest_version {
+                Some(latest) if app.update_available(instance) => {
+                    format!("{version} (update available: {latest})")
+                }
+                _ => version.clone(),
+            };
+            lines.push(detail_field("Version", value));
+        }
+    }
+
+    if !detail.tags.is_empty() {
+        lines.push(detail_field("Tags", detail.tags.join(", ")));
+    }
+    if let Some(note) = &detail.note {
+        lines.push(detail_field("Note", note.clone()));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(section_title("GitHub"));
+    lines.extend(github_record_lines(app, detail));
+
+    lines.push(Line::from(""));
+    lines.push(section_title("Recent jobs"));
+    lines.extend(job_history_lines(&detail.jobs));
+
+    lines.push(Line::from(""));
+    lines.push(section_title("Toolchain"));
+    for tool in &detail.toolchain {
+        let (version, style) = match &tool.version {
+            Some(v) => (v.clone(), Style::default().fg(Color::Green)),
+            None => (
+                "not found".to_string(),
+                Style::default().fg(Color::DarkGray),
+            ),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", tool.tool()),
+                Style::default().fg(Color::Gray),
+            ),
+            Span::styled(version, style),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(section_title("Container engine"));
+    let docker_style = match &detail.docker {
+        DockerHealth::Reachable { .. } => Style::default().fg(Color::Green),
+        DockerHealth::Unreachable { .. } => Style::default().fg(Color::Red),
+        DockerHealth::NotInstalled => Style::default().fg(Color::DarkGray),
+    };
+    lines.push(Line::from(Span::styled(
+        format!("  {}", detail.docker),
+        docker_style,
+    )));
+
+    let area = centered_rect(70, 70, f.area());
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(
+                " {} [Esc close, p re-probe, D restart docker] ",
+                detail.id
+            )),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(widget, area);
+}
+
+fn draw_metrics_detail(f: &mut Frame, detail: &MetricsDetail) {
+    let mut lines = vec![section_title("Summary")];
+    lines.extend(metrics_summary_lines(&detail.metrics));
+
+    lines.push(Line::from(""));
+    lines.push(section_title("Runs per day"));
+    lines.extend(daily_lines(detail));
+
+    lines.push(Line::from(""));
+    lines.push(section_title("Durations"));
+    if detail.durations.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No completed runs",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    let max = detail.durations.iter().map(|b| b.count).max().unwrap_or(0);
+    for bucket in &detail.durations {
+        lines.push(Line::from(format!(
+            "  {:<8}{}",
+            bucket.label,
+            super::charts::count_with_bar(bucket.count, max, 20)
+        )));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(section_title("Uptime (6-hour slices)"));
+    lines.push(uptime_timeline(&detail.uptime));
+
+    lines.push(Line::from(""));
+    lines.push(section_title("Recent failures"));
+    if detail.failures.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No failed jobs recorded",
+            Style::default().fg(Color::Green),
+        )));
+    }
+    for job in &detail.failures {
+        lines.push(job_line(job));
+        lines.extend(job_error_line(job));
+    }
+
+    let area = centered_rect(70, 80, f.area());
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(
+                " {} ({METRICS_DAYS} days) [Esc close] ",
+                detail.scope
+            )),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(widget, area);
+}
+
+fn draw_run_detail(f: &mut Frame, detail: &RunDetail) {
+    let run = &detail.run;
+    let time = |t: &str| {
+        chrono::DateTime::parse_from_rfc3339(t).map_or_else(
+            |_| t.to_string(),
+            |t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            },
+        )
+    };
+    let mut lines = vec![
+        detail_field("Repo", detail.scope.to_display()),
+        detail_field(
+            "Branch",
+            run.head_branch.clone().unwrap_or_else(|| "-".to_string()),
+        ),
+        Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", "Status"),
+                Style::default().fg(Color::Gray),
+            ),
+            workflow_status_colored(&run.status, run.conclusion.as_deref()),
+        ]),
+        detail_field("Created", time(&run.created_at)),
+        detail_field("Updated", time(&run.updated_at)),
+        detail_field("URL", run.html_url.clone()),
+        Line::from(""),
+        section_title("Jobs"),
+    ];
+    if detail.jobs.is_empty() {
+        let message = if run.status == "completed" {
+            "  No jobs recorded for this run"
+        } else {
+            "  Jobs are recorded once the run completes"
+        };
+        lines.push(Line::from(Span::styled(
+            message,
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for job in &detail.jobs {
+        lines.push(run_job_line(job));
+        lines.extend(job_error_line(job));
+    }
+
+    let name = run.name.as_deref().unwrap_or("workflow run");
+    let area = centered_rect(70, 70, f.area());
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(" {name} #{} [o open, Esc close] ", run.id)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(widget, area);
+}
+
+/// A job of the run detail view: its conclusion, duration, runner and name
+fn run_job_line(job: &JobRecord) -> Line<'static> {
+    let duration = job
+        .duration_seconds()
+        .and_then(|s| u32::try_from(s).ok())
+        .map_or_else(|| "-".to_string(), format_duration);
+    let conclusion = job.conclusion.as_deref().unwrap_or("-");
+    let color = match conclusion {
+        "success" => Color::Green,
+        c if notify::is_failed(Some(c)) => Color::Red,
+        _ => Color::DarkGray,
+    };
+    Line::from(vec![
+        Span::styled(format!("  {conclusion:<10}"), Style::default().fg(color)),
+        Span::raw(format!(
+            "{duration:>8}  {:<20} {}",
+            truncate(job.runner.as_deref().unwrap_or("-"), 20),
+            job.name
+        )),
+    ])
+}
+
+/// The first error line of a failed job, under its line
+fn job_error_line(job: &JobRecord) -> Option<Line<'static>> {
+    let error = job.error.as_ref()?;
+    Some(Line::from(Span::styled(
+        format!("      ↳ {error}"),
+        Style::default().fg(Color::Red).add_modifier(Modifier::DIM),
+    )))
+}
+
+/// Runs, success rate, durations and uptime of the period, with their trends
+fn metrics_summary_lines(m: &ScopeMetrics) -> Vec<Line<'static>> {
+    let seconds = |s: Option<u32>| s.map_or("-".to_string(), format_duration);
+    let trend = |t: Option<TrendChange>| {
+        Span::styled(
+            t.map_or(String::new(), |t| format!("  {}", t.display())),
+            trend_color(t),
+        )
+    };
+    let mut runs = detail_field(
+        "Runs",
+        format!(
+            "{} ({} failed), {} succeeded",
+            m.total_runs,
+            m.failed_runs,
+            format_rate(m.success_rate)
+        ),
+    );
+    runs.spans.push(trend(m.success_trend));
+    let mut average = detail_field("Average", seconds(m.avg_duration_seconds));
+    average.spans.push(trend(m.duration_trend));
+    vec![
+        runs,
+        average,
+        detail_field(
+            "Spread",
+            format!(
+                "p50 {}, p90 {}, p99 {}, min {}, max {}",
+                seconds(m.p50_duration_seconds),
+                seconds(m.p90_duration_seconds),
+                seconds(m.p99_duration_seconds),
+                seconds(m.min_duration_seconds),
+                seconds(m.max_duration_seconds)
+            ),
+        ),
+        detail_field(
+            "Uptime",
+            m.runner_uptime
+                .map_or("-".to_string(), |u| format!("{u:.1}%")),
+        ),
+    ]
+}
+
+/// Sparklines of the runs and failures of each day, from the oldest to today
+fn daily_lines(detail: &MetricsDetail) -> Vec<Line<'static>> {
+    use super::charts::mini_sparkline;
+
+    let days = detail.daily.len();
+    let totals: Vec<u32> = detail.daily.iter().map(|d| d.total_runs).collect();
+    let failed: Vec<u32> = detail.daily.iter().map(|d| d.failed_runs).collect();
+    let peak = |values: &[u32]| values.iter().max().copied().unwrap_or(0);
+    let range = match (detail.daily.first(), detail.daily.last()) {
+        (Some(first), Some(last)) => format!("{} to {}", first.date, last.date),
+        _ => String::new(),
+    };
+    vec![
+        Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", "Runs"),
+                Style::default().fg(Color::Gray),
+            ),
+            Span::styled(
+                mini_sparkline(&totals, days),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw(format!("  peak {}/day", peak(&totals))),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", "Failed"),
+                Style::default().fg(Color::Gray),
+            ),
+            Span::styled(
+                mini_sparkline(&failed, days),
+                Style::default().fg(Color::Red),
+            ),
+            Span::raw(format!("  peak {}/day", peak(&failed))),
+        ]),
+        Line::from(Span::styled(
+            format!("  {:<10}{range}", ""),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ]
+}
+
+/// One block per slice, green when the runners were up nearly all the time, yellow for
+/// some downtime, red for a lot; grey dots where no snapshots were taken
+fn uptime_timeline(slices: &[Option<f64>]) -> Line<'static> {
+    let mut spans = vec![Span::raw("  ")];
+    spans.extend(slices.iter().map(|slice| match slice {
+        Some(up) if *up >= 99.0 => Span::styled("█", Style::default().fg(Color::Green)),
+        Some(up) if *up >= 90.0 => Span::styled("█", Style::default().fg(Color::Yellow)),
+        Some(_) => Span::styled("█", Style::default().fg(Color::Red)),
+        None => Span::styled("·", Style::default().fg(Color::DarkGray)),
+    }));
+    Line::from(spans)
+}
+
+/// The jobs the runner executed, newest first, with a failure count so a machine that
+/// fails everything stands out
+fn job_history_lines(jobs: &[JobRecord]) -> Vec<Line<'static>> {
+    if jobs.is_empty() {
+        return vec![Line::from(Span::styled(
+            "  No jobs recorded yet",
+            Style::default().fg(Color::DarkGray),
+        ))];
+    }
+    let failed = jobs
+        .iter()
+        .filter(|j| notify::is_failed(j.conclusion.as_deref()))
+        .count();
+    let mut lines = vec![Line::from(Span::styled(
+        format!("  {failed} of the last {} failed", jobs.len()),
+        if failed > 0 {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default().fg(Color::Green)
+        },
+    ))];
+    lines.extend(jobs.iter().map(job_line));
+    lines
+}
+
+/// When a job ran, its conclusion, duration, queue wait, scope and name
+fn job_line(job: &JobRecord) -> Line<'static> {
+    let when = job.completed_at.or(job.started_at).map_or_else(
+        || "-".to_string(),
+        |t| {
+            t.with_timezone(&chrono::Local)
+                .format("%m-%d %H:%M")
+                .to_string()
+        },
+    );
+    let duration = job
+        .duration_seconds()
+        .and_then(|s| u32::try_from(s).ok())
+        .map_or_else(|| "-".to_string(), format_duration);
+    let wait = job
+        .queue_wait_seconds
+        .and_then(|s| u32::try_from(s).ok())
+        .map_or_else(|| "-".to_string(), format_duration);
+    let conclusion = job.conclusion.as_deref().unwrap_or("-");
+    let color = match conclusion {
+        "success" => Color::Green,
+        c if notify::is_failed(Some(c)) => Color::Red,
+        _ => Color::DarkGray,
+    };
+    let title = match &job.workflow {
+        Some(workflow) => format!("{workflow} / {}", job.name),
+        None => job.name.clone(),
+    };
+    Line::from(vec![
+        Span::styled(format!("  {when:<12}"), Style::default().fg(Color::Gray)),
+        Span::styled(format!("{conclusion:<10}"), Style::default().fg(color)),
+        Span::raw(format!(
+            "{duration:>8} {wait:>8}  {:<24} {title}",
+            job.scope
+        )),
+    ])
+}
+
+/// The scope's runner records on GitHub, marking the one registered by this instance
+fn github_record_lines(app: &App, detail: &RunnerDetail) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let gh_runners = app
+        .github_runners
+        .iter()
+        .find(|(scope, _)| scope == &detail.id.scope)
+        .map(|(_, runners)| runners.as_slice())
+        .unwrap_or_default();
+    if gh_runners.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No runner records",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for r in gh_runners {
+        let labels: Vec<&str> = r.labels.iter().map(|l| l.name.as_str()).collect();
+        lines.push(detail_field(
+            "Runner",
+            format!(
+                "{} ({}{}) [{}]{}",
+                r.name,
+                r.status,
+                if r.busy { ", busy" } else { "" },
+                labels.join(", "),
+                if r.name == detail.runner_name {
+                    " ← this instance"
+                } else {
+                    ""
+                }
+            ),
+        ));
+    }
+    lines
+}
+
+pub fn draw_wizard(f: &mut Frame, wizard: &Wizard) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "runner-mgr has not been set up on this host yet.",
+            Style::default().fg(Color::Gray),
+        )),
+        Line::from(""),
+        section_title(&format!(
+            "Step {} of 4: {}",
+            wizard.step.number(),
+            wizard.step.title()
+        )),
+        Line::from(""),
+    ];
+
+    let input = |value: String| {
+        Line::from(vec![
+            Span::styled("  > ", Style::default().fg(Color::Cyan)),
+            Span::raw(value),
+            Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+        ])
+    };
+
+    match wizard.step {
+        WizardStep::Token => {
+            lines.push(Line::from(
+                "  Enter a GitHub Personal Access Token (needs 'repo' scope).",
+            ));
+            lines.push(Line::from(format!(
+                "  Create one at: {}/settings/tokens",
+                wizard.config().web_url()
+            )));
+            lines.push(Line::from(""));
+            // Never echo the token itself
+            lines.push(input("\u{2022}".repeat(wizard.pat.chars().count())));
+        }
+        WizardStep::RunnerUser => {
+            lines.push(Line::from("  System account the runner services run as."));
+            lines.push(Line::from(""));
+            lines.push(input(wizard.runner_user.clone()));
+        }
+        WizardStep::Directory => {
+            lines.push(Line::from(
+                "  Base directory for the runner template and instances.",
+            ));
+            lines.push(Line::from(""));
+            lines.push(input(wizard.instances_base.clone()));
+        }
+        WizardStep::Confirm => {
+            lines.push(detail_field(
+                "GitHub",
+                wizard.github_user.clone().unwrap_or_default(),
+            ));
+            lines.push(detail_field("User", wizard.runner_user.clone()));
+            lines.push(detail_field("Directory", wizard.instances_base.clone()));
+            lines.push(Line::from(""));
+            lines.push(Line::from(
+                "  The runner template is downloaded after the wizard closes;",
+            ));
+            lines.push(Line::from("  sudo may ask for your password."));
+        }
+    }
+
+    lines.push(Line::from(""));
+    if wizard.validating {
+        lines.push(Line::from(Span::styled(
+            "  Validating token...",
+            Style::default().fg(Color::Yellow),
+        )));
+    } else if let Some(error) = &wizard.error {
+        lines.push(Line::from(Span::styled(
+            format!("  {error}"),
+            Style::default().fg(Color::Red),
+        )));
+    } else if let Some(user) = wizard
+        .github_user
+        .as_ref()
+        .filter(|_| wizard.step == WizardStep::RunnerUser)
+    {
+        lines.push(Line::from(Span::styled(
+            format!("  Authenticated as: {user}"),
+            Style::default().fg(Color::Green),
+        )));
+    }
+
+    let hint = match wizard.step {
+        WizardStep::Token => " Enter: validate | Esc: quit ",
+        WizardStep::Confirm => " Enter: finish setup | Esc: back ",
+        _ => " Enter: next | Esc: back ",
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Welcome to runner-mgr ")
+        .title_bottom(hint)
+        .border_style(Style::default().fg(Color::Cyan));
+    f.render_widget(Paragraph::new(lines).block(block), area);
 }
 
 fn draw_header(f: &mut Frame, app: &App, area: Rect) {
@@ -91,7 +995,11 @@ fn draw_runners_panel(f: &mut Frame, app: &App, area: Rect) {
         Style::default().fg(Color::Gray)
     };
 
-    let header_cells = ["Target", "Local", "GitHub", "Busy"].iter().map(|h| {
+    let header_cells = [
+        "Target", "Local", "GitHub", "Busy", "Queued", "Version", "Labels",
+    ]
+    .iter()
+    .map(|h| {
         Cell::from(*h).style(
             Style::default()
                 .fg(Color::Yellow)
@@ -100,6 +1008,10 @@ fn draw_runners_panel(f: &mut Frame, app: &App, area: Rect) {
     });
     let header = Row::new(header_cells).height(1);
 
+    // What's left after the borders, the other columns (Target at its minimum) and
+    // the spacing between them
+    let labels_width = area.width.saturating_sub(71).clamp(10, 40);
+
     let rows: Vec<Row> = app
         .instances
         .iter()
@@ -107,37 +1019,17 @@ fn draw_runners_panel(f: &mut Frame, app: &App, area: Rect) {
         .map(|(i, instance)| {
             let local_status = status_colored(&instance.status);
 
-            // Find matching GitHub runner info
-            let gh_runner = app
-                .github_runners
-                .iter()
-                .find(|(scope, _)| scope == &instance.scope)
-                .and_then(|(_, runners)| runners.first());
-
-            let (gh_status, busy) = if let Some(r) = gh_runner {
-                let status_style = match r.status.as_str() {
-                    "online" => Style::default().fg(Color::Green),
-                    "offline" => Style::default().fg(Color::Red),
-                    _ => Style::default().fg(Color::Yellow),
-                };
-                let busy_style = if r.busy {
-                    Style::default().fg(Color::Yellow)
-                } else {
-                    Style::default().fg(Color::Gray)
-                };
-                (
-                    Span::styled(&r.status, status_style),
-                    Span::styled(if r.busy { "yes" } else { "no" }, busy_style),
-                )
-            } else {
-                (
-                    Span::styled("-", Style::default().fg(Color::DarkGray)),
-                    Span::styled("-", Style::default().fg(Color::DarkGray)),
-                )
-            };
+            let (gh_status, busy) = github_cells(app, instance);
 
-            // Format scope display with [org] prefix for organizations
-            let scope_display = format_scope_display(&instance.scope, 30);
+            // Format scope display with [org] prefix for organizations, and the
+            // instance name for additional instances
+            let scope_display = match &instance.name {
+                Some(name) => format!(
+                    "{}@{name}",
+                    format_scope_display(&instance.scope, 29 - name.len().min(20))
+                ),
+                None => format_scope_display(&instance.scope, 30),
+            };
 
             let style = if is_active && i == app.selected_runner {
                 Style::default()
@@ -152,6 +1044,9 @@ fn draw_runners_panel(f: &mut Frame, app: &App, area: Rect) {
                 Cell::from(local_status),
                 Cell::from(gh_status),
                 Cell::from(busy),
+                Cell::from(queued_cell(app, instance)),
+                Cell::from(version_cell(app, instance)),
+                Cell::from(labels_cell(app, instance, labels_width)),
             ])
             .style(style)
         })
@@ -169,8 +1064,11 @@ fn draw_runners_panel(f: &mut Frame, app: &App, area: Rect) {
         [
             Constraint::Min(20),
             Constraint::Length(10),
-            Constraint::Length(10),
+            Constraint::Length(11),
+            Constraint::Length(6),
             Constraint::Length(6),
+            Constraint::Length(10),
+            Constraint::Length(labels_width),
         ],
     )
     .header(header)
@@ -179,13 +1077,148 @@ fn draw_runners_panel(f: &mut Frame, app: &App, area: Rect) {
             .borders(Borders::ALL)
             .border_style(border_style)
             .title(format!(
-                " Runners ({running_count}/{runner_count} running) "
+                " Runners ({running_count}/{runner_count} running){}{} ",
+                app.tag_filter
+                    .as_ref()
+                    .map(|tag| format!(" [tag: {tag}]"))
+                    .unwrap_or_default(),
+                if app.watchdog.is_some() {
+                    " [watchdog]"
+                } else {
+                    ""
+                }
             )),
     );
 
     f.render_widget(table, area);
 }
 
+/// Workflow runs waiting to start in the instance's repository: yellow while any are
+/// queued, red when the runner is also stopped or busy and can't pick them up
+fn queued_cell(app: &App, instance: &RunnerInstance) -> Span<'static> {
+    let Some(&queued) = app.queued_runs.get(&instance.scope) else {
+        return Span::styled("-", Style::default().fg(Color::DarkGray));
+    };
+    if queued == 0 {
+        return Span::styled("0", Style::default().fg(Color::Gray));
+    }
+    let busy = app
+        .github_runners
+        .iter()
+        .find(|(s, _)| *s == instance.scope)
+        .and_then(|(_, runners)| app.github_record(instance, runners))
+        .is_some_and(|r| r.busy);
+    let style = if instance.status != RunnerStatus::Running || busy {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Yellow)
+    };
+    Span::styled(queued.to_string(), style)
+}
+
+/// Custom labels of the instance's runner on GitHub, fitted to `width`
+fn labels_cell(app: &App, instance: &RunnerInstance, width: u16) -> Span<'static> {
+    let custom: Vec<&str> = app
+        .github_runners
+        .iter()
+        .find(|(scope, _)| *scope == instance.scope)
+        .and_then(|(_, runners)| app.github_record(instance, runners))
+        .map(|record| {
+            record
+                .labels
+                .iter()
+                .filter(|l| l.is_custom())
+                .map(|l| l.name.as_str())
+                .collect()
+        })
+        .unwrap_or_default();
+    if custom.is_empty() {
+        Span::styled("-", Style::default().fg(Color::DarkGray))
+    } else {
+        Span::raw(labels::fit_labels(&custom, usize::from(width)))
+    }
+}
+
+/// Installed runner version, marked with `↑` in yellow when an update is available
+fn version_cell<'a>(app: &'a App, instance: &RunnerInstance) -> Span<'a> {
+    match app.runner_versions.get(&instance.scope) {
+        Some(version) if app.update_available(instance) => {
+            Span::styled(format!("{version}↑"), Style::default().fg(Color::Yellow))
+        }
+        Some(version) => Span::raw(version.as_str()),
+        None => Span::styled("-", Style::default().fg(Color::DarkGray)),
+    }
+}
+
+/// GitHub status and busy cells for a runner row; scopes whose API calls keep failing
+/// show the error state instead
+fn github_cells<'a>(app: &'a App, instance: &RunnerInstance) -> (Span<'a>, Span<'a>) {
+    let scope = &instance.scope;
+    let gh_runner = app
+        .github_runners
+        .iter()
+        .find(|(s, _)| s == scope)
+        .and_then(|(_, runners)| app.github_record(instance, runners));
+
+    let failing = app
+        .scope_errors
+        .get(scope)
+        .filter(|e| e.failures >= ERROR_THRESHOLD);
+
+    if let Some(err) = failing {
+        let label = if err.unreachable() {
+            "unreachable"
+        } else {
+            "⚠ error"
+        };
+        (
+            Span::styled(
+                label,
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("-", Style::default().fg(Color::DarkGray)),
+        )
+    } else if let Some(r) = gh_runner {
+        let status_style = match r.status.as_str() {
+            "online" => Style::default().fg(Color::Green),
+            "offline" => Style::default().fg(Color::Red),
+            _ => Style::default().fg(Color::Yellow),
+        };
+        let busy_style = if r.busy {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        (
+            Span::styled(&r.status, status_style),
+            Span::styled(if r.busy { "yes" } else { "no" }, busy_style),
+        )
+    } else {
+        (
+            Span::styled("-", Style::default().fg(Color::DarkGray)),
+            Span::styled("-", Style::default().fg(Color::DarkGray)),
+        )
+    }
+}
+
+/// Title of the Workflow Runs panel, naming repositories whose runs aren't polled
+fn workflows_title(app: &App, total_runs: usize) -> String {
+    let mut dormant: Vec<String> = app
+        .dormant
+        .dormant()
+        .into_iter()
+        .map(|(scope, reason)| format!("{} {}", scope.to_display(), reason.label()))
+        .collect();
+    if dormant.is_empty() {
+        return format!(" Workflow Runs ({total_runs}) ");
+    }
+    dormant.sort();
+    format!(
+        " Workflow Runs ({total_runs}) · not polled: {} ",
+        dormant.join(", ")
+    )
+}
+
 /// Format a scope for display in the TUI, with [org] prefix for organizations
 fn format_scope_display(scope: &RunnerScope, max_len: usize) -> String {
     match scope {
@@ -283,19 +1316,21 @@ fn draw_workflows_panel(f: &mut Frame, app: &App, area: Rect) {
         Block::default()
             .borders(Borders::ALL)
             .border_style(border_style)
-            .title(format!(" Workflow Runs ({total_runs}) ")),
+            .title(workflows_title(app, total_runs)),
     );
 
     f.render_widget(table, area);
 }
 
 fn draw_logs_panel(f: &mut Frame, app: &App, area: Rect) {
-    let log_count = app.log_messages.len();
+    let log_count = app.logs.messages.len();
     let visible_lines = (area.height.saturating_sub(2)) as usize; // account for borders
 
-    // Get the visible slice of logs
-    let start = app.log_scroll.min(log_count.saturating_sub(1));
-    let end = (start + visible_lines).min(log_count);
+    // Get the visible slice of logs, `scroll` lines back from the newest
+    let end = log_count
+        .saturating_sub(app.logs.scroll)
+        .max(visible_lines.min(log_count));
+    let start = end.saturating_sub(visible_lines);
 
     let log_lines: Vec<Line> = if log_count == 0 {
         vec![Line::from(Span::styled(
@@ -303,7 +1338,8 @@ fn draw_logs_panel(f: &mut Frame, app: &App, area: Rect) {
             Style::default().fg(Color::DarkGray),
         ))]
     } else {
-        app.log_messages
+        app.logs
+            .messages
             .iter()
             .skip(start)
             .take(end - start)
@@ -327,9 +1363,10 @@ fn draw_logs_panel(f: &mut Frame, app: &App, area: Rect) {
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Magenta))
             .title(format!(
-                " Verbose Logs ({}/{}) [PgUp/PgDn scroll, c clear] ",
-                if log_count > 0 { start + 1 } else { 0 },
-                log_count
+                " Verbose Logs ({}/{}) [PgUp/PgDn scroll, F follow: {}, c clear] ",
+                end,
+                log_count,
+                if app.logs.follow { "on" } else { "off" }
             )),
     );
 
@@ -392,14 +1429,50 @@ fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         ),
-        Span::raw(" stop"),
+        Span::raw(" stop  "),
+        Span::styled(
+            "m",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" menu"),
     ]);
 
     let help_widget =
         Paragraph::new(help).block(Block::default().borders(Borders::ALL).title(" Keys "));
 
-    // Status message or loading indicator
-    let status_text = if app.loading {
+    let status_text = status_line(app);
+
+    let status_widget =
+        Paragraph::new(status_text).block(Block::default().borders(Borders::ALL).title(" Status "));
+
+    f.render_widget(help_widget, chunks[0]);
+    f.render_widget(status_widget, chunks[1]);
+}
+
+/// Confirmation prompt, pending key sequence, rate-limit countdown, status message or
+/// loading indicator
+fn status_line(app: &App) -> Line<'static> {
+    if let Some(confirm) = &app.confirm {
+        Line::from(Span::styled(
+            confirm.prompt.clone(),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ))
+    } else if let Some(key) = app.keys.pending() {
+        Line::from(Span::styled(
+            format!("{key}-"),
+            Style::default().fg(Color::Cyan),
+        ))
+    } else if let Some(left) = app.rate_limit_pause() {
+        Line::from(Span::styled(
+            format!(
+                "GitHub rate limit: API calls resume in {}s",
+                left.as_secs() + 1
+            ),
+            Style::default().fg(Color::Red),
+        ))
+    } else if app.loading {
         Line::from(Span::styled(
             "Loading...",
             Style::default().fg(Color::Yellow),
@@ -410,22 +1483,35 @@ fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
             Style::default().fg(Color::Yellow),
         ))
     } else {
-        let secs = app.last_refresh.elapsed().as_secs();
-        Line::from(Span::styled(
+        let secs = app.last_refresh.map_or(0, |last| last.elapsed().as_secs());
+        let mut spans = vec![Span::styled(
             format!(
                 "Last refresh: {}s ago (auto: {}s)",
                 secs,
-                super::REFRESH_INTERVAL.as_secs()
+                app.config.refresh_interval().as_secs()
             ),
             Style::default().fg(Color::DarkGray),
-        ))
-    };
-
-    let status_widget =
-        Paragraph::new(status_text).block(Block::default().borders(Borders::ALL).title(" Status "));
-
-    f.render_widget(help_widget, chunks[0]);
-    f.render_widget(status_widget, chunks[1]);
+        )];
+        if let Some(quota) = scheduler::rate_limit() {
+            // Under a tenth of the hourly quota left
+            let color = if quota.remaining * 10 < quota.limit {
+                Color::Yellow
+            } else {
+                Color::DarkGray
+            };
+            spans.push(Span::styled(
+                format!("  API {}/{}", quota.remaining, quota.limit),
+                Style::default().fg(color),
+            ));
+        }
+        if recording::replaying() {
+            spans.push(Span::styled(
+                "  Replaying a recording",
+                Style::default().fg(Color::Magenta),
+            ));
+        }
+        Line::from(spans)
+    }
 }
 
 fn status_colored(status: &RunnerStatus) -> Span<'static> {
@@ -511,7 +1597,7 @@ fn draw_success_rates(f: &mut Frame, app: &App, area: Rect) {
         Style::default().fg(Color::Gray)
     };
 
-    let header_cells = ["Scope", "Rate", "Runs", ""].iter().map(|h| {
+    let header_cells = ["Scope", "Rate", "Runs", "Trend", "SLO"].iter().map(|h| {
         Cell::from(*h).style(
             Style::default()
                 .fg(Color::Yellow)
@@ -542,8 +1628,9 @@ fn draw_success_rates(f: &mut Frame, app: &App, area: Rect) {
 
             let trend = metrics
                 .success_trend
-                .map_or("-".to_string(), |t| t.symbol().to_string());
+                .map_or("-".to_string(), TrendChange::display);
             let trend_style = trend_color(metrics.success_trend);
+            let (slo, slo_style) = slo_summary(&metrics.slo);
 
             let style = if is_active && i == app.selected_metric {
                 Style::default()
@@ -558,6 +1645,7 @@ fn draw_success_rates(f: &mut Frame, app: &App, area: Rect) {
                 Cell::from(Span::styled(rate, rate_style)),
                 Cell::from(runs_bar),
                 Cell::from(Span::styled(trend, trend_style)),
+                Cell::from(Span::styled(slo, slo_style)),
             ])
             .style(style)
         })
@@ -569,7 +1657,8 @@ fn draw_success_rates(f: &mut Frame, app: &App, area: Rect) {
             Constraint::Min(20),
             Constraint::Length(12),
             Constraint::Length(10),
-            Constraint::Length(6),
+            Constraint::Length(TREND_COLUMN_WIDTH),
+            Constraint::Length(SLO_COLUMN_WIDTH),
         ],
     )
     .header(header)
@@ -583,10 +1672,46 @@ fn draw_success_rates(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(table, area);
 }
 
+/// Direction plus the percent change, e.g. `↑ 12%`
+const TREND_COLUMN_WIDTH: u16 = 7;
+
+/// State symbol plus the remaining error budget
+const SLO_COLUMN_WIDTH: u16 = 7;
+
+/// The worst objective's state and remaining error budget, e.g. `! 12%`
+fn slo_summary(statuses: &[SloStatus]) -> (String, Style) {
+    let Some(worst) = worst_slo(statuses) else {
+        return ("-".to_string(), Style::default().fg(Color::DarkGray));
+    };
+    let color = match worst.state {
+        SloState::NoData => Color::DarkGray,
+        SloState::Met => Color::Green,
+        SloState::AtRisk => Color::Yellow,
+        SloState::Violated => Color::Red,
+    };
+    let text = match worst.state {
+        SloState::NoData => worst.state.symbol().to_string(),
+        _ => format!("{} {}", worst.state.symbol(), worst.budget_display()),
+    };
+    (text, Style::default().fg(color))
+}
+
+/// Sparkline plus peak count
+const QUEUE_COLUMN_WIDTH: u16 = 19;
+
+/// Percentage plus a self-update count
+const UPTIME_COLUMN_WIDTH: u16 = 11;
+
 fn draw_duration_stats(f: &mut Frame, app: &App, area: Rect) {
+    use super::charts::mini_sparkline;
+
     let border_style = Style::default().fg(Color::Gray);
 
-    let header_cells = ["Scope", "Avg", "Min", "Max", "Uptime"].iter().map(|h| {
+    let header_cells = [
+        "Scope", "Avg", "Trend", "p50", "p90", "Min", "Max", "Wait", "Wait p95", "Uptime", "Queue",
+    ]
+    .iter()
+    .map(|h| {
         Cell::from(*h).style(
             Style::default()
                 .fg(Color::Yellow)
@@ -603,25 +1728,54 @@ fn draw_duration_stats(f: &mut Frame, app: &App, area: Rect) {
             let avg = metrics
                 .avg_duration_seconds
                 .map_or("-".to_string(), format_duration);
+            let trend = metrics
+                .duration_trend
+                .map_or("-".to_string(), TrendChange::display);
+            let p50 = metrics
+                .p50_duration_seconds
+                .map_or("-".to_string(), format_duration);
+            let p90 = metrics
+                .p90_duration_seconds
+                .map_or("-".to_string(), format_duration);
             let min = metrics
                 .min_duration_seconds
                 .map_or("-".to_string(), format_duration);
             let max = metrics
                 .max_duration_seconds
                 .map_or("-".to_string(), format_duration);
-            let uptime = metrics
-                .runner_uptime
-                .map_or("-".to_string(), |u| format!("{u:.1}%"));
+            let wait = metrics
+                .avg_queue_wait_seconds
+                .map_or("-".to_string(), format_duration);
+            let wait_p95 = metrics
+                .p95_queue_wait_seconds
+                .map_or("-".to_string(), format_duration);
+            // Self-updates are marked, as their offline time is left out
+            let uptime = match (metrics.runner_uptime, metrics.self_updates) {
+                (None, _) => "-".to_string(),
+                (Some(u), 0) => format!("{u:.1}%"),
+                (Some(u), n) => format!("{u:.1}% ↻{n}"),
+            };
             let uptime_style = metrics
                 .runner_uptime
                 .map_or(Style::default().fg(Color::DarkGray), rate_color);
+            let peak = metrics.queue_history.iter().max().copied().unwrap_or(0);
+            let queue = format!(
+                "{} {peak}",
+                mini_sparkline(&metrics.queue_history, QUEUE_HISTORY_BUCKETS)
+            );
 
             Row::new(vec![
                 Cell::from(scope_display),
                 Cell::from(avg),
+                Cell::from(Span::styled(trend, trend_color(metrics.duration_trend))),
+                Cell::from(p50),
+                Cell::from(p90),
                 Cell::from(min),
                 Cell::from(max),
+                Cell::from(wait),
+                Cell::from(wait_p95),
                 Cell::from(Span::styled(uptime, uptime_style)),
+                Cell::from(Span::styled(queue, Style::default().fg(Color::Cyan))),
             ])
         })
         .collect();
@@ -631,9 +1785,15 @@ fn draw_duration_stats(f: &mut Frame, app: &App, area: Rect) {
         [
             Constraint::Min(15),
             Constraint::Length(8),
+            Constraint::Length(TREND_COLUMN_WIDTH),
+            Constraint::Length(8),
             Constraint::Length(8),
             Constraint::Length(8),
             Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(UPTIME_COLUMN_WIDTH),
+            Constraint::Length(QUEUE_COLUMN_WIDTH),
         ],
     )
     .header(header)
@@ -641,31 +1801,12 @@ fn draw_duration_stats(f: &mut Frame, app: &App, area: Rect) {
         Block::default()
             .borders(Borders::ALL)
             .border_style(border_style)
-            .title(" Job Durations & Uptime "),
+            .title(" Job Durations, Uptime & Queue "),
     );
 
     f.render_widget(table, area);
 }
 
-/// Format a duration in seconds as human-readable
-fn format_duration(seconds: u32) -> String {
-    if seconds < 60 {
-        format!("{seconds}s")
-    } else if seconds < 3600 {
-        let mins = seconds / 60;
-        let secs = seconds % 60;
-        if secs == 0 {
-            format!("{mins}m")
-        } else {
-            format!("{mins}m {secs}s")
-        }
-    } else {
-        let hours = seconds / 3600;
-        let mins = (seconds % 3600) / 60;
-        format!("{hours}h {mins}m")
-    }
-}
-
 /// Format a percentage rate
 fn format_rate(rate: f64) -> String {
     format!("{rate:.1}%")
@@ -682,12 +1823,12 @@ fn rate_color(rate: f64) -> Style {
     }
 }
 
-/// Get color for a trend
-fn trend_color(trend: Option<Trend>) -> Style {
-    match trend {
-        Some(Trend::Up) => Style::default().fg(Color::Green),
-        Some(Trend::Down) => Style::default().fg(Color::Red),
-        Some(Trend::Stable) | None => Style::default().fg(Color::DarkGray),
+/// Green for improvements, red for regressions, whichever way the metric moved
+fn trend_color(trend: Option<TrendChange>) -> Style {
+    match trend.and_then(TrendChange::improved) {
+        Some(true) => Style::default().fg(Color::Green),
+        Some(false) => Style::default().fg(Color::Red),
+        None => Style::default().fg(Color::DarkGray),
     }
 }
 
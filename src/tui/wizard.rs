@@ -0,0 +1,186 @@
+//! First-run setup wizard, shown when the dashboard starts without a config
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::path::Path;
+
+use super::super::config::{self, Config};
+use super::super::github::GitHubClient;
+use super::super::privilege::Escalation;
+use super::super::users;
+
+const DEFAULT_RUNNER_USER: &str = "github";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WizardStep {
+    Token,
+    RunnerUser,
+    Directory,
+    Confirm,
+}
+
+impl WizardStep {
+    pub fn title(self) -> &'static str {
+        match self {
+            WizardStep::Token => "GitHub token",
+            WizardStep::RunnerUser => "Runner user",
+            WizardStep::Directory => "Instances directory",
+            WizardStep::Confirm => "Confirm",
+        }
+    }
+
+    pub fn number(self) -> usize {
+        match self {
+            WizardStep::Token => 1,
+            WizardStep::RunnerUser => 2,
+            WizardStep::Directory => 3,
+            WizardStep::Confirm => 4,
+        }
+    }
+}
+
+pub struct Wizard {
+    pub step: WizardStep,
+    pub pat: String,
+    /// GitHub login the token authenticated as
+    pub github_user: Option<String>,
+    pub runner_user: String,
+    pub instances_base: String,
+    pub error: Option<String>,
+    /// Set while the token is being checked against the GitHub API
+    pub validating: bool,
+    pub cancelled: bool,
+    pub finished: bool,
+}
+
+impl Wizard {
+    pub fn new() -> Self {
+        Self {
+            step: WizardStep::Token,
+            pat: String::new(),
+            github_user: None,
+            runner_user: DEFAULT_RUNNER_USER.to_string(),
+            instances_base: Config::default_instances_base().to_string(),
+            error: None,
+            validating: false,
+            cancelled: false,
+            finished: false,
+        }
+    }
+
+    /// The text field edited on the current step, if any
+    fn input_mut(&mut self) -> Option<&mut String> {
+        match self.step {
+            WizardStep::Token => Some(&mut self.pat),
+            WizardStep::RunnerUser => Some(&mut self.runner_user),
+            WizardStep::Directory => Some(&mut self.instances_base),
+            WizardStep::Confirm => None,
+        }
+    }
+
+    /// Handle a key press. Returns true when the token should be validated
+    /// (the caller redraws first so "Validating..." is visible during the request).
+    pub fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        match code {
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.cancelled = true;
+            }
+            KeyCode::Esc => self.back(),
+            KeyCode::Enter => return self.next(),
+            KeyCode::Backspace => {
+                if let Some(input) = self.input_mut() {
+                    input.pop();
+                }
+            }
+            KeyCode::Char(c) => {
+                if let Some(input) = self.input_mut() {
+                    input.push(c);
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn back(&mut self) {
+        self.error = None;
+        self.step = match self.step {
+            WizardStep::Token => {
+                self.cancelled = true;
+                WizardStep::Token
+            }
+            WizardStep::RunnerUser => WizardStep::Token,
+            WizardStep::Directory => WizardStep::RunnerUser,
+            WizardStep::Confirm => WizardStep::Directory,
+        };
+    }
+
+    fn next(&mut self) -> bool {
+        self.error = None;
+        match self.step {
+            WizardStep::Token => {
+                if let Err(e) = config::validate_pat_format(self.pat.trim()) {
+                    self.error = Some(e.to_string());
+                    return false;
+                }
+                self.validating = true;
+                return true;
+            }
+            WizardStep::RunnerUser => {
+                let user = self.runner_user.trim().to_string();
+                if user.is_empty() {
+                    self.error = Some("Runner user cannot be empty".to_string());
+                } else if !cfg!(windows) && !users::exists(&user) {
+                    self.error = Some(format!(
+                        "User '{user}' does not exist; create it or run `runner-mgr init`"
+                    ));
+                } else {
+                    self.runner_user = user;
+                    self.step = WizardStep::Directory;
+                }
+            }
+            WizardStep::Directory => {
+                let dir = self.instances_base.trim().trim_end_matches('/').to_string();
+                if Path::new(&dir).is_absolute() {
+                    self.instances_base = dir;
+                    self.step = WizardStep::Confirm;
+                } else {
+                    self.error = Some("Directory must be an absolute path".to_string());
+                }
+            }
+            WizardStep::Confirm => self.finished = true,
+        }
+        false
+    }
+
+    /// Check the entered token against the GitHub API and advance on success
+    pub async fn validate_token(&mut self) {
+        let pat = self.pat.trim().to_string();
+        let config = Config {
+            github_pat: pat.clone(),
+            ..self.config()
+        };
+        match GitHubClient::for_config(&config).get_user().await {
+            Ok(user) => {
+                self.pat = pat;
+                self.github_user = Some(user.login);
+                self.step = WizardStep::RunnerUser;
+            }
+            Err(e) => self.error = Some(format!("Invalid token or network error: {e}")),
+        }
+        self.validating = false;
+    }
+
+    /// Build the config collected by the wizard
+    pub fn config(&self) -> Config {
+        Config {
+            github_pat: self.pat.clone(),
+            github_user: self.github_user.clone().unwrap_or_default(),
+            runner_user: self.runner_user.clone(),
+            runner_os: Config::detect_os(),
+            runner_arch: Config::detect_arch(),
+            instances_base: self.instances_base.clone(),
+            escalation: Escalation::detect(),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Runner release checks: the latest release (cached on disk, so `status` and the
+//! dashboard don't ask GitHub every time) and whether an installed runner is behind it.
+//! Also finds the runner's own self-updates in its `_diag` logs, so the brief offline gap
+//! of an update isn't counted against uptime.
+
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::github::GitHubClient;
+
+/// How long a latest-release lookup is reused
+pub const CACHE_TTL_SECS: i64 = 6 * 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedRelease {
+    version: String,
+    /// Unix timestamp of the lookup
+    checked_at: i64,
+}
+
+fn cache_path() -> PathBuf {
+    Config::config_dir().join("latest-runner-release.json")
+}
+
+/// The cached latest version, if it was looked up less than `CACHE_TTL_SECS` before `now`
+pub fn cached_latest_version(now: i64) -> Option<String> {
+    let content = fs::read_to_string(cache_path()).ok()?;
+    let cached: CachedRelease = serde_json::from_str(&content).ok()?;
+    (now - cached.checked_at < CACHE_TTL_SECS).then_some(cached.version)
+}
+
+/// Latest runner release, from the cache or GitHub
+pub async fn latest_version(client: &GitHubClient) -> Result<String> {
+    let now = chrono::Utc::now().timestamp();
+    if let Some(version) = cached_latest_version(now) {
+        return Ok(version);
+    }
+    let version = client.get_latest_runner_version().await?;
+    let cached = CachedRelease {
+        version: version.clone(),
+        checked_at: now,
+    };
+    // Caching is best-effort
+    if let Ok(content) = serde_json::to_string(&cached) {
+        let _ = fs::create_dir_all(Config::config_dir());
+        let _ = fs::write(cache_path(), content);
+    }
+    Ok(version)
+}
+
+/// Numeric parts of a version like `2.319.1` (a leading `v` is ignored)
+fn version_parts(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Whether `installed` is older than `latest`. Unparseable versions are never
+/// reported as outdated.
+pub fn is_outdated(installed: &str, latest: &str) -> bool {
+    match (version_parts(installed), version_parts(latest)) {
+        (Some(installed), Some(latest)) => installed < latest,
+        _ => false,
+    }
+}
+
+/// Version column for `status`: the installed version, flagged when an update exists
+pub fn version_label(installed: Option<&str>, latest: Option<&str>) -> String {
+    match (installed, latest) {
+        (None, _) => "-".to_string(),
+        (Some(installed), Some(latest)) if is_outdated(installed, latest) => {
+            format!("{installed} (update available)")
+        }
+        (Some(installed), _) => installed.to_string(),
+    }
+}
+
+/// `_diag` log files read for self-updates: an update ends the running log and the
+/// restarted runner starts a new one, so the newest few cover recent updates
+const DIAG_FILES_SCANNED: usize = 3;
+
+/// A self-update of the runner found in its diagnostic log. Times are Unix timestamps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfUpdate {
+    /// When the runner announced the update
+    pub started_at: i64,
+    /// When the updated runner was back, if it came back within the scanned logs
+    pub finished_at: Option<i64>,
+    /// Version updated to
+    pub version: Option<String>,
+}
+
+/// Split a `_diag` log line (`[2024-03-05 10:15:32Z INFO Terminal] message`) into its
+/// timestamp and message
+pub fn parse_diag_line(line: &str) -> Option<(i64, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (header, message) = rest.split_once(']')?;
+    let timestamp = header.get(..20)?;
+    let time = NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%SZ").ok()?;
+    Some((time.and_utc().timestamp(), message.trim()))
+}
+
+fn is_update_start(message: &str) -> bool {
+    message.contains("Runner update in progress") || message.contains("exit shortly for update")
+}
+
+/// The updated runner is connected again
+fn is_update_end(message: &str) -> bool {
+    message.contains("Current runner version") || message.contains("Listening for Jobs")
+}
+
+/// Version in `Downloading 2.320.0 runner` or `Current runner version: '2.320.0'`
+fn announced_version(message: &str) -> Option<String> {
+    if let Some((_, rest)) = message.split_once("Current runner version:") {
+        let version = rest.trim().trim_matches('\'');
+        return (!version.is_empty()).then(|| version.to_string());
+    }
+    let (_, rest) = message.split_once("Downloading ")?;
+    let version = rest.strip_suffix(" runner")?;
+    version_parts(version).map(|_| version.to_string())
+}
+
+/// Self-updates in `(timestamp, message)` log lines, oldest first
+pub fn detect_self_updates<'a>(lines: impl IntoIterator<Item = (i64, &'a str)>) -> Vec<SelfUpdate> {
+    let mut updates = Vec::new();
+    let mut open: Option<SelfUpdate> = None;
+    for (at, message) in lines {
+        if is_update_start(message) && open.is_none() {
+            open = Some(SelfUpdate {
+                started_at: at,
+                finished_at: None,
+                version: None,
+            });
+        }
+        let Some(update) = open.as_mut() else {
+            continue;
+        };
+        if let Some(version) = announced_version(message) {
+            update.version = Some(version);
+        }
+        if is_update_end(message) {
+            update.finished_at = Some(at);
+            updates.extend(open.take());
+        }
+    }
+    updates.extend(open);
+    updates
+}
+
+/// Self-updates recorded in the newest `Runner_*.log` files of the instance in `dir`
+pub fn read_self_updates(dir: &Path) -> Vec<SelfUpdate> {
+    let Ok(entries) = fs::read_dir(dir.join("_diag")) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<_> = entries
+        .flatten()
+        .filter(|e| {
+            e.file_name().to_string_lossy().starts_with("Runner_")
+                && e.file_name().to_string_lossy().ends_with(".log")
+        })
+        .collect();
+    logs.sort_by_key(|e| std::cmp::Reverse(e.metadata().ok().and_then(|m| m.modified().ok())));
+    logs.truncate(DIAG_FILES_SCANNED);
+
+    // Oldest first, so an update started in one file can end in the next
+    let contents: Vec<String> = logs
+        .iter()
+        .rev()
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .collect();
+    detect_self_updates(
+        contents
+            .iter()
+            .flat_map(|content| content.lines())
+            .filter_map(parse_diag_line),
+    )
+}
@@ -0,0 +1,108 @@
+//! Runner user account: check that it exists and create it when it doesn't
+
+use anyhow::{Context, Result};
+use std::process::Command;
+
+use crate::privilege::Escalation;
+
+/// First UID handed out to regular accounts on macOS
+const MACOS_FIRST_UID: u32 = 501;
+
+/// Whether `user` resolves on this host
+pub fn exists(user: &str) -> bool {
+    Command::new("id")
+        .arg(user)
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+/// Name of the account runner-mgr runs as
+pub fn current() -> Option<String> {
+    Command::new("id")
+        .arg("-un")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Whether a `docker` group exists (Linux)
+pub fn docker_group_exists() -> bool {
+    Command::new("getent")
+        .args(["group", "docker"])
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+/// The next free UID from `dscl . -list /Users UniqueID` output
+pub fn next_macos_uid(dscl_output: &str) -> u32 {
+    dscl_output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1)?.parse::<u32>().ok())
+        .filter(|uid| *uid >= MACOS_FIRST_UID)
+        .max()
+        .map_or(MACOS_FIRST_UID, |uid| uid + 1)
+}
+
+/// Commands (run as root) that create `user` with a locked password and a home
+/// directory, optionally in the docker group. `uid` is only used on macOS.
+pub fn create_commands(os: &str, user: &str, uid: u32, docker_group: bool) -> Vec<Vec<String>> {
+    let cmd = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+    if os == "darwin" {
+        let record = format!("/Users/{user}");
+        let uid = uid.to_string();
+        return vec![
+            cmd(&["dscl", ".", "-create", &record]),
+            cmd(&["dscl", ".", "-create", &record, "UserShell", "/bin/zsh"]),
+            cmd(&[
+                "dscl",
+                ".",
+                "-create",
+                &record,
+                "RealName",
+                "GitHub Actions runner",
+            ]),
+            cmd(&["dscl", ".", "-create", &record, "UniqueID", &uid]),
+            cmd(&["dscl", ".", "-create", &record, "PrimaryGroupID", "20"]),
+            cmd(&["dscl", ".", "-create", &record, "NFSHomeDirectory", &record]),
+            // "*" never matches a password: the account can't log in interactively
+            cmd(&["dscl", ".", "-create", &record, "Password", "*"]),
+            cmd(&["createhomedir", "-c", "-u", user]),
+        ];
+    }
+
+    let mut commands = vec![
+        cmd(&["useradd", "--create-home", "--shell", "/bin/bash", user]),
+        cmd(&["passwd", "--lock", user]),
+    ];
+    if docker_group {
+        commands.push(cmd(&["usermod", "-aG", "docker", user]));
+    }
+    commands
+}
+
+/// Create the runner user (see [`create_commands`])
+pub fn create(escalation: Escalation, os: &str, user: &str, docker_group: bool) -> Result<()> {
+    let uid = if os == "darwin" {
+        let output = Command::new("dscl")
+            .args([".", "-list", "/Users", "UniqueID"])
+            .output()
+            .context("Failed to list users with dscl")?;
+        next_macos_uid(&String::from_utf8_lossy(&output.stdout))
+    } else {
+        0
+    };
+
+    for args in create_commands(os, user, uid, docker_group) {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let status = escalation
+            .command(&args)
+            .status()
+            .with_context(|| format!("Failed to run {}", args[0]))?;
+        if !status.success() {
+            anyhow::bail!("{} failed ({status})", args.join(" "));
+        }
+    }
+    Ok(())
+}
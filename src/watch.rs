@@ -0,0 +1,93 @@
+//! Change detection for files the dashboard reads, by polling modification times
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Reports when a file was created, modified or removed since the last check
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    path: PathBuf,
+    /// Modification time at the last check (`None` while the file doesn't exist)
+    last: Option<SystemTime>,
+}
+
+impl FileWatcher {
+    /// Start watching `path` from its current state
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let last = modified(&path);
+        Self { path, last }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file changed since the last call (or since `new`)
+    pub fn changed(&mut self) -> bool {
+        let now = modified(&self.path);
+        if now == self.last {
+            return false;
+        }
+        self.last = now;
+        true
+    }
+}
+
+/// Reports when entries are added to or removed from a directory, or files are
+/// created in one of its subdirectories (e.g. an instance's `.service` file once
+/// `add` installs the service)
+#[derive(Debug, Clone)]
+pub struct DirWatcher {
+    path: PathBuf,
+    /// Modification times of the directory and its subdirectories at the last check
+    last: BTreeMap<PathBuf, SystemTime>,
+}
+
+impl DirWatcher {
+    /// Start watching `path` from its current state
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let last = snapshot(&path);
+        Self { path, last }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the directory changed since the last call (or since `new`)
+    pub fn changed(&mut self) -> bool {
+        let now = snapshot(&self.path);
+        if now == self.last {
+            return false;
+        }
+        self.last = now;
+        true
+    }
+}
+
+/// Modification times of `dir` and its immediate subdirectories (empty when `dir`
+/// doesn't exist)
+fn snapshot(dir: &Path) -> BTreeMap<PathBuf, SystemTime> {
+    let mut times = BTreeMap::new();
+    let Some(time) = modified(dir) else {
+        return times;
+    };
+    times.insert(dir.to_path_buf(), time);
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if let Some(time) = modified(&path) {
+                times.insert(path, time);
+            }
+        }
+    }
+    times
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,197 @@
+//! Automatic restarts of runner services that run locally while GitHub reports their
+//! runner offline, for `runner-mgr watch` and the dashboard's watchdog toggle. Restarts
+//! back off exponentially, and a runner that stays offline is reported once. Runners
+//! whose registration GitHub lost are reported (or registered again) instead.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::collector;
+use crate::config::{Config, WatchdogConfig};
+use crate::github::{GitHubClient, Runner, RunnerScope};
+use crate::notify;
+use crate::runner::{self, InstanceId, RunnerInstance, RunnerStatus};
+
+/// Default seconds between checks of `runner-mgr watch`
+pub const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+/// Log lines searched for a rejected registration before restarting an offline runner
+const REVOKED_LOG_LINES: u32 = 50;
+
+/// One runner's time offline
+#[derive(Debug)]
+struct Outage {
+    restarts: u32,
+    next_restart: Instant,
+    reported: bool,
+}
+
+/// What a check found
+#[derive(Debug, Default)]
+pub struct Check {
+    /// Instances due for a restart
+    pub restart: Vec<RunnerInstance>,
+    /// Instances still offline after `notify_after` restarts, with their restart count
+    /// (each reported once per outage)
+    pub gave_up: Vec<(RunnerInstance, u32)>,
+    /// Registered instances GitHub no longer lists (each reported once until they
+    /// show up again)
+    pub unregistered: Vec<RunnerInstance>,
+}
+
+/// Wait after the `restarts`th restart before the next one
+pub fn backoff(settings: &WatchdogConfig, restarts: u32) -> Duration {
+    let factor = 2u64.saturating_pow(restarts.saturating_sub(1));
+    Duration::from_secs(
+        settings
+            .backoff_seconds
+            .saturating_mul(factor)
+            .min(settings.max_backoff_seconds),
+    )
+}
+
+#[derive(Debug)]
+pub struct Watchdog {
+    settings: WatchdogConfig,
+    outages: HashMap<InstanceId, Outage>,
+    unregistered: HashSet<InstanceId>,
+}
+
+impl Watchdog {
+    pub fn new(settings: WatchdogConfig) -> Self {
+        Self {
+            settings,
+            outages: HashMap::new(),
+            unregistered: HashSet::new(),
+        }
+    }
+
+    /// Compare the local services with `github` (the runners of each polled scope).
+    /// A runner's outage starts when its service runs but GitHub reports it offline, and
+    /// ends when it is online again or its service is stopped. Scopes missing from
+    /// `github` keep their outages as they are.
+    pub fn check(
+        &mut self,
+        now: Instant,
+        instances: &[RunnerInstance],
+        github: &[(RunnerScope, Vec<Runner>)],
+    ) -> Check {
+        let grace = Duration::from_secs(self.settings.grace_seconds);
+        let mut check = Check::default();
+        for instance in instances {
+            let Some(runners) = github
+                .iter()
+                .find(|(scope, _)| *scope == instance.scope)
+                .map(|(_, runners)| runners)
+            else {
+                continue;
+            };
+            let id = instance.id();
+            if runner::registration_missing(instance, runners) {
+                // Restarting doesn't bring the registration back
+                self.outages.remove(&id);
+                if self.unregistered.insert(id) {
+                    check.unregistered.push(instance.clone());
+                }
+                continue;
+            }
+            self.unregistered.remove(&id);
+            let offline = instance.status == RunnerStatus::Running
+                && instance.runner_name.as_ref().is_some_and(|name| {
+                    runners
+                        .iter()
+                        .any(|r| r.name == *name && r.status != "online")
+                });
+            if !offline {
+                self.outages.remove(&id);
+                continue;
+            }
+            let outage = self.outages.entry(id).or_insert_with(|| Outage {
+                restarts: 0,
+                next_restart: now + grace,
+                reported: false,
+            });
+            if outage.restarts >= self.settings.notify_after.max(1) && !outage.reported {
+                outage.reported = true;
+                check.gave_up.push((instance.clone(), outage.restarts));
+            }
+            if now >= outage.next_restart {
+                check.restart.push(instance.clone());
+            }
+        }
+        check
+    }
+
+    /// Record a restart of `id` at `now`, whether or not it succeeded
+    pub fn restarted(&mut self, id: &InstanceId, now: Instant) {
+        if let Some(outage) = self.outages.get_mut(id) {
+            outage.restarts += 1;
+            outage.next_restart = now + backoff(&self.settings, outage.restarts);
+        }
+    }
+}
+
+/// Check every `interval` until the process is stopped
+pub async fn run(config: &Config, interval: Duration) -> Result<()> {
+    let client = GitHubClient::for_config(config);
+    let notifications = config.notifications.clone().unwrap_or_default();
+    let mut watchdog = Watchdog::new(config.watchdog.clone().unwrap_or_default());
+    println!(
+        "Watching {} runner(s), checking every {}s",
+        runner::list_instances(config).len(),
+        interval.as_secs()
+    );
+    loop {
+        let instances = runner::list_instances(config);
+        let mut github = Vec::new();
+        for scope in collector::scopes(&instances) {
+            match client.list_runners(&scope).await {
+                Ok(list) => github.push((scope, list.runners)),
+                Err(e) => eprintln!("{scope}: {e:#}"),
+            }
+        }
+
+        let now = Instant::now();
+        let check = watchdog.check(now, &instances, &github);
+        let mut revoked = check.unregistered.clone();
+        for instance in &check.restart {
+            let id = instance.id();
+            watchdog.restarted(&id, now);
+            // Credentials GitHub rejects: the runner stays offline however often it restarts
+            let logs = runner::get_runner_logs(config, &id, REVOKED_LOG_LINES);
+            if logs.is_ok_and(|logs| runner::registration_revoked(&logs)) {
+                revoked.push(instance.clone());
+                continue;
+            }
+            match runner::restart_runner(config, &id) {
+                Ok(()) => println!("{id}: restarted, GitHub reported it offline"),
+                Err(e) => eprintln!("{id}: restart failed: {e:#}"),
+            }
+        }
+        for instance in &revoked {
+            let id = instance.id();
+            if !watchdog.settings.reregister {
+                println!(
+                    "{id}: GitHub no longer accepts its registration; {}",
+                    runner::reregister_hint(&id)
+                );
+                continue;
+            }
+            println!("{id}: GitHub no longer accepts its registration, registering it again");
+            if let Err(e) = runner::reregister_runner(config, &id).await {
+                eprintln!("{id}: re-registration failed: {e:#}");
+            }
+        }
+        let mut gave_up = Vec::new();
+        for (instance, restarts) in &check.gave_up {
+            println!("{}: still offline after {restarts} restarts", instance.id());
+            gave_up.push(notify::restart_failed_notification(instance, *restarts));
+        }
+        for line in notify::send_all(notifications.clone(), gave_up).await {
+            println!("{line}");
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
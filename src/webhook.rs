@@ -0,0 +1,297 @@
+//! Receiver for GitHub `workflow_job` webhooks (`runner-mgr webhook-server`): records
+//! when jobs are queued, start and finish, for exact queue-wait and duration metrics,
+//! and starts on-demand runners when a job is queued
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::Instant;
+
+use crate::config::Config;
+use crate::github::{RunnerScope, WorkflowJob};
+use crate::metrics::MetricsDb;
+use crate::ondemand;
+
+/// Environment variable that overrides the configured webhook secret
+pub const SECRET_ENV: &str = "RUNNER_MGR_WEBHOOK_SECRET";
+
+/// Largest request accepted (GitHub caps payloads at 25 MB; job events are a few KB)
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// Time a client has to send its whole request, so stalled connections are dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SHA256_BLOCK: usize = 64;
+
+/// HMAC-SHA256 of `message` under `key` (RFC 2104)
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; SHA256_BLOCK];
+    if key.len() > SHA256_BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.map(|b| b ^ byte);
+
+    let inner = Sha256::new()
+        .chain_update(pad(0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(pad(0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+/// Check an `X-Hub-Signature-256` header (`sha256=<hex>`) against the body
+pub fn verify_signature(secret: &str, body: &[u8], header: &str) -> bool {
+    let Some(hex) = header.trim().strip_prefix("sha256=") else {
+        return false;
+    };
+    let expected = hmac_sha256(secret.as_bytes(), body)
+        .iter()
+        .fold(String::new(), |mut hex, b| {
+            let _ = write!(hex, "{b:02x}");
+            hex
+        });
+    // Compare in constant time so the signature can't be guessed byte by byte
+    hex.len() == expected.len()
+        && hex
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a.to_ascii_lowercase() ^ b))
+            == 0
+}
+
+/// The parts of a `workflow_job` event that are recorded
+#[derive(Debug, Deserialize)]
+pub struct WorkflowJobEvent {
+    /// `queued`, `waiting`, `in_progress` or `completed`
+    pub action: String,
+    pub workflow_job: WorkflowJob,
+    pub repository: EventRepository,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventRepository {
+    pub full_name: String,
+}
+
+impl WorkflowJobEvent {
+    pub fn scope(&self) -> Result<RunnerScope> {
+        RunnerScope::parse(&self.repository.full_name)
+    }
+}
+
+/// A parsed HTTP request
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Parse a complete HTTP/1.1 request, or None if more bytes are needed
+pub fn parse_request(data: &[u8]) -> Result<Option<Request>> {
+    let Some(header_end) = data.windows(4).position(|w| w == b"\r\n\r\n") else {
+        return Ok(None);
+    };
+    let head = std::str::from_utf8(&data[..header_end]).context("Headers are not UTF-8")?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line
+        .next()
+        .context("Malformed request line")?
+        .to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let length: usize = headers
+        .iter()
+        .find(|(n, _)| n == "content-length")
+        .map(|(_, v)| v.parse().context("Invalid Content-Length"))
+        .transpose()?
+        .unwrap_or(0);
+    if length > MAX_REQUEST_BYTES {
+        anyhow::bail!("Request too large");
+    }
+    let body_start = header_end + 4;
+    if data.len() < body_start + length {
+        return Ok(None);
+    }
+    Ok(Some(Request {
+        method,
+        path,
+        headers,
+        body: data[body_start..body_start + length].to_vec(),
+    }))
+}
+
+/// Status code and body answered to a webhook delivery
+pub fn handle_request(
+    request: &Request,
+    config: &Config,
+    secret: &str,
+    db: &Mutex<MetricsDb>,
+) -> (u16, String) {
+    if request.method != "POST" {
+        return (405, "Only POST is supported".to_string());
+    }
+    let signature = request.header("x-hub-signature-256").unwrap_or_default();
+    if !verify_signature(secret, &request.body, signature) {
+        return (401, "Invalid signature".to_string());
+    }
+    match request.header("x-github-event") {
+        Some("ping") => (200, "pong".to_string()),
+        Some("workflow_job") => match record_event(&request.body, config, db) {
+            Ok(summary) => {
+                println!("{summary}");
+                (200, "recorded".to_string())
+            }
+            Err(e) => (400, format!("{e:#}")),
+        },
+        Some(other) => (202, format!("Ignored {other} event")),
+        None => (400, "Missing X-GitHub-Event header".to_string()),
+    }
+}
+
+/// Record a `workflow_job` event, returning a line for the server log
+fn record_event(body: &[u8], config: &Config, db: &Mutex<MetricsDb>) -> Result<String> {
+    let event: WorkflowJobEvent =
+        serde_json::from_slice(body).context("Invalid workflow_job payload")?;
+    let scope = event.scope()?;
+    db.lock()
+        .map_err(|_| anyhow::anyhow!("Metrics database lock poisoned"))?
+        .record_job_event(&scope, &event.workflow_job)?;
+    if event.action == "queued" {
+        // A failed trigger shouldn't make GitHub redeliver an event already recorded
+        match ondemand::queue_work(config, &scope) {
+            Ok(ids) => {
+                for id in ids {
+                    println!("Queued work for on-demand runner {id}");
+                }
+            }
+            Err(e) => eprintln!("{scope}: {e:#}"),
+        }
+    }
+    Ok(format!(
+        "{scope}: job {} '{}' {}{}",
+        event.workflow_job.id,
+        event.workflow_job.name,
+        event.action,
+        event
+            .workflow_job
+            .runner_name
+            .as_ref()
+            .map(|r| format!(" on {r}"))
+            .unwrap_or_default()
+    ))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Error",
+    }
+}
+
+/// Read one request from `stream` and answer it with the status and body `handle`
+/// resolves to for it. The request must arrive within `REQUEST_TIMEOUT`.
+pub async fn serve_connection<F>(
+    mut stream: TcpStream,
+    content_type: &str,
+    handle: impl FnOnce(Request) -> F,
+) -> Result<()>
+where
+    F: Future<Output = (u16, String)>,
+{
+    let deadline = Instant::now() + REQUEST_TIMEOUT;
+    let mut data = Vec::new();
+    let mut buf = [0u8; 8192];
+    let (status, body) = loop {
+        match parse_request(&data) {
+            Ok(Some(request)) => break handle(request).await,
+            Ok(None) if data.len() > MAX_REQUEST_BYTES => {
+                break (413, "Request too large".to_string())
+            }
+            Ok(None) => {}
+            Err(e) => break (400, format!("{e:#}")),
+        }
+        let Ok(read) = tokio::time::timeout_at(deadline, stream.read(&mut buf)).await else {
+            break (408, "Request timed out".to_string());
+        };
+        let n = read?;
+        if n == 0 {
+            return Ok(());
+        }
+        data.extend_from_slice(&buf[..n]);
+    };
+    let response = format!(
+        "HTTP/1.1 {status} {}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        reason(status),
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Accept webhook deliveries on `listen` until the process is stopped
+pub async fn serve(config: Config, listen: &str, secret: String) -> Result<()> {
+    let db = Arc::new(Mutex::new(MetricsDb::open()?));
+    let config = Arc::new(config);
+    let secret = Arc::new(secret);
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to listen on {listen}"))?;
+    println!("Listening for workflow_job webhooks on {listen}");
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let db = Arc::clone(&db);
+        let config = Arc::clone(&config);
+        let secret = Arc::clone(&secret);
+        tokio::spawn(async move {
+            // Recording an event writes to SQLite, which blocks
+            let handle = |request: Request| async move {
+                tokio::task::spawn_blocking(move || handle_request(&request, &config, &secret, &db))
+                    .await
+                    .unwrap_or_else(|e| (500, format!("{e:#}")))
+            };
+            if let Err(e) = serve_connection(stream, "text/plain", handle).await {
+                eprintln!("{peer}: {e:#}");
+            }
+        });
+    }
+}
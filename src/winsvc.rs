@@ -0,0 +1,89 @@
+//! Windows runners: service control through `sc.exe` (the runner's `config.cmd
+//! --runasservice` installs a Windows service and records its name in `.service`) and
+//! PowerShell for unpacking the `actions-runner-win` package
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+use crate::runner::RunnerStatus;
+
+/// `sc.exe` exit code when starting a service that is already running
+const ERROR_SERVICE_ALREADY_RUNNING: i32 = 1056;
+
+/// `sc.exe` exit code when stopping a service that isn't running
+const ERROR_SERVICE_NOT_ACTIVE: i32 = 1062;
+
+/// State name from `sc.exe query` output (`STATE : 4  RUNNING`)
+pub fn parse_sc_state(output: &str) -> Option<&str> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("STATE"))
+        .and_then(|rest| rest.split_whitespace().nth(2))
+}
+
+/// Local status for a service state reported by `sc.exe`
+pub fn status_from_state(state: Option<&str>) -> RunnerStatus {
+    match state {
+        Some("RUNNING" | "START_PENDING" | "CONTINUE_PENDING") => RunnerStatus::Running,
+        Some("STOPPED" | "STOP_PENDING" | "PAUSED" | "PAUSE_PENDING") => RunnerStatus::Stopped,
+        _ => RunnerStatus::Unknown,
+    }
+}
+
+pub fn service_status(service: &str) -> RunnerStatus {
+    match Command::new("sc.exe").args(["query", service]).output() {
+        Ok(o) if o.status.success() => {
+            status_from_state(parse_sc_state(&String::from_utf8_lossy(&o.stdout)))
+        }
+        // Missing services are reported like stopped ones on the other platforms
+        _ => RunnerStatus::Stopped,
+    }
+}
+
+/// Run `sc.exe <action> <service>`, accepting `ok_code` (the service was already in
+/// the requested state) as success
+fn control(action: &str, service: &str, ok_code: i32) -> Result<()> {
+    let output = Command::new("sc.exe")
+        .args([action, service])
+        .output()
+        .context("Failed to run sc.exe")?;
+    match output.status.code() {
+        Some(0) => Ok(()),
+        Some(code) if code == ok_code => Ok(()),
+        _ => anyhow::bail!(
+            "sc.exe {action} {service} failed: {}",
+            String::from_utf8_lossy(&output.stdout).trim()
+        ),
+    }
+}
+
+pub fn start_service(service: &str) -> Result<()> {
+    control("start", service, ERROR_SERVICE_ALREADY_RUNNING)
+}
+
+pub fn stop_service(service: &str) -> Result<()> {
+    control("stop", service, ERROR_SERVICE_NOT_ACTIVE)
+}
+
+/// Quote a string for a PowerShell command line
+pub fn powershell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Unpack a `.zip` runner package into `dest` with `Expand-Archive`
+pub fn expand_archive(archive: &Path, dest: &Path) -> Result<()> {
+    let script = format!(
+        "Expand-Archive -Force -LiteralPath {} -DestinationPath {}",
+        powershell_quote(&archive.to_string_lossy()),
+        powershell_quote(&dest.to_string_lossy())
+    );
+    let status = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .status()
+        .context("Failed to run PowerShell")?;
+    if !status.success() {
+        anyhow::bail!("Expand-Archive failed");
+    }
+    Ok(())
+}
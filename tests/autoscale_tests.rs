@@ -0,0 +1,183 @@
+use runner_mgr::autoscale::{count_queued_jobs, is_local_jit_runner, plan, Observation, Plan};
+use runner_mgr::config::{AutoscaleConfig, Config};
+use runner_mgr::github::WorkflowJob;
+
+fn policy(min_runners: u32, max_runners: u32) -> AutoscaleConfig {
+    AutoscaleConfig {
+        min_runners,
+        max_runners,
+        idle_minutes: 15,
+    }
+}
+
+fn job(status: &str, labels: &[&str]) -> WorkflowJob {
+    WorkflowJob {
+        id: 1,
+        name: "build".to_string(),
+        status: status.to_string(),
+        conclusion: None,
+        labels: labels.iter().map(ToString::to_string).collect(),
+        runner_name: None,
+        html_url: None,
+        run_id: 1,
+        workflow_name: Some("CI".to_string()),
+        created_at: None,
+        started_at: None,
+        completed_at: None,
+        steps: Vec::new(),
+    }
+}
+
+#[test]
+fn test_starts_stopped_runner_when_jobs_queued() {
+    let obs = Observation {
+        queued: 1,
+        ..Observation::default()
+    };
+    assert_eq!(
+        plan(&policy(0, 1), &obs, None),
+        Plan {
+            start: true,
+            stop: false,
+            launch_jit: 0,
+        }
+    );
+}
+
+#[test]
+fn test_nothing_queued_leaves_stopped_runner_alone() {
+    assert_eq!(
+        plan(&policy(0, 3), &Observation::default(), None),
+        Plan::default()
+    );
+}
+
+#[test]
+fn test_min_runners_keeps_runner_started() {
+    let p = plan(&policy(1, 1), &Observation::default(), None);
+    assert!(p.start);
+
+    let idle = Observation {
+        running: true,
+        ..Observation::default()
+    };
+    let p = plan(&policy(1, 1), &idle, Some(3600));
+    assert!(!p.stop);
+}
+
+#[test]
+fn test_min_runners_above_one_prewarms_jit_runners() {
+    let p = plan(&policy(3, 5), &Observation::default(), None);
+    assert!(p.start);
+    assert_eq!(p.launch_jit, 2);
+}
+
+#[test]
+fn test_stops_after_idle_timeout() {
+    let idle = Observation {
+        running: true,
+        ..Observation::default()
+    };
+    assert!(!plan(&policy(0, 1), &idle, Some(14 * 60)).stop);
+    assert!(plan(&policy(0, 1), &idle, Some(15 * 60)).stop);
+    assert!(!plan(&policy(0, 1), &idle, None).stop);
+}
+
+#[test]
+fn test_busy_runner_is_not_stopped() {
+    let busy = Observation {
+        running: true,
+        busy: true,
+        ..Observation::default()
+    };
+    assert!(!busy.is_idle());
+    assert!(!plan(&policy(0, 1), &busy, Some(3600)).stop);
+}
+
+#[test]
+fn test_launches_jit_runners_for_backlog_up_to_max() {
+    let obs = Observation {
+        queued: 5,
+        running: true,
+        busy: true,
+        ..Observation::default()
+    };
+    assert_eq!(plan(&policy(0, 3), &obs, None).launch_jit, 2);
+    assert_eq!(plan(&policy(0, 10), &obs, None).launch_jit, 5);
+}
+
+#[test]
+fn test_waiting_jit_runners_count_as_free() {
+    let obs = Observation {
+        queued: 2,
+        running: true,
+        busy: true,
+        jit_running: 2,
+        jit_busy: 0,
+    };
+    assert_eq!(plan(&policy(0, 10), &obs, None).launch_jit, 0);
+
+    let obs = Observation { jit_busy: 2, ..obs };
+    assert_eq!(plan(&policy(0, 10), &obs, None).launch_jit, 2);
+}
+
+#[test]
+fn test_starting_runner_takes_first_queued_job() {
+    let obs = Observation {
+        queued: 3,
+        ..Observation::default()
+    };
+    let p = plan(&policy(0, 2), &obs, None);
+    assert!(p.start);
+    assert_eq!(p.launch_jit, 1);
+}
+
+#[test]
+fn test_count_queued_jobs_only_self_hosted() {
+    let jobs = [
+        job("queued", &["self-hosted", "linux"]),
+        job("queued", &["ubuntu-latest"]),
+        job("in_progress", &["self-hosted"]),
+        job("queued", &["Self-Hosted"]),
+    ];
+    assert_eq!(count_queued_jobs(&jobs), 2);
+}
+
+#[test]
+fn test_is_local_jit_runner() {
+    assert!(is_local_jit_runner("ci-01-jit-1700000000000", "ci-01"));
+    assert!(is_local_jit_runner("ci-0-jit-1700000000000", "ci-01"));
+    assert!(!is_local_jit_runner("ci-02-jit-1700000000000", "ci-01"));
+    assert!(!is_local_jit_runner("ci-01-owner__repo", "ci-01"));
+    assert!(!is_local_jit_runner("ci-01-jit-abc", "ci-01"));
+}
+
+#[test]
+fn test_autoscale_config_validation() {
+    assert!(policy(0, 1).validate().is_ok());
+    assert!(policy(2, 2).validate().is_ok());
+    assert!(policy(0, 0).validate().is_err());
+    assert!(policy(3, 2).validate().is_err());
+}
+
+#[test]
+fn test_autoscale_config_defaults() {
+    let config: Config = toml::from_str(
+        r#"
+github_pat = "ghp_test"
+github_user = "user"
+runner_user = "github"
+runner_os = "linux"
+runner_arch = "x64"
+instances_base = "/opt/github-runners"
+
+[autoscale."owner/repo"]
+max_runners = 4
+"#,
+    )
+    .unwrap();
+    let policy = &config.autoscale["owner/repo"];
+    assert_eq!(policy.min_runners, 0);
+    assert_eq!(policy.max_runners, 4);
+    assert_eq!(policy.idle_minutes, 15);
+}
@@ -0,0 +1,41 @@
+use runner_mgr::bootstrap::{render, shell_quote, BootstrapOptions};
+use runner_mgr::github::RunnerScope;
+
+fn options(ephemeral: bool) -> BootstrapOptions {
+    BootstrapOptions {
+        scope: RunnerScope::parse("owner/repo").unwrap(),
+        web_url: "https://github.com".to_string(),
+        token: "AABBCC".to_string(),
+        expires_at: Some("2026-01-01T00:00:00Z".to_string()),
+        labels: "self-hosted,linux".to_string(),
+        arch: "arm64".to_string(),
+        version: "2.320.0".to_string(),
+        runner_user: "github".to_string(),
+        dir: "/opt/actions-runner".to_string(),
+        ephemeral,
+    }
+}
+
+#[test]
+fn test_shell_quote() {
+    assert_eq!(shell_quote("plain"), "'plain'");
+    assert_eq!(shell_quote("it's"), r"'it'\''s'");
+}
+
+#[test]
+fn test_render_bootstrap_script() {
+    let script = render(&options(false));
+    assert!(script.starts_with("#!/bin/sh\n"));
+    assert!(script.contains("RUNNER_URL='https://github.com/owner/repo'"));
+    assert!(script.contains("RUNNER_TOKEN='AABBCC'"));
+    assert!(script.contains("RUNNER_PACKAGE='actions-runner-linux-arm64-2.320.0.tar.gz'"));
+    assert!(script.contains("expires at 2026-01-01T00:00:00Z"));
+    assert!(script.contains("./svc.sh install \"$RUNNER_USER\""));
+    assert!(!script.contains("--ephemeral"));
+}
+
+#[test]
+fn test_render_ephemeral() {
+    let script = render(&options(true));
+    assert!(script.contains("--ephemeral"));
+}
@@ -0,0 +1,40 @@
+use runner_mgr::deps::Distro;
+
+#[test]
+fn test_distro_from_os_release_id() {
+    let ubuntu = "NAME=\"Ubuntu\"\nID=ubuntu\nID_LIKE=debian\nVERSION_ID=\"24.04\"\n";
+    assert_eq!(Distro::from_os_release(ubuntu), Distro::Debian);
+
+    let alpine = "NAME=\"Alpine Linux\"\nID=alpine\nVERSION_ID=3.20.0\n";
+    assert_eq!(Distro::from_os_release(alpine), Distro::Alpine);
+}
+
+#[test]
+fn test_distro_from_os_release_id_like() {
+    let rocky = "NAME=\"Rocky Linux\"\nID=\"rocky\"\nID_LIKE=\"rhel centos fedora\"\n";
+    assert_eq!(Distro::from_os_release(rocky), Distro::Fedora);
+
+    let mint = "NAME=\"Linux Mint\"\nID=linuxmint\nID_LIKE=\"ubuntu debian\"\n";
+    assert_eq!(Distro::from_os_release(mint), Distro::Debian);
+}
+
+#[test]
+fn test_distro_unknown() {
+    let void = "NAME=\"Void\"\nID=\"void\"\n";
+    let distro = Distro::from_os_release(void);
+    assert_eq!(distro, Distro::Unknown("void".to_string()));
+    assert!(distro.package_commands().is_empty());
+    assert!(!distro.has_runner_script());
+}
+
+#[test]
+fn test_package_commands_install_icu() {
+    for distro in [Distro::Debian, Distro::Fedora, Distro::Alpine, Distro::Arch] {
+        let commands = distro.package_commands();
+        let install = commands.last().unwrap();
+        assert!(
+            install.iter().any(|pkg| pkg.contains("icu")),
+            "{distro} should install ICU"
+        );
+    }
+}
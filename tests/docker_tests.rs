@@ -0,0 +1,24 @@
+use runner_mgr::docker::{parse_df_usage, parse_reclaimed};
+
+#[test]
+fn test_parse_reclaimed() {
+    let output = "Deleted Images:\nuntagged: foo:latest\n\nTotal reclaimed space: 1.25GB\n";
+    assert_eq!(parse_reclaimed(output).as_deref(), Some("1.25GB"));
+}
+
+#[test]
+fn test_parse_reclaimed_missing() {
+    assert!(parse_reclaimed("").is_none());
+}
+
+#[test]
+fn test_parse_df_usage() {
+    let output = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                  /dev/sda1        102400000  85000000  17400000      83% /\n";
+    assert_eq!(parse_df_usage(output), Some(83));
+}
+
+#[test]
+fn test_parse_df_usage_garbage() {
+    assert!(parse_df_usage("nonsense").is_none());
+}
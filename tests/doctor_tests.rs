@@ -0,0 +1,229 @@
+use chrono::{TimeZone, Utc};
+use runner_mgr::docker::{DockerHealth, Engine};
+use runner_mgr::doctor::{
+    check_container_engine, check_labels, check_template, check_token, check_unrecognized,
+    disk_finding, parse_expiration, Severity,
+};
+use runner_mgr::github::{RunnerScope, TokenInfo, DEFAULT_WEB_URL};
+use runner_mgr::runner::{InstanceId, UnrecognizedDir};
+use std::path::{Path, PathBuf};
+
+fn token(scopes: Option<&[&str]>, expires: Option<&str>) -> TokenInfo {
+    TokenInfo {
+        login: "octocat".to_string(),
+        scopes: scopes.map(|s| s.iter().map(|s| (*s).to_string()).collect()),
+        expires: expires.map(str::to_string),
+    }
+}
+
+fn worst(findings: &[runner_mgr::doctor::Finding]) -> Severity {
+    findings
+        .iter()
+        .map(|f| f.severity)
+        .max()
+        .unwrap_or(Severity::Ok)
+}
+
+#[test]
+fn test_parse_expiration() {
+    let expected = Utc.with_ymd_and_hms(2026, 11, 1, 12, 0, 0).unwrap();
+    assert_eq!(parse_expiration("2026-11-01 12:00:00 UTC"), Some(expected));
+    assert_eq!(
+        parse_expiration("2026-11-01 05:00:00 -0700"),
+        Some(expected)
+    );
+    assert_eq!(parse_expiration("next week"), None);
+}
+
+#[test]
+fn test_token_scopes() {
+    let now = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+    assert_eq!(
+        worst(&check_token(
+            &Ok(token(Some(&["repo"][..]), None)),
+            false,
+            DEFAULT_WEB_URL,
+            now
+        )),
+        Severity::Ok
+    );
+
+    // Organization runners need admin:org (or the narrower manage_runners:org)
+    let findings = check_token(
+        &Ok(token(Some(&["repo"][..]), None)),
+        true,
+        DEFAULT_WEB_URL,
+        now,
+    );
+    assert_eq!(worst(&findings), Severity::Fail);
+    assert!(findings.iter().any(|f| f.detail.contains("admin:org")));
+    let ghes = check_token(
+        &Ok(token(Some(&["repo"][..]), None)),
+        true,
+        "https://ghe.example.com",
+        now,
+    );
+    assert!(ghes.iter().any(|f| f
+        .fix
+        .as_deref()
+        .is_some_and(|fix| fix.contains("https://ghe.example.com/settings/tokens"))));
+    let narrower = token(Some(&["repo", "manage_runners:org"][..]), None);
+    assert_eq!(
+        worst(&check_token(&Ok(narrower), true, DEFAULT_WEB_URL, now)),
+        Severity::Ok
+    );
+
+    // Fine-grained tokens don't report scopes
+    assert_eq!(
+        worst(&check_token(
+            &Ok(token(None, None)),
+            true,
+            DEFAULT_WEB_URL,
+            now
+        )),
+        Severity::Ok
+    );
+
+    // A failed request means GitHub can't be used at all
+    let findings = check_token(
+        &Err(anyhow::anyhow!("connection refused")),
+        false,
+        DEFAULT_WEB_URL,
+        now,
+    );
+    assert_eq!(worst(&findings), Severity::Fail);
+    assert!(findings[0].fix.is_some());
+}
+
+#[test]
+fn test_token_expiry() {
+    let now = Utc.with_ymd_and_hms(2026, 10, 25, 0, 0, 0).unwrap();
+    let expiring = |date| {
+        check_token(
+            &Ok(token(Some(&["repo"][..]), Some(date))),
+            false,
+            DEFAULT_WEB_URL,
+            now,
+        )
+    };
+    assert_eq!(worst(&expiring("2026-12-31 00:00:00 UTC")), Severity::Ok);
+    let soon = expiring("2026-11-01 12:00:00 UTC");
+    assert_eq!(worst(&soon), Severity::Warn);
+    assert!(soon.iter().any(|f| f.detail.contains("in 7 day(s)")));
+    assert_eq!(worst(&expiring("2026-10-01 00:00:00 UTC")), Severity::Fail);
+}
+
+#[test]
+fn test_template_findings() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = check_template(dir.path(), None, Some("2.320.0"));
+    assert_eq!(worst(&missing), Severity::Fail);
+    assert!(missing[0]
+        .fix
+        .as_deref()
+        .unwrap()
+        .contains("runner-mgr update"));
+
+    let script = if cfg!(windows) {
+        "config.cmd"
+    } else {
+        "config.sh"
+    };
+    std::fs::write(dir.path().join(script), "").unwrap();
+    let outdated = check_template(dir.path(), Some("2.319.1"), Some("2.320.0"));
+    assert_eq!(worst(&outdated), Severity::Warn);
+    assert_eq!(
+        worst(&check_template(
+            dir.path(),
+            Some("2.320.0"),
+            Some("2.320.0")
+        )),
+        Severity::Ok
+    );
+    // Without GitHub the latest release is unknown, which isn't a problem
+    assert_eq!(
+        worst(&check_template(dir.path(), Some("2.319.1"), None)),
+        Severity::Ok
+    );
+}
+
+#[test]
+fn test_disk_finding() {
+    let work = Path::new("/opt/runners/owner__repo/_work");
+    assert_eq!(disk_finding(work, Some(42)).severity, Severity::Ok);
+    assert_eq!(disk_finding(work, Some(85)).severity, Severity::Warn);
+    let full = disk_finding(work, Some(97));
+    assert_eq!(full.severity, Severity::Fail);
+    assert!(full.detail.contains("97%"));
+    assert_eq!(disk_finding(work, None).severity, Severity::Ok);
+}
+
+#[test]
+fn test_label_findings() {
+    let id = InstanceId::parse("owner/repo").unwrap();
+    let matching = ["self-hosted".to_string(), "linux".to_string()];
+    assert_eq!(
+        worst(&check_labels(&id, &matching, None, "linux", "x64")),
+        Severity::Ok
+    );
+
+    let wrong = ["self-hosted".to_string(), "macos".to_string()];
+    let findings = check_labels(&id, &wrong, None, "linux", "x64");
+    assert_eq!(worst(&findings), Severity::Warn);
+    assert!(findings[0].detail.contains("'macos'"));
+    assert!(findings[0]
+        .fix
+        .as_deref()
+        .unwrap()
+        .contains("runner-mgr labels set owner/repo"));
+}
+
+#[test]
+fn test_container_engine_finding() {
+    let reachable = DockerHealth::Reachable {
+        engine: Engine::Docker,
+        version: "27.3.1".to_string(),
+    };
+    assert_eq!(check_container_engine(&reachable).severity, Severity::Ok);
+    assert_eq!(
+        check_container_engine(&DockerHealth::NotInstalled).severity,
+        Severity::Ok
+    );
+
+    let down = check_container_engine(&DockerHealth::Unreachable {
+        engine: Engine::Podman,
+        error: "connection refused".to_string(),
+    });
+    assert_eq!(down.severity, Severity::Warn);
+    assert!(down.detail.contains("podman daemon not reachable"));
+    assert!(down
+        .fix
+        .as_deref()
+        .unwrap()
+        .contains("runner-mgr docker restart"));
+}
+
+#[test]
+fn test_unrecognized_finding() {
+    let registered = check_unrecognized(&UnrecognizedDir {
+        path: PathBuf::from("/opt/github-runners/instances/actions-runner"),
+        scope: Some(RunnerScope::parse("owner/repo").unwrap()),
+    });
+    assert_eq!(registered.severity, Severity::Warn);
+    assert!(registered.detail.contains("registered for owner/repo"));
+    assert!(registered
+        .fix
+        .as_deref()
+        .unwrap()
+        .contains("runner-mgr import /opt/github-runners/instances/actions-runner"));
+
+    let unknown = check_unrecognized(&UnrecognizedDir {
+        path: PathBuf::from("/opt/github-runners/instances/old"),
+        scope: None,
+    });
+    assert!(unknown
+        .fix
+        .as_deref()
+        .unwrap()
+        .contains("--target <owner/repo>"));
+}
@@ -0,0 +1,68 @@
+use runner_mgr::dormant::{reason, suggestion, Check, DormantScopes, Reason, RECHECK_INTERVAL};
+use runner_mgr::github::{ActionsPermissions, Repository, RunnerScope};
+use std::time::{Duration, Instant};
+
+fn repository(archived: bool, disabled: bool) -> Repository {
+    Repository {
+        full_name: "owner/repo".to_string(),
+        private: false,
+        archived,
+        disabled,
+        permissions: None,
+    }
+}
+
+#[test]
+fn test_reason() {
+    let enabled = ActionsPermissions { enabled: true };
+    let off = ActionsPermissions { enabled: false };
+    assert_eq!(reason(&repository(false, false), Some(&enabled)), None);
+    // Without admin access the Actions settings are unknown
+    assert_eq!(reason(&repository(false, false), None), None);
+    assert_eq!(
+        reason(&repository(false, false), Some(&off)),
+        Some(Reason::ActionsDisabled)
+    );
+    assert_eq!(
+        reason(&repository(false, true), None),
+        Some(Reason::Disabled)
+    );
+    assert_eq!(
+        reason(&repository(true, true), Some(&off)),
+        Some(Reason::Archived)
+    );
+}
+
+#[test]
+fn test_dormant_scopes() {
+    let repo = RunnerScope::parse("owner/repo").unwrap();
+    let org = RunnerScope::parse("org:acme").unwrap();
+    let start = Instant::now();
+    let mut dormant = DormantScopes::default();
+
+    // Organizations are never checked
+    assert!(!dormant.due(&org, start));
+    assert!(dormant.due(&repo, start));
+
+    // Reported once when the repository becomes dormant
+    assert_eq!(
+        dormant.record(&repo, Check::Dormant(Reason::Archived), start),
+        Some(Reason::Archived)
+    );
+    assert!(!dormant.due(&repo, start + Duration::from_mins(1)));
+    assert_eq!(dormant.reason(&repo), Some(Reason::Archived));
+
+    // A failed recheck keeps it dormant without reporting it again
+    let later = start + RECHECK_INTERVAL;
+    assert!(dormant.due(&repo, later));
+    assert_eq!(dormant.record(&repo, Check::Unknown, later), None);
+    assert_eq!(dormant.dormant(), vec![(&repo, Reason::Archived)]);
+
+    // Unarchived: polled again
+    assert_eq!(dormant.record(&repo, Check::Active, later), None);
+    assert_eq!(dormant.reason(&repo), None);
+    assert!(dormant.dormant().is_empty());
+
+    assert!(suggestion(&repo, Reason::ActionsDisabled)
+        .contains("has Actions disabled: its workflow runs are no longer polled"));
+}
@@ -0,0 +1,132 @@
+use runner_mgr::download::{
+    content_range_start, download, progress_line, sha256_file, verify_sha256,
+};
+use std::fs;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// SHA-256 of "abc"
+const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+#[test]
+fn test_sha256_file() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("abc");
+    fs::write(&path, "abc").unwrap();
+    assert_eq!(sha256_file(&path).unwrap(), ABC_SHA256);
+    assert!(verify_sha256(&path, &ABC_SHA256.to_uppercase()).is_ok());
+    let err = verify_sha256(&path, &"0".repeat(64)).unwrap_err();
+    assert!(err.to_string().contains("Checksum mismatch"));
+}
+
+#[test]
+fn test_content_range_start() {
+    assert_eq!(content_range_start("bytes 100-199/200"), Some(100));
+    assert_eq!(content_range_start("bytes 0-9/*"), Some(0));
+    assert_eq!(content_range_start("bytes */200"), None);
+    assert_eq!(content_range_start("items 1-2/3"), None);
+}
+
+#[test]
+fn test_progress_line() {
+    let line = progress_line(12_000_000, Some(24_000_000));
+    assert!(line.starts_with(&format!("[{}{}]", "#".repeat(15), "-".repeat(15))));
+    assert!(line.contains(" 50%"));
+    assert!(line.ends_with("12.0/24.0 MB"));
+    assert!(progress_line(30, Some(10)).contains("100%"));
+    assert_eq!(progress_line(2_500_000, None), "2.5 MB");
+}
+
+/// Serve `body` for one request, honouring `Range: bytes=<start>-` when `ranges` is
+/// set. Returns the server URL and the request it received.
+async fn serve_once(
+    body: &'static [u8],
+    ranges: bool,
+) -> (String, tokio::task::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/runner.tar.gz", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = socket.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        let request = String::from_utf8_lossy(&request).to_lowercase();
+        let start = request
+            .lines()
+            .find_map(|l| l.strip_prefix("range: bytes="))
+            .and_then(|r| r.trim_end_matches('-').parse::<usize>().ok())
+            .filter(|_| ranges);
+        let response = match start {
+            Some(start) => format!(
+                "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\n\
+                 Content-Range: bytes {start}-{}/{}\r\nConnection: close\r\n\r\n",
+                body.len() - start,
+                body.len() - 1,
+                body.len()
+            ),
+            None => format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            ),
+        };
+        socket.write_all(response.as_bytes()).await.unwrap();
+        socket.write_all(&body[start.unwrap_or(0)..]).await.unwrap();
+        socket.shutdown().await.unwrap();
+        request
+    });
+    (url, handle)
+}
+
+#[tokio::test]
+async fn test_download_verifies_checksum() {
+    let tmp = TempDir::new().unwrap();
+    let dest = tmp.path().join("runner.tar.gz");
+    let client = reqwest::Client::new();
+
+    let (url, server) = serve_once(b"abc", true).await;
+    download(&client, &url, &dest, Some(ABC_SHA256))
+        .await
+        .unwrap();
+    server.await.unwrap();
+    assert_eq!(fs::read(&dest).unwrap(), b"abc");
+    assert!(!tmp.path().join("runner.tar.gz.part").exists());
+
+    // A corrupted download is deleted, not left to be resumed or cached
+    let dest = tmp.path().join("bad.tar.gz");
+    let (url, server) = serve_once(b"abd", true).await;
+    let err = download(&client, &url, &dest, Some(ABC_SHA256))
+        .await
+        .unwrap_err();
+    server.await.unwrap();
+    assert!(err.to_string().contains("Checksum mismatch"));
+    assert!(!dest.exists());
+    assert!(!tmp.path().join("bad.tar.gz.part").exists());
+}
+
+#[tokio::test]
+async fn test_download_resumes_partial_file() {
+    let tmp = TempDir::new().unwrap();
+    let dest = tmp.path().join("runner.tar.gz");
+    fs::write(tmp.path().join("runner.tar.gz.part"), "ab").unwrap();
+    let client = reqwest::Client::new();
+
+    let (url, server) = serve_once(b"abc", true).await;
+    download(&client, &url, &dest, Some(ABC_SHA256))
+        .await
+        .unwrap();
+    let request = server.await.unwrap();
+    assert!(request.contains("range: bytes=2-"));
+    assert_eq!(fs::read(&dest).unwrap(), b"abc");
+
+    // A server ignoring the range sends the whole file, which replaces the partial one
+    let dest = tmp.path().join("full.tar.gz");
+    fs::write(tmp.path().join("full.tar.gz.part"), "xx").unwrap();
+    let (url, server) = serve_once(b"abc", false).await;
+    download(&client, &url, &dest, None).await.unwrap();
+    server.await.unwrap();
+    assert_eq!(fs::read(&dest).unwrap(), b"abc");
+}
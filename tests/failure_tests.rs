@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serial_test::serial;
+use tempfile::TempDir;
+
+use runner_mgr::failure::{
+    exit_code, Failure, EXIT_API, EXIT_AUTH, EXIT_CONFIG_MISSING, EXIT_FAILURE,
+    EXIT_INVALID_ACCOUNT, EXIT_RUNNER_NOT_FOUND, EXIT_SERVICE_CONTROL,
+};
+use runner_mgr::github::{ApiError, RunnerScope};
+use runner_mgr::runner::{RunnerInstance, RunnerStatus};
+
+fn api_error(status: u16) -> ApiError {
+    ApiError {
+        action: "list runners".to_string(),
+        status,
+        rate_limit_remaining: None,
+        rate_limit_reset: None,
+        retry_after: None,
+        message: None,
+        accepted_permissions: None,
+        accepted_scopes: None,
+        token_scopes: None,
+        scope: None,
+    }
+}
+
+#[test]
+fn test_exit_codes_are_distinct() {
+    let codes = [
+        EXIT_FAILURE,
+        EXIT_CONFIG_MISSING,
+        EXIT_AUTH,
+        EXIT_API,
+        EXIT_SERVICE_CONTROL,
+        EXIT_RUNNER_NOT_FOUND,
+        EXIT_INVALID_ACCOUNT,
+    ];
+    for (i, code) in codes.iter().enumerate() {
+        assert!(!codes[i + 1..].contains(code), "{code} is used twice");
+        // 2 is clap's usage error, 130 an interrupt
+        assert!(![0, 2, 130].contains(code));
+    }
+}
+
+#[test]
+fn test_failure_exit_code_through_context() {
+    let e = anyhow::Error::from(Failure::ServiceControl("systemctl failed".to_string()));
+    assert_eq!(exit_code(&e), EXIT_SERVICE_CONTROL);
+    assert_eq!(format!("{e:#}"), "systemctl failed");
+
+    let e = e.context("Failed to restart owner/repo");
+    assert_eq!(exit_code(&e), EXIT_SERVICE_CONTROL);
+
+    let e = Err::<(), _>(std::io::Error::other("exit status 1"))
+        .with_context(|| Failure::ServiceControl("Failed to start".to_string()))
+        .unwrap_err();
+    assert_eq!(exit_code(&e), EXIT_SERVICE_CONTROL);
+    assert_eq!(format!("{e:#}"), "Failed to start: exit status 1");
+}
+
+#[test]
+fn test_api_error_exit_codes() {
+    let code = |e: ApiError| exit_code(&anyhow::Error::from(e).context("Failed to add runner"));
+    assert_eq!(code(api_error(401)), EXIT_AUTH);
+    assert_eq!(code(api_error(403)), EXIT_AUTH);
+    assert_eq!(code(api_error(404)), EXIT_API);
+    assert_eq!(code(api_error(503)), EXIT_API);
+
+    let mut rate_limited = api_error(403);
+    rate_limited.rate_limit_remaining = Some(0);
+    assert_eq!(code(rate_limited), EXIT_API);
+}
+
+#[test]
+fn test_other_errors_exit_1() {
+    assert_eq!(exit_code(&anyhow::anyhow!("something broke")), EXIT_FAILURE);
+}
+
+#[test]
+fn test_unknown_target_exits_runner_not_found() {
+    let instances = vec![RunnerInstance {
+        scope: RunnerScope::parse("owner/repo").unwrap(),
+        name: None,
+        dir: PathBuf::from("/opt/github-runners/instances/owner__repo"),
+        service_name: None,
+        status: RunnerStatus::NoService,
+        runner_name: None,
+    }];
+    let e = runner_mgr::target::resolve(&instances, "owner/other").unwrap_err();
+    assert_eq!(exit_code(&e), EXIT_RUNNER_NOT_FOUND);
+}
+
+#[test]
+#[serial]
+fn test_missing_config_exits_config_missing() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("nonexistent");
+    std::env::set_var("RUNNER_MGR_CONFIG_DIR", config_dir.to_str().unwrap());
+
+    let e = runner_mgr::config::Config::load().unwrap_err();
+    assert_eq!(exit_code(&e), EXIT_CONFIG_MISSING);
+
+    std::env::remove_var("RUNNER_MGR_CONFIG_DIR");
+}
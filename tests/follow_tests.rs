@@ -0,0 +1,67 @@
+use runner_mgr::follow::{newest_diag_log, DiagTail};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+fn write_log(dir: &Path, name: &str, content: &str, age: Duration) {
+    let path = dir.join("_diag").join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, content).unwrap();
+    File::open(&path)
+        .unwrap()
+        .set_modified(SystemTime::now() - age)
+        .unwrap();
+}
+
+fn append(path: &Path, content: &str) {
+    let mut file = OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(content.as_bytes()).unwrap();
+}
+
+#[test]
+fn test_newest_diag_log() {
+    let tmp = TempDir::new().unwrap();
+    assert_eq!(newest_diag_log(tmp.path()), None);
+
+    write_log(tmp.path(), "Runner_1.log", "", Duration::from_secs(20));
+    write_log(tmp.path(), "Runner_2.log", "", Duration::from_secs(10));
+    write_log(tmp.path(), "Worker_3.log", "", Duration::ZERO);
+    assert_eq!(
+        newest_diag_log(tmp.path()),
+        Some(tmp.path().join("_diag/Runner_2.log"))
+    );
+}
+
+#[test]
+fn test_diag_tail_follows_new_lines() {
+    let tmp = TempDir::new().unwrap();
+    let mut tail = DiagTail::new(tmp.path(), 2);
+    assert!(tail.poll().unwrap().is_empty(), "no logs yet");
+
+    write_log(
+        tmp.path(),
+        "Runner_1.log",
+        "a\nb\nc\n",
+        Duration::from_secs(20),
+    );
+    // Only the backlog of the existing log
+    assert_eq!(tail.poll().unwrap(), ["b", "c"]);
+    assert!(tail.poll().unwrap().is_empty());
+
+    // A partial line waits for its newline
+    let log = tmp.path().join("_diag/Runner_1.log");
+    append(&log, "d\r\ne");
+    assert_eq!(tail.poll().unwrap(), ["d"]);
+    append(&log, "nd\n");
+    assert_eq!(tail.poll().unwrap(), ["end"]);
+
+    // The runner restarted: a new log is read from its start
+    write_log(tmp.path(), "Runner_2.log", "x\ny\nz\n", Duration::ZERO);
+    assert_eq!(tail.poll().unwrap(), ["x", "y", "z"]);
+
+    // Truncated in place: read again from the start
+    fs::write(tmp.path().join("_diag/Runner_2.log"), "new\n").unwrap();
+    assert_eq!(tail.poll().unwrap(), ["new"]);
+}
@@ -1,4 +1,8 @@
-use runner_mgr::github::{GitHubClient, RunnerScope};
+use runner_mgr::github::{
+    describe_permissions, find_runner, first_error, http_log_line, repo_search_query, ApiError,
+    CheckAnnotation, GitHubClient, Repository, Runner, RunnerScope, WorkflowJob, DEFAULT_WEB_URL,
+};
+use std::time::Duration;
 
 #[tokio::test]
 async fn test_client_creation() {
@@ -45,3 +49,360 @@ async fn test_list_workflow_runs_invalid_token() {
     let result = client.list_workflow_runs("nonexistent", "repo", 5).await;
     assert!(result.is_err());
 }
+
+fn api_error(status: u16, remaining: Option<u64>) -> ApiError {
+    ApiError {
+        action: "list runners".to_string(),
+        status,
+        rate_limit_remaining: remaining,
+        rate_limit_reset: None,
+        retry_after: None,
+        message: None,
+        accepted_permissions: None,
+        accepted_scopes: None,
+        token_scopes: None,
+        scope: None,
+    }
+}
+
+#[test]
+fn test_api_error_display() {
+    assert_eq!(
+        api_error(401, None).to_string(),
+        "Failed to list runners: 401 Unauthorized"
+    );
+}
+
+#[test]
+fn test_api_error_rate_limit_detection() {
+    assert!(api_error(403, Some(0)).is_rate_limited());
+    assert!(api_error(429, None).is_rate_limited());
+    assert!(!api_error(403, Some(4999)).is_rate_limited());
+    assert!(!api_error(401, None).is_rate_limited());
+}
+
+#[test]
+fn test_secondary_rate_limit() {
+    let secondary = ApiError {
+        retry_after: Some(90),
+        ..api_error(403, Some(4000))
+    };
+    assert!(secondary.is_secondary_rate_limit());
+    assert!(secondary.is_rate_limited());
+    assert_eq!(secondary.pause(0), Some(Duration::from_secs(90)));
+    assert!(secondary
+        .suggestion(DEFAULT_WEB_URL)
+        .contains("secondary rate limit"));
+    assert!(!api_error(403, Some(4000)).is_secondary_rate_limit());
+}
+
+#[test]
+fn test_rate_limit_pause() {
+    let exhausted = ApiError {
+        rate_limit_reset: Some(1_000_120),
+        ..api_error(403, Some(0))
+    };
+    assert_eq!(exhausted.pause(1_000_000), Some(Duration::from_mins(2)));
+    assert_eq!(exhausted.pause(1_000_500), Some(Duration::ZERO));
+    assert_eq!(api_error(429, None).pause(0), Some(Duration::from_mins(1)));
+    assert_eq!(api_error(403, Some(10)).pause(0), None);
+    assert_eq!(api_error(502, None).pause(0), None);
+}
+
+#[test]
+fn test_api_error_suggestions() {
+    assert!(api_error(401, None)
+        .suggestion(DEFAULT_WEB_URL)
+        .contains("invalid or expired"));
+    assert!(api_error(403, Some(10))
+        .suggestion(DEFAULT_WEB_URL)
+        .contains("permission"));
+    assert!(api_error(403, Some(0))
+        .suggestion(DEFAULT_WEB_URL)
+        .contains("rate limit"));
+    assert!(api_error(404, None)
+        .suggestion(DEFAULT_WEB_URL)
+        .contains("Not found"));
+    assert!(api_error(502, None)
+        .suggestion(DEFAULT_WEB_URL)
+        .contains("githubstatus"));
+}
+
+#[test]
+fn test_repository_admin_permission() {
+    let admin: Repository = serde_json::from_str(
+        r#"{"full_name": "org/app", "private": true, "archived": false,
+            "permissions": {"admin": true, "push": true, "pull": true}}"#,
+    )
+    .unwrap();
+    assert!(admin.is_admin());
+
+    let writer: Repository = serde_json::from_str(
+        r#"{"full_name": "org/app", "private": true, "archived": false,
+            "permissions": {"admin": false, "push": true, "pull": true}}"#,
+    )
+    .unwrap();
+    assert!(!writer.is_admin());
+
+    let unknown: Repository =
+        serde_json::from_str(r#"{"full_name": "org/app", "private": false, "archived": false}"#)
+            .unwrap();
+    assert!(!unknown.is_admin());
+}
+
+#[test]
+fn test_repo_search_query_restricts_owners() {
+    let owners = vec!["me".to_string(), "acme".to_string(), "tools".to_string()];
+    assert_eq!(
+        repo_search_query(" billing ", &owners),
+        "billing in:name user:me org:acme org:tools"
+    );
+}
+
+#[test]
+fn test_http_log_line() {
+    let line = http_log_line(
+        "GET",
+        "https://api.github.com/repos/owner/repo/actions/runners",
+        "403 Forbidden",
+        &[
+            ("x-ratelimit-remaining", "0".to_string()),
+            ("x-ratelimit-reset", "1700000000".to_string()),
+        ],
+        Duration::from_millis(182),
+        "ghp_secret",
+    );
+    assert_eq!(
+        line,
+        "[http] GET https://api.github.com/repos/owner/repo/actions/runners -> 403 Forbidden \
+         in 182ms [ratelimit remaining=0 reset=1700000000]"
+    );
+}
+
+#[test]
+fn test_http_log_line_redacts_token() {
+    let line = http_log_line(
+        "POST",
+        "https://api.github.com/?access_token=ghp_secret",
+        "error: connection refused",
+        &[],
+        Duration::from_millis(3),
+        "ghp_secret",
+    );
+    assert!(!line.contains("ghp_secret"));
+    assert!(line.contains("access_token=[REDACTED]"));
+    assert!(!line.contains("ratelimit"));
+}
+
+fn runner_record(name: &str) -> Runner {
+    serde_json::from_value(serde_json::json!({
+        "id": 1,
+        "name": name,
+        "os": "Linux",
+        "status": "online",
+        "busy": false,
+        "labels": [],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_find_runner_by_name() {
+    let runners = vec![
+        runner_record("ci-01-owner__repo"),
+        runner_record("ci-02-owner__repo"),
+    ];
+    assert_eq!(
+        find_runner(&runners, Some("ci-02-owner__repo")).map(|r| r.name.as_str()),
+        Some("ci-02-owner__repo")
+    );
+    assert!(find_runner(&runners, Some("ci-03-owner__repo")).is_none());
+    // Several runners and no name: ambiguous
+    assert!(find_runner(&runners, None).is_none());
+    // A single runner is assumed to be ours
+    assert!(find_runner(&runners[..1], None).is_some());
+}
+
+#[test]
+fn test_runner_release_package_sha256() {
+    use runner_mgr::github::{ReleaseAsset, RunnerRelease};
+
+    let hash = "a".repeat(64);
+    let release = RunnerRelease {
+        tag_name: "v2.321.0".to_string(),
+        body: Some(format!(
+            "- actions-runner-linux-x64-2.321.0.tar.gz <!-- BEGIN SHA linux-x64 -->{hash}<!-- END SHA linux-x64 -->\n\
+             - actions-runner-osx-arm64-2.321.0.tar.gz <!-- BEGIN SHA osx-arm64 -->not-a-hash<!-- END SHA osx-arm64 -->"
+        )),
+        assets: vec![ReleaseAsset {
+            name: "actions-runner-win-x64-2.321.0.zip".to_string(),
+            digest: Some(format!("sha256:{}", "b".repeat(64))),
+        }],
+    };
+    // The asset digest wins; the release notes are the fallback
+    assert_eq!(
+        release.package_sha256("actions-runner-win-x64-2.321.0.zip"),
+        Some("b".repeat(64))
+    );
+    assert_eq!(
+        release.package_sha256("actions-runner-linux-x64-2.321.0.tar.gz"),
+        Some(hash)
+    );
+    assert_eq!(
+        release.package_sha256("actions-runner-osx-arm64-2.321.0.tar.gz"),
+        None
+    );
+    assert_eq!(
+        release.package_sha256("actions-runner-linux-arm64-2.321.0.tar.gz"),
+        None
+    );
+}
+
+#[test]
+fn test_workflow_run_cancel_and_rerun() {
+    use runner_mgr::github::WorkflowRun;
+
+    let run = |status: &str| WorkflowRun {
+        id: 1,
+        name: Some("CI".to_string()),
+        status: status.to_string(),
+        conclusion: None,
+        head_branch: None,
+        created_at: "2025-01-01T00:00:00Z".to_string(),
+        updated_at: "2025-01-01T00:05:00Z".to_string(),
+        html_url: "https://github.com/owner/repo/actions/runs/1".to_string(),
+    };
+    for status in ["queued", "in_progress", "waiting"] {
+        assert!(run(status).can_cancel(), "{status}");
+        assert!(!run(status).can_rerun(), "{status}");
+    }
+    assert!(!run("completed").can_cancel());
+    assert!(run("completed").can_rerun());
+}
+
+#[test]
+fn test_describe_permissions() {
+    assert_eq!(
+        describe_permissions("administration=write"),
+        "'Administration: write'"
+    );
+    assert_eq!(
+        describe_permissions("organization_self_hosted_runners=write; administration=read"),
+        "'Self-hosted runners: write' or 'Administration: read'"
+    );
+    assert_eq!(
+        describe_permissions("actions=read,metadata=read"),
+        "'Actions: read' and 'Metadata: read'"
+    );
+}
+
+#[test]
+fn test_permission_hint_for_fine_grained_tokens() {
+    let error = ApiError {
+        action: "get a registration token".to_string(),
+        message: Some("Resource not accessible by personal access token".to_string()),
+        accepted_permissions: Some("administration=write".to_string()),
+        scope: Some(RunnerScope::parse("owner/repo").unwrap()),
+        ..api_error(403, Some(4000))
+    };
+    assert_eq!(
+        error.permission_hint().as_deref(),
+        Some("fine-grained token needs 'Administration: write' on repo owner/repo")
+    );
+    assert!(error
+        .suggestion(DEFAULT_WEB_URL)
+        .contains("'Administration: write'"));
+    assert!(error
+        .suggestion("https://ghe.example.com")
+        .contains("https://ghe.example.com/settings/tokens"));
+    assert!(error.to_string().ends_with(
+        "403 Forbidden (fine-grained token needs 'Administration: write' on repo owner/repo)"
+    ));
+
+    let org = ApiError {
+        accepted_permissions: Some("organization_self_hosted_runners=write".to_string()),
+        scope: Some(RunnerScope::parse("org:acme").unwrap()),
+        ..error.clone()
+    };
+    assert_eq!(
+        org.permission_hint().as_deref(),
+        Some("fine-grained token needs 'Self-hosted runners: write' on organization acme")
+    );
+
+    // Rate limits and errors without a scope keep the generic text
+    let limited = ApiError {
+        rate_limit_remaining: Some(0),
+        ..error.clone()
+    };
+    assert_eq!(limited.permission_hint(), None);
+    assert_eq!(api_error(403, Some(4000)).permission_hint(), None);
+}
+
+#[test]
+fn test_permission_hint_for_classic_tokens() {
+    let error = ApiError {
+        accepted_permissions: Some("organization_self_hosted_runners=write".to_string()),
+        accepted_scopes: Some("admin:org".to_string()),
+        token_scopes: Some("repo, workflow".to_string()),
+        scope: Some(RunnerScope::parse("org:acme").unwrap()),
+        ..api_error(403, Some(4000))
+    };
+    assert_eq!(
+        error.permission_hint().as_deref(),
+        Some("classic token needs the 'admin:org' scope for organization acme (it has: repo, workflow)")
+    );
+}
+
+#[test]
+fn test_first_error() {
+    let job: WorkflowJob = serde_json::from_value(serde_json::json!({
+        "id": 1,
+        "name": "test",
+        "status": "completed",
+        "conclusion": "failure",
+        "runner_name": "ci-01",
+        "html_url": null,
+        "steps": [
+            {"name": "Checkout", "status": "completed", "conclusion": "success", "number": 1},
+            {"name": "Run tests", "status": "completed", "conclusion": "failure", "number": 2}
+        ]
+    }))
+    .unwrap();
+    let annotation = |path: &str, line: Option<u64>, level: &str, message: &str| CheckAnnotation {
+        path: path.to_string(),
+        start_line: line,
+        annotation_level: Some(level.to_string()),
+        message: message.to_string(),
+    };
+    let exit_code = annotation(
+        ".github",
+        Some(12),
+        "failure",
+        "Process completed with exit code 101.",
+    );
+
+    // The failed step when there are no annotations
+    assert_eq!(
+        first_error(&job, &[]).as_deref(),
+        Some("Step 'Run tests' failed")
+    );
+    // The generic exit code annotation beats the step, but not a real error
+    assert_eq!(
+        first_error(&job, std::slice::from_ref(&exit_code)).as_deref(),
+        Some("Process completed with exit code 101.")
+    );
+    let annotations = [
+        annotation("src/lib.rs", Some(3), "warning", "unused variable"),
+        exit_code,
+        annotation(
+            "src/lib.rs",
+            Some(7),
+            "failure",
+            "\nassertion failed: left == right\n  left: 1",
+        ),
+    ];
+    assert_eq!(
+        first_error(&job, &annotations).as_deref(),
+        Some("src/lib.rs:7: assertion failed: left == right")
+    );
+}
@@ -0,0 +1,67 @@
+use runner_mgr::health::{handle_request, CollectorHealth, MIN_STALE_AFTER};
+use runner_mgr::webhook::parse_request;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+fn get(path: &str, health: &Mutex<CollectorHealth>) -> (u16, serde_json::Value) {
+    let raw = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n");
+    let request = parse_request(raw.as_bytes()).unwrap().unwrap();
+    let (status, body) = handle_request(&request, health);
+    (status, serde_json::from_str(&body).unwrap())
+}
+
+#[test]
+fn test_liveness_follows_polls() {
+    let start = Instant::now();
+    let interval = Duration::from_mins(10);
+    let mut health = CollectorHealth::started_at(start, interval);
+
+    // Live while the first poll runs, but not ready until it finished
+    assert!(health.is_live(start + Duration::from_mins(1)));
+    assert!(!health.is_ready(start + Duration::from_mins(1)));
+    assert!(!health.is_live(start + interval * 3 + Duration::from_secs(1)));
+
+    let polled = start + Duration::from_secs(30);
+    health.record_poll(polled, &Ok(()), &Ok(()));
+    assert!(health.is_ready(polled + interval));
+    assert!(!health.is_live(polled + interval * 4));
+
+    // Short intervals still allow a slow poll
+    let health = CollectorHealth::started_at(start, Duration::from_secs(5));
+    assert!(health.is_live(start + MIN_STALE_AFTER));
+}
+
+#[test]
+fn test_readiness_needs_database_and_github() {
+    let start = Instant::now();
+    let mut health = CollectorHealth::started_at(start, Duration::from_mins(1));
+    health.record_poll(
+        start,
+        &Ok(()),
+        &Err(anyhow::anyhow!("GitHub API unreachable")),
+    );
+    assert!(health.is_live(start));
+    assert!(!health.is_ready(start));
+    let report = health.report(start);
+    assert_eq!(report["github"]["ok"], false);
+    assert_eq!(report["github"]["error"], "GitHub API unreachable");
+    assert_eq!(report["database"]["ok"], true);
+}
+
+#[test]
+fn test_health_endpoints() {
+    let health = Mutex::new(CollectorHealth::new(Duration::from_mins(1)));
+    let (status, body) = get("/healthz", &health);
+    assert_eq!(status, 200);
+    assert_eq!(body["live"], true);
+    let (status, body) = get("/readyz", &health);
+    assert_eq!(status, 503);
+    assert_eq!(body["ready"], false);
+
+    health
+        .lock()
+        .unwrap()
+        .record_poll(Instant::now(), &Ok(()), &Ok(()));
+    assert_eq!(get("/readyz?verbose", &health).0, 200);
+    assert_eq!(get("/metrics", &health).0, 404);
+}
@@ -0,0 +1,32 @@
+use runner_mgr::interrupt::{on_interrupt, run_cleanups};
+use serial_test::serial;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+#[serial]
+fn test_cleanup_runs_once_while_armed() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = Arc::clone(&count);
+    let _guard = on_interrupt(move || {
+        c.fetch_add(1, Ordering::SeqCst);
+    });
+
+    run_cleanups();
+    run_cleanups();
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+#[serial]
+fn test_dropped_guard_disarms_cleanup() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = Arc::clone(&count);
+    let guard = on_interrupt(move || {
+        c.fetch_add(1, Ordering::SeqCst);
+    });
+    drop(guard);
+
+    run_cleanups();
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
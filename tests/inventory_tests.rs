@@ -0,0 +1,58 @@
+use runner_mgr::inventory::{to_csv, to_markdown, InventoryRow};
+
+fn rows() -> Vec<InventoryRow> {
+    vec![
+        InventoryRow {
+            scope: "owner/web".to_string(),
+            host: "ci-01".to_string(),
+            labels: vec!["self-hosted".to_string(), "linux".to_string()],
+            version: Some("2.320.0".to_string()),
+            status: "running".to_string(),
+            uptime: Some(99.25),
+            note: Some("pending RAM upgrade, \"soon\"".to_string()),
+        },
+        InventoryRow {
+            scope: "org:acme".to_string(),
+            host: "ci-01".to_string(),
+            labels: vec!["self-hosted".to_string()],
+            version: None,
+            status: "stopped".to_string(),
+            uptime: None,
+            note: Some("a | b".to_string()),
+        },
+    ]
+}
+
+#[test]
+fn test_inventory_markdown() {
+    let md = to_markdown(&rows());
+    let lines: Vec<&str> = md.lines().collect();
+    assert_eq!(
+        lines[0],
+        "| Scope | Host | Labels | Version | Status | Uptime (7d) | Notes |"
+    );
+    assert_eq!(lines[1], "|---|---|---|---|---|---|---|");
+    assert_eq!(
+        lines[2],
+        "| owner/web | ci-01 | self-hosted,linux | 2.320.0 | running | 99.2% | pending RAM upgrade, \"soon\" |"
+    );
+    assert_eq!(
+        lines[3],
+        "| org:acme | ci-01 | self-hosted | - | stopped | - | a \\| b |"
+    );
+}
+
+#[test]
+fn test_inventory_csv_quoting() {
+    let csv = to_csv(&rows());
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "Scope,Host,Labels,Version,Status,Uptime (7d),Notes"
+    );
+    assert_eq!(
+        lines[1],
+        "owner/web,ci-01,\"self-hosted,linux\",2.320.0,running,99.2%,\"pending RAM upgrade, \"\"soon\"\"\""
+    );
+    assert_eq!(lines[2], "org:acme,ci-01,self-hosted,-,stopped,-,a | b");
+}
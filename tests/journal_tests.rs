@@ -0,0 +1,63 @@
+use runner_mgr::github::RunnerScope;
+use runner_mgr::journal::{
+    identifier_dropin, parse_entries, parse_entry, priority_label, syslog_identifier,
+};
+
+#[test]
+fn test_parse_journal_entry() {
+    let entry = parse_entry(
+        r#"{"__REALTIME_TIMESTAMP":"1700000000123456","PRIORITY":"3",
+            "SYSLOG_IDENTIFIER":"runner-mgr-owner__repo",
+            "MESSAGE":"Job build completed with result: Failed\n"}"#,
+    )
+    .unwrap();
+    assert_eq!(entry.priority, 3);
+    assert_eq!(entry.level(), "ERROR");
+    assert_eq!(entry.message, "Job build completed with result: Failed");
+    assert_eq!(entry.identifier.as_deref(), Some("runner-mgr-owner__repo"));
+    assert_eq!(entry.timestamp.unwrap().timestamp(), 1_700_000_000);
+    assert!(entry
+        .display()
+        .ends_with("ERROR   Job build completed with result: Failed"));
+}
+
+#[test]
+fn test_parse_journal_entry_defaults_and_binary_message() {
+    // Non-UTF-8 messages come as byte arrays; missing priority means info
+    let entry = parse_entry(r#"{"MESSAGE":[104,105,255]}"#).unwrap();
+    assert_eq!(entry.priority, 6);
+    assert!(entry.message.starts_with("hi"));
+    assert!(entry.timestamp.is_none());
+    assert!(entry.display().starts_with("--:--:-- INFO"));
+    // Records without a message are skipped
+    assert!(parse_entry(r#"{"PRIORITY":"6"}"#).is_none());
+}
+
+#[test]
+fn test_parse_journal_entries_skips_noise() {
+    let output =
+        "-- No entries --\n{\"MESSAGE\":\"one\"}\n\n{\"MESSAGE\":\"two\",\"PRIORITY\":\"4\"}\n";
+    let entries = parse_entries(output);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].level(), "WARN");
+}
+
+#[test]
+fn test_priority_labels() {
+    assert_eq!(priority_label(0), "ERROR");
+    assert_eq!(priority_label(5), "NOTICE");
+    assert_eq!(priority_label(7), "DEBUG");
+}
+
+#[test]
+fn test_identifier_dropin() {
+    let scope = RunnerScope::parse("owner/repo").unwrap();
+    assert_eq!(syslog_identifier(&scope), "runner-mgr-owner__repo");
+    assert!(
+        identifier_dropin(&scope).contains("[Service]\nSyslogIdentifier=runner-mgr-owner__repo\n")
+    );
+
+    // Escaped names keep their `%` literal in the unit file
+    let scope = RunnerScope::parse("my_user/repo").unwrap();
+    assert!(identifier_dropin(&scope).contains("SyslogIdentifier=runner-mgr-my%%5Fuser__repo\n"));
+}
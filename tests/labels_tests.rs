@@ -0,0 +1,181 @@
+use runner_mgr::github::Runner;
+use runner_mgr::labels::{
+    audit_labels, expand_auto_labels, fit_labels, host_mismatches, label_arch, label_os,
+    merge_labels, parse_labels, record_mismatches, registered_after_edit, runner_mismatches,
+    suggest_label,
+};
+
+fn labels(s: &str) -> Vec<String> {
+    parse_labels(s)
+}
+
+#[test]
+fn test_parse_labels_trims_and_dedupes() {
+    assert_eq!(
+        parse_labels(" self-hosted, linux ,,Linux,gpu"),
+        vec!["self-hosted", "linux", "gpu"]
+    );
+}
+
+#[test]
+fn test_label_os_aliases() {
+    assert_eq!(label_os("macOS"), Some("darwin"));
+    assert_eq!(label_os("osx"), Some("darwin"));
+    assert_eq!(label_os("Linux"), Some("linux"));
+    assert_eq!(label_os("gpu"), None);
+}
+
+#[test]
+fn test_label_arch_aliases() {
+    assert_eq!(label_arch("X64"), Some("x64"));
+    assert_eq!(label_arch("aarch64"), Some("arm64"));
+    assert_eq!(label_arch("self-hosted"), None);
+}
+
+#[test]
+fn test_host_mismatches_none_when_matching() {
+    let warnings = host_mismatches(&labels("self-hosted,linux,x64,docker"), "linux", "x64");
+    assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+}
+
+#[test]
+fn test_host_mismatches_wrong_os_and_arch() {
+    let warnings = host_mismatches(&labels("self-hosted,macos,arm64"), "linux", "x64");
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].contains("'macos'"));
+    assert!(warnings[1].contains("'arm64'"));
+}
+
+#[test]
+fn test_record_mismatches_ignores_default_labels() {
+    let registered = labels("self-hosted,gpu");
+    let github = labels("self-hosted,Linux,X64,gpu");
+    assert!(record_mismatches(&registered, &github).is_empty());
+}
+
+#[test]
+fn test_record_mismatches_reports_both_directions() {
+    let registered = labels("self-hosted,gpu");
+    let github = labels("self-hosted,Linux,cuda");
+    let warnings = record_mismatches(&registered, &github);
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].contains("'gpu' is missing on GitHub"));
+    assert!(warnings[1].contains("'cuda'"));
+}
+
+#[test]
+fn test_runner_mismatches_checks_host_and_record() {
+    let record: Runner = serde_json::from_value(serde_json::json!({
+        "id": 1,
+        "name": "host-gpu",
+        "os": "macOS",
+        "status": "online",
+        "busy": false,
+        "labels": [{"name": "self-hosted"}, {"name": "cuda"}],
+    }))
+    .unwrap();
+    let registered = labels("self-hosted,linux,gpu");
+    assert!(runner_mismatches(&registered, None, "linux", "x64").is_empty());
+
+    let warnings = runner_mismatches(&registered, Some(&record), "linux", "x64");
+    assert_eq!(warnings.len(), 3, "{warnings:?}");
+    assert!(warnings[0].contains("GitHub reports os 'macOS'"));
+    assert!(warnings[1].contains("'gpu' is missing on GitHub"));
+    assert!(warnings[2].contains("'cuda'"));
+}
+
+#[test]
+fn test_merge_labels_dedupes_case_insensitively() {
+    let merged = merge_labels(&labels("self-hosted,gpu"), &labels("GPU,linux"));
+    assert_eq!(merged, vec!["self-hosted", "gpu", "linux"]);
+}
+
+#[test]
+fn test_expand_auto_labels() {
+    let patterns = vec![
+        "{os}".to_string(),
+        "{arch}".to_string(),
+        "host-{hostname}".to_string(),
+    ];
+    let expanded = expand_auto_labels(&patterns, "darwin", "arm64", "build mac");
+    assert_eq!(expanded, vec!["macos", "arm64", "host-build-mac"]);
+}
+
+#[test]
+fn test_expand_auto_labels_linux() {
+    let patterns = vec!["{os}".to_string(), "{arch}".to_string()];
+    assert_eq!(
+        expand_auto_labels(&patterns, "linux", "x64", "box"),
+        vec!["linux", "x64"]
+    );
+}
+
+#[test]
+fn test_suggest_label() {
+    let known = labels("gpu,docker,build-cache");
+    assert_eq!(suggest_label("dokcer", &known), None);
+    assert_eq!(suggest_label("docke", &known), Some("docker"));
+    assert_eq!(suggest_label("bild-cahe", &known), Some("build-cache"));
+    assert_eq!(suggest_label("gpus", &known), Some("gpu"));
+    // Short labels and exact (case-insensitive) matches are never typos
+    assert_eq!(suggest_label("cpu", &known), None);
+    assert_eq!(suggest_label("Docker", &known), None);
+}
+
+#[test]
+fn test_audit_labels() {
+    let known = labels("gpu,docker");
+    let registered = labels("self-hosted,docker,gpu");
+    let github = labels("Linux,X64,self-hostd,dokker,staging");
+
+    let audit = audit_labels(&github, &registered, &known);
+    assert!(audit.missing_self_hosted);
+    assert_eq!(
+        audit.typos,
+        vec![
+            ("self-hostd".to_string(), "self-hosted".to_string()),
+            ("dokker".to_string(), "docker".to_string()),
+        ]
+    );
+    assert_eq!(audit.missing, labels("docker,gpu"));
+    assert_eq!(audit.unexpected, labels("staging"));
+    assert_eq!(audit.findings().len(), 6);
+
+    // Typos replaced, missing labels added, deliberate extras kept
+    let custom = labels("self-hostd,dokker,staging");
+    assert_eq!(
+        audit.fixed_labels(&custom, &github),
+        labels("staging,self-hosted,docker,gpu")
+    );
+
+    let clean = audit_labels(&labels("self-hosted,Linux,docker,gpu"), &registered, &known);
+    assert!(clean.is_clean());
+}
+
+#[test]
+fn test_registered_after_edit() {
+    let registered = parse_labels("self-hosted,linux,x64,gpu,cuda-11");
+    // Default labels stay; the custom ones are replaced
+    assert_eq!(
+        registered_after_edit(&registered, &parse_labels("gpu,cuda-12")),
+        parse_labels("self-hosted,linux,x64,gpu,cuda-12")
+    );
+    // Runners registered before `.labels` existed still get self-hosted
+    assert_eq!(
+        registered_after_edit(&[], &parse_labels("gpu")),
+        parse_labels("self-hosted,gpu")
+    );
+}
+
+#[test]
+fn test_fit_labels() {
+    let all = ["gpu", "docker", "ios-build", "xl"];
+    assert_eq!(fit_labels(&all, 40), "gpu,docker,ios-build,xl");
+    assert_eq!(fit_labels(&all, 13), "gpu,docker +2");
+    assert_eq!(fit_labels(&all, 8), "gpu +3");
+    assert_eq!(
+        fit_labels(&["self-hosted-macos-arm64", "gpu"], 10),
+        "self-h… +1"
+    );
+    assert_eq!(fit_labels(&[], 10), "");
+}
@@ -0,0 +1,337 @@
+use chrono::{DateTime, Duration, Utc};
+use runner_mgr::metrics::models::bucket_ratios;
+use runner_mgr::metrics::slo::SloObjective;
+use runner_mgr::metrics::{
+    bucket_peaks, fleet_failure_rate, stack_spans, worst_slo, RunSpan, RunnerFailures,
+    ScopeMetrics, SloState, SloStatus,
+};
+
+fn span(start_min: i64, end_min: i64) -> RunSpan {
+    let base: DateTime<Utc> = "2025-01-01T00:00:00Z".parse().unwrap();
+    RunSpan {
+        scope: "owner/repo".to_string(),
+        start: base + Duration::minutes(start_min),
+        end: base + Duration::minutes(end_min),
+        status: "completed".to_string(),
+        conclusion: Some("success".to_string()),
+    }
+}
+
+#[test]
+fn test_span_columns() {
+    let from: DateTime<Utc> = "2025-01-01T00:00:00Z".parse().unwrap();
+    let to = from + Duration::minutes(100);
+
+    assert_eq!(span(10, 30).columns(from, to, 10), Some((1, 3)));
+    // Short runs still get a column
+    assert_eq!(span(50, 51).columns(from, to, 10), Some((5, 6)));
+    // Runs crossing the edges are clipped
+    assert_eq!(span(-20, 15).columns(from, to, 10), Some((0, 1)));
+    assert_eq!(span(95, 130).columns(from, to, 10), Some((9, 10)));
+    // Outside the window
+    assert_eq!(span(-30, -10).columns(from, to, 10), None);
+    assert_eq!(span(10, 30).columns(from, to, 0), None);
+}
+
+#[test]
+fn test_stack_spans_separates_overlaps() {
+    let rows = stack_spans(&[span(0, 30), span(10, 20), span(30, 40), span(15, 50)]);
+    let starts: Vec<Vec<i64>> = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|s| (s.start - span(0, 0).start).num_minutes())
+                .collect()
+        })
+        .collect();
+    assert_eq!(starts, vec![vec![0, 30], vec![10], vec![15]]);
+}
+
+#[test]
+fn test_bucket_peaks() {
+    let samples = [(0, 2), (5, 4), (12, 1), (39, 3), (40, 9), (-1, 7)];
+    // Four 10-second slices of [0, 40); samples outside are ignored
+    assert_eq!(bucket_peaks(&samples, 0, 40, 4), vec![4, 1, 0, 3]);
+    assert_eq!(bucket_peaks(&samples, 0, 40, 0), Vec::<u32>::new());
+    assert_eq!(bucket_peaks(&samples, 40, 40, 2), vec![0, 0]);
+}
+
+fn failures(name: &str, failed: u32, total: u32) -> RunnerFailures {
+    RunnerFailures {
+        runner_name: name.to_string(),
+        total,
+        failed,
+    }
+}
+
+#[test]
+fn test_runner_failure_outliers() {
+    let fleet = vec![
+        failures("ci-01", 2, 40),
+        failures("ci-02", 3, 40),
+        failures("ci-03", 12, 20),
+        failures("ci-04", 2, 2),
+    ];
+    let rate = fleet_failure_rate(&fleet);
+    assert!((rate - 19.0 / 102.0).abs() < 1e-9);
+
+    assert!(fleet[2].is_outlier(rate));
+    assert!(!fleet[0].is_outlier(rate));
+    // Too few jobs to tell
+    assert!(!fleet[3].is_outlier(rate));
+    // A flawless fleet: any failure stands out
+    assert!(failures("ci-05", 1, 10).is_outlier(0.0));
+    assert!(fleet_failure_rate(&[]).abs() < f64::EPSILON);
+}
+
+fn slo(target: f64, good: u64, total: u64) -> SloStatus {
+    SloStatus::evaluate(SloObjective::Availability, target, 30, good, total)
+}
+
+#[test]
+fn test_slo_error_budget() {
+    // 99% over 1000 events allows 10 bad ones
+    let met = slo(99.0, 998, 1000);
+    assert_eq!(met.state, SloState::Met);
+    assert!((met.budget_remaining.unwrap() - 0.8).abs() < 1e-9);
+    assert_eq!(met.budget_display(), "80%");
+
+    let at_risk = slo(99.0, 991, 1000);
+    assert_eq!(at_risk.state, SloState::AtRisk);
+
+    let violated = slo(99.0, 986, 1000);
+    assert_eq!(violated.state, SloState::Violated);
+    assert_eq!(violated.budget_display(), "-40%");
+    assert!((violated.actual.unwrap() - 98.6).abs() < 1e-9);
+
+    let empty = slo(99.0, 0, 0);
+    assert_eq!(empty.state, SloState::NoData);
+    assert_eq!(empty.budget_display(), "-");
+}
+
+#[test]
+fn test_slo_full_target_has_no_budget() {
+    assert_eq!(slo(100.0, 50, 50).state, SloState::Met);
+    assert_eq!(slo(100.0, 49, 50).state, SloState::Violated);
+}
+
+#[test]
+fn test_worst_slo() {
+    assert!(worst_slo(&[]).is_none());
+    let statuses = [slo(99.0, 998, 1000), slo(95.0, 0, 0), slo(99.0, 992, 1000)];
+    assert_eq!(worst_slo(&statuses), Some(&statuses[2]));
+    let statuses = [slo(99.0, 998, 1000), slo(99.0, 999, 1000)];
+    assert_eq!(worst_slo(&statuses), Some(&statuses[0]));
+}
+
+#[test]
+fn test_report_render() {
+    use runner_mgr::metrics::report::render;
+
+    assert!(render(&[], 7).contains("No metrics recorded yet"));
+
+    let plain = ScopeMetrics {
+        total_runs: 20,
+        successful_runs: 19,
+        failed_runs: 1,
+        success_rate: 95.0,
+        runner_uptime: Some(99.5),
+        ..Default::default()
+    };
+    let out = render(&[("owner/repo".to_string(), plain.clone())], 7);
+    assert!(out.starts_with("Metrics report (last 7 days)"));
+    assert!(out.contains("95.0%"));
+    assert!(out.contains("99.5%"));
+    assert!(!out.contains("Service level objectives"));
+
+    let with_slo = ScopeMetrics {
+        slo: vec![
+            slo(99.0, 998, 1000),
+            SloStatus::evaluate(SloObjective::SuccessRate, 99.0, 7, 986, 1000),
+        ],
+        ..plain
+    };
+    let out = render(&[("owner/repo".to_string(), with_slo)], 7);
+    assert!(out.contains("Service level objectives"));
+    let row = out.lines().find(|l| l.contains("success rate")).unwrap();
+    assert!(row.contains("99%"));
+    assert!(row.contains("7d"));
+    assert!(row.contains("-40%"));
+    assert!(row.ends_with("violated"));
+    // The summary column shows the worst objective
+    let summary = out.lines().nth(3).unwrap();
+    assert!(summary.ends_with("violated"));
+}
+
+#[test]
+fn test_host_of() {
+    use runner_mgr::metrics::hosts::host_of;
+
+    assert_eq!(host_of("ci-01-owner__repo", "owner/repo"), "ci-01");
+    // Additional instances keep their suffix after the scope
+    assert_eq!(host_of("ci-01-owner__repo-gpu", "owner/repo"), "ci-01");
+    // A scope whose name extends this one's is a different match
+    assert_eq!(
+        host_of("ci-01-owner__repo2", "owner/repo"),
+        "ci-01-owner__repo2"
+    );
+    // Runners registered some other way are their own host
+    assert_eq!(host_of("build-box", "owner/repo"), "build-box");
+    assert_eq!(host_of("owner__repo", "owner/repo"), "owner__repo");
+}
+
+#[test]
+fn test_summarize_hosts() {
+    use runner_mgr::github::{Runner, RunnerScope};
+    use runner_mgr::metrics::hosts::{summarize_hosts, RunnerStats};
+
+    let runner = |name: &str, status: &str, busy: bool| Runner {
+        id: 1,
+        name: name.to_string(),
+        os: "Linux".to_string(),
+        status: status.to_string(),
+        busy,
+        labels: Vec::new(),
+    };
+    let repo = RunnerScope::parse("owner/repo").unwrap();
+    let other = RunnerScope::parse("owner/other").unwrap();
+    let current = vec![
+        (
+            repo,
+            vec![
+                runner("ci-01-owner__repo", "online", true),
+                runner("ci-01-owner__repo-2", "offline", false),
+                runner("ci-02-owner__repo", "online", false),
+            ],
+        ),
+        (other, vec![runner("ci-01-owner__other", "online", false)]),
+    ];
+    let history = vec![
+        RunnerStats {
+            scope: "owner/repo".to_string(),
+            runner_name: "ci-01-owner__repo".to_string(),
+            snapshots: 10,
+            online_snapshots: 9,
+            jobs: 8,
+            failed_jobs: 2,
+        },
+        RunnerStats {
+            scope: "owner/other".to_string(),
+            runner_name: "ci-01-owner__other".to_string(),
+            snapshots: 10,
+            online_snapshots: 10,
+            jobs: 2,
+            failed_jobs: 0,
+        },
+    ];
+
+    let hosts = summarize_hosts(&current, &history);
+    assert_eq!(hosts.len(), 2);
+    let ci01 = &hosts[0];
+    assert_eq!(ci01.host, "ci-01");
+    assert_eq!((ci01.runners, ci01.online, ci01.busy), (3, 2, 1));
+    assert_eq!(ci01.uptime(), Some(95.0));
+    assert_eq!(ci01.failure_rate(), Some(0.2));
+
+    let ci02 = &hosts[1];
+    assert_eq!(ci02.host, "ci-02");
+    assert_eq!(ci02.uptime(), None);
+    assert_eq!(ci02.failure_rate(), None);
+
+    let table = runner_mgr::metrics::host_table(&hosts);
+    assert_eq!(table.len(), 3);
+    assert!(table[1].starts_with("ci-01"));
+    assert!(table[1].contains("2/3"));
+    assert!(table[1].contains("95.0%"));
+    assert!(table[1].contains("20.0%"));
+    assert!(table[2].ends_with('-'));
+}
+
+#[test]
+fn test_metrics_formats() {
+    use runner_mgr::metrics::models::{Better, TrendChange};
+    use runner_mgr::metrics::report::{render_metrics, to_csv, to_json};
+
+    let metrics = ScopeMetrics {
+        total_runs: 10,
+        successful_runs: 9,
+        failed_runs: 1,
+        success_rate: 90.0,
+        success_trend: Some(TrendChange {
+            percent: 12.4,
+            better: Better::Higher,
+        }),
+        avg_duration_seconds: Some(95),
+        duration_trend: Some(TrendChange {
+            percent: -2.0,
+            better: Better::Lower,
+        }),
+        runner_uptime: Some(99.5),
+        queue_wait_jobs: 4,
+        avg_queue_wait_seconds: Some(30),
+        p95_queue_wait_seconds: Some(120),
+        queue_history: vec![0, 3, 1],
+        ..Default::default()
+    };
+    let rows = vec![("owner/repo".to_string(), metrics)];
+
+    let table = render_metrics(&rows, 7);
+    let row = table.lines().find(|l| l.starts_with("owner/repo")).unwrap();
+    assert!(row.contains("90.0% ↑ 12%"));
+    assert!(row.contains("1m 35s -"));
+    assert!(row.contains("1m 35s"));
+    assert!(row.contains("2m"));
+
+    let csv = to_csv(&rows);
+    let mut lines = csv.lines();
+    let headers: Vec<_> = lines.next().unwrap().split(',').collect();
+    let cells: Vec<_> = lines.next().unwrap().split(',').collect();
+    assert_eq!(headers.len(), cells.len());
+    let cell = |name: &str| cells[headers.iter().position(|h| *h == name).unwrap()];
+    assert_eq!(cell("success_trend"), "up");
+    assert_eq!(cell("success_change_percent"), "12.4");
+    assert_eq!(cell("duration_trend"), "stable");
+    assert_eq!(cell("min_duration_seconds"), "");
+    assert_eq!(cell("p95_queue_wait_seconds"), "120");
+    assert_eq!(cell("peak_queue"), "3");
+
+    let json: serde_json::Value = serde_json::from_str(&to_json(&rows, 7)).unwrap();
+    assert_eq!(json["days"], 7);
+    let scope = &json["scopes"][0];
+    assert_eq!(scope["scope"], "owner/repo");
+    assert_eq!(scope["success_rate"], 90.0);
+    assert_eq!(scope["success_change_percent"], 12.4);
+    assert_eq!(scope["duration_trend"], "stable");
+    assert_eq!(scope["avg_queue_wait_seconds"], 30);
+}
+
+#[test]
+fn test_duration_stats() {
+    use runner_mgr::metrics::models::DurationStats;
+
+    let stats = DurationStats::from_durations((1..=100).rev().collect());
+    assert_eq!(stats.avg, Some(50));
+    assert_eq!(stats.min, Some(1));
+    assert_eq!(stats.max, Some(100));
+    assert_eq!(stats.p50, Some(50));
+    assert_eq!(stats.p90, Some(90));
+    assert_eq!(stats.p99, Some(99));
+    assert_eq!(
+        DurationStats::from_durations(Vec::new()),
+        DurationStats::default()
+    );
+}
+
+#[test]
+fn test_bucket_ratios() {
+    let samples = [(0, true), (1, false), (5, true), (10, false)];
+    assert_eq!(
+        bucket_ratios(&samples, 0, 10, 2),
+        vec![Some(50.0), Some(100.0)]
+    );
+    // Buckets without samples stay empty
+    assert_eq!(bucket_ratios(&samples, 0, 20, 4)[3], None);
+    assert!(bucket_ratios(&samples, 10, 10, 3)
+        .iter()
+        .all(Option::is_none));
+}
@@ -0,0 +1,65 @@
+use runner_mgr::github::{Runner, RunnerScope};
+use runner_mgr::migrate::check_runner;
+use runner_mgr::runner::{RunnerInstance, RunnerStatus};
+use std::path::PathBuf;
+
+fn instance(service: Option<&str>, status: RunnerStatus) -> RunnerInstance {
+    RunnerInstance {
+        scope: RunnerScope::parse("owner/repo").unwrap(),
+        dir: PathBuf::from("/opt/github-runners/instances/owner__repo"),
+        service_name: service.map(str::to_string),
+        status,
+        runner_name: Some("host-owner__repo".to_string()),
+        name: None,
+    }
+}
+
+fn github_runner(name: &str, status: &str) -> Runner {
+    Runner {
+        id: 1,
+        name: name.to_string(),
+        os: "Linux".to_string(),
+        status: status.to_string(),
+        busy: false,
+        labels: Vec::new(),
+    }
+}
+
+#[test]
+fn test_check_runner_all_passing() {
+    let runners = [github_runner("host-owner__repo", "online")];
+    let inst = instance(Some("actions.runner.svc"), RunnerStatus::Running);
+    let checks = check_runner(Some(&inst), Ok(&runners));
+    assert_eq!(checks.len(), 3);
+    assert!(checks.iter().all(|c| c.passed), "{checks:?}");
+}
+
+#[test]
+fn test_check_runner_missing_instance() {
+    let checks = check_runner(None, Ok(&[]));
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0].name, "instance");
+    assert!(!checks[0].passed);
+}
+
+#[test]
+fn test_check_runner_stopped_service_and_offline() {
+    let runners = [github_runner("host-owner__repo", "offline")];
+    let inst = instance(Some("actions.runner.svc"), RunnerStatus::Stopped);
+    let checks = check_runner(Some(&inst), Ok(&runners));
+    assert!(checks[0].passed);
+    assert!(!checks[1].passed);
+    assert!(checks[1].detail.contains("runner-mgr start owner/repo"));
+    assert!(!checks[2].passed);
+    assert!(checks[2].detail.contains("offline"));
+}
+
+#[test]
+fn test_check_runner_without_service() {
+    let inst = instance(None, RunnerStatus::NoService);
+    let checks = check_runner(Some(&inst), Err("HTTP 401".to_string()));
+    assert!(!checks[1].passed);
+    assert!(checks[1].detail.contains("svc.sh install"));
+    assert!(!checks[2].passed);
+    assert!(checks[2].detail.contains("HTTP 401"));
+}
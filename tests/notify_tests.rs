@@ -0,0 +1,253 @@
+use runner_mgr::github::{Runner, RunnerScope, WorkflowJob, WorkflowRun};
+use runner_mgr::notify::{
+    discord_payload, failure_notification, is_failed, offline_notification, render_hook, run_hook,
+    slack_payload, split_command, RunTracker, RunnerTracker, ServiceTracker,
+};
+use runner_mgr::runner::{RunnerInstance, RunnerStatus};
+use std::path::PathBuf;
+
+fn run(id: u64, status: &str, conclusion: Option<&str>) -> WorkflowRun {
+    WorkflowRun {
+        id,
+        name: Some("CI".to_string()),
+        status: status.to_string(),
+        conclusion: conclusion.map(str::to_string),
+        head_branch: Some("main".to_string()),
+        created_at: "2025-01-01T00:00:00Z".to_string(),
+        updated_at: "2025-01-01T00:05:00Z".to_string(),
+        html_url: format!("https://github.com/owner/repo/actions/runs/{id}"),
+    }
+}
+
+fn job(name: &str, conclusion: &str, labels: &[&str], runner: Option<&str>) -> WorkflowJob {
+    WorkflowJob {
+        id: 1,
+        name: name.to_string(),
+        status: "completed".to_string(),
+        conclusion: Some(conclusion.to_string()),
+        labels: labels.iter().map(ToString::to_string).collect(),
+        runner_name: runner.map(str::to_string),
+        html_url: None,
+        run_id: 1,
+        workflow_name: Some("CI".to_string()),
+        created_at: None,
+        started_at: None,
+        completed_at: None,
+        steps: Vec::new(),
+    }
+}
+
+#[test]
+fn test_failed_conclusions() {
+    assert!(is_failed(Some("failure")));
+    assert!(is_failed(Some("timed_out")));
+    assert!(!is_failed(Some("success")));
+    assert!(!is_failed(Some("cancelled")));
+    assert!(!is_failed(None));
+}
+
+#[test]
+fn test_tracker_reports_each_new_failure_once() {
+    let scope = RunnerScope::parse("owner/repo").unwrap();
+    let mut tracker = RunTracker::default();
+
+    // Runs already finished on the first refresh are not reported
+    let first = vec![
+        run(1, "completed", Some("failure")),
+        run(2, "in_progress", None),
+    ];
+    assert!(tracker.new_failures(&scope, &first).is_empty());
+
+    let second = vec![
+        run(1, "completed", Some("failure")),
+        run(2, "completed", Some("failure")),
+        run(3, "completed", Some("success")),
+    ];
+    let failures = tracker.new_failures(&scope, &second);
+    assert_eq!(failures.iter().map(|r| r.id).collect::<Vec<_>>(), vec![2]);
+    assert!(tracker.new_failures(&scope, &second).is_empty());
+}
+
+#[test]
+fn test_notification_only_for_self_hosted_failures() {
+    let scope = RunnerScope::parse("owner/repo").unwrap();
+    let failed = run(7, "completed", Some("failure"));
+    let runners = vec!["ci-01".to_string()];
+
+    let hosted = vec![
+        job(
+            "lint",
+            "failure",
+            &["ubuntu-latest"],
+            Some("GitHub Actions 2"),
+        ),
+        job("build", "success", &["self-hosted", "linux"], Some("ci-01")),
+    ];
+    assert!(failure_notification(&scope, &failed, &hosted, &runners).is_none());
+
+    let mine = vec![
+        job(
+            "lint",
+            "success",
+            &["ubuntu-latest"],
+            Some("GitHub Actions 2"),
+        ),
+        job("build", "failure", &["self-hosted", "linux"], Some("ci-01")),
+    ];
+    let n = failure_notification(&scope, &failed, &mine, &runners).unwrap();
+    assert_eq!(n.title, "owner/repo: CI failed on main");
+    assert_eq!(n.body, "Failed on self-hosted runners: build (on ci-01)");
+    assert_eq!(n.url, "https://github.com/owner/repo/actions/runs/7");
+
+    // A registered runner counts even if the job didn't request the self-hosted label
+    let by_name = vec![job("test", "timed_out", &["gpu"], Some("ci-01"))];
+    assert!(failure_notification(&scope, &failed, &by_name, &runners).is_some());
+}
+
+#[test]
+fn test_slack_payload() {
+    let scope = RunnerScope::parse("owner/repo").unwrap();
+    let jobs = vec![job("build", "failure", &["self-hosted"], None)];
+    let n =
+        failure_notification(&scope, &run(9, "completed", Some("failure")), &jobs, &[]).unwrap();
+    let payload = slack_payload(&n);
+    let text = payload["text"].as_str().unwrap();
+    assert!(text.starts_with("*owner/repo: CI failed on main*\n"));
+    assert!(text.ends_with("<https://github.com/owner/repo/actions/runs/9|View run>"));
+}
+
+fn runner(name: &str, status: &str) -> Runner {
+    Runner {
+        id: 1,
+        name: name.to_string(),
+        os: "Linux".to_string(),
+        status: status.to_string(),
+        busy: false,
+        labels: Vec::new(),
+    }
+}
+
+#[test]
+fn test_runner_tracker_reports_each_outage_once() {
+    let scope = RunnerScope::parse("owner/repo").unwrap();
+    let mut tracker = RunnerTracker::default();
+
+    // Runners already offline when first seen are not reported
+    let first = tracker.went_offline(&scope, &[runner("a", "online"), runner("b", "offline")]);
+    assert!(first.is_empty());
+
+    let offline = tracker.went_offline(&scope, &[runner("a", "offline"), runner("b", "offline")]);
+    assert_eq!(offline.len(), 1);
+    assert_eq!(offline[0].name, "a");
+    assert!(tracker
+        .went_offline(&scope, &[runner("a", "offline")])
+        .is_empty());
+
+    tracker.went_offline(&scope, &[runner("a", "online")]);
+    assert_eq!(
+        tracker
+            .went_offline(&scope, &[runner("a", "offline")])
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_offline_payloads_have_no_link() {
+    let scope = RunnerScope::parse("owner/repo").unwrap();
+    let n = offline_notification(&scope, &runner("ci-01", "offline"));
+    assert_eq!(
+        slack_payload(&n)["text"],
+        "*owner/repo: runner ci-01 went offline*\nGitHub reports runner ci-01 as offline"
+    );
+    assert_eq!(
+        discord_payload(&n)["content"],
+        "**owner/repo: runner ci-01 went offline**\nGitHub reports runner ci-01 as offline"
+    );
+}
+
+fn instance(service: &str, status: RunnerStatus) -> RunnerInstance {
+    RunnerInstance {
+        scope: RunnerScope::parse("owner/repo").unwrap(),
+        name: None,
+        dir: PathBuf::from("/opt/runners/owner-repo"),
+        service_name: Some(service.to_string()),
+        status,
+        runner_name: Some("ci-01".to_string()),
+    }
+}
+
+#[test]
+fn test_service_tracker_ignores_observed_stops() {
+    let mut tracker = ServiceTracker::default();
+    assert!(tracker
+        .crashed(&[instance("a", RunnerStatus::Running)])
+        .is_empty());
+    let crashed = tracker.crashed(&[instance("a", RunnerStatus::Stopped)]);
+    assert_eq!(crashed.len(), 1);
+    assert!(tracker
+        .crashed(&[instance("a", RunnerStatus::Stopped)])
+        .is_empty());
+
+    // A stop runner-mgr made itself is recorded first and not reported
+    tracker.crashed(&[instance("a", RunnerStatus::Running)]);
+    tracker.observe(&[instance("a", RunnerStatus::Stopped)]);
+    assert!(tracker
+        .crashed(&[instance("a", RunnerStatus::Stopped)])
+        .is_empty());
+
+    // Unknown statuses neither report nor forget
+    tracker.crashed(&[instance("a", RunnerStatus::Running)]);
+    assert!(tracker
+        .crashed(&[instance("a", RunnerStatus::Unknown)])
+        .is_empty());
+    assert_eq!(
+        tracker
+            .crashed(&[instance("a", RunnerStatus::Stopped)])
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_split_command() {
+    assert_eq!(
+        split_command("  page-me --to 'on call'  \"{scope}\" "),
+        vec!["page-me", "--to", "on call", "{scope}"]
+    );
+    assert_eq!(split_command("notify ''"), vec!["notify", ""]);
+    assert!(split_command("   ").is_empty());
+}
+
+#[test]
+fn test_render_hook_substitutes_after_splitting() {
+    let context = [
+        ("scope", "owner/repo".to_string()),
+        ("runner", "ci-01; rm -rf /".to_string()),
+    ];
+    assert_eq!(
+        render_hook(
+            "/usr/local/bin/page-me {scope} runner={runner} {unknown}",
+            &context
+        ),
+        vec![
+            "/usr/local/bin/page-me",
+            "owner/repo",
+            "runner=ci-01; rm -rf /",
+            "{unknown}"
+        ]
+    );
+}
+
+#[test]
+fn test_run_hook() {
+    let context = [("scope", "owner/repo".to_string())];
+    run_hook(
+        r#"sh -c 'test "$RUNNER_MGR_SCOPE" = "$0"' {scope}"#,
+        &context,
+    )
+    .unwrap();
+    let err = run_hook("false", &context).unwrap_err();
+    assert!(err.to_string().contains("exited with"));
+    assert!(run_hook("", &context).is_err());
+}
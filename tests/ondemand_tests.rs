@@ -0,0 +1,51 @@
+use runner_mgr::runner::InstanceId;
+use std::path::Path;
+
+#[test]
+fn test_trigger_path() {
+    let config = runner_mgr::config::Config {
+        instances_base: "/opt/github-runners".to_string(),
+        ..Default::default()
+    };
+    let id = InstanceId::parse("owner/repo").unwrap();
+    assert_eq!(
+        runner_mgr::ondemand::trigger_path(&config, &id),
+        Path::new("/opt/github-runners/queue/owner__repo")
+    );
+    let id = InstanceId::parse("owner/repo@gpu").unwrap();
+    assert_eq!(
+        runner_mgr::ondemand::trigger_path(&config, &id),
+        Path::new("/opt/github-runners/queue/owner__repo@gpu")
+    );
+}
+
+#[test]
+fn test_on_demand_units() {
+    let units = runner_mgr::ondemand::units(
+        "actions.runner.owner-repo.host",
+        Path::new("/opt/github-runners/queue/owner__repo"),
+        Path::new("/opt/github-runners/instances/owner__repo"),
+        10,
+    );
+    let unit = |name: &str| {
+        let (_, contents) = units
+            .iter()
+            .find(|(n, _)| n == name)
+            .unwrap_or_else(|| panic!("missing {name}"));
+        contents.as_str()
+    };
+
+    let path = unit("actions.runner.owner-repo.host-ondemand.path");
+    assert!(path.contains("PathExists=/opt/github-runners/queue/owner__repo"));
+    assert!(path.contains("Unit=actions.runner.owner-repo.host.service"));
+
+    let dropin = unit("actions.runner.owner-repo.host.service.d/runner-mgr-ondemand.conf");
+    assert!(dropin.contains("ExecStartPre=+/bin/rm -f /opt/github-runners/queue/owner__repo"));
+
+    let idle = unit("actions.runner.owner-repo.host-idle.service");
+    assert!(idle.contains("pgrep -f /opt/github-runners/instances/owner__repo/bin/[R]unner.Worker"));
+    assert!(idle.contains("systemctl stop actions.runner.owner-repo.host.service"));
+
+    let timer = unit("actions.runner.owner-repo.host-idle.timer");
+    assert!(timer.contains("OnUnitActiveSec=10min"));
+}
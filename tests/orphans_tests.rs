@@ -0,0 +1,79 @@
+use runner_mgr::github::{Runner, RunnerScope};
+use runner_mgr::orphans::{local_instance, select};
+use runner_mgr::runner::{RunnerInstance, RunnerStatus};
+use std::path::PathBuf;
+
+fn runner(id: u64, name: &str, status: &str, busy: bool) -> Runner {
+    Runner {
+        id,
+        name: name.to_string(),
+        os: "Linux".to_string(),
+        status: status.to_string(),
+        busy,
+        labels: Vec::new(),
+    }
+}
+
+fn runners() -> Vec<Runner> {
+    vec![
+        runner(1, "host-a-owner-repo", "online", false),
+        runner(2, "dead-host-owner-repo", "offline", false),
+        runner(3, "busy-host-owner-repo", "online", true),
+        runner(4, "old-host-owner-repo", "offline", false),
+    ]
+}
+
+fn local() -> Vec<RunnerInstance> {
+    vec![RunnerInstance {
+        scope: RunnerScope::parse("owner/repo").unwrap(),
+        name: None,
+        dir: PathBuf::from("/opt/runners/owner__repo"),
+        service_name: None,
+        status: RunnerStatus::Stopped,
+        runner_name: Some("host-a-owner-repo".to_string()),
+    }]
+}
+
+fn ids(selected: &[&Runner]) -> Vec<u64> {
+    selected.iter().map(|r| r.id).collect()
+}
+
+#[test]
+fn test_local_instance() {
+    let runners = runners();
+    let local = local();
+    assert!(local_instance(&runners[0], &local).is_some());
+    assert!(local_instance(&runners[1], &local).is_none());
+}
+
+#[test]
+fn test_select_offline() {
+    let runners = runners();
+    let selection = select(&runners, &local(), &[], true).unwrap();
+    assert_eq!(ids(&selection.delete), vec![2, 4]);
+    // The busy runner is reported; online ones that weren't named aren't
+    assert_eq!(selection.skipped.len(), 2);
+    assert!(selection.skipped[0]
+        .1
+        .contains("runner-mgr remove owner/repo"));
+    assert!(selection.skipped[1].1.contains("running a job"));
+}
+
+#[test]
+fn test_select_named() {
+    let runners = runners();
+    let named = vec!["4".to_string(), "host-a-owner-repo".to_string()];
+    let selection = select(&runners, &local(), &named, false).unwrap();
+    assert_eq!(ids(&selection.delete), vec![4]);
+    assert_eq!(selection.skipped.len(), 1);
+
+    // With --offline, named runners that stay say why
+    let named = vec!["busy-host-owner-repo".to_string(), "2".to_string()];
+    let selection = select(&runners, &[], &named, true).unwrap();
+    assert_eq!(ids(&selection.delete), vec![2]);
+    assert_eq!(selection.skipped.len(), 1);
+
+    let err = select(&runners, &[], &["nope".to_string()], false).unwrap_err();
+    assert!(err.to_string().contains("No runner named 'nope'"));
+    assert!(select(&runners, &[], &[], false).is_err());
+}
@@ -0,0 +1,62 @@
+use runner_mgr::privilege::Escalation;
+
+#[test]
+fn test_wrap_sudo_keeps_flags() {
+    let (program, args) = Escalation::Sudo.wrap(&["-H", "-u", "github", "./svc.sh", "start"]);
+    assert_eq!(program, "sudo");
+    assert_eq!(args, vec!["-H", "-u", "github", "./svc.sh", "start"]);
+}
+
+#[test]
+fn test_wrap_doas_drops_set_home() {
+    let (program, args) = Escalation::Doas.wrap(&["-H", "-n", "-u", "github", "id"]);
+    assert_eq!(program, "doas");
+    assert_eq!(args, vec!["-n", "-u", "github", "id"]);
+}
+
+#[test]
+fn test_wrap_run0_non_interactive() {
+    let (program, args) = Escalation::Run0.wrap(&["-n", "systemctl", "daemon-reload"]);
+    assert_eq!(program, "run0");
+    assert_eq!(
+        args,
+        vec!["--no-ask-password", "systemctl", "daemon-reload"]
+    );
+}
+
+#[test]
+fn test_wrap_none_runs_directly() {
+    let (program, args) = Escalation::None.wrap(&["-u", "github", "mkdir", "-p", "/tmp/x"]);
+    assert_eq!(program, "mkdir");
+    assert_eq!(args, vec!["-p", "/tmp/x"]);
+}
+
+#[test]
+fn test_escalation_parses_from_config() {
+    let config: runner_mgr::config::Config = toml::from_str(
+        r#"
+        github_pat = "ghp_test"
+        github_user = "user"
+        runner_user = "github"
+        runner_os = "linux"
+        runner_arch = "x64"
+        instances_base = "/opt/github-runners"
+        escalation = "doas"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.escalation, Escalation::Doas);
+
+    let default: runner_mgr::config::Config = toml::from_str(
+        r#"
+        github_pat = "ghp_test"
+        github_user = "user"
+        runner_user = "github"
+        runner_os = "linux"
+        runner_arch = "x64"
+        instances_base = "/opt/github-runners"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(default.escalation, Escalation::Sudo);
+}
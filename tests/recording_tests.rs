@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+use runner_mgr::github::RunnerScope;
+use runner_mgr::recording::{Exchange, Recorder, Replay};
+use runner_mgr::runner::{RunnerInstance, RunnerStatus};
+
+fn exchange(path: &str, body: &str) -> Exchange {
+    Exchange {
+        method: "GET".to_string(),
+        path: path.to_string(),
+        status: 200,
+        headers: BTreeMap::from([("x-ratelimit-remaining".to_string(), "4999".to_string())]),
+        body: body.to_string(),
+    }
+}
+
+#[test]
+fn test_replay_in_recorded_order() {
+    let tmp = TempDir::new().unwrap();
+    let recorder = Recorder::create(tmp.path()).unwrap();
+    let runners = "/repos/owner/repo/actions/runners?per_page=100";
+    recorder.save(&exchange(runners, "first")).unwrap();
+    recorder
+        .save(&exchange(
+            "/repos/owner/repo/actions/runs?per_page=5",
+            "runs",
+        ))
+        .unwrap();
+    recorder.save(&exchange(runners, "second")).unwrap();
+
+    let replay = Replay::load(tmp.path()).unwrap();
+    assert_eq!(replay.next("GET", runners).unwrap().body, "first");
+    assert_eq!(replay.next("GET", runners).unwrap().body, "second");
+    // Once the recording runs out, the last response is repeated
+    assert_eq!(replay.next("GET", runners).unwrap().body, "second");
+    let runs = replay
+        .next("GET", "/repos/owner/repo/actions/runs?per_page=5")
+        .unwrap();
+    assert_eq!(runs.body, "runs");
+    assert_eq!(runs.headers["x-ratelimit-remaining"], "4999");
+
+    assert!(replay.next("POST", runners).is_none());
+    assert!(replay
+        .next("GET", "/repos/owner/other/actions/runners")
+        .is_none());
+    assert!(replay.instances().is_empty());
+}
+
+#[test]
+fn test_replay_recorded_instances() {
+    let tmp = TempDir::new().unwrap();
+    let recorder = Recorder::create(tmp.path()).unwrap();
+    let instance = RunnerInstance {
+        scope: RunnerScope::parse("Owner/Repo").unwrap(),
+        name: Some("gpu".to_string()),
+        dir: PathBuf::from("/opt/github-runners/instances/Owner__Repo@gpu"),
+        service_name: Some("actions.runner.Owner-Repo.host-gpu".to_string()),
+        status: RunnerStatus::NoService,
+        runner_name: Some("host-Owner__Repo-gpu".to_string()),
+    };
+    recorder
+        .save_instances(std::slice::from_ref(&instance))
+        .unwrap();
+
+    let replay = Replay::load(tmp.path()).unwrap();
+    let instances = replay.instances();
+    assert_eq!(instances.len(), 1);
+    assert_eq!(instances[0].id(), instance.id());
+    assert_eq!(instances[0].scope.to_display(), "Owner/Repo");
+    assert_eq!(instances[0].dir, instance.dir);
+    assert_eq!(instances[0].service_name, instance.service_name);
+    assert_eq!(instances[0].status, RunnerStatus::NoService);
+    assert_eq!(instances[0].runner_name, instance.runner_name);
+}
+
+#[test]
+fn test_record_refuses_existing_recording() {
+    let tmp = TempDir::new().unwrap();
+    Recorder::create(tmp.path()).unwrap();
+    let err = Recorder::create(tmp.path()).err().unwrap();
+    assert!(format!("{err:#}").contains("already contains a recording"));
+}
+
+#[test]
+fn test_replay_without_recording() {
+    let tmp = TempDir::new().unwrap();
+    let err = Replay::load(&tmp.path().join("missing")).err().unwrap();
+    assert!(format!("{err:#}").contains("No recording in"));
+}
@@ -0,0 +1,69 @@
+use runner_mgr::github::RunnerScope;
+use runner_mgr::remote::{parse_scan_output, scan_script};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tempfile::TempDir;
+
+#[test]
+fn test_parse_scan_output() {
+    let output = "/home/ci/actions-runner\t\u{feff}{\"agentName\": \"build1-web\", \"gitHubUrl\": \"https://github.com/owner/web\"}\n\
+                  /opt/runners/org\t{\"gitHubUrl\": \"https://github.com/acme\"}\n\
+                  /opt/broken\t{not json\n\
+                  no tab here\n";
+    let runners = parse_scan_output("ci@build1", output);
+    assert_eq!(runners.len(), 2);
+    assert_eq!(runners[0].host, "ci@build1");
+    assert_eq!(runners[0].path, "/home/ci/actions-runner");
+    assert_eq!(runners[0].scope, RunnerScope::parse("owner/web").unwrap());
+    assert_eq!(runners[0].agent_name.as_deref(), Some("build1-web"));
+    assert_eq!(runners[0].location(), "ci@build1:/home/ci/actions-runner");
+    assert_eq!(runners[1].scope, RunnerScope::parse("org:acme").unwrap());
+    assert_eq!(runners[1].agent_name, None);
+}
+
+fn fake_runner(dir: &std::path::Path, url: &str) {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(dir.join("config.sh"), "").unwrap();
+    fs::write(
+        dir.join(".runner"),
+        format!("{{\r\n  \"gitHubUrl\": \"{url}\",\r\n  \"agentName\": \"r\"\r\n}}"),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_scan_script_finds_runners() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().canonicalize().unwrap();
+    fake_runner(&home.join("actions-runner"), "https://github.com/owner/one");
+    fake_runner(&home.join("a/b/c/deep"), "https://github.com/owner/deep");
+    // Without config.sh it isn't a runner directory
+    fs::create_dir_all(home.join("half")).unwrap();
+    fs::write(home.join("half/.runner"), "{}").unwrap();
+
+    let mut child = Command::new("sh")
+        .arg("-s")
+        .env("HOME", &home)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(scan_script(2).as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let runners: Vec<_> = parse_scan_output("local", &String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .filter(|r| r.path.starts_with(&*home.to_string_lossy()))
+        .collect();
+    assert_eq!(runners.len(), 1, "{runners:?}");
+    assert_eq!(
+        runners[0].path,
+        home.join("actions-runner").to_string_lossy()
+    );
+    assert_eq!(runners[0].scope.to_display(), "owner/one");
+}
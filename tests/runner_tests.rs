@@ -1,5 +1,7 @@
 use runner_mgr::github::RunnerScope;
+use runner_mgr::runner::InstanceId;
 use serial_test::serial;
+use std::path::Path;
 use tempfile::TempDir;
 
 #[test]
@@ -12,6 +14,7 @@ fn test_list_instances_empty() {
         runner_os: "linux".to_string(),
         runner_arch: "x64".to_string(),
         instances_base: tmp.path().to_str().unwrap().to_string(),
+        ..Default::default()
     };
 
     let instances = runner_mgr::runner::list_instances(&config);
@@ -36,6 +39,7 @@ fn test_list_instances_with_dirs() {
         runner_os: "linux".to_string(),
         runner_arch: "x64".to_string(),
         instances_base: tmp.path().to_str().unwrap().to_string(),
+        ..Default::default()
     };
 
     let instances = runner_mgr::runner::list_instances(&config);
@@ -63,6 +67,7 @@ fn test_list_instances_sorted() {
         runner_os: "linux".to_string(),
         runner_arch: "x64".to_string(),
         instances_base: tmp.path().to_str().unwrap().to_string(),
+        ..Default::default()
     };
 
     let instances = runner_mgr::runner::list_instances(&config);
@@ -108,6 +113,7 @@ fn test_instance_with_service_file() {
         runner_os: "linux".to_string(),
         runner_arch: "x64".to_string(),
         instances_base: tmp.path().to_str().unwrap().to_string(),
+        ..Default::default()
     };
 
     let instances = runner_mgr::runner::list_instances(&config);
@@ -129,10 +135,11 @@ fn test_get_logs_nonexistent_repo() {
         runner_os: "linux".to_string(),
         runner_arch: "x64".to_string(),
         instances_base: tmp.path().to_str().unwrap().to_string(),
+        ..Default::default()
     };
 
-    let scope = RunnerScope::parse("nonexistent/repo").unwrap();
-    let result = runner_mgr::runner::get_runner_logs(&config, &scope, 50);
+    let id = InstanceId::parse("nonexistent/repo").unwrap();
+    let result = runner_mgr::runner::get_runner_logs(&config, &id, 50);
     assert!(result.is_err());
     let err = format!("{:#}", result.unwrap_err());
     assert!(err.contains("No runner configured"));
@@ -241,6 +248,7 @@ fn test_import_runner_nonexistent_path() {
         runner_os: "darwin".to_string(),
         runner_arch: "arm64".to_string(),
         instances_base: tmp.path().join("runners").to_str().unwrap().to_string(),
+        ..Default::default()
     };
     config.save().unwrap();
 
@@ -269,6 +277,7 @@ fn test_import_runner_not_a_runner_directory() {
         runner_os: "darwin".to_string(),
         runner_arch: "arm64".to_string(),
         instances_base: tmp.path().join("runners").to_str().unwrap().to_string(),
+        ..Default::default()
     };
     config.save().unwrap();
 
@@ -279,3 +288,615 @@ fn test_import_runner_not_a_runner_directory() {
 
     std::env::remove_var("RUNNER_MGR_CONFIG_DIR");
 }
+
+#[test]
+fn test_plan_import_describes_changes() {
+    let tmp = TempDir::new().unwrap();
+    let source = tmp.path().join("actions-runner");
+    std::fs::create_dir_all(&source).unwrap();
+    std::fs::write(source.join("config.sh"), "").unwrap();
+    std::fs::write(source.join(".service"), "actions.runner.owner-repo.host").unwrap();
+
+    let config = runner_mgr::config::Config {
+        runner_user: "github".to_string(),
+        runner_os: "linux".to_string(),
+        instances_base: tmp.path().join("runners").to_str().unwrap().to_string(),
+        ..Default::default()
+    };
+    let plan =
+        runner_mgr::runner::plan_import(&config, source.to_str().unwrap(), Some("owner/repo"))
+            .unwrap();
+    assert_eq!(plan.target, config.instances_dir().join("owner__repo"));
+    assert_eq!(
+        plan.service.as_deref(),
+        Some("actions.runner.owner-repo.host")
+    );
+    assert!(!plan.writes_service_file);
+
+    let changes = plan.describe();
+    assert!(changes[0].starts_with("Symlink "));
+    assert!(changes[0].contains("not moved"));
+    assert_eq!(
+        changes[1],
+        "Adopt the existing service actions.runner.owner-repo.host"
+    );
+    // Planning changes nothing
+    assert!(!plan.target.exists());
+}
+
+#[test]
+fn test_list_unrecognized_instance_dirs() {
+    let tmp = TempDir::new().unwrap();
+    let config = runner_mgr::config::Config {
+        instances_base: tmp.path().to_str().unwrap().to_string(),
+        ..Default::default()
+    };
+    let instances = config.instances_dir();
+    std::fs::create_dir_all(instances.join("owner__repo")).unwrap();
+    std::fs::create_dir_all(instances.join("actions-runner")).unwrap();
+    std::fs::write(
+        instances.join("actions-runner/.runner"),
+        r#"{"gitHubUrl": "https://github.com/owner/other"}"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(instances.join("old copy")).unwrap();
+    std::fs::write(instances.join("notes.txt"), "").unwrap();
+
+    let dirs = runner_mgr::runner::list_unrecognized(&config);
+    let names: Vec<_> = dirs
+        .iter()
+        .map(|d| d.path.file_name().unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(names, ["actions-runner", "old copy"]);
+    assert_eq!(
+        dirs[0].scope,
+        Some(RunnerScope::parse("owner/other").unwrap())
+    );
+    assert_eq!(dirs[1].scope, None);
+
+    // Once an instance links to it, the directory is managed
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(
+            instances.join("actions-runner"),
+            instances.join("owner__other"),
+        )
+        .unwrap();
+        let dirs = runner_mgr::runner::list_unrecognized(&config);
+        assert_eq!(dirs.len(), 1);
+        assert!(dirs[0].path.ends_with("old copy"));
+    }
+}
+
+#[test]
+fn test_legacy_instance_dirs_found_by_runner_config() {
+    let tmp = TempDir::new().unwrap();
+    let config = runner_mgr::config::Config {
+        instances_base: tmp.path().to_str().unwrap().to_string(),
+        ..Default::default()
+    };
+    let instances = config.instances_dir();
+    // Older versions named `my_user/repo`'s second instance like this
+    let legacy = instances.join("my_user__repo@2");
+    std::fs::create_dir_all(&legacy).unwrap();
+    std::fs::write(
+        legacy.join(".runner"),
+        r#"{"gitHubUrl": "https://github.com/my_user/repo"}"#,
+    )
+    .unwrap();
+    // An unrecognized directory that isn't an old instance name
+    let other = instances.join("actions-runner");
+    std::fs::create_dir_all(&other).unwrap();
+    std::fs::write(
+        other.join(".runner"),
+        r#"{"gitHubUrl": "https://github.com/owner/repo"}"#,
+    )
+    .unwrap();
+
+    let dirs = runner_mgr::runner::legacy_instance_dirs(&config);
+    assert_eq!(dirs.len(), 1);
+    assert_eq!(dirs[0].path, legacy);
+    assert_eq!(dirs[0].id, InstanceId::parse("my_user/repo@2").unwrap());
+    assert_eq!(dirs[0].id.dir_name(), "my%5Fuser__repo@2");
+}
+
+#[test]
+fn test_rename_into_instance_refuses_outside_instances_dir() {
+    let tmp = TempDir::new().unwrap();
+    let source = tmp.path().join("actions-runner");
+    std::fs::create_dir_all(&source).unwrap();
+    std::fs::write(source.join("config.sh"), "").unwrap();
+    let config = runner_mgr::config::Config {
+        runner_os: "linux".to_string(),
+        instances_base: tmp.path().join("runners").to_str().unwrap().to_string(),
+        ..Default::default()
+    };
+    std::fs::create_dir_all(config.instances_dir()).unwrap();
+
+    let err = runner_mgr::runner::rename_into_instance(
+        &config,
+        source.to_str().unwrap(),
+        Some("owner/repo"),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("--rename only applies"));
+    assert!(source.exists());
+}
+
+#[test]
+fn test_merge_env_file_replaces_and_appends() {
+    let existing = "LANG=en_US.UTF-8\nFOO=old\n";
+    let vars = std::collections::BTreeMap::from([
+        ("FOO".to_string(), "new".to_string()),
+        ("BAR".to_string(), "1".to_string()),
+    ]);
+    let merged = runner_mgr::runner::merge_env_file(existing, &vars);
+    assert_eq!(merged, "LANG=en_US.UTF-8\nBAR=1\nFOO=new\n");
+}
+
+#[test]
+fn test_resource_limits_dropin() {
+    let mut profile = runner_mgr::config::RunnerProfile::default();
+    assert!(runner_mgr::runner::resource_limits_dropin(&profile).is_none());
+
+    profile.memory_max = Some("8G".to_string());
+    profile.cpu_quota = Some("200%".to_string());
+    let dropin = runner_mgr::runner::resource_limits_dropin(&profile).unwrap();
+    assert!(dropin.contains("[Service]"));
+    assert!(dropin.contains("MemoryMax=8G"));
+    assert!(dropin.contains("CPUQuota=200%"));
+}
+
+#[test]
+fn test_pre_start_dropin() {
+    use runner_mgr::config::PreStartConfig;
+    use runner_mgr::runner::{pre_start_dropin, systemd_shell_command};
+
+    assert!(pre_start_dropin(&PreStartConfig::default()).is_none());
+    assert_eq!(
+        systemd_shell_command("echo '50%' $HOME"),
+        r"/bin/sh -c 'echo \'50%%\' $$HOME'"
+    );
+
+    let hooks = PreStartConfig {
+        commands: vec!["mount /mnt/cache".to_string(), "rm -rf /tmp/ci".to_string()],
+        as_root: true,
+    };
+    let dropin = pre_start_dropin(&hooks).unwrap();
+    assert!(dropin.contains("[Service]"));
+    assert!(dropin.contains("ExecStartPre=+/bin/sh -c 'mount /mnt/cache'\n"));
+    assert!(dropin.contains("ExecStartPre=+/bin/sh -c 'rm -rf /tmp/ci'\n"));
+    let hooks = PreStartConfig {
+        as_root: false,
+        ..hooks
+    };
+    assert!(pre_start_dropin(&hooks)
+        .unwrap()
+        .contains("ExecStartPre=/bin/sh -c 'mount /mnt/cache'"));
+}
+
+#[test]
+fn test_parse_pre_start_failure() {
+    use runner_mgr::runner::parse_pre_start_failure;
+
+    let ok = "{ path=/bin/sh ; argv[]=/bin/sh -c mount /mnt/cache ; ignore_errors=no ; \
+              start_time=[Mon 2025-01-06 10:00:00 UTC] ; stop_time=[Mon 2025-01-06 10:00:00 UTC] ; \
+              pid=100 ; code=exited ; status=0/SUCCESS }";
+    let failed = "{ path=/bin/sh ; argv[]=/bin/sh -c systemctl start docker ; \
+                  ignore_errors=no ; start_time=[n/a] ; stop_time=[n/a] ; pid=101 ; \
+                  code=exited ; status=1/FAILURE }";
+    let never_ran = "{ path=/bin/sh ; argv[]=/bin/sh -c true ; ignore_errors=no ; \
+                     start_time=[n/a] ; stop_time=[n/a] ; pid=0 ; code=(null) ; status=0/0 }";
+
+    assert_eq!(parse_pre_start_failure(ok), None);
+    assert_eq!(parse_pre_start_failure(""), None);
+    assert_eq!(parse_pre_start_failure(never_ran), None);
+    assert_eq!(
+        parse_pre_start_failure(&format!("{ok}\n{failed}")),
+        Some((
+            "systemctl start docker".to_string(),
+            "exit status 1".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_diagnose_listener_failure() {
+    let hints = runner_mgr::runner::diagnose_listener_failure(
+        "Process terminated. Couldn't find a valid ICU package installed on the system.",
+        "x64",
+    );
+    assert_eq!(hints.len(), 1);
+    assert!(hints[0].contains("libicu"));
+
+    let hints = runner_mgr::runner::diagnose_listener_failure(
+        "bash: ./bin/Runner.Listener: cannot execute binary file: Exec format error",
+        "arm64",
+    );
+    assert!(hints[0].contains("Wrong architecture"));
+    assert!(hints[0].contains("arm64"));
+
+    // Unknown failures still point at the dependency installer
+    let hints = runner_mgr::runner::diagnose_listener_failure("Segmentation fault", "x64");
+    assert!(hints[0].contains("deps install"));
+}
+
+#[test]
+fn test_default_runner_name() {
+    let scope = RunnerScope::parse("owner/repo").unwrap();
+    assert_eq!(
+        runner_mgr::runner::default_runner_name("ci-01", &scope),
+        "ci-01-owner__repo"
+    );
+    let long = RunnerScope::parse(&format!("owner/{}", "r".repeat(80))).unwrap();
+    assert_eq!(
+        runner_mgr::runner::default_runner_name("ci-01", &long)
+            .chars()
+            .count(),
+        64
+    );
+}
+
+#[test]
+fn test_list_instances_reads_registered_name() {
+    let tmp = TempDir::new().unwrap();
+    let instances_dir = tmp.path().join("instances");
+    std::fs::create_dir_all(instances_dir.join("owner__named")).unwrap();
+    std::fs::create_dir_all(instances_dir.join("owner__unnamed")).unwrap();
+    // config.sh writes .runner with a UTF-8 BOM
+    std::fs::write(
+        instances_dir.join("owner__named").join(".runner"),
+        "\u{feff}{\"agentName\": \"build-box-7\", \"gitHubUrl\": \"https://github.com/owner/named\"}",
+    )
+    .unwrap();
+
+    let config = runner_mgr::config::Config {
+        instances_base: tmp.path().to_str().unwrap().to_string(),
+        ..Default::default()
+    };
+
+    let instances = runner_mgr::runner::list_instances(&config);
+    assert_eq!(instances.len(), 2);
+    assert_eq!(instances[0].runner_name.as_deref(), Some("build-box-7"));
+    assert_eq!(instances[1].runner_name, None);
+}
+
+#[test]
+fn test_jit_runner_name() {
+    assert_eq!(
+        runner_mgr::runner::jit_runner_name("ci-01", 1_700_000_000),
+        "ci-01-jit-1700000000"
+    );
+    // Long hostnames are cut, keeping the unique suffix
+    let name = runner_mgr::runner::jit_runner_name(&"h".repeat(80), 1_700_000_000);
+    assert_eq!(name.len(), 64);
+    assert!(name.ends_with("-jit-1700000000"));
+}
+
+#[test]
+fn test_instance_id_parse() {
+    let id = InstanceId::parse("owner/repo").unwrap();
+    assert_eq!(
+        id,
+        InstanceId::primary(RunnerScope::parse("owner/repo").unwrap())
+    );
+    assert_eq!(id.to_string(), "owner/repo");
+
+    let id = InstanceId::parse("org:my-org@build-2").unwrap();
+    assert_eq!(id.scope, RunnerScope::parse("org:my-org").unwrap());
+    assert_eq!(id.name.as_deref(), Some("build-2"));
+    assert_eq!(id.to_string(), "org:my-org@build-2");
+
+    assert!(InstanceId::parse("owner/repo@").is_err());
+    assert!(InstanceId::parse("owner/repo@a.b").is_err());
+}
+
+#[test]
+fn test_instance_id_dir_name_round_trip() {
+    for target in ["owner/repo", "owner/repo@2", "org:my-org@gpu"] {
+        let id = InstanceId::parse(target).unwrap();
+        assert_eq!(InstanceId::from_dir_name(&id.dir_name()), Some(id));
+    }
+    assert_eq!(
+        InstanceId::parse("owner/repo@2").unwrap().dir_name(),
+        "owner__repo@2"
+    );
+    assert_eq!(InstanceId::from_dir_name("owner__repo@bad name"), None);
+}
+
+#[test]
+fn test_instance_runner_name_keeps_suffix() {
+    let id = InstanceId::parse("owner/repo@2").unwrap();
+    assert_eq!(id.runner_name("ci-01"), "ci-01-owner__repo-2");
+
+    let long = InstanceId::parse(&format!("owner/{}@gpu", "r".repeat(80))).unwrap();
+    let name = long.runner_name("ci-01");
+    assert_eq!(name.len(), 64);
+    assert!(name.ends_with("-gpu"));
+}
+
+#[test]
+fn test_user_unit_name() {
+    use runner_mgr::runner::user_unit_name;
+    let primary = InstanceId::parse("owner/repo").unwrap();
+    assert_eq!(user_unit_name(&primary), "actions.runner.owner__repo");
+    // `@` marks template units in systemd
+    let named = InstanceId::parse("owner/repo@2").unwrap();
+    assert_eq!(user_unit_name(&named), "actions.runner.owner__repo.2");
+}
+
+#[test]
+fn test_user_unit() {
+    let id = InstanceId::parse("owner/repo@2").unwrap();
+    let unit = runner_mgr::runner::user_unit(&id, Path::new("/home/ci/runners/owner__repo@2"));
+    assert!(unit.contains("Description=GitHub Actions Runner (owner/repo@2)\n"));
+    assert!(unit.contains("ExecStart=/home/ci/runners/owner__repo@2/runsvc.sh\n"));
+    assert!(unit.contains("WorkingDirectory=/home/ci/runners/owner__repo@2\n"));
+    assert!(unit.contains("KillMode=process\n"));
+    // User units have no multi-user.target
+    assert!(unit.ends_with("[Install]\nWantedBy=default.target\n"));
+    assert!(!unit.contains("User="));
+}
+
+#[test]
+fn test_validate_instance_name() {
+    use runner_mgr::runner::validate_instance_name;
+    assert!(validate_instance_name("gpu_2-large").is_ok());
+    assert!(validate_instance_name("").is_err());
+    assert!(validate_instance_name("a/b").is_err());
+    assert!(validate_instance_name(&"x".repeat(33)).is_err());
+}
+
+#[test]
+fn test_next_instance_ids() {
+    use runner_mgr::runner::next_instance_ids;
+    let scope = RunnerScope::parse("owner/repo").unwrap();
+    let names =
+        |ids: Vec<InstanceId>| -> Vec<String> { ids.iter().map(ToString::to_string).collect() };
+
+    assert_eq!(
+        names(next_instance_ids(&[], &scope, 3)),
+        ["owner/repo", "owner/repo@2", "owner/repo@3"]
+    );
+
+    let existing = [
+        InstanceId::parse("owner/repo").unwrap(),
+        InstanceId::parse("owner/repo@3").unwrap(),
+    ];
+    assert_eq!(
+        names(next_instance_ids(&existing, &scope, 2)),
+        ["owner/repo@2", "owner/repo@4"]
+    );
+}
+
+#[test]
+fn test_runner_package() {
+    use runner_mgr::runner::{runner_package, runner_package_url};
+    assert_eq!(
+        runner_package("linux", "x64", "2.320.0"),
+        "actions-runner-linux-x64-2.320.0.tar.gz"
+    );
+    assert_eq!(
+        runner_package("darwin", "arm64", "2.320.0"),
+        "actions-runner-osx-arm64-2.320.0.tar.gz"
+    );
+    let package = runner_package("windows", "x64", "2.320.0");
+    assert_eq!(package, "actions-runner-win-x64-2.320.0.zip");
+    assert_eq!(
+        runner_package_url(&package, "2.320.0"),
+        "https://github.com/actions/runner/releases/download/v2.320.0/actions-runner-win-x64-2.320.0.zip"
+    );
+}
+
+#[test]
+fn test_log_export_path() {
+    let id = InstanceId::parse("owner/repo").unwrap();
+    assert_eq!(
+        runner_mgr::runner::log_export_path(Path::new("/tmp/dl"), &id, "20250101-120000"),
+        Path::new("/tmp/dl/runner-mgr-owner__repo-20250101-120000.log")
+    );
+    let id = InstanceId::parse("owner/repo@2").unwrap();
+    assert_eq!(
+        runner_mgr::runner::log_export_path(Path::new("/tmp/dl"), &id, "20250101-120000"),
+        Path::new("/tmp/dl/runner-mgr-owner__repo@2-20250101-120000.log")
+    );
+}
+
+#[test]
+fn test_export_logs_creates_directory() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("tickets/1234/runner.log");
+    let written =
+        runner_mgr::runner::export_logs(path.to_str().unwrap(), "line 1\nline 2").unwrap();
+    assert_eq!(written, path);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "line 1\nline 2\n");
+}
+
+#[test]
+fn test_expand_home() {
+    let home = dirs::home_dir().unwrap();
+    assert_eq!(
+        runner_mgr::runner::expand_home("~/logs/a.log"),
+        home.join("logs/a.log")
+    );
+    assert_eq!(
+        runner_mgr::runner::expand_home("/var/log/a.log"),
+        Path::new("/var/log/a.log")
+    );
+}
+
+#[test]
+fn test_open_log_file_appends() {
+    use std::io::Write;
+
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("logs/verbose.log");
+    let path_str = path.to_str().unwrap();
+    writeln!(
+        runner_mgr::runner::open_log_file(path_str).unwrap(),
+        "first"
+    )
+    .unwrap();
+    writeln!(
+        runner_mgr::runner::open_log_file(path_str).unwrap(),
+        "second"
+    )
+    .unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+}
+
+fn fake_runner(dir: &Path, repo: &str) {
+    std::fs::create_dir_all(dir.join("_work/nested")).unwrap();
+    std::fs::write(dir.join("config.sh"), "").unwrap();
+    std::fs::write(
+        dir.join(".runner"),
+        format!(r#"{{"gitHubUrl": "https://github.com/{repo}", "agentName": "runner"}}"#),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_scan_tree_depth_limit() {
+    use runner_mgr::runner::scan_tree;
+    use std::cell::Cell;
+
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path().canonicalize().unwrap();
+    fake_runner(&root.join("a/owner-one"), "owner/one");
+    fake_runner(&root.join("b/c/owner-two"), "owner/two");
+    fake_runner(&root.join("b/c/d/e/owner-deep"), "owner/deep");
+    fake_runner(&root.join(".hidden/owner-hidden"), "owner/hidden");
+    // A runner inside another runner's work folder isn't searched
+    fake_runner(
+        &root.join("a/owner-one/_work/nested/owner-inner"),
+        "owner/inner",
+    );
+
+    let calls = Cell::new(0);
+    let found = scan_tree(std::slice::from_ref(&root), 3, |_| {
+        calls.set(calls.get() + 1);
+    });
+    let scopes: Vec<String> = found.iter().map(|r| r.scope.to_display()).collect();
+    assert_eq!(scopes, ["owner/one", "owner/two"]);
+    assert_eq!(found[0].path, root.join("a/owner-one"));
+    assert!(calls.get() >= 1, "progress is reported at least once");
+
+    let found = scan_tree(std::slice::from_ref(&root), 5, |_| {});
+    assert_eq!(found.len(), 3);
+
+    // A root that is itself a runner; duplicate roots are reported once
+    let one = root.join("a/owner-one");
+    let found = scan_tree(&[one.clone(), one], 0, |_| {});
+    assert_eq!(found.len(), 1);
+}
+
+#[test]
+fn test_credential_problem() {
+    use runner_mgr::runner::{credential_problem, reregister_hint};
+
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path();
+    // Never registered: nothing to check
+    assert_eq!(credential_problem(dir), None);
+
+    std::fs::write(dir.join(".runner"), "{}").unwrap();
+    assert_eq!(
+        credential_problem(dir).as_deref(),
+        Some(".credentials is missing")
+    );
+
+    std::fs::write(dir.join(".credentials"), "{\"scheme\": \"OAuth\"}").unwrap();
+    std::fs::write(dir.join(".credentials_rsaparams"), "").unwrap();
+    assert_eq!(
+        credential_problem(dir).as_deref(),
+        Some(".credentials_rsaparams is empty")
+    );
+
+    std::fs::write(dir.join(".credentials_rsaparams"), "{\"d\": ").unwrap();
+    assert_eq!(
+        credential_problem(dir).as_deref(),
+        Some(".credentials_rsaparams is corrupt")
+    );
+
+    std::fs::write(dir.join(".credentials_rsaparams"), "\u{feff}{\"d\": \"x\"}").unwrap();
+    assert_eq!(credential_problem(dir), None);
+
+    let id = InstanceId::from_dir_name("owner__repo@gpu").unwrap();
+    assert_eq!(
+        reregister_hint(&id),
+        "re-register it: runner-mgr reregister owner/repo@gpu"
+    );
+}
+
+#[test]
+fn test_registration_revoked() {
+    use runner_mgr::runner::registration_revoked;
+
+    assert!(registration_revoked(
+        "[RUNNER 2025-01-06 10:00:00Z ERR  Runner] Failed to create a session. The runner \
+         registration has been deleted from the server, please re-configure."
+    ));
+    assert!(registration_revoked(
+        "GitHub.DistributedTask.WebApi.TaskAgentNotFoundException: No agent found"
+    ));
+    assert!(registration_revoked(
+        "Failed to get access token: {\"error\":\"invalid_client\"}"
+    ));
+    assert!(!registration_revoked(
+        "Failed to connect: the SSL connection could not be established"
+    ));
+}
+
+#[test]
+fn test_registration_missing() {
+    use runner_mgr::github::Runner;
+    use runner_mgr::runner::{registration_missing, RunnerInstance, RunnerStatus};
+
+    let mut instance = RunnerInstance {
+        scope: runner_mgr::github::RunnerScope::parse("owner/repo").unwrap(),
+        name: None,
+        dir: std::path::PathBuf::from("/opt/runners/owner__repo"),
+        service_name: None,
+        status: RunnerStatus::Running,
+        runner_name: Some("ci-01".to_string()),
+    };
+    let runners = vec![Runner {
+        id: 1,
+        name: "ci-02".to_string(),
+        os: "Linux".to_string(),
+        status: "online".to_string(),
+        busy: false,
+        labels: Vec::new(),
+    }];
+    assert!(registration_missing(&instance, &runners));
+    assert!(registration_missing(&instance, &[]));
+
+    instance.runner_name = Some("ci-02".to_string());
+    assert!(!registration_missing(&instance, &runners));
+
+    // Never registered
+    instance.runner_name = None;
+    assert!(!registration_missing(&instance, &[]));
+}
+
+#[test]
+fn test_is_package_dir() {
+    use runner_mgr::runner::is_package_dir;
+
+    assert!(is_package_dir("bin"));
+    assert!(is_package_dir("externals"));
+    // Versioned copies left by a runner's self-update
+    assert!(is_package_dir("bin.2.321.0"));
+    assert!(is_package_dir("externals.2.321.0"));
+    // Registration, work and log directories are kept
+    for kept in [
+        "_work",
+        "_diag",
+        ".runner",
+        ".credentials",
+        "binaries",
+        "bin.bak",
+    ] {
+        assert!(!is_package_dir(kept), "{kept}");
+    }
+}
@@ -0,0 +1,110 @@
+use runner_mgr::scheduler::{
+    backoff, rate_limit, record_rate_limit, retry_after, retry_delay, RateLimit, RequestLimits,
+    Scheduler, BASE_BACKOFF, MAX_PAUSE,
+};
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_retry_after() {
+    assert_eq!(retry_after(403, Some("60")), Some(Duration::from_mins(1)));
+    assert_eq!(retry_after(429, Some(" 5 ")), Some(Duration::from_secs(5)));
+    assert_eq!(retry_after(403, Some("86400")), Some(MAX_PAUSE));
+    // Other statuses, missing or unparseable headers aren't rate limits
+    assert_eq!(retry_after(503, Some("60")), None);
+    assert_eq!(retry_after(403, None), None);
+    assert_eq!(
+        retry_after(429, Some("Wed, 21 Oct 2015 07:28:00 GMT")),
+        None
+    );
+}
+
+#[test]
+fn test_backoff_doubles_with_jitter() {
+    for attempt in 0..4 {
+        let full = BASE_BACKOFF * 2u32.pow(attempt);
+        let wait = backoff(attempt);
+        assert!(
+            wait >= full / 2 && wait <= full,
+            "{wait:?} for attempt {attempt}"
+        );
+    }
+}
+
+#[test]
+fn test_retry_delay() {
+    let now = 1_700_000_000;
+    // retry-after wins
+    assert_eq!(
+        retry_delay(429, true, Some("7"), None, None, now, 0),
+        Some(Duration::from_secs(7))
+    );
+    // Exhausted quota: wait for the reset, unless that's too far off
+    assert_eq!(
+        retry_delay(403, true, None, Some("0"), Some("1700000030"), now, 0),
+        Some(Duration::from_secs(31))
+    );
+    assert_eq!(
+        retry_delay(403, true, None, Some("0"), Some("1700003600"), now, 0),
+        None
+    );
+    // Server errors and bare 429s back off
+    let wait = retry_delay(502, true, None, None, None, now, 1).unwrap();
+    assert!(wait >= BASE_BACKOFF && wait <= BASE_BACKOFF * 2);
+    assert!(retry_delay(429, true, None, Some("12"), None, now, 0).is_some());
+    // A POST may have been applied before the server error, so it isn't sent again,
+    // but rate-limited requests never were
+    assert_eq!(retry_delay(502, false, None, None, None, now, 0), None);
+    assert!(retry_delay(429, false, None, None, None, now, 0).is_some());
+    assert_eq!(
+        retry_delay(403, false, None, Some("0"), Some("1700000030"), now, 0),
+        Some(Duration::from_secs(31))
+    );
+    // Permission errors and other failures aren't retried
+    assert_eq!(
+        retry_delay(403, true, None, Some("4000"), None, now, 0),
+        None
+    );
+    assert_eq!(retry_delay(404, true, None, None, None, now, 0), None);
+    assert_eq!(retry_delay(501, true, None, None, None, now, 0), None);
+}
+
+#[test]
+fn test_record_rate_limit() {
+    record_rate_limit(Some("core"), Some("5000"), Some("4321"), Some("1700000000"));
+    let core = RateLimit {
+        limit: 5000,
+        remaining: 4321,
+        reset: 1_700_000_000,
+    };
+    assert_eq!(rate_limit(), Some(core));
+    // Other quotas and incomplete headers leave it as it is
+    record_rate_limit(Some("search"), Some("30"), Some("29"), Some("1700000000"));
+    record_rate_limit(None, Some("5000"), None, Some("1700000000"));
+    assert_eq!(rate_limit(), Some(core));
+}
+
+#[tokio::test]
+async fn test_scheduler_paces_request_starts() {
+    // 600 per minute: one start every 100ms
+    let scheduler = Scheduler::new(RequestLimits {
+        max_concurrent: 4,
+        per_minute: 600,
+    });
+    let start = Instant::now();
+    for _ in 0..3 {
+        drop(scheduler.acquire().await);
+    }
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn test_scheduler_pause_delays_next_request() {
+    let scheduler = Scheduler::new(RequestLimits {
+        max_concurrent: 1,
+        per_minute: 60_000,
+    });
+    scheduler.pause(Duration::from_millis(150));
+    let start = Instant::now();
+    drop(scheduler.acquire().await);
+    assert!(start.elapsed() >= Duration::from_millis(150));
+}
@@ -95,6 +95,69 @@ fn test_from_dir_name_invalid() {
     assert!(RunnerScope::from_dir_name("org__").is_none());
 }
 
+#[test]
+fn test_dir_name_escapes_ambiguous_scopes() {
+    let cases = [
+        (
+            RunnerScope::parse("owner/my__repo").unwrap(),
+            "owner__my__repo",
+        ),
+        (
+            RunnerScope::parse("my_user/repo").unwrap(),
+            "my%5Fuser__repo",
+        ),
+        (RunnerScope::parse("org/repo").unwrap(), "%6Frg__repo"),
+        (RunnerScope::parse("Org/repo").unwrap(), "%4Frg__repo"),
+        (RunnerScope::parse("org:repo").unwrap(), "org__repo"),
+        (
+            RunnerScope::parse("owner/we ird%").unwrap(),
+            "owner__we%20ird%25",
+        ),
+        (
+            RunnerScope::parse("owner/.github").unwrap(),
+            "owner__.github",
+        ),
+    ];
+    for (scope, dir_name) in cases {
+        assert_eq!(scope.to_dir_name(), dir_name);
+        assert_eq!(RunnerScope::from_dir_name(dir_name), Some(scope));
+    }
+}
+
+#[test]
+fn test_org_owner_dir_name_differs_from_organization() {
+    // Instance directories are looked up case-insensitively
+    let owner = RunnerScope::parse("Org/x").unwrap().to_dir_name();
+    let org = RunnerScope::parse("org:x").unwrap().to_dir_name();
+    assert!(!owner.eq_ignore_ascii_case(&org));
+    assert!(RunnerScope::from_dir_name("Org__x").is_none());
+}
+
+#[test]
+fn test_from_dir_name_rejects_non_canonical_names() {
+    // Written by older versions for `my_user/repo`; underscores in owners are escaped now
+    assert!(RunnerScope::from_dir_name("my_user__repo").is_none());
+    assert!(RunnerScope::from_dir_name("owner__bad%2").is_none());
+    assert!(RunnerScope::from_dir_name("owner__lower%2f").is_none());
+}
+
+#[test]
+fn test_scopes_compare_case_insensitively() {
+    use std::collections::HashSet;
+
+    let added = RunnerScope::parse("Foo/Bar").unwrap();
+    let typed = RunnerScope::parse("foo/bar").unwrap();
+    assert_eq!(added, typed);
+    assert!(HashSet::from([added.clone()]).contains(&typed));
+    // The original case is kept for display
+    assert_eq!(added.to_display(), "Foo/Bar");
+    assert_eq!(
+        RunnerScope::parse("org:MyOrg").unwrap(),
+        RunnerScope::parse("org:myorg").unwrap()
+    );
+    assert_ne!(added, RunnerScope::parse("org:foo").unwrap());
+}
+
 // Tests for from_github_url()
 
 #[test]
@@ -270,3 +333,44 @@ fn test_scope_hash() {
     set.insert(scope1);
     assert!(set.contains(&scope2));
 }
+
+#[test]
+fn test_runners_settings_url() {
+    let repo = RunnerScope::parse("owner/repo").unwrap();
+    assert_eq!(
+        repo.runners_settings_url(),
+        "https://github.com/owner/repo/settings/actions/runners"
+    );
+
+    let org = RunnerScope::parse("org:myorg").unwrap();
+    assert_eq!(
+        org.runners_settings_url(),
+        "https://github.com/organizations/myorg/settings/actions/runners"
+    );
+}
+
+#[test]
+fn test_scope_urls_on_enterprise_host() {
+    let repo = RunnerScope::parse("owner/repo").unwrap();
+    let org = RunnerScope::parse("org:acme").unwrap();
+    assert_eq!(
+        repo.web_url("https://ghe.example.com"),
+        "https://ghe.example.com/owner/repo"
+    );
+    assert_eq!(
+        org.settings_url("https://ghe.example.com"),
+        "https://ghe.example.com/organizations/acme/settings/actions/runners"
+    );
+    assert_eq!(
+        RunnerScope::from_web_url(
+            "https://ghe.example.com/owner/repo",
+            "https://ghe.example.com"
+        )
+        .unwrap(),
+        repo
+    );
+    assert!(
+        RunnerScope::from_web_url("https://github.com/owner/repo", "https://ghe.example.com")
+            .is_err()
+    );
+}
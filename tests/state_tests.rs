@@ -0,0 +1,86 @@
+use runner_mgr::github::RunnerScope;
+use runner_mgr::state::{validate_tag, StateDb};
+use tempfile::TempDir;
+
+fn open() -> (TempDir, StateDb) {
+    let tmp = TempDir::new().unwrap();
+    let db = StateDb::open_at(&tmp.path().join("state.db")).unwrap();
+    (tmp, db)
+}
+
+#[test]
+fn test_notes_set_update_and_clear() {
+    let (_tmp, db) = open();
+    let scope = RunnerScope::parse("owner/repo").unwrap();
+    assert_eq!(db.note(&scope).unwrap(), None);
+
+    db.set_note(&scope, "pending RAM upgrade").unwrap();
+    assert_eq!(
+        db.note(&scope).unwrap().as_deref(),
+        Some("pending RAM upgrade")
+    );
+
+    db.set_note(&scope, "RAM upgraded").unwrap();
+    assert_eq!(db.note(&scope).unwrap().as_deref(), Some("RAM upgraded"));
+
+    db.set_note(&scope, "  ").unwrap();
+    assert_eq!(db.note(&scope).unwrap(), None);
+}
+
+#[test]
+fn test_tags_add_remove_and_list() {
+    let (_tmp, db) = open();
+    let web = RunnerScope::parse("owner/web").unwrap();
+    let org = RunnerScope::parse("org:acme").unwrap();
+
+    db.add_tag(&web, "flaky-disk").unwrap();
+    db.add_tag(&web, "gpu").unwrap();
+    db.add_tag(&web, "gpu").unwrap();
+    db.add_tag(&org, "arm").unwrap();
+    assert_eq!(db.tags(&web).unwrap(), ["flaky-disk", "gpu"]);
+    assert_eq!(db.all_tags().unwrap(), ["arm", "flaky-disk", "gpu"]);
+
+    db.remove_tag(&web, "gpu").unwrap();
+    assert_eq!(db.tags(&web).unwrap(), ["flaky-disk"]);
+
+    db.forget(&web).unwrap();
+    assert!(db.tags(&web).unwrap().is_empty());
+    assert_eq!(db.tags(&org).unwrap(), ["arm"]);
+}
+
+#[test]
+fn test_validate_tag() {
+    assert_eq!(validate_tag(" flaky-disk ").unwrap(), "flaky-disk");
+    assert!(validate_tag("").is_err());
+    assert!(validate_tag("two words").is_err());
+    assert!(validate_tag("a,b").is_err());
+}
+
+#[test]
+fn test_remote_instances_import() {
+    use runner_mgr::remote::RemoteRunner;
+
+    let (_tmp, db) = open();
+    assert!(db.remote_instances().unwrap().is_empty());
+
+    let mut runner = RemoteRunner {
+        host: "ci@build2".to_string(),
+        path: "/opt/runners/web".to_string(),
+        scope: RunnerScope::parse("owner/web").unwrap(),
+        agent_name: Some("build2-web".to_string()),
+    };
+    db.import_remote(&runner).unwrap();
+    let other = RemoteRunner {
+        host: "build1".to_string(),
+        path: "/home/ci/actions-runner".to_string(),
+        scope: RunnerScope::parse("org:acme").unwrap(),
+        agent_name: None,
+    };
+    db.import_remote(&other).unwrap();
+
+    // Importing the same directory again updates it
+    runner.agent_name = Some("renamed".to_string());
+    db.import_remote(&runner).unwrap();
+
+    assert_eq!(db.remote_instances().unwrap(), [other, runner]);
+}
@@ -0,0 +1,88 @@
+use runner_mgr::config::StatusFileConfig;
+use runner_mgr::github::{Runner, RunnerScope};
+use runner_mgr::status_file::{self, FleetStatus, Health, ScopeStatus};
+use tempfile::TempDir;
+
+fn runner(status: &str, busy: bool) -> Runner {
+    Runner {
+        id: 1,
+        name: "runner".to_string(),
+        os: "Linux".to_string(),
+        status: status.to_string(),
+        busy,
+        labels: Vec::new(),
+    }
+}
+
+fn scope(name: &str) -> RunnerScope {
+    RunnerScope::parse(name).unwrap()
+}
+
+#[test]
+fn test_scope_health() {
+    let all_online = [runner("online", true), runner("online", false)];
+    let status = ScopeStatus::new(&scope("owner/repo"), Some(&all_online[..]));
+    assert_eq!(status.health, Health::Ok);
+    assert_eq!((status.online, status.busy, status.total), (2, 1, 2));
+
+    let one_offline = [runner("online", false), runner("offline", false)];
+    let status = ScopeStatus::new(&scope("owner/repo"), Some(&one_offline[..]));
+    assert_eq!(status.health, Health::Degraded);
+
+    let status = ScopeStatus::new(&scope("org:acme"), Some(&[][..]));
+    assert_eq!(status.health, Health::Down);
+    assert_eq!(status.scope, "org:acme");
+
+    let status = ScopeStatus::new(&scope("owner/repo"), None);
+    assert_eq!(status.health, Health::Unknown);
+}
+
+#[test]
+fn test_fleet_health_and_badge() {
+    let online = [runner("online", false)];
+    let offline = [runner("offline", false)];
+    let mut fleet = FleetStatus {
+        scopes: vec![
+            ScopeStatus::new(&scope("owner/a"), Some(&online[..])),
+            ScopeStatus::new(&scope("owner/b"), Some(&online[..])),
+        ],
+    };
+    assert_eq!(fleet.health(), Health::Ok);
+
+    fleet
+        .scopes
+        .push(ScopeStatus::new(&scope("owner/c"), Some(&offline[..])));
+    assert_eq!(fleet.health(), Health::Degraded);
+    let badge = fleet.badge();
+    assert_eq!(badge["schemaVersion"], 1);
+    assert_eq!(badge["message"], "2/3 online");
+    assert_eq!(badge["color"], "yellow");
+
+    assert_eq!(FleetStatus::default().health(), Health::Unknown);
+    assert_eq!(FleetStatus::default().badge()["message"], "unknown");
+}
+
+#[test]
+fn test_write_status_file() {
+    let tmp = TempDir::new().unwrap();
+    let settings = StatusFileConfig {
+        path: tmp.path().join("status/runners.json").display().to_string(),
+        badge: Some(tmp.path().join("badge.json").display().to_string()),
+    };
+    let online = [runner("online", true)];
+    let fleet = FleetStatus {
+        scopes: vec![ScopeStatus::new(&scope("Owner/Repo"), Some(&online[..]))],
+    };
+    status_file::write(&settings, &fleet).unwrap();
+
+    let doc: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&settings.path).unwrap()).unwrap();
+    assert_eq!(doc["status"], "ok");
+    assert_eq!(doc["scopes"][0]["scope"], "Owner/Repo");
+    assert_eq!(doc["scopes"][0]["busy"], 1);
+    let badge: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(tmp.path().join("badge.json")).unwrap())
+            .unwrap();
+    assert_eq!(badge["color"], "brightgreen");
+    assert!(!tmp.path().join("status/runners.json.partial").exists());
+}
@@ -0,0 +1,90 @@
+use runner_mgr::systemd::{self, parse_watchdog, ServeUnit, WATCHDOG_SEC};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[test]
+fn test_parse_watchdog() {
+    // Pinged at half the watchdog timeout
+    assert_eq!(
+        parse_watchdog(Some("60000000"), None, 42),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(
+        parse_watchdog(Some("60000000"), Some("42"), 42),
+        Some(Duration::from_secs(30))
+    );
+    // The watchdog belongs to another process, or there is none
+    assert_eq!(parse_watchdog(Some("60000000"), Some("7"), 42), None);
+    assert_eq!(parse_watchdog(None, None, 42), None);
+    assert_eq!(parse_watchdog(Some("0"), None, 42), None);
+    assert_eq!(parse_watchdog(Some("soon"), None, 42), None);
+}
+
+#[test]
+fn test_serve_unit() {
+    let unit = ServeUnit {
+        exe: PathBuf::from("/usr/local/bin/runner-mgr"),
+        user: Some("ci".to_string()),
+        account: None,
+        interval: Some(60),
+        listen: Some("127.0.0.1:8788".to_string()),
+        user_unit: false,
+    }
+    .render();
+    assert!(unit.contains("Type=notify\n"));
+    assert!(unit.contains(
+        "ExecStart=/usr/local/bin/runner-mgr serve --interval 60 --listen 127.0.0.1:8788\n"
+    ));
+    assert!(unit.contains("User=ci\n"));
+    assert!(unit.contains(&format!("WatchdogSec={WATCHDOG_SEC}\n")));
+    assert!(unit.contains("WantedBy=multi-user.target\n"));
+
+    let unit = ServeUnit {
+        exe: PathBuf::from("/home/ci/my tools/runner-mgr"),
+        user_unit: true,
+        ..ServeUnit::default()
+    };
+    assert_eq!(unit.exec_start(), "\"/home/ci/my tools/runner-mgr\" serve");
+    let unit = unit.render();
+    assert!(!unit.contains("User="));
+    assert!(unit.contains("WantedBy=default.target\n"));
+}
+
+#[test]
+fn test_serve_unit_for_account() {
+    let unit = ServeUnit {
+        exe: PathBuf::from("/usr/local/bin/runner-mgr"),
+        account: Some("work".to_string()),
+        interval: Some(60),
+        ..ServeUnit::default()
+    };
+    assert_eq!(
+        unit.exec_start(),
+        "/usr/local/bin/runner-mgr serve --account work --interval 60"
+    );
+    assert_eq!(systemd::serve_unit_name(None), systemd::SERVE_UNIT);
+    assert_eq!(
+        systemd::serve_unit_name(Some("work")),
+        "runner-mgr-serve-work.service"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+#[serial_test::serial]
+fn test_notify_sends_to_the_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("notify");
+    let socket = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+
+    std::env::remove_var("NOTIFY_SOCKET");
+    assert!(!systemd::notify("READY=1").unwrap());
+
+    std::env::set_var("NOTIFY_SOCKET", &path);
+    let sent = systemd::notify("READY=1");
+    std::env::remove_var("NOTIFY_SOCKET");
+    assert!(sent.unwrap());
+    let mut buf = [0; 64];
+    let len = socket.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"READY=1");
+}
@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use runner_mgr::github::RunnerScope;
+use runner_mgr::runner::{RunnerInstance, RunnerStatus};
+use runner_mgr::target::{edit_distance, resolve, resolve_one, resolve_scope, suggest};
+
+fn instance(scope: &str, name: Option<&str>) -> RunnerInstance {
+    RunnerInstance {
+        scope: RunnerScope::parse(scope).unwrap(),
+        name: name.map(str::to_string),
+        dir: PathBuf::from("/opt/github-runners/instances/owner__repo"),
+        service_name: None,
+        status: RunnerStatus::NoService,
+        runner_name: None,
+    }
+}
+
+fn instances() -> Vec<RunnerInstance> {
+    vec![
+        instance("acme/web-app", None),
+        instance("acme/web-app", Some("gpu")),
+        instance("org:acme", None),
+    ]
+}
+
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance("", ""), 0);
+    assert_eq!(edit_distance("web-app", "web-app"), 0);
+    assert_eq!(edit_distance("web-ap", "web-app"), 1);
+    assert_eq!(edit_distance("wbe-app", "web-app"), 2);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+}
+
+#[test]
+fn test_suggest_close_matches() {
+    let instances = instances();
+    assert_eq!(
+        suggest(&instances, "acme/web-ap").as_deref(),
+        Some("acme/web-app")
+    );
+    assert_eq!(
+        suggest(&instances, "Acme/Web-App").as_deref(),
+        Some("acme/web-app")
+    );
+    assert_eq!(
+        suggest(&instances, "web-app").as_deref(),
+        Some("acme/web-app")
+    );
+    assert_eq!(
+        suggest(&instances, "acme/web-app@gpo").as_deref(),
+        Some("acme/web-app@gpu")
+    );
+    assert_eq!(suggest(&instances, "org:acm").as_deref(), Some("org:acme"));
+    assert_eq!(suggest(&instances, "other/project"), None);
+}
+
+#[test]
+fn test_resolve_scope_and_instance() {
+    let instances = instances();
+    let ids = resolve(&instances, "acme/web-app").unwrap();
+    assert_eq!(ids.len(), 2);
+    let ids = resolve(&instances, "acme/web-app@gpu").unwrap();
+    assert_eq!(ids.len(), 1);
+    assert_eq!(ids[0].to_string(), "acme/web-app@gpu");
+    assert!(resolve_one(&instances, "acme/web-app")
+        .unwrap()
+        .name
+        .is_none());
+    assert!(resolve_scope(&instances, "org:acme").is_ok());
+}
+
+#[test]
+fn test_resolve_suggests_on_typos() {
+    let instances = instances();
+    let err = format!("{:#}", resolve(&instances, "acme/web-ap").unwrap_err());
+    assert!(err.contains("No runner configured for acme/web-ap"));
+    assert!(err.ends_with("did you mean acme/web-app?"));
+
+    let err = format!("{:#}", resolve_one(&instances, "web-app").unwrap_err());
+    assert!(err.contains("Invalid identifier"));
+    assert!(err.ends_with("did you mean acme/web-app?"));
+
+    let err = format!("{:#}", resolve_scope(&instances, "org:acm").unwrap_err());
+    assert!(err.ends_with("did you mean org:acme?"));
+
+    let err = format!("{:#}", resolve(&instances, "other/project").unwrap_err());
+    assert!(!err.contains("did you mean"));
+}
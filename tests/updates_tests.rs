@@ -0,0 +1,109 @@
+use runner_mgr::updates::{
+    detect_self_updates, is_outdated, parse_diag_line, read_self_updates, version_label, SelfUpdate,
+};
+use tempfile::TempDir;
+
+#[test]
+fn test_is_outdated() {
+    assert!(is_outdated("2.319.1", "2.320.0"));
+    assert!(is_outdated("2.9.0", "2.10.0"));
+    assert!(is_outdated("v2.319.1", "2.319.2"));
+    assert!(!is_outdated("2.320.0", "2.320.0"));
+    assert!(!is_outdated("2.321.0", "2.320.0"));
+    // Unparseable versions are never flagged
+    assert!(!is_outdated("unknown", "2.320.0"));
+    assert!(!is_outdated("2.319.1", ""));
+}
+
+#[test]
+fn test_version_label() {
+    assert_eq!(
+        version_label(Some("2.319.1"), Some("2.320.0")),
+        "2.319.1 (update available)"
+    );
+    assert_eq!(version_label(Some("2.320.0"), Some("2.320.0")), "2.320.0");
+    assert_eq!(version_label(Some("2.319.1"), None), "2.319.1");
+    assert_eq!(version_label(None, Some("2.320.0")), "-");
+}
+
+#[test]
+fn test_parse_diag_line() {
+    let (at, message) =
+        parse_diag_line("[2024-03-05 10:15:32Z INFO Terminal] WRITE LINE: Listening for Jobs")
+            .unwrap();
+    assert_eq!(at, 1_709_633_732);
+    assert_eq!(message, "WRITE LINE: Listening for Jobs");
+    assert!(parse_diag_line("   at GitHub.Runner.Listener.Program.Main()").is_none());
+}
+
+#[test]
+fn test_detect_self_updates() {
+    let lines = [
+        (100, "WRITE LINE: Listening for Jobs"),
+        (
+            200,
+            "WRITE LINE: Runner update in progress, do not shutdown runner.",
+        ),
+        (201, "WRITE LINE: Downloading 2.320.0 runner"),
+        (
+            230,
+            "WRITE LINE: Runner will exit shortly for update, should be back within 10 seconds.",
+        ),
+        (250, "WRITE LINE: Current runner version: '2.320.0'"),
+        (251, "WRITE LINE: Listening for Jobs"),
+        (
+            900,
+            "WRITE LINE: Runner update in progress, do not shutdown runner.",
+        ),
+    ];
+    assert_eq!(
+        detect_self_updates(lines),
+        [
+            SelfUpdate {
+                started_at: 200,
+                finished_at: Some(250),
+                version: Some("2.320.0".to_string()),
+            },
+            SelfUpdate {
+                started_at: 900,
+                finished_at: None,
+                version: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_restart_without_update_is_not_a_self_update() {
+    let lines = [
+        (100, "WRITE LINE: Current runner version: '2.320.0'"),
+        (101, "WRITE LINE: Listening for Jobs"),
+    ];
+    assert!(detect_self_updates(lines).is_empty());
+}
+
+#[test]
+fn test_read_self_updates_across_log_files() {
+    let tmp = TempDir::new().unwrap();
+    let diag = tmp.path().join("_diag");
+    std::fs::create_dir(&diag).unwrap();
+    std::fs::write(
+        diag.join("Runner_20240305-101000-utc.log"),
+        "[2024-03-05 10:15:32Z INFO Terminal] WRITE LINE: Runner update in progress, do not shutdown runner.\n",
+    )
+    .unwrap();
+    // Keep the modification times in order
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    std::fs::write(
+        diag.join("Runner_20240305-101600-utc.log"),
+        "[2024-03-05 10:16:02Z INFO Terminal] WRITE LINE: Current runner version: '2.320.0'\n",
+    )
+    .unwrap();
+    std::fs::write(diag.join("Worker_20240305-101000-utc.log"), "ignored").unwrap();
+
+    let updates = read_self_updates(tmp.path());
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].finished_at, Some(updates[0].started_at + 30));
+    assert_eq!(updates[0].version.as_deref(), Some("2.320.0"));
+    assert!(read_self_updates(&tmp.path().join("missing")).is_empty());
+}
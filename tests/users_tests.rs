@@ -0,0 +1,36 @@
+use runner_mgr::users::{create_commands, next_macos_uid};
+
+#[test]
+fn test_next_macos_uid() {
+    let output = "_www 70\nroot 0\nalice 501\nbob 502\nnobody -2\n";
+    assert_eq!(next_macos_uid(output), 503);
+    // Only system accounts: start at the first regular UID
+    assert_eq!(next_macos_uid("root 0\n_www 70\n"), 501);
+}
+
+#[test]
+fn test_create_commands_linux() {
+    let commands = create_commands("linux", "github", 0, true);
+    assert_eq!(
+        commands[0],
+        ["useradd", "--create-home", "--shell", "/bin/bash", "github"]
+    );
+    assert_eq!(commands[1], ["passwd", "--lock", "github"]);
+    assert_eq!(commands[2], ["usermod", "-aG", "docker", "github"]);
+
+    let without_docker = create_commands("linux", "github", 0, false);
+    assert_eq!(without_docker.len(), 2);
+}
+
+#[test]
+fn test_create_commands_macos() {
+    let commands = create_commands("darwin", "github", 503, true);
+    assert!(commands
+        .iter()
+        .any(|c| c.ends_with(&["UniqueID".to_string(), "503".to_string()])));
+    assert!(commands
+        .iter()
+        .any(|c| c.ends_with(&["Password".to_string(), "*".to_string()])));
+    // No docker group on macOS
+    assert!(!commands.iter().any(|c| c[0] == "usermod"));
+}
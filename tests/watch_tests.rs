@@ -0,0 +1,66 @@
+use runner_mgr::watch::{DirWatcher, FileWatcher};
+use std::fs::{self, File};
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+fn touch(path: &std::path::Path, age: Duration) {
+    let file = File::open(path).unwrap();
+    file.set_modified(SystemTime::now() - age).unwrap();
+}
+
+#[test]
+fn test_file_watcher_detects_changes() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("config.toml");
+    fs::write(&path, "a = 1\n").unwrap();
+    touch(&path, Duration::from_mins(1));
+
+    let mut watcher = FileWatcher::new(&path);
+    assert!(!watcher.changed(), "nothing changed since new");
+
+    touch(&path, Duration::from_secs(30));
+    assert!(watcher.changed());
+    assert!(!watcher.changed(), "a change is reported once");
+
+    fs::remove_file(&path).unwrap();
+    assert!(watcher.changed(), "removal is a change");
+    assert!(!watcher.changed());
+}
+
+#[test]
+fn test_file_watcher_missing_file() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("config.toml");
+
+    let mut watcher = FileWatcher::new(&path);
+    assert!(!watcher.changed());
+
+    fs::write(&path, "a = 1\n").unwrap();
+    assert!(watcher.changed(), "creation is a change");
+}
+
+#[test]
+fn test_dir_watcher_detects_instances() {
+    let tmp = TempDir::new().unwrap();
+    let instances = tmp.path().join("instances");
+    let mut watcher = DirWatcher::new(&instances);
+    assert!(!watcher.changed());
+
+    fs::create_dir(&instances).unwrap();
+    assert!(watcher.changed(), "directory created");
+
+    let runner = instances.join("owner-repo");
+    fs::create_dir(&runner).unwrap();
+    touch(&instances, Duration::from_secs(30));
+    touch(&runner, Duration::from_secs(30));
+    assert!(watcher.changed(), "instance added");
+    assert!(!watcher.changed());
+
+    // A file created in an instance, e.g. `.service`
+    touch(&runner, Duration::from_secs(10));
+    assert!(watcher.changed(), "instance changed");
+
+    fs::remove_dir(&runner).unwrap();
+    assert!(watcher.changed(), "instance removed");
+    assert!(!watcher.changed());
+}
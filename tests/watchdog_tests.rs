@@ -0,0 +1,141 @@
+use runner_mgr::config::WatchdogConfig;
+use runner_mgr::github::{Runner, RunnerScope};
+use runner_mgr::runner::{RunnerInstance, RunnerStatus};
+use runner_mgr::watchdog::{backoff, Watchdog};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+fn settings() -> WatchdogConfig {
+    WatchdogConfig {
+        grace_seconds: 120,
+        backoff_seconds: 60,
+        max_backoff_seconds: 300,
+        notify_after: 2,
+        reregister: false,
+    }
+}
+
+fn scope() -> RunnerScope {
+    RunnerScope::parse("owner/repo").unwrap()
+}
+
+fn instance(status: RunnerStatus) -> RunnerInstance {
+    RunnerInstance {
+        scope: scope(),
+        name: None,
+        dir: PathBuf::from("/opt/runners/owner-repo"),
+        service_name: Some("actions.runner.owner-repo".to_string()),
+        status,
+        runner_name: Some("ci-01".to_string()),
+    }
+}
+
+fn github(status: &str) -> Vec<(RunnerScope, Vec<Runner>)> {
+    vec![(
+        scope(),
+        vec![Runner {
+            id: 1,
+            name: "ci-01".to_string(),
+            os: "Linux".to_string(),
+            status: status.to_string(),
+            busy: false,
+            labels: Vec::new(),
+        }],
+    )]
+}
+
+#[test]
+fn test_backoff_doubles_up_to_the_limit() {
+    let settings = settings();
+    assert_eq!(backoff(&settings, 1), Duration::from_mins(1));
+    assert_eq!(backoff(&settings, 2), Duration::from_mins(2));
+    assert_eq!(backoff(&settings, 3), Duration::from_mins(4));
+    assert_eq!(backoff(&settings, 4), Duration::from_mins(5));
+    assert_eq!(backoff(&settings, 40), Duration::from_mins(5));
+}
+
+#[test]
+fn test_watchdog_restarts_with_backoff_then_reports() {
+    let mut watchdog = Watchdog::new(settings());
+    let running = [instance(RunnerStatus::Running)];
+    let offline = github("offline");
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+
+    // Offline runners get a grace period before the first restart
+    assert!(watchdog.check(start, &running, &offline).restart.is_empty());
+    let check = watchdog.check(at(120), &running, &offline);
+    assert_eq!(check.restart.len(), 1);
+    watchdog.restarted(&running[0].id(), at(120));
+
+    // The second restart waits for the backoff
+    assert!(watchdog
+        .check(at(150), &running, &offline)
+        .restart
+        .is_empty());
+    assert_eq!(watchdog.check(at(180), &running, &offline).restart.len(), 1);
+    watchdog.restarted(&running[0].id(), at(180));
+
+    // Still offline after `notify_after` restarts: reported once
+    let check = watchdog.check(at(200), &running, &offline);
+    assert_eq!(check.gave_up.len(), 1);
+    assert_eq!(check.gave_up[0].1, 2);
+    assert!(watchdog
+        .check(at(210), &running, &offline)
+        .gave_up
+        .is_empty());
+
+    // Coming back online ends the outage; the next one starts from scratch
+    assert!(watchdog
+        .check(at(220), &running, &github("online"))
+        .restart
+        .is_empty());
+    assert!(watchdog
+        .check(at(230), &running, &offline)
+        .restart
+        .is_empty());
+    assert_eq!(watchdog.check(at(350), &running, &offline).restart.len(), 1);
+}
+
+#[test]
+fn test_watchdog_leaves_stopped_and_unpolled_runners_alone() {
+    let mut watchdog = Watchdog::new(settings());
+    let start = Instant::now();
+    let later = start + Duration::from_mins(10);
+
+    // A stopped service is meant to be offline
+    let stopped = [instance(RunnerStatus::Stopped)];
+    watchdog.check(start, &stopped, &github("offline"));
+    assert!(watchdog
+        .check(later, &stopped, &github("offline"))
+        .restart
+        .is_empty());
+
+    // Without GitHub data for the scope nothing is decided
+    let running = [instance(RunnerStatus::Running)];
+    assert!(watchdog.check(later, &running, &[]).restart.is_empty());
+}
+
+#[test]
+fn test_watchdog_reports_lost_registrations_once() {
+    let mut watchdog = Watchdog::new(settings());
+    let running = [instance(RunnerStatus::Running)];
+    let start = Instant::now();
+    let later = start + Duration::from_mins(10);
+    let deleted = vec![(scope(), Vec::new())];
+
+    // Not restarted: that doesn't bring the registration back
+    let check = watchdog.check(start, &running, &deleted);
+    assert_eq!(check.unregistered.len(), 1);
+    assert!(check.restart.is_empty());
+    let check = watchdog.check(later, &running, &deleted);
+    assert!(check.unregistered.is_empty());
+    assert!(check.restart.is_empty());
+
+    // Registered again, then lost again: reported again
+    watchdog.check(later, &running, &github("online"));
+    assert_eq!(
+        watchdog.check(later, &running, &deleted).unregistered.len(),
+        1
+    );
+}
@@ -0,0 +1,81 @@
+use std::fmt::Write as _;
+
+use runner_mgr::webhook::{hmac_sha256, parse_request, verify_signature, WorkflowJobEvent};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{b:02x}");
+        hex
+    })
+}
+
+#[test]
+fn test_hmac_sha256_rfc4231() {
+    assert_eq!(
+        hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+    // Keys longer than a block are hashed first
+    assert_eq!(
+        hex(&hmac_sha256(
+            &[0xaa; 131],
+            b"Test Using Larger Than Block-Size Key - Hash Key First"
+        )),
+        "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+    );
+}
+
+#[test]
+fn test_verify_github_signature() {
+    // Example from GitHub's webhook documentation
+    let secret = "It's a Secret to Everybody";
+    let header = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+    assert!(verify_signature(secret, b"Hello, World!", header));
+    assert!(!verify_signature(secret, b"Hello, World?", header));
+    assert!(!verify_signature("wrong secret", b"Hello, World!", header));
+    assert!(!verify_signature(secret, b"Hello, World!", "sha1=757107ea"));
+    assert!(!verify_signature(secret, b"Hello, World!", ""));
+}
+
+#[test]
+fn test_parse_request() {
+    let raw = b"POST /webhook HTTP/1.1\r\nHost: x\r\nX-GitHub-Event: ping\r\nContent-Length: 5\r\n\r\nhello";
+    let request = parse_request(raw).unwrap().unwrap();
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/webhook");
+    assert_eq!(request.header("x-github-event"), Some("ping"));
+    assert_eq!(request.body, b"hello");
+
+    // Incomplete headers or body: wait for more data
+    assert!(parse_request(&raw[..20]).unwrap().is_none());
+    assert!(parse_request(&raw[..raw.len() - 1]).unwrap().is_none());
+    assert!(parse_request(b"POST /x HTTP/1.1\r\nContent-Length: nope\r\n\r\n").is_err());
+}
+
+#[test]
+fn test_parse_workflow_job_event() {
+    let event: WorkflowJobEvent = serde_json::from_str(
+        r#"{
+            "action": "in_progress",
+            "workflow_job": {
+                "id": 42, "run_id": 7, "name": "build", "workflow_name": "CI",
+                "status": "in_progress", "conclusion": null,
+                "labels": ["self-hosted", "linux"], "runner_name": "ci-01-owner__repo",
+                "html_url": "https://github.com/owner/repo/actions/runs/7/job/42",
+                "created_at": "2025-01-01T10:00:00Z", "started_at": "2025-01-01T10:02:00Z",
+                "completed_at": null
+            },
+            "repository": {"full_name": "owner/repo"}
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(event.scope().unwrap().to_display(), "owner/repo");
+    assert_eq!(
+        event.workflow_job.created_at.as_deref(),
+        Some("2025-01-01T10:00:00Z")
+    );
+    assert_eq!(
+        event.workflow_job.runner_name.as_deref(),
+        Some("ci-01-owner__repo")
+    );
+}
@@ -0,0 +1,47 @@
+use runner_mgr::runner::RunnerStatus;
+use runner_mgr::winsvc::{parse_sc_state, powershell_quote, status_from_state};
+
+const SC_QUERY_RUNNING: &str = "
+SERVICE_NAME: actions.runner.owner-repo.ci-win-01
+        TYPE               : 10  WIN32_OWN_PROCESS
+        STATE              : 4  RUNNING
+                                (STOPPABLE, NOT_PAUSABLE, ACCEPTS_SHUTDOWN)
+        WIN32_EXIT_CODE    : 0  (0x0)
+        SERVICE_EXIT_CODE  : 0  (0x0)
+        CHECKPOINT         : 0x0
+        WAIT_HINT          : 0x0
+";
+
+#[test]
+fn test_parse_sc_state() {
+    assert_eq!(parse_sc_state(SC_QUERY_RUNNING), Some("RUNNING"));
+    assert_eq!(
+        parse_sc_state(&SC_QUERY_RUNNING.replace("4  RUNNING", "1  STOPPED")),
+        Some("STOPPED")
+    );
+    assert_eq!(
+        parse_sc_state("[SC] EnumQueryServicesStatus:OpenService FAILED 1060:"),
+        None
+    );
+}
+
+#[test]
+fn test_status_from_state() {
+    assert_eq!(status_from_state(Some("RUNNING")), RunnerStatus::Running);
+    assert_eq!(
+        status_from_state(Some("START_PENDING")),
+        RunnerStatus::Running
+    );
+    assert_eq!(status_from_state(Some("STOPPED")), RunnerStatus::Stopped);
+    assert_eq!(
+        status_from_state(Some("STOP_PENDING")),
+        RunnerStatus::Stopped
+    );
+    assert_eq!(status_from_state(None), RunnerStatus::Unknown);
+}
+
+#[test]
+fn test_powershell_quote() {
+    assert_eq!(powershell_quote(r"C:\runners"), r"'C:\runners'");
+    assert_eq!(powershell_quote("it's"), "'it''s'");
+}